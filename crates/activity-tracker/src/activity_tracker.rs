@@ -34,7 +34,7 @@ impl ActivityTracker<DefaultPlatform> {
         let mut res = TypeMap::new();
         res.insert(Database::new()?);
         res.insert(Stylesheet::load()?);
-        res.insert(Locale::new(&LocaleSettings::load()?.lang));
+        res.insert(Locale::from_settings(&LocaleSettings::load()?));
         res.insert(Into::<geom::Size>::into(display.size()));
         let res = Resources::new(res);
 
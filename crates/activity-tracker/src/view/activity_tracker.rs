@@ -61,6 +61,13 @@ impl ActivityTracker {
                         Sort::MostPlayed.button_hint(&locale),
                         Alignment::Right,
                     ),
+                    ButtonHint::new(
+                        res.clone(),
+                        Point::zero(),
+                        Key::X,
+                        locale.t("activity-tracker-statistics"),
+                        Alignment::Right,
+                    ),
                 ]
             },
             Alignment::Right,
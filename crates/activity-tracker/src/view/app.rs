@@ -8,13 +8,13 @@ use common::command::Command;
 use common::display::Display;
 use common::geom::{Alignment, Point, Rect};
 use common::locale::Locale;
-use common::platform::{DefaultPlatform, KeyEvent, Platform};
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
 use common::stylesheet::Stylesheet;
 use common::view::{BatteryIndicator, Clock, Label, Row, View};
 use tokio::sync::mpsc::Sender;
 
-use crate::view::ActivityTracker;
+use crate::view::{ActivityTracker, Statistics};
 
 #[derive(Debug)]
 pub struct App<B>
@@ -22,9 +22,12 @@ where
     B: Battery + 'static,
 {
     rect: Rect,
+    res: Resources,
     label: Label<String>,
     row: Row<Box<dyn View>>,
     view: ActivityTracker,
+    /// Shown instead of the list of games when toggled with X.
+    statistics: Option<Statistics>,
     dirty: bool,
     _phantom_battery: PhantomData<B>,
 }
@@ -76,13 +79,15 @@ where
         drop(styles);
         drop(locale);
 
-        let view = ActivityTracker::new(rect, res)?;
+        let view = ActivityTracker::new(rect, res.clone())?;
 
         Ok(Self {
             rect,
+            res,
             label,
             row,
             view,
+            statistics: None,
             dirty: true,
             _phantom_battery: PhantomData,
         })
@@ -108,13 +113,21 @@ where
 
         drawn |= self.label.should_draw() && self.label.draw(display, styles)?;
         drawn |= self.row.should_draw() && self.row.draw(display, styles)?;
-        drawn |= self.view.should_draw() && self.view.draw(display, styles)?;
+        drawn |= match self.statistics.as_mut() {
+            Some(statistics) => statistics.should_draw() && statistics.draw(display, styles)?,
+            None => self.view.should_draw() && self.view.draw(display, styles)?,
+        };
 
         Ok(drawn)
     }
 
     fn should_draw(&self) -> bool {
-        self.label.should_draw() || self.row.should_draw() || self.view.should_draw()
+        self.label.should_draw()
+            || self.row.should_draw()
+            || match self.statistics.as_ref() {
+                Some(statistics) => statistics.should_draw(),
+                None => self.view.should_draw(),
+            }
     }
 
     fn set_should_draw(&mut self) {
@@ -122,6 +135,9 @@ where
         self.label.set_should_draw();
         self.row.set_should_draw();
         self.view.set_should_draw();
+        if let Some(statistics) = self.statistics.as_mut() {
+            statistics.set_should_draw();
+        }
     }
 
     async fn handle_key_event(
@@ -130,15 +146,33 @@ where
         commands: Sender<Command>,
         bubble: &mut VecDeque<Command>,
     ) -> Result<bool> {
-        self.view.handle_key_event(event, commands, bubble).await
+        if let KeyEvent::Pressed(Key::X) = event {
+            self.statistics = match self.statistics {
+                Some(_) => None,
+                None => Some(Statistics::new(self.rect, self.res.clone())?),
+            };
+            self.set_should_draw();
+            return Ok(true);
+        }
+
+        match self.statistics.as_mut() {
+            Some(statistics) => statistics.handle_key_event(event, commands, bubble).await,
+            None => self.view.handle_key_event(event, commands, bubble).await,
+        }
     }
 
     fn children(&self) -> Vec<&dyn View> {
-        vec![&self.row, &self.view]
+        match self.statistics.as_ref() {
+            Some(statistics) => vec![&self.row, statistics],
+            None => vec![&self.row, &self.view],
+        }
     }
 
     fn children_mut(&mut self) -> Vec<&mut dyn View> {
-        vec![&mut self.row, &mut self.view]
+        match self.statistics.as_mut() {
+            Some(statistics) => vec![&mut self.row, statistics],
+            None => vec![&mut self.row, &mut self.view],
+        }
     }
 
     fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
@@ -0,0 +1,162 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::platform::{DefaultPlatform, KeyEvent, Platform};
+use common::stylesheet::Stylesheet;
+use common::view::{Label, View};
+use embedded_graphics::Drawable;
+use embedded_graphics::prelude::Size;
+use embedded_graphics::primitives::{Primitive, PrimitiveStyle, RoundedRectangle};
+use tokio::sync::mpsc::Sender;
+
+/// A bar of the chart, with its label and the value as text.
+#[derive(Debug, Clone)]
+pub struct Bar {
+    pub label: String,
+    pub value: i64,
+    pub text: String,
+}
+
+#[derive(Debug)]
+struct BarRow {
+    label: Label<String>,
+    text: Label<String>,
+    value: i64,
+}
+
+/// Horizontal bar chart with one row per bar: the label on the left, then the bar, scaled to
+/// the largest value, then the value on the right.
+#[derive(Debug)]
+pub struct BarChart {
+    rect: Rect,
+    row_height: u32,
+    rows: Vec<BarRow>,
+    dirty: bool,
+}
+
+impl BarChart {
+    pub fn new(rect: Rect, row_height: u32) -> Self {
+        Self {
+            rect,
+            row_height,
+            rows: Vec::new(),
+            dirty: true,
+        }
+    }
+
+    pub fn set_bars(&mut self, bars: Vec<Bar>) {
+        let Rect { x, y, w, .. } = self.rect;
+        self.rows = bars
+            .into_iter()
+            .enumerate()
+            .map(|(i, bar)| {
+                let y = y + (i as u32 * self.row_height) as i32;
+                BarRow {
+                    label: Label::new(
+                        Point::new(x, y),
+                        bar.label,
+                        Alignment::Left,
+                        Some(Self::label_width(w)),
+                    ),
+                    text: Label::new(
+                        Point::new(x + w as i32, y),
+                        bar.text,
+                        Alignment::Right,
+                        None,
+                    ),
+                    value: bar.value,
+                }
+            })
+            .collect();
+        self.dirty = true;
+    }
+
+    fn label_width(w: u32) -> u32 {
+        w / 3
+    }
+
+    fn text_width(w: u32) -> u32 {
+        w / 4
+    }
+}
+
+#[async_trait(?Send)]
+impl View for BarChart {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        display.load(self.rect)?;
+
+        let Rect { x, w, .. } = self.rect;
+        let bar_x = x + Self::label_width(w) as i32 + 8;
+        let bar_w = w - Self::label_width(w) - Self::text_width(w) - 16;
+        let bar_h = styles.ui_font.size * 3 / 5;
+        let max = self.rows.iter().map(|r| r.value).max().unwrap_or(0).max(1);
+
+        for row in &mut self.rows {
+            row.label.set_should_draw();
+            row.label.draw(display, styles)?;
+            row.text.set_should_draw();
+            row.text.draw(display, styles)?;
+
+            let width = (bar_w as i64 * row.value / max) as u32;
+            if width > 0 {
+                let label = row.label.bounding_box(styles);
+                RoundedRectangle::with_equal_corners(
+                    Rect::new(
+                        bar_x,
+                        label.y + (label.h as i32 - bar_h as i32) / 2,
+                        width,
+                        bar_h,
+                    )
+                    .into(),
+                    Size::new_equal(4),
+                )
+                .into_styled(PrimitiveStyle::with_fill(styles.highlight_color))
+                .draw(display)?;
+            }
+        }
+
+        self.dirty = false;
+        Ok(true)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        _event: KeyEvent,
+        _commands: Sender<Command>,
+        _bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
@@ -1,5 +1,8 @@
 mod activity_tracker;
 mod app;
+mod bar_chart;
+mod statistics;
 
 pub use activity_tracker::ActivityTracker;
 pub use app::App;
+pub use statistics::Statistics;
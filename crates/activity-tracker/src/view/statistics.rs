@@ -0,0 +1,306 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Duration, Local};
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::database::Database;
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::play_stats::PlayStats;
+use common::resources::Resources;
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::{ButtonHint, ButtonIcon, Label, Row, View};
+use tokio::sync::mpsc::Sender;
+
+use crate::view::bar_chart::{Bar, BarChart};
+
+/// Play time totals: a summary, and a bar chart of play time by week, console or game.
+#[derive(Debug)]
+pub struct Statistics {
+    rect: Rect,
+    res: Resources,
+    stats: PlayStats,
+    chart: Chart,
+    summary: Vec<Label<String>>,
+    title: Label<String>,
+    bar_chart: BarChart,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl Statistics {
+    pub fn new(rect: Rect, res: Resources) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let stats = PlayStats::load(&res.get::<Database>(), Local::now().date_naive())?;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+        let row_height = styles.ui_font.size + SELECTION_MARGIN;
+
+        let summary: Vec<_> = summary_text(&locale, &stats)
+            .into_iter()
+            .enumerate()
+            .map(|(i, text)| {
+                Label::new(
+                    Point::new(x + 12, y + (i as u32 * row_height) as i32),
+                    text,
+                    Alignment::Left,
+                    Some(w - 24),
+                )
+            })
+            .collect();
+
+        let chart_y = y + (summary.len() as u32 * row_height + 8) as i32;
+        let mut title = Label::new(
+            Point::new(x + 12, chart_y),
+            String::new(),
+            Alignment::Left,
+            None,
+        );
+        title.color(StylesheetColor::Highlight);
+
+        let bottom = y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8;
+        let chart_y = chart_y + row_height as i32;
+        let bar_chart = BarChart::new(
+            Rect::new(x + 12, chart_y, w - 24, (bottom - chart_y).max(0) as u32),
+            row_height,
+        );
+
+        let button_hints = Row::new(
+            Point::new(x + w as i32 - 12, bottom),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::X,
+                    locale.t("activity-tracker-games"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::Right,
+                    locale.t("activity-tracker-next-chart"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+
+        let mut this = Self {
+            rect,
+            res,
+            stats,
+            chart: Chart::Weeks,
+            summary,
+            title,
+            bar_chart,
+            button_hints,
+            dirty: true,
+        };
+        this.load_chart();
+
+        Ok(this)
+    }
+
+    fn load_chart(&mut self) {
+        let locale = self.res.get::<Locale>();
+        let bars = match self.chart {
+            Chart::Weeks => self
+                .stats
+                .weeks
+                .iter()
+                .map(|(week, play_time)| {
+                    bar(&locale, week.format("%b %-d").to_string(), *play_time)
+                })
+                .collect(),
+            Chart::Consoles => self
+                .stats
+                .consoles
+                .iter()
+                .map(|(console, play_time)| bar(&locale, console.clone(), *play_time))
+                .collect(),
+            Chart::Games => self
+                .stats
+                .games
+                .iter()
+                .map(|(game, play_time)| bar(&locale, game.clone(), *play_time))
+                .collect(),
+        };
+        self.title.set_text(self.chart.title(&locale));
+        self.bar_chart.set_bars(bars);
+        self.dirty = true;
+    }
+}
+
+fn hours_decimal(play_time: Duration) -> String {
+    format!("{:.1}", play_time.num_minutes() as f32 / 60.0)
+}
+
+fn bar(locale: &Locale, label: String, play_time: Duration) -> Bar {
+    let mut map = HashMap::new();
+    map.insert("hours_decimal".into(), hours_decimal(play_time).into());
+    Bar {
+        label,
+        value: play_time.num_seconds(),
+        text: locale.ta("activity-tracker-play-time", &map),
+    }
+}
+
+fn summary_text(locale: &Locale, stats: &PlayStats) -> Vec<String> {
+    let mut map = HashMap::new();
+    map.insert("hours_decimal".into(), hours_decimal(stats.total).into());
+    let total = locale.ta("activity-tracker-total", &map);
+
+    let longest_session = match stats.longest_session.as_ref() {
+        Some((game, play_time)) => {
+            let mut map = HashMap::new();
+            map.insert("game".into(), game.clone().into());
+            map.insert("hours".into(), play_time.num_hours().into());
+            map.insert("minutes".into(), (play_time.num_minutes() % 60).into());
+            locale.ta("activity-tracker-longest-session", &map)
+        }
+        None => locale.t("activity-tracker-longest-session-none"),
+    };
+
+    let mut map = HashMap::new();
+    map.insert("days".into(), stats.current_streak.into());
+    map.insert("best".into(), stats.longest_streak.into());
+    let streak = locale.ta("activity-tracker-streak", &map);
+
+    vec![total, longest_session, streak]
+}
+
+#[async_trait(?Send)]
+impl View for Statistics {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            for label in &mut self.summary {
+                label.set_should_draw();
+            }
+            self.title.set_should_draw();
+            self.bar_chart.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        for label in &mut self.summary {
+            drawn |= label.should_draw() && label.draw(display, styles)?;
+        }
+        drawn |= self.title.should_draw() && self.title.draw(display, styles)?;
+        drawn |= self.bar_chart.should_draw() && self.bar_chart.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.summary.iter().any(|l| l.should_draw())
+            || self.title.should_draw()
+            || self.bar_chart.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        _bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::Right) | KeyEvent::Autorepeat(Key::Right) => {
+                self.chart = self.chart.next();
+                self.load_chart();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Left) | KeyEvent::Autorepeat(Key::Left) => {
+                self.chart = self.chart.prev();
+                self.load_chart();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                commands.send(Command::Exit).await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.title, &self.bar_chart, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.title, &mut self.bar_chart, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Chart {
+    Weeks,
+    Consoles,
+    Games,
+}
+
+impl Chart {
+    fn title(&self, locale: &Locale) -> String {
+        match self {
+            Chart::Weeks => locale.t("activity-tracker-chart-weeks"),
+            Chart::Consoles => locale.t("activity-tracker-chart-consoles"),
+            Chart::Games => locale.t("activity-tracker-chart-games"),
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Chart::Weeks => Chart::Consoles,
+            Chart::Consoles => Chart::Games,
+            Chart::Games => Chart::Weeks,
+        }
+    }
+
+    fn prev(self) -> Self {
+        match self {
+            Chart::Weeks => Chart::Games,
+            Chart::Consoles => Chart::Weeks,
+            Chart::Games => Chart::Consoles,
+        }
+    }
+}
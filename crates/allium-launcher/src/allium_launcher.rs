@@ -1,69 +1,157 @@
 use std::collections::VecDeque;
-use std::path::Path;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::process;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
+use chrono::Local;
 use common::command::Command;
-use common::constants::{ALLIUM_GAMES_DIR, ALLIUM_SD_ROOT};
+use common::constants::{
+    ALLIUM_BOOT_VAR, ALLIUM_EXTERNAL_GAMES_DIR, ALLIUM_GAMES_DIR, ALLIUM_LIBRARY_BACKUP,
+    ALLIUM_REMOTE_LAUNCH, ALLIUM_SD_ROOT, BATTERY_SAVER_FRAME_INTERVAL,
+    DEVICE_STATUS_UPDATE_INTERVAL, IDLE_DIM_BRIGHTNESS, LAUNCH_SPLASH_FRAME_INTERVAL,
+    LAUNCHER_FRAME_INTERVAL, LOCALE_RELOAD_INTERVAL, RESTART_ALLIUMD_EXIT_CODE,
+    SEASONAL_SPLASH_DURATION, UNDO_DURATION,
+};
 use common::display::color::Color;
+use common::display::image_cache;
+use common::display::settings::{DisplaySettings, Screensaver as ScreensaverMode};
+use common::game_info::GameInfo;
 use common::geom;
+use common::launch_diagnostic::LaunchDiagnostic;
+use common::launcher::{LauncherSettings, StartupBehavior};
 use common::locale::{Locale, LocaleSettings};
+use common::network;
+use common::parental::ParentalBlock;
+use common::power::PowerSettings;
+use common::reset::ResetTarget;
 use common::resources::Resources;
-use common::view::View;
+use common::save_sync::{self, SaveSyncSettings};
+use common::status::DeviceStatus;
+use common::storage::{self, Volume};
+use common::view::{Dialog, Toast, Toasts, View, set_redraw_sender};
 use embedded_graphics::image::ImageRaw;
 use embedded_graphics::prelude::*;
 use enum_map::EnumMap;
-use log::{error, info, trace, warn};
+use log::{debug, error, info, trace, warn};
+use tokio::sync::mpsc::{self, Receiver, Sender};
 
 use common::database::Database;
 use common::display::Display;
-use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::platform::touch::{Gesture, GestureRecognizer};
+use common::platform::{DefaultPlatform, InputEvent, Key, KeyEvent, Platform};
 use common::stylesheet::Stylesheet;
+use common::sync::{self, LibraryMetadata};
 use type_map::TypeMap;
 
+use crate::archive;
 use crate::consoles::ConsoleMapper;
-use crate::entry::directory::Directory;
+use crate::entry::Entry;
+use crate::entry::directory::{Directory, VisitedDirs};
 use crate::entry::game::Game;
-use crate::view::{App, Toast};
+use crate::entry::{playlist, rom_files};
+use crate::prefetch::PrefetchCache;
+use crate::view::{App, Attract, LaunchSplash, Manual, Screensaver};
+#[cfg(target_os = "linux")]
+use crate::watcher;
+
+/// Loads the resources shared by the launcher's views.
+pub fn load_resources(size: geom::Size, styles: Stylesheet, locale: Locale) -> Result<Resources> {
+    let mut console_mapper = ConsoleMapper::new();
+    console_mapper.load_config()?;
+
+    let mut res = TypeMap::new();
+    res.insert(Database::new()?);
+    res.insert(console_mapper);
+    res.insert(styles);
+    res.insert(locale);
+    res.insert(size);
+    res.insert(PrefetchCache::default());
+    // Reloaded by the event loop, so that views can tell whether the battery saver is on
+    res.insert(DeviceStatus::load().unwrap_or_default());
+    Ok(Resources::new(res))
+}
 
 #[derive(Debug)]
 pub struct AlliumLauncher<P: Platform> {
     platform: P,
     display: P::Display,
     res: Resources,
-    view: App<P::Battery>,
-    toast: Option<Toast>,
+    view: App<DeviceStatus>,
+    toasts: Toasts,
+    attract: Option<Attract>,
+    /// How long to wait for input before starting attract mode, if it is enabled.
+    attract_mode_delay: Option<Duration>,
+    /// Manual opened from a game's context menu, shown over the whole screen.
+    manual: Option<Manual>,
+    /// Dialog explaining why the last game failed to launch, shown over the launcher.
+    launch_diagnostic: Option<Dialog>,
+    /// Idle dimming and screensaver settings.
+    display_settings: DisplaySettings,
+    /// Screensaver shown once the launcher has been idle for long enough.
+    screensaver: Option<Screensaver>,
+    /// Brightness to restore once the backlight, dimmed while idle, is woken up.
+    dimmed: Option<u8>,
+    /// Commands handled by the event loop, sent by views and background tasks.
+    commands: Sender<Command>,
+    /// Taken by the event loop when it starts.
+    receiver: Option<Receiver<Command>>,
 }
 
 impl AlliumLauncher<DefaultPlatform> {
     pub fn new(mut platform: DefaultPlatform) -> Result<Self> {
         let display = platform.display()?;
-        let battery = platform.battery()?;
+        let battery = DeviceStatus::load()?;
 
-        let mut console_mapper = ConsoleMapper::new();
-        console_mapper.load_config()?;
+        let mut styles = Stylesheet::load()?;
+        styles.apply_seasonal_overlay(Local::now().date_naive());
+        let locale = Locale::from_settings(&LocaleSettings::load()?);
+        let res = load_resources(display.size().into(), styles, locale)?;
+
+        // Pruned before the recents are listed
+        if let Some(days) = LauncherSettings::load()?.prune_recents_days
+            && let Err(e) = res.get::<Database>().prune_recents(days)
+        {
+            warn!("failed to prune recents: {}", e);
+        }
+
+        let mut view = App::load_or_new(display.bounding_box().into(), res.clone(), battery)?;
+        if LauncherSettings::is_boot()
+            && let Err(e) = view.open_startup(&LauncherSettings::load()?)
+        {
+            warn!("failed to open startup view: {}", e);
+        }
 
-        let mut res = TypeMap::new();
-        res.insert(Database::new()?);
-        res.insert(console_mapper);
-        res.insert(Stylesheet::load()?);
-        res.insert(Locale::new(&LocaleSettings::load()?.lang));
-        res.insert(Into::<geom::Size>::into(display.size()));
-        let res = Resources::new(res);
+        let power_settings = PowerSettings::load()?;
+        let attract_mode_delay = power_settings.attract_mode.then(|| {
+            Duration::from_secs(power_settings.attract_mode_delay_minutes as u64 * 60)
+        });
 
-        let view = App::load_or_new(display.bounding_box().into(), res.clone(), battery)?;
+        let (commands, receiver) = mpsc::channel(100);
 
         Ok(AlliumLauncher {
             platform,
             display,
             res,
             view,
-            toast: None,
+            toasts: Toasts::new(),
+            attract: None,
+            attract_mode_delay,
+            manual: None,
+            launch_diagnostic: None,
+            display_settings: DisplaySettings::load()?,
+            screensaver: None,
+            dimmed: None,
+            commands,
+            receiver: Some(receiver),
         })
     }
 
     pub async fn run_event_loop(&mut self) -> Result<()> {
+        self.show_seasonal_splash().await;
+
         {
             let styles = self.res.get::<Stylesheet>();
 
@@ -79,34 +167,156 @@ impl AlliumLauncher<DefaultPlatform> {
 
         self.display.save()?;
 
+        tokio::task::spawn_blocking(|| {
+            if let Err(e) = image_cache::prune() {
+                error!("Failed to prune image cache: {}", e);
+            }
+        });
+
+        // Changes from before the launcher started can't be undone, as their toasts are gone
+        self.expire_undo(Duration::ZERO);
+
+        if let Some(command) = self.take_remote_launch()? {
+            self.handle_command(command).await?;
+        } else if let Some(command) = self.startup_launch()? {
+            self.handle_command(command).await?;
+        }
+
+        // alliumd leaves a diagnostic behind when the last game exited right after launching
+        match LaunchDiagnostic::take() {
+            Ok(Some(diagnostic)) => self.show_launch_diagnostic(diagnostic),
+            Ok(None) => {}
+            Err(e) => warn!("failed to load launch diagnostic: {}", e),
+        }
+
         #[cfg(unix)]
         let mut sigterm =
             tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
 
-        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        let tx = self.commands.clone();
+        let mut rx = self
+            .receiver
+            .take()
+            .expect("event loop should only be run once");
+
+        set_redraw_sender(tx.clone());
+
+        #[cfg(target_os = "linux")]
+        if let Err(e) = watcher::spawn(tx.clone()) {
+            warn!("failed to watch games folder: {}", e);
+        }
 
         let mut keys: EnumMap<Key, bool> = EnumMap::default();
+        // Only the simulator has a touchscreen, so devices never see a gesture
+        let mut gestures = DefaultPlatform::has_touchscreen().then(GestureRecognizer::new);
 
-        let mut frame_interval = tokio::time::interval(tokio::time::Duration::from_micros(166_667));
+        let mut battery_saver = self.res.get::<DeviceStatus>().battery_saver;
+        let mut external_storage = self.res.get::<DeviceStatus>().external_storage;
+        let mut frame_interval = tokio::time::interval(frame_interval_duration(battery_saver));
 
         let mut last_frame = Instant::now();
+        let mut last_input = Instant::now();
+        // Unlike `last_input`, this isn't reset when attract mode starts
+        let mut idle_since = Instant::now();
+        let mut last_status = Instant::now();
+        let mut last_locale_check = Instant::now();
         loop {
+            if last_status.elapsed() >= DEVICE_STATUS_UPDATE_INTERVAL {
+                last_status = Instant::now();
+                let status = DeviceStatus::load().unwrap_or_default();
+                self.res.insert(status);
+                // Leaves time for the undo toast to be shown after any toasts queued before it
+                self.expire_undo(UNDO_DURATION * 2);
+                if status.battery_saver != battery_saver {
+                    info!("battery saver changed: {}", status.battery_saver);
+                    battery_saver = status.battery_saver;
+                    frame_interval = tokio::time::interval(frame_interval_duration(battery_saver));
+                    // Box art is shown or hidden
+                    self.handle_command(Command::Redraw).await?;
+                }
+                if status.external_storage != external_storage {
+                    info!("external storage changed: {}", status.external_storage);
+                    external_storage = status.external_storage;
+                    self.res.get::<PrefetchCache>().clear();
+                    // Games on an inserted device are added, and those on a removed one greyed out
+                    self.handle_command(if external_storage {
+                        Command::PopulateDb
+                    } else {
+                        Command::LibraryChanged
+                    })
+                    .await?;
+                    let text = self.res.get::<Locale>().t(if external_storage {
+                        "storage-connected"
+                    } else {
+                        "storage-removed"
+                    });
+                    self.toast(text);
+                }
+            }
+
+            if last_locale_check.elapsed() >= LOCALE_RELOAD_INTERVAL {
+                last_locale_check = Instant::now();
+                if self.res.get::<Locale>().is_outdated() {
+                    info!("custom translations changed, reloading");
+                    self.load_locale(&LocaleSettings::load()?)?;
+                }
+            }
+
+            if self.attract.is_none()
+                && let Some(delay) = self.attract_mode_delay
+                && last_input.elapsed() >= delay
+            {
+                last_input = Instant::now();
+                self.attract = Attract::new(self.display.bounding_box().into(), self.res.clone())
+                    .unwrap_or_else(|e| {
+                        warn!("failed to start attract mode: {}", e);
+                        None
+                    });
+            }
+
+            if let Err(e) = self.check_idle(idle_since.elapsed()) {
+                warn!("failed to start screensaver: {}", e);
+            }
+
             let dt = last_frame.elapsed();
-            self.view.update(dt);
             last_frame = Instant::now();
 
-            let mut drawn = self.view.should_draw()
-                && self
-                    .view
-                    .draw(&mut self.display, &self.res.get::<Stylesheet>())?;
-
-            if let Some(toast) = self.toast.as_mut() {
-                if toast.has_expired() {
-                    self.handle_command(Command::Redraw).await?;
-                    self.toast = None;
-                } else {
-                    drawn |= toast.draw(&mut self.display, &self.res.get::<Stylesheet>())?;
+            let mut drawn = if let Some(screensaver) = self.screensaver.as_mut() {
+                screensaver.update(dt);
+                screensaver.should_draw()
+                    && screensaver.draw(&mut self.display, &self.res.get::<Stylesheet>())?
+            } else if let Some(attract) = self.attract.as_mut() {
+                attract.update(dt);
+                attract.should_draw()
+                    && attract.draw(&mut self.display, &self.res.get::<Stylesheet>())?
+            } else if let Some(manual) = self.manual.as_mut() {
+                manual.update(dt);
+                manual.should_draw()
+                    && manual.draw(&mut self.display, &self.res.get::<Stylesheet>())?
+            } else {
+                self.view.update(dt);
+                let mut drawn = self.view.should_draw()
+                    && self
+                        .view
+                        .draw(&mut self.display, &self.res.get::<Stylesheet>())?;
+                if let Some(dialog) = self.launch_diagnostic.as_mut() {
+                    // Keep the dialog on top of anything drawn underneath it
+                    if drawn {
+                        dialog.set_should_draw();
+                    }
+                    drawn |= dialog.should_draw()
+                        && dialog.draw(&mut self.display, &self.res.get::<Stylesheet>())?;
                 }
+                drawn
+            };
+
+            if self.toasts.update_queue() {
+                // The toasts shown changed, so what was below them is drawn again first
+                self.handle_command(Command::Redraw).await?;
+            } else if self.toasts.should_draw() {
+                drawn |= self
+                    .toasts
+                    .draw(&mut self.display, &self.res.get::<Stylesheet>())?;
             }
 
             if drawn {
@@ -117,6 +327,7 @@ impl AlliumLauncher<DefaultPlatform> {
             tokio::select! {
                 _ = frame_interval.tick() => {}
                 _ = sigterm.recv() => {
+                    self.wake().await?;
                     self.handle_command(Command::Exit).await?;
                 }
                 cmd = rx.recv() => {
@@ -124,8 +335,29 @@ impl AlliumLauncher<DefaultPlatform> {
                         self.handle_command(cmd).await?;
                     }
                 }
-                event = self.platform.poll() => {
+                event = self.platform.poll_input() => {
+                    last_input = Instant::now();
+                    idle_since = Instant::now();
+                    // Any input wakes the launcher up, without being passed on
+                    if self.wake().await? {
+                        continue;
+                    }
                     let mut bubble = VecDeque::new();
+                    let event = match event {
+                        InputEvent::Key(event) => event,
+                        touch => {
+                            if self.attract.take().is_some() {
+                                self.handle_command(Command::Redraw).await?;
+                                continue;
+                            }
+                            if let Some(gesture) =
+                                gestures.as_mut().and_then(|gestures| gestures.handle(touch))
+                            {
+                                self.handle_gesture(gesture, tx.clone()).await?;
+                            }
+                            continue;
+                        }
+                    };
                     match event {
                         KeyEvent::Pressed(key) => {
                             keys[key] = true;
@@ -136,6 +368,38 @@ impl AlliumLauncher<DefaultPlatform> {
                         KeyEvent::Autorepeat(_) => {}
                     }
 
+                    // Any input exits attract mode, without being passed on to the launcher
+                    if self.attract.take().is_some() {
+                        self.handle_command(Command::Redraw).await?;
+                        continue;
+                    }
+
+                    if let Some(manual) = self.manual.as_mut() {
+                        manual.handle_key_event(event, tx.clone(), &mut bubble).await?;
+                        if bubble.iter().any(|c| matches!(c, Command::CloseView)) {
+                            self.manual = None;
+                            self.handle_command(Command::Redraw).await?;
+                        }
+                        continue;
+                    }
+
+                    if let Some(dialog) = self.launch_diagnostic.as_mut() {
+                        dialog.handle_key_event(event, tx.clone(), &mut bubble).await?;
+                        if bubble.iter().any(|c| matches!(c, Command::CloseView)) {
+                            self.launch_diagnostic = None;
+                            self.handle_command(Command::Redraw).await?;
+                        }
+                        continue;
+                    }
+
+                    // The undo toast's button undoes the last change to the library
+                    if matches!(event, KeyEvent::Pressed(Key::Select))
+                        && self.toasts.take_action(Key::Select)
+                    {
+                        self.undo().await?;
+                        continue;
+                    }
+
                     // Ignore menu key presses
                     if !keys[Key::Menu] && !matches!(event, KeyEvent::Released(Key::Menu)) {
                         self.view.handle_key_event(event, tx.clone(), &mut bubble).await?;
@@ -155,6 +419,419 @@ impl AlliumLauncher<DefaultPlatform> {
         }
     }
 
+    /// Dims the backlight and starts the screensaver once the launcher has been idle for as long
+    /// as set in the display settings.
+    fn check_idle(&mut self, idle: Duration) -> Result<()> {
+        let settings = &self.display_settings;
+        if self.dimmed.is_none()
+            && settings.idle_dim_seconds > 0
+            && idle >= Duration::from_secs(settings.idle_dim_seconds as u64)
+        {
+            let brightness = self.platform.get_brightness()?;
+            info!("idle, dimming backlight");
+            self.platform
+                .set_brightness(brightness.min(IDLE_DIM_BRIGHTNESS))?;
+            self.dimmed = Some(brightness);
+        }
+
+        let settings = &self.display_settings;
+        if self.screensaver.is_none()
+            && self.attract.is_none()
+            && settings.screensaver != ScreensaverMode::Off
+            && idle >= Duration::from_secs(settings.screensaver_minutes as u64 * 60)
+        {
+            info!("idle, starting screensaver");
+            self.screensaver = Some(Screensaver::new(
+                self.display.bounding_box().into(),
+                self.res.clone(),
+                settings.screensaver,
+            )?);
+        }
+        Ok(())
+    }
+
+    /// Passes a gesture to the topmost of the manual, the launch diagnostic and the launcher, as
+    /// key events are. Button hints tapped on it press their button on it, and closing an overlay
+    /// shows what's below.
+    async fn handle_gesture(&mut self, gesture: Gesture, tx: Sender<Command>) -> Result<()> {
+        let styles = self.res.get::<Stylesheet>().clone();
+        let overlay = self.manual.is_some() || self.launch_diagnostic.is_some();
+        let view: &mut dyn View = if let Some(manual) = self.manual.as_mut() {
+            manual
+        } else if let Some(dialog) = self.launch_diagnostic.as_mut() {
+            dialog
+        } else {
+            &mut self.view
+        };
+
+        let mut bubble = VecDeque::new();
+        view.handle_gesture(gesture, &styles, tx.clone(), &mut bubble)
+            .await?;
+        let mut close = false;
+        // Pressing a key can queue more commands, so they're taken one at a time
+        while let Some(command) = bubble.pop_front() {
+            match command {
+                Command::PressKey(key) => {
+                    for event in [KeyEvent::Pressed(key), KeyEvent::Released(key)] {
+                        view.handle_key_event(event, tx.clone(), &mut bubble)
+                            .await?;
+                    }
+                }
+                Command::CloseView => close = true,
+                _ => {}
+            }
+        }
+
+        if overlay && close {
+            if self.manual.take().is_none() {
+                self.launch_diagnostic = None;
+            }
+            self.handle_command(Command::Redraw).await?;
+        }
+        Ok(())
+    }
+
+    /// Restores the backlight and closes the screensaver. Returns whether the launcher was idle.
+    async fn wake(&mut self) -> Result<bool> {
+        let dimmed = self.dimmed.take();
+        if let Some(brightness) = dimmed {
+            self.platform.set_brightness(brightness)?;
+        }
+        let screensaver = self.screensaver.take().is_some();
+        if screensaver {
+            self.handle_command(Command::Redraw).await?;
+        }
+        Ok(dimmed.is_some() || screensaver)
+    }
+
+    /// Returns the command to launch a game requested from the web remote, if any.
+    fn take_remote_launch(&self) -> Result<Option<Command>> {
+        if !ALLIUM_REMOTE_LAUNCH.exists() {
+            return Ok(None);
+        }
+        let path = PathBuf::from(fs::read_to_string(ALLIUM_REMOTE_LAUNCH.as_path())?);
+        fs::remove_file(ALLIUM_REMOTE_LAUNCH.as_path())?;
+        info!("launching game from web remote: {}", path.display());
+        self.launch_path(path)
+    }
+
+    /// Returns the command to launch the startup game on boot, if one is set.
+    fn startup_launch(&self) -> Result<Option<Command>> {
+        if !LauncherSettings::is_boot() {
+            return Ok(None);
+        }
+        let StartupBehavior::Game(path) = LauncherSettings::load()?.startup else {
+            return Ok(None);
+        };
+        if !path.exists() {
+            warn!("startup game {} not found", path.display());
+            return Ok(None);
+        }
+        info!("launching startup game: {}", path.display());
+        self.launch_path(path)
+    }
+
+    fn launch_path(&self, path: PathBuf) -> Result<Option<Command>> {
+        let database = self.res.get::<Database>();
+        let mut game = match database.select_game(&path)? {
+            Some(game) => Game::from_db(game),
+            None => Game::new(path),
+        };
+        self.res
+            .get::<ConsoleMapper>()
+            .launch_game(&database, &mut game, false)
+    }
+
+    /// Shows the boot splash of the active seasonal overlay, if any, when started on boot.
+    async fn show_seasonal_splash(&mut self) {
+        if !LauncherSettings::is_boot() {
+            return;
+        }
+        let Some(splash) = self.res.get::<Stylesheet>().splash.clone() else {
+            return;
+        };
+        let path = ALLIUM_SD_ROOT.join(splash);
+        let shown = set_wallpaper(&mut self.display, &path).and_then(|_| self.display.flush());
+        if let Err(e) = shown {
+            error!("failed to show seasonal splash: {}", e);
+            return;
+        }
+        tokio::time::sleep(SEASONAL_SPLASH_DURATION).await;
+    }
+
+    /// Plays the launch transition. The splash stays on screen until the core draws over it.
+    async fn show_launch_splash(&mut self, game_info: &GameInfo) -> Result<()> {
+        let styles = self.res.get::<Stylesheet>().clone();
+        let mut splash = LaunchSplash::new(
+            self.display.bounding_box().into(),
+            self.res.clone(),
+            game_info,
+        );
+        let mut last_frame = Instant::now();
+        while !splash.is_finished() {
+            let dt = last_frame.elapsed();
+            last_frame = Instant::now();
+            splash.update(dt);
+            if splash.should_draw() && splash.draw(&mut self.display, &styles)? {
+                self.display.flush()?;
+            }
+            tokio::time::sleep(LAUNCH_SPLASH_FRAME_INTERVAL).await;
+        }
+        Ok(())
+    }
+
+    /// Switches to the language of the settings, and loads the views again to show it.
+    fn load_locale(&mut self, settings: &LocaleSettings) -> Result<()> {
+        self.res.insert(Locale::from_settings(settings));
+        self.view.save()?;
+        self.view = App::load_or_new(
+            self.display.bounding_box().into(),
+            self.res.clone(),
+            DeviceStatus::load()?,
+        )?;
+        Ok(())
+    }
+
+    /// Opens a dialog explaining why a game failed to launch, with the last lines it wrote to
+    /// stderr.
+    fn show_launch_diagnostic(&mut self, diagnostic: LaunchDiagnostic) {
+        let locale = self.res.get::<Locale>();
+        let mut map = std::collections::HashMap::new();
+        map.insert("name".into(), diagnostic.name.into());
+        let title = locale.ta("launch-failed", &map);
+        let mut message = locale.t(diagnostic.error.locale_key());
+        if !diagnostic.output.is_empty() {
+            message.push('\n');
+            message.push_str(&diagnostic.output);
+        }
+        drop(locale);
+
+        self.launch_diagnostic = Some(Dialog::confirm(
+            self.display.bounding_box().into(),
+            self.res.clone(),
+            title,
+            Some(message),
+        ));
+    }
+
+    /// Shows a message while a long-running command runs.
+    #[cfg_attr(not(feature = "miyoo"), allow(unused_variables))]
+    /// Queues a toast that's shown for a few seconds.
+    fn toast(&mut self, text: String) {
+        self.toasts
+            .push(Toast::new(text, Some(Duration::from_secs(5))));
+    }
+
+    /// Undoes the last change to the library, moving its deleted files back out of the trash.
+    async fn undo(&mut self) -> Result<()> {
+        let Some(undo) = self.res.get::<Database>().pop_undo()? else {
+            return Ok(());
+        };
+        info!("undoing {:?}", undo.action);
+        rom_files::restore(&undo.files);
+        self.handle_command(Command::LibraryChanged).await?;
+        self.handle_command(Command::Redraw).await
+    }
+
+    /// Forgets the changes that can't be undone anymore, and empties them from the trash.
+    fn expire_undo(&self, max_age: Duration) {
+        match self.res.get::<Database>().expire_undo(max_age) {
+            Ok(undo_ids) => rom_files::empty_trash(&undo_ids),
+            Err(e) => warn!("failed to expire undo: {}", e),
+        }
+    }
+
+    fn show_progress(&self, key: &str) -> Result<()> {
+        #[cfg(feature = "miyoo")]
+        {
+            std::process::Command::new("show")
+                .arg("--clear")
+                .spawn()?
+                .wait()?;
+            std::process::Command::new("say")
+                .arg(self.res.get::<Locale>().t(key))
+                .spawn()?
+                .wait()?;
+        }
+        Ok(())
+    }
+
+    /// Scans the games folder, only populating the database with the directories that changed
+    /// since the last scan.
+    fn populate_db(&mut self) -> Result<()> {
+        let res = self.res.clone();
+        let database = res.get::<Database>();
+        let console_mapper = res.get::<ConsoleMapper>();
+
+        self.show_populate_progress(0)?;
+
+        let volume = Volume::load();
+        if let Some(volume) = &volume {
+            database.switch_volume(&volume.id)?;
+        }
+
+        let mut fingerprints = database.select_directory_fingerprints()?;
+        if fingerprints.values().all(Option::is_none) {
+            // Never scanned incrementally before, so start from scratch
+            database.delete_all_directories()?;
+            database.delete_all_unplayed_games()?;
+            fingerprints.clear();
+        }
+
+        // Finding the directories that changed only reads metadata, so it's quick
+        let mut changed = Vec::new();
+        let mut visited = VisitedDirs::new();
+        let mut queue = VecDeque::from([Directory::new(ALLIUM_GAMES_DIR.clone())]);
+        if volume.is_some() && ALLIUM_EXTERNAL_GAMES_DIR.exists() {
+            queue.push_back(Directory::new(ALLIUM_EXTERNAL_GAMES_DIR.clone()));
+        }
+        while let Some(dir) = queue.pop_front() {
+            if !visited.visit(&dir.path) {
+                debug!("skipping {:?}, already scanned", dir.path);
+                continue;
+            }
+            let (fingerprint, subdirectories) = match dir.fingerprint(&console_mapper) {
+                Ok(fingerprint) => fingerprint,
+                Err(e) => {
+                    warn!("failed to read directory {:?}: {}", dir.path, e);
+                    continue;
+                }
+            };
+            queue.extend(subdirectories);
+            if fingerprints.remove(&dir.path) != Some(Some(fingerprint)) {
+                changed.push((dir, fingerprint));
+            }
+        }
+        info!("populating {} changed directories", changed.len());
+
+        // The directories left weren't found, so they were deleted. Those on the second storage
+        // device are kept while it's removed, including when it was removed during the scan.
+        for path in fingerprints.into_keys() {
+            if storage::is_external(&path) && (volume.is_none() || !storage::is_mounted()) {
+                continue;
+            }
+            database.delete_unplayed_games_in_directory(&path)?;
+            database.delete_directory(&path)?;
+        }
+
+        for mut game in database.select_all_games()? {
+            if game.last_played == 0 && game.play_time == chrono::Duration::zero() {
+                continue;
+            }
+            // Games on a storage device are only pruned while it's the one mounted, so that those
+            // on a removed device are kept until it's inserted again
+            if storage::is_external(&game.path)
+                && (volume
+                    .as_ref()
+                    .is_none_or(|volume| game.volume.as_ref() != Some(&volume.id))
+                    || !storage::is_mounted())
+            {
+                continue;
+            }
+            if let Some(old) = Game::resync(&mut game.path)? {
+                if let Err(e) = database.update_game_path(&old, &game.path) {
+                    warn!("failed to update game path: {}", e);
+                }
+            } else if !game.path.exists() {
+                database.delete_game(&game.path)?;
+            }
+        }
+
+        let mut percent = 0;
+        let total = changed.len();
+        for (i, (dir, fingerprint)) in changed.into_iter().enumerate() {
+            dir.repopulate_db(&database, &console_mapper, &res.get(), fingerprint)?;
+
+            if (i + 1) * 100 / total != percent {
+                percent = (i + 1) * 100 / total;
+                self.show_populate_progress(percent)?;
+            }
+        }
+
+        if let Err(e) = playlist::import_playlists(&database, &console_mapper) {
+            warn!("failed to import RetroArch playlists: {}", e);
+        }
+
+        database.set_has_indexed(true)?;
+        self.toasts.dismiss();
+
+        Ok(())
+    }
+
+    /// Populates the database with the directories the games watcher found changes in. Returns
+    /// whether any of them changed since they were last populated.
+    fn populate_changed_directories(&self, dirs: Vec<PathBuf>) -> Result<bool> {
+        let database = self.res.get::<Database>();
+        let console_mapper = self.res.get::<ConsoleMapper>();
+        let fingerprints = database.select_directory_fingerprints()?;
+
+        let mut changed = false;
+        for path in dirs {
+            if !path.exists() {
+                // Folders on the second storage device disappear when it's removed
+                if storage::is_external(&path) && !storage::is_mounted() {
+                    continue;
+                }
+                // Deleted along with its subdirectories
+                for dir in fingerprints.keys().filter(|dir| dir.starts_with(&path)) {
+                    database.delete_unplayed_games_in_directory(dir)?;
+                    database.delete_directory(dir)?;
+                    changed = true;
+                }
+                continue;
+            }
+
+            let Some(Entry::Directory(dir)) = Entry::new(path, &console_mapper)? else {
+                continue;
+            };
+            let (fingerprint, _) = dir.fingerprint(&console_mapper)?;
+            if fingerprints.get(&dir.path) != Some(&Some(fingerprint)) {
+                info!("populating changed directory {:?}", dir.path);
+                dir.repopulate_db(&database, &console_mapper, &self.res.get(), fingerprint)?;
+                changed = true;
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Shows the progress of populating the database. The toast is drawn right away, as the
+    /// event loop waits for the database to be populated.
+    fn show_populate_progress(&mut self, percent: usize) -> Result<()> {
+        let mut map = std::collections::HashMap::new();
+        map.insert("percent".into(), percent.into());
+        let text = self
+            .res
+            .get::<Locale>()
+            .ta("populating-database-progress", &map);
+        self.toasts.dismiss();
+        self.toasts.push(Toast::new(text, None));
+
+        self.display.load(self.display.bounding_box().into())?;
+        self.toasts
+            .draw(&mut self.display, &self.res.get::<Stylesheet>())?;
+        self.display.flush()
+    }
+
+    /// Runs a task in the background, showing a toast until it finishes. The commands the task
+    /// returns, such as a toast with its result, are then handled by the event loop.
+    fn spawn_task<F>(&mut self, key: &str, task: F)
+    where
+        F: Future<Output = Vec<Command>> + Send + 'static,
+    {
+        let text = self.res.get::<Locale>().t(key);
+        self.toasts.push(Toast::new(text, None));
+
+        let commands = self.commands.clone();
+        tokio::spawn(async move {
+            let result = task.await;
+            commands.send(Command::DismissToast).await.ok();
+            for command in result {
+                commands.send(command).await.ok();
+            }
+        });
+    }
+
     async fn handle_command(&mut self, command: Command) -> Result<()> {
         match command {
             Command::Exit => {
@@ -164,36 +841,24 @@ impl AlliumLauncher<DefaultPlatform> {
                 self.display.flush()?;
                 process::exit(0);
             }
-            #[allow(unused_mut)]
-            Command::Exec(mut cmd) => {
+            Command::Exec(cmd) => {
                 info!("executing command: {:?}", cmd);
                 self.view.save()?;
                 self.display.clear(Color::new(0, 0, 0))?;
                 self.display.flush()?;
-                #[cfg(feature = "miyoo")]
-                {
-                    use std::os::unix::process::CommandExt;
-                    let _ = cmd.exec();
-                }
-                #[cfg(not(feature = "miyoo"))]
-                {
-                    #[cfg(unix)]
-                    {
-                        use std::os::unix::process::CommandExt;
-                        let _ = process::Command::new("/bin/sh")
-                            .arg("-c")
-                            .arg("make simulator bin=allium-menu")
-                            .exec();
-                    }
-
-                    #[cfg(not(unix))]
-                    process::exit(0);
-                }
+                exec(cmd);
+            }
+            Command::LaunchGame(game_info) => {
+                info!("launching game: {}", game_info.name);
+                self.view.save()?;
+                self.show_launch_splash(&game_info).await?;
+                exec(game_info.command());
             }
             Command::SaveStylesheet(mut styles) => {
                 trace!("saving stylesheet");
                 styles.load_fonts()?;
                 styles.save()?;
+                styles.apply_seasonal_overlay(Local::now().date_naive());
 
                 {
                     let old_styles = self.res.get::<Stylesheet>();
@@ -216,23 +881,28 @@ impl AlliumLauncher<DefaultPlatform> {
                 self.view = App::load_or_new(
                     self.display.bounding_box().into(),
                     self.res.clone(),
-                    self.platform.battery()?,
+                    DeviceStatus::load()?,
                 )?;
             }
             Command::SaveDisplaySettings(mut settings) => {
                 trace!("saving display settings");
                 self.platform.set_display_settings(&mut settings)?;
                 settings.save()?;
+                self.display_settings = *settings;
             }
             Command::SaveLocaleSettings(settings) => {
                 trace!("saving locale settings");
                 settings.save()?;
-                self.res.insert(Locale::new(&settings.lang));
+                self.load_locale(&settings)?;
+            }
+            Command::SaveLauncherSettings(settings) => {
+                trace!("saving launcher settings");
+                settings.save()?;
                 self.view.save()?;
                 self.view = App::load_or_new(
                     self.display.bounding_box().into(),
                     self.res.clone(),
-                    self.platform.battery()?,
+                    DeviceStatus::load()?,
                 )?;
             }
             Command::Redraw => {
@@ -248,85 +918,376 @@ impl AlliumLauncher<DefaultPlatform> {
                 trace!("searching");
                 self.view.search(query)?;
             }
+            Command::ShowInFolder(path) => {
+                trace!("showing {:?} in folder", path);
+                if let Err(e) = self.view.show_in_folder(&path) {
+                    warn!("failed to show {:?} in folder: {}", path, e);
+                    let text = self.res.get::<Locale>().t("show-in-folder-failed");
+                    self.toast(text);
+                }
+            }
+            Command::ViewManual(path) => {
+                trace!("viewing manual {:?}", path);
+                self.manual = Some(Manual::new(
+                    self.display.bounding_box().into(),
+                    self.res.clone(),
+                    path,
+                ));
+            }
             Command::Toast(text, duration) => {
                 trace!("showing toast: {:?}", text);
-                self.toast = Some(Toast::new(text, duration));
+                self.toasts.push(Toast::new(text, duration));
             }
             Command::ImageToast(image, text, duration) => {
                 trace!("showing image toast: {:?}", text);
-                self.toast = Some(Toast::with_image(image, text, duration));
+                self.toasts.push(Toast::with_image(image, text, duration));
+            }
+            Command::Notify(toast) => {
+                trace!("showing notification: {:?}", toast);
+                self.toasts.push(toast);
             }
             Command::DismissToast => {
-                trace!("dismissing toast");
-                self.toast = None;
-                self.display.load(self.display.bounding_box().into())?;
-                self.view.set_should_draw();
+                trace!("dismissing toasts");
+                self.toasts.dismiss();
+            }
+            Command::Rumble(rumble) => {
+                rumble.play();
             }
             Command::PopulateDb => {
-                #[cfg(feature = "miyoo")]
-                {
-                    std::process::Command::new("show")
-                        .arg("--clear")
-                        .spawn()?
-                        .wait()?;
-                    std::process::Command::new("say")
-                        .arg(self.res.get::<Locale>().t("populating-database"))
-                        .spawn()?
-                        .wait()?;
-                }
+                self.populate_db()?;
+                self.res.get::<PrefetchCache>().clear();
 
-                let mut queue = VecDeque::with_capacity(10);
-                queue.push_back(Directory::new(ALLIUM_GAMES_DIR.clone()));
+                self.view.save()?;
+                self.view = App::load_or_new(
+                    self.display.bounding_box().into(),
+                    self.res.clone(),
+                    DeviceStatus::load()?,
+                )?;
+            }
+            Command::GamesChanged(dirs) => {
+                if self.populate_changed_directories(dirs)? {
+                    self.res.get::<PrefetchCache>().clear();
+                    self.view.save()?;
+                    self.view = App::load_or_new(
+                        self.display.bounding_box().into(),
+                        self.res.clone(),
+                        DeviceStatus::load()?,
+                    )?;
+                }
+            }
+            Command::LibraryChanged => {
+                self.res.get::<PrefetchCache>().clear();
+                self.view.save()?;
+                self.view = App::load_or_new(
+                    self.display.bounding_box().into(),
+                    self.res.clone(),
+                    DeviceStatus::load()?,
+                )?;
+            }
+            Command::Undoable(action) => {
+                trace!("showing undo toast: {:?}", action);
+                let locale = self.res.get::<Locale>();
+                let toast = Toast::new(locale.t(action.locale_key()), Some(UNDO_DURATION))
+                    .action(Key::Select, locale.t("button-undo"));
+                drop(locale);
+                self.toasts.push(toast);
+            }
+            Command::ImportOnionData => {
+                self.show_progress("importing-onion-data")?;
 
-                let database = self.res.get::<Database>();
-                let console_mapper = self.res.get::<ConsoleMapper>();
+                let count = Directory::new(ALLIUM_GAMES_DIR.clone())
+                    .import_miyoo_game_lists(&self.res.get::<Database>())?;
+                info!("imported {} games from miyoogamelist.xml", count);
 
-                database.delete_all_directories()?;
-                database.delete_all_unplayed_games()?;
+                self.view.save()?;
+                self.view = App::load_or_new(
+                    self.display.bounding_box().into(),
+                    self.res.clone(),
+                    DeviceStatus::load()?,
+                )?;
 
-                let mut games = database.select_all_games()?;
-                for game in games.iter_mut() {
-                    if let Some(old) = Game::resync(&mut game.path)? {
-                        if let Err(e) = database.update_game_path(&old, &game.path) {
-                            warn!("failed to update game path: {}", e);
+                let mut map = std::collections::HashMap::new();
+                map.insert("count".into(), count.into());
+                let text = self.res.get::<Locale>().ta("imported-onion-data", &map);
+                self.toast(text);
+            }
+            Command::ExportBundle => {
+                let library = LibraryMetadata::load(&self.res.get::<Database>())?;
+                let (done, failed) = {
+                    let locale = self.res.get::<Locale>();
+                    (
+                        locale.t("exported-bundle"),
+                        locale.t("export-bundle-failed"),
+                    )
+                };
+                self.spawn_task("exporting-bundle", async move {
+                    let text = match sync::export_bundle(&library).await {
+                        Ok(()) => done,
+                        Err(e) => {
+                            warn!("failed to export bundle: {}", e);
+                            failed
+                        }
+                    };
+                    vec![Command::Toast(text, None)]
+                });
+            }
+            Command::ImportBundle => {
+                let (done, failed) = {
+                    let locale = self.res.get::<Locale>();
+                    (
+                        locale.t("imported-bundle"),
+                        locale.t("import-bundle-failed"),
+                    )
+                };
+                self.spawn_task("importing-bundle", async move {
+                    let result = match sync::import_bundle().await {
+                        Ok(library) => Database::new().and_then(|db| library.merge_into(&db)),
+                        Err(e) => Err(e),
+                    };
+                    match result {
+                        Ok(_) => vec![Command::LibraryChanged, Command::Toast(done, None)],
+                        Err(e) => {
+                            warn!("failed to import bundle: {}", e);
+                            vec![Command::Toast(failed, None)]
                         }
-                    } else if !game.path.exists() {
-                        database.delete_game(&game.path)?;
                     }
-                }
+                });
+            }
+            Command::BackupLibrary => {
+                self.show_progress("backing-up-library")?;
 
-                while let Some(dir) = queue.pop_front() {
-                    #[cfg(feature = "miyoo")]
-                    {
-                        std::process::Command::new("show")
-                            .arg("--clear")
-                            .spawn()?
-                            .wait()?;
-                        let mut map = std::collections::HashMap::new();
-                        map.insert(
-                            "directory".into(),
-                            dir.path
-                                .file_name()
-                                .unwrap_or_default()
-                                .to_string_lossy()
-                                .into(),
-                        );
-                        std::process::Command::new("say")
-                            .arg(self.res.get::<Locale>().ta("populating-games", &map))
-                            .spawn()?
-                            .wait()?;
+                let text = match self
+                    .res
+                    .get::<Database>()
+                    .export_json(&ALLIUM_LIBRARY_BACKUP)
+                {
+                    Ok(()) => self.res.get::<Locale>().t("backed-up-library"),
+                    Err(e) => {
+                        warn!("failed to back up library: {}", e);
+                        self.res.get::<Locale>().t("back-up-library-failed")
                     }
-                    dir.populate_db(&mut queue, &database, &console_mapper, &self.res.get())?;
-                }
-
-                database.set_has_indexed(true)?;
+                };
+                self.toast(text);
+            }
+            Command::RestoreLibrary => {
+                self.show_progress("restoring-library")?;
 
+                let restored = self
+                    .res
+                    .get::<Database>()
+                    .import_json(&ALLIUM_LIBRARY_BACKUP);
+                let text = match restored {
+                    Ok(count) => {
+                        self.view.save()?;
+                        self.view = App::load_or_new(
+                            self.display.bounding_box().into(),
+                            self.res.clone(),
+                            DeviceStatus::load()?,
+                        )?;
+                        let mut map = std::collections::HashMap::new();
+                        map.insert("count".into(), count.into());
+                        self.res.get::<Locale>().ta("restored-library", &map)
+                    }
+                    Err(e) => {
+                        warn!("failed to restore library: {}", e);
+                        self.res.get::<Locale>().t("restore-library-failed")
+                    }
+                };
+                self.toast(text);
+            }
+            Command::SyncLibrary => {
+                let (no_devices, failed, poor_network) = {
+                    let locale = self.res.get::<Locale>();
+                    (
+                        locale.t("sync-library-no-devices"),
+                        locale.t("sync-library-failed"),
+                        locale.t("network-quality-poor-warning"),
+                    )
+                };
+                let commands = self.commands.clone();
+                self.spawn_task("syncing-library", async move {
+                    // Syncing is bandwidth-heavy, so warn if the local network is poor
+                    if network::lan_quality().await.should_warn() {
+                        commands.send(Command::Toast(poor_network, None)).await.ok();
+                    }
+                    match sync_library().await {
+                        Ok(Some(count)) => vec![Command::LibrarySynced(count)],
+                        Ok(None) => vec![Command::Toast(no_devices, None)],
+                        Err(e) => {
+                            warn!("failed to sync library: {}", e);
+                            vec![Command::Toast(failed, None)]
+                        }
+                    }
+                });
+            }
+            Command::LibrarySynced(count) => {
+                self.res.get::<PrefetchCache>().clear();
                 self.view.save()?;
                 self.view = App::load_or_new(
                     self.display.bounding_box().into(),
                     self.res.clone(),
-                    self.platform.battery()?,
+                    DeviceStatus::load()?,
                 )?;
+                let mut map = std::collections::HashMap::new();
+                map.insert("count".into(), count.into());
+                let text = self.res.get::<Locale>().ta("synced-library", &map);
+                self.toast(text);
+            }
+            Command::Reset(target) => {
+                self.show_progress("resetting")?;
+
+                let backup = match target.reset().await {
+                    Ok(backup) => backup,
+                    Err(e) => {
+                        error!("failed to reset {}: {}", target.name(), e);
+                        let text = self.res.get::<Locale>().t("reset-failed");
+                        self.toast(text);
+                        return Ok(());
+                    }
+                };
+
+                match target {
+                    ResetTarget::Database => {
+                        self.res.insert(Database::new()?);
+                        self.res.get::<PrefetchCache>().clear();
+                        Box::pin(self.handle_command(Command::PopulateDb)).await?;
+                    }
+                    ResetTarget::Theme => {
+                        let styles = Stylesheet::load()?;
+                        Box::pin(self.handle_command(Command::SaveStylesheet(Box::new(styles))))
+                            .await?;
+                    }
+                    ResetTarget::WiFi => {}
+                    ResetTarget::All => {
+                        // alliumd restarts, and then starts the launcher again, so both load the
+                        // default settings. The view isn't saved, so that it starts from scratch.
+                        info!("reset all data, restarting alliumd");
+                        self.display.clear(Color::new(0, 0, 0))?;
+                        self.display.flush()?;
+                        process::exit(RESTART_ALLIUMD_EXIT_CODE);
+                    }
+                }
+
+                let text = match backup.as_deref().and_then(Path::file_name) {
+                    Some(name) => {
+                        let mut map = std::collections::HashMap::new();
+                        map.insert("backup".into(), name.to_string_lossy().into_owned().into());
+                        self.res.get::<Locale>().ta("reset-done", &map)
+                    }
+                    None => self.res.get::<Locale>().t("reset-done-no-backup"),
+                };
+                self.toast(text);
+            }
+            Command::SyncSaves => {
+                let settings = SaveSyncSettings::load()?;
+                if !settings.is_configured() {
+                    let text = self.res.get::<Locale>().t("sync-saves-not-configured");
+                    self.toast(text);
+                    return Ok(());
+                }
+
+                let (failed, poor_network) = {
+                    let locale = self.res.get::<Locale>();
+                    (
+                        locale.t("sync-saves-failed"),
+                        locale.t("network-quality-poor-warning"),
+                    )
+                };
+                let commands = self.commands.clone();
+                self.spawn_task("syncing-saves", async move {
+                    // Syncing is bandwidth-heavy, so warn if the connection is poor
+                    if network::quality().await.should_warn() {
+                        commands.send(Command::Toast(poor_network, None)).await.ok();
+                    }
+                    let progress = |copied, total| {
+                        if total > 0 {
+                            commands
+                                .try_send(Command::SyncSavesProgress(copied, total))
+                                .ok();
+                        }
+                    };
+                    match save_sync::sync(&settings, progress).await {
+                        Ok(report) => vec![Command::SavesSynced {
+                            uploaded: report.uploaded,
+                            downloaded: report.downloaded,
+                            conflicts: report.conflicts.len(),
+                        }],
+                        Err(e) => {
+                            warn!("failed to sync saves: {}", e);
+                            vec![Command::Toast(failed, None)]
+                        }
+                    }
+                });
+            }
+            Command::SyncSavesProgress(copied, total) => {
+                let mut map = std::collections::HashMap::new();
+                map.insert("copied".into(), copied.into());
+                map.insert("total".into(), total.into());
+                let text = self.res.get::<Locale>().ta("syncing-saves-progress", &map);
+                self.toasts.dismiss();
+                self.toasts.push(Toast::new(text, None));
+            }
+            Command::SavesSynced {
+                uploaded,
+                downloaded,
+                conflicts,
+            } => {
+                let mut map = std::collections::HashMap::new();
+                map.insert("uploaded".into(), uploaded.into());
+                map.insert("downloaded".into(), downloaded.into());
+                map.insert("conflicts".into(), conflicts.into());
+                let locale = self.res.get::<Locale>();
+                let text = if conflicts == 0 {
+                    locale.ta("synced-saves", &map)
+                } else {
+                    locale.ta("synced-saves-with-conflicts", &map)
+                };
+                drop(locale);
+                self.toast(text);
+            }
+            Command::ExtractArchive {
+                archive: path,
+                game_info,
+            } => {
+                info!("extracting archive: {}", path.display());
+                let result = {
+                    let locale = self.res.get::<Locale>();
+                    let styles = self.res.get::<Stylesheet>();
+                    let display = &mut self.display;
+                    let mut show_progress = |percent: u8| {
+                        let mut map = std::collections::HashMap::new();
+                        map.insert("percent".into(), percent.into());
+                        let mut toast = Toast::new(locale.ta("extracting-archive", &map), None);
+                        if toast.draw(display, &styles).is_ok() {
+                            display.flush().ok();
+                        }
+                    };
+                    show_progress(0);
+                    archive::extract(&path, show_progress).await
+                };
+
+                match result {
+                    Ok(()) => {
+                        game_info.save()?;
+                        Box::pin(self.handle_command(Command::LaunchGame(game_info))).await?;
+                    }
+                    Err(e) => {
+                        warn!("failed to extract archive: {}", e);
+                        let text = self.res.get::<Locale>().t("extract-archive-failed");
+                        self.toast(text);
+                        self.handle_command(Command::Redraw).await?;
+                    }
+                }
+            }
+            Command::LaunchFailed(diagnostic) => {
+                self.show_launch_diagnostic(*diagnostic);
+            }
+            Command::ParentalBlock(block) => {
+                let text = self.res.get::<Locale>().t(match block {
+                    ParentalBlock::PlayTimeLimit => "parental-play-time-limit",
+                    ParentalBlock::Restricted => "parental-restricted",
+                });
+                self.toast(text);
             }
             command => {
                 warn!("unhandled command: {:?}", command);
@@ -336,7 +1297,55 @@ impl AlliumLauncher<DefaultPlatform> {
     }
 }
 
-fn set_wallpaper(display: &mut impl Display, path: &Path) -> Result<()> {
+/// Merges library metadata with another device on the local network, returning the number of
+/// games merged, or `None` if no other device was found.
+async fn sync_library() -> Result<Option<usize>> {
+    let Some(peer) = sync::discover().await? else {
+        return Ok(None);
+    };
+    info!("syncing library with {}", peer);
+
+    let library = sync::fetch_library(peer).await?;
+    let count = library.merge_into(&Database::new()?)?;
+    sync::request_sync(peer).await?;
+    Ok(Some(count))
+}
+
+/// Returns how often the launcher draws, which is less often while the battery saver is on.
+fn frame_interval_duration(battery_saver: bool) -> Duration {
+    if battery_saver {
+        BATTERY_SAVER_FRAME_INTERVAL
+    } else {
+        LAUNCHER_FRAME_INTERVAL
+    }
+}
+
+/// Replaces the launcher with the command. In the simulator, the menu is started instead.
+fn exec(mut cmd: process::Command) {
+    // Only the launcher started at boot resumes the last game, not what it starts
+    cmd.env_remove(ALLIUM_BOOT_VAR);
+    #[cfg(feature = "miyoo")]
+    {
+        use std::os::unix::process::CommandExt;
+        let _ = cmd.exec();
+    }
+    #[cfg(not(feature = "miyoo"))]
+    {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            let _ = process::Command::new("/bin/sh")
+                .arg("-c")
+                .arg("make simulator bin=allium-menu")
+                .exec();
+        }
+
+        #[cfg(not(unix))]
+        process::exit(0);
+    }
+}
+
+pub(crate) fn set_wallpaper(display: &mut impl Display, path: &Path) -> Result<()> {
     if !path.exists() {
         return Ok(());
     }
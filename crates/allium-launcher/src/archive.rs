@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use anyhow::{Result, bail};
+use common::constants::ALLIUM_ARCHIVE_DIR;
+use common::developer::is_installed;
+use lazy_static::lazy_static;
+use log::{debug, info};
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+
+/// File in `ALLIUM_ARCHIVE_DIR` holding the path of the archive that was last extracted.
+const SOURCE_FILE: &str = ".source";
+
+/// Extensions of files that list the other files of a game, which are launched instead of them.
+const PLAYLIST_EXTENSIONS: [&str; 2] = ["m3u", "cue"];
+
+lazy_static! {
+    static ref AVAILABLE: bool = is_installed("7z");
+    /// Listings of archives by path, along with when the archive was modified.
+    static ref LISTINGS: Mutex<HashMap<PathBuf, (Option<SystemTime>, Vec<PathBuf>)>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Whether 7z is installed, without which archives can't be listed or extracted.
+pub fn is_available() -> bool {
+    *AVAILABLE
+}
+
+/// Whether the path is a zip or 7z archive.
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip") || ext.eq_ignore_ascii_case("7z"))
+}
+
+/// Lists the files in an archive, without extracting it. Listings are cached until the archive
+/// is modified. Archives with files outside of the folder they're extracted to are refused.
+pub fn list(archive: &Path) -> Result<Vec<PathBuf>> {
+    let modified = fs::metadata(archive).and_then(|m| m.modified()).ok();
+    if let Some((cached, files)) = LISTINGS.lock().unwrap().get(archive)
+        && *cached == modified
+    {
+        return Ok(files.clone());
+    }
+
+    if !is_available() {
+        bail!("7z is not installed");
+    }
+    let output = std::process::Command::new("7z")
+        .args(["l", "-ba", "-slt"])
+        .arg(archive)
+        .output()?;
+    if !output.status.success() {
+        bail!("failed to list {}: {}", archive.display(), output.status);
+    }
+    let files = parse_listing(&String::from_utf8_lossy(&output.stdout));
+    if let Some(file) = files.iter().find(|file| !is_contained(file)) {
+        bail!("{} contains {}", archive.display(), file.display());
+    }
+
+    LISTINGS
+        .lock()
+        .unwrap()
+        .insert(archive.to_path_buf(), (modified, files.clone()));
+    Ok(files)
+}
+
+/// Whether a path in an archive is made only of file and folder names, so it can't be extracted
+/// outside of `ALLIUM_ARCHIVE_DIR`.
+fn is_contained(file: &Path) -> bool {
+    file.components().next().is_some()
+        && file.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Parses the technical listing of `7z l -slt`, which has a block of `key = value` lines for
+/// each entry. Folders are skipped.
+fn parse_listing(listing: &str) -> Vec<PathBuf> {
+    listing
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut path = None;
+            for line in block.lines() {
+                match line.split_once(" = ") {
+                    Some(("Path", value)) => path = Some(PathBuf::from(value)),
+                    Some(("Folder", "+")) => return None,
+                    _ => {}
+                }
+            }
+            path
+        })
+        .collect()
+}
+
+/// Returns the path the ROM in an archive is extracted to.
+pub fn extracted_rom(archive: &Path, extensions: &[String]) -> Result<PathBuf> {
+    match pick_rom(&list(archive)?, extensions) {
+        Some(rom) => Ok(ALLIUM_ARCHIVE_DIR.join(rom)),
+        None => bail!("{} is empty", archive.display()),
+    }
+}
+
+/// Picks the ROM among the files in an archive: an .m3u or .cue file if there is one, since the
+/// files they list can't be launched on their own, then the first file with one of the console's
+/// extensions, falling back to the first file.
+fn pick_rom<'a>(files: &'a [PathBuf], extensions: &[String]) -> Option<&'a PathBuf> {
+    let extension = |file: &Path| {
+        file.extension()
+            .and_then(OsStr::to_str)
+            .map(str::to_lowercase)
+    };
+    PLAYLIST_EXTENSIONS
+        .iter()
+        .find_map(|playlist| {
+            files
+                .iter()
+                .find(|file| extension(file).as_deref() == Some(*playlist))
+        })
+        .or_else(|| {
+            files.iter().find(|file| {
+                extension(file)
+                    .is_some_and(|ext| extensions.iter().any(|e| e.eq_ignore_ascii_case(&ext)))
+            })
+        })
+        .or_else(|| files.first())
+}
+
+/// Extracts an archive to `ALLIUM_ARCHIVE_DIR`, replacing the previously extracted archive.
+/// `progress` is called with the percentage extracted so far. Extraction is skipped if the
+/// archive was the last one extracted, so relaunching a game is instant.
+pub async fn extract(archive: &Path, mut progress: impl FnMut(u8)) -> Result<()> {
+    let source = ALLIUM_ARCHIVE_DIR.join(SOURCE_FILE);
+    if fs::read_to_string(&source).is_ok_and(|s| Path::new(&s) == archive) {
+        debug!("{} is already extracted", archive.display());
+        return Ok(());
+    }
+
+    // Refuses archives that would extract files elsewhere
+    list(archive)?;
+
+    if ALLIUM_ARCHIVE_DIR.exists() {
+        fs::remove_dir_all(ALLIUM_ARCHIVE_DIR.as_path())?;
+    }
+    fs::create_dir_all(ALLIUM_ARCHIVE_DIR.as_path())?;
+
+    let mut child = Command::new("7z")
+        .args(["x", "-y", "-bso0", "-bsp1"])
+        .arg(format!("-o{}", ALLIUM_ARCHIVE_DIR.display()))
+        .arg(archive)
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    // 7z overwrites the progress in place, so read whatever has been written so far
+    if let Some(mut stdout) = child.stdout.take() {
+        let mut buf = [0; 256];
+        loop {
+            let n = stdout.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            if let Some(percent) = parse_progress(&String::from_utf8_lossy(&buf[..n])) {
+                progress(percent);
+            }
+        }
+    }
+
+    let status = child.wait().await?;
+    if !status.success() {
+        bail!("failed to extract {}: {}", archive.display(), status);
+    }
+    fs::write(&source, archive.as_os_str().as_encoded_bytes())?;
+
+    info!("extracted {}", archive.display());
+    Ok(())
+}
+
+/// Returns the last percentage in the progress output of 7z, e.g. 42 for ` 42% 3 - rom.bin`.
+fn parse_progress(output: &str) -> Option<u8> {
+    let end = output.rfind('%')?;
+    let start = output[..end]
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(0, |i| i + 1);
+    output[start..end].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_archive() {
+        assert!(is_archive(Path::new("Roms/GBA/rom.zip")));
+        assert!(is_archive(Path::new("Roms/GBA/rom.gba.7Z")));
+        assert!(!is_archive(Path::new("Roms/GBA/rom.gba")));
+        assert!(!is_archive(Path::new("Roms/GBA")));
+    }
+
+    #[test]
+    fn test_parse_listing() {
+        let listing = "Path = Disc\nFolder = +\nSize = 0\n\n\
+                       Path = Disc/rom.cue\nFolder = -\nSize = 100\n\n\
+                       Path = Disc/rom.bin\nFolder = -\nSize = 1000\n";
+        assert_eq!(
+            parse_listing(listing),
+            vec![PathBuf::from("Disc/rom.cue"), PathBuf::from("Disc/rom.bin")]
+        );
+    }
+
+    #[test]
+    fn test_is_contained() {
+        assert!(is_contained(Path::new("rom.gba")));
+        assert!(is_contained(Path::new("Disc/rom.cue")));
+        assert!(!is_contained(Path::new("")));
+        assert!(!is_contained(Path::new("../rom.gba")));
+        assert!(!is_contained(Path::new("Disc/../../rom.gba")));
+        assert!(!is_contained(Path::new("/mnt/SDCARD/rom.gba")));
+        assert!(!is_contained(Path::new("./rom.gba")));
+    }
+
+    #[test]
+    fn test_pick_rom() {
+        let extensions = vec!["bin".to_owned(), "cue".to_owned()];
+        let files = vec![
+            PathBuf::from("readme.txt"),
+            PathBuf::from("rom (Track 1).bin"),
+            PathBuf::from("rom.cue"),
+        ];
+        assert_eq!(pick_rom(&files, &extensions), Some(&files[2]));
+
+        let files = vec![
+            PathBuf::from("rom (Disc 1).cue"),
+            PathBuf::from("rom.m3u"),
+            PathBuf::from("rom (Disc 1).bin"),
+        ];
+        assert_eq!(pick_rom(&files, &extensions), Some(&files[1]));
+
+        let files = vec![PathBuf::from("readme.txt"), PathBuf::from("rom.bin")];
+        assert_eq!(pick_rom(&files, &extensions), Some(&files[1]));
+        assert_eq!(pick_rom(&files, &[]), Some(&files[0]));
+        assert_eq!(pick_rom(&[], &extensions), None);
+    }
+
+    #[test]
+    fn test_parse_progress() {
+        assert_eq!(
+            parse_progress("  5% - rom.bin\u{8}\u{8} 42% - rom.bin"),
+            Some(42)
+        );
+        assert_eq!(parse_progress("100%"), Some(100));
+        assert_eq!(parse_progress("rom.bin"), None);
+    }
+}
@@ -0,0 +1,72 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use common::constants::ALLIUM_BIOS_DIR;
+use md5::{Digest, Md5};
+use serde::Deserialize;
+
+/// A BIOS file needed by a console's cores, defined in `consoles.toml` as e.g.
+/// `bios = [{ file = "scph1001.bin", md5 = "924e392ed05558ffdb115408c263dccf" }]`.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct BiosFile {
+    /// Path of the file, relative to the BIOS folder.
+    pub file: String,
+    /// Expected MD5 checksum, if known.
+    #[serde(default)]
+    pub md5: Option<String>,
+    /// Whether the cores can run without it, e.g. with a built-in replacement.
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// Whether a BIOS file is in the BIOS folder, and is the expected one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BiosStatus {
+    Present,
+    Missing,
+    BadChecksum,
+}
+
+impl BiosFile {
+    pub fn path(&self) -> PathBuf {
+        ALLIUM_BIOS_DIR.join(&self.file)
+    }
+
+    /// Checks that the file exists and matches its checksum.
+    pub fn check(&self) -> Result<BiosStatus> {
+        let path = self.path();
+        if !path.is_file() {
+            return Ok(BiosStatus::Missing);
+        }
+        Ok(self.verify(&fs::read(path)?))
+    }
+
+    fn verify(&self, contents: &[u8]) -> BiosStatus {
+        match &self.md5 {
+            Some(md5) if !md5.eq_ignore_ascii_case(&format!("{:x}", Md5::digest(contents))) => {
+                BiosStatus::BadChecksum
+            }
+            _ => BiosStatus::Present,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let mut bios = BiosFile {
+            file: "bios.bin".into(),
+            md5: Some("D41D8CD98F00B204E9800998ECF8427E".into()),
+            optional: false,
+        };
+        assert_eq!(bios.verify(b""), BiosStatus::Present);
+        assert_eq!(bios.verify(b"bios"), BiosStatus::BadChecksum);
+
+        bios.md5 = None;
+        assert_eq!(bios.verify(b"bios"), BiosStatus::Present);
+    }
+}
@@ -1,20 +1,43 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::path::PathBuf;
 use std::{collections::HashMap, path::Path};
 
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Context, Result, anyhow};
 use common::command::Command;
 use common::database::Database;
+use common::display::color::Color;
 use common::game_info::GameInfo;
-use serde::Deserialize;
-
-use common::constants::{ALLIUM_CONFIG_CONSOLES, ALLIUM_CONFIG_CORES, ALLIUM_RETROARCH};
-use log::{debug, error, trace};
-
+use common::hotkeys::HotkeySettings;
+use common::launch_diagnostic::{LaunchDiagnostic, LaunchError};
+use common::parental::ParentalSettings;
+use common::platform::cpu::CpuProfile;
+use common::status::DeviceStatus;
+use common::storage;
+use common::stylesheet::{ConsoleStyle, Stylesheet};
+use serde::{Deserialize, Serialize};
+
+use common::constants::{
+    ALLIUM_BASE_DIR, ALLIUM_CONFIG_CONSOLES, ALLIUM_CONFIG_CORES, ALLIUM_CONSOLE_ALIASES,
+    ALLIUM_IMAGES_DIR, ALLIUM_RETROARCH, ALLIUM_SD_ROOT,
+};
+use log::{debug, error, trace, warn};
+
+use crate::archive;
+use crate::bios::BiosFile;
+use crate::entry::Entry;
 use crate::entry::game::Game;
+use crate::ports;
 
 pub type CoreName = String;
 
+/// Number of files looked at when guessing the console of a folder from file extensions.
+const DETECT_SAMPLE_SIZE: usize = 16;
+
+/// Replaced with the path of the rom in the arguments of standalone emulators.
+const ROM_PLACEHOLDER: &str = "%ROM%";
+
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
 pub struct Console {
     /// The name of the console.
@@ -34,6 +57,39 @@ pub struct Console {
     /// e.g. "Doukutsu.exe" for NXEngine
     #[serde(default)]
     pub file_name: Vec<String>,
+    /// CPU profile to run games with, unless overridden per game.
+    #[serde(default)]
+    pub cpu_profile: CpuProfile,
+    /// BIOS files needed by the cores.
+    #[serde(default)]
+    pub bios: Vec<BiosFile>,
+    /// Icon shown next to the console's folders, relative to the Allium directory. An
+    /// `images/consoles/<name>.png` icon is used instead if it exists.
+    #[serde(default)]
+    pub icon: Option<PathBuf>,
+    /// Accent color of the console's folders.
+    #[serde(default)]
+    pub color: Option<Color>,
+}
+
+impl Console {
+    /// Icon and accent color of the console. The theme's are used if it sets them, then an icon
+    /// named after the console, then the defaults of the console definition.
+    pub fn style(&self, styles: &Stylesheet) -> ConsoleStyle {
+        let theme = styles.consoles.get(&self.name);
+        let icon = theme
+            .and_then(|style| style.icon.as_ref())
+            .map(|icon| ALLIUM_SD_ROOT.join(icon))
+            .or_else(|| {
+                let icon = ALLIUM_IMAGES_DIR
+                    .join("consoles")
+                    .join(format!("{}.png", self.name));
+                icon.is_file().then_some(icon)
+            })
+            .or_else(|| self.icon.as_ref().map(|icon| ALLIUM_BASE_DIR.join(icon)));
+        let color = theme.and_then(|style| style.color).or(self.color);
+        ConsoleStyle { icon, color }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -51,6 +107,9 @@ pub struct Core {
     /// Whether swap should be enabled.
     #[serde(default)]
     pub swap: bool,
+    /// Whether archived ROMs must be extracted first, for cores that can't load them directly.
+    #[serde(default)]
+    pub extract_archives: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
@@ -60,6 +119,48 @@ pub enum CoreType {
     RetroArch(String),
     /// Path of launch script.
     Path(PathBuf),
+    /// Emulator or port that is run directly.
+    Standalone(StandaloneCore),
+}
+
+/// A standalone emulator or port, defined in `cores.toml` as e.g.
+/// `standalone = { binary = "/mnt/SDCARD/Emu/PPSSPP/PPSSPP", args = ["--fullscreen", "%ROM%"] }`.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct StandaloneCore {
+    /// Path of the binary to run.
+    pub binary: PathBuf,
+    /// Arguments to run the binary with. `%ROM%` is replaced with the path of the rom.
+    #[serde(default = "StandaloneCore::default_args")]
+    pub args: Vec<String>,
+    /// Directory to run the binary in. Defaults to the directory of the binary.
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+    /// Whether the in-game menu can be opened. Its save states and settings only work with
+    /// RetroArch, so it's off unless the emulator handles being paused.
+    #[serde(default)]
+    pub has_menu: bool,
+}
+
+impl StandaloneCore {
+    fn default_args() -> Vec<String> {
+        vec![ROM_PLACEHOLDER.to_string()]
+    }
+
+    /// Returns the arguments to run the rom with.
+    pub fn args(&self, rom: &Path) -> Vec<String> {
+        let rom = rom.display().to_string();
+        self.args
+            .iter()
+            .map(|arg| arg.replace(ROM_PLACEHOLDER, &rom))
+            .collect()
+    }
+
+    /// Returns the directory to run the binary in.
+    pub fn working_dir(&self) -> Option<PathBuf> {
+        self.working_dir
+            .clone()
+            .or_else(|| self.binary.parent().map(Path::to_path_buf))
+    }
 }
 
 impl fmt::Display for Core {
@@ -73,10 +174,31 @@ struct CoresConfig {
     cores: HashMap<CoreName, Core>,
 }
 
+/// User-editable folder aliases, also written when the user picks a console for a folder, and
+/// the console order and hidden consoles, written from the settings.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UserConfig {
+    /// Maps folder names to console names.
+    #[serde(default)]
+    aliases: BTreeMap<String, String>,
+    /// Console names, in the order their folders are listed in the Games tab. Folders of other
+    /// consoles are listed after them.
+    #[serde(default)]
+    order: Vec<String>,
+    /// Console names whose folders aren't listed in the Games tab.
+    #[serde(default)]
+    hidden: BTreeSet<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ConsoleMapper {
     cores: HashMap<CoreName, Core>,
     consoles: Vec<Console>,
+    aliases: BTreeMap<String, String>,
+    order: Vec<String>,
+    hidden: BTreeSet<String>,
+    /// Index of the console guessed for each directory, as listing it can be slow.
+    suggestions: RefCell<HashMap<PathBuf, Option<usize>>>,
 }
 
 impl Default for ConsoleMapper {
@@ -90,6 +212,10 @@ impl ConsoleMapper {
         ConsoleMapper {
             cores: HashMap::new(),
             consoles: Vec::new(),
+            aliases: BTreeMap::new(),
+            order: Vec::new(),
+            hidden: BTreeSet::new(),
+            suggestions: RefCell::new(HashMap::new()),
         }
     }
 
@@ -115,22 +241,192 @@ impl ConsoleMapper {
         let cores: CoresConfig = toml::from_str(&cores).context("Failed to parse cores.toml.")?;
         self.cores = cores.cores;
 
+        // A mistake in the hand-edited aliases shouldn't stop the launcher from starting
+        if ALLIUM_CONSOLE_ALIASES.exists() {
+            let aliases = std::fs::read_to_string(ALLIUM_CONSOLE_ALIASES.as_path())?;
+            match toml::from_str::<UserConfig>(&aliases) {
+                Ok(config) => {
+                    self.aliases = config.aliases;
+                    self.order = config.order;
+                    self.hidden = config.hidden;
+                }
+                Err(e) => warn!("Failed to parse console aliases: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns all consoles, in the order they are configured.
+    pub fn consoles(&self) -> &[Console] {
+        &self.consoles
+    }
+
+    /// Remembers that games in folders named `folder` are for the console named `console`.
+    pub fn set_alias(&mut self, folder: String, console: String) -> Result<()> {
+        self.aliases.insert(folder, console);
+        self.save_user_config()
+    }
+
+    /// Sets the order that consoles' folders are listed in, by console name.
+    pub fn set_console_order(&mut self, order: Vec<String>) -> Result<()> {
+        self.order = order;
+        self.save_user_config()
+    }
+
+    /// Hides or shows the folders of the console in the Games tab.
+    pub fn set_console_hidden(&mut self, console: &str, hidden: bool) -> Result<()> {
+        if hidden {
+            self.hidden.insert(console.to_string());
+        } else {
+            self.hidden.remove(console);
+        }
+        self.save_user_config()
+    }
+
+    pub fn is_console_hidden(&self, console: &str) -> bool {
+        self.hidden.contains(console)
+    }
+
+    /// Sorts console names by the user's console order. Consoles that aren't ordered keep their
+    /// order, after the ordered ones.
+    pub fn sort_console_names(&self, names: &mut [String]) {
+        names.sort_by_key(|name| self.console_rank(name));
+    }
+
+    /// Leaves out the folders of hidden consoles, and moves the folders of ordered consoles to
+    /// the front in the user's console order. Other entries keep their order. The console of a
+    /// folder collapsed into its only subfolder is found by the folder in `root`, e.g. `GBA` for
+    /// `GBA/Roms`.
+    pub fn arrange_console_folders(&self, root: &Path, entries: &mut Vec<Entry>) {
+        if self.order.is_empty() && self.hidden.is_empty() {
+            return;
+        }
+
+        let console_name = |entry: &Entry| match entry {
+            Entry::Directory(dir) => {
+                let folder = match dir
+                    .path
+                    .strip_prefix(root)
+                    .ok()
+                    .and_then(|p| p.components().next())
+                {
+                    Some(first) => root.join(first),
+                    None => dir.path.clone(),
+                };
+                self.get_console_by_dir(&folder)
+                    .map(|console| console.name.as_str())
+            }
+            _ => None,
+        };
+        entries.retain(|entry| console_name(entry).is_none_or(|name| !self.hidden.contains(name)));
+        entries.sort_by_cached_key(|entry| {
+            console_name(entry).map_or(usize::MAX, |name| self.console_rank(name))
+        });
+    }
+
+    /// Position of the console in the user's console order, or `usize::MAX` if it isn't ordered.
+    fn console_rank(&self, console: &str) -> usize {
+        self.order
+            .iter()
+            .position(|name| name == console)
+            .unwrap_or(usize::MAX)
+    }
+
+    fn save_user_config(&self) -> Result<()> {
+        let config = UserConfig {
+            aliases: self.aliases.clone(),
+            order: self.order.clone(),
+            hidden: self.hidden.clone(),
+        };
+        std::fs::write(ALLIUM_CONSOLE_ALIASES.as_path(), toml::to_string(&config)?)?;
         Ok(())
     }
 
-    /// Returns a console that matches the directory name exactly, or none.
+    /// Returns a console that matches the directory name exactly or is aliased to it, or none.
     pub fn get_console_by_dir(&self, path: &Path) -> Option<&Console> {
-        if let Some(name) = path.file_name().and_then(std::ffi::OsStr::to_str) {
-            let console = self
-                .consoles
+        let name = path.file_name().and_then(std::ffi::OsStr::to_str)?;
+        self.consoles
+            .iter()
+            .find(|core| core.patterns.iter().any(|s| name == s))
+            .or_else(|| self.get_console_by_alias(name))
+    }
+
+    /// Returns the console that a folder name is aliased to, or none.
+    fn get_console_by_alias(&self, folder: &str) -> Option<&Console> {
+        let name = self.aliases.get(folder)?;
+        self.consoles.iter().find(|console| console.name == *name)
+    }
+
+    /// Returns the console that handles any of the extensions of a lowercase file name, or none.
+    fn get_console_by_extension(&self, file_name: &str) -> Option<&Console> {
+        file_name.split('.').skip(1).find_map(|ext| {
+            self.consoles
                 .iter()
-                .find(|core| core.patterns.iter().any(|s| name == s));
-            if console.is_some() {
-                return console;
+                .find(|core| core.extensions.iter().any(|s| s == ext))
+        })
+    }
+
+    /// Guesses the console of a directory from the extensions of the files in it, to suggest
+    /// when asking which console its games are for. The guess is never used without asking, as
+    /// it can be wrong.
+    pub fn suggest_console(&self, dir: &Path) -> Option<&Console> {
+        let cached = self.suggestions.borrow().get(dir).copied();
+        let i = cached.unwrap_or_else(|| {
+            let i = self.detect_console(dir);
+            self.suggestions.borrow_mut().insert(dir.to_path_buf(), i);
+            i
+        });
+        i.map(|i| &self.consoles[i])
+    }
+
+    /// Returns the index of the console that the most files in the directory match, preferring
+    /// the first configured console on ties.
+    fn detect_console(&self, dir: &Path) -> Option<usize> {
+        let entries = std::fs::read_dir(dir).ok()?;
+
+        let mut counts = vec![0; self.consoles.len()];
+        for entry in entries.filter_map(|e| e.ok()).take(DETECT_SAMPLE_SIZE) {
+            let file_name = entry.file_name().to_string_lossy().to_lowercase();
+            if let Some(console) = self.get_console_by_extension(&file_name)
+                && let Some(i) = self.consoles.iter().position(|c| c == console)
+            {
+                counts[i] += 1;
             }
         }
 
-        None
+        let mut best = None;
+        for (i, count) in counts.into_iter().enumerate() {
+            if count > 0 && best.is_none_or(|(_, best)| count > best) {
+                best = Some((i, count));
+            }
+        }
+        best.map(|(i, _)| i)
+    }
+
+    /// Returns the name of the core that runs the given RetroArch core, e.g. "gpsp" for
+    /// "gpsp_libretro.so", or none.
+    pub fn get_core_by_retroarch_core(&self, libretro_core: &str) -> Option<&CoreName> {
+        let libretro_core = libretro_core
+            .strip_suffix(".so")
+            .unwrap_or(libretro_core)
+            .trim_end_matches("_libretro");
+        self.cores
+            .iter()
+            .filter(|(_, core)| {
+                matches!(&core.core, CoreType::RetroArch(name) if name == libretro_core)
+            })
+            .map(|(name, _)| name)
+            .min()
+    }
+
+    /// Returns the core that runs a game: the one chosen for it, or its console's default.
+    pub fn game_core<'a>(
+        &'a self,
+        path: &Path,
+        core: Option<&'a CoreName>,
+    ) -> Option<&'a CoreName> {
+        core.or_else(|| self.get_console(path)?.cores.first())
     }
 
     /// Returns a console that this path maps to, or none.
@@ -147,15 +443,19 @@ impl ConsoleMapper {
             }
         }
 
+        // Aliases are chosen by the user, so they take precedence over the other heuristics
+        for dir in path.ancestors() {
+            if let Some(name) = dir.file_name().and_then(std::ffi::OsStr::to_str)
+                && let Some(console) = self.get_console_by_alias(name)
+            {
+                return Some(console);
+            }
+        }
+
         if let Some(extensions) = path_lowercase.to_str() {
-            for ext in extensions.split('.').skip(1) {
-                let console = self
-                    .consoles
-                    .iter()
-                    .find(|core| core.extensions.iter().any(|s| s == ext));
-                if console.is_some() {
-                    return console;
-                }
+            let console = self.get_console_by_extension(extensions);
+            if console.is_some() {
+                return console;
             }
         }
 
@@ -175,6 +475,18 @@ impl ConsoleMapper {
             parent = path.parent();
         }
 
+        // Archives outside of console folders are matched by the ROMs they contain
+        if archive::is_archive(path) && archive::is_available() {
+            let files = archive::list(path).unwrap_or_else(|e| {
+                warn!("failed to list archive: {}", e);
+                Vec::new()
+            });
+            return files
+                .iter()
+                .filter(|file| !archive::is_archive(file))
+                .find_map(|file| self.get_console(file));
+        }
+
         None
     }
 
@@ -190,24 +502,81 @@ impl ConsoleMapper {
             database.update_game_path(&old, &game.path)?;
         }
 
+        if !storage::is_available(&game.path) {
+            warn!("storage device of {:?} isn't connected", game.path);
+            return Ok(Some(Command::LaunchFailed(Box::new(LaunchDiagnostic::new(
+                game.name.clone(),
+                None,
+                LaunchError::StorageUnavailable,
+            )))));
+        }
+
+        if let Some(block) = ParentalSettings::load()?.check_launch(database, &game.path)? {
+            debug!("parental controls blocked {:?}: {:?}", game.path, block);
+            return Ok(Some(Command::ParentalBlock(block)));
+        }
+
         let image = game.image().map(Path::to_path_buf);
         database.increment_play_count(&game.clone().into())?;
 
+        // Ports are run by their launch scripts rather than by a console's core
+        if ports::is_port(&game.path) {
+            let mut game_info = ports::game_info(game.name.clone(), &game.path, image);
+            game_info.cpu_profile = database.get_cpu_profile(&game.path)?.unwrap_or_default();
+            apply_cpu_profile(game_info.cpu_profile);
+            debug!("Saving game info: {:?}", game_info);
+            game_info.save()?;
+            return Ok(Some(Command::LaunchGame(Box::new(game_info))));
+        }
+
         let console = self.get_console(game.path.as_path());
         let Some(console) = console else {
-            bail!(
+            error!(
                 "Console for game \"{}\" does not exist.",
                 game.path.to_string_lossy()
             );
+            return Ok(Some(Command::LaunchFailed(Box::new(
+                LaunchDiagnostic::new(game.name.clone(), None, LaunchError::MissingConsole),
+            ))));
         };
         let Some(core_name) = game.core.as_ref().or_else(|| console.cores.first()) else {
-            return Ok(None);
+            error!("Console \"{}\" has no cores.", console.name);
+            return Ok(Some(Command::LaunchFailed(Box::new(
+                LaunchDiagnostic::new(game.name.clone(), None, LaunchError::BadCore),
+            ))));
         };
         let Some(core) = self.cores.get(core_name) else {
             error!("Core \"{}\" does not exist.", core_name);
-            return Ok(None);
+            return Ok(Some(Command::LaunchFailed(Box::new(
+                LaunchDiagnostic::new(
+                    game.name.clone(),
+                    Some(core_name.clone()),
+                    LaunchError::BadCore,
+                ),
+            ))));
         };
-        let game_info = match &core.core {
+        let rom = if core.extract_archives && archive::is_archive(&game.path) {
+            if !archive::is_available() {
+                error!("7z is needed to extract {:?}", game.path);
+                return Ok(Some(Command::LaunchFailed(Box::new(
+                    LaunchDiagnostic::new(
+                        game.name.clone(),
+                        Some(core_name.clone()),
+                        LaunchError::UnsupportedExtension,
+                    ),
+                ))));
+            }
+            archive::extracted_rom(&game.path, &console.extensions)?
+        } else {
+            game.path.clone()
+        };
+        // Written for every launch, so that changes to alliumd's hotkeys are picked up too
+        if matches!(core.core, CoreType::RetroArch(_))
+            && let Err(e) = HotkeySettings::load().and_then(|s| s.write_retroarch_config())
+        {
+            error!("Failed to write RetroArch hotkeys: {}", e);
+        }
+        let mut game_info = match &core.core {
             CoreType::RetroArch(libretro_core) => GameInfo::new(
                 game.name.clone(),
                 game.path.clone(),
@@ -223,7 +592,7 @@ impl ConsoleMapper {
                 } else {
                     ALLIUM_RETROARCH.display().to_string()
                 },
-                vec![libretro_core.to_string(), game.path.display().to_string()],
+                vec![libretro_core.to_string(), rom.display().to_string()],
                 true,
                 core.swap,
             ),
@@ -233,14 +602,42 @@ impl ConsoleMapper {
                 core_name.clone(),
                 image,
                 path.to_string_lossy().to_string(),
-                vec![game.path.display().to_string()],
+                vec![rom.display().to_string()],
                 false,
                 core.swap,
             ),
+            CoreType::Standalone(standalone) => {
+                let mut game_info = GameInfo::new(
+                    game.name.clone(),
+                    game.path.clone(),
+                    core_name.clone(),
+                    image,
+                    standalone.binary.display().to_string(),
+                    standalone.args(&rom),
+                    standalone.has_menu,
+                    core.swap,
+                );
+                game_info.working_dir = standalone.working_dir();
+                game_info
+            }
         };
+        game_info.cpu_profile = database
+            .get_cpu_profile(&game.path)?
+            .unwrap_or(console.cpu_profile);
+        apply_cpu_profile(game_info.cpu_profile);
+
+        // The game info is saved once the archive is extracted, so a failed extraction doesn't
+        // leave a game running
+        if rom != game.path {
+            return Ok(Some(Command::ExtractArchive {
+                archive: game.path.clone(),
+                game_info: Box::new(game_info),
+            }));
+        }
+
         debug!("Saving game info: {:?}", game_info);
         game_info.save()?;
-        Ok(Some(Command::Exec(game_info.command())))
+        Ok(Some(Command::LaunchGame(Box::new(game_info))))
     }
 
     pub fn get_core_name(&self, core: &str) -> String {
@@ -251,11 +648,20 @@ impl ConsoleMapper {
     }
 }
 
+/// Applies the game's CPU profile, which is capped while the battery saver is on.
+fn apply_cpu_profile(cpu_profile: CpuProfile) {
+    let battery_saver = DeviceStatus::load().is_ok_and(|status| status.battery_saver);
+    if let Err(e) = cpu_profile.capped(battery_saver).apply() {
+        error!("Failed to set CPU profile: {}", e);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::env;
 
     use super::*;
+    use crate::entry::directory::Directory;
     use serial_test::serial;
 
     #[test]
@@ -267,6 +673,10 @@ mod tests {
             extensions: vec!["gb".into(), "gbc".into()],
             cores: vec![],
             file_name: vec![],
+            cpu_profile: CpuProfile::default(),
+            bios: vec![],
+            icon: None,
+            color: None,
         }];
 
         assert!(mapper.get_console(Path::new("Roms/POKE/rom.zip")).is_some());
@@ -287,6 +697,150 @@ mod tests {
         assert!(mapper.get_console(Path::new("Roms/rom.zip.gbc")).is_some());
         assert!(mapper.get_console(Path::new("Roms/gbc")).is_none());
         assert!(mapper.get_console(Path::new("Roms/rom.gba")).is_none());
+
+        mapper
+            .aliases
+            .insert("Pocket Monsters".into(), "Test".into());
+        assert!(
+            mapper
+                .get_console(Path::new("Roms/Pocket Monsters/rom.zip"))
+                .is_some()
+        );
+        assert!(
+            mapper
+                .get_console_by_dir(Path::new("Roms/Pocket Monsters"))
+                .is_some()
+        );
+        mapper.aliases.insert("Other".into(), "Missing".into());
+        assert!(
+            mapper
+                .get_console(Path::new("Roms/Other/rom.zip"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_suggest_console() {
+        let console = |name: &str, extension: &str| Console {
+            name: name.into(),
+            patterns: vec![name.into()],
+            extensions: vec![extension.into()],
+            cores: vec![],
+            file_name: vec![],
+            cpu_profile: CpuProfile::default(),
+            bios: vec![],
+            icon: None,
+            color: None,
+        };
+        let mut mapper = ConsoleMapper::new();
+        mapper.consoles = vec![console("GB", "gb"), console("NES", "nes")];
+
+        let dir = env::temp_dir().join(format!("allium-suggest-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["a.nes", "b.gb", "c.nes", "notes.txt"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        // Games in a folder that isn't recognised are never matched by a guess
+        assert!(mapper.get_console(&dir.join("a.zip")).is_none());
+        assert!(mapper.get_console_by_dir(&dir).is_none());
+        assert_eq!(
+            mapper.suggest_console(&dir).map(|c| c.name.as_str()),
+            Some("NES")
+        );
+
+        // Guesses are cached until the launcher restarts
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            mapper.suggest_console(&dir).map(|c| c.name.as_str()),
+            Some("NES")
+        );
+        assert!(mapper.suggest_console(Path::new("Roms/Missing")).is_none());
+    }
+
+    #[test]
+    fn test_arrange_console_folders() {
+        let console = |name: &str| Console {
+            name: name.into(),
+            patterns: vec![name.into()],
+            extensions: vec![],
+            cores: vec![],
+            file_name: vec![],
+            cpu_profile: CpuProfile::default(),
+            bios: vec![],
+            icon: None,
+            color: None,
+        };
+        let mut mapper = ConsoleMapper::new();
+        mapper.consoles = vec![console("GB"), console("GBA"), console("NES")];
+        mapper.order = vec!["NES".into(), "GB".into()];
+        mapper.hidden.insert("GBA".into());
+
+        let mut entries = ["Roms/GB", "Roms/GBA", "Roms/NES", "Roms/Other"]
+            .map(|path| Entry::Directory(Directory::new(PathBuf::from(path))))
+            .to_vec();
+        mapper.arrange_console_folders(Path::new("Roms"), &mut entries);
+        assert_eq!(
+            entries.iter().map(Entry::name).collect::<Vec<_>>(),
+            vec!["NES", "GB", "Other"]
+        );
+
+        // Collapsed folders keep the name of the top folder, but open its only subfolder
+        let collapsed = |top: &str, path: &str| {
+            Entry::Directory(Directory::with_name(PathBuf::from(path), top.into()))
+        };
+        let mut entries = vec![
+            collapsed("GB", "Roms/GB/Games"),
+            collapsed("GBA", "Roms/GBA/Roms"),
+            collapsed("NES", "Roms/NES/Roms/USA"),
+        ];
+        mapper.arrange_console_folders(Path::new("Roms"), &mut entries);
+        assert_eq!(
+            entries.iter().map(Entry::name).collect::<Vec<_>>(),
+            vec!["NES", "GB"]
+        );
+
+        let mut names = vec!["GBA".to_string(), "GB".into(), "NES".into()];
+        mapper.sort_console_names(&mut names);
+        assert_eq!(names, vec!["NES", "GB", "GBA"]);
+    }
+
+    #[test]
+    fn test_console_style() {
+        let console = Console {
+            name: "Test".into(),
+            patterns: vec![],
+            extensions: vec![],
+            cores: vec![],
+            file_name: vec![],
+            cpu_profile: CpuProfile::default(),
+            bios: vec![],
+            icon: None,
+            color: Some(Color::new(255, 0, 0)),
+        };
+        let mut styles = Stylesheet::default();
+        assert_eq!(
+            console.style(&styles),
+            ConsoleStyle {
+                icon: None,
+                color: Some(Color::new(255, 0, 0)),
+            }
+        );
+
+        styles.consoles.insert(
+            "Test".into(),
+            ConsoleStyle {
+                icon: Some("Themes/Test/consoles/Test.png".into()),
+                color: Some(Color::new(0, 0, 255)),
+            },
+        );
+        assert_eq!(
+            console.style(&styles),
+            ConsoleStyle {
+                icon: Some(ALLIUM_SD_ROOT.join("Themes/Test/consoles/Test.png")),
+                color: Some(Color::new(0, 0, 255)),
+            }
+        );
     }
 
     #[test]
@@ -366,6 +920,42 @@ mod tests {
         assert!(eq("Cave Story (NXENGINE).m3u", "Cave Story", "nxengine"));
     }
 
+    #[test]
+    fn test_standalone_core() {
+        let cores: CoresConfig = toml::from_str(
+            r#"
+[cores.ppsspp]
+name = "PPSSPP"
+standalone = { binary = "/mnt/SDCARD/Emu/PPSSPP/PPSSPP", args = ["--fullscreen", "%ROM%"] }
+
+[cores.port]
+name = "Port"
+standalone = { binary = "/mnt/SDCARD/Roms/PORTS/game/run", working_dir = "/tmp", has_menu = true }
+"#,
+        )
+        .unwrap();
+
+        let CoreType::Standalone(ppsspp) = &cores.cores["ppsspp"].core else {
+            panic!("expected a standalone core");
+        };
+        assert_eq!(
+            ppsspp.args(Path::new("/mnt/SDCARD/Roms/PSP/My Game.iso")),
+            vec!["--fullscreen", "/mnt/SDCARD/Roms/PSP/My Game.iso"]
+        );
+        assert_eq!(
+            ppsspp.working_dir(),
+            Some(PathBuf::from("/mnt/SDCARD/Emu/PPSSPP"))
+        );
+        assert!(!ppsspp.has_menu);
+
+        let CoreType::Standalone(port) = &cores.cores["port"].core else {
+            panic!("expected a standalone core");
+        };
+        assert_eq!(port.args(Path::new("rom")), vec!["rom"]);
+        assert_eq!(port.working_dir(), Some(PathBuf::from("/tmp")));
+        assert!(port.has_menu);
+    }
+
     #[test]
     #[serial(env_ALLIUM_BASE_DIR)]
     fn test_core_names() {
@@ -1,24 +1,28 @@
 use std::{
     collections::{HashSet, VecDeque},
-    ffi::OsStr,
+    ffi::{OsStr, OsString},
     fs::{self, File},
     io::Read,
     path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{Result, anyhow};
 use common::{
     constants::ALLIUM_GAMES_DIR,
-    database::{Database, NewGame},
+    database::{Database, Game as DbGame, NewGame},
+    game_tags::GameTags,
     locale::Locale,
+    storage,
 };
 use itertools::Itertools;
-use log::{debug, error, trace};
+use log::{debug, error, trace, warn};
+use md5::{Digest, Md5};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     consoles::ConsoleMapper,
-    entry::{Entry, game::Game, gamelist::GameList, lazy_image::LazyImage, short_name},
+    entry::{Entry, game::Game, gamelist::GameList, is_listed, lazy_image::LazyImage, short_name},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -90,6 +94,21 @@ impl Directory {
         self.image.image()
     }
 
+    /// Follows a chain of folders that each only contain one folder, e.g. `GBA/Roms`, so that
+    /// opening the first folder opens the last one. The first folder's name and image are kept.
+    pub fn collapse(mut self) -> Directory {
+        let mut visited = VisitedDirs::new();
+        visited.visit(&self.path);
+        while let Some(child) = only_subdirectory(&self.path) {
+            if !visited.visit(&child) {
+                break;
+            }
+            trace!("collapsing {:?} into {:?}", self.path, child);
+            self.path = child;
+        }
+        self
+    }
+
     fn parse_game_list(&self, game_list: &Path) -> Result<Vec<Entry>> {
         let mut file = File::open(game_list)?;
         let mut s = String::with_capacity(1024);
@@ -121,18 +140,14 @@ impl Directory {
             let full_name = game.name.clone();
 
             let image = game.image.or(game.thumbnail);
-            let image = match image {
-                Some(image) => {
-                    let path = self.path.join(image).canonicalize().ok()?;
-                    if path.exists() {
-                        LazyImage::Found(path)
-                    } else {
-                        LazyImage::Unknown(self.path.clone())
-                    }
-                }
-                None => LazyImage::Unknown(path.clone()),
+            // Keep the game even if its image is missing, as OnionOS lists often refer to images
+            // that were never downloaded.
+            let image = match image.map(|image| self.path.join(image).canonicalize()) {
+                Some(Ok(image)) if image.exists() => LazyImage::Found(image),
+                _ => LazyImage::Unknown(path.clone()),
             };
 
+            let tags = GameTags::from_path(&path);
             Some(Entry::Game(Game {
                 path,
                 name: game.name,
@@ -147,6 +162,7 @@ impl Directory {
                 genres: game.genres,
                 favorite: false,
                 screenshot_path: None,
+                tags,
             }))
         });
 
@@ -245,7 +261,7 @@ impl Directory {
                                     genres: game.genres.clone(),
                                     favorite: game.favorite,
                                 }),
-                                Entry::App(_) | Entry::Directory(_) => None,
+                                Entry::App(_) | Entry::Directory(_) | Entry::Header(_) => None,
                             })
                             .collect::<Vec<_>>(),
                     )?;
@@ -298,7 +314,7 @@ impl Directory {
                                         genres: game.genres.clone(),
                                         favorite: game.favorite,
                                     }),
-                                    Entry::App(_) | Entry::Directory(_) => None,
+                                    Entry::App(_) | Entry::Directory(_) | Entry::Header(_) => None,
                                 })
                                 .collect::<Vec<_>>(),
                         )?;
@@ -348,6 +364,45 @@ impl Directory {
             entries.iter().map(|e| e.path()).collect::<Vec<_>>()
         );
 
+        // The same folder on the second storage device is merged in. Its games are listed from
+        // the database too, so they're still shown while the device is removed.
+        if let Some(mirror) = storage::mirror(&self.path) {
+            entries.extend(
+                database
+                    .select_games_in_directory(&mirror)?
+                    .into_iter()
+                    .map(Game::from_db)
+                    .map(Entry::Game),
+            );
+
+            if let Ok(read_dir) = std::fs::read_dir(&mirror) {
+                let folders: HashSet<String> = entries
+                    .iter()
+                    .filter(|e| matches!(e, Entry::Directory(_)))
+                    .map(|e| e.name().to_owned())
+                    .collect();
+                entries.extend(
+                    read_dir
+                        .filter_map(std::result::Result::ok)
+                        .filter_map(|entry| match Entry::new(entry.path(), console_mapper) {
+                            Ok(Some(entry)) => Some(entry),
+                            _ => None,
+                        })
+                        .filter(|entry| {
+                            !matches!(entry, Entry::Directory(dir) if folders.contains(&dir.name))
+                        })
+                        .sorted()
+                        .dedup_by(|a, b| a.name() == b.name()),
+                );
+            }
+
+            trace!(
+                "Entries after {:?}: {:?}",
+                mirror,
+                entries.iter().map(|e| e.path()).collect::<Vec<_>>()
+            );
+        }
+
         let mut uniques = HashSet::new();
         entries.retain(|e| uniques.insert(e.path().to_path_buf()));
 
@@ -357,35 +412,31 @@ impl Directory {
             entries.iter().map(|e| e.path()).collect::<Vec<_>>()
         );
 
+        let custom_images = database.select_custom_images()?;
         for entry in entries.iter_mut() {
-            if let Entry::Game(game) = entry
-                && let Some(core) = database.get_core(&game.path)?
-            {
-                game.core = Some(core);
+            if let Entry::Game(game) = entry {
+                if let Some(core) = database.get_core(&game.path)? {
+                    game.core = Some(core);
+                }
+                if let Some(image) = custom_images.get(&game.path) {
+                    game.image = LazyImage::Found(image.clone());
+                }
             }
         }
 
         Ok(entries)
     }
 
-    /// Populate the database with the games in this directory, pushing any subdirectories onto the
-    /// queue.
+    /// Populate the database with the games in this directory. Subdirectories are populated
+    /// separately.
     pub fn populate_db(
         &self,
-        queue: &mut VecDeque<Directory>,
         database: &Database,
         console_mapper: &ConsoleMapper,
         locale: &Locale,
     ) -> Result<()> {
         let entries = self.entries(database, console_mapper, locale)?;
 
-        for entry in &entries {
-            match entry {
-                Entry::Directory(dir) => queue.push_back(dir.clone()),
-                Entry::Game(_) | Entry::App(_) => {}
-            }
-        }
-
         let games: Vec<_> = entries
             .into_iter()
             .filter_map(|entry| match entry {
@@ -408,6 +459,195 @@ impl Directory {
 
         Ok(())
     }
+
+    /// Populates the database with the games in this directory again, after its files changed.
+    /// Unplayed games are removed first, so that deleted games don't stay.
+    pub fn repopulate_db(
+        &self,
+        database: &Database,
+        console_mapper: &ConsoleMapper,
+        locale: &Locale,
+        fingerprint: u64,
+    ) -> Result<()> {
+        database.delete_unplayed_games_in_directory(&self.path)?;
+        // Also forgets the gamelist fingerprint, so that the gamelist is parsed again
+        database.delete_directory(&self.path)?;
+        self.populate_db(database, console_mapper, locale)?;
+        database.set_directory_fingerprint(&self.path, fingerprint)
+    }
+
+    /// Hashes the names, sizes and modification times of the files in this directory, to tell
+    /// whether it changed since it was last scanned. Also returns the subdirectories, which
+    /// are fingerprinted separately so only their names count.
+    pub fn fingerprint(&self, console_mapper: &ConsoleMapper) -> Result<(u64, Vec<Directory>)> {
+        let mut files = Vec::new();
+        let mut subdirectories = Vec::new();
+        for entry in fs::read_dir(&self.path)?.filter_map(std::result::Result::ok) {
+            let Ok(metadata) = fs::metadata(entry.path()) else {
+                continue;
+            };
+            if metadata.is_dir() {
+                files.push((entry.file_name(), 0, None));
+                if let Ok(Some(Entry::Directory(dir))) = Entry::new(entry.path(), console_mapper) {
+                    subdirectories.push(dir);
+                }
+            } else {
+                files.push((entry.file_name(), metadata.len(), metadata.modified().ok()));
+            }
+        }
+        files.sort_unstable();
+
+        Ok((hash_files(&files), subdirectories))
+    }
+
+    /// Imports the display names and images from OnionOS `miyoogamelist.xml` files in this
+    /// directory and its subdirectories, even if they haven't changed since the last scan.
+    /// Folders with a `gamelist.xml` are skipped, as it takes precedence when scanning. Returns
+    /// the number of games imported.
+    pub fn import_miyoo_game_lists(&self, database: &Database) -> Result<usize> {
+        let mut count = 0;
+        let mut visited = VisitedDirs::new();
+        let mut queue = VecDeque::from([self.path.clone()]);
+        while let Some(dir) = queue.pop_front() {
+            if !visited.visit(&dir) {
+                continue;
+            }
+            let Ok(read_dir) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in read_dir.filter_map(std::result::Result::ok) {
+                let path = entry.path();
+                if path.is_dir() && path.file_name() != Some(OsStr::new("Imgs")) {
+                    queue.push_back(path);
+                }
+            }
+
+            let gamelist = dir.join("miyoogamelist.xml");
+            if !gamelist.exists() || dir.join("gamelist.xml").exists() {
+                continue;
+            }
+            debug!("Importing miyoogamelist.xml at {:?}", &gamelist);
+
+            let entries = match Directory::new(dir.clone()).parse_game_list(&gamelist) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    error!(
+                        "Failed to parse miyoogamelist.xml: {:#} ({})",
+                        e,
+                        gamelist.to_string_lossy()
+                    );
+                    continue;
+                }
+            };
+
+            let mut games = Vec::with_capacity(entries.len());
+            for entry in entries {
+                if let Entry::Game(game) = entry {
+                    let existing = database.select_game(&game.path)?;
+                    let game = NewGame {
+                        name: game.name,
+                        image: game.image.try_image().map(Path::to_path_buf),
+                        path: game.path,
+                        core: None,
+                        rating: game.rating,
+                        release_date: game.release_date,
+                        developer: game.developer,
+                        publisher: game.publisher,
+                        genres: game.genres,
+                        favorite: game.favorite,
+                    };
+                    games.push(merge_game(game, existing));
+                }
+            }
+            database.update_games(&games)?;
+            database.set_gamelist_fingerprint(&dir, fs::metadata(&gamelist)?.len())?;
+            count += games.len();
+        }
+        Ok(count)
+    }
+}
+
+/// Fills in what a game list leaves out from what's known about the game already, such as the
+/// core chosen for it, or the release date scraped from a `gamelist.xml` before. OnionOS lists
+/// usually only have names and images.
+fn merge_game(game: NewGame, existing: Option<DbGame>) -> NewGame {
+    let Some(existing) = existing else {
+        return game;
+    };
+    NewGame {
+        image: game.image.or(existing.image),
+        core: game.core.or(existing.core),
+        rating: game.rating.or(existing.rating),
+        release_date: game.release_date.or(existing.release_date),
+        developer: game.developer.or(existing.developer),
+        publisher: game.publisher.or(existing.publisher),
+        genres: if game.genres.is_empty() {
+            existing.genres
+        } else {
+            game.genres
+        },
+        ..game
+    }
+}
+
+/// Returns the only file or folder in the directory if it's a folder that can be navigated into.
+/// Folders with game lists are never skipped, as the lists may name games in their subfolders.
+fn only_subdirectory(dir: &Path) -> Option<PathBuf> {
+    if dir.join("gamelist.xml").exists() || dir.join("miyoogamelist.xml").exists() {
+        return None;
+    }
+    let mut listed = fs::read_dir(dir)
+        .ok()?
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| is_listed(path));
+    let child = listed.next()?;
+    if listed.next().is_some() || !child.is_dir() || child.extension().is_some() {
+        return None;
+    }
+    Some(child)
+}
+
+/// Folders already visited while scanning the games folder, by their canonical path, so that a
+/// symlink to a parent folder doesn't make the scan loop forever.
+#[derive(Debug, Default)]
+pub struct VisitedDirs(HashSet<PathBuf>);
+
+impl VisitedDirs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks the folder as visited. Returns false if it already was, or if it can't be resolved.
+    pub fn visit(&mut self, path: &Path) -> bool {
+        match fs::canonicalize(path) {
+            Ok(path) => self.0.insert(path),
+            Err(e) => {
+                warn!("failed to resolve {}: {}", path.display(), e);
+                false
+            }
+        }
+    }
+}
+
+/// Hashes the names, sizes and modification times of files. The fingerprint is saved in the
+/// database, so it's hashed with MD5 rather than `DefaultHasher`, which may change between Rust
+/// releases and force every directory to be scanned again.
+fn hash_files(files: &[(OsString, u64, Option<SystemTime>)]) -> u64 {
+    let mut hasher = Md5::new();
+    for (name, size, modified) in files {
+        let modified = modified
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        hasher.update(name.as_encoded_bytes());
+        // File names can't contain NUL, so it separates them
+        hasher.update([0]);
+        hasher.update(size.to_le_bytes());
+        hasher.update(modified.as_secs().to_le_bytes());
+        hasher.update(modified.subsec_nanos().to_le_bytes());
+    }
+    let digest = hasher.finalize();
+    u64::from_le_bytes(digest[..8].try_into().unwrap())
 }
 
 impl From<&Path> for Directory {
@@ -415,3 +655,128 @@ impl From<&Path> for Directory {
         Directory::new(path.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_miyoo_game_lists() {
+        let root = std::env::temp_dir().join(format!("allium-test-miyoo-{}", std::process::id()));
+        fs::create_dir_all(root.join("GBA")).unwrap();
+        fs::create_dir_all(root.join("GB")).unwrap();
+        let root = root.canonicalize().unwrap();
+        File::create(root.join("GBA/Game.gba")).unwrap();
+        File::create(root.join("GB/Game.gb")).unwrap();
+        let list = |name: &str, file: &str| {
+            format!("<gameList><game><path>./{file}</path><name>{name}</name></game></gameList>")
+        };
+        fs::write(
+            root.join("GBA/miyoogamelist.xml"),
+            list("Nice Name", "Game.gba"),
+        )
+        .unwrap();
+        fs::write(
+            root.join("GB/miyoogamelist.xml"),
+            list("Onion Name", "Game.gb"),
+        )
+        .unwrap();
+        fs::write(
+            root.join("GB/gamelist.xml"),
+            list("Scraped Name", "Game.gb"),
+        )
+        .unwrap();
+
+        let database = Database::in_memory().unwrap();
+        let scraped = |path: PathBuf| NewGame {
+            name: "Scraped Name".to_owned(),
+            path,
+            image: None,
+            core: Some("mgba".to_owned()),
+            rating: Some(8),
+            release_date: None,
+            developer: Some("Developer".to_owned()),
+            publisher: None,
+            genres: vec!["Platform".to_owned()],
+            favorite: false,
+        };
+        database
+            .update_games(&[
+                scraped(root.join("GBA/Game.gba")),
+                scraped(root.join("GB/Game.gb")),
+            ])
+            .unwrap();
+
+        let count = Directory::new(root.clone())
+            .import_miyoo_game_lists(&database)
+            .unwrap();
+        assert_eq!(count, 1);
+
+        let game = database
+            .select_game(&root.join("GBA/Game.gba"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(game.name, "Nice Name");
+        assert_eq!(game.core.as_deref(), Some("mgba"));
+        assert_eq!(game.rating, Some(8));
+        assert_eq!(game.developer.as_deref(), Some("Developer"));
+        assert_eq!(game.genres, vec!["Platform".to_owned()]);
+
+        // gamelist.xml takes precedence
+        let game = database
+            .select_game(&root.join("GB/Game.gb"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(game.name, "Scraped Name");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_hash_files() {
+        // The hash is saved in the database, so it must never change
+        assert_eq!(hash_files(&[]), 338333539836370388);
+        let modified = UNIX_EPOCH + std::time::Duration::new(1_700_000_000, 500);
+        let files = [
+            (OsString::from("Game.gba"), 1024, Some(modified)),
+            (OsString::from("Imgs"), 0, None),
+        ];
+        assert_eq!(hash_files(&files), 4021455181323305261);
+    }
+
+    #[test]
+    fn test_collapse() {
+        let root =
+            std::env::temp_dir().join(format!("allium-test-collapse-{}", std::process::id()));
+        let roms = root.join("GBA/Roms");
+        fs::create_dir_all(roms.join("Imgs")).unwrap();
+        fs::create_dir_all(root.join("GB/Hacks")).unwrap();
+        File::create(root.join("GB/Tetris.gb")).unwrap();
+
+        let dir = Directory::new(root.join("GBA")).collapse();
+        assert_eq!(dir.name, "GBA");
+        assert_eq!(dir.path, roms);
+
+        let dir = Directory::new(root.join("GB")).collapse();
+        assert_eq!(dir.path, root.join("GB"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_collapse_symlink_cycle() {
+        let root = std::env::temp_dir().join(format!("allium-test-cycle-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        std::os::unix::fs::symlink(&root, root.join("Loop")).unwrap();
+
+        let dir = Directory::new(root.clone()).collapse();
+        assert_eq!(dir.path, root);
+
+        let mut visited = VisitedDirs::new();
+        assert!(visited.visit(&root));
+        assert!(!visited.visit(&root.join("Loop/Loop")));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
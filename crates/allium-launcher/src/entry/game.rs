@@ -9,6 +9,7 @@ use anyhow::Result;
 use chrono::NaiveDate;
 use common::constants::ALLIUM_GAMES_DIR;
 use common::database::{Game as DbGame, NewGame};
+use common::game_tags::GameTags;
 use log::info;
 use serde::{Deserialize, Serialize};
 
@@ -45,6 +46,9 @@ pub struct Game {
     pub favorite: bool,
     /// Path to the save state screenshot.
     pub screenshot_path: Option<PathBuf>,
+    /// Tags in the file name, such as the region.
+    #[serde(default)]
+    pub tags: GameTags,
 }
 
 impl Game {
@@ -61,6 +65,7 @@ impl Game {
             .unwrap_or("")
             .to_string();
         let image = LazyImage::Unknown(path.clone());
+        let tags = GameTags::from_path(&path);
         Game {
             name,
             full_name,
@@ -75,6 +80,7 @@ impl Game {
             genres: Vec::new(),
             favorite: false,
             screenshot_path: None,
+            tags,
         }
     }
 
@@ -95,6 +101,7 @@ impl Game {
             .and_then(std::ffi::OsStr::to_str)
             .unwrap_or("")
             .to_string();
+        let tags = GameTags::from_path(&game.path);
 
         Game {
             name: game.name,
@@ -110,6 +117,7 @@ impl Game {
             genres: game.genres,
             favorite: game.favorite,
             screenshot_path: game.screenshot_path,
+            tags,
         }
     }
 
@@ -3,15 +3,20 @@ pub mod directory;
 pub mod game;
 mod gamelist;
 pub mod lazy_image;
+pub mod playlist;
+pub mod rom_files;
 
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fmt::Debug;
 use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use common::database::Database;
+use common::game_tags::{GameTags, Region};
 use common::locale::Locale;
 use lazy_static::lazy_static;
+use log::warn;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
@@ -25,16 +30,13 @@ pub enum Entry {
     Directory(Directory),
     App(App),
     Game(Game),
+    /// Label of a section in a list, such as when the recents were played.
+    Header(String),
 }
 
 impl Entry {
     pub fn new(path: PathBuf, console_mapper: &ConsoleMapper) -> Result<Option<Entry>> {
-        // Don't add hidden files starting with . or _
-        let file_name = match path.file_name().and_then(OsStr::to_str) {
-            Some(file_name) => file_name,
-            None => return Ok(None),
-        };
-        if file_name.starts_with('.') || file_name.starts_with('_') {
+        if !is_listed(&path) {
             return Ok(None);
         }
 
@@ -44,32 +46,15 @@ impl Entry {
             .unwrap_or_default()
             .to_owned();
 
-        // Exclude Imgs and Guide directories
-        if file_name == "Imgs"
-            || file_name == "Guides"
-            || file_name == "gamelist.xml"
-            || file_name == "miyoogamelist.xml"
-            || file_name == "neogeo.zip"
-        {
-            return Ok(None);
-        }
-
-        // Exclude DB
-        const EXCLUDE_EXTENSIONS: [&str; 3] = ["db", "sbi", "nfo"];
-        if EXCLUDE_EXTENSIONS.contains(&extension.as_str()) {
-            return Ok(None);
-        }
-
         if path.is_dir() {
             // Directories without extensions can be navigated into
             if extension.is_empty() {
-                return Ok(Some(Entry::Directory(
-                    if let Some(console) = console_mapper.get_console_by_dir(&path) {
-                        Directory::with_name(path, console.name.clone())
-                    } else {
-                        Directory::new(path)
-                    },
-                )));
+                let directory = if let Some(console) = console_mapper.get_console_by_dir(&path) {
+                    Directory::with_name(path, console.name.clone())
+                } else {
+                    Directory::new(path)
+                };
+                return Ok(Some(Entry::Directory(directory.collapse())));
             }
 
             // Apps are directories with .pak extension and have a config.json file inside
@@ -86,6 +71,7 @@ impl Entry {
             Entry::Game(game) => &game.path,
             Entry::Directory(directory) => &directory.path,
             Entry::App(app) => &app.directory,
+            Entry::Header(_) => Path::new(""),
         }
     }
 
@@ -94,6 +80,7 @@ impl Entry {
             Entry::Game(game) => &game.name,
             Entry::Directory(directory) => &directory.name,
             Entry::App(app) => &app.name,
+            Entry::Header(label) => label,
         }
     }
 
@@ -102,10 +89,38 @@ impl Entry {
             Entry::Game(game) => game.image(),
             Entry::Directory(dir) => dir.image(),
             Entry::App(app) => app.image.as_deref(),
+            Entry::Header(_) => None,
         }
     }
 }
 
+/// Whether the file or folder is shown in lists. Hidden files starting with . or _, and the
+/// folders and files used for metadata, aren't.
+fn is_listed(path: &Path) -> bool {
+    let Some(file_name) = path.file_name().and_then(OsStr::to_str) else {
+        return false;
+    };
+    if file_name.starts_with('.') || file_name.starts_with('_') {
+        return false;
+    }
+
+    // Exclude Imgs, Guides and Manuals directories
+    if file_name == "Imgs"
+        || file_name == "Guides"
+        || file_name == "Manuals"
+        || file_name == "gamelist.xml"
+        || file_name == "miyoogamelist.xml"
+        || file_name == "neogeo.zip"
+    {
+        return false;
+    }
+
+    // Exclude DB
+    const EXCLUDE_EXTENSIONS: [&str; 3] = ["db", "sbi", "nfo"];
+    let extension = path.extension().and_then(OsStr::to_str).unwrap_or_default();
+    !EXCLUDE_EXTENSIONS.contains(&extension)
+}
+
 fn short_name(mut name: &str) -> String {
     // Remove the .p8 extension for .p8.png files
     if name.ends_with(".p8") {
@@ -116,25 +131,63 @@ fn short_name(mut name: &str) -> String {
     lazy_static! {
         static ref NUMBERS_RE: Regex = Regex::new(r"^\d+[.\)]").unwrap();
     }
-    let mut name = NUMBERS_RE.replace(name, "").to_string();
+    let name = NUMBERS_RE.replace(name, "");
 
-    // Remove trailing parenthesis
-    lazy_static! {
-        static ref PARENTHESIS_RE: Regex = Regex::new(r"[\(\[].+[\)\]]$").unwrap();
-    }
-    name = PARENTHESIS_RE.replace(&name, "").to_string();
+    // Remove trailing tags, such as the region
+    GameTags::parse(&name).0.trim().to_owned()
+}
 
-    // Trim whitespaces
-    name = name.trim().to_owned();
+/// Leaves out games that are also in the list for the preferred region, by comparing titles
+/// without tags. Games without a region tag are always kept.
+pub fn retain_preferred_region(entries: &mut Vec<Entry>, region: Region) {
+    fn title(game: &Game) -> String {
+        let stem = game.path.file_stem().unwrap_or_default().to_string_lossy();
+        GameTags::parse(&stem).0.to_lowercase()
+    }
 
-    name
+    let preferred: HashSet<String> = entries
+        .iter()
+        .filter_map(|entry| match entry {
+            Entry::Game(game) if game.tags.regions.contains(&region) => Some(title(game)),
+            _ => None,
+        })
+        .collect();
+    entries.retain(|entry| match entry {
+        Entry::Game(game) => {
+            game.tags.regions.is_empty()
+                || game.tags.regions.contains(&region)
+                || !preferred.contains(&title(game))
+        }
+        _ => true,
+    });
 }
 
 pub trait Sort: Debug + Clone {
     const HAS_BUTTON_HINTS: bool = true;
     fn button_hint(&self, locale: &Locale) -> String;
-    fn next(&self) -> Self;
+    fn next(&self, database: &Database) -> Self;
     fn with_directory(&self, directory: Directory) -> Self;
+    /// Returns the sort for a folder opened from this list: the sort last used in that folder if
+    /// it's remembered, or else this sort.
+    fn open_directory(&self, database: &Database, directory: Directory) -> Self {
+        let sort = self.with_directory(directory);
+        match sort.load_folder_sort(database) {
+            Ok(Some(saved)) => saved,
+            Ok(None) => sort,
+            Err(e) => {
+                warn!("failed to load folder sort: {}", e);
+                sort
+            }
+        }
+    }
+    /// Loads the sort last used in this sort's folder, if sorts are remembered per folder.
+    fn load_folder_sort(&self, _database: &Database) -> Result<Option<Self>> {
+        Ok(None)
+    }
+    /// Remembers this sort for its folder, so that it's used the next time the folder is opened.
+    fn save_folder_sort(&self, _database: &Database) -> Result<()> {
+        Ok(())
+    }
     fn entries(
         &self,
         database: &Database,
@@ -142,4 +195,68 @@ pub trait Sort: Debug + Clone {
         locale: &Locale,
     ) -> Result<Vec<Entry>>;
     fn preserve_selection(&self) -> bool;
+    /// Whether favorite games should be grouped before other games, if enabled in the stylesheet.
+    fn group_favorites(&self) -> bool {
+        false
+    }
+    /// Whether entries can be manually reordered by holding Y.
+    fn can_reorder(&self) -> bool {
+        false
+    }
+    /// Folder whose entries can be prefetched before the list is opened.
+    fn prefetch_dir(&self) -> Option<&Path> {
+        None
+    }
+    /// Whether games for other regions are left out when the preferred region's is listed too.
+    fn prefer_region(&self) -> bool {
+        false
+    }
+    /// Whether games can be shown in their folder, for lists that aren't of a single folder.
+    fn can_show_in_folder(&self) -> bool {
+        false
+    }
+    /// Whether folders are listed as sections that expand in place, rather than being opened.
+    fn groups_directories(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_name() {
+        assert_eq!(
+            short_name("Super Mario World (USA) [!]"),
+            "Super Mario World"
+        );
+        assert_eq!(short_name("01. Tetris (World) (Rev 1)"), "Tetris");
+        assert_eq!(short_name("celeste.p8"), "celeste");
+        assert_eq!(short_name("Mega Man (Rockman) X"), "Mega Man (Rockman) X");
+    }
+
+    #[test]
+    fn test_retain_preferred_region() {
+        let mut entries: Vec<_> = [
+            "/Roms/SNES/Super Mario World (USA).sfc",
+            "/Roms/SNES/Super Mario World (Europe).sfc",
+            "/Roms/SNES/Super Mario World (Japan).sfc",
+            "/Roms/SNES/Seiken Densetsu 3 (Japan).sfc",
+            "/Roms/SNES/Super Mario World (Hack).sfc",
+        ]
+        .into_iter()
+        .map(|path| Entry::Game(Game::new(PathBuf::from(path))))
+        .collect();
+
+        retain_preferred_region(&mut entries, Region::Usa);
+        assert_eq!(
+            entries.iter().map(Entry::path).collect::<Vec<_>>(),
+            vec![
+                Path::new("/Roms/SNES/Super Mario World (USA).sfc"),
+                Path::new("/Roms/SNES/Seiken Densetsu 3 (Japan).sfc"),
+                Path::new("/Roms/SNES/Super Mario World (Hack).sfc"),
+            ]
+        );
+    }
 }
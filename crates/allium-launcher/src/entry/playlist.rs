@@ -0,0 +1,214 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use common::constants::RETROARCH_PLAYLISTS_DIR;
+use common::database::{Database, NewGame};
+use log::{debug, info, warn};
+use serde::Deserialize;
+
+use crate::consoles::ConsoleMapper;
+use crate::entry::short_name;
+
+/// A RetroArch playlist (`.lpl`).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Playlist {
+    #[serde(default)]
+    pub items: Vec<PlaylistItem>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PlaylistItem {
+    pub path: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub core_path: String,
+}
+
+impl Playlist {
+    /// Parses a playlist in either the JSON format, or the six-lines-per-entry
+    /// format used before RetroArch 1.7.6.
+    pub fn parse(s: &str) -> Result<Self> {
+        if s.trim_start().starts_with('{') {
+            return Ok(serde_json::from_str(s)?);
+        }
+
+        let lines = s.lines().collect::<Vec<_>>();
+        let items = lines
+            .chunks(6)
+            .filter(|chunk| chunk.len() >= 3)
+            .map(|chunk| PlaylistItem {
+                path: chunk[0].to_string(),
+                label: chunk[1].to_string(),
+                core_path: chunk[2].to_string(),
+            })
+            .collect();
+        Ok(Self { items })
+    }
+}
+
+impl PlaylistItem {
+    /// Path to the game. Archive entries ("game.zip#game.gba") refer to the archive.
+    fn game_path(&self) -> PathBuf {
+        PathBuf::from(self.path.split('#').next().unwrap_or_default())
+    }
+
+    /// Name of the RetroArch core associated with this item, if any.
+    fn libretro_core(&self) -> Option<&str> {
+        if self.core_path.is_empty() || self.core_path == "DETECT" {
+            return None;
+        }
+        Path::new(&self.core_path)
+            .file_name()
+            .and_then(std::ffi::OsStr::to_str)
+    }
+}
+
+/// Imports RetroArch playlists as collections. Games are added to the database if
+/// needed, and playlist core associations are used for games without a core set.
+pub fn import_playlists(database: &Database, console_mapper: &ConsoleMapper) -> Result<()> {
+    let Ok(entries) = fs::read_dir(RETROARCH_PLAYLISTS_DIR.as_path()) else {
+        debug!("no RetroArch playlists found");
+        return Ok(());
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "lpl") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(std::ffi::OsStr::to_str) else {
+            continue;
+        };
+        // History playlists aren't curated by the user
+        if name.starts_with("content_") && name.ends_with("_history") {
+            continue;
+        }
+
+        let playlist = match fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|s| Playlist::parse(&s))
+        {
+            Ok(playlist) => playlist,
+            Err(e) => {
+                warn!("failed to parse playlist {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        info!("importing playlist: {}", name);
+        import_playlist(database, console_mapper, name, &playlist)?;
+    }
+
+    Ok(())
+}
+
+fn import_playlist(
+    database: &Database,
+    console_mapper: &ConsoleMapper,
+    name: &str,
+    playlist: &Playlist,
+) -> Result<()> {
+    let mut paths = Vec::with_capacity(playlist.items.len());
+    let mut new_games = Vec::new();
+
+    for item in &playlist.items {
+        let path = item.game_path();
+        if !path.exists() {
+            debug!("skipping missing playlist entry: {}", path.display());
+            continue;
+        }
+
+        let core = item
+            .libretro_core()
+            .and_then(|core| console_mapper.get_core_by_retroarch_core(core))
+            .cloned();
+
+        match database.select_game(&path)? {
+            Some(game) => {
+                if game.core.is_none()
+                    && let Some(core) = core.as_deref()
+                {
+                    database.set_core(&path, core)?;
+                }
+            }
+            None => {
+                let name = if item.label.is_empty() {
+                    short_name(
+                        path.file_stem()
+                            .and_then(std::ffi::OsStr::to_str)
+                            .unwrap_or_default(),
+                    )
+                } else {
+                    item.label.clone()
+                };
+                new_games.push(NewGame {
+                    name,
+                    path: path.clone(),
+                    image: None,
+                    core,
+                    rating: None,
+                    release_date: None,
+                    developer: None,
+                    publisher: None,
+                    genres: Vec::new(),
+                    favorite: false,
+                });
+            }
+        }
+
+        paths.push(path);
+    }
+
+    database.update_games(&new_games)?;
+    database.set_collection(name, &paths.iter().map(PathBuf::as_path).collect::<Vec<_>>())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_playlist() {
+        let playlist = Playlist::parse(
+            r#"{
+  "version": "1.5",
+  "items": [
+    {
+      "path": "/mnt/SDCARD/Roms/GBA/Game.zip#Game.gba",
+      "label": "Game",
+      "core_path": "/mnt/SDCARD/RetroArch/.retroarch/cores/gpsp_libretro.so",
+      "core_name": "gpSP",
+      "crc32": "DETECT",
+      "db_name": "Nintendo - Game Boy Advance.lpl"
+    }
+  ]
+}"#,
+        )
+        .unwrap();
+
+        assert_eq!(playlist.items.len(), 1);
+        let item = &playlist.items[0];
+        assert_eq!(item.label, "Game");
+        assert_eq!(item.game_path(), Path::new("/mnt/SDCARD/Roms/GBA/Game.zip"));
+        assert_eq!(item.libretro_core(), Some("gpsp_libretro.so"));
+    }
+
+    #[test]
+    fn test_parse_legacy_playlist() {
+        let playlist = Playlist::parse(
+            "/mnt/SDCARD/Roms/GB/One.gb\nOne\nDETECT\nDETECT\nDETECT\nlist.lpl\n\
+             /mnt/SDCARD/Roms/GB/Two.gb\nTwo\n/cores/gambatte_libretro.so\n\
+             Gambatte\nDETECT\nlist.lpl\n",
+        )
+        .unwrap();
+
+        assert_eq!(playlist.items.len(), 2);
+        assert_eq!(playlist.items[0].libretro_core(), None);
+        assert_eq!(playlist.items[1].label, "Two");
+        assert_eq!(playlist.items[1].libretro_core(), Some("gambatte_libretro.so"));
+    }
+}
@@ -0,0 +1,502 @@
+use std::collections::VecDeque;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Result, bail};
+use common::constants::{ALLIUM_EXTERNAL_GAMES_DIR, ALLIUM_GAMES_DIR, RETROARCH_PROFILE_DIR};
+use common::database::Database;
+use common::game_info::{find_guide, find_manual};
+use common::{save_backup, storage};
+use log::{debug, info, warn};
+
+use crate::entry::directory::VisitedDirs;
+use crate::entry::game::Game;
+use crate::entry::{is_listed, short_name};
+
+/// Deletes a game by moving its files, save files, states, artwork, guide and manual to the
+/// trash, so that the deletion can be undone with the change `undo_id`. The game is removed from
+/// the database. `core` is the core that runs the game, whose folder its saves may be sorted in.
+pub fn trash(database: &Database, undo_id: i64, game: &mut Game, core: Option<&str>) -> Result<()> {
+    info!("deleting {:?}", game.path);
+    let renames: Vec<(PathBuf, PathBuf)> = std::iter::once(game.path.clone())
+        .chain(companions(&game.path))
+        .chain(save_files(&game.path, core)?)
+        .chain(artwork(game))
+        .chain(documents(&game.path))
+        .map(|file| {
+            let to = storage::trash_path(undo_id, &file);
+            (file, to)
+        })
+        .collect();
+
+    rename_all(&renames, || {
+        database.trash_game(undo_id, &game.path, &renames)
+    })
+}
+
+/// Moves the files of an undone deletion back out of the trash.
+pub fn restore(files: &[(PathBuf, PathBuf)]) {
+    for (path, trash_path) in files {
+        debug!("restoring {:?}", path);
+        let result = match path.parent() {
+            Some(parent) => fs::create_dir_all(parent),
+            None => Ok(()),
+        }
+        .and_then(|()| fs::rename(trash_path, path));
+        if let Err(e) = result {
+            warn!("failed to restore {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Removes the files of deletions that can't be undone anymore from the trash.
+pub fn empty_trash(undo_ids: &[i64]) {
+    for dir in storage::trash_dirs() {
+        for undo_id in undo_ids {
+            let dir = dir.join(undo_id.to_string());
+            if dir.exists()
+                && let Err(e) = fs::remove_dir_all(&dir)
+            {
+                warn!("failed to empty trash {:?}: {}", dir, e);
+            }
+        }
+    }
+}
+
+/// Renames a game's file, keeping its extension, along with its save files, states, artwork,
+/// guide and manual. The tracks of a cue sheet and the discs of a playlist keep their names, as
+/// they're listed by name in it. Returns the game's new path.
+pub fn rename(
+    database: &Database,
+    game: &mut Game,
+    name: &str,
+    core: Option<&str>,
+) -> Result<PathBuf> {
+    let name = name.trim();
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        bail!("invalid file name: {:?}", name);
+    }
+    let Some(stem) = game
+        .path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+    else {
+        bail!("game has no file name: {:?}", game.path);
+    };
+    let path = match game.path.extension() {
+        Some(ext) => game
+            .path
+            .with_file_name(format!("{}.{}", name, ext.to_string_lossy())),
+        None => game.path.with_file_name(name),
+    };
+    info!("renaming {:?} to {:?}", game.path, path);
+
+    let mut renames = vec![(game.path.clone(), path.clone())];
+    for file in save_files(&game.path, core)? {
+        let to = renamed(&file, &stem, name);
+        renames.push((file, to));
+    }
+    if let Some(image) = artwork(game) {
+        let to = renamed(&image, &stem, name);
+        renames.push((image, to));
+    }
+    let documents: Vec<(PathBuf, PathBuf)> = documents(&game.path)
+        .map(|file| {
+            let to = renamed(&file, &stem, name);
+            (file, to)
+        })
+        .collect();
+    renames.extend(documents.iter().cloned());
+
+    rename_all(&renames, || {
+        database.rename_game(&game.path, &path, &short_name(name), &documents)
+    })?;
+    Ok(path)
+}
+
+/// Moves a game's files, artwork, guide and manual into another folder. Save files and states are
+/// named after the game rather than its folder, so they're left where they are. Returns the
+/// game's new path.
+pub fn move_to(database: &Database, game: &mut Game, dir: &Path) -> Result<PathBuf> {
+    let Some(file_name) = game.path.file_name() else {
+        bail!("game has no file name: {:?}", game.path);
+    };
+    let path = dir.join(file_name);
+    info!("moving {:?} to {:?}", game.path, path);
+
+    let mut renames = vec![(game.path.clone(), path.clone())];
+    // Tracks and discs are listed relative to the cue sheet or playlist, so they keep their
+    // place next to it
+    if let Some(parent) = game.path.parent() {
+        for file in companions(&game.path) {
+            if let Ok(relative) = file.strip_prefix(parent) {
+                let to = dir.join(relative);
+                renames.push((file, to));
+            }
+        }
+    }
+    if let Some(image) = artwork(game)
+        && let Some(image_name) = image.file_name()
+    {
+        let to = dir.join("Imgs").join(image_name);
+        renames.push((image, to));
+    }
+    let documents: Vec<(PathBuf, PathBuf)> = documents(&game.path)
+        .filter_map(|file| {
+            let to = moved_document(&file, &game.path, &path)?;
+            Some((file, to))
+        })
+        .collect();
+    renames.extend(documents.iter().cloned());
+
+    rename_all(&renames, || {
+        database.move_game(&game.path, &path, &documents)
+    })?;
+    Ok(path)
+}
+
+/// Folders a game can be moved to: its console's folder and the folders in it, except the one
+/// it's in already.
+pub fn move_targets(game: &Path) -> Vec<PathBuf> {
+    let root = [
+        ALLIUM_GAMES_DIR.as_path(),
+        ALLIUM_EXTERNAL_GAMES_DIR.as_path(),
+    ]
+    .into_iter()
+    .find_map(|dir| {
+        let console = game.strip_prefix(dir).ok()?.components().next()?;
+        Some(dir.join(console))
+    });
+    let Some(root) = root.filter(|root| root.is_dir()) else {
+        return Vec::new();
+    };
+
+    let mut targets = Vec::new();
+    let mut visited = VisitedDirs::new();
+    let mut queue = VecDeque::from([root]);
+    while let Some(dir) = queue.pop_front() {
+        if !visited.visit(&dir) {
+            continue;
+        }
+        if let Ok(read_dir) = fs::read_dir(&dir) {
+            let mut children: Vec<PathBuf> = read_dir
+                .filter_map(std::result::Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir() && is_listed(path))
+                .collect();
+            children.sort();
+            queue.extend(children);
+        }
+        if game.parent() != Some(dir.as_path()) {
+            targets.push(dir);
+        }
+    }
+    targets
+}
+
+/// Save files and states of the game.
+fn save_files(game: &Path, core: Option<&str>) -> Result<Vec<PathBuf>> {
+    Ok(save_backup::save_files(game, core)?
+        .into_iter()
+        .map(|file| RETROARCH_PROFILE_DIR.join(file))
+        .collect())
+}
+
+/// Files the game is split into besides its own: the tracks listed in a cue sheet, or the discs
+/// listed in a playlist and their tracks. Only files in the game's folder, or folders in it, are
+/// included.
+fn companions(game: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut queue = vec![game.to_path_buf()];
+    while let Some(file) = queue.pop() {
+        let Some(dir) = file.parent() else {
+            continue;
+        };
+        for name in listed_files(&file) {
+            let name = Path::new(&name);
+            if !name
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                warn!("ignoring {:?} listed in {:?}", name, file);
+                continue;
+            }
+            let path = dir.join(name);
+            if path.is_file() && path != game && !files.contains(&path) {
+                files.push(path.clone());
+                queue.push(path);
+            }
+        }
+    }
+    files
+}
+
+/// Names of the files listed in a cue sheet or playlist.
+fn listed_files(file: &Path) -> Vec<String> {
+    let extension = file
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_ascii_lowercase);
+    let is_cue = match extension.as_deref() {
+        Some("cue") => true,
+        Some("m3u") => false,
+        _ => return Vec::new(),
+    };
+    let Ok(contents) = fs::read_to_string(file) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .map(str::trim)
+        .filter_map(|line| {
+            if is_cue {
+                cue_file(line)
+            } else {
+                (!line.is_empty() && !line.starts_with('#')).then(|| line.to_owned())
+            }
+        })
+        .collect()
+}
+
+/// The file named in a cue sheet's `FILE "Track 01.bin" BINARY` line.
+fn cue_file(line: &str) -> Option<String> {
+    let (command, rest) = line.split_once(char::is_whitespace)?;
+    if !command.eq_ignore_ascii_case("FILE") {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let name = match rest.strip_prefix('"') {
+        Some(rest) => rest.split('"').next()?,
+        None => rest.split_whitespace().next()?,
+    };
+    (!name.is_empty()).then(|| name.to_owned())
+}
+
+/// The game's guide and manual, found the same way as when it's launched.
+fn documents(game: &Path) -> impl Iterator<Item = PathBuf> {
+    find_guide(game).into_iter().chain(find_manual(game))
+}
+
+/// Where a guide or manual goes when its game moves from `old` to `new`, so that it's still
+/// found: the same place in its `Guides` or `Manuals` folder as the game's in the folder that
+/// contains it. Returns `None` if the game moves out of that folder.
+fn moved_document(document: &Path, old: &Path, new: &Path) -> Option<PathBuf> {
+    let (root, folder) = old.ancestors().skip(1).find_map(|root| {
+        let mut components = document.strip_prefix(root).ok()?.components();
+        let folder = components.next()?;
+        let relative = old.strip_prefix(root).ok()?;
+        (components.as_path().with_extension("") == relative.with_extension(""))
+            .then_some((root, folder))
+    })?;
+    let mut moved = root.join(folder).join(new.strip_prefix(root).ok()?);
+    moved.set_extension(document.extension()?);
+    Some(moved)
+}
+
+/// Artwork named after the game's file, such as `Imgs/Game.png`, which is only found as long as
+/// it keeps the same name. Artwork shared with other games or chosen on the device is left alone.
+fn artwork(game: &mut Game) -> Option<PathBuf> {
+    let rom = game.path.clone();
+    let stem = rom.file_stem()?;
+    let image = game.image.image()?;
+    let in_imgs = image.parent().and_then(Path::file_name) == Some(OsStr::new("Imgs"));
+    (in_imgs && image.file_stem() == Some(stem) && image != rom).then(|| image.to_path_buf())
+}
+
+/// The file renamed from the game's old name to its new one, e.g. `Game.state1` to
+/// `New Name.state1`.
+fn renamed(file: &Path, old: &str, new: &str) -> PathBuf {
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    match name.strip_prefix(old) {
+        Some(rest) => file.with_file_name(format!("{new}{rest}")),
+        None => file.to_path_buf(),
+    }
+}
+
+/// Renames the files, then updates the database. If anything fails, the files that were renamed
+/// already are renamed back, so that a game is never left half renamed.
+fn rename_all(renames: &[(PathBuf, PathBuf)], update: impl FnOnce() -> Result<()>) -> Result<()> {
+    if let Some((_, to)) = renames.iter().find(|(_, to)| to.exists()) {
+        bail!("{} already exists", to.display());
+    }
+
+    for (i, (from, to)) in renames.iter().enumerate() {
+        debug!("renaming {:?} to {:?}", from, to);
+        let result = match to.parent() {
+            Some(parent) => fs::create_dir_all(parent),
+            None => Ok(()),
+        }
+        .and_then(|()| fs::rename(from, to));
+        if let Err(e) = result {
+            undo(&renames[..i]);
+            return Err(e.into());
+        }
+    }
+
+    if let Err(e) = update() {
+        undo(renames);
+        return Err(e);
+    }
+    Ok(())
+}
+
+fn undo(renames: &[(PathBuf, PathBuf)]) {
+    for (from, to) in renames.iter().rev() {
+        if let Err(e) = fs::rename(to, from) {
+            warn!("failed to rename {:?} back to {:?}: {}", to, from, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("allium-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("GBA/Imgs")).unwrap();
+        File::create(dir.join("GBA/Game.gba")).unwrap();
+        File::create(dir.join("GBA/Imgs/Game.png")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_renamed() {
+        assert_eq!(
+            renamed(Path::new("states/Game.state1"), "Game", "New Name"),
+            PathBuf::from("states/New Name.state1")
+        );
+        assert_eq!(
+            renamed(Path::new("states/Other.state1"), "Game", "New Name"),
+            PathBuf::from("states/Other.state1")
+        );
+    }
+
+    #[test]
+    fn test_rename() {
+        let dir = temp_dir("rename");
+        let database = Database::in_memory().unwrap();
+        let mut game = Game::new(dir.join("GBA/Game.gba"));
+
+        let path = rename(&database, &mut game, "New Name", None).unwrap();
+        assert_eq!(path, dir.join("GBA/New Name.gba"));
+        assert!(path.exists());
+        assert!(dir.join("GBA/Imgs/New Name.png").exists());
+        assert!(!dir.join("GBA/Game.gba").exists());
+
+        let mut game = Game::new(path);
+        assert!(rename(&database, &mut game, "../Escape", None).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_move_to() {
+        let dir = temp_dir("move");
+        fs::create_dir_all(dir.join("GBA/Hacks")).unwrap();
+        let database = Database::in_memory().unwrap();
+        let mut game = Game::new(dir.join("GBA/Game.gba"));
+
+        let path = move_to(&database, &mut game, &dir.join("GBA/Hacks")).unwrap();
+        assert_eq!(path, dir.join("GBA/Hacks/Game.gba"));
+        assert!(path.exists());
+        assert!(dir.join("GBA/Hacks/Imgs/Game.png").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_companions() {
+        let dir = temp_dir("companions");
+        fs::create_dir_all(dir.join("PS/Discs")).unwrap();
+        fs::write(
+            dir.join("PS/Discs/Game (Disc 1).cue"),
+            "FILE \"Game (Disc 1).bin\" BINARY\n  TRACK 01 MODE2/2352\n",
+        )
+        .unwrap();
+        File::create(dir.join("PS/Discs/Game (Disc 1).bin")).unwrap();
+        File::create(dir.join("PS/Discs/Game (Disc 2).chd")).unwrap();
+        fs::write(
+            dir.join("PS/Game.m3u"),
+            "# Discs\nDiscs/Game (Disc 1).cue\nDiscs/Game (Disc 2).chd\n../GBA/Game.gba\n",
+        )
+        .unwrap();
+
+        let mut companions = companions(&dir.join("PS/Game.m3u"));
+        companions.sort();
+        assert_eq!(
+            companions,
+            vec![
+                dir.join("PS/Discs/Game (Disc 1).bin"),
+                dir.join("PS/Discs/Game (Disc 1).cue"),
+                dir.join("PS/Discs/Game (Disc 2).chd"),
+            ]
+        );
+        assert!(companions(&dir.join("PS/Missing.cue")).is_empty());
+
+        let database = Database::in_memory().unwrap();
+        fs::create_dir_all(dir.join("PS/Hacks")).unwrap();
+        let mut game = Game::new(dir.join("PS/Game.m3u"));
+        let path = move_to(&database, &mut game, &dir.join("PS/Hacks")).unwrap();
+        assert_eq!(path, dir.join("PS/Hacks/Game.m3u"));
+        assert!(dir.join("PS/Hacks/Discs/Game (Disc 1).bin").exists());
+        assert!(dir.join("GBA/Game.gba").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_cue_file() {
+        assert_eq!(
+            cue_file(r#"FILE "Game (Track 1).bin" BINARY"#).as_deref(),
+            Some("Game (Track 1).bin")
+        );
+        assert_eq!(
+            cue_file("file game.bin binary").as_deref(),
+            Some("game.bin")
+        );
+        assert_eq!(cue_file("TRACK 01 AUDIO"), None);
+        assert_eq!(cue_file(r#"FILE "" BINARY"#), None);
+    }
+
+    #[test]
+    fn test_moved_document() {
+        assert_eq!(
+            moved_document(
+                Path::new("/Roms/GBA/Guides/Game.txt"),
+                Path::new("/Roms/GBA/Game.gba"),
+                Path::new("/Roms/GBA/Hacks/Game.gba"),
+            ),
+            Some(PathBuf::from("/Roms/GBA/Guides/Hacks/Game.txt"))
+        );
+        assert_eq!(
+            moved_document(
+                Path::new("/Roms/GBA/Hacks/Manuals/Game.pdf"),
+                Path::new("/Roms/GBA/Hacks/Game.gba"),
+                Path::new("/Roms/GBA/Game.gba"),
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rename_all_undo() {
+        let dir = temp_dir("undo");
+        // A file where the folder should be makes the second rename fail
+        File::create(dir.join("Blocked")).unwrap();
+        let renames = [
+            (dir.join("GBA/Game.gba"), dir.join("GBA/Moved.gba")),
+            (dir.join("GBA/Imgs/Game.png"), dir.join("Blocked/Game.png")),
+        ];
+
+        assert!(rename_all(&renames, || Ok(())).is_err());
+        assert!(dir.join("GBA/Game.gba").exists());
+        assert!(!dir.join("GBA/Moved.gba").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -2,21 +2,61 @@
 #![warn(rust_2018_idioms)]
 
 mod allium_launcher;
+mod archive;
+mod bios;
 mod consoles;
 mod entry;
+mod ports;
+mod prefetch;
+#[cfg(feature = "simulator")]
+mod snapshot;
 mod view;
+#[cfg(target_os = "linux")]
+mod watcher;
 
 use anyhow::Result;
 
 use allium_launcher::AlliumLauncher;
+use common::logging::{self, LogComponent};
 use common::platform::{DefaultPlatform, Platform};
-use simple_logger::SimpleLogger;
+
+#[cfg(feature = "simulator")]
+#[derive(clap::Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Renders every screen to PNG files in this directory, then exits
+    #[arg(long)]
+    snapshot: Option<std::path::PathBuf>,
+
+    /// Theme to render snapshots with, by name
+    #[arg(long)]
+    theme: Option<String>,
+
+    /// Language to render snapshots with
+    #[arg(long)]
+    lang: Option<String>,
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    SimpleLogger::new().env().init().unwrap();
+    logging::init(LogComponent::Launcher);
 
     let platform = DefaultPlatform::new()?;
+
+    #[cfg(feature = "simulator")]
+    {
+        use clap::Parser;
+
+        let cli = Cli::parse();
+        if let Some(dir) = cli.snapshot {
+            let config = common::snapshot::SnapshotConfig {
+                theme: cli.theme,
+                lang: cli.lang,
+            };
+            return snapshot::snapshot(platform, dir, config).await;
+        }
+    }
+
     let mut app = AlliumLauncher::new(platform)?;
     app.run_event_loop().await?;
     Ok(())
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use common::constants::{ALLIUM_APPS_DIR, ALLIUM_PORTS_DIR, ALLIUM_SD_ROOT};
+use common::game_info::GameInfo;
+use log::warn;
+use serde::Deserialize;
+
+use crate::entry::game::Game;
+use crate::entry::lazy_image::LazyImage;
+
+/// Metadata file that PortMaster installs in each port's folder.
+const PORT_METADATA: &str = "port.json";
+
+/// Core name that ports are launched with, used for their screenshots.
+const PORT_CORE: &str = "port";
+
+/// Shell that runs the launch scripts.
+const PORT_SHELL: &str = "/bin/sh";
+
+/// Images in the port's metadata, in order of preference.
+const PORT_IMAGES: [&str; 2] = ["cover", "screenshot"];
+
+/// A PortMaster-style port: a launch script in the ports folder, with its data and `port.json`
+/// in a folder next to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Port {
+    /// Title of the port, or the name of the script if it has no metadata.
+    pub name: String,
+    /// Path to the launch script.
+    pub script: PathBuf,
+    /// Image from the metadata, or the box art in `Imgs`.
+    pub image: Option<PathBuf>,
+    /// Description from the metadata.
+    pub description: Option<String>,
+}
+
+/// Corresponds to PortMaster's `port.json`.
+#[derive(Debug, Default, Deserialize)]
+struct PortMetadata {
+    /// Files and folders installed by the port, relative to the ports folder. Folders end with
+    /// a slash.
+    #[serde(default)]
+    items: Vec<String>,
+    #[serde(default)]
+    attr: PortAttributes,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PortAttributes {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    desc: Option<String>,
+    /// Images by kind, relative to the port's folder.
+    #[serde(default)]
+    image: Option<HashMap<String, String>>,
+}
+
+impl PortMetadata {
+    fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    fn has_item(&self, name: &str) -> bool {
+        self.items.iter().any(|item| item == name)
+    }
+
+    fn image(&self) -> Option<&str> {
+        let images = self.attr.image.as_ref()?;
+        PORT_IMAGES
+            .iter()
+            .find_map(|kind| images.get(*kind))
+            .or_else(|| images.values().next())
+            .map(String::as_str)
+    }
+}
+
+impl Port {
+    /// Returns the ports in the ports folder, sorted by name.
+    pub fn find_all() -> Vec<Port> {
+        Self::find_in(&ALLIUM_PORTS_DIR)
+    }
+
+    fn find_in(dir: &Path) -> Vec<Port> {
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut scripts = Vec::new();
+        let mut metadata = Vec::new();
+        for entry in read_dir.filter_map(std::result::Result::ok) {
+            let path = entry.path();
+            if path.is_dir() {
+                let json = path.join(PORT_METADATA);
+                if json.is_file() {
+                    match PortMetadata::load(&json) {
+                        Ok(port) => metadata.push((path, port)),
+                        Err(e) => warn!("failed to read {}: {}", json.display(), e),
+                    }
+                }
+            } else if is_script(&path) {
+                scripts.push(path);
+            }
+        }
+
+        let mut ports: Vec<_> = scripts
+            .into_iter()
+            .map(|script| Self::new(script, &metadata))
+            .collect();
+        ports.sort_unstable_by_key(|port| port.name.to_lowercase());
+        ports
+    }
+
+    /// Creates a port from its launch script, with the metadata of the folder that lists it.
+    fn new(script: PathBuf, metadata: &[(PathBuf, PortMetadata)]) -> Self {
+        let file_name = script
+            .file_name()
+            .and_then(OsStr::to_str)
+            .unwrap_or_default();
+        let port = metadata
+            .iter()
+            .find(|(_, metadata)| metadata.has_item(file_name));
+
+        let name = port
+            .and_then(|(_, metadata)| metadata.attr.title.clone())
+            .unwrap_or_else(|| {
+                script
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            });
+        let description = port.and_then(|(_, metadata)| metadata.attr.desc.clone());
+        let image = port
+            .and_then(|(dir, metadata)| metadata.image().map(|image| dir.join(image)))
+            .filter(|image| image.is_file())
+            .or_else(|| {
+                LazyImage::Unknown(script.clone())
+                    .image()
+                    .map(Path::to_path_buf)
+            });
+
+        Self {
+            name,
+            script,
+            image,
+            description,
+        }
+    }
+
+    /// Returns the port as a game, so that it's launched and tracked like one.
+    pub fn game(&self) -> Game {
+        let mut game = Game::new(self.script.clone());
+        game.name.clone_from(&self.name);
+        game.image = LazyImage::from_path(&self.script, self.image.clone());
+        game
+    }
+}
+
+/// Whether the path is the launch script of a port.
+pub fn is_port(path: &Path) -> bool {
+    path.parent() == Some(ALLIUM_PORTS_DIR.as_path()) && is_script(path)
+}
+
+fn is_script(path: &Path) -> bool {
+    let hidden = path
+        .file_name()
+        .and_then(OsStr::to_str)
+        .is_none_or(|name| name.starts_with('.') || name.starts_with('_'));
+    !hidden
+        && path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("sh"))
+}
+
+/// Returns the game info that runs a port's launch script from the ports folder.
+pub fn game_info(name: String, script: &Path, image: Option<PathBuf>) -> GameInfo {
+    let mut game_info = GameInfo::new(
+        name,
+        script.to_path_buf(),
+        PORT_CORE.to_string(),
+        image,
+        PORT_SHELL.to_string(),
+        vec![script.display().to_string()],
+        false,
+        false,
+    );
+    game_info.working_dir = script.parent().map(Path::to_path_buf);
+    game_info.env = environment();
+    game_info
+}
+
+/// Environment that ports expect: PortMaster is installed as an app and found in
+/// `$XDG_DATA_HOME/PortMaster`, and ports keep their config in `$HOME`.
+fn environment() -> Vec<(String, String)> {
+    vec![
+        ("HOME".to_string(), ALLIUM_SD_ROOT.display().to_string()),
+        (
+            "XDG_DATA_HOME".to_string(),
+            ALLIUM_APPS_DIR.join("PortMaster").display().to_string(),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_port_metadata() {
+        let json = r#"{
+            "version": 2,
+            "name": "2048.zip",
+            "items": ["2048.sh", "2048/"],
+            "items_opt": [],
+            "attr": {
+                "title": "2048",
+                "desc": "Join the numbers and get to the 2048 tile!",
+                "porter": ["Porter"],
+                "image": {"screenshot": "screenshot.png"},
+                "rtr": true
+            }
+        }"#;
+        let metadata: PortMetadata = serde_json::from_str(json).unwrap();
+        assert!(metadata.has_item("2048.sh"));
+        assert!(!metadata.has_item("2049.sh"));
+        assert_eq!(metadata.image(), Some("screenshot.png"));
+
+        let port = Port::new(
+            PathBuf::from("/Roms/PORTS/2048.sh"),
+            &[(PathBuf::from("/Roms/PORTS/2048"), metadata)],
+        );
+        assert_eq!(port.name, "2048");
+        assert_eq!(
+            port.description.as_deref(),
+            Some("Join the numbers and get to the 2048 tile!")
+        );
+
+        let port = Port::new(PathBuf::from("/Roms/PORTS/Cave Story.sh"), &[]);
+        assert_eq!(port.name, "Cave Story");
+        assert_eq!(port.description, None);
+    }
+}
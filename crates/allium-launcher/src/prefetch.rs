@@ -0,0 +1,122 @@
+use std::cell::{Cell, RefCell};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use common::constants::{ALLIUM_GAMES_DIR, RECENT_GAMES_LIMIT};
+use common::database::Database;
+
+use crate::entry::Entry;
+
+/// Entries of folders loaded ahead of time while the launcher is idle, so that opening them is
+/// instant. Entries are taken out when the folder is opened, so they're only used once.
+#[derive(Debug, Default)]
+pub struct PrefetchCache {
+    entries: RefCell<HashMap<PathBuf, Vec<Entry>>>,
+    /// Incremented whenever the cache is cleared, so that entries loaded before then are
+    /// dropped.
+    generation: Cell<u64>,
+}
+
+impl PrefetchCache {
+    pub fn generation(&self) -> u64 {
+        self.generation.get()
+    }
+
+    pub fn insert(&self, dir: PathBuf, entries: Vec<Entry>) {
+        self.entries.borrow_mut().insert(dir, entries);
+    }
+
+    pub fn contains(&self, dir: &Path) -> bool {
+        self.entries.borrow().contains_key(dir)
+    }
+
+    pub fn take(&self, dir: &Path) -> Option<Vec<Entry>> {
+        self.entries.borrow_mut().remove(dir)
+    }
+
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+        self.generation.set(self.generation.get() + 1);
+    }
+}
+
+/// Returns the console folders in the games folder with the most played games, by play count.
+pub fn most_played_consoles(database: &Database, limit: usize) -> Result<Vec<PathBuf>> {
+    let mut counts: HashMap<PathBuf, i64> = HashMap::new();
+    for game in database.select_most_played(RECENT_GAMES_LIMIT)? {
+        let Ok(relative) = game.path.strip_prefix(ALLIUM_GAMES_DIR.as_path()) else {
+            continue;
+        };
+        // Games directly in the games folder aren't in a console folder
+        if let Some(console) = relative.parent().and_then(|p| p.components().next()) {
+            *counts.entry(ALLIUM_GAMES_DIR.join(console)).or_default() += game.play_count;
+        }
+    }
+
+    let mut consoles = counts.into_iter().collect::<Vec<_>>();
+    consoles.sort_unstable_by(|(a, a_count), (b, b_count)| {
+        (Reverse(a_count), a).cmp(&(Reverse(b_count), b))
+    });
+    Ok(consoles
+        .into_iter()
+        .take(limit)
+        .map(|(console, _)| console)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use common::database::NewGame;
+
+    use super::*;
+
+    fn play(database: &Database, path: &str) {
+        database
+            .increment_play_count(&NewGame {
+                name: path.to_string(),
+                path: ALLIUM_GAMES_DIR.join(path),
+                image: None,
+                core: None,
+                rating: None,
+                release_date: None,
+                developer: None,
+                publisher: None,
+                genres: Vec::new(),
+                favorite: false,
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_most_played_consoles() {
+        let database = Database::in_memory().unwrap();
+        play(&database, "GBA/one.gba");
+        play(&database, "SFC/two.sfc");
+        play(&database, "SFC/Hacks/three.sfc");
+        play(&database, "four.gb");
+        play(&database, "four.gb");
+
+        assert_eq!(
+            most_played_consoles(&database, 3).unwrap(),
+            vec![ALLIUM_GAMES_DIR.join("SFC"), ALLIUM_GAMES_DIR.join("GBA")]
+        );
+        assert_eq!(
+            most_played_consoles(&database, 1).unwrap(),
+            vec![ALLIUM_GAMES_DIR.join("SFC")]
+        );
+    }
+
+    #[test]
+    fn test_clear_invalidates_generation() {
+        let cache = PrefetchCache::default();
+        let generation = cache.generation();
+        cache.insert(ALLIUM_GAMES_DIR.join("GBA"), Vec::new());
+        assert!(cache.contains(&ALLIUM_GAMES_DIR.join("GBA")));
+
+        cache.clear();
+        assert!(!cache.contains(&ALLIUM_GAMES_DIR.join("GBA")));
+        assert_ne!(cache.generation(), generation);
+    }
+}
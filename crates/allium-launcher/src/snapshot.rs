@@ -0,0 +1,95 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+
+use anyhow::Result;
+use common::constants::ALLIUM_SD_ROOT;
+use common::display::Display;
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::snapshot::{SnapshotConfig, Snapshotter};
+use common::stylesheet::Stylesheet;
+use common::view::{Toast, View};
+use embedded_graphics::prelude::*;
+use log::warn;
+use tokio::sync::mpsc;
+
+use crate::allium_launcher::{load_resources, set_wallpaper};
+use crate::view::{App, Settings};
+
+const TABS: [&str; 5] = ["recents", "games", "apps", "ports", "settings"];
+const SETTINGS_TAB: usize = 4;
+
+/// Renders every tab and settings page of the launcher to `dir`, without saving any state.
+pub async fn snapshot(
+    mut platform: DefaultPlatform,
+    dir: PathBuf,
+    config: SnapshotConfig,
+) -> Result<()> {
+    let mut display = platform.display()?;
+    let battery = platform.battery()?;
+
+    let styles = config.stylesheet()?;
+    if let Some(wallpaper) = styles.wallpaper.as_deref()
+        && let Err(e) = set_wallpaper(&mut display, &ALLIUM_SD_ROOT.join(wallpaper))
+    {
+        warn!("failed to set wallpaper: {}", e);
+    }
+    display.clear(styles.background_color)?;
+
+    let res = load_resources(display.size().into(), styles.clone(), config.locale()?)?;
+    let mut snapshotter = Snapshotter::new(dir, display)?;
+    let mut app = App::without_state(snapshotter.rect(), res.clone(), battery)?;
+
+    for (i, tab) in TABS.iter().enumerate() {
+        app.tab_change(i);
+        snapshotter.capture(&format!("launcher-{tab}"), |display| {
+            draw(&mut app, display, &styles)
+        })?;
+    }
+
+    // Settings pages are opened in the order they're listed. The last one imports data and
+    // starts doing so as soon as it's opened.
+    app.tab_change(SETTINGS_TAB);
+    let entries = Settings::entries();
+    for key in &entries[..entries.len() - 1] {
+        press(&mut app, Key::A).await?;
+        snapshotter.capture(&format!("launcher-{key}"), |display| {
+            draw(&mut app, display, &styles)
+        })?;
+        press(&mut app, Key::B).await?;
+        press(&mut app, Key::Down).await?;
+    }
+
+    let text = {
+        let locale = res.get::<Locale>();
+        let mut map = HashMap::new();
+        map.insert("percent".into(), 50u8.into());
+        locale.ta("extracting-archive", &map)
+    };
+    let mut toast = Toast::new(text, None);
+    app.tab_change(1);
+    snapshotter.capture("launcher-toast", |display| {
+        draw(&mut app, display, &styles)?;
+        toast.draw(display, &styles).map(|_| ())
+    })
+}
+
+fn draw(
+    app: &mut App<<DefaultPlatform as Platform>::Battery>,
+    display: &mut <DefaultPlatform as Platform>::Display,
+    styles: &Stylesheet,
+) -> Result<()> {
+    app.set_should_draw();
+    app.draw(display, styles)?;
+    Ok(())
+}
+
+/// Passes a key press to the app, discarding the commands it sends.
+async fn press(app: &mut App<<DefaultPlatform as Platform>::Battery>, key: Key) -> Result<()> {
+    let (tx, mut rx) = mpsc::channel(100);
+    let mut bubble = VecDeque::new();
+    app.handle_key_event(KeyEvent::Pressed(key), tx, &mut bubble)
+        .await?;
+    while rx.try_recv().is_ok() {}
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use std::collections::VecDeque;
 use std::fs::{self, File};
 use std::marker::PhantomData;
+use std::path::Path;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -9,11 +10,13 @@ use common::command::Command;
 use common::constants::ALLIUM_LAUNCHER_STATE;
 use common::display::Display;
 use common::geom::{Alignment, Point, Rect};
+use common::launcher::{LauncherSettings, LauncherTab, StartupBehavior};
 use common::locale::Locale;
+use common::platform::touch::Gesture;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
 use common::stylesheet::{Stylesheet, StylesheetColor};
-use common::view::{BatteryIndicator, Clock, Label, Row, View};
+use common::view::{Label, Row, StatusBar, View};
 use log::{trace, warn};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::Sender;
@@ -21,9 +24,10 @@ use tokio::sync::mpsc::Sender;
 use crate::view::Recents;
 use crate::view::apps::AppsState;
 use crate::view::games::GamesState;
+use crate::view::ports::PortsState;
 use crate::view::recents::RecentsState;
 use crate::view::settings::SettingsState;
-use crate::view::{Apps, Games, Settings};
+use crate::view::{Apps, Games, Ports, Settings};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct AppState {
@@ -31,6 +35,8 @@ struct AppState {
     recents: RecentsState,
     games: GamesState,
     apps: AppsState,
+    #[serde(default)]
+    ports: PortsState,
     settings: SettingsState,
 }
 
@@ -40,9 +46,11 @@ where
     B: Battery + 'static,
 {
     rect: Rect,
-    status_bar: Row<Box<dyn View>>,
-    views: (Recents, Games, Apps, Settings),
+    status_bar: StatusBar,
+    views: (Recents, Games, Apps, Ports, Settings),
     selected: usize,
+    /// Views of the visible tabs, in the order they're shown.
+    order: Vec<usize>,
     tabs: Row<Label<String>>,
     // title: Label<String>,
     dirty: bool,
@@ -56,7 +64,7 @@ where
     pub fn new(
         rect: Rect,
         res: Resources,
-        views: (Recents, Games, Apps, Settings),
+        views: (Recents, Games, Apps, Ports, Settings),
         selected: usize,
         battery: B,
     ) -> Result<Self> {
@@ -64,58 +72,41 @@ where
         let styles = res.get::<Stylesheet>();
         let locale = res.get::<Locale>();
 
-        let battery_indicator = BatteryIndicator::new(
-            res.clone(),
-            Point::new(0, 0),
-            battery,
-            styles.show_battery_level,
-        );
-
-        let mut children: Vec<Box<dyn View>> = vec![Box::new(battery_indicator)];
-
-        if styles.show_clock {
-            let clock = Clock::new(res.clone(), Point::new(0, 0), Alignment::Right);
-            children.push(Box::new(clock));
-        }
+        let status_bar = StatusBar::new(res.clone(), Point::new(w as i32 - 12, y + 8), battery);
 
-        let status_bar: Row<Box<dyn View>> = Row::new(
-            Point::new(w as i32 - 12, y + 8),
-            children,
-            Alignment::Right,
-            8,
-        );
+        let order = LauncherSettings::load()?.order();
+        // Fall back to the first visible tab if the selected one was hidden
+        let selected = if order.contains(&selected) {
+            selected
+        } else {
+            order[0]
+        };
 
         let mut tabs = Row::new(
             Point::new(x + 12, y + 8),
-            {
-                let mut tabs = vec![
-                    Label::new(
+            order
+                .iter()
+                .filter_map(|&i| LauncherTab::from_index(i))
+                .map(|tab| {
+                    let mut label = Label::new(
                         Point::zero(),
-                        locale.t("tab-recents"),
+                        locale.t(tab.locale_key()),
                         Alignment::Left,
                         None,
-                    ),
-                    Label::new(Point::zero(), locale.t("tab-games"), Alignment::Left, None),
-                    Label::new(Point::zero(), locale.t("tab-apps"), Alignment::Left, None),
-                    Label::new(
-                        Point::zero(),
-                        locale.t("tab-settings"),
-                        Alignment::Left,
-                        None,
-                    ),
-                ];
-                for tab in tabs.iter_mut() {
-                    tab.color(StylesheetColor::Tab);
-                    tab.font_size(styles.tab_font_size);
-                }
-                tabs
-            },
+                    );
+                    label.color(StylesheetColor::Tab);
+                    label.font_size(styles.tab_font_size);
+                    label
+                })
+                .collect(),
             Alignment::Left,
             12,
         );
-        tabs.get_mut(selected)
-            .unwrap()
-            .color(StylesheetColor::TabSelected);
+        if let Some(position) = order.iter().position(|&i| i == selected) {
+            tabs.get_mut(position)
+                .unwrap()
+                .color(StylesheetColor::TabSelected);
+        }
 
         // let mut title = Label::new(
         //     Point::new(x + 24, y + 8),
@@ -132,6 +123,7 @@ where
             rect,
             views,
             selected,
+            order,
             status_bar,
             tabs,
             // title,
@@ -141,16 +133,7 @@ where
     }
 
     pub fn load_or_new(rect: Rect, res: Resources, battery: B) -> Result<Self> {
-        let tab_rect = {
-            let styles = res.get::<Stylesheet>();
-            let font_size = (styles.ui_font.size as f32 * styles.tab_font_size) as u32;
-            Rect::new(
-                rect.x,
-                rect.y + font_size as i32 + 8,
-                rect.w,
-                rect.h - font_size - 8,
-            )
-        };
+        let tab_rect = Self::tab_rect(rect, &res);
 
         if ALLIUM_LAUNCHER_STATE.exists() {
             let file = File::open(ALLIUM_LAUNCHER_STATE.as_path())?;
@@ -161,10 +144,11 @@ where
                         |_| Games::load_or_new(tab_rect, res.clone(), None).unwrap(),
                     ),
                     Apps::load_or_new(tab_rect, res.clone(), Some(state.apps))?,
+                    Ports::new(tab_rect, res.clone(), Some(state.ports))?,
                     Settings::new(
                         tab_rect,
                         res.clone(),
-                        if state.selected == 3 {
+                        if state.selected == 4 {
                             // Only load settings if it was the last selected tab
                             state.settings
                         } else {
@@ -178,16 +162,35 @@ where
             fs::remove_file(ALLIUM_LAUNCHER_STATE.as_path())?;
         }
 
+        Self::without_state(rect, res, battery)
+    }
+
+    /// Creates the launcher on the games tab, ignoring the saved state.
+    pub fn without_state(rect: Rect, res: Resources, battery: B) -> Result<Self> {
+        let tab_rect = Self::tab_rect(rect, &res);
         let views = (
             Recents::load_or_new(tab_rect, res.clone(), None)?,
             Games::load_or_new(tab_rect, res.clone(), None)?,
             Apps::load_or_new(tab_rect, res.clone(), None)?,
+            Ports::new(tab_rect, res.clone(), None)?,
             Settings::new(tab_rect, res.clone(), Default::default())?,
         );
         let selected = 1;
         Self::new(rect, res, views, selected, battery)
     }
 
+    /// Area below the tab titles.
+    fn tab_rect(rect: Rect, res: &Resources) -> Rect {
+        let styles = res.get::<Stylesheet>();
+        let font_size = (styles.ui_font.size as f32 * styles.tab_font_size) as u32;
+        Rect::new(
+            rect.x,
+            rect.y + font_size as i32 + 8,
+            rect.w,
+            rect.h - font_size - 8,
+        )
+    }
+
     pub fn save(&self) -> Result<()> {
         let file = File::create(ALLIUM_LAUNCHER_STATE.as_path())?;
         let state = AppState {
@@ -195,7 +198,8 @@ where
             recents: self.views.0.save(),
             games: self.views.1.save(),
             apps: self.views.2.save(),
-            settings: self.views.3.save(),
+            ports: self.views.3.save(),
+            settings: self.views.4.save(),
         };
         serde_json::to_writer(file, &state)?;
         Ok(())
@@ -207,6 +211,7 @@ where
             1 => &self.views.1,
             2 => &self.views.2,
             3 => &self.views.3,
+            4 => &self.views.4,
             _ => unreachable!(),
         }
     }
@@ -217,33 +222,68 @@ where
             1 => &mut self.views.1,
             2 => &mut self.views.2,
             3 => &mut self.views.3,
+            4 => &mut self.views.4,
             _ => unreachable!(),
         }
     }
 
-    fn tab_change(&mut self, selected: usize) {
-        self.tabs
-            .get_mut(self.selected)
-            .unwrap()
-            .color(StylesheetColor::Tab);
+    /// Position of the view in the tab titles, or `None` if its tab is hidden.
+    fn position(&self, selected: usize) -> Option<usize> {
+        self.order.iter().position(|&i| i == selected)
+    }
+
+    pub fn tab_change(&mut self, selected: usize) {
+        if let Some(position) = self.position(self.selected) {
+            self.tabs
+                .get_mut(position)
+                .unwrap()
+                .color(StylesheetColor::Tab);
+        }
         self.selected = selected;
         self.view_mut().set_should_draw();
         self.set_should_draw();
-        self.tabs
-            .get_mut(self.selected)
-            .unwrap()
-            .color(StylesheetColor::TabSelected);
+        if let Some(position) = self.position(self.selected) {
+            self.tabs
+                .get_mut(position)
+                .unwrap()
+                .color(StylesheetColor::TabSelected);
+        }
         // self.title.set_text(self.title());
     }
 
+    /// Opens what the launcher settings say to open on boot: the startup tab if one is set and
+    /// visible, or a folder of the games tab.
+    pub fn open_startup(&mut self, settings: &LauncherSettings) -> Result<()> {
+        match &settings.startup {
+            StartupBehavior::LastView => {
+                if let Some(tab) = settings.startup_tab
+                    && self.order.contains(&tab.index())
+                    && tab.index() != self.selected
+                {
+                    self.tab_change(tab.index());
+                }
+            }
+            StartupBehavior::Folder(dir) => {
+                self.views.1.show_folder(dir)?;
+                self.tab_change(1);
+            }
+            // Launched by the launcher once it's running
+            StartupBehavior::Game(_) => {}
+        }
+        Ok(())
+    }
+
     fn next(&mut self) {
-        let selected = (self.selected + 1).rem_euclid(4);
-        self.tab_change(selected)
+        let position = self.position(self.selected).map_or(0, |p| p + 1);
+        self.tab_change(self.order[position.rem_euclid(self.order.len())])
     }
 
     fn prev(&mut self) {
-        let selected = (self.selected as isize - 1).rem_euclid(4);
-        self.tab_change(selected as usize)
+        let position = self
+            .position(self.selected)
+            .map_or(0, |p| p as isize - 1)
+            .rem_euclid(self.order.len() as isize);
+        self.tab_change(self.order[position as usize])
     }
 
     pub fn start_search(&mut self) {
@@ -257,6 +297,12 @@ where
         Ok(())
     }
 
+    pub fn show_in_folder(&mut self, path: &Path) -> Result<()> {
+        self.views.1.show_in_folder(path)?;
+        self.tab_change(1);
+        Ok(())
+    }
+
     // fn title(&self) -> String {
     //     title(&self.res.get::<Locale>(), self.selected)
     // }
@@ -335,6 +381,25 @@ where
         }
     }
 
+    async fn handle_gesture(
+        &mut self,
+        gesture: Gesture,
+        styles: &Stylesheet,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Gesture::Tap(point) = gesture
+            && let Some(position) = self.tabs.child_at(point, styles)
+        {
+            self.tab_change(self.order[position]);
+            return Ok(true);
+        }
+
+        self.view_mut()
+            .handle_gesture(gesture, styles, commands, bubble)
+            .await
+    }
+
     fn children(&self) -> Vec<&dyn View> {
         vec![&self.status_bar, self.view(), &self.tabs]
     }
@@ -345,6 +410,7 @@ where
             1 => &mut self.views.1,
             2 => &mut self.views.2,
             3 => &mut self.views.3,
+            4 => &mut self.views.4,
             _ => unreachable!(),
         };
         vec![&mut self.status_bar, view, &mut self.tabs]
@@ -364,7 +430,8 @@ where
 //         0 => locale.t("tab-recents"),
 //         1 => locale.t("tab-games"),
 //         2 => locale.t("tab-apps"),
-//         3 => locale.t("tab-settings"),
+//         3 => locale.t("tab-ports"),
+//         4 => locale.t("tab-settings"),
 //         _ => unreachable!(),
 //     }
 // }
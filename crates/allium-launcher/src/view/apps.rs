@@ -19,39 +19,49 @@ use crate::consoles::ConsoleMapper;
 use crate::entry::directory::Directory;
 use crate::entry::{Entry, Sort};
 use crate::view::entry_list::{EntryList, EntryListState};
+use crate::view::navigation::NavigationStack;
 
-pub type AppsState = EntryListState<AppsSort>;
+/// Saved state of each folder opened, from the apps folder to the current folder.
+pub type AppsState = Vec<EntryListState<AppsSort>>;
 
 #[derive(Debug)]
 pub struct Apps {
     rect: Rect,
-    list: EntryList<AppsSort>,
+    stack: NavigationStack<EntryList<AppsSort>>,
 }
 
 impl Apps {
-    pub fn new(rect: Rect, _res: Resources, list: EntryList<AppsSort>) -> Result<Self> {
-        Ok(Self { rect, list })
+    pub fn new(
+        rect: Rect,
+        _res: Resources,
+        stack: NavigationStack<EntryList<AppsSort>>,
+    ) -> Result<Self> {
+        Ok(Self { rect, stack })
     }
 
     pub fn load_or_new(rect: Rect, res: Resources, state: Option<AppsState>) -> Result<Self> {
-        let list = if let Some(state) = state {
-            let selected = state.selected;
-            let mut list = EntryList::load(rect, res.clone(), state)?;
-            list.select(selected);
-            list
-        } else {
-            EntryList::new(
+        let mut lists = state
+            .unwrap_or_default()
+            .into_iter()
+            .map(|state| EntryList::load(rect, res.clone(), state))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter();
+        let root = match lists.next() {
+            Some(root) => root,
+            None => EntryList::new(
                 rect,
                 res.clone(),
                 AppsSort::Alphabetical(Directory::new(ALLIUM_APPS_DIR.clone())),
-            )?
+            )?,
         };
 
-        Self::new(rect, res, list)
+        let mut stack = NavigationStack::new(rect, root);
+        stack.extend(lists);
+        Self::new(rect, res, stack)
     }
 
     pub fn save(&self) -> AppsState {
-        self.list.save()
+        self.stack.iter().map(EntryList::save).collect()
     }
 }
 
@@ -64,17 +74,17 @@ impl View for Apps {
     ) -> Result<bool> {
         let mut drawn = false;
 
-        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.stack.should_draw() && self.stack.draw(display, styles)?;
 
         Ok(drawn)
     }
 
     fn should_draw(&self) -> bool {
-        self.list.should_draw()
+        self.stack.should_draw()
     }
 
     fn set_should_draw(&mut self) {
-        self.list.set_should_draw();
+        self.stack.set_should_draw();
     }
 
     async fn handle_key_event(
@@ -83,15 +93,15 @@ impl View for Apps {
         commands: Sender<Command>,
         bubble: &mut VecDeque<Command>,
     ) -> Result<bool> {
-        self.list.handle_key_event(event, commands, bubble).await
+        self.stack.handle_key_event(event, commands, bubble).await
     }
 
     fn children(&self) -> Vec<&dyn View> {
-        vec![&self.list]
+        vec![&self.stack]
     }
 
     fn children_mut(&mut self) -> Vec<&mut dyn View> {
-        vec![&mut self.list]
+        vec![&mut self.stack]
     }
 
     fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
@@ -125,7 +135,7 @@ impl Sort for AppsSort {
         }
     }
 
-    fn next(&self) -> Self {
+    fn next(&self, _database: &Database) -> Self {
         match self {
             AppsSort::Alphabetical(d) => AppsSort::Alphabetical(d.clone()),
         }
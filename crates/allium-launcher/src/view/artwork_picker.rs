@@ -0,0 +1,261 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::{Command, Value};
+use common::constants::{ALLIUM_GAMES_DIR, ALLIUM_SCREENSHOTS_DIR, ALLIUM_SD_ROOT};
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Image, ImageMode, Label, Row, ScrollList, View};
+use tokio::sync::mpsc::Sender;
+
+/// Extensions of the images that can be chosen.
+const IMAGE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "gif"];
+
+/// Full-screen browser for choosing a game's artwork, from the screenshots and the `Imgs`
+/// folders of the game's folder and its parents. The selected image is previewed.
+///
+/// Choosing an image bubbles `Command::ValueChanged` with its path, then `Command::CloseView`.
+#[derive(Debug)]
+pub struct ArtworkPicker {
+    rect: Rect,
+    images: Vec<PathBuf>,
+    title: Label<String>,
+    list: ScrollList,
+    image: Image,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl ArtworkPicker {
+    pub fn new(rect: Rect, res: Resources, game: &Path) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let images = find_images(game);
+
+        let mut title = Label::new(
+            Point::new(x + 12, y + 8),
+            if images.is_empty() {
+                locale.t("artwork-empty")
+            } else {
+                locale.t("artwork-title")
+            },
+            Alignment::Left,
+            Some(w - 24),
+        );
+        title.font_size(styles.tab_font_size);
+
+        let top = y + 8 + (styles.ui_font.size as f32 * styles.tab_font_size) as i32 + 8;
+        let height = (y + h as i32 - top) as u32 - 8 - ButtonHint::bar_height(&styles);
+        let list = ScrollList::new(
+            Rect::new(x + 12, top, w / 2 - 24, height),
+            images
+                .iter()
+                .map(|image| {
+                    image
+                        .strip_prefix(ALLIUM_SD_ROOT.as_path())
+                        .unwrap_or(image)
+                        .display()
+                        .to_string()
+                })
+                .collect(),
+            Alignment::Left,
+            styles.list_entry_height(),
+        );
+
+        let mut image = Image::empty(
+            Rect::new(x + w as i32 / 2, top, w / 2 - 12, height),
+            ImageMode::Contain,
+        );
+        image.set_border_radius(12);
+        image.set_alignment(Alignment::Right);
+        image.set_path(images.first().cloned());
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-select"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+
+        Self {
+            rect,
+            images,
+            title,
+            list,
+            image,
+            button_hints,
+            dirty: true,
+        }
+    }
+}
+
+/// Returns the images that can be chosen for a game: screenshots, newest first, then the images
+/// in the `Imgs` folders of the game's folder and its parents, by name.
+fn find_images(game: &Path) -> Vec<PathBuf> {
+    let mut screenshots: Vec<(SystemTime, PathBuf)> = list_images(&ALLIUM_SCREENSHOTS_DIR)
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            (modified, path)
+        })
+        .collect();
+    screenshots.sort_unstable_by(|a, b| b.cmp(a));
+    let mut images: Vec<_> = screenshots.into_iter().map(|(_, path)| path).collect();
+
+    for dir in game.ancestors().skip(1) {
+        let mut imgs = list_images(&dir.join("Imgs"));
+        imgs.sort_unstable_by_key(|path| path.to_string_lossy().to_lowercase());
+        images.extend(imgs);
+        if dir == ALLIUM_GAMES_DIR.as_path() {
+            break;
+        }
+    }
+
+    images
+}
+
+fn list_images(dir: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    read_dir
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| {
+                    IMAGE_EXTENSIONS
+                        .iter()
+                        .any(|image| ext.eq_ignore_ascii_case(image))
+                })
+        })
+        .collect()
+}
+
+#[async_trait(?Send)]
+impl View for ArtworkPicker {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.title.set_should_draw();
+            self.list.set_should_draw();
+            self.image.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.title.should_draw() && self.title.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.image.should_draw() && self.image.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.title.should_draw()
+            || self.list.should_draw()
+            || self.image.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                if let Some(image) = self.images.get(self.list.selected()) {
+                    bubble.push_back(Command::ValueChanged(
+                        self.list.selected(),
+                        Value::String(image.display().to_string()),
+                    ));
+                    bubble.push_back(Command::CloseView);
+                }
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+            }
+            _ => {
+                let selected = self.list.selected();
+                self.list.handle_key_event(event, commands, bubble).await?;
+                if self.list.selected() != selected {
+                    self.image
+                        .set_path(self.images.get(self.list.selected()).cloned());
+                }
+            }
+        }
+        // Traps focus, so that the tabs don't change underneath
+        Ok(true)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.title, &self.list, &self.image, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![
+            &mut self.title,
+            &mut self.list,
+            &mut self.image,
+            &mut self.button_hints,
+        ]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
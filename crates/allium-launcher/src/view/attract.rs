@@ -0,0 +1,239 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::{
+    ALLIUM_RECORDINGS_DIR, ATTRACT_MODE_FRAME_INTERVAL, ATTRACT_MODE_GAMES_LIMIT,
+    ATTRACT_MODE_SLIDE_DURATION,
+};
+use common::database::Database;
+use common::geom::{Alignment, Point, Rect};
+use common::platform::{DefaultPlatform, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{Image, ImageMode, Label, View};
+use embedded_graphics::Drawable;
+use embedded_graphics::primitives::{Primitive, PrimitiveStyle, Rectangle};
+use tokio::sync::mpsc::Sender;
+
+use crate::entry::game::Game;
+
+/// A single slide shown in attract mode.
+#[derive(Debug, Clone)]
+enum Slide {
+    /// A still image, such as box art or a screenshot.
+    Still(PathBuf),
+    /// A short gameplay recording, stored as a directory of numbered frames.
+    Recording(Vec<PathBuf>),
+}
+
+/// Cycles through box art, screenshots and recordings of favorite games while the device is idle.
+#[derive(Debug)]
+pub struct Attract {
+    rect: Rect,
+    slides: Vec<(Slide, String)>,
+    index: usize,
+    frame: usize,
+    elapsed: Duration,
+    image: Image,
+    caption: Label<String>,
+    dirty: bool,
+}
+
+impl Attract {
+    /// Returns `None` if there is nothing to show.
+    pub fn new(rect: Rect, res: Resources) -> Result<Option<Self>> {
+        let Rect { x, y, w, h } = rect;
+
+        let games = {
+            let database = res.get::<Database>();
+            let games = database.select_favorites(ATTRACT_MODE_GAMES_LIMIT)?;
+            if games.is_empty() {
+                database.select_most_played(ATTRACT_MODE_GAMES_LIMIT)?
+            } else {
+                games
+            }
+        };
+
+        let slides: Vec<_> = games
+            .into_iter()
+            .flat_map(|game| slides(Game::from_db(game)))
+            .collect();
+        if slides.is_empty() {
+            return Ok(None);
+        }
+
+        let styles = res.get::<Stylesheet>();
+        let caption_h = styles.ui_font.size + 16;
+
+        let image = Image::empty(Rect::new(x, y, w, h - caption_h), ImageMode::Contain);
+        let caption = Label::new(
+            Point::new(x + 12, y + (h - caption_h) as i32 + 8),
+            String::new(),
+            Alignment::Left,
+            Some(w - 24),
+        );
+
+        drop(styles);
+
+        let mut this = Self {
+            rect,
+            slides,
+            index: 0,
+            frame: 0,
+            elapsed: Duration::ZERO,
+            image,
+            caption,
+            dirty: true,
+        };
+        this.show();
+        Ok(Some(this))
+    }
+
+    fn next(&mut self) {
+        self.index = (self.index + 1) % self.slides.len();
+        self.show();
+    }
+
+    fn show(&mut self) {
+        let (slide, caption) = &self.slides[self.index];
+        let path = match slide {
+            Slide::Still(path) => path,
+            Slide::Recording(frames) => &frames[0],
+        };
+        self.image.set_path(Some(path.clone()));
+        self.caption.set_text(caption.clone());
+        self.elapsed = Duration::ZERO;
+        self.frame = 0;
+        self.dirty = true;
+    }
+}
+
+/// Returns the slides of a game, all captioned with its name and developer.
+fn slides(mut game: Game) -> Vec<(Slide, String)> {
+    let caption = match game.developer.as_deref() {
+        Some(developer) => format!("{} · {}", game.name, developer),
+        None => game.name.clone(),
+    };
+
+    let mut slides = Vec::new();
+    if let Some(image) = game.image() {
+        slides.push(Slide::Still(image.to_path_buf()));
+    }
+    if let Some(screenshot) = game.screenshot_path.as_ref().filter(|p| p.exists()) {
+        slides.push(Slide::Still(screenshot.clone()));
+    }
+
+    let recording = game
+        .path
+        .file_stem()
+        .map(|stem| ALLIUM_RECORDINGS_DIR.join(stem));
+    if let Some(Ok(entries)) = recording.map(fs::read_dir) {
+        let mut frames: Vec<_> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "png"))
+            .collect();
+        frames.sort();
+        if !frames.is_empty() {
+            slides.push(Slide::Recording(frames));
+        }
+    }
+
+    slides
+        .into_iter()
+        .map(|slide| (slide, caption.clone()))
+        .collect()
+}
+
+#[async_trait(?Send)]
+impl View for Attract {
+    fn update(&mut self, dt: Duration) {
+        self.elapsed += dt;
+        match &self.slides[self.index].0 {
+            Slide::Still(_) => {
+                if self.elapsed >= ATTRACT_MODE_SLIDE_DURATION {
+                    self.next();
+                }
+            }
+            Slide::Recording(frames) => {
+                let interval = ATTRACT_MODE_FRAME_INTERVAL.as_millis();
+                let frame = (self.elapsed.as_millis() / interval) as usize;
+                if frame >= frames.len() {
+                    self.next();
+                } else if frame != self.frame {
+                    self.frame = frame;
+                    let path = frames[frame].clone();
+                    self.image.set_path(Some(path));
+                }
+            }
+        }
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.image.should_draw() {
+            drawn |= self.image.draw(display, styles)?;
+        }
+
+        if self.dirty {
+            let caption_h = styles.ui_font.size + 16;
+            Rectangle::from(Rect::new(
+                self.rect.x,
+                self.rect.y + (self.rect.h - caption_h) as i32,
+                self.rect.w,
+                caption_h,
+            ))
+            .into_styled(PrimitiveStyle::with_fill(styles.background_color))
+            .draw(display)?;
+            self.caption.draw(display, styles)?;
+            self.dirty = false;
+            drawn = true;
+        }
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty || self.image.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        self.image.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        _event: KeyEvent,
+        _commands: Sender<Command>,
+        _bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.image, &self.caption]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.image, &mut self.caption]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
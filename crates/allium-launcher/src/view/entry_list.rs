@@ -1,32 +1,52 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::mem;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use async_trait::async_trait;
-use common::command::Command;
-use common::constants::SELECTION_MARGIN;
-use common::database::Database;
+use common::command::{Command, Value};
+use common::constants::{ALLIUM_EXTERNAL_GAMES_DIR, ALLIUM_GAMES_DIR};
+use common::database::{Database, UndoAction};
 use common::display::Display;
+use common::game_info::find_manual;
 use common::geom::{Alignment, Point, Rect};
+use common::launcher::{LauncherSettings, StartupBehavior};
 use common::locale::Locale;
+use common::platform::cpu::CpuProfile;
+use common::platform::touch::Gesture;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
-use common::stylesheet::{Stylesheet, StylesheetColor};
-use common::view::{ButtonHint, ButtonIcon, Image, ImageMode, Row, ScrollList, View};
+use common::status::DeviceStatus;
+use common::storage;
+use common::stylesheet::{ConsoleStyle, Stylesheet, StylesheetColor, TagDisplay};
+use common::view::{
+    ButtonHint, ButtonIcon, Dialog, Image, ImageMode, Keyboard, Label, PdfViewer, Row, ScrollList,
+    View,
+};
 use embedded_graphics::Drawable;
 use embedded_graphics::prelude::{Dimensions, OriginDimensions, Size};
 use embedded_graphics::primitives::{CornerRadii, Primitive, PrimitiveStyle, RoundedRectangle};
-use log::{debug, trace};
+use log::{debug, trace, warn};
 use serde::{Deserialize, Serialize};
+use strum::EnumCount;
 use tokio::sync::mpsc::Sender;
 
 use crate::consoles::ConsoleMapper;
-use crate::entry::{Entry, Sort};
+use crate::entry::game::Game;
+use crate::entry::lazy_image::LazyImage;
+use crate::entry::rom_files;
+use crate::entry::{Entry, Sort, retain_preferred_region};
+use crate::prefetch::PrefetchCache;
+use crate::view::artwork_picker::ArtworkPicker;
+use crate::view::letter_picker::{self, LetterPicker};
+use crate::view::navigation::Navigable;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EntryListState<S> {
     pub sort: S,
     pub selected: usize,
-    pub child: Option<Box<EntryListState<S>>>,
+    #[serde(default)]
+    pub expanded: HashSet<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -43,14 +63,39 @@ where
     rect: Rect,
     res: Resources,
     entries: Vec<Entry>,
+    /// Whether each entry is a section header, when the sort groups its folders into sections.
+    headers: Vec<bool>,
+    /// Folders whose sections are expanded, showing their entries below the header.
+    expanded: HashSet<PathBuf>,
     sort: S,
     list: ScrollList,
     image: Image,
     menu: Option<ScrollList>,
+    menu_title: Option<Label<String>>,
     menu_entries: Vec<MenuEntry>,
     core: Option<CoreSelection>,
+    /// Asks to confirm the menu entry in `confirming`, e.g. removing the selected game from
+    /// recents.
+    dialog: Option<Dialog>,
+    confirming: Option<MenuEntry>,
+    /// Enters a new file name for the selected game, opened from the menu.
+    keyboard: Option<Keyboard>,
+    /// Jumps to the first entry starting with a letter, opened with Select+Up or by holding L2.
+    letter_picker: Option<LetterPicker>,
+    /// Chooses the selected game's artwork, opened from the menu.
+    artwork_picker: Option<ArtworkPicker>,
+    /// Whether Select is held. The menu opens on release, unless Up opened the letter picker.
+    select_held: bool,
+    /// Whether games are being checked with A, for the menu to change them all at once. Toggled
+    /// with Start, as X opens the search.
+    selecting: bool,
     button_hints: Row<ButtonHint<String>>,
-    pub child: Option<Box<EntryList<S>>>,
+    /// Whether Y is being held to reorder entries.
+    reordering: bool,
+    /// Whether any entries were moved while reordering.
+    reordered: bool,
+    /// Folder opened from this list, to be pushed onto the navigation stack.
+    pushed: Option<Box<EntryList<S>>>,
 }
 
 impl<S> EntryList<S>
@@ -71,11 +116,11 @@ where
                 } else {
                     w - 12 - 12
                 },
-                h - 8 - ButtonIcon::diameter(&styles) - 8,
+                h - 8 - ButtonHint::bar_height(&styles),
             ),
             Vec::new(),
             Alignment::Left,
-            res.get::<Stylesheet>().ui_font.size + SELECTION_MARGIN,
+            styles.list_entry_height(),
         );
 
         let mut image = Image::empty(
@@ -83,7 +128,7 @@ where
                 x + w as i32 - styles.boxart_width as i32 - 24,
                 y + 8,
                 styles.boxart_width,
-                h - 8 - 8 - 8 - ButtonIcon::diameter(&styles) - 8,
+                h - 8 - 8 - 8 - ButtonHint::bar_height(&styles),
             ),
             ImageMode::Contain,
         );
@@ -95,7 +140,7 @@ where
                 x + w as i32 - 12,
                 y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
             ),
-            Vec::with_capacity(2),
+            Vec::with_capacity(3),
             Alignment::Right,
             12,
         );
@@ -118,6 +163,13 @@ where
                     Alignment::Right,
                 ))
             }
+            button_hints.push(ButtonHint::new(
+                res.clone(),
+                Point::zero(),
+                Key::Start,
+                locale.t("button-select-several"),
+                Alignment::Right,
+            ));
         }
 
         drop(styles);
@@ -126,14 +178,26 @@ where
             rect,
             res,
             entries: vec![],
+            headers: vec![],
+            expanded: HashSet::new(),
             sort,
             list,
             image,
             menu: None,
+            menu_title: None,
             menu_entries: vec![],
             core: None,
+            dialog: None,
+            confirming: None,
+            keyboard: None,
+            letter_picker: None,
+            artwork_picker: None,
+            select_held: false,
+            selecting: false,
             button_hints,
-            child: None,
+            reordering: false,
+            reordered: false,
+            pushed: None,
         };
 
         this.load_entries()?;
@@ -145,16 +209,17 @@ where
         EntryListState {
             sort: self.sort.clone(),
             selected: self.list.selected(),
-            child: self.child.as_ref().map(|c| Box::new(c.save())),
+            expanded: self.expanded.clone(),
         }
     }
 
     pub fn load(rect: Rect, res: Resources, state: EntryListState<S>) -> Result<Self> {
-        let mut this = Self::new(rect, res.clone(), state.sort)?;
-        this.select(state.selected);
-        if let Some(child) = state.child {
-            this.child = Some(Box::new(Self::load(rect, res, *child)?));
+        let mut this = Self::new(rect, res, state.sort)?;
+        if !state.expanded.is_empty() {
+            this.expanded = state.expanded;
+            this.load_entries()?;
         }
+        this.select(state.selected);
         Ok(this)
     }
 
@@ -163,16 +228,84 @@ where
         debug!("Selected entry: {:?}", self.entries.get(index));
     }
 
+    /// Selects the entry at the path, returning whether it's in the list.
+    pub fn select_path(&mut self, path: &Path) -> bool {
+        match self.entries.iter().position(|e| e.path() == path) {
+            Some(i) => {
+                self.select(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Starts or stops checking games to change them all at once.
+    fn set_selecting(&mut self, selecting: bool) {
+        self.selecting = selecting;
+        self.list.set_checkboxes(selecting);
+        let (select, select_several) = if selecting {
+            ("button-check", "button-done")
+        } else {
+            ("button-select", "button-select-several")
+        };
+        let locale = self.res.get::<Locale>();
+        self.button_hints
+            .get_mut(0)
+            .unwrap()
+            .set_text(locale.t(select));
+        let last = self.button_hints.len() - 1;
+        self.button_hints
+            .get_mut(last)
+            .unwrap()
+            .set_text(locale.t(select_several));
+    }
+
+    /// Checks or unchecks the selected game.
+    fn toggle_checked(&mut self) {
+        let selected = self.list.selected();
+        if let Some(Entry::Game(_)) = self.entries.get(selected) {
+            self.list
+                .set_checked(selected, !self.list.is_checked(selected));
+        }
+    }
+
+    /// Indices of the games that the menu changes: the checked games while selecting, otherwise
+    /// the selected game.
+    fn targets(&self) -> Vec<usize> {
+        let indices = if self.selecting {
+            self.list.checked()
+        } else {
+            vec![self.list.selected()]
+        };
+        indices
+            .into_iter()
+            .filter(|i| matches!(self.entries.get(*i), Some(Entry::Game(_))))
+            .collect()
+    }
+
     async fn select_entry(&mut self, commands: Sender<Command>) -> Result<()> {
+        let unknown_console = match self.entries.get(self.list.selected()) {
+            Some(Entry::Game(game)) => self
+                .res
+                .get::<ConsoleMapper>()
+                .get_console(&game.path)
+                .is_none(),
+            _ => false,
+        };
+        if unknown_console {
+            self.open_console_picker();
+            return Ok(());
+        }
+
         if let Some(entry) = self.entries.get_mut(self.list.selected()) {
             match entry {
                 Entry::Directory(dir) => {
                     let child = EntryList::new(
                         self.rect,
                         self.res.clone(),
-                        self.sort.with_directory(dir.clone()),
+                        self.sort.open_directory(&self.res.get(), dir.clone()),
                     )?;
-                    self.child = Some(Box::new(child));
+                    self.pushed = Some(Box::new(child));
                 }
                 Entry::Game(game) => {
                     let command = self.res.get::<ConsoleMapper>().launch_game(
@@ -187,6 +320,7 @@ where
                 Entry::App(app) => {
                     commands.send(app.command()).await?;
                 }
+                Entry::Header(_) => {}
             }
         }
         Ok(())
@@ -204,37 +338,241 @@ where
         Ok(())
     }
 
+    /// Switches to the next sort, remembering it for the folder.
+    fn next_sort(&mut self) -> Result<()> {
+        let next = self.sort.next(&self.res.get());
+        self.sort(next)?;
+        self.sort.save_folder_sort(&self.res.get())
+    }
+
+    /// Lists the entries of the sort, taking them from the prefetch cache if they're there.
+    fn fetch_entries(&self, sort: &S) -> Result<Vec<Entry>> {
+        let prefetched = sort
+            .prefetch_dir()
+            .and_then(|dir| self.res.get::<PrefetchCache>().take(dir));
+        let mut entries = match prefetched {
+            Some(entries) => entries,
+            None => sort.entries(&self.res.get(), &self.res.get(), &self.res.get())?,
+        };
+        let styles = self.res.get::<Stylesheet>();
+        if sort.prefer_region()
+            && let Some(region) = styles.preferred_region
+        {
+            retain_preferred_region(&mut entries, region);
+        }
+        if sort.group_favorites() && styles.group_favorites {
+            // Stable sort keeps directories and apps first, and the existing order within each group
+            entries.sort_by_key(|e| match e {
+                Entry::Game(game) if game.favorite => 1,
+                Entry::Game(_) => 2,
+                _ => 0,
+            });
+        }
+        Ok(entries)
+    }
+
     fn load_entries(&mut self) -> Result<()> {
-        self.entries = self
-            .sort
-            .entries(&self.res.get(), &self.res.get(), &self.res.get())?;
+        self.entries = self.fetch_entries(&self.sort)?;
+        self.headers = self
+            .entries
+            .iter()
+            .map(|entry| matches!(entry, Entry::Header(_)))
+            .collect();
+        if self.sort.groups_directories() {
+            self.group_entries()?;
+        }
+
+        let tag_display = self.res.get::<Stylesheet>().tag_display;
         self.list.set_items(
             self.entries
                 .iter()
-                .map(|e| match e {
-                    Entry::Game(game) => {
-                        format!("{}{}", if game.favorite { "♥ " } else { "" }, e.name())
-                    }
-                    _ => e.name().to_string(),
-                })
+                .map(|entry| entry_label(entry, tag_display))
                 .collect(),
             self.sort.preserve_selection(),
         );
+        // Games on a removed storage device are kept, but can't be launched until it's back
+        for (i, entry) in self.entries.iter().enumerate() {
+            if !storage::is_available(entry.path()) {
+                self.list.set_disabled(i, true);
+            }
+        }
+        // Expanded headers can't be selected, so that the selection moves between their entries
+        for i in 0..self.entries.len() {
+            if self.headers[i] && self.headers.get(i + 1) == Some(&false) {
+                self.list.set_header(i, true);
+            }
+        }
+        self.set_console_styles();
 
         Ok(())
     }
 
-    fn open_menu(&mut self) -> Result<()> {
-        let Rect { x, y, w, h } = self.rect;
+    /// Shows the icon and accent color of each console's folders.
+    fn set_console_styles(&mut self) {
         let styles = self.res.get::<Stylesheet>();
-        let locale = self.res.get::<Locale>();
+        let console_mapper = self.res.get::<ConsoleMapper>();
+        let console_styles: Vec<ConsoleStyle> = self
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::Directory(dir) => console_mapper
+                    .get_console_by_dir(&dir.path)
+                    .map(|console| console.style(&styles))
+                    .unwrap_or_default(),
+                _ => ConsoleStyle::default(),
+            })
+            .collect();
+        drop(console_mapper);
+        drop(styles);
+
+        for (i, style) in console_styles.iter().enumerate() {
+            if style.color.is_some() {
+                self.list.set_color(i, style.color);
+            }
+        }
+        if console_styles.iter().any(|style| style.icon.is_some()) {
+            self.list
+                .set_icons(console_styles.into_iter().map(|style| style.icon).collect());
+        }
+    }
+
+    /// Turns each folder into a section header, followed by the folder's entries if its section
+    /// is expanded. Entries that aren't folders are listed before the sections.
+    fn group_entries(&mut self) -> Result<()> {
+        let mut entries = Vec::with_capacity(self.entries.len());
+        let mut headers = Vec::with_capacity(self.entries.len());
+        let mut sections = Vec::new();
+        for entry in mem::take(&mut self.entries) {
+            match entry {
+                Entry::Directory(ref dir) => {
+                    let children = if self.expanded.contains(&dir.path) {
+                        let sort = self.sort.open_directory(&self.res.get(), dir.clone());
+                        self.fetch_entries(&sort)?
+                    } else {
+                        Vec::new()
+                    };
+                    sections.push(entry);
+                    headers.push(true);
+                    headers.extend(std::iter::repeat_n(false, children.len()));
+                    sections.extend(children);
+                }
+                _ => entries.push(entry),
+            }
+        }
+        let mut loose = vec![false; entries.len()];
+        loose.append(&mut headers);
+        entries.append(&mut sections);
+        self.entries = entries;
+        self.headers = loose;
+        Ok(())
+    }
+
+    /// Index of the header of the folder section that the entry is listed in, if any.
+    fn section_of(&self, index: usize) -> Option<usize> {
+        if self.headers.get(index).is_none_or(|header| *header) {
+            return None;
+        }
+        (0..index)
+            .rev()
+            .find(|&i| self.headers[i])
+            .filter(|&i| matches!(self.entries[i], Entry::Directory(_)))
+    }
+
+    /// Expands or collapses the section with its header at the index. The first entry of an
+    /// expanded section is selected, or the header of a collapsed one.
+    fn toggle_section(&mut self, index: usize) -> Result<()> {
+        let path = self.entries[index].path().to_path_buf();
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path.clone());
+        }
+        self.load_entries()?;
+
+        if let Some(i) = self.entries.iter().position(|e| e.path() == path) {
+            let has_entries = self.headers.get(i + 1) == Some(&false);
+            if self.expanded.contains(&path) && has_entries {
+                self.select(i + 1);
+            } else {
+                self.select(i);
+            }
+        }
+        Ok(())
+    }
+
+    fn open_letter_picker(&mut self) {
+        let Some(current) = self.entries.get(self.list.selected()) else {
+            return;
+        };
+        self.letter_picker = Some(LetterPicker::new(
+            self.rect,
+            self.entries.iter().map(Entry::name),
+            current.name(),
+        ));
+    }
+
+    /// Selects the first entry listed under the letter in the letter picker.
+    fn select_letter(&mut self, letter: char) {
+        if let Some(i) = self
+            .entries
+            .iter()
+            .position(|e| letter_picker::letter(e.name()) == letter)
+        {
+            self.select(i);
+        }
+    }
+
+    /// Moves the selected entry up or down by one, swapping it with its neighbour.
+    fn move_selected(&mut self, up: bool) {
+        let selected = self.list.selected();
+        let other = if up {
+            match selected.checked_sub(1) {
+                Some(other) => other,
+                None => return,
+            }
+        } else if selected + 1 < self.entries.len() {
+            selected + 1
+        } else {
+            return;
+        };
+
+        self.entries.swap(selected, other);
+        let tag_display = self.res.get::<Stylesheet>().tag_display;
+        self.list
+            .set_item(selected, entry_label(&self.entries[selected], tag_display));
+        self.list
+            .set_item(other, entry_label(&self.entries[other], tag_display));
+        self.list.select(other);
+        self.reordered = true;
+    }
+
+    /// Persists the current order of the entries as the favorites order.
+    fn save_order(&self) -> Result<()> {
+        let paths = self
+            .entries
+            .iter()
+            .filter_map(|e| match e {
+                Entry::Game(game) => Some(game.path.as_path()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        self.res.get::<Database>().set_favorite_order(&paths)
+    }
+
+    fn open_menu(&mut self) -> Result<()> {
+        if self.selecting {
+            self.open_selection_menu();
+            return Ok(());
+        }
 
         let entry = self.entries.get(self.list.selected()).unwrap();
         let entries = match entry {
             Entry::Game(game) => {
+                let queued = self.res.get::<Database>().is_queued(&game.path)?;
+                let cpu_profile = self.res.get::<Database>().get_cpu_profile(&game.path)?;
                 let mut entries = vec![
                     MenuEntry::Favorite(game.favorite),
+                    MenuEntry::UpNext(queued),
                     MenuEntry::Launch(None),
+                    MenuEntry::CpuProfile(cpu_profile),
                     MenuEntry::Reset,
                     MenuEntry::RemoveFromRecents,
                     MenuEntry::RepopulateDatabase,
@@ -251,7 +589,7 @@ where
                     let core = game.core.to_owned().unwrap_or_else(|| cores[0].clone());
                     let i = cores.iter().position(|c| c == &core).unwrap_or_default();
 
-                    if let MenuEntry::Launch(ref mut launch_core) = entries[1] {
+                    if let MenuEntry::Launch(ref mut launch_core) = entries[2] {
                         let console_mapper = self.res.get::<ConsoleMapper>();
                         *launch_core = Some(console_mapper.get_core_name(&core));
                     }
@@ -261,36 +599,270 @@ where
                     self.core = None;
                 }
 
+                if let Some(manual) =
+                    find_manual(&game.path).filter(|manual| PdfViewer::can_open(manual))
+                {
+                    entries.push(MenuEntry::Manual(manual));
+                }
+
+                entries.push(MenuEntry::SetArtwork);
+
+                if self.sort.can_show_in_folder() {
+                    entries.push(MenuEntry::ShowInFolder);
+                }
+
+                entries.push(startup_entry(StartupBehavior::Game(game.path.clone()))?);
+
+                if game.path.exists() {
+                    entries.extend([MenuEntry::Rename, MenuEntry::Move, MenuEntry::Delete]);
+                }
+
                 entries
             }
+            Entry::Header(_) => return Ok(()),
             Entry::App(_) | Entry::Directory(_) => {
-                vec![
+                let mut entries = vec![
                     MenuEntry::Launch(None),
                     MenuEntry::Reset,
                     MenuEntry::RemoveFromRecents,
                     MenuEntry::RepopulateDatabase,
-                ]
+                ];
+                if let Entry::Directory(dir) = entry
+                    && dir.path.starts_with(ALLIUM_GAMES_DIR.as_path())
+                {
+                    entries.push(startup_entry(StartupBehavior::Folder(dir.path.clone()))?);
+                }
+                entries
             }
         };
 
-        let height = entries.len() as u32 * (styles.ui_font.size + SELECTION_MARGIN);
+        self.show_menu(entries, None);
+
+        Ok(())
+    }
+
+    /// Opens the menu for the checked games, which only has the entries that apply to all of them.
+    fn open_selection_menu(&mut self) {
+        let games: Vec<&Game> = self
+            .targets()
+            .into_iter()
+            .filter_map(|i| match &self.entries[i] {
+                Entry::Game(game) => Some(game),
+                _ => None,
+            })
+            .collect();
+        if games.is_empty() {
+            return;
+        }
+
+        let mut entries = vec![
+            MenuEntry::Favorite(games.iter().all(|game| game.favorite)),
+            MenuEntry::Hide,
+        ];
+        if games.iter().all(|game| game.path.exists()) {
+            // Games are only moved together within one folder, as the folders to move to depend
+            // on the game's console
+            if games
+                .iter()
+                .all(|game| game.path.parent() == games[0].path.parent())
+            {
+                entries.push(MenuEntry::Move);
+            }
+            entries.push(MenuEntry::Delete);
+        }
+
+        self.core = None;
+        let mut map = HashMap::new();
+        map.insert("count".into(), games.len().into());
+        let title = self.res.get::<Locale>().ta("menu-selected-games", &map);
+        self.show_menu(entries, Some(title));
+    }
+
+    /// Asks which console the selected game is for, when it can't be detected. The console
+    /// guessed from the other files in its folder is listed first. The choice is remembered for
+    /// the game's folder.
+    fn open_console_picker(&mut self) {
+        let console_mapper = self.res.get::<ConsoleMapper>();
+        let mut names = console_mapper
+            .consoles()
+            .iter()
+            .map(|console| console.name.clone())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+
+        let suggestion = match self.entries.get(self.list.selected()) {
+            Some(Entry::Game(game)) => game
+                .path
+                .parent()
+                .and_then(|dir| console_mapper.suggest_console(dir))
+                .map(|console| console.name.clone()),
+            _ => None,
+        };
+        drop(console_mapper);
+
+        let locale = self.res.get::<Locale>();
+        let title = match suggestion {
+            Some(suggestion) => {
+                names.retain(|name| *name != suggestion);
+                let mut map = HashMap::new();
+                map.insert("console".into(), suggestion.clone().into());
+                names.insert(0, suggestion);
+                locale.ta("menu-pick-console-suggested", &map)
+            }
+            None => locale.t("menu-pick-console"),
+        };
+        drop(locale);
+
+        self.core = None;
+        let entries = names.into_iter().map(MenuEntry::Console).collect();
+        self.show_menu(entries, Some(title));
+    }
+
+    /// Removes the selected game from recents, deleting it from the database if it no longer
+    /// exists.
+    async fn remove_from_recents(&mut self, commands: &Sender<Command>) -> Result<()> {
+        if let Some(Entry::Game(game)) = self.entries.get(self.list.selected()) {
+            let database = self.res.get::<Database>();
+            database.push_undo(UndoAction::RemoveFromRecents, &[&game.path])?;
+            if game.path.exists() {
+                database.reset_game(&game.path)?;
+            } else {
+                database.delete_game(&game.path)?;
+            }
+            drop(database);
+            self.load_entries()?;
+            commands
+                .send(Command::Undoable(UndoAction::RemoveFromRecents))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the targeted games, moving their files to the trash so that it can be undone.
+    async fn delete(&mut self, commands: &Sender<Command>) -> Result<()> {
+        let paths: Vec<PathBuf> = self
+            .targets()
+            .into_iter()
+            .map(|i| self.entries[i].path().to_path_buf())
+            .collect();
+        let undo_id = self.res.get::<Database>().push_undo(
+            UndoAction::Delete,
+            &paths.iter().map(PathBuf::as_path).collect::<Vec<_>>(),
+        )?;
+        let deleted = self
+            .file_operation(commands, |database, game, core| {
+                rom_files::trash(database, undo_id, game, core).map(|()| None)
+            })
+            .await?;
+        if deleted > 0 {
+            commands.send(Command::Undoable(UndoAction::Delete)).await?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the selected game's image with one chosen in the artwork picker. The choice is
+    /// kept in the database, so it's shown everywhere the game is listed.
+    fn set_artwork(&mut self, image: PathBuf) -> Result<()> {
+        if let Some(Entry::Game(game)) = self.entries.get_mut(self.list.selected()) {
+            self.res
+                .get::<Database>()
+                .set_custom_image(&game.path, &image)?;
+            game.image = LazyImage::Found(image);
+            // Prefetched folders would still show the previous image
+            self.res.get::<PrefetchCache>().clear();
+            self.image.set_should_draw();
+        }
+        Ok(())
+    }
+
+    /// Runs a file operation on each of the targeted games, then selects the game at the last
+    /// path it returns. A failed operation leaves that game's files as they were, stops the
+    /// remaining games from being changed, and is shown as a toast. Returns how many games were
+    /// changed. The operation is also given the core that runs the game.
+    async fn file_operation(
+        &mut self,
+        commands: &Sender<Command>,
+        mut operation: impl FnMut(&Database, &mut Game, Option<&str>) -> Result<Option<PathBuf>>,
+    ) -> Result<usize> {
+        let mut changed = 0;
+        let mut selected = None;
+        let mut result = Ok(());
+        for i in self.targets() {
+            let Some(Entry::Game(game)) = self.entries.get_mut(i) else {
+                continue;
+            };
+            let core = self
+                .res
+                .get::<ConsoleMapper>()
+                .game_core(&game.path, game.core.as_ref())
+                .cloned();
+            match operation(&self.res.get::<Database>(), game, core.as_deref()) {
+                Ok(path) => {
+                    changed += 1;
+                    selected = path.or(selected);
+                }
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
 
+        self.res.get::<PrefetchCache>().clear();
+        self.set_selecting(false);
+        self.load_entries()?;
+        if let Some(path) = selected {
+            self.select_path(&path);
+        }
+        if let Err(e) = result {
+            warn!("failed to change game files: {:#}", e);
+            let text = self.res.get::<Locale>().t("file-operation-failed");
+            commands
+                .send(Command::Toast(
+                    text,
+                    Some(std::time::Duration::from_secs(3)),
+                ))
+                .await?;
+        }
+        commands.send(Command::Redraw).await?;
+        Ok(changed)
+    }
+
+    fn show_menu(&mut self, entries: Vec<MenuEntry>, title: Option<String>) {
+        let Rect { x, y, w, h } = self.rect;
+        let styles = self.res.get::<Stylesheet>();
+        let locale = self.res.get::<Locale>();
+
+        let entry_height = styles.list_entry_height();
+        let title_height = if title.is_some() { entry_height } else { 0 };
+        // Long menus scroll instead of overflowing the screen
+        let height = (entries.len() as u32 * entry_height).min(h - 48 - title_height);
+        let top = (y + h as i32 - height as i32 - title_height as i32) / 2;
+
+        let menu_rect = Rect::new(
+            x + 12 + (w as i32 - 24) / 6,
+            top + title_height as i32,
+            (w - 24) * 2 / 3,
+            height,
+        );
         let mut menu = ScrollList::new(
-            Rect::new(
-                x + 12 + (w as i32 - 24) / 6,
-                (y + h as i32 - height as i32) / 2,
-                (w - 24) * 2 / 3,
-                height,
-            ),
+            menu_rect,
             entries.iter().map(|e| e.text(&locale)).collect(),
             Alignment::Left,
-            styles.ui_font.size + SELECTION_MARGIN,
+            entry_height,
         );
         menu.set_background_color(Some(StylesheetColor::BackgroundHighlightBlend));
         self.menu = Some(menu);
+        self.menu_title = title.map(|title| {
+            Label::new(
+                Point::new(menu_rect.x + 12, top + 4),
+                title,
+                Alignment::Left,
+                Some(menu_rect.w - 24),
+            )
+        });
         self.menu_entries = entries;
-
-        Ok(())
     }
 }
 
@@ -304,15 +876,19 @@ where
         display: &mut <DefaultPlatform as Platform>::Display,
         styles: &Stylesheet,
     ) -> Result<bool> {
-        if let Some(child) = &mut self.child {
-            return child.draw(display, styles);
-        }
-
         let mut drawn = false;
 
+        if let Some(picker) = self.artwork_picker.as_mut() {
+            return Ok(picker.should_draw() && picker.draw(display, styles)?);
+        }
+
         if let Some(menu) = &mut self.menu {
             if menu.should_draw() {
                 let mut rect = menu.bounding_box(styles);
+                if self.menu_title.is_some() {
+                    rect.y -= styles.list_entry_height() as i32;
+                    rect.h += styles.list_entry_height();
+                }
                 rect.y -= 12;
                 rect.h += 24;
                 rect.x -= 24;
@@ -328,6 +904,10 @@ where
                 .draw(display)?;
                 menu.set_should_draw();
                 menu.draw(display, styles)?;
+                if let Some(title) = self.menu_title.as_mut() {
+                    title.set_should_draw();
+                    title.draw(display, styles)?;
+                }
                 drawn = true;
             }
             return Ok(drawn);
@@ -335,7 +915,9 @@ where
 
         drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
 
-        if styles.boxart_width > 0 {
+        // Box art isn't loaded while the battery saver is on
+        let battery_saver = self.res.get::<DeviceStatus>().battery_saver;
+        if styles.boxart_width > 0 && !battery_saver {
             if let Some(entry) = self.entries.get_mut(self.list.selected()) {
                 if let Some(path) = entry.image() {
                     trace!("Loading image from {:?}", path);
@@ -365,33 +947,74 @@ where
             }
         }
 
+        if let Some(dialog) = self.dialog.as_mut() {
+            // Keep the dialog on top of anything drawn underneath it
+            if drawn {
+                dialog.set_should_draw();
+            }
+            drawn |= dialog.should_draw() && dialog.draw(display, styles)?;
+        }
+
+        if let Some(picker) = self.letter_picker.as_mut() {
+            if drawn {
+                picker.set_should_draw();
+            }
+            drawn |= picker.should_draw() && picker.draw(display, styles)?;
+        }
+
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            if drawn {
+                keyboard.set_should_draw();
+            }
+            drawn |= keyboard.should_draw() && keyboard.draw(display, styles)?;
+        }
+
         Ok(drawn)
     }
 
     fn should_draw(&self) -> bool {
-        if let Some(child) = self.child.as_ref() {
-            child.should_draw()
-        } else {
-            self.menu
+        if let Some(picker) = self.artwork_picker.as_ref() {
+            return picker.should_draw();
+        }
+        self.menu
+            .as_ref()
+            .is_some_and(common::view::View::should_draw)
+            || self
+                .dialog
                 .as_ref()
                 .is_some_and(common::view::View::should_draw)
-                || self.list.should_draw()
-                || self.image.should_draw()
-                || self.button_hints.should_draw()
-        }
+            || self
+                .letter_picker
+                .as_ref()
+                .is_some_and(common::view::View::should_draw)
+            || self
+                .keyboard
+                .as_ref()
+                .is_some_and(common::view::View::should_draw)
+            || self.list.should_draw()
+            || self.image.should_draw()
+            || self.button_hints.should_draw()
     }
 
     fn set_should_draw(&mut self) {
-        if let Some(child) = self.child.as_mut() {
-            child.set_should_draw();
-        } else {
-            if let Some(menu) = self.menu.as_mut() {
-                menu.set_should_draw();
-            }
-            self.list.set_should_draw();
-            self.image.set_should_draw();
-            self.button_hints.set_should_draw();
+        if let Some(menu) = self.menu.as_mut() {
+            menu.set_should_draw();
+        }
+        if let Some(dialog) = self.dialog.as_mut() {
+            dialog.set_should_draw();
+        }
+        if let Some(picker) = self.letter_picker.as_mut() {
+            picker.set_should_draw();
+        }
+        if let Some(picker) = self.artwork_picker.as_mut() {
+            picker.set_should_draw();
         }
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            keyboard.set_should_draw();
+        }
+        self.list.set_should_draw();
+        self.image.set_should_draw();
+        self.button_hints.set_should_draw();
     }
 
     async fn handle_key_event(
@@ -400,26 +1023,105 @@ where
         commands: Sender<Command>,
         bubble: &mut VecDeque<Command>,
     ) -> Result<bool> {
-        if let Some(child) = self.child.as_mut() {
-            match child.handle_key_event(event, commands, bubble).await? {
-                true => {
-                    bubble.retain_mut(|c| match c {
-                        Command::CloseView => {
-                            self.child = None;
-                            self.set_should_draw();
-                            false
+        if let Some(dialog) = self.dialog.as_mut() {
+            dialog
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?;
+            while let Some(command) = bubble.pop_front() {
+                match command {
+                    Command::ValueChanged(_, _) => {
+                        if matches!(self.confirming, Some(MenuEntry::Delete)) {
+                            self.delete(&commands).await?;
+                        } else {
+                            self.remove_from_recents(&commands).await?;
                         }
-                        _ => true,
-                    });
-                    Ok(true)
+                    }
+                    Command::CloseView => {
+                        self.dialog = None;
+                        self.confirming = None;
+                        commands.send(Command::Redraw).await?;
+                    }
+                    _ => {}
                 }
-                false => Ok(false),
             }
-        } else if let Some(menu) = self.menu.as_mut() {
+            return Ok(true);
+        }
+
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            keyboard
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?;
+            while let Some(command) = bubble.pop_front() {
+                match command {
+                    Command::ValueChanged(_, Value::String(name)) => {
+                        self.file_operation(&commands, |database, game, core| {
+                            rom_files::rename(database, game, &name, core).map(Some)
+                        })
+                        .await?;
+                    }
+                    Command::CloseView => {
+                        self.keyboard = None;
+                        commands.send(Command::Redraw).await?;
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(true);
+        }
+
+        if let Some(picker) = self.artwork_picker.as_mut() {
+            picker
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?;
+            while let Some(command) = bubble.pop_front() {
+                match command {
+                    Command::ValueChanged(_, Value::String(image)) => {
+                        self.set_artwork(PathBuf::from(image))?;
+                    }
+                    Command::CloseView => {
+                        self.artwork_picker = None;
+                        commands.send(Command::Redraw).await?;
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(true);
+        }
+
+        if let Some(picker) = self.letter_picker.as_mut() {
+            picker
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?;
+            while let Some(command) = bubble.pop_front() {
+                match command {
+                    Command::ValueChanged(_, Value::String(letter)) => {
+                        if let Some(letter) = letter.chars().next() {
+                            self.select_letter(letter);
+                        }
+                    }
+                    Command::CloseView => {
+                        self.letter_picker = None;
+                        commands.send(Command::Redraw).await?;
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(true);
+        }
+
+        if let Some(menu) = self.menu.as_mut() {
             match event {
                 KeyEvent::Pressed(Key::Left) => {
-                    if let Some(core) = self.core.as_mut() {
-                        let selected = &mut self.menu_entries[menu.selected()];
+                    let selected = &mut self.menu_entries[menu.selected()];
+                    if let MenuEntry::CpuProfile(profile) = selected {
+                        *profile = cycle_cpu_profile(*profile, false);
+                        if let Some(Entry::Game(game)) = self.entries.get(self.list.selected()) {
+                            self.res
+                                .get::<Database>()
+                                .set_cpu_profile(&game.path, *profile)?;
+                        }
+                        menu.set_item(menu.selected(), selected.text(&self.res.get()));
+                    } else if let Some(core) = self.core.as_mut() {
                         if let MenuEntry::Launch(launch_core) = selected {
                             core.core = core.core.saturating_sub(1);
                             let console_mapper = self.res.get::<ConsoleMapper>();
@@ -431,8 +1133,16 @@ where
                     Ok(true) // trap tab focus
                 }
                 KeyEvent::Pressed(Key::Right) => {
-                    if let Some(core) = self.core.as_mut() {
-                        let selected = &mut self.menu_entries[menu.selected()];
+                    let selected = &mut self.menu_entries[menu.selected()];
+                    if let MenuEntry::CpuProfile(profile) = selected {
+                        *profile = cycle_cpu_profile(*profile, true);
+                        if let Some(Entry::Game(game)) = self.entries.get(self.list.selected()) {
+                            self.res
+                                .get::<Database>()
+                                .set_cpu_profile(&game.path, *profile)?;
+                        }
+                        menu.set_item(menu.selected(), selected.text(&self.res.get()));
+                    } else if let Some(core) = self.core.as_mut() {
                         if let MenuEntry::Launch(launch_core) = selected {
                             core.core = (core.core + 1).min(core.cores.len() - 1);
                             let console_mapper = self.res.get::<ConsoleMapper>();
@@ -445,27 +1155,63 @@ where
                 }
                 KeyEvent::Pressed(Key::Select | Key::B) => {
                     self.menu = None;
+                    self.menu_title = None;
                     commands.send(Command::Redraw).await?;
                     Ok(true)
                 }
                 KeyEvent::Pressed(Key::A) => {
                     let selected = &self.menu_entries[menu.selected()];
+                    // Cleared first, as launching may open the console picker
+                    self.menu = None;
+                    self.menu_title = None;
                     match selected {
-                        MenuEntry::Favorite(_) => {
-                            let entry = self.entries.get_mut(self.list.selected()).unwrap();
-                            if let Entry::Game(game) = entry {
-                                game.favorite = !game.favorite;
-                                self.res
-                                    .get::<Database>()
-                                    .set_favorite(&game.path, game.favorite)?;
-                                self.list.set_item(
-                                    self.list.selected(),
-                                    format!(
-                                        "{}{}",
-                                        if game.favorite { "♥ " } else { "" },
-                                        entry.name()
-                                    ),
-                                );
+                        MenuEntry::Favorite(is_favorite) => {
+                            let favorite = !*is_favorite;
+                            let tag_display = self.res.get::<Stylesheet>().tag_display;
+                            for i in self.targets() {
+                                let entry = &mut self.entries[i];
+                                if let Entry::Game(game) = entry {
+                                    game.favorite = favorite;
+                                    self.res
+                                        .get::<Database>()
+                                        .set_favorite(&game.path, game.favorite)?;
+                                    self.list.set_item(i, entry_label(entry, tag_display));
+                                }
+                            }
+                            commands.send(Command::Redraw).await?;
+                        }
+                        MenuEntry::Hide => {
+                            let paths: Vec<&Path> = self
+                                .targets()
+                                .into_iter()
+                                .map(|i| self.entries[i].path())
+                                .collect();
+                            let database = self.res.get::<Database>();
+                            database.push_undo(UndoAction::Hide, &paths)?;
+                            for path in paths {
+                                database.set_hidden(path, true)?;
+                            }
+                            drop(database);
+                            self.res.get::<PrefetchCache>().clear();
+                            self.set_selecting(false);
+                            self.load_entries()?;
+                            commands.send(Command::Undoable(UndoAction::Hide)).await?;
+                            commands.send(Command::Redraw).await?;
+                        }
+                        MenuEntry::UpNext(queued) => {
+                            let queued = *queued;
+                            if let Some(Entry::Game(game)) = self.entries.get(self.list.selected())
+                            {
+                                let db = self.res.get::<Database>();
+                                if queued {
+                                    // Marking a game as beaten advances the queue
+                                    db.remove_from_queue(&game.path)?;
+                                } else {
+                                    db.add_to_queue(&game.path)?;
+                                }
+                            }
+                            if queued {
+                                self.load_entries()?;
                             }
                             commands.send(Command::Redraw).await?;
                         }
@@ -480,10 +1226,14 @@ where
                             self.core = None;
                             self.select_entry(commands).await?;
                         }
+                        MenuEntry::CpuProfile(_) => {
+                            // Changed with left/right, which saves it immediately
+                            commands.send(Command::Redraw).await?;
+                        }
                         MenuEntry::Reset => {
                             let entry = self.entries.get_mut(self.list.selected()).unwrap();
                             match entry {
-                                Entry::Directory(_) | Entry::App(_) => {}
+                                Entry::Directory(_) | Entry::App(_) | Entry::Header(_) => {}
                                 Entry::Game(game) => {
                                     let command = self.res.get::<ConsoleMapper>().launch_game(
                                         &self.res.get(),
@@ -500,14 +1250,21 @@ where
                         MenuEntry::RemoveFromRecents => {
                             if let Some(Entry::Game(game)) = self.entries.get(self.list.selected())
                             {
-                                if game.path.exists() {
-                                    self.res.get::<Database>().reset_game(&game.path)?;
-                                } else {
-                                    self.res.get::<Database>().delete_game(&game.path)?;
-                                }
-                                self.load_entries()?;
-                                commands.send(Command::Redraw).await?;
+                                let locale = self.res.get::<Locale>();
+                                let mut map = HashMap::new();
+                                map.insert("name".into(), game.name.clone().into());
+                                let title = locale.ta("remove-from-recents-title", &map);
+                                let message = locale.t("remove-from-recents-message");
+                                drop(locale);
+                                self.dialog = Some(Dialog::confirm(
+                                    self.rect,
+                                    self.res.clone(),
+                                    title,
+                                    Some(message),
+                                ));
+                                self.confirming = Some(MenuEntry::RemoveFromRecents);
                             }
+                            commands.send(Command::Redraw).await?;
                         }
                         MenuEntry::RepopulateDatabase => {
                             commands.send(Command::Redraw).await?;
@@ -528,12 +1285,144 @@ where
                             }
                             commands.send(Command::Redraw).await?;
                         }
+                        MenuEntry::Manual(path) => {
+                            commands.send(Command::ViewManual(path.clone())).await?;
+                        }
+                        MenuEntry::Startup(behavior, is_startup) => {
+                            let mut settings = LauncherSettings::load()?;
+                            settings.startup = if *is_startup {
+                                StartupBehavior::LastView
+                            } else {
+                                behavior.clone()
+                            };
+                            settings.save()?;
+                            commands.send(Command::Redraw).await?;
+                        }
+                        MenuEntry::SetArtwork => {
+                            if let Some(Entry::Game(game)) = self.entries.get(self.list.selected())
+                            {
+                                self.artwork_picker = Some(ArtworkPicker::new(
+                                    self.rect,
+                                    self.res.clone(),
+                                    &game.path,
+                                ));
+                            }
+                            commands.send(Command::Redraw).await?;
+                        }
+                        MenuEntry::ShowInFolder => {
+                            if let Some(Entry::Game(game)) = self.entries.get(self.list.selected())
+                            {
+                                commands
+                                    .send(Command::ShowInFolder(game.path.clone()))
+                                    .await?;
+                            }
+                        }
+                        MenuEntry::Rename => {
+                            if let Some(Entry::Game(game)) = self.entries.get(self.list.selected())
+                            {
+                                let name = game
+                                    .path
+                                    .file_stem()
+                                    .map(|stem| stem.to_string_lossy().into_owned())
+                                    .unwrap_or_default();
+                                self.keyboard = Some(Keyboard::new(self.res.clone(), name, false));
+                            }
+                            commands.send(Command::Redraw).await?;
+                        }
+                        MenuEntry::Move => {
+                            if let Some(&i) = self.targets().first()
+                                && let Entry::Game(game) = &self.entries[i]
+                            {
+                                let targets = rom_files::move_targets(&game.path);
+                                let locale = self.res.get::<Locale>();
+                                if targets.is_empty() {
+                                    commands
+                                        .send(Command::Toast(
+                                            locale.t("move-no-folders"),
+                                            Some(std::time::Duration::from_secs(3)),
+                                        ))
+                                        .await?;
+                                } else {
+                                    let title = locale.t("menu-move-to");
+                                    drop(locale);
+                                    self.show_menu(
+                                        targets.into_iter().map(MenuEntry::MoveTo).collect(),
+                                        Some(title),
+                                    );
+                                }
+                            }
+                            commands.send(Command::Redraw).await?;
+                        }
+                        MenuEntry::MoveTo(dir) => {
+                            let dir = dir.clone();
+                            self.file_operation(&commands, |database, game, _| {
+                                rom_files::move_to(database, game, &dir).map(Some)
+                            })
+                            .await?;
+                        }
+                        MenuEntry::Delete => {
+                            let targets = self.targets();
+                            if let Some(&i) = targets.first() {
+                                let locale = self.res.get::<Locale>();
+                                let mut map = HashMap::new();
+                                let title = if targets.len() == 1 {
+                                    map.insert("name".into(), self.entries[i].name().into());
+                                    locale.ta("delete-game-title", &map)
+                                } else {
+                                    map.insert("count".into(), targets.len().into());
+                                    locale.ta("delete-games-title", &map)
+                                };
+                                let message = locale.t("delete-game-message");
+                                drop(locale);
+                                self.dialog = Some(Dialog::confirm(
+                                    self.rect,
+                                    self.res.clone(),
+                                    title,
+                                    Some(message),
+                                ));
+                                self.confirming = Some(MenuEntry::Delete);
+                            }
+                            commands.send(Command::Redraw).await?;
+                        }
+                        MenuEntry::Console(console) => {
+                            let console = console.clone();
+                            if let Some(Entry::Game(game)) = self.entries.get(self.list.selected())
+                                && let Some(folder) = game.path.parent().and_then(Path::file_name)
+                            {
+                                let folder = folder.to_string_lossy().to_string();
+                                let mut console_mapper = self.res.get::<ConsoleMapper>().clone();
+                                console_mapper.set_alias(folder, console)?;
+                                self.res.insert(console_mapper);
+                            }
+                            commands.send(Command::Redraw).await?;
+                            self.select_entry(commands).await?;
+                        }
                     }
-                    self.menu = None;
                     Ok(true)
                 }
                 _ => menu.handle_key_event(event, commands, bubble).await,
             }
+        } else if self.reordering {
+            match event {
+                KeyEvent::Pressed(Key::Up) | KeyEvent::Autorepeat(Key::Up) => {
+                    self.move_selected(true);
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::Down) | KeyEvent::Autorepeat(Key::Down) => {
+                    self.move_selected(false);
+                    Ok(true)
+                }
+                KeyEvent::Released(Key::Y) => {
+                    self.reordering = false;
+                    if self.reordered {
+                        self.save_order()?;
+                    } else {
+                        self.next_sort()?;
+                    }
+                    Ok(true)
+                }
+                _ => Ok(true),
+            }
         } else {
             match event {
                 KeyEvent::Pressed(Key::L2) => {
@@ -577,19 +1466,66 @@ where
                     }
                     Ok(true)
                 }
+                KeyEvent::Pressed(Key::B) if self.selecting => {
+                    self.set_selecting(false);
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::B) if self.section_of(self.list.selected()).is_some() => {
+                    if let Some(header) = self.section_of(self.list.selected()) {
+                        self.toggle_section(header)?;
+                    }
+                    Ok(true)
+                }
                 KeyEvent::Pressed(Key::B) => {
                     bubble.push_back(Command::CloseView);
                     Ok(true)
                 }
+                KeyEvent::Pressed(Key::A) if self.selecting => {
+                    self.toggle_checked();
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::A)
+                    if matches!(
+                        self.entries.get(self.list.selected()),
+                        Some(Entry::Directory(_))
+                    ) && self.headers[self.list.selected()] =>
+                {
+                    self.toggle_section(self.list.selected())?;
+                    Ok(true)
+                }
                 KeyEvent::Pressed(Key::A) => {
                     self.select_entry(commands).await?;
                     Ok(true)
                 }
+                KeyEvent::Pressed(Key::Start) if !self.entries.is_empty() => {
+                    self.set_selecting(!self.selecting);
+                    Ok(true)
+                }
                 KeyEvent::Pressed(Key::Y) => {
-                    self.sort(self.sort.next())?;
+                    if self.sort.can_reorder() {
+                        // Sort is changed on release, unless entries were moved while Y was held
+                        self.reordering = true;
+                        self.reordered = false;
+                    } else {
+                        self.next_sort()?;
+                    }
+                    Ok(true)
+                }
+                KeyEvent::Autorepeat(Key::L2) => {
+                    self.open_letter_picker();
                     Ok(true)
                 }
                 KeyEvent::Pressed(Key::Select) => {
+                    self.select_held = true;
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::Up) if self.select_held => {
+                    self.select_held = false;
+                    self.open_letter_picker();
+                    Ok(true)
+                }
+                KeyEvent::Released(Key::Select) if self.select_held => {
+                    self.select_held = false;
                     self.open_menu()?;
                     Ok(true)
                 }
@@ -605,20 +1541,53 @@ where
         }
     }
 
-    fn children(&self) -> Vec<&dyn View> {
-        if let Some(child) = self.child.as_ref() {
-            vec![child.as_ref() as &dyn View]
-        } else {
-            vec![&self.list, &self.image, &self.button_hints]
+    async fn handle_gesture(
+        &mut self,
+        gesture: Gesture,
+        styles: &Stylesheet,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self.artwork_picker.is_some() {
+            return Ok(true);
+        }
+        if let Some(menu) = self.menu.as_mut() {
+            return menu.handle_gesture(gesture, styles, commands, bubble).await;
+        }
+        if self.reordering {
+            return Ok(false);
+        }
+
+        let start = gesture.start();
+        match gesture {
+            // Swiping right goes back to the parent folder
+            Gesture::Swipe { .. } if gesture.direction() == Some(Key::Right) => {
+                bubble.push_back(Command::PressKey(Key::B));
+                Ok(true)
+            }
+            Gesture::Tap(_) if self.image.bounding_box(styles).contains(start) => {
+                bubble.push_back(Command::PressKey(Key::A));
+                Ok(true)
+            }
+            _ if self.button_hints.bounding_box(styles).contains(start) => {
+                self.button_hints
+                    .handle_gesture(gesture, styles, commands, bubble)
+                    .await
+            }
+            _ => {
+                self.list
+                    .handle_gesture(gesture, styles, commands, bubble)
+                    .await
+            }
         }
     }
 
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.image, &self.button_hints]
+    }
+
     fn children_mut(&mut self) -> Vec<&mut dyn View> {
-        if let Some(child) = self.child.as_mut() {
-            vec![child.as_mut() as &mut dyn View]
-        } else {
-            vec![&mut self.list, &mut self.image, &mut self.button_hints]
-        }
+        vec![&mut self.list, &mut self.image, &mut self.button_hints]
     }
 
     fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
@@ -630,13 +1599,78 @@ where
     }
 }
 
+impl<S> Navigable for EntryList<S>
+where
+    S: Sort,
+{
+    fn take_pushed(&mut self) -> Option<Self> {
+        self.pushed.take().map(|list| *list)
+    }
+}
+
+fn entry_label(entry: &Entry, tag_display: TagDisplay) -> String {
+    match entry {
+        Entry::Game(game) => {
+            let tags = match tag_display {
+                TagDisplay::Hidden => None,
+                TagDisplay::Region => game.tags.region_codes().map(|codes| format!("[{codes}]")),
+                TagDisplay::All => Some(game.tags.to_string()).filter(|tags| !tags.is_empty()),
+            };
+            let favorite = if game.favorite { "♥ " } else { "" };
+            match tags {
+                Some(tags) => format!("{}{} {}", favorite, entry.name(), tags),
+                None => format!("{}{}", favorite, entry.name()),
+            }
+        }
+        _ => entry.name().to_string(),
+    }
+}
+
+/// Steps through the CPU profiles, where `None` uses the console's profile.
+fn cycle_cpu_profile(profile: Option<CpuProfile>, forward: bool) -> Option<CpuProfile> {
+    let i = profile.map_or(0, |p| p as usize + 1);
+    let i = if forward {
+        (i + 1).min(CpuProfile::COUNT)
+    } else {
+        i.saturating_sub(1)
+    };
+    i.checked_sub(1).and_then(CpuProfile::from_repr)
+}
+
+/// Menu entry that sets the folder or game as what's opened on boot.
+fn startup_entry(behavior: StartupBehavior) -> Result<MenuEntry> {
+    let is_startup = LauncherSettings::load()?.startup == behavior;
+    Ok(MenuEntry::Startup(behavior, is_startup))
+}
+
 #[derive(Debug, Clone)]
 enum MenuEntry {
     Favorite(bool),
+    UpNext(bool),
     Launch(Option<String>),
+    CpuProfile(Option<CpuProfile>),
     Reset,
     RemoveFromRecents,
     RepopulateDatabase,
+    /// Opens the game's manual, at the path.
+    Manual(PathBuf),
+    /// Opens the artwork picker for the game.
+    SetArtwork,
+    ShowInFolder,
+    /// Sets the folder or game to open on boot, or unsets it if it's already set.
+    Startup(StartupBehavior, bool),
+    /// Renames the game's file, along with its saves and artwork.
+    Rename,
+    /// Lists the folders to move the game to.
+    Move,
+    /// A folder to move the game to.
+    MoveTo(PathBuf),
+    /// Deletes the game's file, along with its saves and artwork.
+    Delete,
+    /// Hides the checked games from the games list.
+    Hide,
+    /// A console to pick for a game that no console was detected for.
+    Console(String),
 }
 
 impl MenuEntry {
@@ -649,6 +1683,13 @@ impl MenuEntry {
                     locale.t("menu-set-as-favorite")
                 }
             }
+            MenuEntry::UpNext(queued) => {
+                if *queued {
+                    locale.t("menu-mark-as-beaten")
+                } else {
+                    locale.t("menu-add-to-up-next")
+                }
+            }
             MenuEntry::Launch(core) => {
                 if let Some(core) = core.as_deref() {
                     locale.ta(
@@ -659,9 +1700,45 @@ impl MenuEntry {
                     locale.t("menu-launch")
                 }
             }
+            MenuEntry::CpuProfile(profile) => {
+                let profile = match profile {
+                    None => locale.t("menu-cpu-profile-console"),
+                    Some(CpuProfile::Powersave) => locale.t("menu-cpu-profile-powersave"),
+                    Some(CpuProfile::Balanced) => locale.t("menu-cpu-profile-balanced"),
+                    Some(CpuProfile::Performance) => locale.t("menu-cpu-profile-performance"),
+                    Some(CpuProfile::Overclock) => locale.t("menu-cpu-profile-overclock"),
+                };
+                locale.ta(
+                    "menu-cpu-profile",
+                    &[("profile".into(), profile.into())].into_iter().collect(),
+                )
+            }
             MenuEntry::Reset => locale.t("menu-reset"),
             MenuEntry::RemoveFromRecents => locale.t("menu-remove-from-recents"),
             MenuEntry::RepopulateDatabase => locale.t("menu-repopulate-database"),
+            MenuEntry::Manual(_) => locale.t("menu-view-manual"),
+            MenuEntry::SetArtwork => locale.t("menu-set-artwork"),
+            MenuEntry::ShowInFolder => locale.t("menu-show-in-folder"),
+            MenuEntry::Startup(behavior, is_startup) => match (behavior, is_startup) {
+                (StartupBehavior::Game(_), false) => locale.t("menu-launch-on-startup"),
+                (StartupBehavior::Game(_), true) => locale.t("menu-unset-launch-on-startup"),
+                (_, false) => locale.t("menu-open-on-startup"),
+                (_, true) => locale.t("menu-unset-open-on-startup"),
+            },
+            MenuEntry::Rename => locale.t("menu-rename"),
+            MenuEntry::Move => locale.t("menu-move"),
+            MenuEntry::MoveTo(dir) => [
+                ALLIUM_GAMES_DIR.as_path(),
+                ALLIUM_EXTERNAL_GAMES_DIR.as_path(),
+            ]
+            .into_iter()
+            .find_map(|root| dir.strip_prefix(root).ok())
+            .unwrap_or(dir)
+            .display()
+            .to_string(),
+            MenuEntry::Delete => locale.t("menu-delete"),
+            MenuEntry::Hide => locale.t("menu-hide"),
+            MenuEntry::Console(name) => name.clone(),
         }
     }
 }
@@ -1,38 +1,60 @@
 use std::cmp::Reverse;
 use std::collections::VecDeque;
+use std::mem::{self, Discriminant};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use async_trait::async_trait;
 use common::command::Command;
-use common::constants::ALLIUM_GAMES_DIR;
+use common::constants::{ALLIUM_GAMES_DIR, PREFETCH_CONSOLE_COUNT, PREFETCH_IDLE_DELAY};
 use common::database::Database;
 use common::geom::{Alignment, Point, Rect};
+use common::launcher::LauncherSettings;
 use common::locale::Locale;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
+use common::status::DeviceStatus;
 use common::stylesheet::Stylesheet;
 use common::view::{ButtonHint, ButtonIcon, Row, View};
-use itertools::Itertools;
+use log::{debug, warn};
 use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::Sender;
+use tokio::sync::oneshot;
 
 use crate::consoles::ConsoleMapper;
 use crate::entry::directory::Directory;
 use crate::entry::{Entry, Sort};
+use crate::prefetch::{self, PrefetchCache};
 use crate::view::entry_list::{EntryList, EntryListState};
+use crate::view::navigation::NavigationStack;
 
-pub type GamesState = EntryListState<GamesSort>;
+/// Saved state of each folder opened, from the games folder to the current folder.
+pub type GamesState = Vec<EntryListState<GamesSort>>;
 
 #[derive(Debug)]
 pub struct Games {
     rect: Rect,
-    list: EntryList<GamesSort>,
+    res: Resources,
+    stack: NavigationStack<EntryList<GamesSort>>,
     button_hints: Row<ButtonHint<String>>,
+    /// How long since the last key press.
+    idle: Duration,
+    /// Consoles left to prefetch, computed once idle.
+    prefetch_queue: Option<VecDeque<PathBuf>>,
+    /// Sort the prefetched consoles were loaded with, as they're only valid for that sort.
+    prefetch_sort: Option<Discriminant<GamesSort>>,
+    /// Console being prefetched in the background, with the cache generation it was started in.
+    prefetching: Option<(u64, oneshot::Receiver<Result<(PathBuf, Vec<Entry>)>>)>,
 }
 
 impl Games {
-    pub fn new(rect: Rect, res: Resources, list: EntryList<GamesSort>) -> Result<Self> {
+    pub fn new(
+        rect: Rect,
+        res: Resources,
+        stack: NavigationStack<EntryList<GamesSort>>,
+    ) -> Result<Self> {
         let Rect { x, y, w: _, h } = rect;
 
         let styles = res.get::<Stylesheet>();
@@ -56,37 +78,192 @@ impl Games {
             12,
         );
 
+        drop(styles);
+
         Ok(Self {
             rect,
-            list,
+            res,
+            stack,
             button_hints,
+            idle: Duration::ZERO,
+            prefetch_queue: None,
+            prefetch_sort: None,
+            prefetching: None,
         })
     }
 
     pub fn load_or_new(rect: Rect, res: Resources, state: Option<GamesState>) -> Result<Self> {
-        let list = if let Some(state) = state {
-            let selected = state.selected;
-            let mut list = EntryList::load(rect, res.clone(), state)?;
-            list.select(selected);
-            list
-        } else {
-            EntryList::new(
-                rect,
-                res.clone(),
-                GamesSort::Alphabetical(Directory::new(ALLIUM_GAMES_DIR.clone())),
-            )?
+        let mut lists = state
+            .unwrap_or_default()
+            .into_iter()
+            .map(|state| EntryList::load(rect, res.clone(), state))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter();
+        let root = match lists.next() {
+            Some(root) => root,
+            None => {
+                let sort = GamesSort::Alphabetical(Directory::new(ALLIUM_GAMES_DIR.clone()));
+                let sort = sort.load_folder_sort(&res.get())?.unwrap_or(sort);
+                EntryList::new(rect, res.clone(), sort)?
+            }
         };
 
-        Self::new(rect, res, list)
+        let mut stack = NavigationStack::new(rect, root);
+        stack.extend(lists);
+        Self::new(rect, res, stack)
     }
 
     pub fn save(&self) -> GamesState {
-        self.list.save()
+        self.stack.iter().map(EntryList::save).collect()
+    }
+
+    /// Opens each folder from the games folder down to the folder containing the game, replacing
+    /// the folders that were open, and selects the game. Each folder is sorted the way it was
+    /// last sorted, or else with the current sort.
+    pub fn show_in_folder(&mut self, path: &Path) -> Result<()> {
+        let Some(dir) = path.parent() else {
+            bail!("{} is not in the games folder", path.display());
+        };
+        let mut lists = self.open_folders(dir)?;
+        if !lists.last_mut().unwrap().select_path(path) {
+            bail!("{} was not found in its folder", path.display());
+        }
+        self.set_folders(lists);
+        Ok(())
+    }
+
+    /// Opens each folder from the games folder down to the folder, replacing the folders that
+    /// were open.
+    pub fn show_folder(&mut self, dir: &Path) -> Result<()> {
+        let lists = self.open_folders(dir)?;
+        self.set_folders(lists);
+        Ok(())
+    }
+
+    /// Lists each folder from the games folder down to the folder, with the folder below each
+    /// one selected. Folders that are collapsed into their only subfolder aren't listed.
+    fn open_folders(&self, dir: &Path) -> Result<Vec<EntryList<GamesSort>>> {
+        let Ok(relative) = dir.strip_prefix(ALLIUM_GAMES_DIR.as_path()) else {
+            bail!("{} is not in the games folder", dir.display());
+        };
+
+        let sort = self.stack.top().save().sort;
+        let mut dir = ALLIUM_GAMES_DIR.clone();
+        let mut lists = vec![self.open_folder(&sort, &dir)?];
+        for component in relative.components() {
+            dir.push(component);
+            if lists.last_mut().unwrap().select_path(&dir) {
+                lists.push(self.open_folder(&sort, &dir)?);
+            }
+        }
+        Ok(lists)
+    }
+
+    fn set_folders(&mut self, lists: Vec<EntryList<GamesSort>>) {
+        let mut lists = lists.into_iter();
+        let mut stack = NavigationStack::new(self.rect, lists.next().unwrap());
+        stack.extend(lists);
+        self.stack = stack;
+    }
+
+    /// Prefetches the next of the most played consoles that isn't prefetched yet. Consoles are
+    /// loaded one at a time in the background, with their own database connection, so that key
+    /// presses aren't delayed. Entries loaded before the cache was cleared are dropped.
+    fn prefetch_next(&mut self) -> Result<()> {
+        let sort = self.stack.top().save().sort;
+        if self.prefetch_sort != Some(mem::discriminant(&sort)) {
+            self.res.get::<PrefetchCache>().clear();
+            self.prefetch_sort = Some(mem::discriminant(&sort));
+        }
+
+        if let Some((generation, rx)) = self.prefetching.as_mut() {
+            let (dir, entries) = match rx.try_recv() {
+                Ok(prefetched) => {
+                    self.prefetching = None;
+                    prefetched?
+                }
+                Err(oneshot::error::TryRecvError::Empty) => return Ok(()),
+                Err(oneshot::error::TryRecvError::Closed) => {
+                    self.prefetching = None;
+                    bail!("prefetch task stopped");
+                }
+            };
+            let cache = self.res.get::<PrefetchCache>();
+            if cache.generation() == *generation {
+                debug!("prefetched {} entries of {}", entries.len(), dir.display());
+                cache.insert(dir, entries);
+            }
+            return Ok(());
+        }
+
+        let queue = match self.prefetch_queue.as_mut() {
+            Some(queue) => queue,
+            None => {
+                let consoles =
+                    prefetch::most_played_consoles(&self.res.get(), PREFETCH_CONSOLE_COUNT)?;
+                self.prefetch_queue.insert(consoles.into())
+            }
+        };
+        let Some(dir) = queue.pop_front() else {
+            return Ok(());
+        };
+        if self.res.get::<PrefetchCache>().contains(&dir) {
+            return Ok(());
+        }
+
+        let console_mapper = self.res.get::<ConsoleMapper>().clone();
+        let lang = self.res.get::<Locale>().lang.to_string();
+        // Looking up box art is slow on the SD card, so do it now for the first page
+        let styles = self.res.get::<Stylesheet>();
+        let page = if styles.boxart_width > 0 {
+            (self.rect.h / styles.list_entry_height()) as usize
+        } else {
+            0
+        };
+        drop(styles);
+
+        let (tx, rx) = oneshot::channel();
+        tokio::task::spawn_blocking(move || {
+            let prefetch = || -> Result<(PathBuf, Vec<Entry>)> {
+                let database = Database::new()?;
+                let sort = sort.open_directory(&database, Directory::new(dir.clone()));
+                let mut entries = sort.entries(&database, &console_mapper, &Locale::new(&lang))?;
+                for entry in entries.iter_mut().take(page) {
+                    entry.image();
+                }
+                Ok((dir, entries))
+            };
+            tx.send(prefetch()).ok();
+        });
+        self.prefetching = Some((self.res.get::<PrefetchCache>().generation(), rx));
+        Ok(())
+    }
+
+    fn open_folder(&self, sort: &GamesSort, dir: &Path) -> Result<EntryList<GamesSort>> {
+        let sort = sort.open_directory(&self.res.get(), Directory::new(dir.to_path_buf()));
+        EntryList::new(self.rect, self.res.clone(), sort)
     }
 }
 
 #[async_trait(?Send)]
 impl View for Games {
+    fn update(&mut self, dt: Duration) {
+        self.stack.update(dt);
+
+        // Only prefetch while idle on the console list, as the consoles are opened from there,
+        // and not while the battery saver is on
+        if !self.stack.is_root() || self.res.get::<DeviceStatus>().battery_saver {
+            return;
+        }
+        self.idle += dt;
+        if self.idle >= PREFETCH_IDLE_DELAY
+            && let Err(e) = self.prefetch_next()
+        {
+            warn!("failed to prefetch consoles: {}", e);
+            self.prefetch_queue = Some(VecDeque::new());
+        }
+    }
+
     fn draw(
         &mut self,
         display: &mut <DefaultPlatform as Platform>::Display,
@@ -94,8 +271,8 @@ impl View for Games {
     ) -> Result<bool> {
         let mut drawn = false;
 
-        if self.list.should_draw() {
-            drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        if self.stack.should_draw() {
+            drawn |= self.stack.should_draw() && self.stack.draw(display, styles)?;
             self.button_hints.set_should_draw();
         }
         drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
@@ -104,11 +281,11 @@ impl View for Games {
     }
 
     fn should_draw(&self) -> bool {
-        self.list.should_draw() || self.button_hints.should_draw()
+        self.stack.should_draw() || self.button_hints.should_draw()
     }
 
     fn set_should_draw(&mut self) {
-        self.list.set_should_draw();
+        self.stack.set_should_draw();
         self.button_hints.set_should_draw();
     }
 
@@ -118,21 +295,24 @@ impl View for Games {
         commands: Sender<Command>,
         bubble: &mut VecDeque<Command>,
     ) -> Result<bool> {
+        self.idle = Duration::ZERO;
+        self.prefetch_queue = None;
+
         match event {
             KeyEvent::Pressed(Key::X) => {
                 commands.send(Command::StartSearch).await?;
                 return Ok(true);
             }
-            _ => self.list.handle_key_event(event, commands, bubble).await,
+            _ => self.stack.handle_key_event(event, commands, bubble).await,
         }
     }
 
     fn children(&self) -> Vec<&dyn View> {
-        vec![&self.list]
+        vec![&self.stack]
     }
 
     fn children_mut(&mut self) -> Vec<&mut dyn View> {
-        vec![&mut self.list]
+        vec![&mut self.stack]
     }
 
     fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
@@ -165,9 +345,37 @@ impl GamesSort {
             GamesSort::Random(d) => d,
         }
     }
+
+    /// Name that the sort is remembered by for its folder.
+    fn name(&self) -> &'static str {
+        match self {
+            GamesSort::Alphabetical(_) => "alphabetical",
+            GamesSort::LastPlayed(_) => "last-played",
+            GamesSort::MostPlayed(_) => "most-played",
+            GamesSort::Rating(_) => "rating",
+            GamesSort::ReleaseDate(_) => "release-date",
+            GamesSort::Random(_) => "random",
+        }
+    }
+
+    fn from_name(name: &str, directory: Directory) -> Option<Self> {
+        Some(match name {
+            "alphabetical" => GamesSort::Alphabetical(directory),
+            "last-played" => GamesSort::LastPlayed(directory),
+            "most-played" => GamesSort::MostPlayed(directory),
+            "rating" => GamesSort::Rating(directory),
+            "release-date" => GamesSort::ReleaseDate(directory),
+            "random" => GamesSort::Random(directory),
+            _ => return None,
+        })
+    }
 }
 
 impl Sort for GamesSort {
+    fn prefetch_dir(&self) -> Option<&Path> {
+        Some(&self.directory().path)
+    }
+
     fn button_hint(&self, locale: &Locale) -> String {
         match self {
             GamesSort::Alphabetical(_) => locale.t("sort-alphabetical"),
@@ -179,7 +387,7 @@ impl Sort for GamesSort {
         }
     }
 
-    fn next(&self) -> Self {
+    fn next(&self, _database: &Database) -> Self {
         match self {
             GamesSort::Alphabetical(d) => GamesSort::LastPlayed(d.clone()),
             GamesSort::LastPlayed(d) => GamesSort::MostPlayed(d.clone()),
@@ -201,6 +409,17 @@ impl Sort for GamesSort {
         }
     }
 
+    fn load_folder_sort(&self, database: &Database) -> Result<Option<Self>> {
+        let directory = self.directory();
+        Ok(database
+            .get_folder_sort(&directory.path)?
+            .and_then(|name| GamesSort::from_name(&name, directory.clone())))
+    }
+
+    fn save_folder_sort(&self, database: &Database) -> Result<()> {
+        database.set_folder_sort(&self.directory().path, self.name())
+    }
+
     fn entries(
         &self,
         database: &Database,
@@ -208,36 +427,12 @@ impl Sort for GamesSort {
         locale: &Locale,
     ) -> Result<Vec<Entry>> {
         let mut entries = self.directory().entries(database, console_mapper, locale)?;
+        let hidden = database.select_hidden_games()?;
+        entries.retain(|entry| !hidden.contains(entry.path()));
 
         match self {
             GamesSort::Alphabetical(_) => {
-                let mut games = Vec::with_capacity(entries.len());
-                let mut i = 0;
-                while i < entries.len() {
-                    if matches!(entries[i], Entry::Game(_)) {
-                        match entries.remove(i) {
-                            Entry::Game(game) => games.push(game),
-                            _ => unreachable!(),
-                        }
-                    } else {
-                        i += 1;
-                    }
-                }
-
-                let db_games = database
-                    .select_games(&games.iter().map(|g| g.path.as_path()).collect::<Vec<_>>())?;
-
-                let games = games.into_iter().zip(db_games).collect::<Vec<_>>();
-                let (favorites, non_favorites): (Vec<_>, Vec<_>) =
-                    games.into_iter().partition(|(_, db_game)| {
-                        db_game.as_ref().map(|g| g.favorite).unwrap_or_default()
-                    });
-                let favorites = favorites.into_iter().map(|(g, _)| g).sorted_unstable();
-                let non_favorites = non_favorites.into_iter().map(|(g, _)| g).sorted_unstable();
-                entries.retain(|e| matches!(e, Entry::Directory(_) | Entry::App(_)));
                 entries.sort_unstable();
-                entries.extend(favorites.map(Entry::Game));
-                entries.extend(non_favorites.map(Entry::Game));
             }
             GamesSort::LastPlayed(_) => {
                 // With this current implementation, apps will appear before games.
@@ -346,10 +541,27 @@ impl Sort for GamesSort {
             }
         }
 
+        if self.directory().path == *ALLIUM_GAMES_DIR {
+            console_mapper.arrange_console_folders(&ALLIUM_GAMES_DIR, &mut entries);
+        }
+
         Ok(entries)
     }
 
     fn preserve_selection(&self) -> bool {
         false
     }
+
+    fn groups_directories(&self) -> bool {
+        self.directory().path == *ALLIUM_GAMES_DIR
+            && LauncherSettings::load().is_ok_and(|settings| settings.group_consoles)
+    }
+
+    fn group_favorites(&self) -> bool {
+        !matches!(self, GamesSort::Random(_))
+    }
+
+    fn prefer_region(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,188 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::display::Display;
+use common::game_info::GameInfo;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{Easing, Image, ImageMode, Label, Tween, View};
+use embedded_graphics::Drawable;
+use embedded_graphics::primitives::{Primitive, PrimitiveStyle, Rectangle};
+use tokio::sync::mpsc::Sender;
+
+/// How long the screen takes to fade out.
+const FADE_DURATION: Duration = Duration::from_millis(200);
+/// How long the box art takes to slide into place.
+const SLIDE_DURATION: Duration = Duration::from_millis(300);
+const SLIDE_DISTANCE: i32 = 48;
+/// How long to wait for the box art to load before launching without it.
+const MAX_DURATION: Duration = Duration::from_secs(1);
+
+/// Shown while a game launches, instead of a black screen until the core starts. The screen
+/// fades to the background color, then the box art slides up above "Launching…".
+#[derive(Debug)]
+pub struct LaunchSplash {
+    rect: Rect,
+    fade: Tween<u8>,
+    slide: Tween<i32>,
+    image: Option<Image>,
+    label: Label<String>,
+    /// Where the image and label end up.
+    image_y: i32,
+    label_y: i32,
+    elapsed: Duration,
+    /// Whether the fade has finished and the screen was cleared.
+    cleared: bool,
+    dirty: bool,
+}
+
+impl LaunchSplash {
+    pub fn new(rect: Rect, res: Resources, game_info: &GameInfo) -> Self {
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let label_height = styles.ui_font.size as i32 + 8;
+        let size = if game_info.image.is_some() {
+            rect.h / 2
+        } else {
+            0
+        };
+        let image_y = rect.y + (rect.h as i32 - size as i32 - label_height) / 2;
+        let label_y = image_y + size as i32 + 8;
+
+        let image = game_info.image.clone().map(|path| {
+            let mut image = Image::new(
+                Rect::new(
+                    rect.x + (rect.w - size) as i32 / 2,
+                    image_y + SLIDE_DISTANCE,
+                    size,
+                    size,
+                ),
+                path,
+                ImageMode::Contain,
+            );
+            image.set_border_radius(12);
+            image.set_alignment(Alignment::Center);
+            image
+        });
+        let label = Label::new(
+            Point::new(rect.x + rect.w as i32 / 2, label_y + SLIDE_DISTANCE),
+            locale.t("launching"),
+            Alignment::Center,
+            Some(rect.w - 24),
+        );
+
+        Self {
+            rect,
+            fade: Tween::new(0, u8::MAX, FADE_DURATION),
+            slide: Tween::new(SLIDE_DISTANCE, 0, SLIDE_DURATION).easing(Easing::EaseOut),
+            image,
+            label,
+            image_y,
+            label_y,
+            elapsed: Duration::ZERO,
+            cleared: false,
+            dirty: true,
+        }
+    }
+
+    /// Whether the transition has finished, so the game can be launched.
+    pub fn is_finished(&self) -> bool {
+        let loading = self.image.as_ref().is_some_and(Image::is_loading);
+        self.slide.is_finished() && (!loading || self.elapsed >= MAX_DURATION)
+    }
+}
+
+#[async_trait(?Send)]
+impl View for LaunchSplash {
+    fn update(&mut self, dt: Duration) {
+        self.elapsed += dt;
+        if self.fade.is_finished() {
+            self.dirty |= self.slide.update(dt);
+        } else {
+            self.dirty |= self.fade.update(dt);
+        }
+        if self.image.as_ref().is_some_and(Image::is_loading) {
+            self.dirty = true;
+        }
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if !self.fade.is_finished() {
+            // Each frame is blended over the last, so the fade speeds up towards the end
+            Rectangle::from(self.rect)
+                .into_styled(PrimitiveStyle::with_fill(
+                    styles.background_color.with_a(self.fade.value()),
+                ))
+                .draw(display)?;
+            self.dirty = false;
+            return Ok(true);
+        }
+
+        if !self.cleared {
+            // The game is launched right after, so the launcher's background isn't needed again
+            display.clear(styles.background_color)?;
+            display.save()?;
+            self.cleared = true;
+        }
+
+        display.load(self.rect)?;
+        let offset = self.slide.value();
+        if let Some(image) = self.image.as_mut() {
+            let x = image.bounding_box(styles).x;
+            image.set_position(Point::new(x, self.image_y + offset));
+            image.draw(display, styles)?;
+        }
+        self.label.set_position(Point::new(
+            self.rect.x + self.rect.w as i32 / 2,
+            self.label_y + offset,
+        ));
+        self.label.draw(display, styles)?;
+
+        self.dirty = false;
+        Ok(true)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        _event: KeyEvent,
+        _commands: Sender<Command>,
+        _bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        Vec::new()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        Vec::new()
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::{Command, Value};
+use common::display::font::FontTextStyleBuilder;
+use common::geom::{Alignment, Point, Rect};
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::View;
+use embedded_graphics::Drawable;
+use embedded_graphics::prelude::Size;
+use embedded_graphics::primitives::{CornerRadii, Primitive, PrimitiveStyle, RoundedRectangle};
+use embedded_graphics::text::Text;
+use tokio::sync::mpsc::Sender;
+
+/// Letters that can be picked. `#` stands for names that don't start with a letter.
+const LETTERS: [char; 27] = [
+    '#', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R',
+    'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
+];
+const COLUMNS: usize = 7;
+
+/// Returns the letter that a name is listed under in the picker.
+pub fn letter(name: &str) -> char {
+    match name.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => c.to_ascii_uppercase(),
+        _ => '#',
+    }
+}
+
+/// Overlay showing the alphabet in a grid, to jump to the first entry starting with a letter.
+/// Letters that no entry starts with are dimmed, and can't be picked.
+///
+/// Picking a letter bubbles `Command::ValueChanged` with the letter, then `Command::CloseView`.
+#[derive(Debug)]
+pub struct LetterPicker {
+    rect: Rect,
+    available: [bool; LETTERS.len()],
+    selected: usize,
+    dirty: bool,
+}
+
+impl LetterPicker {
+    /// Creates a picker centered in `rect`, for entries with the given names. The letter of
+    /// `current` is selected.
+    pub fn new<'a>(rect: Rect, names: impl Iterator<Item = &'a str>, current: &str) -> Self {
+        let mut available = [false; LETTERS.len()];
+        for name in names {
+            available[index(letter(name))] = true;
+        }
+
+        Self {
+            rect,
+            available,
+            selected: index(letter(current)),
+            dirty: true,
+        }
+    }
+
+    fn move_selection(&mut self, key: Key) {
+        let column = self.selected % COLUMNS;
+        self.selected = match key {
+            Key::Left if column > 0 => self.selected - 1,
+            Key::Right if column + 1 < COLUMNS && self.selected + 1 < LETTERS.len() => {
+                self.selected + 1
+            }
+            Key::Up if self.selected >= COLUMNS => self.selected - COLUMNS,
+            Key::Down if self.selected + COLUMNS < LETTERS.len() => self.selected + COLUMNS,
+            _ => return,
+        };
+        self.dirty = true;
+    }
+
+    fn cell_size(styles: &Stylesheet) -> u32 {
+        styles.ui_font.size + 16
+    }
+}
+
+fn index(letter: char) -> usize {
+    LETTERS
+        .iter()
+        .position(|&c| c == letter)
+        .unwrap_or_default()
+}
+
+#[async_trait(?Send)]
+impl View for LetterPicker {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if !self.dirty {
+            return Ok(false);
+        }
+
+        let rect = self.bounding_box(styles);
+        RoundedRectangle::new(
+            rect.into(),
+            CornerRadii::new(Size::new_equal((styles.ui_font.size + 8) / 2)),
+        )
+        .into_styled(PrimitiveStyle::with_fill(
+            StylesheetColor::BackgroundHighlightBlend.to_color(styles),
+        ))
+        .draw(display)?;
+
+        let cell = Self::cell_size(styles);
+        for (i, letter) in LETTERS.iter().enumerate() {
+            let x = rect.x + 12 + (i % COLUMNS) as i32 * cell as i32;
+            let y = rect.y + 12 + (i / COLUMNS) as i32 * cell as i32;
+
+            let selected = i == self.selected;
+            if selected {
+                RoundedRectangle::new(
+                    Rect::new(x, y, cell, cell).into(),
+                    CornerRadii::new(Size::new_equal(cell / 2)),
+                )
+                .into_styled(PrimitiveStyle::with_fill(styles.highlight_color))
+                .draw(display)?;
+            }
+
+            let color = if self.available[i] {
+                styles.foreground_color
+            } else {
+                styles.disabled_color
+            };
+            let text_style = FontTextStyleBuilder::new(styles.ui_font.font())
+                .font_fallbacks(styles.fallbacks())
+                .font_size(styles.ui_font.size)
+                .text_color(color)
+                .build();
+            Text::with_alignment(
+                &letter.to_string(),
+                Point::new(
+                    x + cell as i32 / 2,
+                    y + (cell - styles.ui_font.size) as i32 / 2,
+                )
+                .into(),
+                text_style,
+                Alignment::Center.into(),
+            )
+            .draw(display)?;
+        }
+
+        self.dirty = false;
+        Ok(true)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        _commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(key @ (Key::Up | Key::Down | Key::Left | Key::Right))
+            | KeyEvent::Autorepeat(key @ (Key::Up | Key::Down | Key::Left | Key::Right)) => {
+                self.move_selection(key);
+            }
+            KeyEvent::Pressed(Key::A) if self.available[self.selected] => {
+                bubble.push_back(Command::ValueChanged(
+                    self.selected,
+                    Value::String(LETTERS[self.selected].to_string()),
+                ));
+                bubble.push_back(Command::CloseView);
+            }
+            KeyEvent::Pressed(Key::B | Key::Select) => {
+                bubble.push_back(Command::CloseView);
+            }
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        Vec::new()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        Vec::new()
+    }
+
+    fn bounding_box(&mut self, styles: &Stylesheet) -> Rect {
+        let cell = Self::cell_size(styles);
+        let rows = LETTERS.len().div_ceil(COLUMNS) as u32;
+        let w = COLUMNS as u32 * cell + 24;
+        let h = rows * cell + 24;
+        Rect::new(
+            self.rect.x + (self.rect.w as i32 - w as i32) / 2,
+            self.rect.y + (self.rect.h as i32 - h as i32) / 2,
+            w,
+            h,
+        )
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letter() {
+        assert_eq!(letter("super mario world"), 'S');
+        assert_eq!(letter("Tetris"), 'T');
+        assert_eq!(letter("1942"), '#');
+        assert_eq!(letter("Épée"), '#');
+        assert_eq!(letter(""), '#');
+    }
+
+    #[test]
+    fn test_move_selection() {
+        let rect = Rect::new(0, 0, 640, 480);
+        let mut picker = LetterPicker::new(rect, ["Zelda", "1942"].into_iter(), "Zelda");
+        assert_eq!(picker.selected, index('Z'));
+        assert!(picker.available[index('#')] && !picker.available[index('A')]);
+
+        picker.move_selection(Key::Right);
+        assert_eq!(picker.selected, index('Z'));
+        picker.move_selection(Key::Up);
+        assert_eq!(picker.selected, index('S'));
+        picker.move_selection(Key::Left);
+        assert_eq!(picker.selected, index('R'));
+        picker.move_selection(Key::Down);
+        assert_eq!(picker.selected, index('Y'));
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::geom::{Point, Rect};
+use common::platform::{DefaultPlatform, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{Keyboard, PdfViewer, TextReader, View};
+use tokio::sync::mpsc::Sender;
+
+#[derive(Debug)]
+enum Reader {
+    Pdf(PdfViewer),
+    Text(TextReader),
+}
+
+/// Full-screen viewer for a game's manual, opened from the context menu. PDFs are shown a page at
+/// a time, and text files with the guide reader, which can be searched.
+///
+/// Bubbles `Command::CloseView` when closed.
+#[derive(Debug)]
+pub struct Manual {
+    reader: Reader,
+    keyboard: Option<Keyboard>,
+}
+
+impl Manual {
+    pub fn new(rect: Rect, res: Resources, path: PathBuf) -> Self {
+        let is_pdf = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+        let reader = if is_pdf {
+            Reader::Pdf(PdfViewer::new(rect, res, path))
+        } else {
+            Reader::Text(TextReader::new(rect, res, path))
+        };
+        Self {
+            reader,
+            keyboard: None,
+        }
+    }
+
+    fn reader(&mut self) -> &mut dyn View {
+        match &mut self.reader {
+            Reader::Pdf(viewer) => viewer,
+            Reader::Text(reader) => reader,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Manual {
+    fn update(&mut self, dt: Duration) {
+        self.reader().update(dt);
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            keyboard.update(dt);
+        }
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+        if self.reader().should_draw() {
+            drawn |= self.reader().draw(display, styles)?;
+            if let Some(keyboard) = self.keyboard.as_mut() {
+                keyboard.set_should_draw();
+            }
+        }
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            drawn |= keyboard.should_draw() && keyboard.draw(display, styles)?;
+        }
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        let reader: &dyn View = match &self.reader {
+            Reader::Pdf(viewer) => viewer,
+            Reader::Text(reader) => reader,
+        };
+        reader.should_draw() || self.keyboard.as_ref().is_some_and(View::should_draw)
+    }
+
+    fn set_should_draw(&mut self) {
+        self.reader().set_should_draw();
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            keyboard.set_should_draw();
+        }
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            keyboard
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?;
+            while let Some(command) = bubble.pop_front() {
+                match command {
+                    Command::ValueChanged(_, value) => {
+                        if let Reader::Text(reader) = &mut self.reader {
+                            reader.search(value.as_string().unwrap());
+                        }
+                    }
+                    Command::CloseView => {
+                        self.keyboard = None;
+                        self.reader().set_should_draw();
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(true);
+        }
+
+        let handled = self
+            .reader()
+            .handle_key_event(event, commands, bubble)
+            .await?;
+        if let Reader::Text(reader) = &mut self.reader {
+            self.keyboard = reader.take_keyboard();
+        }
+        Ok(handled)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        Vec::new()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        Vec::new()
+    }
+
+    fn bounding_box(&mut self, styles: &Stylesheet) -> Rect {
+        self.reader().bounding_box(styles)
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
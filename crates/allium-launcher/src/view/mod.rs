@@ -1,14 +1,25 @@
 mod app;
 mod apps;
+mod artwork_picker;
+mod attract;
 mod entry_list;
 mod games;
+mod launch_splash;
+mod letter_picker;
+mod manual;
+mod navigation;
+mod ports;
 mod recents;
+mod screensaver;
 mod settings;
-mod toast;
 
 pub use app::App;
 pub use apps::Apps;
+pub use attract::Attract;
 pub use games::Games;
+pub use launch_splash::LaunchSplash;
+pub use manual::Manual;
+pub use ports::Ports;
 pub use recents::Recents;
+pub use screensaver::Screensaver;
 pub use settings::Settings;
-pub use toast::Toast;
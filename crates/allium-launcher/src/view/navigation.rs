@@ -0,0 +1,199 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::NAVIGATION_TRANSITION_DURATION;
+use common::display::Display;
+use common::geom::{Point, Rect};
+use common::platform::{DefaultPlatform, KeyEvent, Platform};
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::View;
+use embedded_graphics::Drawable;
+use embedded_graphics::primitives::{Primitive, PrimitiveStyle, Rectangle};
+use tokio::sync::mpsc::Sender;
+
+/// A view that can open another view on top of itself in a `NavigationStack`.
+pub trait Navigable: View + Sized {
+    /// Takes the view that was opened while handling the last key event, if any.
+    fn take_pushed(&mut self) -> Option<Self>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Forward,
+    Back,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Transition {
+    direction: Direction,
+    elapsed: Duration,
+}
+
+/// A stack of views where only the top view is shown and receives input. The views below keep
+/// their state, so going back returns to them as they were left. A `Command::CloseView` bubbled
+/// up by the top view pops it, unless it's the root view, in which case it keeps bubbling up.
+#[derive(Debug)]
+pub struct NavigationStack<V>
+where
+    V: Navigable,
+{
+    rect: Rect,
+    /// Views from the root to the top. Never empty.
+    views: Vec<V>,
+    transition: Option<Transition>,
+}
+
+impl<V> NavigationStack<V>
+where
+    V: Navigable,
+{
+    pub fn new(rect: Rect, root: V) -> Self {
+        Self {
+            rect,
+            views: vec![root],
+            transition: None,
+        }
+    }
+
+    /// Pushes views on top of the stack without a transition, e.g. when restoring saved state.
+    pub fn extend(&mut self, views: impl IntoIterator<Item = V>) {
+        self.views.extend(views);
+        self.top_mut().set_should_draw();
+    }
+
+    /// Opens a view on top of the current one.
+    pub fn push(&mut self, view: V) {
+        self.views.push(view);
+        self.start_transition(Direction::Forward);
+    }
+
+    /// Goes back to the previous view, returning the view that was closed. The root view is
+    /// never popped.
+    pub fn pop(&mut self) -> Option<V> {
+        if self.views.len() == 1 {
+            return None;
+        }
+        let view = self.views.pop();
+        self.start_transition(Direction::Back);
+        view
+    }
+
+    /// Whether only the root view is open.
+    pub fn is_root(&self) -> bool {
+        self.views.len() == 1
+    }
+
+    pub fn top(&self) -> &V {
+        self.views.last().unwrap()
+    }
+
+    pub fn top_mut(&mut self) -> &mut V {
+        self.views.last_mut().unwrap()
+    }
+
+    /// Iterates over the views from the root to the top.
+    pub fn iter(&self) -> impl Iterator<Item = &V> {
+        self.views.iter()
+    }
+
+    fn start_transition(&mut self, direction: Direction) {
+        self.transition = Some(Transition {
+            direction,
+            elapsed: Duration::ZERO,
+        });
+    }
+}
+
+#[async_trait(?Send)]
+impl<V> View for NavigationStack<V>
+where
+    V: Navigable,
+{
+    fn update(&mut self, dt: Duration) {
+        if let Some(transition) = self.transition.as_mut() {
+            transition.elapsed += dt;
+        }
+        self.top_mut().update(dt);
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if let Some(transition) = self.transition {
+            // Wipes across the screen from the side the view comes from
+            let progress =
+                transition.elapsed.as_secs_f32() / NAVIGATION_TRANSITION_DURATION.as_secs_f32();
+            if progress < 1.0 {
+                let w = (self.rect.w as f32 * progress) as u32;
+                let x = match transition.direction {
+                    Direction::Forward => self.rect.x + (self.rect.w - w) as i32,
+                    Direction::Back => self.rect.x,
+                };
+                Rectangle::from(Rect::new(x, self.rect.y, w, self.rect.h))
+                    .into_styled(PrimitiveStyle::with_fill(
+                        StylesheetColor::BackgroundHighlightBlend.to_color(styles),
+                    ))
+                    .draw(display)?;
+                return Ok(true);
+            }
+
+            self.transition = None;
+            display.load(self.rect)?;
+            self.top_mut().set_should_draw();
+        }
+
+        self.top_mut().draw(display, styles)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.transition.is_some() || self.top().should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.top_mut().set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        let handled = self
+            .top_mut()
+            .handle_key_event(event, commands, bubble)
+            .await?;
+
+        if let Some(view) = self.top_mut().take_pushed() {
+            self.push(view);
+        }
+
+        if self.views.len() > 1 && bubble.iter().any(|c| matches!(c, Command::CloseView)) {
+            bubble.retain(|c| !matches!(c, Command::CloseView));
+            self.pop();
+        }
+
+        Ok(handled)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![self.top()]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![self.top_mut()]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
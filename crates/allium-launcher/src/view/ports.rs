@@ -0,0 +1,246 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::status::DeviceStatus;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Image, ImageMode, Label, Row, ScrollList, View};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::Sender;
+
+use crate::consoles::ConsoleMapper;
+use crate::ports::Port;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PortsState {
+    selected: usize,
+}
+
+/// Lists the ports in the ports folder, with the image and description of the selected port.
+#[derive(Debug)]
+pub struct Ports {
+    rect: Rect,
+    res: Resources,
+    ports: Vec<Port>,
+    list: ScrollList,
+    image: Image,
+    description: Label<String>,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Ports {
+    pub fn new(rect: Rect, res: Resources, state: Option<PortsState>) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let ports = Port::find_all();
+
+        // The description is shown on a single line above the button hints
+        let description_y =
+            y + h as i32 - ButtonHint::bar_height(&styles) as i32 - 8 - styles.ui_font.size as i32;
+
+        let mut list = ScrollList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                if styles.boxart_width > 0 {
+                    w - styles.boxart_width - 12 - 12 - 24
+                } else {
+                    w - 12 - 12
+                },
+                (description_y - 8 - y - 8) as u32,
+            ),
+            ports.iter().map(|port| port.name.clone()).collect(),
+            Alignment::Left,
+            styles.list_entry_height(),
+        );
+        if let Some(state) = state {
+            list.select(state.selected.min(ports.len().saturating_sub(1)));
+        }
+
+        let mut image = Image::empty(
+            Rect::new(
+                x + w as i32 - styles.boxart_width as i32 - 24,
+                y + 8,
+                styles.boxart_width,
+                (description_y - 8 - y - 8) as u32,
+            ),
+            ImageMode::Contain,
+        );
+        image.set_border_radius(12);
+        image.set_alignment(Alignment::Right);
+
+        let mut description = Label::new(
+            Point::new(x + 12, description_y),
+            String::new(),
+            Alignment::Left,
+            Some(w - 24),
+        );
+        description.scroll(true);
+        if ports.is_empty() {
+            description.set_text(locale.t("ports-empty"));
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![ButtonHint::new(
+                res.clone(),
+                Point::zero(),
+                Key::A,
+                locale.t("ports-button-play"),
+                Alignment::Right,
+            )],
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+
+        let mut this = Self {
+            rect,
+            res,
+            ports,
+            list,
+            image,
+            description,
+            button_hints,
+        };
+        this.update_selection();
+
+        Ok(this)
+    }
+
+    pub fn save(&self) -> PortsState {
+        PortsState {
+            selected: self.list.selected(),
+        }
+    }
+
+    /// Shows the image and description of the selected port.
+    fn update_selection(&mut self) {
+        let Some(port) = self.ports.get(self.list.selected()) else {
+            return;
+        };
+        self.image.set_path(port.image.clone());
+        self.description
+            .set_text(port.description.clone().unwrap_or_default());
+    }
+
+    async fn launch(&mut self, commands: Sender<Command>) -> Result<()> {
+        let Some(port) = self.ports.get(self.list.selected()) else {
+            return Ok(());
+        };
+        let mut game = port.game();
+        let command =
+            self.res
+                .get::<ConsoleMapper>()
+                .launch_game(&self.res.get(), &mut game, false)?;
+        if let Some(command) = command {
+            commands.send(command).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Ports {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        // Box art isn't loaded while the battery saver is on
+        if styles.boxart_width > 0 && !self.res.get::<DeviceStatus>().battery_saver {
+            drawn |= self.image.should_draw() && self.image.draw(display, styles)?;
+        }
+        if self.description.should_draw() {
+            display.load(Rect::new(
+                self.rect.x,
+                self.description.bounding_box(styles).y,
+                self.rect.w,
+                styles.ui_font.size,
+            ))?;
+            drawn |= self.description.draw(display, styles)?;
+        }
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw()
+            || self.image.should_draw()
+            || self.description.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.image.set_should_draw();
+        self.description.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                self.launch(commands).await?;
+                Ok(true)
+            }
+            _ => {
+                let selected = self.list.selected();
+                let handled = self.list.handle_key_event(event, commands, bubble).await?;
+                if self.list.selected() != selected {
+                    self.update_selection();
+                }
+                Ok(handled)
+            }
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![
+            &self.list,
+            &self.image,
+            &self.description,
+            &self.button_hints,
+        ]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![
+            &mut self.list,
+            &mut self.image,
+            &mut self.description,
+            &mut self.button_hints,
+        ]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
@@ -29,7 +29,6 @@ impl Default for RecentsState {
         RecentsState::List(RecentsListState {
             sort: RecentsSort::LastPlayed,
             selected: 0,
-            child: None,
         })
     }
 }
@@ -1,17 +1,18 @@
-use std::collections::VecDeque;
-use std::time::Duration;
+use std::collections::{HashMap, VecDeque};
 
 use anyhow::Result;
 use async_trait::async_trait;
+use chrono::Duration;
 use common::command::{Command, Value};
-use common::constants::RECENT_GAMES_LIMIT;
 use common::database::Database;
 use common::display::Display;
+use common::game_tags::GameTags;
 use common::geom::{Alignment, Point, Rect};
+use common::launcher::LauncherSettings;
 use common::locale::Locale;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
-use common::stylesheet::Stylesheet;
+use common::stylesheet::{Stylesheet, StylesheetColor};
 use common::view::{ButtonHint, ButtonIcon, Image, ImageMode, Keyboard, Label, Row, View};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::Sender;
@@ -35,9 +36,12 @@ pub struct RecentsCarousel {
     rect: Rect,
     res: Resources,
     games: Vec<Game>,
+    /// Lifetime play time of each game.
+    play_times: Vec<Duration>,
     selected: usize,
     screenshot: Image,
     game_name: Label<String>,
+    play_time: Label<String>,
     button_hints: Row<ButtonHint<String>>,
     keyboard: Option<Keyboard>,
     dirty: bool,
@@ -47,14 +51,14 @@ impl RecentsCarousel {
     pub fn new(rect: Rect, res: Resources, state: RecentsCarouselState) -> Result<Self> {
         let Rect { x, y, w, h } = rect;
 
-        let games = Self::load_games(&res)?;
+        let (games, play_times) = Self::load_games(&res)?;
         let selected = state.selected.min(games.len().saturating_sub(1));
 
         let styles = res.get::<Stylesheet>();
         let y_margin = 8;
         let x_margin = 12;
         let ui_font_size = styles.ui_font.size as i32;
-        let bottom_area_height = (y_margin * 3) + (ui_font_size * 2);
+        let bottom_area_height = (y_margin * 4) + (ui_font_size * 3);
         let screenshot_height = h.saturating_sub((bottom_area_height + y_margin) as u32);
 
         let mut screenshot = Image::empty(
@@ -74,6 +78,17 @@ impl RecentsCarousel {
             Some(w - (x_margin * 2) as u32),
         );
 
+        let mut play_time = Label::new(
+            Point::new(
+                x + w as i32 / 2,
+                y + y_margin + screenshot_height as i32 + y_margin + ui_font_size + y_margin,
+            ),
+            String::new(),
+            Alignment::Center,
+            Some(w - (x_margin * 2) as u32),
+        );
+        play_time.color(StylesheetColor::Disabled);
+
         let button_hints = Row::new(
             Point::new(
                 x + w as i32 - 12,
@@ -81,13 +96,22 @@ impl RecentsCarousel {
             ),
             {
                 let locale = res.get::<Locale>();
-                vec![ButtonHint::new(
-                    res.clone(),
-                    Point::zero(),
-                    Key::A,
-                    locale.t("button-select"),
-                    Alignment::Right,
-                )]
+                vec![
+                    ButtonHint::new(
+                        res.clone(),
+                        Point::zero(),
+                        Key::A,
+                        locale.t("button-select"),
+                        Alignment::Right,
+                    ),
+                    ButtonHint::new(
+                        res.clone(),
+                        Point::zero(),
+                        Key::Y,
+                        locale.t("menu-show-in-folder"),
+                        Alignment::Right,
+                    ),
+                ]
             },
             Alignment::Right,
             12,
@@ -99,9 +123,11 @@ impl RecentsCarousel {
             rect,
             res,
             games,
+            play_times,
             selected,
             screenshot,
             game_name,
+            play_time,
             button_hints,
             keyboard: None,
             dirty: true,
@@ -122,11 +148,13 @@ impl RecentsCarousel {
         Self::new(rect, res, state)
     }
 
-    fn load_games(res: &Resources) -> Result<Vec<Game>> {
+    fn load_games(res: &Resources) -> Result<(Vec<Game>, Vec<Duration>)> {
         let database = res.get::<Database>();
-        let db_games = database.select_last_played(RECENT_GAMES_LIMIT)?;
+        let limit = LauncherSettings::load().unwrap_or_default().recents_limit;
+        let db_games = database.select_last_played(limit)?;
 
         let mut games = Vec::new();
+        let mut play_times = Vec::new();
 
         for game in db_games {
             let extension = game
@@ -139,6 +167,8 @@ impl RecentsCarousel {
             let image =
                 crate::entry::lazy_image::LazyImage::from_path(&game.path, game.image.clone());
 
+            play_times.push(game.play_time);
+            let tags = GameTags::from_path(&game.path);
             games.push(Game {
                 name: game.name.clone(),
                 full_name: game.name,
@@ -153,16 +183,18 @@ impl RecentsCarousel {
                 genres: game.genres,
                 favorite: game.favorite,
                 screenshot_path: game.screenshot_path,
+                tags,
             });
         }
 
-        Ok(games)
+        Ok((games, play_times))
     }
 
     fn update_current_game(&mut self) -> Result<()> {
         if self.games.is_empty() {
             self.screenshot.set_path(None);
             self.game_name.set_text(String::new());
+            self.play_time.set_text(String::new());
             return Ok(());
         }
 
@@ -171,6 +203,16 @@ impl RecentsCarousel {
         self.screenshot.set_path(game.screenshot_path.clone());
         self.screenshot.set_should_draw();
         self.game_name.set_text(game.name.clone());
+
+        let play_time = self.play_times[self.selected];
+        let mut map = HashMap::new();
+        map.insert("hours".into(), play_time.num_hours().into());
+        map.insert("minutes".into(), (play_time.num_minutes() % 60).into());
+        let text = self
+            .res
+            .get::<Locale>()
+            .ta("recents-lifetime-play-time", &map);
+        self.play_time.set_text(text);
         self.button_hints.set_should_draw();
 
         self.dirty = true;
@@ -190,9 +232,7 @@ impl RecentsCarousel {
             let toast = self.res.get::<Locale>().t("populating-database");
             commands.send(Command::Toast(toast, None)).await?;
             commands.send(Command::PopulateDb).await?;
-            commands
-                .send(Command::Toast(String::new(), Some(Duration::ZERO)))
-                .await?;
+            commands.send(Command::DismissToast).await?;
         }
 
         commands.send(Command::Search(query)).await?;
@@ -269,6 +309,9 @@ impl View for RecentsCarousel {
             if self.game_name.should_draw() {
                 drawn |= self.game_name.draw(display, styles)?;
             }
+            if self.play_time.should_draw() {
+                drawn |= self.play_time.draw(display, styles)?;
+            }
         }
 
         if self.button_hints.should_draw() {
@@ -289,6 +332,7 @@ impl View for RecentsCarousel {
         self.dirty
             || self.screenshot.should_draw()
             || self.game_name.should_draw()
+            || self.play_time.should_draw()
             || self.button_hints.should_draw()
             || self.keyboard.as_ref().is_some_and(|k| k.should_draw())
     }
@@ -297,6 +341,7 @@ impl View for RecentsCarousel {
         self.dirty = true;
         self.screenshot.set_should_draw();
         self.game_name.set_should_draw();
+        self.play_time.set_should_draw();
         self.button_hints.set_should_draw();
         if let Some(keyboard) = self.keyboard.as_mut() {
             keyboard.set_should_draw();
@@ -347,6 +392,14 @@ impl View for RecentsCarousel {
                 self.launch_game(commands).await?;
                 Ok(true)
             }
+            KeyEvent::Pressed(Key::Y) => {
+                if let Some(game) = self.games.get(self.selected) {
+                    commands
+                        .send(Command::ShowInFolder(game.path.clone()))
+                        .await?;
+                }
+                Ok(true)
+            }
             KeyEvent::Pressed(Key::X) => {
                 if self.keyboard.is_none() {
                     self.start_search();
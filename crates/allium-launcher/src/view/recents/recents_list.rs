@@ -1,12 +1,12 @@
 use std::collections::VecDeque;
-use std::time::Duration;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use common::command::{Command, Value};
-use common::constants::RECENT_GAMES_LIMIT;
-use common::database::Database;
+use common::database::{Database, Game as DbGame};
+use common::game_tags::GameTags;
 use common::geom::{Alignment, Point, Rect};
+use common::launcher::LauncherSettings;
 use common::locale::Locale;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
@@ -31,6 +31,8 @@ pub struct RecentsList {
     list: EntryList<RecentsSort>,
     button_hints: Row<ButtonHint<String>>,
     keyboard: Option<Keyboard>,
+    /// Head of the "Up next" queue, launched with Start.
+    up_next: Option<Game>,
 }
 
 impl RecentsList {
@@ -60,13 +62,53 @@ impl RecentsList {
 
         drop(styles);
 
-        Ok(Self {
+        let mut this = Self {
             res,
             rect,
             list,
             button_hints,
             keyboard: None,
-        })
+            up_next: None,
+        };
+        this.load_up_next()?;
+
+        Ok(this)
+    }
+
+    /// Reloads the head of the "Up next" queue. Returns true if it changed.
+    fn load_up_next(&mut self) -> Result<bool> {
+        let head = self
+            .res
+            .get::<Database>()
+            .select_queue(1)?
+            .into_iter()
+            .next()
+            .map(Game::from_db);
+        if head.as_ref().map(|g| &g.path) == self.up_next.as_ref().map(|g| &g.path) {
+            return Ok(false);
+        }
+
+        if self.button_hints.len() > 1 {
+            self.button_hints.remove(1);
+        }
+        if let Some(game) = head.as_ref() {
+            let locale = self.res.get::<Locale>();
+            self.button_hints.push(ButtonHint::new(
+                self.res.clone(),
+                Point::zero(),
+                Key::Start,
+                locale.ta(
+                    "up-next",
+                    &[("name".into(), game.name.clone().into())]
+                        .into_iter()
+                        .collect(),
+                ),
+                Alignment::Left,
+            ));
+        }
+        self.up_next = head;
+
+        Ok(true)
     }
 
     pub fn load_or_new(
@@ -96,9 +138,7 @@ impl RecentsList {
             let toast = self.res.get::<Locale>().t("populating-database");
             commands.send(Command::Toast(toast, None)).await?;
             commands.send(Command::PopulateDb).await?;
-            commands
-                .send(Command::Toast(String::new(), Some(Duration::ZERO)))
-                .await?;
+            commands.send(Command::DismissToast).await?;
         }
 
         commands.send(Command::Search(query)).await?;
@@ -193,7 +233,29 @@ impl View for RecentsList {
                 }
                 return Ok(true);
             }
-            _ => self.list.handle_key_event(event, commands, bubble).await,
+            KeyEvent::Pressed(Key::Start) => {
+                if let Some(game) = self.up_next.as_mut() {
+                    let command = self.res.get::<ConsoleMapper>().launch_game(
+                        &self.res.get(),
+                        game,
+                        false,
+                    )?;
+                    if let Some(cmd) = command {
+                        commands.send(cmd).await?;
+                    }
+                }
+                Ok(true)
+            }
+            _ => {
+                let handled = self
+                    .list
+                    .handle_key_event(event, commands.clone(), bubble)
+                    .await?;
+                if self.load_up_next()? {
+                    commands.send(Command::Redraw).await?;
+                }
+                Ok(handled)
+            }
         }
     }
 
@@ -219,7 +281,9 @@ pub enum RecentsSort {
     LastPlayed,
     MostPlayed,
     Favorites,
+    UpNext,
     Random,
+    Collection(String),
     Search(String),
 }
 
@@ -229,17 +293,40 @@ impl Sort for RecentsSort {
             RecentsSort::LastPlayed => locale.t("sort-last-played"),
             RecentsSort::MostPlayed => locale.t("sort-most-played"),
             RecentsSort::Favorites => locale.t("sort-favorites"),
+            RecentsSort::UpNext => locale.t("sort-up-next"),
             RecentsSort::Random => locale.t("sort-random"),
+            RecentsSort::Collection(name) => locale.ta(
+                "sort-collection",
+                &[("name".into(), name.clone().into())].into_iter().collect(),
+            ),
             RecentsSort::Search(_) => locale.t("sort-search"),
         }
     }
 
-    fn next(&self) -> Self {
+    fn next(&self, database: &Database) -> Self {
         match self {
             RecentsSort::LastPlayed => RecentsSort::MostPlayed,
             RecentsSort::MostPlayed => RecentsSort::Favorites,
-            RecentsSort::Favorites => RecentsSort::Random,
-            RecentsSort::Random => RecentsSort::LastPlayed,
+            RecentsSort::Favorites => RecentsSort::UpNext,
+            RecentsSort::UpNext => RecentsSort::Random,
+            RecentsSort::Random | RecentsSort::Collection(_) => {
+                // Cycle through collections after the built-in sorts
+                let collections = database.select_collections().unwrap_or_else(|e| {
+                    log::error!("Failed to select collections: {}", e);
+                    Vec::new()
+                });
+                let next = match self {
+                    RecentsSort::Collection(name) => collections
+                        .iter()
+                        .position(|c| c == name)
+                        .and_then(|i| collections.get(i + 1)),
+                    _ => collections.first(),
+                };
+                match next {
+                    Some(name) => RecentsSort::Collection(name.clone()),
+                    None => RecentsSort::LastPlayed,
+                }
+            }
             RecentsSort::Search(_) => RecentsSort::LastPlayed,
         }
     }
@@ -252,14 +339,36 @@ impl Sort for RecentsSort {
         &self,
         database: &Database,
         _console_mapper: &ConsoleMapper,
-        _locale: &Locale,
+        locale: &Locale,
     ) -> Result<Vec<Entry>> {
+        let settings = LauncherSettings::load().unwrap_or_default();
+        let limit = settings.recents_limit;
+
+        if matches!(self, RecentsSort::LastPlayed) && settings.group_recents_by_day {
+            let games = database
+                .select_last_played_by_period(limit)
+                .inspect_err(|err| log::error!("Failed to select games: {}", err))?;
+
+            let mut entries = Vec::with_capacity(games.len());
+            let mut current = None;
+            for (game, period) in games {
+                if current != Some(period) {
+                    entries.push(Entry::Header(locale.t(period.locale_key())));
+                    current = Some(period);
+                }
+                entries.push(game_entry(game));
+            }
+            return Ok(entries);
+        }
+
         let games = match self {
-            RecentsSort::LastPlayed => database.select_last_played(RECENT_GAMES_LIMIT),
-            RecentsSort::MostPlayed => database.select_most_played(RECENT_GAMES_LIMIT),
-            RecentsSort::Favorites => database.select_favorites(RECENT_GAMES_LIMIT),
-            RecentsSort::Random => database.select_random(RECENT_GAMES_LIMIT),
-            RecentsSort::Search(query) => database.search(query, RECENT_GAMES_LIMIT),
+            RecentsSort::LastPlayed => database.select_last_played(limit),
+            RecentsSort::MostPlayed => database.select_most_played(limit),
+            RecentsSort::Favorites => database.select_favorites(limit),
+            RecentsSort::UpNext => database.select_queue(limit),
+            RecentsSort::Random => database.select_random(limit),
+            RecentsSort::Collection(name) => database.select_collection(name, i64::MAX),
+            RecentsSort::Search(query) => database.search(query, limit),
         };
 
         let games = match games {
@@ -270,40 +379,50 @@ impl Sort for RecentsSort {
             }
         };
 
-        Ok(games
-            .into_iter()
-            .map(|game| {
-                let extension = game
-                    .path
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or_default()
-                    .to_owned();
-
-                let full_name = game.name.clone();
-
-                let image = LazyImage::from_path(&game.path, game.image);
-
-                Entry::Game(Game {
-                    name: game.name,
-                    full_name,
-                    path: game.path,
-                    image,
-                    extension,
-                    core: game.core,
-                    rating: game.rating,
-                    release_date: game.release_date,
-                    developer: game.developer,
-                    publisher: game.publisher,
-                    genres: game.genres,
-                    favorite: game.favorite,
-                    screenshot_path: game.screenshot_path,
-                })
-            })
-            .collect())
+        Ok(games.into_iter().map(game_entry).collect())
     }
 
     fn preserve_selection(&self) -> bool {
         false
     }
+
+    fn can_reorder(&self) -> bool {
+        matches!(self, RecentsSort::Favorites)
+    }
+
+    fn can_show_in_folder(&self) -> bool {
+        true
+    }
+}
+
+/// Converts a game from the database into an entry that can be launched.
+fn game_entry(game: DbGame) -> Entry {
+    let extension = game
+        .path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_owned();
+
+    let full_name = game.name.clone();
+
+    let image = LazyImage::from_path(&game.path, game.image);
+    let tags = GameTags::from_path(&game.path);
+
+    Entry::Game(Game {
+        name: game.name,
+        full_name,
+        path: game.path,
+        image,
+        extension,
+        core: game.core,
+        rating: game.rating,
+        release_date: game.release_date,
+        developer: game.developer,
+        publisher: game.publisher,
+        genres: game.genres,
+        favorite: game.favorite,
+        screenshot_path: game.screenshot_path,
+        tags,
+    })
 }
@@ -0,0 +1,180 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Local;
+use common::command::Command;
+use common::constants::{ATTRACT_MODE_GAMES_LIMIT, SCREENSAVER_MOVE_INTERVAL};
+use common::database::Database;
+use common::display::settings::Screensaver as ScreensaverMode;
+use common::geom::{Alignment, Point, Rect};
+use common::platform::{DefaultPlatform, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{Image, ImageMode, Label, View};
+use embedded_graphics::Drawable;
+use embedded_graphics::primitives::{Primitive, PrimitiveStyle, Rectangle};
+use tokio::sync::mpsc::Sender;
+
+use crate::entry::game::Game;
+
+/// Size of the clock, relative to the UI font.
+const CLOCK_FONT_SIZE: f32 = 3.0;
+
+/// Offsets of the screensaver from the center of the screen, in quarters of the screen size.
+/// It moves to the next one every `SCREENSAVER_MOVE_INTERVAL`.
+const OFFSETS: [(i32, i32); 5] = [(0, 0), (-1, -1), (1, 1), (1, -1), (-1, 1)];
+
+/// Shows the time, and optionally box art of recently played games, while the launcher is idle.
+/// Everything moves around the screen, so that nothing is burned in.
+#[derive(Debug)]
+pub struct Screensaver {
+    rect: Rect,
+    images: Vec<PathBuf>,
+    index: usize,
+    elapsed: Duration,
+    image: Image,
+    clock: Label<String>,
+    dirty: bool,
+}
+
+impl Screensaver {
+    pub fn new(rect: Rect, res: Resources, mode: ScreensaverMode) -> Result<Self> {
+        let Rect { w, h, .. } = rect;
+
+        let images = if mode == ScreensaverMode::BoxArt {
+            res.get::<Database>()
+                .select_last_played(ATTRACT_MODE_GAMES_LIMIT)?
+                .into_iter()
+                .filter_map(|game| Game::from_db(game).image().map(Path::to_path_buf))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let image = Image::empty(Rect::new(0, 0, w / 2, h / 2), ImageMode::Contain);
+        let mut clock = Label::new(Point::zero(), time(), Alignment::Center, None);
+        if images.is_empty() {
+            clock.font_size(CLOCK_FONT_SIZE);
+        }
+
+        let mut this = Self {
+            rect,
+            images,
+            index: 0,
+            elapsed: Duration::ZERO,
+            image,
+            clock,
+            dirty: true,
+        };
+        this.show();
+        Ok(this)
+    }
+
+    /// Moves to the next position, showing the next box art.
+    fn show(&mut self) {
+        let Rect { x, y, w, h } = self.rect;
+        let (dx, dy) = OFFSETS[self.index % OFFSETS.len()];
+        let center = Point::new(
+            x + w as i32 / 2 + dx * w as i32 / 4,
+            y + h as i32 / 2 + dy * h as i32 / 4,
+        );
+
+        if self.images.is_empty() {
+            self.clock.set_position(center);
+        } else {
+            let image = &self.images[self.index % self.images.len()];
+            self.image.set_path(Some(image.clone()));
+            // The box art is a quarter of the screen, with the clock below it
+            let image_w = w / 2;
+            let image_h = h / 2;
+            let image_x = (center.x - image_w as i32 / 2).clamp(x, x + (w - image_w) as i32);
+            let image_y = (center.y - image_h as i32 / 2).clamp(y, y + (h - image_h) as i32 - 48);
+            self.image.set_position(Point::new(image_x, image_y));
+            self.clock.set_position(Point::new(
+                image_x + image_w as i32 / 2,
+                image_y + image_h as i32 + 8,
+            ));
+        }
+        self.dirty = true;
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Screensaver {
+    fn update(&mut self, dt: Duration) {
+        self.elapsed += dt;
+        if self.elapsed >= SCREENSAVER_MOVE_INTERVAL {
+            self.elapsed = Duration::ZERO;
+            self.index += 1;
+            self.show();
+        }
+        let time = time();
+        if self.clock.text() != time {
+            self.clock.set_text(time);
+            self.dirty = true;
+        }
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if !self.dirty {
+            return Ok(false);
+        }
+
+        Rectangle::from(self.rect)
+            .into_styled(PrimitiveStyle::with_fill(styles.background_color))
+            .draw(display)?;
+        if !self.images.is_empty() {
+            self.image.set_should_draw();
+            self.image.draw(display, styles)?;
+        }
+        self.clock.set_should_draw();
+        self.clock.draw(display, styles)?;
+        self.dirty = false;
+
+        Ok(true)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        _event: KeyEvent,
+        _commands: Sender<Command>,
+        _bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.image, &self.clock]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.image, &mut self.clock]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+fn time() -> String {
+    Local::now().format("%H:%M").to_string()
+}
@@ -14,6 +14,16 @@ use tokio::sync::mpsc::Sender;
 
 use crate::view::settings::{ChildState, SettingsChild};
 
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &[
+    "settings-about-allium-version",
+    "settings-about-model-name",
+    "settings-about-firmware-version",
+    "settings-about-operating-system-version",
+    "settings-about-kernel-version",
+    "settings-about-memory-used",
+];
+
 pub struct About {
     rect: Rect,
     list: SettingsList,
@@ -39,14 +49,7 @@ impl About {
                 w - 24,
                 h - 8 - ButtonIcon::diameter(&styles) - 8,
             ),
-            vec![
-                locale.t("settings-about-allium-version"),
-                locale.t("settings-about-model-name"),
-                locale.t("settings-about-firmware-version"),
-                locale.t("settings-about-operating-system-version"),
-                locale.t("settings-about-kernel-version"),
-                locale.t("settings-about-memory-used"),
-            ],
+            LABELS.iter().map(|key| locale.t(key)).collect(),
             vec![
                 Box::new(Label::new(
                     Point::zero(),
@@ -0,0 +1,287 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use common::battery::{drain_rates, estimate_runtime};
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::database::{BatterySample, Database};
+use common::display::Display as DisplayTrait;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, Row, SettingsList, View};
+use embedded_graphics::Drawable;
+use embedded_graphics::primitives::{Line, Primitive, PrimitiveStyle, Rectangle};
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// How far back the discharge curve goes.
+const GRAPH_DURATION: i64 = 24 * 60 * 60;
+
+/// Samples further apart than this are not joined on the discharge curve.
+const GRAPH_MAX_GAP: i64 = 10 * 60;
+
+/// Games played for less than this aren't shown, as their drain rate would be mostly noise.
+const MIN_DRAIN_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// Maximum number of games to show the drain rate of.
+const DRAIN_RATES_LIMIT: usize = 5;
+
+/// Locale keys of the labels of the rows before the drain rate of each game.
+pub const LABELS: &[&str] = &["settings-battery-runtime"];
+
+pub struct Battery {
+    rect: Rect,
+    graph: Rect,
+    samples: Vec<BatterySample>,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl Battery {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+        let database = res.get::<Database>();
+
+        let now = Utc::now().timestamp();
+        let samples = database
+            .select_battery_history(now - GRAPH_DURATION)
+            .unwrap_or_default();
+
+        let content_h = h - 8 - ButtonIcon::diameter(&styles) - 8;
+        let graph = Rect::new(x + 12, y + 8, w - 24, content_h * 2 / 5);
+
+        let mut left: Vec<String> = LABELS.iter().map(|key| locale.t(key)).collect();
+        let mut right: Vec<Box<dyn View>> = vec![Box::new(Label::new(
+            Point::zero(),
+            match estimate_runtime(&samples, MIN_DRAIN_DURATION) {
+                Some(runtime) => format_duration(&locale, runtime),
+                None if samples.last().is_some_and(|s| s.charging) => {
+                    locale.t("settings-battery-charging")
+                }
+                None => locale.t("settings-battery-not-enough-data"),
+            },
+            Alignment::Right,
+            None,
+        ))];
+
+        for (game, rate) in drain_rates(&samples, MIN_DRAIN_DURATION)
+            .into_iter()
+            .take(DRAIN_RATES_LIMIT)
+        {
+            left.push(match game {
+                Some(path) => match database.select_game(&path) {
+                    Ok(Some(game)) => game.name,
+                    _ => path
+                        .file_stem()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string(),
+                },
+                None => locale.t("settings-battery-not-in-game"),
+            });
+            let mut map = HashMap::new();
+            map.insert("rate".into(), format!("{rate:.1}").into());
+            right.push(Box::new(Label::new(
+                Point::zero(),
+                locale.ta("settings-battery-drain-rate", &map),
+                Alignment::Right,
+                None,
+            )));
+        }
+
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                graph.y + graph.h as i32 + 8,
+                w - 24,
+                content_h - graph.h - 8,
+            ),
+            left,
+            right,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![ButtonHint::new(
+                res.clone(),
+                Point::zero(),
+                Key::B,
+                locale.t("button-back"),
+                Alignment::Right,
+            )],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+        drop(database);
+
+        Self {
+            rect,
+            graph,
+            samples,
+            list,
+            button_hints,
+            dirty: true,
+        }
+    }
+
+    /// Draws the battery percentage over the last day. Charging periods are highlighted.
+    fn draw_graph(
+        &self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<()> {
+        let Rect { x, y, w, h } = self.graph;
+
+        Rectangle::from(self.graph)
+            .into_styled(PrimitiveStyle::with_stroke(styles.disabled_color, 1))
+            .draw(display)?;
+        let middle = y + h as i32 / 2;
+        Line::new(
+            Point::new(x, middle).into(),
+            Point::new(x + w as i32, middle).into(),
+        )
+        .into_styled(PrimitiveStyle::with_stroke(styles.disabled_color, 1))
+        .draw(display)?;
+
+        let end = self.samples.last().map_or(0, |s| s.timestamp);
+        let start = end - GRAPH_DURATION;
+        let point = |sample: &BatterySample| {
+            Point::new(
+                x + ((sample.timestamp - start) * w as i64 / GRAPH_DURATION) as i32,
+                y + h as i32 - sample.percentage.clamp(0, 100) * h as i32 / 100,
+            )
+        };
+
+        for pair in self.samples.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            if b.timestamp - a.timestamp > GRAPH_MAX_GAP {
+                continue;
+            }
+            let color = if b.charging {
+                styles.highlight_color
+            } else {
+                styles.foreground_color
+            };
+            Line::new(point(a).into(), point(b).into())
+                .into_styled(PrimitiveStyle::with_stroke(color, 2))
+                .draw(display)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Formats a duration as hours and minutes.
+fn format_duration(locale: &Locale, duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    let mut map = HashMap::new();
+    map.insert("hours".into(), (minutes / 60).into());
+    map.insert("minutes".into(), (minutes % 60).into());
+    locale.ta("settings-battery-runtime-value", &map)
+}
+
+#[async_trait(?Send)]
+impl View for Battery {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.graph)?;
+            self.draw_graph(display, styles)?;
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+
+        if self.button_hints.should_draw() {
+            display.load(Rect::new(
+                self.rect.x,
+                self.rect.y + self.rect.h as i32 - ButtonIcon::diameter(styles) as i32 - 8,
+                self.rect.w,
+                ButtonIcon::diameter(styles),
+            ))?;
+            drawn |= self.button_hints.draw(display, styles)?;
+        }
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty || self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self.list.handle_key_event(event, commands, bubble).await? {
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Battery {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, Row, SettingsList, View};
+use log::warn;
+use tokio::sync::mpsc::Sender;
+
+use crate::bios::BiosStatus;
+use crate::consoles::ConsoleMapper;
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Lists the BIOS files needed by each console, and whether they're in the BIOS folder with the
+/// expected checksums.
+pub struct Bios {
+    rect: Rect,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Bios {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        for console in res.get::<ConsoleMapper>().consoles() {
+            for bios in &console.bios {
+                let status = bios.check().unwrap_or_else(|e| {
+                    warn!("failed to check BIOS {}: {}", bios.file, e);
+                    BiosStatus::Missing
+                });
+                let key = match status {
+                    BiosStatus::Present => "settings-bios-present",
+                    BiosStatus::Missing if bios.optional => "settings-bios-optional",
+                    BiosStatus::Missing => "settings-bios-missing",
+                    BiosStatus::BadChecksum => "settings-bios-bad-checksum",
+                };
+                left.push(format!("{}: {}", console.name, bios.file));
+                right.push(value(locale.t(key)));
+            }
+        }
+        if left.is_empty() {
+            left.push(locale.t("settings-bios-none"));
+            right.push(value(String::new()));
+        }
+        let len = left.len();
+
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            left,
+            right,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected.min(len - 1));
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![ButtonHint::new(
+                res.clone(),
+                Point::zero(),
+                Key::B,
+                locale.t("button-back"),
+                Alignment::Right,
+            )],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            list,
+            button_hints,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Bios {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Bios {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
+
+fn value(text: String) -> Box<dyn View> {
+    Box::new(Label::new(Point::zero(), text, Alignment::Right, None))
+}
@@ -0,0 +1,393 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::bluetooth::{self, BluetoothDevice, BluetoothSettings};
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, Row, SettingsList, Toggle, View};
+use log::warn;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+const ENABLED_INDEX: usize = 0;
+const AUDIO_OUTPUT_INDEX: usize = 1;
+const SCAN_INDEX: usize = 2;
+/// Index of the first device found by scanning.
+const DEVICES_INDEX: usize = 3;
+
+#[derive(Debug)]
+enum BluetoothEvent {
+    Scanned(Vec<BluetoothDevice>),
+    Connected(BluetoothDevice),
+    ConnectFailed,
+}
+
+/// Locale keys of the labels of the rows before the paired and found devices.
+pub const LABELS: &[&str] = &[
+    "settings-bluetooth-enabled",
+    "settings-bluetooth-audio-output",
+    "settings-bluetooth-scan",
+];
+
+pub struct Bluetooth {
+    rect: Rect,
+    res: Resources,
+    settings: BluetoothSettings,
+    list: SettingsList,
+    /// Devices found by the last scan.
+    devices: Vec<BluetoothDevice>,
+    scanning: bool,
+    tx: Sender<BluetoothEvent>,
+    events: Receiver<BluetoothEvent>,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Bluetooth {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let settings = BluetoothSettings::load().unwrap();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            Vec::new(),
+            Vec::new(),
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-select"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        let (tx, events) = mpsc::channel(4);
+        let mut this = Self {
+            rect,
+            res,
+            settings,
+            list,
+            devices: Vec::new(),
+            scanning: false,
+            tx,
+            events,
+            button_hints,
+        };
+        this.update_list();
+        if let Some(state) = state {
+            this.list.select(state.selected.min(SCAN_INDEX));
+        }
+        this
+    }
+
+    /// Rebuilds the list from the settings and the devices found by the last scan.
+    fn update_list(&mut self) {
+        let locale = self.res.get::<Locale>();
+
+        let mut left: Vec<String> = LABELS.iter().map(|key| locale.t(key)).collect();
+        let mut right = vec![
+            Box::new(Toggle::new(
+                Point::zero(),
+                self.settings.enabled,
+                Alignment::Right,
+            )) as Box<dyn View>,
+            value(match &self.settings.sink {
+                Some(sink) => sink.name.clone(),
+                None => locale.t("settings-bluetooth-device-speaker"),
+            }),
+            value(if self.scanning {
+                locale.t("settings-bluetooth-scanning")
+            } else {
+                locale.t("settings-bluetooth-scan-start")
+            }),
+        ];
+        for device in &self.devices {
+            left.push(device.name.clone());
+            right.push(value(if self.is_sink(device) {
+                locale.t("settings-bluetooth-connected")
+            } else {
+                String::new()
+            }));
+        }
+
+        drop(locale);
+
+        let selected = self.list.selected().min(left.len() - 1);
+        self.list.set_items(left, right);
+        self.list.select(selected);
+    }
+
+    fn is_sink(&self, device: &BluetoothDevice) -> bool {
+        self.settings
+            .sink
+            .as_ref()
+            .is_some_and(|sink| sink.address == device.address)
+    }
+
+    async fn toast(&self, commands: &Sender<Command>, key: &str) -> Result<()> {
+        let locale = self.res.get::<Locale>();
+        commands
+            .send(Command::Toast(locale.t(key), Some(Duration::from_secs(3))))
+            .await?;
+        Ok(())
+    }
+
+    /// Scans for devices in the background. They're listed when the scan finishes.
+    async fn scan(&mut self, commands: &Sender<Command>) -> Result<()> {
+        if !self.settings.enabled {
+            return self.toast(commands, "settings-bluetooth-not-enabled").await;
+        }
+        if self.scanning {
+            return Ok(());
+        }
+
+        self.scanning = true;
+        self.update_list();
+
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            let devices = bluetooth::scan().await.unwrap_or_else(|e| {
+                warn!("failed to scan for bluetooth devices: {}", e);
+                Vec::new()
+            });
+            tx.send(BluetoothEvent::Scanned(devices)).await.ok();
+        });
+        Ok(())
+    }
+
+    /// Pairs with and connects to a device in the background, and uses it as the audio output.
+    async fn connect(&mut self, index: usize, commands: &Sender<Command>) -> Result<()> {
+        if !self.settings.enabled {
+            return self.toast(commands, "settings-bluetooth-not-enabled").await;
+        }
+        let device = self.devices[index - DEVICES_INDEX].clone();
+        if self.is_sink(&device) {
+            return Ok(());
+        }
+
+        let failed = {
+            let locale = self.res.get::<Locale>();
+            self.list
+                .set_right(index, value(locale.t("settings-bluetooth-connecting")));
+            let mut map = HashMap::new();
+            map.insert("name".into(), device.name.clone().into());
+            locale.ta("settings-bluetooth-connect-failed", &map)
+        };
+
+        let previous = self.settings.sink.clone();
+        let tx = self.tx.clone();
+        let commands = commands.clone();
+        tokio::spawn(async move {
+            if let Some(previous) = previous
+                && let Err(e) = bluetooth::disconnect(&previous).await
+            {
+                warn!("failed to disconnect from {}: {}", previous.name, e);
+            }
+            match bluetooth::connect(&device).await {
+                Ok(()) => {
+                    tx.send(BluetoothEvent::Connected(device)).await.ok();
+                }
+                Err(e) => {
+                    warn!("failed to connect to {}: {}", device.name, e);
+                    commands
+                        .send(Command::Toast(failed, Some(Duration::from_secs(3))))
+                        .await
+                        .ok();
+                    tx.send(BluetoothEvent::ConnectFailed).await.ok();
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Switches the audio output back to the device's speaker.
+    fn use_speaker(&mut self) -> Result<()> {
+        let Some(sink) = self.settings.sink.take() else {
+            return Ok(());
+        };
+        self.settings.save()?;
+        self.update_list();
+
+        tokio::spawn(async move {
+            if let Err(e) = bluetooth::disconnect(&sink).await {
+                warn!("failed to disconnect from {}: {}", sink.name, e);
+            }
+        });
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Bluetooth {
+    fn update(&mut self, _dt: Duration) {
+        let mut changed = false;
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                BluetoothEvent::Scanned(devices) => {
+                    self.scanning = false;
+                    self.devices = devices;
+                }
+                BluetoothEvent::Connected(device) => {
+                    self.settings.sink = Some(device);
+                    if let Err(e) = self.settings.save() {
+                        warn!("failed to save bluetooth settings: {}", e);
+                    }
+                }
+                BluetoothEvent::ConnectFailed => {}
+            }
+            changed = true;
+        }
+        if changed {
+            self.update_list();
+        }
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if event == KeyEvent::Pressed(Key::A) {
+            match self.list.selected() {
+                AUDIO_OUTPUT_INDEX => {
+                    self.use_speaker()?;
+                    return Ok(true);
+                }
+                SCAN_INDEX => {
+                    self.scan(&commands).await?;
+                    return Ok(true);
+                }
+                i if i >= DEVICES_INDEX => {
+                    self.connect(i, &commands).await?;
+                    return Ok(true);
+                }
+                _ => {}
+            }
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    match i {
+                        ENABLED_INDEX => {
+                            if !bluetooth::is_available() {
+                                self.update_list();
+                                self.toast(&commands, "bluetooth-unavailable").await?;
+                                continue;
+                            }
+                            self.settings.set_enabled(val.as_bool().unwrap());
+                            if !self.settings.enabled {
+                                self.devices.clear();
+                                self.update_list();
+                            }
+                        }
+                        _ => unreachable!("Invalid index"),
+                    }
+                    self.settings.save()?;
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Bluetooth {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
+
+fn value(text: String) -> Box<dyn View> {
+    Box::new(Label::new(Point::zero(), text, Alignment::Right, None))
+}
@@ -1,26 +1,26 @@
 use std::collections::VecDeque;
-use std::env;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::Local;
 use common::command::Command;
-use common::constants::{ALLIUM_TIMEZONE, SELECTION_MARGIN};
+use common::constants::SELECTION_MARGIN;
 
 use common::display::Display as DisplayTrait;
 use common::geom::{Alignment, Point, Rect};
-use common::locale::Locale;
+use common::locale::{Locale, LocaleSettings};
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
 use common::stylesheet::Stylesheet;
 use common::view::{ButtonHint, ButtonIcon, DateTime, Row, Select, SettingsList, View};
 
-use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
 use tokio::sync::mpsc::Sender;
 
 use crate::view::settings::{ChildState, SettingsChild};
 
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &["settings-clock-datetime", "settings-clock-timezone"];
+
 pub struct Clock {
     rect: Rect,
     timezone: usize,
@@ -118,8 +118,10 @@ impl Clock {
     pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
         let Rect { x, y, w, h } = rect;
 
-        let timezone = env::var("TZ")
-            .map(|tz| TIMEZONE_VALUES.iter().position(|&s| s == tz).unwrap_or(0))
+        let settings = LocaleSettings::load().unwrap_or_default();
+        let timezone = TIMEZONE_VALUES
+            .iter()
+            .position(|&s| s == settings.timezone)
             .unwrap_or(0);
         let locale = res.get::<Locale>();
         let styles = res.get::<Stylesheet>();
@@ -131,10 +133,7 @@ impl Clock {
                 w - 24,
                 h - 8 - ButtonIcon::diameter(&styles) - 8,
             ),
-            vec![
-                locale.t("settings-clock-datetime"),
-                locale.t("settings-clock-timezone"),
-            ],
+            LABELS.iter().map(|key| locale.t(key)).collect(),
             vec![
                 Box::new(DateTime::new(
                     Point::zero(),
@@ -237,12 +236,9 @@ impl View for Clock {
                         }
                         1 => {
                             self.timezone = val.as_int().unwrap() as usize;
-                            let timezone = TIMEZONE_VALUES[self.timezone];
-                            File::create(ALLIUM_TIMEZONE.as_path())
-                                .await?
-                                .write_all(timezone.as_bytes())
-                                .await?;
-                            unsafe { env::set_var("TZ", timezone) };
+                            let mut settings = LocaleSettings::load()?;
+                            settings.set_timezone(TIMEZONE_VALUES[self.timezone]);
+                            settings.save()?;
                             self.list.set_right(
                                 0,
                                 Box::new(DateTime::new(
@@ -0,0 +1,287 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::{ALLIUM_GAMES_DIR, SELECTION_MARGIN};
+use common::database::Database;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, Row, SettingsList, Toggle, View};
+use log::warn;
+use tokio::sync::mpsc::Sender;
+
+use crate::consoles::ConsoleMapper;
+use crate::entry::Entry;
+use crate::entry::directory::Directory;
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Reorders the consoles in the Games tab, and hides the ones that aren't used. Only consoles
+/// with folders in the games folder are listed.
+pub struct Consoles {
+    rect: Rect,
+    res: Resources,
+    /// Console name of each row, in the order their folders are listed.
+    rows: Vec<String>,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+    /// Whether the order or hidden consoles changed, to reload the Games tab when closed.
+    changed: bool,
+}
+
+impl Consoles {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            Vec::new(),
+            Vec::new(),
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::Y,
+                    locale.t("settings-tabs-move-up"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::X,
+                    locale.t("settings-tabs-move-down"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        let rows = console_names(&res).unwrap_or_else(|e| {
+            warn!("failed to list consoles: {}", e);
+            Vec::new()
+        });
+
+        let mut this = Self {
+            rect,
+            res,
+            rows,
+            list,
+            button_hints,
+            changed: false,
+        };
+        this.set_items();
+        if let Some(state) = state {
+            this.list
+                .select(state.selected.min(this.rows.len().saturating_sub(1)));
+        }
+        this
+    }
+
+    fn set_items(&mut self) {
+        let locale = self.res.get::<Locale>();
+        let console_mapper = self.res.get::<ConsoleMapper>();
+
+        let mut left = self.rows.clone();
+        let mut right: Vec<Box<dyn View>> = self
+            .rows
+            .iter()
+            .map(|name| {
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    !console_mapper.is_console_hidden(name),
+                    Alignment::Right,
+                )) as Box<dyn View>
+            })
+            .collect();
+        if left.is_empty() {
+            left.push(locale.t("settings-consoles-none"));
+            right.push(Box::new(Label::new(
+                Point::zero(),
+                String::new(),
+                Alignment::Right,
+                None,
+            )));
+        }
+
+        drop(locale);
+        drop(console_mapper);
+        self.list.set_items(left, right);
+    }
+
+    /// Moves the selected console up (negative offset) or down (positive offset), remembering
+    /// the order of all the listed consoles.
+    fn move_console(&mut self, offset: isize) -> Result<()> {
+        let i = self.list.selected();
+        if i >= self.rows.len() {
+            return Ok(());
+        }
+        let j = (i as isize + offset).clamp(0, self.rows.len() as isize - 1) as usize;
+        let name = self.rows.remove(i);
+        self.rows.insert(j, name);
+
+        let mut console_mapper = self.res.get::<ConsoleMapper>().clone();
+        console_mapper.set_console_order(self.rows.clone())?;
+        self.res.insert(console_mapper);
+        self.changed = true;
+
+        self.set_items();
+        self.list.select(j);
+        Ok(())
+    }
+
+    fn set_hidden(&mut self, i: usize, hidden: bool) -> Result<()> {
+        let Some(name) = self.rows.get(i) else {
+            return Ok(());
+        };
+        let mut console_mapper = self.res.get::<ConsoleMapper>().clone();
+        console_mapper.set_console_hidden(name, hidden)?;
+        self.res.insert(console_mapper);
+        self.changed = true;
+        Ok(())
+    }
+}
+
+/// Names of the consoles with folders in the games folder, in the user's console order.
+fn console_names(res: &Resources) -> Result<Vec<String>> {
+    let console_mapper = res.get::<ConsoleMapper>();
+    let entries = Directory::new(ALLIUM_GAMES_DIR.clone()).entries(
+        &res.get::<Database>(),
+        &console_mapper,
+        &res.get::<Locale>(),
+    )?;
+
+    let mut names: Vec<String> = Vec::new();
+    for entry in entries {
+        if let Entry::Directory(dir) = entry
+            && let Some(console) = console_mapper.get_console_by_dir(&dir.path)
+            && !names.contains(&console.name)
+        {
+            names.push(console.name.clone());
+        }
+    }
+    console_mapper.sort_console_names(&mut names);
+    Ok(names)
+}
+
+#[async_trait(?Send)]
+impl View for Consoles {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::Y) => {
+                self.move_console(-1)?;
+                return Ok(true);
+            }
+            KeyEvent::Pressed(Key::X) => {
+                self.move_console(1)?;
+                return Ok(true);
+            }
+            _ => {}
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    self.set_hidden(i, !val.as_bool().unwrap())?;
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                // The Games tab is reloaded once, rather than on every change
+                if self.changed {
+                    commands.send(Command::LibraryChanged).await?;
+                }
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Consoles {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
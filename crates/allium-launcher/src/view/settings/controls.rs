@@ -0,0 +1,545 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::analog::{AnalogSettings, AxisRange, StickCalibration};
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::macros::MacroSettings;
+use common::platform::layout::{ButtonLayout, ButtonSettings};
+use common::platform::rumble::{Rumble, RumbleSettings};
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::power::PowerSettings;
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{
+    ButtonHint, ButtonIcon, Label, Number, Percentage, Row, Select, SettingsList, Toggle, View,
+};
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+const CALIBRATE_INDEX: usize = 3;
+const CONTROLLERS_INDEX: usize = 4;
+const TEST_BUTTONS_INDEX: usize = 5;
+
+/// Keys that can play the macro with Menu, which aren't already taken by other hotkeys.
+const MACRO_HOTKEYS: [Key; 5] = [Key::Y, Key::A, Key::B, Key::L2, Key::R2];
+
+/// Keys that can toggle the battery saver with Menu, the first being none.
+const BATTERY_SAVER_HOTKEYS: [Option<Key>; 6] = [
+    None,
+    Some(Key::Y),
+    Some(Key::A),
+    Some(Key::B),
+    Some(Key::L2),
+    Some(Key::R2),
+];
+
+/// Range of the stick seen so far while calibrating.
+#[derive(Debug, Clone, Copy)]
+struct Calibrating {
+    x: AxisRange,
+    y: AxisRange,
+    position: Point,
+}
+
+impl Calibrating {
+    fn new(position: Point) -> Self {
+        let range = |value| AxisRange {
+            min: value,
+            center: value,
+            max: value,
+        };
+        Self {
+            x: range(position.x),
+            y: range(position.y),
+            position,
+        }
+    }
+
+    fn extend(&mut self, position: Point) {
+        self.x.min = self.x.min.min(position.x);
+        self.x.max = self.x.max.max(position.x);
+        self.y.min = self.y.min.min(position.y);
+        self.y.max = self.y.max.max(position.y);
+        self.position = position;
+    }
+
+    /// Finishes calibrating with the stick at rest, or `None` if it wasn't moved in every
+    /// direction.
+    fn finish(mut self) -> Option<StickCalibration> {
+        self.x.center = self.position.x;
+        self.y.center = self.position.y;
+        let valid = |r: &AxisRange| r.min < r.center && r.center < r.max;
+        (valid(&self.x) && valid(&self.y)).then_some(StickCalibration {
+            x: self.x,
+            y: self.y,
+        })
+    }
+}
+
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &[
+    "settings-controls-analog-enabled",
+    "settings-controls-analog-deadzone",
+    "settings-controls-analog-repeat-interval",
+    "settings-controls-calibrate",
+    "settings-controls-controllers",
+    "settings-controls-test-buttons",
+    "settings-controls-macro-hotkey",
+    "settings-controls-button-layout",
+    "settings-controls-rumble-strength",
+    "settings-controls-battery-saver-hotkey",
+];
+
+pub struct Controls {
+    res: Resources,
+    rect: Rect,
+    settings: AnalogSettings,
+    calibrating: Option<Calibrating>,
+    /// Names of the connected controllers shown in the list.
+    controllers: Vec<String>,
+    /// Whether button presses are being shown instead of handled. Holds the last pressed key.
+    testing: Option<Option<Key>>,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Controls {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+        let settings = AnalogSettings::load().unwrap_or_default();
+        let controllers = DefaultPlatform::controllers();
+        let macro_hotkey = MacroSettings::load().unwrap_or_default().hotkey;
+        let macro_hotkey = MACRO_HOTKEYS
+            .iter()
+            .position(|&k| k == macro_hotkey)
+            .unwrap_or_default();
+        let button_layout = ButtonSettings::load().unwrap_or_default().layout;
+        let rumble_strength = RumbleSettings::load().unwrap_or_default().strength;
+        let battery_saver_hotkey = PowerSettings::load()
+            .unwrap_or_default()
+            .battery_saver_hotkey;
+        let battery_saver_hotkey = BATTERY_SAVER_HOTKEYS
+            .iter()
+            .position(|&k| k == battery_saver_hotkey)
+            .unwrap_or_default();
+
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            LABELS.iter().map(|key| locale.t(key)).collect(),
+            vec![
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    settings.enabled,
+                    Alignment::Right,
+                )),
+                Box::new(Number::new(
+                    Point::zero(),
+                    settings.deadzone,
+                    0,
+                    90,
+                    5,
+                    |x: &i32| format!("{x}%"),
+                    Alignment::Right,
+                )),
+                Box::new(Number::new(
+                    Point::zero(),
+                    settings.repeat_interval_ms,
+                    50,
+                    500,
+                    25,
+                    |x: &i32| format!("{x}ms"),
+                    Alignment::Right,
+                )),
+                Box::new(Label::new(
+                    Point::zero(),
+                    calibration_text(&locale, &settings),
+                    Alignment::Right,
+                    None,
+                )),
+                Box::new(Label::new(
+                    Point::zero(),
+                    controllers_text(&locale, &controllers),
+                    Alignment::Right,
+                    None,
+                )),
+                Box::new(Label::new(
+                    Point::zero(),
+                    locale.t("settings-controls-test-buttons-start"),
+                    Alignment::Right,
+                    None,
+                )),
+                Box::new(Select::new(
+                    Point::zero(),
+                    macro_hotkey,
+                    MACRO_HOTKEYS.iter().map(|k| format!("{k:?}")).collect(),
+                    Alignment::Right,
+                )),
+                Box::new(Select::new(
+                    Point::zero(),
+                    button_layout as usize,
+                    vec![
+                        locale.t("settings-controls-button-layout-nintendo"),
+                        locale.t("settings-controls-button-layout-xbox"),
+                    ],
+                    Alignment::Right,
+                )),
+                Box::new(Percentage::new(
+                    Point::zero(),
+                    i32::from(rumble_strength),
+                    0,
+                    100,
+                    Alignment::Right,
+                )),
+                Box::new(Select::new(
+                    Point::zero(),
+                    battery_saver_hotkey,
+                    BATTERY_SAVER_HOTKEYS
+                        .iter()
+                        .map(|k| match k {
+                            Some(k) => format!("{k:?}"),
+                            None => locale.t("settings-controls-battery-saver-hotkey-none"),
+                        })
+                        .collect(),
+                    Alignment::Right,
+                )),
+            ],
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![ButtonHint::new(
+                res.clone(),
+                Point::zero(),
+                Key::B,
+                locale.t("button-back"),
+                Alignment::Right,
+            )],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            res,
+            rect,
+            settings,
+            calibrating: None,
+            controllers,
+            testing: None,
+            list,
+            button_hints,
+        }
+    }
+
+    fn set_calibrate_text(&mut self, text: String) {
+        self.list.set_right(
+            CALIBRATE_INDEX,
+            Box::new(Label::new(Point::zero(), text, Alignment::Right, None)),
+        );
+    }
+
+    async fn start_calibration(&mut self, commands: &Sender<Command>) -> Result<()> {
+        let locale = self.res.get::<Locale>();
+        let Some(position) = DefaultPlatform::analog_stick() else {
+            commands
+                .send(Command::Toast(
+                    locale.t("settings-controls-no-analog-stick"),
+                    Some(Duration::from_secs(3)),
+                ))
+                .await?;
+            return Ok(());
+        };
+        commands
+            .send(Command::Toast(
+                locale.t("settings-controls-calibrate-instructions"),
+                None,
+            ))
+            .await?;
+        drop(locale);
+        self.calibrating = Some(Calibrating::new(position));
+        Ok(())
+    }
+
+    async fn finish_calibration(&mut self, commands: &Sender<Command>, save: bool) -> Result<()> {
+        let Some(calibrating) = self.calibrating.take() else {
+            return Ok(());
+        };
+        commands.send(Command::DismissToast).await?;
+
+        if save {
+            let locale = self.res.get::<Locale>();
+            if let Some(calibration) = calibrating.finish() {
+                self.settings.calibration = Some(calibration);
+                self.settings.save()?;
+                commands
+                    .send(Command::Toast(
+                        locale.t("settings-needs-restart-for-effect"),
+                        Some(Duration::from_secs(5)),
+                    ))
+                    .await?;
+            } else {
+                commands
+                    .send(Command::Toast(
+                        locale.t("settings-controls-calibration-failed"),
+                        Some(Duration::from_secs(3)),
+                    ))
+                    .await?;
+            }
+        }
+
+        let text = calibration_text(&self.res.get::<Locale>(), &self.settings);
+        self.set_calibrate_text(text);
+        Ok(())
+    }
+
+    async fn start_test(&mut self, commands: &Sender<Command>) -> Result<()> {
+        let locale = self.res.get::<Locale>();
+        commands
+            .send(Command::Toast(
+                locale.t("settings-controls-test-buttons-instructions"),
+                None,
+            ))
+            .await?;
+        drop(locale);
+        self.testing = Some(None);
+        Ok(())
+    }
+
+    async fn test_key(&mut self, key: Key, commands: &Sender<Command>) -> Result<()> {
+        let Some(last) = self.testing else {
+            return Ok(());
+        };
+        self.testing = Some(Some(key));
+
+        let locale = self.res.get::<Locale>();
+        let right: Box<dyn View> = if key == Key::B && last == Some(Key::B) {
+            self.testing = None;
+            commands.send(Command::DismissToast).await?;
+            let text = locale.t("settings-controls-test-buttons-start");
+            Box::new(Label::new(Point::zero(), text, Alignment::Right, None))
+        } else if key == Key::Unknown {
+            let text = locale.t("settings-controls-unknown-button");
+            Box::new(Label::new(Point::zero(), text, Alignment::Right, None))
+        } else {
+            Box::new(ButtonIcon::new(Point::zero(), key, Alignment::Right))
+        };
+        drop(locale);
+        self.list.set_right(TEST_BUTTONS_INDEX, right);
+        Ok(())
+    }
+}
+
+fn calibration_text(locale: &Locale, settings: &AnalogSettings) -> String {
+    if settings.calibration.is_some() {
+        locale.t("settings-controls-calibrated")
+    } else {
+        locale.t("settings-controls-not-calibrated")
+    }
+}
+
+fn controllers_text(locale: &Locale, controllers: &[String]) -> String {
+    if controllers.is_empty() {
+        locale.t("settings-controls-no-controllers")
+    } else {
+        controllers.join(", ")
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Controls {
+    fn update(&mut self, _dt: Duration) {
+        let controllers = DefaultPlatform::controllers();
+        if controllers != self.controllers {
+            let text = controllers_text(&self.res.get::<Locale>(), &controllers);
+            self.list.set_right(
+                CONTROLLERS_INDEX,
+                Box::new(Label::new(Point::zero(), text, Alignment::Right, None)),
+            );
+            self.controllers = controllers;
+        }
+
+        let Some(calibrating) = self.calibrating.as_mut() else {
+            return;
+        };
+        let Some(position) = DefaultPlatform::analog_stick() else {
+            return;
+        };
+        if position != calibrating.position {
+            calibrating.extend(position);
+            self.set_calibrate_text(format!("{}, {}", position.x, position.y));
+        }
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self.calibrating.is_some() {
+            // The stick moves the selection while calibrating, so only A and B do anything
+            match event {
+                KeyEvent::Pressed(Key::A) => self.finish_calibration(&commands, true).await?,
+                KeyEvent::Pressed(Key::B) => self.finish_calibration(&commands, false).await?,
+                _ => {}
+            }
+            return Ok(true);
+        }
+
+        if self.testing.is_some() {
+            if let KeyEvent::Pressed(key) = event {
+                self.test_key(key, &commands).await?;
+            }
+            return Ok(true);
+        }
+
+        if event == KeyEvent::Pressed(Key::A) {
+            match self.list.selected() {
+                CALIBRATE_INDEX => {
+                    self.start_calibration(&commands).await?;
+                    return Ok(true);
+                }
+                TEST_BUTTONS_INDEX => {
+                    self.start_test(&commands).await?;
+                    return Ok(true);
+                }
+                _ => {}
+            }
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    match i {
+                        0 => self.settings.enabled = val.as_bool().unwrap(),
+                        1 => self.settings.deadzone = val.as_int().unwrap(),
+                        2 => self.settings.repeat_interval_ms = val.as_int().unwrap(),
+                        6 => {
+                            // alliumd reads the hotkey when it's pressed
+                            let mut macro_settings = MacroSettings::load()?;
+                            macro_settings.hotkey = MACRO_HOTKEYS[val.as_int().unwrap() as usize];
+                            macro_settings.save()?;
+                            continue;
+                        }
+                        7 => {
+                            ButtonSettings {
+                                layout: ButtonLayout::from_repr(val.as_int().unwrap() as usize)
+                                    .unwrap_or_default(),
+                            }
+                            .save()?;
+                        }
+                        8 => {
+                            // Rumbles are played at the saved strength, so preview it
+                            RumbleSettings {
+                                strength: val.as_int().unwrap() as u8,
+                            }
+                            .save()?;
+                            commands.send(Command::Rumble(Rumble::TICK)).await?;
+                            continue;
+                        }
+                        9 => {
+                            let mut power_settings = PowerSettings::load()?;
+                            power_settings.battery_saver_hotkey =
+                                BATTERY_SAVER_HOTKEYS[val.as_int().unwrap() as usize];
+                            power_settings.save()?;
+                        }
+                        _ => unreachable!("Invalid index"),
+                    }
+                    if i != 7 && i != 9 {
+                        self.settings.save()?;
+                    }
+
+                    let locale = self.res.get::<Locale>();
+                    commands
+                        .send(Command::Toast(
+                            locale.t("settings-needs-restart-for-effect"),
+                            Some(Duration::from_secs(5)),
+                        ))
+                        .await?;
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Controls {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
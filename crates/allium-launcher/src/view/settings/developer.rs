@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::developer::{DeveloperSettings, RemoteShell};
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, Row, SettingsList, Toggle, View};
+use common::wifi;
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+const SSH_INDEX: usize = 0;
+const TELNET_INDEX: usize = 1;
+const IP_ADDRESS_INDEX: usize = 2;
+/// Index of the command that connects to the SSH server. The telnet command follows it.
+const CONNECT_INDEX: usize = 3;
+
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &[
+    "settings-developer-ssh",
+    "settings-developer-telnet",
+    "settings-developer-ip-address",
+    "settings-developer-ssh-command",
+    "settings-developer-telnet-command",
+];
+
+pub struct Developer {
+    rect: Rect,
+    res: Resources,
+    settings: DeveloperSettings,
+    list: SettingsList,
+    ip_address: Option<String>,
+    check_ip_delay: Duration,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Developer {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let settings = DeveloperSettings::load().unwrap_or_default();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            LABELS.iter().map(|key| locale.t(key)).collect(),
+            vec![
+                toggle(&locale, RemoteShell::Ssh, settings.ssh),
+                toggle(&locale, RemoteShell::Telnet, settings.telnet),
+                value(String::new()),
+                value(String::new()),
+                value(String::new()),
+            ],
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-edit"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        let mut this = Self {
+            rect,
+            res,
+            settings,
+            list,
+            ip_address: wifi::ip_address(),
+            check_ip_delay: Duration::from_secs(1),
+            button_hints,
+        };
+        this.update_connection_info();
+        this
+    }
+
+    /// Shows the IP address, and how to connect to each remote shell that is turned on.
+    fn update_connection_info(&mut self) {
+        let locale = self.res.get::<Locale>();
+
+        let ip_address = self
+            .ip_address
+            .clone()
+            .unwrap_or_else(|| locale.t("settings-developer-not-connected"));
+        self.list.set_right(IP_ADDRESS_INDEX, value(ip_address));
+
+        for (i, shell) in [RemoteShell::Ssh, RemoteShell::Telnet]
+            .into_iter()
+            .enumerate()
+        {
+            let text = match self.ip_address.as_deref() {
+                Some(ip_address)
+                    if self.settings.is_enabled(shell) && shell.unavailable_reason().is_none() =>
+                {
+                    shell.connect_command(ip_address)
+                }
+                _ => locale.t("settings-developer-stopped"),
+            };
+            self.list.set_right(CONNECT_INDEX + i, value(text));
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Developer {
+    fn update(&mut self, dt: Duration) {
+        if self.check_ip_delay > dt {
+            self.check_ip_delay -= dt;
+            return;
+        }
+        self.check_ip_delay = Duration::from_secs(1);
+
+        let ip_address = wifi::ip_address();
+        if ip_address != self.ip_address {
+            self.ip_address = ip_address;
+            self.update_connection_info();
+        }
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    match i {
+                        SSH_INDEX => self.settings.ssh = val.as_bool().unwrap(),
+                        TELNET_INDEX => self.settings.telnet = val.as_bool().unwrap(),
+                        _ => unreachable!("Invalid index"),
+                    }
+                    // alliumd starts and stops the servers when the settings change
+                    self.settings.save()?;
+                    self.update_connection_info();
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Developer {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
+
+/// Returns the toggle for a remote shell, or why it can't be turned on in its place.
+fn toggle(locale: &Locale, shell: RemoteShell, enabled: bool) -> Box<dyn View> {
+    match shell.unavailable_reason() {
+        Some(reason) => value(locale.t(reason)),
+        None => Box::new(Toggle::new(Point::zero(), enabled, Alignment::Right)),
+    }
+}
+
+fn value(text: String) -> Box<dyn View> {
+    Box::new(Label::new(Point::zero(), text, Alignment::Right, None))
+}
@@ -6,18 +6,39 @@ use common::command::Command;
 use common::constants::SELECTION_MARGIN;
 
 use common::display::Display as DisplayTrait;
-use common::display::settings::DisplaySettings;
+use common::display::settings::{DisplaySettings, NightMode, Screensaver};
 use common::geom::{Alignment, Point, Rect, Size};
 use common::locale::Locale;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
 use common::stylesheet::Stylesheet;
-use common::view::{ButtonHint, ButtonIcon, Label, Percentage, Row, SettingsList, View};
+use common::view::{
+    ButtonHint, ButtonIcon, Label, Number, Percentage, Row, Select, SettingsList, View,
+};
 
 use tokio::sync::mpsc::Sender;
 
 use crate::view::settings::{ChildState, SettingsChild};
 
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &[
+    "settings-display-screen-resolution",
+    "settings-display-luminance",
+    "settings-display-hue",
+    "settings-display-saturation",
+    "settings-display-contrast",
+    "settings-display-red",
+    "settings-display-green",
+    "settings-display-blue",
+    "settings-display-idle-dim",
+    "settings-display-screensaver",
+    "settings-display-screensaver-minutes",
+    "settings-display-night-mode",
+    "settings-display-night-mode-strength",
+    "settings-display-night-mode-start",
+    "settings-display-night-mode-end",
+];
+
 pub struct Display {
     rect: Rect,
     settings: DisplaySettings,
@@ -35,6 +56,8 @@ impl Display {
         let locale = res.get::<Locale>();
         let styles = res.get::<Stylesheet>();
 
+        let idle_dim_off_label = locale.t("settings-display-idle-dim-off");
+
         let mut list = SettingsList::new(
             Rect::new(
                 x + 12,
@@ -42,16 +65,7 @@ impl Display {
                 w - 24,
                 h - 8 - ButtonIcon::diameter(&styles) - 8,
             ),
-            vec![
-                locale.t("settings-display-screen-resolution"),
-                locale.t("settings-display-luminance"),
-                locale.t("settings-display-hue"),
-                locale.t("settings-display-saturation"),
-                locale.t("settings-display-contrast"),
-                locale.t("settings-display-red"),
-                locale.t("settings-display-green"),
-                locale.t("settings-display-blue"),
-            ],
+            LABELS.iter().map(|key| locale.t(key)).collect(),
             vec![
                 Box::new(Label::new(
                     Point::zero(),
@@ -111,6 +125,75 @@ impl Display {
                     100,
                     Alignment::Right,
                 )),
+                Box::new(Number::new(
+                    Point::zero(),
+                    settings.idle_dim_seconds as i32,
+                    0,
+                    300,
+                    15,
+                    move |x: &i32| {
+                        if *x == 0 {
+                            idle_dim_off_label.clone()
+                        } else {
+                            format!("{x}s")
+                        }
+                    },
+                    Alignment::Right,
+                )),
+                Box::new(Select::new(
+                    Point::zero(),
+                    settings.screensaver as usize,
+                    vec![
+                        locale.t("settings-display-screensaver-off"),
+                        locale.t("settings-display-screensaver-clock"),
+                        locale.t("settings-display-screensaver-box-art"),
+                    ],
+                    Alignment::Right,
+                )),
+                Box::new(Number::new(
+                    Point::zero(),
+                    settings.screensaver_minutes as i32,
+                    1,
+                    30,
+                    1,
+                    i32::to_string,
+                    Alignment::Right,
+                )),
+                Box::new(Select::new(
+                    Point::zero(),
+                    settings.night_mode as usize,
+                    vec![
+                        locale.t("settings-display-night-mode-off"),
+                        locale.t("settings-display-night-mode-on"),
+                        locale.t("settings-display-night-mode-scheduled"),
+                    ],
+                    Alignment::Right,
+                )),
+                Box::new(Percentage::new(
+                    Point::zero(),
+                    i32::from(settings.night_mode_strength),
+                    0,
+                    100,
+                    Alignment::Right,
+                )),
+                Box::new(Number::new(
+                    Point::zero(),
+                    settings.night_mode_start as i32,
+                    0,
+                    23,
+                    1,
+                    hour,
+                    Alignment::Right,
+                )),
+                Box::new(Number::new(
+                    Point::zero(),
+                    settings.night_mode_end as i32,
+                    0,
+                    23,
+                    1,
+                    hour,
+                    Alignment::Right,
+                )),
             ],
             styles.ui_font.size + SELECTION_MARGIN,
         );
@@ -213,6 +296,21 @@ impl View for Display {
                         5 => self.settings.r = val.as_int().unwrap() as u8,
                         6 => self.settings.g = val.as_int().unwrap() as u8,
                         7 => self.settings.b = val.as_int().unwrap() as u8,
+                        8 => self.settings.idle_dim_seconds = val.as_int().unwrap() as u32,
+                        9 => {
+                            self.settings.screensaver =
+                                Screensaver::from_repr(val.as_int().unwrap() as usize)
+                                    .unwrap_or_default();
+                        }
+                        10 => self.settings.screensaver_minutes = val.as_int().unwrap() as u32,
+                        11 => {
+                            self.settings.night_mode =
+                                NightMode::from_repr(val.as_int().unwrap() as usize)
+                                    .unwrap_or_default();
+                        }
+                        12 => self.settings.night_mode_strength = val.as_int().unwrap() as u8,
+                        13 => self.settings.night_mode_start = val.as_int().unwrap() as u32,
+                        14 => self.settings.night_mode_end = val.as_int().unwrap() as u32,
                         _ => unreachable!("Invalid index"),
                     }
 
@@ -252,6 +350,11 @@ impl View for Display {
     }
 }
 
+/// Formats an hour of the day of the night mode schedule.
+fn hour(hour: &i32) -> String {
+    format!("{hour:02}:00")
+}
+
 impl SettingsChild for Display {
     fn save(&self) -> ChildState {
         ChildState {
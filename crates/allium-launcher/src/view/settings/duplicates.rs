@@ -0,0 +1,457 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::{ALLIUM_GAMES_DIR, SELECTION_MARGIN};
+use common::database::Database;
+use common::display::Display;
+use common::duplicates::{self, ChecksumFile, DuplicateGroup, DuplicateScan};
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Dialog, Label, Row, SettingsList, Toggle, View};
+use log::{error, warn};
+use tokio::sync::mpsc::Sender;
+use tokio::sync::oneshot;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Finds duplicate games, and lets the extra copies be hidden from the games list or deleted.
+pub struct Duplicates {
+    rect: Rect,
+    res: Resources,
+    list: SettingsList,
+    status: Label<String>,
+    button_hints: Row<ButtonHint<String>>,
+    /// The scan, while the checksums it needs are computed on a blocking task.
+    scan: Option<(DuplicateScan, oneshot::Receiver<Vec<(ChecksumFile, u32)>>)>,
+    groups: Vec<DuplicateGroup>,
+    hidden: HashSet<PathBuf>,
+    /// The group whose games are shown.
+    group: Option<usize>,
+    /// Asks to confirm deleting the selected game.
+    dialog: Option<Dialog>,
+    /// Whether any games were hidden or deleted, so that the games list is loaded again.
+    changed: bool,
+    /// Group to select once the scan is done.
+    selected: usize,
+    dirty: bool,
+}
+
+impl Duplicates {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            Vec::new(),
+            Vec::new(),
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let locale = res.get::<Locale>();
+        let status = Label::new(
+            Point::new(x + 12, y + 8),
+            locale.t("settings-duplicates-scanning"),
+            Alignment::Left,
+            Some(w - 24),
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-select"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        let database = res.get::<Database>().clone();
+        let scan = match DuplicateScan::new(&database) {
+            Ok(mut scan) => {
+                let files = scan.take_uncached();
+                let (tx, rx) = oneshot::channel();
+                tokio::task::spawn_blocking(move || {
+                    tx.send(duplicates::compute_checksums(files)).ok();
+                });
+                Some((scan, rx))
+            }
+            Err(e) => {
+                error!("failed to scan for duplicates: {}", e);
+                None
+            }
+        };
+        let hidden = database.select_hidden_games().unwrap_or_else(|e| {
+            warn!("failed to load hidden games: {}", e);
+            HashSet::new()
+        });
+
+        let mut this = Self {
+            rect,
+            res,
+            list,
+            status,
+            button_hints,
+            scan,
+            groups: Vec::new(),
+            hidden,
+            group: None,
+            dialog: None,
+            changed: false,
+            selected: state.map(|state| state.selected).unwrap_or_default(),
+            dirty: true,
+        };
+        if this.scan.is_none() {
+            this.show_groups(0);
+        }
+        this
+    }
+
+    fn finish_scan(&mut self, computed: Vec<(ChecksumFile, u32)>) {
+        let Some((scan, _)) = self.scan.take() else {
+            return;
+        };
+        self.groups = scan
+            .finish(&self.res.get::<Database>(), computed)
+            .unwrap_or_else(|e| {
+                error!("failed to scan for duplicates: {}", e);
+                Vec::new()
+            });
+        self.show_groups(self.selected);
+    }
+
+    fn show_groups(&mut self, selected: usize) {
+        self.group = None;
+
+        let locale = self.res.get::<Locale>();
+        let labels = self.groups.iter().map(|group| group.name.clone()).collect();
+        let values = self
+            .groups
+            .iter()
+            .map(|group| {
+                let mut map = HashMap::new();
+                map.insert("count".into(), group.games.len().into());
+                Box::new(Label::new(
+                    Point::zero(),
+                    locale.ta("settings-duplicates-count", &map),
+                    Alignment::Right,
+                    None,
+                )) as Box<dyn View>
+            })
+            .collect();
+        self.status.set_text(locale.t("settings-duplicates-none"));
+        drop(locale);
+
+        self.list.set_items(labels, values);
+        self.list
+            .select(selected.min(self.groups.len().saturating_sub(1)));
+        if let Some(hint) = self.button_hints.get_mut(0) {
+            hint.set_text(self.res.get::<Locale>().t("button-select"));
+        }
+        if self.button_hints.len() > 2 {
+            self.button_hints.pop();
+        }
+        self.dirty = true;
+    }
+
+    fn show_group(&mut self, group: usize, selected: usize) {
+        let games = &self.groups[group].games;
+        let labels = games
+            .iter()
+            .map(|game| {
+                game.path
+                    .strip_prefix(ALLIUM_GAMES_DIR.as_path())
+                    .unwrap_or(&game.path)
+                    .display()
+                    .to_string()
+            })
+            .collect();
+        let values = games
+            .iter()
+            .map(|game| {
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    self.hidden.contains(&game.path),
+                    Alignment::Right,
+                )) as Box<dyn View>
+            })
+            .collect();
+        self.list.set_items(labels, values);
+        self.list.select(selected.min(games.len() - 1));
+        self.group = Some(group);
+
+        let locale = self.res.get::<Locale>();
+        if let Some(hint) = self.button_hints.get_mut(0) {
+            hint.set_text(locale.t("settings-duplicates-hide"));
+        }
+        if self.button_hints.len() == 2 {
+            self.button_hints.push(ButtonHint::new(
+                self.res.clone(),
+                Point::zero(),
+                Key::X,
+                locale.t("button-delete"),
+                Alignment::Right,
+            ));
+        }
+        self.dirty = true;
+    }
+
+    fn set_hidden(&mut self, index: usize, hidden: bool) -> Result<()> {
+        let Some(group) = self.group else {
+            return Ok(());
+        };
+        let path = &self.groups[group].games[index].path;
+        self.res.get::<Database>().set_hidden(path, hidden)?;
+        if hidden {
+            self.hidden.insert(path.clone());
+        } else {
+            self.hidden.remove(path);
+        }
+        self.changed = true;
+        Ok(())
+    }
+
+    fn confirm_delete(&mut self, index: usize) {
+        let locale = self.res.get::<Locale>();
+        let mut map = HashMap::new();
+        map.insert("name".into(), self.list.left(index).to_string().into());
+        let title = locale.ta("settings-duplicates-delete-title", &map);
+        let message = locale.t("settings-duplicates-delete-message");
+        drop(locale);
+        self.dialog = Some(Dialog::confirm(
+            self.rect,
+            self.res.clone(),
+            title,
+            Some(message),
+        ));
+    }
+
+    async fn delete(&mut self, index: usize, commands: Sender<Command>) -> Result<()> {
+        let Some(group) = self.group else {
+            return Ok(());
+        };
+        let game = self.groups[group].games[index].clone();
+        let result = duplicates::delete_game(&self.res.get::<Database>(), &game.path);
+        if let Err(e) = result {
+            error!("failed to delete {}: {}", game.path.display(), e);
+            let mut map = HashMap::new();
+            map.insert("name".into(), game.name.into());
+            let text = self
+                .res
+                .get::<Locale>()
+                .ta("settings-duplicates-delete-failed", &map);
+            commands
+                .send(Command::Toast(text, Some(Duration::from_secs(5))))
+                .await?;
+            return Ok(());
+        }
+        self.hidden.remove(&game.path);
+        self.changed = true;
+
+        let games = &mut self.groups[group].games;
+        games.remove(index);
+        if games.len() < 2 {
+            self.groups.remove(group);
+            self.show_groups(group);
+        } else {
+            self.show_group(group, index);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Duplicates {
+    fn update(&mut self, dt: Duration) {
+        if let Some((_, rx)) = self.scan.as_mut() {
+            match rx.try_recv() {
+                Ok(computed) => self.finish_scan(computed),
+                Err(oneshot::error::TryRecvError::Empty) => {}
+                Err(oneshot::error::TryRecvError::Closed) => self.finish_scan(Vec::new()),
+            }
+        }
+
+        self.children_mut().iter_mut().for_each(|c| c.update(dt));
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.list.set_should_draw();
+            self.status.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        if self.scan.is_some() || self.groups.is_empty() {
+            drawn |= self.status.should_draw() && self.status.draw(display, styles)?;
+        } else {
+            drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        }
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        if let Some(dialog) = self.dialog.as_mut() {
+            // Keep the dialog on top of anything drawn underneath it
+            if drawn {
+                dialog.set_should_draw();
+            }
+            drawn |= dialog.should_draw() && dialog.draw(display, styles)?;
+        }
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.list.should_draw()
+            || self.status.should_draw()
+            || self.button_hints.should_draw()
+            || self.dialog.as_ref().is_some_and(|d| d.should_draw())
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        if let Some(dialog) = self.dialog.as_mut() {
+            dialog.set_should_draw();
+        }
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(dialog) = self.dialog.as_mut() {
+            dialog
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?;
+            while let Some(command) = bubble.pop_front() {
+                match command {
+                    Command::ValueChanged(_, _) => {
+                        self.delete(self.list.selected(), commands.clone()).await?;
+                    }
+                    Command::CloseView => {
+                        self.dialog = None;
+                        self.dirty = true;
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(true);
+        }
+
+        let selected = self.list.selected();
+        if let Some(group) = self.group {
+            match event {
+                KeyEvent::Pressed(Key::X) => {
+                    self.confirm_delete(selected);
+                    return Ok(true);
+                }
+                KeyEvent::Pressed(Key::B) => {
+                    self.show_groups(group);
+                    return Ok(true);
+                }
+                _ => {}
+            }
+        } else if event == KeyEvent::Pressed(Key::A)
+            && self.scan.is_none()
+            && !self.groups.is_empty()
+        {
+            self.show_group(selected, 0);
+            return Ok(true);
+        }
+
+        if self.scan.is_none()
+            && !self.groups.is_empty()
+            && self
+                .list
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    self.set_hidden(i, val.as_bool().unwrap())?;
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                if self.changed {
+                    commands.send(Command::LibraryChanged).await?;
+                }
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.status, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.status, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Duplicates {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: match self.group {
+                Some(group) => group,
+                None if self.scan.is_some() => self.selected,
+                None => self.list.selected(),
+            },
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::geom::{Alignment, Point, Rect};
+use common::hotkeys::{HOTKEYS, HotkeySettings};
+use common::locale::Locale;
+use common::macros::MacroSettings;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::power::PowerSettings;
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Row, Select, SettingsList, Toggle, View};
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &[
+    "settings-hotkeys-enabled",
+    "settings-hotkeys-menu",
+    "settings-hotkeys-save-state",
+    "settings-hotkeys-load-state",
+    "settings-hotkeys-fast-forward",
+];
+
+/// Settings for the RetroArch hotkeys that are written into its config when a game is launched.
+pub struct Hotkeys {
+    rect: Rect,
+    res: Resources,
+    settings: HotkeySettings,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Hotkeys {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+        let settings = HotkeySettings::load().unwrap_or_default();
+
+        let mut keys = vec![locale.t("settings-hotkeys-off")];
+        keys.extend(HOTKEYS.iter().map(|k| format!("{k:?}")));
+        let select = |key: Option<Key>| -> Box<dyn View> {
+            let selected = key
+                .and_then(|key| HOTKEYS.iter().position(|&k| k == key))
+                .map_or(0, |i| i + 1);
+            Box::new(Select::new(
+                Point::zero(),
+                selected,
+                keys.clone(),
+                Alignment::Right,
+            ))
+        };
+
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            LABELS.iter().map(|key| locale.t(key)).collect(),
+            vec![
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    settings.enabled,
+                    Alignment::Right,
+                )),
+                select(settings.menu),
+                select(settings.save_state),
+                select(settings.load_state),
+                select(settings.fast_forward),
+            ],
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![ButtonHint::new(
+                res.clone(),
+                Point::zero(),
+                Key::B,
+                locale.t("button-back"),
+                Alignment::Right,
+            )],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            res,
+            settings,
+            list,
+            button_hints,
+        }
+    }
+
+    /// Warns if a hotkey won't be bound, because its key is taken by alliumd or by an earlier
+    /// hotkey.
+    async fn check_conflict(&self, index: usize, commands: &Sender<Command>) -> Result<()> {
+        let reserved = HotkeySettings::reserved_keys(
+            &PowerSettings::load().unwrap_or_default(),
+            &MacroSettings::load().unwrap_or_default(),
+        );
+        let bindings = self.settings.bindings(&reserved);
+        let key = match index {
+            1 => self.settings.menu,
+            2 => self.settings.save_state,
+            3 => self.settings.load_state,
+            _ => self.settings.fast_forward,
+        };
+        if key.is_some() && bindings[index - 1].1.is_none() {
+            let locale = self.res.get::<Locale>();
+            commands
+                .send(Command::Toast(
+                    locale.t("settings-hotkeys-conflict"),
+                    Some(Duration::from_secs(3)),
+                ))
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Hotkeys {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    let key = || {
+                        (val.as_int().unwrap() as usize)
+                            .checked_sub(1)
+                            .map(|i| HOTKEYS[i])
+                    };
+                    match i {
+                        0 => self.settings.enabled = val.as_bool().unwrap(),
+                        1 => self.settings.menu = key(),
+                        2 => self.settings.save_state = key(),
+                        3 => self.settings.load_state = key(),
+                        4 => self.settings.fast_forward = key(),
+                        _ => unreachable!("Invalid index"),
+                    }
+                    // The RetroArch config is written when the next game is launched
+                    self.settings.save()?;
+                    if i > 0 {
+                        self.check_conflict(i, &commands).await?;
+                    }
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Hotkeys {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
@@ -10,12 +10,19 @@ use common::locale::{Locale, LocaleSettings};
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
 use common::stylesheet::Stylesheet;
-use common::view::{ButtonHint, ButtonIcon, Row, Select, SettingsList, View};
+use common::view::{ButtonHint, ButtonIcon, Label, Row, Select, SettingsList, Toggle, View};
 
 use tokio::sync::mpsc::Sender;
 
 use crate::view::settings::{ChildState, SettingsChild};
 
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &[
+    "settings-language-language",
+    "settings-language-translation-debug",
+    "settings-language-missing-keys",
+];
+
 pub struct Language {
     rect: Rect,
     langs: Vec<String>,
@@ -44,19 +51,32 @@ impl Language {
                 w - 24,
                 h - 8 - ButtonIcon::diameter(&styles) - 8,
             ),
-            vec![locale.t("settings-language-language")],
-            vec![Box::new(Select::new(
-                Point::zero(),
-                lang,
-                langs
-                    .iter()
-                    .map(|l| {
-                        let name = locale.t(&format!("lang-{}", l));
-                        if name.is_empty() { l.clone() } else { name }
-                    })
-                    .collect(),
-                Alignment::Right,
-            ))],
+            LABELS.iter().map(|key| locale.t(key)).collect(),
+            vec![
+                Box::new(Select::new(
+                    Point::zero(),
+                    lang,
+                    langs
+                        .iter()
+                        .map(|l| {
+                            let name = locale.t(&format!("lang-{}", l));
+                            if name.is_empty() { l.clone() } else { name }
+                        })
+                        .collect(),
+                    Alignment::Right,
+                )),
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    settings.translation_debug,
+                    Alignment::Right,
+                )),
+                Box::new(Label::new(
+                    Point::zero(),
+                    locale.missing_count().to_string(),
+                    Alignment::Right,
+                    None,
+                )),
+            ],
             styles.ui_font.size + SELECTION_MARGIN,
         );
         if let Some(state) = state {
@@ -146,6 +166,7 @@ impl View for Language {
                             .settings
                             .lang
                             .clone_from(&self.langs[val.as_int().unwrap() as usize]),
+                        1 => self.settings.translation_debug = val.as_bool().unwrap(),
                         _ => unreachable!("Invalid index"),
                     }
 
@@ -0,0 +1,249 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::lock::LockSettings;
+use common::pin::KeyPin;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, Row, SettingsList, Toggle, View};
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &["settings-lock-enabled", "settings-lock-pattern"];
+
+pub struct Lock {
+    rect: Rect,
+    res: Resources,
+    settings: LockSettings,
+    /// Buttons pressed so far while recording a new pattern.
+    recording: Option<Vec<Key>>,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Lock {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let settings = LockSettings::load().unwrap_or_default();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            LABELS.iter().map(|key| locale.t(key)).collect(),
+            vec![
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    settings.enabled,
+                    Alignment::Right,
+                )),
+                Box::new(Label::new(
+                    Point::zero(),
+                    pattern_text(&locale, settings.pattern.len(), false),
+                    Alignment::Right,
+                    None,
+                )),
+            ],
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![ButtonHint::new(
+                res.clone(),
+                Point::zero(),
+                Key::B,
+                locale.t("button-back"),
+                Alignment::Right,
+            )],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            res,
+            settings,
+            recording: None,
+            list,
+            button_hints,
+        }
+    }
+
+    fn update_pattern(&mut self) {
+        let locale = self.res.get::<Locale>();
+        let text = match self.recording.as_ref() {
+            Some(pattern) => pattern_text(&locale, pattern.len(), true),
+            None => pattern_text(&locale, self.settings.pattern.len(), false),
+        };
+        self.list.set_right(
+            1,
+            Box::new(Label::new(Point::zero(), text, Alignment::Right, None)),
+        );
+    }
+
+    fn start_recording(&mut self) {
+        self.recording = Some(Vec::new());
+        self.list.select(1);
+        self.update_pattern();
+    }
+
+    /// Handles a key while recording a pattern. Start saves the pattern, and select cancels.
+    fn record(&mut self, key: Key) -> Result<()> {
+        let Some(pattern) = self.recording.as_mut() else {
+            return Ok(());
+        };
+        match key {
+            Key::Start => {
+                if !pattern.is_empty() {
+                    self.settings.pattern = KeyPin::new(pattern);
+                    self.settings.save()?;
+                }
+                self.recording = None;
+            }
+            Key::Select => self.recording = None,
+            key if LockSettings::is_pattern_key(key) => pattern.push(key),
+            _ => {}
+        }
+        self.update_pattern();
+        Ok(())
+    }
+}
+
+/// Describes a pattern by its number of buttons, without revealing it.
+fn pattern_text(locale: &Locale, len: usize, recording: bool) -> String {
+    if recording {
+        let mut map = HashMap::new();
+        map.insert("count".into(), len.into());
+        locale.ta("settings-lock-pattern-recording", &map)
+    } else if len == 0 {
+        locale.t("settings-lock-pattern-not-set")
+    } else {
+        let mut map = HashMap::new();
+        map.insert("count".into(), len.into());
+        locale.ta("settings-lock-pattern-set", &map)
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Lock {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self.recording.is_some() {
+            if let KeyEvent::Pressed(key) = event {
+                self.record(key)?;
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) if self.list.selected() == 1 => {
+                self.start_recording();
+                return Ok(true);
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                return Ok(true);
+            }
+            _ => {}
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    match i {
+                        0 => {
+                            self.settings.enabled = val.as_bool().unwrap();
+                            self.settings.save()?;
+                            if self.settings.enabled && self.settings.pattern.is_empty() {
+                                self.start_recording();
+                            }
+                        }
+                        _ => unreachable!("Invalid index"),
+                    }
+                }
+            }
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Lock {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::display::Display as DisplayTrait;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::logging::LogComponent;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, Row, SettingsList, View};
+use tokio::sync::mpsc::Sender;
+
+use crate::view::Manual;
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Lists the log files of alliumd, the launcher and the menu, and opens them in the text reader,
+/// so that crashes can be looked into on the device.
+pub struct Logs {
+    rect: Rect,
+    res: Resources,
+    files: Vec<PathBuf>,
+    list: SettingsList,
+    viewer: Option<Manual>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl Logs {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let files = LogComponent::all_files();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let (left, right): (Vec<String>, Vec<Box<dyn View>>) = if files.is_empty() {
+            (
+                vec![locale.t("settings-logs-empty")],
+                vec![value(String::new())],
+            )
+        } else {
+            files
+                .iter()
+                .map(|path| {
+                    let name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    let size = fs::metadata(path).map(|m| m.len()).unwrap_or_default();
+                    (name, value(format!("{:.1} KB", size as f64 / 1024.0)))
+                })
+                .unzip()
+        };
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            left,
+            right,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected.min(files.len().saturating_sub(1)));
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-select"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            res,
+            files,
+            list,
+            viewer: None,
+            button_hints,
+            dirty: false,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Logs {
+    fn update(&mut self, dt: Duration) {
+        if let Some(viewer) = self.viewer.as_mut() {
+            viewer.update(dt);
+        }
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if self.dirty {
+            display.load(self.rect)?;
+            self.dirty = false;
+        }
+
+        if let Some(viewer) = self.viewer.as_mut() {
+            return viewer.draw(display, styles);
+        }
+
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        if let Some(viewer) = self.viewer.as_ref() {
+            return self.dirty || viewer.should_draw();
+        }
+        self.dirty || self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        if let Some(viewer) = self.viewer.as_mut() {
+            viewer.set_should_draw();
+            return;
+        }
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(viewer) = self.viewer.as_mut() {
+            let handled = viewer.handle_key_event(event, commands, bubble).await?;
+            let mut closed = false;
+            bubble.retain(|c| match c {
+                Command::CloseView => {
+                    closed = true;
+                    false
+                }
+                _ => true,
+            });
+            if closed {
+                self.viewer = None;
+                self.dirty = true;
+                self.set_should_draw();
+            }
+            return Ok(handled);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                if let Some(path) = self.files.get(self.list.selected()) {
+                    self.viewer = Some(Manual::new(self.rect, self.res.clone(), path.clone()));
+                    self.dirty = true;
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        if let Some(viewer) = self.viewer.as_ref() {
+            return vec![viewer];
+        }
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        if let Some(viewer) = self.viewer.as_mut() {
+            return vec![viewer];
+        }
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Logs {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
+
+fn value(text: String) -> Box<dyn View> {
+    Box::new(Label::new(Point::zero(), text, Alignment::Right, None))
+}
@@ -0,0 +1,348 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::display::Display as DisplayTrait;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::lock::LockSettings;
+use common::parental::ParentalSettings;
+use common::pin::KeyPin;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::reset::ResetTarget;
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Dialog, Label, NullView, Row, SettingsList, View};
+use strum::IntoEnumIterator;
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Locale keys of the rows' labels, one for each `ResetTarget` in order.
+pub const LABELS: &[&str] = &[
+    "settings-maintenance-reset-database",
+    "settings-maintenance-reset-theme",
+    "settings-maintenance-reset-wifi",
+    "settings-maintenance-reset-all",
+];
+
+/// Resets parts of Allium's data, after asking for confirmation. Everything that's reset is
+/// backed up first.
+pub struct Maintenance {
+    rect: Rect,
+    res: Resources,
+    targets: Vec<ResetTarget>,
+    list: SettingsList,
+    /// PINs left to enter before the selected reset is confirmed.
+    unlocking: Option<Unlocking>,
+    prompt: Label<String>,
+    dialog: Option<Dialog>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+/// The lock screen pattern and parental controls PIN, which must be entered before a reset so
+/// that it can't be used to get around them.
+struct Unlocking {
+    target: ResetTarget,
+    /// Locale key of the prompt for each PIN, and the PIN.
+    pins: Vec<(&'static str, KeyPin)>,
+    entered: Vec<Key>,
+}
+
+impl Maintenance {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let targets: Vec<ResetTarget> = ResetTarget::iter().collect();
+        let labels: Vec<String> = LABELS.iter().map(|key| locale.t(key)).collect();
+        let values = labels
+            .iter()
+            .map(|_| Box::new(NullView) as Box<dyn View>)
+            .collect();
+
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            labels,
+            values,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-select"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        let prompt = Label::new(
+            Point::new(x + w as i32 / 2, y + h as i32 / 2),
+            String::new(),
+            Alignment::Center,
+            None,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            res,
+            targets,
+            list,
+            unlocking: None,
+            prompt,
+            dialog: None,
+            button_hints,
+            dirty: false,
+        }
+    }
+
+    /// Asks for the PINs that are set before confirming the reset.
+    fn start_reset(&mut self, target: ResetTarget) {
+        let mut pins = Vec::new();
+        let lock = LockSettings::load().unwrap_or_default();
+        if !lock.pattern.is_empty() {
+            pins.push(("settings-maintenance-enter-lock-pattern", lock.pattern));
+        }
+        let parental = ParentalSettings::load().unwrap_or_default();
+        if !parental.pin.is_empty() {
+            pins.push(("settings-maintenance-enter-parental-pin", parental.pin));
+        }
+
+        if pins.is_empty() {
+            self.confirm_reset(target);
+        } else {
+            self.unlocking = Some(Unlocking {
+                target,
+                pins,
+                entered: Vec::new(),
+            });
+            self.update_prompt(None);
+        }
+    }
+
+    /// Shows the prompt for the next PIN to enter, or an error instead.
+    fn update_prompt(&mut self, error: Option<&str>) {
+        let Some(unlocking) = self.unlocking.as_ref() else {
+            return;
+        };
+        let locale = self.res.get::<Locale>();
+        let text = match error {
+            Some(key) => locale.t(key),
+            None => {
+                let mut map = HashMap::new();
+                map.insert("count".into(), unlocking.entered.len().into());
+                locale.ta(unlocking.pins[0].0, &map)
+            }
+        };
+        self.prompt.set_text(text);
+        self.dirty = true;
+    }
+
+    /// Handles a key while entering a PIN. The reset is confirmed once every PIN is entered, and
+    /// select cancels it.
+    fn unlock(&mut self, key: Key) {
+        let Some(unlocking) = self.unlocking.as_mut() else {
+            return;
+        };
+        match key {
+            Key::Select => {
+                self.unlocking = None;
+                self.dirty = true;
+                return;
+            }
+            key if LockSettings::is_pattern_key(key) => unlocking.entered.push(key),
+            _ => return,
+        }
+
+        let pin = &unlocking.pins[0].1;
+        if unlocking.entered.len() < pin.len() {
+            self.update_prompt(None);
+        } else if pin.verify(&unlocking.entered) {
+            unlocking.pins.remove(0);
+            unlocking.entered.clear();
+            if unlocking.pins.is_empty() {
+                let target = unlocking.target;
+                self.unlocking = None;
+                self.dirty = true;
+                self.confirm_reset(target);
+            } else {
+                self.update_prompt(None);
+            }
+        } else {
+            unlocking.entered.clear();
+            self.update_prompt(Some("settings-maintenance-wrong-pin"));
+        }
+    }
+
+    fn confirm_reset(&mut self, target: ResetTarget) {
+        let locale = self.res.get::<Locale>();
+        let title = locale.t(&format!("settings-maintenance-confirm-{}", target.name()));
+        let message = locale.t("settings-maintenance-confirm-message");
+        drop(locale);
+        self.dialog = Some(Dialog::confirm(
+            self.rect,
+            self.res.clone(),
+            title,
+            Some(message),
+        ));
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Maintenance {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.unlocking.is_some() {
+            if self.dirty || self.prompt.should_draw() {
+                display.load(self.rect)?;
+                drawn |= self.prompt.draw(display, styles)?;
+                self.dirty = false;
+            }
+            return Ok(drawn);
+        }
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.list.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        if let Some(dialog) = self.dialog.as_mut() {
+            // Keep the dialog on top of anything drawn underneath it
+            if drawn {
+                dialog.set_should_draw();
+            }
+            drawn |= dialog.should_draw() && dialog.draw(display, styles)?;
+        }
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.prompt.should_draw()
+            || self.list.should_draw()
+            || self.button_hints.should_draw()
+            || self.dialog.as_ref().is_some_and(|d| d.should_draw())
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        if let Some(dialog) = self.dialog.as_mut() {
+            dialog.set_should_draw();
+        }
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self.unlocking.is_some() {
+            if let KeyEvent::Pressed(key) = event {
+                self.unlock(key);
+            }
+            return Ok(true);
+        }
+
+        if let Some(dialog) = self.dialog.as_mut() {
+            dialog
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?;
+            while let Some(command) = bubble.pop_front() {
+                match command {
+                    Command::ValueChanged(_, _) => {
+                        let target = self.targets[self.list.selected()];
+                        commands.send(Command::Reset(target)).await?;
+                    }
+                    Command::CloseView => {
+                        self.dialog = None;
+                        self.dirty = true;
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                self.start_reset(self.targets[self.list.selected()]);
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Maintenance {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
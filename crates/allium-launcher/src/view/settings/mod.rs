@@ -1,18 +1,61 @@
 mod about;
+mod battery;
+mod bios;
+mod bluetooth;
 mod clock;
+mod consoles;
+mod controls;
+mod developer;
+mod device_sync;
 mod display;
+mod duplicates;
+mod hotkeys;
 mod language;
+mod lock;
+mod logs;
+mod maintenance;
+mod network;
+mod parental;
 mod power;
+mod recents;
+mod registry;
+mod save_backups;
+mod save_sync;
+mod sound;
+mod tabs;
 mod theme;
+mod webhook;
 mod wifi;
+mod wifi_networks;
 
 use crate::view::settings::clock::Clock;
 
 use self::about::About;
+use self::battery::Battery;
+use self::bios::Bios;
+use self::bluetooth::Bluetooth;
+use self::consoles::Consoles;
+use self::controls::Controls;
+use self::developer::Developer;
+use self::device_sync::DeviceSync;
 use self::display::Display;
+use self::duplicates::Duplicates;
+use self::hotkeys::Hotkeys;
 use self::language::Language;
+use self::lock::Lock;
+use self::logs::Logs;
+use self::maintenance::Maintenance;
+use self::network::Network;
+use self::parental::Parental;
 use self::power::Power;
+use self::recents::Recents;
+use self::registry::SettingsItem;
+use self::save_backups::SaveBackups;
+use self::save_sync::SaveSync;
+use self::sound::Sound;
+use self::tabs::Tabs;
 use self::theme::Theme;
+use self::webhook::Webhook;
 use self::wifi::Wifi;
 
 use std::collections::VecDeque;
@@ -20,15 +63,14 @@ use std::fmt::Debug;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use common::command::Command;
-use common::constants::SELECTION_MARGIN;
+use common::command::{Command, Value};
 use common::display::Display as DisplayTrait;
 use common::geom::{Alignment, Point, Rect};
 use common::locale::Locale;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
 use common::stylesheet::Stylesheet;
-use common::view::{ButtonHint, ButtonIcon, Row, ScrollList, View};
+use common::view::{ButtonHint, ButtonIcon, Keyboard, Row, ScrollList, View};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::Sender;
 
@@ -60,11 +102,64 @@ pub struct Settings {
     list: ScrollList,
     child: Option<Box<dyn SettingsChild>>,
     button_hints: Row<ButtonHint<String>>,
+    keyboard: Option<Keyboard>,
+    search: Option<Search>,
     has_wifi: bool,
     dirty: bool,
 }
 
+/// Settings found by searching, listed in place of the pages below the query.
+#[derive(Debug)]
+struct Search {
+    query: String,
+    items: Vec<SettingsItem>,
+    /// Page selected before searching, selected again once the search is closed.
+    previous: usize,
+}
+
 impl Settings {
+    /// Locale keys of the entries. The last one imports Onion data instead of opening a page.
+    const ENTRIES: [&str; 28] = [
+        "settings-wifi",
+        "settings-network",
+        "settings-clock",
+        "settings-power",
+        "settings-lock",
+        "settings-parental",
+        "settings-battery",
+        "settings-display",
+        "settings-controls",
+        "settings-bluetooth",
+        "settings-theme",
+        "settings-language",
+        "settings-about",
+        "settings-device-sync",
+        "settings-webhook",
+        "settings-save-sync",
+        "settings-save-backups",
+        "settings-duplicates",
+        "settings-hotkeys",
+        "settings-sound",
+        "settings-developer",
+        "settings-logs",
+        "settings-bios",
+        "settings-maintenance",
+        "settings-tabs",
+        "settings-consoles",
+        "settings-recents",
+        "settings-import-onion-data",
+    ];
+
+    /// Returns the locale keys of the entries shown on this device. Wi-Fi and network
+    /// diagnostics are only shown on devices with Wi-Fi.
+    pub fn entries() -> &'static [&'static str] {
+        if DefaultPlatform::has_wifi() {
+            &Self::ENTRIES
+        } else {
+            &Self::ENTRIES[2..]
+        }
+    }
+
     pub fn new(rect: Rect, res: Resources, state: SettingsState) -> Result<Self> {
         let Rect { x, y, w, h } = rect;
 
@@ -72,43 +167,20 @@ impl Settings {
         let styles = res.get::<Stylesheet>();
 
         let has_wifi = DefaultPlatform::has_wifi();
-        let mut labels = Vec::with_capacity(7);
-        if has_wifi {
-            labels.push(locale.t("settings-wifi"));
-        }
-        labels.push(locale.t("settings-clock"));
-        labels.push(locale.t("settings-power"));
-        labels.push(locale.t("settings-display"));
-        labels.push(locale.t("settings-theme"));
-        labels.push(locale.t("settings-language"));
-        labels.push(locale.t("settings-about"));
+        let labels = Self::entries().iter().map(|key| locale.t(key)).collect();
 
         let mut list = ScrollList::new(
             Rect::new(x + 12, y + 8, w - 24, h - 8 - styles.ui_font.size - 8),
             labels,
             Alignment::Left,
-            styles.ui_font.size + SELECTION_MARGIN,
+            styles.list_entry_height(),
         );
         list.select(state.selected);
 
-        let child: Option<Box<dyn SettingsChild>> = if let Some(child) = state.child {
-            let mut selected = state.selected;
-            if !has_wifi {
-                selected += 1;
-            };
-            match selected {
-                0 => Some(Box::new(Wifi::new(rect, res.clone(), Some(child)))),
-                1 => Some(Box::new(Clock::new(rect, res.clone(), Some(child)))),
-                2 => Some(Box::new(Power::new(rect, res.clone(), Some(child)))),
-                3 => Some(Box::new(Display::new(rect, res.clone(), Some(child)))),
-                4 => Some(Box::new(Theme::new(rect, res.clone(), Some(child)))),
-                5 => Some(Box::new(Language::new(rect, res.clone(), Some(child)))),
-                6 => Some(Box::new(About::new(rect, res.clone(), Some(child)))),
-                _ => None,
-            }
-        } else {
-            None
-        };
+        let child = state.child.and_then(|child| {
+            let page = Self::page(state.selected, has_wifi);
+            Self::open_page(page, rect, &res, Some(child))
+        });
 
         let button_hints = Row::new(
             Point::new(
@@ -116,6 +188,13 @@ impl Settings {
                 y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
             ),
             vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::X,
+                    locale.t("settings-search"),
+                    Alignment::Right,
+                ),
                 ButtonHint::new(
                     res.clone(),
                     Point::zero(),
@@ -143,36 +222,155 @@ impl Settings {
             list,
             child,
             button_hints,
+            keyboard: None,
+            search: None,
             has_wifi,
             dirty: true,
         })
     }
 
     pub fn save(&self) -> SettingsState {
+        // Searches aren't restored, so the page of the selected result is remembered instead
+        let selected = match self.search.as_ref() {
+            Some(search) => search
+                .items
+                .get(self.list.selected().wrapping_sub(1))
+                .map_or(search.previous, |item| self.row(item.page)),
+            None => self.list.selected(),
+        };
         SettingsState {
-            selected: self.list.selected(),
+            selected,
             child: self.child.as_ref().map(|c| c.save()),
         }
     }
 
-    async fn select_entry(&mut self, _commands: Sender<Command>) -> Result<()> {
-        let mut selected = self.list.selected();
-        if !self.has_wifi {
-            selected += 1
+    /// Index of the page in `ENTRIES` listed at the row of the pages list.
+    fn page(row: usize, has_wifi: bool) -> usize {
+        if has_wifi { row } else { row + 2 }
+    }
+
+    /// Row of the pages list that the page in `ENTRIES` is listed at.
+    fn row(&self, page: usize) -> usize {
+        if self.has_wifi { page } else { page - 2 }
+    }
+
+    /// Opens the page at the index in `ENTRIES`, or `None` if it isn't a page.
+    fn open_page(
+        page: usize,
+        rect: Rect,
+        res: &Resources,
+        state: Option<ChildState>,
+    ) -> Option<Box<dyn SettingsChild>> {
+        let res = res.clone();
+        Some(match page {
+            0 => Box::new(Wifi::new(rect, res, state)),
+            1 => Box::new(Network::new(rect, res, state)),
+            2 => Box::new(Clock::new(rect, res, state)),
+            3 => Box::new(Power::new(rect, res, state)),
+            4 => Box::new(Lock::new(rect, res, state)),
+            5 => Box::new(Parental::new(rect, res, state)),
+            6 => Box::new(Battery::new(rect, res, state)),
+            7 => Box::new(Display::new(rect, res, state)),
+            8 => Box::new(Controls::new(rect, res, state)),
+            9 => Box::new(Bluetooth::new(rect, res, state)),
+            10 => Box::new(Theme::new(rect, res, state)),
+            11 => Box::new(Language::new(rect, res, state)),
+            12 => Box::new(About::new(rect, res, state)),
+            13 => Box::new(DeviceSync::new(rect, res, state)),
+            14 => Box::new(Webhook::new(rect, res, state)),
+            15 => Box::new(SaveSync::new(rect, res, state)),
+            16 => Box::new(SaveBackups::new(rect, res, state)),
+            17 => Box::new(Duplicates::new(rect, res, state)),
+            18 => Box::new(Hotkeys::new(rect, res, state)),
+            19 => Box::new(Sound::new(rect, res, state)),
+            20 => Box::new(Developer::new(rect, res, state)),
+            21 => Box::new(Logs::new(rect, res, state)),
+            22 => Box::new(Bios::new(rect, res, state)),
+            23 => Box::new(Maintenance::new(rect, res, state)),
+            24 => Box::new(Tabs::new(rect, res, state)),
+            25 => Box::new(Consoles::new(rect, res, state)),
+            26 => Box::new(Recents::new(rect, res, state)),
+            _ => return None,
+        })
+    }
+
+    async fn select_entry(&mut self, commands: Sender<Command>) -> Result<()> {
+        let (page, state) = match self.search.as_ref() {
+            Some(search) => {
+                // The first row is the query
+                let Some(item) = search.items.get(self.list.selected().wrapping_sub(1)) else {
+                    return Ok(());
+                };
+                (item.page, Some(ChildState { selected: item.row }))
+            }
+            None => (Self::page(self.list.selected(), self.has_wifi), None),
         };
-        match selected {
-            0 => self.child = Some(Box::new(Wifi::new(self.rect, self.res.clone(), None))),
-            1 => self.child = Some(Box::new(Clock::new(self.rect, self.res.clone(), None))),
-            2 => self.child = Some(Box::new(Power::new(self.rect, self.res.clone(), None))),
-            3 => self.child = Some(Box::new(Display::new(self.rect, self.res.clone(), None))),
-            4 => self.child = Some(Box::new(Theme::new(self.rect, self.res.clone(), None))),
-            5 => self.child = Some(Box::new(Language::new(self.rect, self.res.clone(), None))),
-            6 => self.child = Some(Box::new(About::new(self.rect, self.res.clone(), None))),
-            _ => unreachable!("Invalid index"),
+
+        if page == Self::ENTRIES.len() - 1 {
+            commands.send(Command::ImportOnionData).await?;
+            return Ok(());
         }
+        self.child = Self::open_page(page, self.rect, &self.res, state);
         self.dirty = true;
         Ok(())
     }
+
+    /// Lists the pages and rows with labels containing the query, or the pages again if it's
+    /// empty.
+    fn search(&mut self, query: String) {
+        let previous = match self.search.take() {
+            Some(search) => search.previous,
+            None => self.list.selected(),
+        };
+        if query.trim().is_empty() {
+            self.close_search(previous);
+            return;
+        }
+
+        let locale = self.res.get::<Locale>();
+        let items = registry::search(&locale, &query);
+        let mut labels = vec![
+            locale.ta(
+                "settings-search-query",
+                &[("query".into(), query.clone().into())]
+                    .into_iter()
+                    .collect(),
+            ),
+        ];
+        if items.is_empty() {
+            labels.push(locale.t("settings-search-none"));
+        }
+        labels.extend(items.iter().map(|item| {
+            if item.row == 0 && Self::ENTRIES[item.page] == item.key {
+                locale.t(item.key)
+            } else {
+                format!(
+                    "{}: {}",
+                    locale.t(Self::ENTRIES[item.page]),
+                    locale.t(item.key)
+                )
+            }
+        }));
+        drop(locale);
+
+        self.list.set_items(labels, false);
+        self.list.set_header(0, true);
+        self.search = Some(Search {
+            query,
+            items,
+            previous,
+        });
+    }
+
+    /// Lists the pages again, selecting the page at the row.
+    fn close_search(&mut self, selected: usize) {
+        self.search = None;
+        let locale = self.res.get::<Locale>();
+        let labels = Self::entries().iter().map(|key| locale.t(key)).collect();
+        drop(locale);
+        self.list.set_items(labels, false);
+        self.list.select(selected);
+    }
 }
 
 #[async_trait(?Send)]
@@ -196,6 +394,13 @@ impl View for Settings {
         drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
         drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
 
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            if drawn {
+                keyboard.set_should_draw();
+            }
+            drawn |= keyboard.should_draw() && keyboard.draw(display, styles)?;
+        }
+
         Ok(drawn)
     }
 
@@ -203,7 +408,9 @@ impl View for Settings {
         if let Some(child) = self.child.as_ref() {
             child.should_draw()
         } else {
-            self.list.should_draw() || self.button_hints.should_draw()
+            self.list.should_draw()
+                || self.button_hints.should_draw()
+                || self.keyboard.as_ref().is_some_and(|k| k.should_draw())
         }
     }
 
@@ -213,6 +420,9 @@ impl View for Settings {
         } else {
             self.list.set_should_draw();
             self.button_hints.set_should_draw();
+            if let Some(keyboard) = self.keyboard.as_mut() {
+                keyboard.set_should_draw();
+            }
         }
     }
 
@@ -236,12 +446,57 @@ impl View for Settings {
             } else {
                 Ok(false)
             }
+        } else if let Some(keyboard) = self.keyboard.as_mut() {
+            if !keyboard
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+            {
+                return Ok(false);
+            }
+            let mut query = None;
+            bubble.retain_mut(|c| match c {
+                Command::ValueChanged(_, val) => {
+                    if let Value::String(val) = val {
+                        query = Some(val.clone());
+                    }
+                    false
+                }
+                Command::CloseView => {
+                    self.keyboard = None;
+                    false
+                }
+                _ => true,
+            });
+            if let Some(query) = query {
+                self.search(query);
+            }
+            if self.keyboard.is_none() {
+                // Clears the keyboard from the screen
+                self.dirty = true;
+                self.set_should_draw();
+            }
+            Ok(true)
         } else {
             match event {
                 KeyEvent::Pressed(Key::A) => {
                     self.select_entry(commands).await?;
                     Ok(true)
                 }
+                KeyEvent::Pressed(Key::X) => {
+                    let query = self.search.as_ref().map(|s| s.query.clone());
+                    self.keyboard = Some(Keyboard::new(
+                        self.res.clone(),
+                        query.unwrap_or_default(),
+                        false,
+                    ));
+                    Ok(true)
+                }
+                KeyEvent::Pressed(Key::B) if self.search.is_some() => {
+                    if let Some(search) = self.search.as_ref() {
+                        self.close_search(search.previous);
+                    }
+                    Ok(true)
+                }
                 _ => self.list.handle_key_event(event, commands, bubble).await,
             }
         }
@@ -250,6 +505,8 @@ impl View for Settings {
     fn children(&self) -> Vec<&dyn View> {
         if let Some(child) = self.child.as_deref() {
             vec![child as &dyn View]
+        } else if let Some(keyboard) = self.keyboard.as_ref() {
+            vec![&self.list, &self.button_hints, keyboard]
         } else {
             vec![&self.list, &self.button_hints]
         }
@@ -258,6 +515,8 @@ impl View for Settings {
     fn children_mut(&mut self) -> Vec<&mut dyn View> {
         if let Some(child) = self.child.as_deref_mut() {
             vec![child as &mut dyn View]
+        } else if let Some(keyboard) = self.keyboard.as_mut() {
+            vec![&mut self.list, &mut self.button_hints, keyboard]
         } else {
             vec![&mut self.list, &mut self.button_hints]
         }
@@ -0,0 +1,287 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::{NETWORK_INTERNET_HOST, SELECTION_MARGIN};
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::network::{self, NetworkQuality};
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, Row, SettingsList, View};
+use common::wifi::WiFiSettings;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+#[derive(Debug, Clone)]
+enum Diagnostic {
+    Quality(NetworkQuality),
+    Gateway(Option<String>),
+    GatewayPing(Option<Duration>),
+    InternetPing(Option<Duration>),
+    DnsLookup(Option<Duration>),
+    DownloadSpeed(Option<f64>),
+}
+
+impl Diagnostic {
+    fn index(&self) -> usize {
+        match self {
+            Diagnostic::Quality(_) => 0,
+            Diagnostic::Gateway(_) => 1,
+            Diagnostic::GatewayPing(_) => 2,
+            Diagnostic::InternetPing(_) => 3,
+            Diagnostic::DnsLookup(_) => 4,
+            Diagnostic::DownloadSpeed(_) => 5,
+        }
+    }
+
+    fn text(&self, locale: &Locale) -> String {
+        let failed = || locale.t("settings-network-failed");
+        let millis = |d: &Option<Duration>| {
+            d.map_or_else(failed, |d| format!("{}ms", d.as_millis()))
+        };
+        match self {
+            Diagnostic::Quality(quality) => match quality {
+                NetworkQuality::Offline => locale.t("settings-network-quality-offline"),
+                NetworkQuality::Poor => locale.t("settings-network-quality-poor"),
+                NetworkQuality::Fair => locale.t("settings-network-quality-fair"),
+                NetworkQuality::Good => locale.t("settings-network-quality-good"),
+            },
+            Diagnostic::Gateway(gateway) => gateway.clone().unwrap_or_else(failed),
+            Diagnostic::GatewayPing(d)
+            | Diagnostic::InternetPing(d)
+            | Diagnostic::DnsLookup(d) => millis(d),
+            Diagnostic::DownloadSpeed(speed) => {
+                speed.map_or_else(failed, |speed| format!("{:.1} KB/s", speed / 1024.0))
+            }
+        }
+    }
+}
+
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &[
+    "settings-network-quality",
+    "settings-network-gateway",
+    "settings-network-gateway-ping",
+    "settings-network-internet-ping",
+    "settings-network-dns-lookup",
+    "settings-network-download-speed",
+];
+
+pub struct Network {
+    rect: Rect,
+    res: Resources,
+    list: SettingsList,
+    results: Option<Receiver<Diagnostic>>,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Network {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let labels: Vec<String> = LABELS.iter().map(|key| locale.t(key)).collect();
+        let values = labels
+            .iter()
+            .map(|_| {
+                Box::new(Label::new(
+                    Point::zero(),
+                    String::new(),
+                    Alignment::Right,
+                    None,
+                )) as Box<dyn View>
+            })
+            .collect();
+
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            labels,
+            values,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-retry"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        let mut this = Self {
+            rect,
+            res,
+            list,
+            results: None,
+            button_hints,
+        };
+        this.run();
+        this
+    }
+
+    /// Runs all diagnostics in the background. Results are shown as they arrive.
+    fn run(&mut self) {
+        {
+            let locale = self.res.get::<Locale>();
+            for i in 0..6 {
+                self.list.set_right(
+                    i,
+                    Box::new(Label::new(
+                        Point::zero(),
+                        locale.t("settings-network-testing"),
+                        Alignment::Right,
+                        None,
+                    )),
+                );
+            }
+        }
+
+        let speed_test_url = WiFiSettings::load().unwrap_or_default().speed_test_url;
+        let (tx, rx) = mpsc::channel(6);
+        self.results = Some(rx);
+        tokio::spawn(async move {
+            let gateway = network::gateway().await;
+            let gateway_ping = match gateway.as_deref() {
+                Some(gateway) => network::ping(gateway).await,
+                None => None,
+            };
+            let internet_ping = network::ping(NETWORK_INTERNET_HOST).await;
+            let diagnostics = [
+                Diagnostic::Quality(NetworkQuality::from_latency(internet_ping)),
+                Diagnostic::Gateway(gateway),
+                Diagnostic::GatewayPing(gateway_ping),
+                Diagnostic::InternetPing(internet_ping),
+            ];
+            for diagnostic in diagnostics {
+                if tx.send(diagnostic).await.is_err() {
+                    return;
+                }
+            }
+            if tx
+                .send(Diagnostic::DnsLookup(network::dns_lookup().await))
+                .await
+                .is_err()
+            {
+                return;
+            }
+            let speed = network::download_speed(&speed_test_url).await;
+            tx.send(Diagnostic::DownloadSpeed(speed)).await.ok();
+        });
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Network {
+    fn update(&mut self, _dt: Duration) {
+        let Some(results) = self.results.as_mut() else {
+            return;
+        };
+        while let Ok(diagnostic) = results.try_recv() {
+            let text = diagnostic.text(&self.res.get::<Locale>());
+            self.list.set_right(
+                diagnostic.index(),
+                Box::new(Label::new(Point::zero(), text, Alignment::Right, None)),
+            );
+        }
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                self.run();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Network {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
@@ -0,0 +1,427 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Duration, Weekday};
+use common::command::Command;
+use common::constants::{ALLIUM_GAMES_DIR, SELECTION_MARGIN};
+use common::database::Database;
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::lock::LockSettings;
+use common::parental::ParentalSettings;
+use common::pin::KeyPin;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, Number, Row, SettingsList, Toggle, View};
+use log::warn;
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// Index of the first daily play time budget in the list.
+const BUDGETS_INDEX: usize = 2;
+/// Index of the first console folder in the list.
+const CONSOLES_INDEX: usize = BUDGETS_INDEX + WEEKDAYS.len();
+
+/// Locale keys of the labels of the rows before the time limits and console restrictions.
+pub const LABELS: &[&str] = &["settings-parental-enabled", "settings-parental-pin"];
+
+pub struct Parental {
+    rect: Rect,
+    res: Resources,
+    settings: ParentalSettings,
+    /// Console folders that can be restricted.
+    consoles: Vec<PathBuf>,
+    /// Buttons pressed so far while entering the PIN to unlock this screen.
+    unlocking: Option<Vec<Key>>,
+    /// Buttons pressed so far while recording a new PIN.
+    recording: Option<Vec<Key>>,
+    prompt: Label<String>,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Parental {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let settings = ParentalSettings::load().unwrap_or_default();
+        let consoles = console_dirs();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+        let database = res.get::<Database>();
+
+        let mut left: Vec<String> = LABELS.iter().map(|key| locale.t(key)).collect();
+        let mut right: Vec<Box<dyn View>> = vec![
+            Box::new(Toggle::new(
+                Point::zero(),
+                settings.enabled,
+                Alignment::Right,
+            )),
+            Box::new(Label::new(
+                Point::zero(),
+                pin_text(&locale, settings.pin.len(), false),
+                Alignment::Right,
+                None,
+            )),
+        ];
+
+        let unlimited = locale.t("settings-parental-unlimited");
+        for weekday in WEEKDAYS {
+            let key = format!("settings-parental-{}", weekday.to_string().to_lowercase());
+            left.push(locale.t(&key));
+
+            let budget = database.get_play_time_budget(weekday).unwrap_or_else(|e| {
+                warn!("failed to load play time budget: {}", e);
+                None
+            });
+            let unlimited = unlimited.clone();
+            right.push(Box::new(Number::new(
+                Point::zero(),
+                budget.map_or(0, |budget| budget.num_minutes() as i32),
+                0,
+                24 * 60,
+                15,
+                move |x: &i32| {
+                    if *x == 0 {
+                        unlimited.clone()
+                    } else {
+                        format!("{}:{:02}", x / 60, x % 60)
+                    }
+                },
+                Alignment::Right,
+            )));
+        }
+
+        for console in &consoles {
+            let name = console.file_name().unwrap_or_default().to_string_lossy();
+            let mut map = HashMap::new();
+            map.insert("console".into(), name.into());
+            left.push(locale.ta("settings-parental-restrict", &map));
+            right.push(Box::new(Toggle::new(
+                Point::zero(),
+                settings.restricted.contains(console),
+                Alignment::Right,
+            )));
+        }
+
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            left,
+            right,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let prompt = Label::new(
+            Point::new(x + w as i32 / 2, y + h as i32 / 2),
+            pin_prompt(&locale, 0),
+            Alignment::Center,
+            None,
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![ButtonHint::new(
+                res.clone(),
+                Point::zero(),
+                Key::B,
+                locale.t("button-back"),
+                Alignment::Right,
+            )],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+        drop(database);
+
+        let unlocking = (!settings.pin.is_empty()).then(Vec::new);
+
+        Self {
+            rect,
+            res,
+            settings,
+            consoles,
+            unlocking,
+            recording: None,
+            prompt,
+            list,
+            button_hints,
+        }
+    }
+
+    fn update_pin(&mut self) {
+        let locale = self.res.get::<Locale>();
+        let text = match self.recording.as_ref() {
+            Some(pin) => pin_text(&locale, pin.len(), true),
+            None => pin_text(&locale, self.settings.pin.len(), false),
+        };
+        self.list.set_right(
+            1,
+            Box::new(Label::new(Point::zero(), text, Alignment::Right, None)),
+        );
+    }
+
+    fn start_recording(&mut self) {
+        self.recording = Some(Vec::new());
+        self.list.select(1);
+        self.update_pin();
+    }
+
+    /// Handles a key while recording a PIN. Start saves the PIN, and select cancels.
+    fn record(&mut self, key: Key) -> Result<()> {
+        let Some(pin) = self.recording.as_mut() else {
+            return Ok(());
+        };
+        match key {
+            Key::Start => {
+                if !pin.is_empty() {
+                    self.settings.pin = KeyPin::new(pin);
+                    self.settings.save()?;
+                }
+                self.recording = None;
+            }
+            Key::Select => self.recording = None,
+            key if LockSettings::is_pattern_key(key) => pin.push(key),
+            _ => {}
+        }
+        self.update_pin();
+        Ok(())
+    }
+
+    /// Handles a key while entering the PIN. The screen unlocks once the PIN is entered, and
+    /// select leaves without unlocking.
+    fn unlock(&mut self, key: Key, bubble: &mut VecDeque<Command>) {
+        let Some(entered) = self.unlocking.as_mut() else {
+            return;
+        };
+        match key {
+            Key::Select => {
+                bubble.push_back(Command::CloseView);
+                return;
+            }
+            key if LockSettings::is_pattern_key(key) => entered.push(key),
+            _ => return,
+        }
+
+        if entered.len() < self.settings.pin.len() {
+            let count = entered.len();
+            let text = pin_prompt(&self.res.get::<Locale>(), count);
+            self.prompt.set_text(text);
+        } else if self.settings.pin.verify(entered) {
+            self.unlocking = None;
+            self.set_should_draw();
+        } else {
+            entered.clear();
+            let text = self.res.get::<Locale>().t("settings-parental-wrong-pin");
+            self.prompt.set_text(text);
+        }
+    }
+
+    fn set_budget(&self, weekday: Weekday, minutes: i32) -> Result<()> {
+        let budget = (minutes > 0).then(|| Duration::minutes(minutes as i64));
+        self.res
+            .get::<Database>()
+            .set_play_time_budget(weekday, budget)
+    }
+
+    fn set_restricted(&mut self, console: PathBuf, restricted: bool) -> Result<()> {
+        self.settings.restricted.retain(|path| *path != console);
+        if restricted {
+            self.settings.restricted.push(console);
+        }
+        self.settings.save()
+    }
+}
+
+/// Returns the top-level folders of the games directory, which hold each console's games.
+fn console_dirs() -> Vec<PathBuf> {
+    let mut dirs = fs::read_dir(ALLIUM_GAMES_DIR.as_path())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+/// Describes a PIN with `len` buttons without revealing it.
+fn pin_text(locale: &Locale, len: usize, recording: bool) -> String {
+    if recording {
+        let mut map = HashMap::new();
+        map.insert("count".into(), len.into());
+        locale.ta("settings-parental-pin-recording", &map)
+    } else if len == 0 {
+        locale.t("settings-parental-pin-not-set")
+    } else {
+        let mut map = HashMap::new();
+        map.insert("count".into(), len.into());
+        locale.ta("settings-parental-pin-set", &map)
+    }
+}
+
+fn pin_prompt(locale: &Locale, count: usize) -> String {
+    let mut map = HashMap::new();
+    map.insert("count".into(), count.into());
+    locale.ta("settings-parental-enter-pin", &map)
+}
+
+#[async_trait(?Send)]
+impl View for Parental {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.unlocking.is_some() {
+            if self.prompt.should_draw() {
+                display.load(self.rect)?;
+                drawn |= self.prompt.draw(display, styles)?;
+            }
+            drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+            return Ok(drawn);
+        }
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        if self.unlocking.is_some() {
+            self.prompt.should_draw() || self.button_hints.should_draw()
+        } else {
+            self.list.should_draw() || self.button_hints.should_draw()
+        }
+    }
+
+    fn set_should_draw(&mut self) {
+        self.prompt.set_should_draw();
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self.unlocking.is_some() {
+            if let KeyEvent::Pressed(key) = event {
+                self.unlock(key, bubble);
+            }
+            return Ok(true);
+        }
+
+        if self.recording.is_some() {
+            if let KeyEvent::Pressed(key) = event {
+                self.record(key)?;
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) if self.list.selected() == 1 => {
+                self.start_recording();
+                return Ok(true);
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                return Ok(true);
+            }
+            _ => {}
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    match i {
+                        0 => {
+                            self.settings.enabled = val.as_bool().unwrap();
+                            self.settings.save()?;
+                            if self.settings.enabled && self.settings.pin.is_empty() {
+                                self.start_recording();
+                            }
+                        }
+                        i if (BUDGETS_INDEX..CONSOLES_INDEX).contains(&i) => {
+                            let weekday = WEEKDAYS[i - BUDGETS_INDEX];
+                            self.set_budget(weekday, val.as_int().unwrap())?;
+                        }
+                        i if i >= CONSOLES_INDEX => {
+                            let console = self.consoles[i - CONSOLES_INDEX].clone();
+                            self.set_restricted(console, val.as_bool().unwrap())?;
+                        }
+                        _ => unreachable!("Invalid index"),
+                    }
+                }
+            }
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Parental {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
@@ -1,14 +1,16 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::time::Duration;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use common::clock::{ClockSettings, SleepTimerAction};
 use common::command::Command;
-use common::constants::SELECTION_MARGIN;
+use common::constants::{SELECTION_MARGIN, SLEEP_TIMER_PRESETS};
 
 use common::display::Display as DisplayTrait;
 use common::geom::{Alignment, Point, Rect};
 use common::locale::Locale;
+use common::platform::led::{LedPattern, LedSettings};
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::power::{PowerButtonAction, PowerSettings};
 use common::resources::Resources;
@@ -19,10 +21,31 @@ use tokio::sync::mpsc::Sender;
 
 use crate::view::settings::{ChildState, SettingsChild};
 
+/// Locale keys of the labels of the rows, which are also found by the settings search. The lid
+/// row is only shown on devices with a lid.
+pub const LABELS: &[&str] = &[
+    "settings-power-auto-sleep-when-charging",
+    "settings-power-auto-sleep-duration-minutes",
+    "settings-power-fast-forward-hotkeys",
+    "settings-power-attract-mode",
+    "settings-power-attract-mode-delay-minutes",
+    "settings-power-sleep-timer",
+    "settings-power-sleep-timer-action",
+    "settings-power-power-button-action",
+    "settings-power-auto-save-state",
+    "settings-power-battery-saver-threshold",
+    "settings-power-battery-saver-brightness",
+    "settings-power-led",
+    "settings-power-led-suspended",
+    "settings-power-led-low-battery",
+    "settings-power-led-charging",
+];
+
 pub struct Power {
     res: Resources,
     rect: Rect,
     power_settings: PowerSettings,
+    led_settings: LedSettings,
     list: SettingsList,
     button_hints: Row<ButtonHint<String>>,
 }
@@ -34,67 +57,166 @@ impl Power {
         let locale = res.get::<Locale>();
         let styles = res.get::<Stylesheet>();
         let power_settings = PowerSettings::load().unwrap_or_default();
+        let led_settings = LedSettings::load().unwrap_or_default();
+        let clock_settings = ClockSettings::load().unwrap_or_default();
+
+        let sleep_timer = clock_settings
+            .sleep_timer
+            .and_then(|_| {
+                SLEEP_TIMER_PRESETS
+                    .iter()
+                    .position(|&m| m == clock_settings.sleep_timer_minutes)
+            })
+            .map_or(0, |i| i + 1);
+        let mut sleep_timer_labels = vec![locale.t("settings-power-sleep-timer-off")];
+        for minutes in SLEEP_TIMER_PRESETS {
+            let mut map = HashMap::new();
+            map.insert("minutes".into(), minutes.into());
+            sleep_timer_labels.push(locale.ta("settings-power-sleep-timer-minutes", &map));
+        }
 
         let auto_sleep_duration_disabled_label =
             locale.t("settings-power-auto-sleep-duration-disabled");
+        let battery_saver_disabled_label = locale.t("settings-power-battery-saver-disabled");
+        let led_patterns = vec![
+            locale.t("settings-power-led-pattern-off"),
+            locale.t("settings-power-led-pattern-solid"),
+            locale.t("settings-power-led-pattern-blink"),
+            locale.t("settings-power-led-pattern-breathe"),
+        ];
 
-        let mut buttons: Vec<(String, Box<dyn View>)> = vec![
-            (
-                locale.t("settings-power-auto-sleep-when-charging"),
-                Box::new(Toggle::new(
-                    Point::zero(),
-                    power_settings.auto_sleep_when_charging,
-                    Alignment::Right,
-                )),
-            ),
-            (
-                locale.t("settings-power-auto-sleep-duration-minutes"),
-                Box::new(Number::new(
-                    Point::zero(),
-                    power_settings.auto_sleep_duration_minutes,
-                    0,
-                    60,
-                    5,
-                    move |x: &i32| {
-                        if *x == 0 {
-                            auto_sleep_duration_disabled_label.clone()
-                        } else {
-                            x.to_string()
-                        }
-                    },
-                    Alignment::Right,
-                )),
-            ),
-            (
-                locale.t("settings-power-power-button-action"),
-                Box::new(Select::new(
-                    Point::zero(),
-                    power_settings.power_button_action as usize,
-                    vec![
-                        locale.t("settings-power-power-button-action-suspend"),
-                        locale.t("settings-power-power-button-action-shutdown"),
-                        locale.t("settings-power-power-button-action-nothing"),
-                    ],
-                    Alignment::Right,
-                )),
-            ),
+        let mut right: Vec<Box<dyn View>> = vec![
+            Box::new(Toggle::new(
+                Point::zero(),
+                power_settings.auto_sleep_when_charging,
+                Alignment::Right,
+            )),
+            Box::new(Number::new(
+                Point::zero(),
+                power_settings.auto_sleep_duration_minutes,
+                0,
+                60,
+                5,
+                move |x: &i32| {
+                    if *x == 0 {
+                        auto_sleep_duration_disabled_label.clone()
+                    } else {
+                        x.to_string()
+                    }
+                },
+                Alignment::Right,
+            )),
+            Box::new(Toggle::new(
+                Point::zero(),
+                power_settings.fast_forward_hotkeys,
+                Alignment::Right,
+            )),
+            Box::new(Toggle::new(
+                Point::zero(),
+                power_settings.attract_mode,
+                Alignment::Right,
+            )),
+            Box::new(Number::new(
+                Point::zero(),
+                power_settings.attract_mode_delay_minutes,
+                1,
+                30,
+                1,
+                i32::to_string,
+                Alignment::Right,
+            )),
+            Box::new(Select::new(
+                Point::zero(),
+                sleep_timer,
+                sleep_timer_labels,
+                Alignment::Right,
+            )),
+            Box::new(Select::new(
+                Point::zero(),
+                clock_settings.sleep_timer_action as usize,
+                vec![
+                    locale.t("settings-power-power-button-action-suspend"),
+                    locale.t("settings-power-power-button-action-shutdown"),
+                ],
+                Alignment::Right,
+            )),
+            Box::new(Select::new(
+                Point::zero(),
+                power_settings.power_button_action as usize,
+                vec![
+                    locale.t("settings-power-power-button-action-suspend"),
+                    locale.t("settings-power-power-button-action-shutdown"),
+                    locale.t("settings-power-power-button-action-nothing"),
+                ],
+                Alignment::Right,
+            )),
+            Box::new(Toggle::new(
+                Point::zero(),
+                power_settings.auto_save_state,
+                Alignment::Right,
+            )),
+            Box::new(Number::new(
+                Point::zero(),
+                power_settings.battery_saver_threshold,
+                0,
+                50,
+                5,
+                move |x: &i32| {
+                    if *x == 0 {
+                        battery_saver_disabled_label.clone()
+                    } else {
+                        format!("{x}%")
+                    }
+                },
+                Alignment::Right,
+            )),
+            Box::new(Number::new(
+                Point::zero(),
+                power_settings.battery_saver_brightness as i32,
+                0,
+                100,
+                10,
+                |x: &i32| format!("{x}%"),
+                Alignment::Right,
+            )),
+            Box::new(Toggle::new(
+                Point::zero(),
+                led_settings.enabled,
+                Alignment::Right,
+            )),
+            Box::new(Select::new(
+                Point::zero(),
+                led_settings.suspended as usize,
+                led_patterns.clone(),
+                Alignment::Right,
+            )),
+            Box::new(Select::new(
+                Point::zero(),
+                led_settings.low_battery as usize,
+                led_patterns.clone(),
+                Alignment::Right,
+            )),
+            Box::new(Select::new(
+                Point::zero(),
+                led_settings.charging as usize,
+                led_patterns,
+                Alignment::Right,
+            )),
         ];
+        let mut left: Vec<String> = LABELS.iter().map(|key| locale.t(key)).collect();
         if DefaultPlatform::has_lid() {
-            buttons.push((
-                locale.t("settings-power-lid-close-action"),
-                Box::new(Select::new(
-                    Point::zero(),
-                    power_settings.lid_close_action as usize,
-                    vec![
-                        locale.t("settings-power-power-button-action-suspend"),
-                        locale.t("settings-power-power-button-action-shutdown"),
-                        locale.t("settings-power-power-button-action-nothing"),
-                    ],
-                    Alignment::Right,
-                )),
-            ));
+            left.push(locale.t("settings-power-lid-close-action"));
+            right.push(Box::new(Select::new(
+                Point::zero(),
+                power_settings.lid_close_action as usize,
+                vec![
+                    locale.t("settings-power-power-button-action-suspend"),
+                    locale.t("settings-power-power-button-action-shutdown"),
+                    locale.t("settings-power-power-button-action-nothing"),
+                ],
+                Alignment::Right,
+            )));
         }
-        let (left, right) = buttons.into_iter().unzip();
 
         let mut list = SettingsList::new(
             Rect::new(
@@ -134,6 +256,7 @@ impl Power {
             res,
             rect,
             power_settings,
+            led_settings,
             list,
             button_hints,
         }
@@ -192,6 +315,53 @@ impl View for Power {
                             self.power_settings.auto_sleep_duration_minutes = val.as_int().unwrap()
                         }
                         2 => {
+                            self.power_settings.fast_forward_hotkeys = val.as_bool().unwrap();
+                            let locale = self.res.get::<Locale>();
+                            commands
+                                .send(Command::Toast(
+                                    locale.t("settings-needs-restart-for-effect"),
+                                    Some(Duration::from_secs(5)),
+                                ))
+                                .await?;
+                        }
+                        3 => {
+                            self.power_settings.attract_mode = val.as_bool().unwrap();
+                            let locale = self.res.get::<Locale>();
+                            commands
+                                .send(Command::Toast(
+                                    locale.t("settings-needs-restart-for-effect"),
+                                    Some(Duration::from_secs(5)),
+                                ))
+                                .await?;
+                        }
+                        4 => {
+                            self.power_settings.attract_mode_delay_minutes = val.as_int().unwrap();
+                            let locale = self.res.get::<Locale>();
+                            commands
+                                .send(Command::Toast(
+                                    locale.t("settings-needs-restart-for-effect"),
+                                    Some(Duration::from_secs(5)),
+                                ))
+                                .await?;
+                        }
+                        5 => {
+                            // alliumd picks up the sleep timer from the clock settings
+                            let mut clock_settings = ClockSettings::load()?;
+                            let minutes = match val.as_int().unwrap() as usize {
+                                0 => None,
+                                i => SLEEP_TIMER_PRESETS.get(i - 1).copied(),
+                            };
+                            clock_settings.set_sleep_timer(minutes);
+                            clock_settings.save()?;
+                        }
+                        6 => {
+                            let mut clock_settings = ClockSettings::load()?;
+                            clock_settings.sleep_timer_action =
+                                SleepTimerAction::from_repr(val.as_int().unwrap() as usize)
+                                    .unwrap_or_default();
+                            clock_settings.save()?;
+                        }
+                        7 => {
                             self.power_settings.power_button_action =
                                 PowerButtonAction::from_repr(val.as_int().unwrap() as usize)
                                     .unwrap_or_default();
@@ -203,7 +373,53 @@ impl View for Power {
                                 ))
                                 .await?;
                         }
-                        3 => {
+                        8 => {
+                            self.power_settings.auto_save_state = val.as_bool().unwrap();
+                            let locale = self.res.get::<Locale>();
+                            commands
+                                .send(Command::Toast(
+                                    locale.t("settings-needs-restart-for-effect"),
+                                    Some(Duration::from_secs(5)),
+                                ))
+                                .await?;
+                        }
+                        9 => {
+                            self.power_settings.battery_saver_threshold = val.as_int().unwrap();
+                            let locale = self.res.get::<Locale>();
+                            commands
+                                .send(Command::Toast(
+                                    locale.t("settings-needs-restart-for-effect"),
+                                    Some(Duration::from_secs(5)),
+                                ))
+                                .await?;
+                        }
+                        10 => {
+                            self.power_settings.battery_saver_brightness =
+                                val.as_int().unwrap() as u8;
+                            let locale = self.res.get::<Locale>();
+                            commands
+                                .send(Command::Toast(
+                                    locale.t("settings-needs-restart-for-effect"),
+                                    Some(Duration::from_secs(5)),
+                                ))
+                                .await?;
+                        }
+                        11..=14 => {
+                            // alliumd reads the LED settings each time it updates the LED
+                            let pattern = || {
+                                LedPattern::from_repr(val.as_int().unwrap() as usize)
+                                    .unwrap_or_default()
+                            };
+                            match i {
+                                11 => self.led_settings.enabled = val.as_bool().unwrap(),
+                                12 => self.led_settings.suspended = pattern(),
+                                13 => self.led_settings.low_battery = pattern(),
+                                _ => self.led_settings.charging = pattern(),
+                            }
+                            self.led_settings.save()?;
+                            continue;
+                        }
+                        15 => {
                             self.power_settings.lid_close_action =
                                 PowerButtonAction::from_repr(val.as_int().unwrap() as usize)
                                     .unwrap_or_default();
@@ -0,0 +1,209 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::geom::{Alignment, Point, Rect};
+use common::launcher::LauncherSettings;
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Number, Row, Select, SettingsList, Toggle, View};
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Days after which games can be pruned from the recents, after the option to never prune them.
+const PRUNE_DAYS: [u32; 5] = [7, 30, 90, 180, 365];
+
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &[
+    "settings-recents-limit",
+    "settings-recents-prune",
+    "settings-recents-group-by-day",
+];
+
+pub struct Recents {
+    rect: Rect,
+    settings: LauncherSettings,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Recents {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let settings = LauncherSettings::load().unwrap_or_default();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let prune_days = settings
+            .prune_recents_days
+            .and_then(|days| PRUNE_DAYS.iter().position(|d| *d == days))
+            .map_or(0, |i| i + 1);
+        let mut prune_labels = vec![locale.t("settings-recents-prune-never")];
+        prune_labels.extend(PRUNE_DAYS.iter().map(|days| {
+            locale.ta(
+                "settings-recents-prune-days",
+                &[("days".into(), (*days).into())].into_iter().collect(),
+            )
+        }));
+
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            LABELS.iter().map(|key| locale.t(key)).collect(),
+            vec![
+                Box::new(Number::new(
+                    Point::zero(),
+                    settings.recents_limit as i32,
+                    10,
+                    200,
+                    10,
+                    i32::to_string,
+                    Alignment::Right,
+                )),
+                Box::new(Select::new(
+                    Point::zero(),
+                    prune_days,
+                    prune_labels,
+                    Alignment::Right,
+                )),
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    settings.group_recents_by_day,
+                    Alignment::Right,
+                )),
+            ],
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![ButtonHint::new(
+                res.clone(),
+                Point::zero(),
+                Key::B,
+                locale.t("button-back"),
+                Alignment::Right,
+            )],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            settings,
+            list,
+            button_hints,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Recents {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    match i {
+                        0 => self.settings.recents_limit = val.as_int().unwrap().into(),
+                        1 => {
+                            self.settings.prune_recents_days = match val.as_int().unwrap() {
+                                0 => None,
+                                i => PRUNE_DAYS.get(i as usize - 1).copied(),
+                            };
+                        }
+                        2 => self.settings.group_recents_by_day = val.as_bool().unwrap(),
+                        _ => unreachable!("Invalid index"),
+                    }
+                    // The launcher is reloaded, so the recents are listed with the new settings
+                    commands
+                        .send(Command::SaveLauncherSettings(self.settings.clone()))
+                        .await?;
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Recents {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use common::launcher::LauncherTab;
+use common::locale::Locale;
+use strum::IntoEnumIterator;
+
+use crate::view::settings::{
+    Settings, about, battery, bluetooth, clock, controls, developer, device_sync, display, hotkeys,
+    language, lock, maintenance, network, parental, power, recents, save_backups, save_sync, sound,
+    tabs, theme, webhook, wifi,
+};
+
+/// A row on one of the settings pages, listed so that settings can be searched without opening
+/// every page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SettingsItem {
+    /// Index of the page in `Settings::ENTRIES`.
+    pub page: usize,
+    /// Index of the row on the page.
+    pub row: usize,
+    /// Locale key of the row's label.
+    pub key: &'static str,
+}
+
+const fn item(page: usize, row: usize, key: &'static str) -> SettingsItem {
+    SettingsItem { page, row, key }
+}
+
+/// Labels of the rows of each page, by index of the page in `Settings::ENTRIES`, along with the
+/// row of the first label. Rows that depend on the device or the library, such as consoles and
+/// paired devices, aren't included.
+fn pages() -> [(usize, usize, &'static [&'static str]); 23] {
+    [
+        (0, 0, wifi::LABELS),
+        (1, 0, network::LABELS),
+        (2, 0, clock::LABELS),
+        (3, 0, power::LABELS),
+        (4, 0, lock::LABELS),
+        (5, 0, parental::LABELS),
+        (6, 0, battery::LABELS),
+        (7, 0, display::LABELS),
+        (8, 0, controls::LABELS),
+        (9, 0, bluetooth::LABELS),
+        (10, 0, theme::LABELS),
+        (11, 0, language::LABELS),
+        (12, 0, about::LABELS),
+        (13, 0, device_sync::LABELS),
+        (14, 0, webhook::LABELS),
+        (15, 0, save_sync::LABELS),
+        (16, 0, save_backups::LABELS),
+        (18, 0, hotkeys::LABELS),
+        (19, 0, sound::LABELS),
+        (20, 0, developer::LABELS),
+        (23, 0, maintenance::LABELS),
+        (24, LauncherTab::iter().count(), tabs::LABELS),
+        (26, 0, recents::LABELS),
+    ]
+}
+
+/// Rows of the settings pages, in the order they're listed.
+fn items() -> impl Iterator<Item = SettingsItem> {
+    pages().into_iter().flat_map(|(page, first, labels)| {
+        labels
+            .iter()
+            .enumerate()
+            .map(move |(i, key)| item(page, first + i, key))
+    })
+}
+
+/// Finds the pages and rows shown on this device with labels containing the query, ignoring
+/// case. Pages are listed before their rows.
+pub fn search(locale: &Locale, query: &str) -> Vec<SettingsItem> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let matches = |key: &str| locale.t(key).to_lowercase().contains(&query);
+
+    // Pages without Wi-Fi aren't shown on devices without it
+    let first_page = Settings::ENTRIES.len() - Settings::entries().len();
+    let mut results = Vec::new();
+    for (page, key) in Settings::ENTRIES.iter().enumerate().skip(first_page) {
+        if matches(key) {
+            results.push(item(page, 0, key));
+        }
+        results.extend(items().filter(|item| item.page == page && matches(item.key)));
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use common::reset::ResetTarget;
+
+    use super::*;
+
+    #[test]
+    fn test_items_are_on_their_pages() {
+        let pages = pages();
+        assert!(pages.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        for item in items() {
+            let page = Settings::ENTRIES[item.page];
+            assert!(
+                item.key.starts_with(&format!("{page}-")),
+                "{} isn't on {}",
+                item.key,
+                page
+            );
+        }
+    }
+
+    #[test]
+    fn test_maintenance_labels() {
+        let keys: Vec<String> = ResetTarget::iter()
+            .map(|target| format!("settings-maintenance-reset-{}", target.name()))
+            .collect();
+        assert_eq!(keys, maintenance::LABELS);
+    }
+}
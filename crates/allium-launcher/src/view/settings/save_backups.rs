@@ -0,0 +1,356 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::database::Database;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::save_backup::{self, SaveBackup, SaveBackupSettings};
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, NullView, Number, Row, SettingsList, Toggle, View};
+use log::{error, warn};
+use tokio::sync::mpsc::Sender;
+use tokio::sync::oneshot;
+
+use crate::consoles::ConsoleMapper;
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Number of setting entries above the list of games.
+const SETTINGS_COUNT: usize = 2;
+
+/// Locale keys of the labels of the rows before the backed up games.
+pub const LABELS: &[&str] = &[
+    "settings-save-backups-enabled",
+    "settings-save-backups-versions",
+];
+
+pub struct SaveBackups {
+    rect: Rect,
+    res: Resources,
+    settings: SaveBackupSettings,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+    games: Vec<PathBuf>,
+    /// The game whose backups are shown, and its backups, newest first.
+    versions: Option<(usize, Vec<SaveBackup>)>,
+    /// Restore running in the background, and the game it's of.
+    restoring: Option<(usize, oneshot::Receiver<()>)>,
+}
+
+impl SaveBackups {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let settings = SaveBackupSettings::load().unwrap_or_default();
+
+        let styles = res.get::<Stylesheet>();
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            Vec::new(),
+            Vec::new(),
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let locale = res.get::<Locale>();
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-select"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        let mut this = Self {
+            rect,
+            res,
+            settings,
+            list,
+            button_hints,
+            games: Vec::new(),
+            versions: None,
+            restoring: None,
+        };
+        this.show_games(state.map(|state| state.selected).unwrap_or_default());
+        this
+    }
+
+    fn show_games(&mut self, selected: usize) {
+        self.versions = None;
+        self.games = save_backup::games().unwrap_or_else(|e| {
+            warn!("failed to list save backups: {}", e);
+            Vec::new()
+        });
+
+        let locale = self.res.get::<Locale>();
+        let mut labels: Vec<String> = LABELS.iter().map(|key| locale.t(key)).collect();
+        labels.extend(self.games.iter().map(|game| {
+            let name = game.file_stem().unwrap_or_default().to_string_lossy();
+            match game.parent().and_then(|p| p.file_name()) {
+                Some(dir) => format!("{} ({})", name, dir.to_string_lossy()),
+                None => name.to_string(),
+            }
+        }));
+        let mut values: Vec<Box<dyn View>> = vec![
+            Box::new(Toggle::new(
+                Point::zero(),
+                self.settings.enabled,
+                Alignment::Right,
+            )),
+            Box::new(Number::new(
+                Point::zero(),
+                self.settings.versions,
+                1,
+                50,
+                1,
+                i32::to_string,
+                Alignment::Right,
+            )),
+        ];
+        values.extend(
+            self.games
+                .iter()
+                .map(|_| Box::new(NullView) as Box<dyn View>),
+        );
+        drop(locale);
+
+        self.list.set_items(labels, values);
+        self.list
+            .select(selected.min(SETTINGS_COUNT + self.games.len() - 1));
+    }
+
+    fn show_versions(&mut self, game: usize) {
+        let versions = save_backup::versions(&self.games[game]).unwrap_or_else(|e| {
+            warn!("failed to list save backups: {}", e);
+            Vec::new()
+        });
+        if versions.is_empty() {
+            self.show_games(SETTINGS_COUNT + game);
+            return;
+        }
+
+        let labels = versions
+            .iter()
+            .map(|backup| backup.time.format("%Y-%m-%d %H:%M:%S").to_string())
+            .collect();
+        let values = versions
+            .iter()
+            .map(|_| Box::new(NullView) as Box<dyn View>)
+            .collect();
+        self.list.set_items(labels, values);
+        self.list.select(0);
+        self.versions = Some((game, versions));
+    }
+
+    /// Restores a backup in the background, as archiving and extracting the saves takes a while.
+    /// A toast tells how it went, and the backups are listed again once it's done.
+    fn restore(&mut self, index: usize, commands: Sender<Command>) -> Result<()> {
+        let Some((game, versions)) = &self.versions else {
+            return Ok(());
+        };
+        let game = *game;
+        let backup = versions[index].clone();
+
+        let path = self.games[game].clone();
+        let core = {
+            let core = self
+                .res
+                .get::<Database>()
+                .select_game(&path)?
+                .and_then(|game| game.core);
+            self.res
+                .get::<ConsoleMapper>()
+                .game_core(&path, core.as_ref())
+                .cloned()
+        };
+        let (restored, failed) = {
+            let locale = self.res.get::<Locale>();
+            let mut map = HashMap::new();
+            map.insert(
+                "time".into(),
+                backup.time.format("%Y-%m-%d %H:%M").to_string().into(),
+            );
+            (
+                locale.ta("save-backup-restored", &map),
+                locale.t("save-backup-restore-failed"),
+            )
+        };
+
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let text = match save_backup::restore(&path, core.as_deref(), &backup).await {
+                Ok(()) => restored,
+                Err(e) => {
+                    error!("failed to restore save backup: {}", e);
+                    failed
+                }
+            };
+            commands
+                .send(Command::Toast(text, Some(Duration::from_secs(5))))
+                .await
+                .ok();
+            tx.send(()).ok();
+        });
+        self.restoring = Some((game, rx));
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl View for SaveBackups {
+    fn update(&mut self, dt: Duration) {
+        if let Some((game, rx)) = self.restoring.as_mut() {
+            let game = *game;
+            match rx.try_recv() {
+                Err(oneshot::error::TryRecvError::Empty) => {}
+                _ => {
+                    self.restoring = None;
+                    // The saves that were replaced are now the newest backup
+                    if self
+                        .versions
+                        .as_ref()
+                        .is_some_and(|(shown, _)| *shown == game)
+                    {
+                        self.show_versions(game);
+                    }
+                }
+            }
+        }
+
+        self.children_mut().iter_mut().for_each(|c| c.update(dt));
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        let selected = self.list.selected();
+        if self.versions.is_some() {
+            match event {
+                // Only one backup is restored at a time
+                KeyEvent::Pressed(Key::A) if self.restoring.is_some() => return Ok(true),
+                KeyEvent::Pressed(Key::A) => {
+                    self.restore(selected, commands)?;
+                    return Ok(true);
+                }
+                KeyEvent::Pressed(Key::B) => {
+                    let game = self.versions.as_ref().map(|(game, _)| *game).unwrap();
+                    self.show_games(SETTINGS_COUNT + game);
+                    return Ok(true);
+                }
+                _ => {}
+            }
+        } else if event == KeyEvent::Pressed(Key::A) && selected >= SETTINGS_COUNT {
+            self.show_versions(selected - SETTINGS_COUNT);
+            return Ok(true);
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    match i {
+                        0 => self.settings.enabled = val.as_bool().unwrap(),
+                        1 => self.settings.versions = val.as_int().unwrap(),
+                        _ => unreachable!("Invalid index"),
+                    }
+                    self.settings.save()?;
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for SaveBackups {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: match &self.versions {
+                Some((game, _)) => SETTINGS_COUNT + game,
+                None => self.list.selected(),
+            },
+        }
+    }
+}
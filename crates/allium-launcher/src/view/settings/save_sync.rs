@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::save_sync::{SaveSyncBackend, SaveSyncSettings};
+use common::stylesheet::Stylesheet;
+use common::view::{
+    ButtonHint, ButtonIcon, NullView, Row, Select, SettingsList, TextBox, Toggle, View,
+};
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Index of the entry that syncs right away.
+const SYNC_NOW_INDEX: usize = 7;
+
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &[
+    "settings-save-sync-enabled",
+    "settings-save-sync-backend",
+    "settings-save-sync-server",
+    "settings-save-sync-folder",
+    "settings-save-sync-username",
+    "settings-save-sync-password",
+    "settings-save-sync-on-exit",
+    "settings-save-sync-now",
+];
+
+pub struct SaveSync {
+    rect: Rect,
+    settings: SaveSyncSettings,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl SaveSync {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let settings = SaveSyncSettings::load().unwrap_or_default();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            LABELS.iter().map(|key| locale.t(key)).collect(),
+            vec![
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    settings.enabled,
+                    Alignment::Right,
+                )),
+                Box::new(Select::new(
+                    Point::zero(),
+                    settings.backend as usize,
+                    vec![
+                        locale.t("settings-save-sync-backend-webdav"),
+                        locale.t("settings-save-sync-backend-sftp"),
+                    ],
+                    Alignment::Right,
+                )),
+                Box::new(TextBox::new(
+                    Point::zero(),
+                    res.clone(),
+                    settings.server.clone(),
+                    Alignment::Right,
+                    false,
+                )),
+                Box::new(TextBox::new(
+                    Point::zero(),
+                    res.clone(),
+                    settings.folder.clone(),
+                    Alignment::Right,
+                    false,
+                )),
+                Box::new(TextBox::new(
+                    Point::zero(),
+                    res.clone(),
+                    settings.username.clone(),
+                    Alignment::Right,
+                    false,
+                )),
+                // Only the obscured password is kept, so it can't be shown
+                Box::new(TextBox::new(
+                    Point::zero(),
+                    res.clone(),
+                    String::new(),
+                    Alignment::Right,
+                    true,
+                )),
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    settings.sync_on_exit,
+                    Alignment::Right,
+                )),
+                Box::new(NullView),
+            ],
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-edit"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            settings,
+            list,
+            button_hints,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for SaveSync {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if event == KeyEvent::Pressed(Key::A) && self.list.selected() == SYNC_NOW_INDEX {
+            commands.send(Command::SyncSaves).await?;
+            return Ok(true);
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    match i {
+                        0 => self.settings.enabled = val.as_bool().unwrap(),
+                        1 => {
+                            self.settings.backend =
+                                SaveSyncBackend::from_repr(val.as_int().unwrap() as usize)
+                                    .unwrap_or_default()
+                        }
+                        2 => self.settings.server = val.as_string().unwrap().trim().to_string(),
+                        3 => self.settings.folder = val.as_string().unwrap().trim().to_string(),
+                        4 => self.settings.username = val.as_string().unwrap().to_string(),
+                        5 => {
+                            self.settings
+                                .set_password(&val.as_string().unwrap())
+                                .await?
+                        }
+                        6 => self.settings.sync_on_exit = val.as_bool().unwrap(),
+                        _ => unreachable!("Invalid index"),
+                    }
+                    self.settings.save()?;
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for SaveSync {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
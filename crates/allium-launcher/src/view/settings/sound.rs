@@ -0,0 +1,229 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::audio::{AudioSettings, Equalizer, VolumeCurve};
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, Number, Row, Select, SettingsList, View};
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &[
+    "settings-sound-volume-curve",
+    "settings-sound-max-volume",
+    "settings-sound-equalizer",
+];
+
+pub struct Sound {
+    rect: Rect,
+    res: Resources,
+    settings: AudioSettings,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Sound {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let settings = AudioSettings::load().unwrap_or_default();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let max_volume_disabled_label = locale.t("settings-sound-max-volume-disabled");
+        let mut list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            LABELS.iter().map(|key| locale.t(key)).collect(),
+            vec![
+                Box::new(Select::new(
+                    Point::zero(),
+                    settings.volume_curve as usize,
+                    vec![
+                        locale.t("settings-sound-volume-curve-logarithmic"),
+                        locale.t("settings-sound-volume-curve-linear"),
+                    ],
+                    Alignment::Right,
+                )),
+                Box::new(Number::new(
+                    Point::zero(),
+                    settings.max_volume,
+                    10,
+                    100,
+                    10,
+                    move |x: &i32| {
+                        if *x == 100 {
+                            max_volume_disabled_label.clone()
+                        } else {
+                            format!("{x}%")
+                        }
+                    },
+                    Alignment::Right,
+                )),
+                if Equalizer::is_available() {
+                    Box::new(Select::new(
+                        Point::zero(),
+                        settings.equalizer as usize,
+                        vec![
+                            locale.t("settings-sound-equalizer-flat"),
+                            locale.t("settings-sound-equalizer-bass-boost"),
+                            locale.t("settings-sound-equalizer-speech"),
+                        ],
+                        Alignment::Right,
+                    ))
+                } else {
+                    Box::new(Label::new(
+                        Point::zero(),
+                        locale.t("settings-sound-equalizer-not-installed"),
+                        Alignment::Right,
+                        None,
+                    ))
+                },
+            ],
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        if let Some(state) = state {
+            list.select(state.selected);
+        }
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![ButtonHint::new(
+                res.clone(),
+                Point::zero(),
+                Key::B,
+                locale.t("button-back"),
+                Alignment::Right,
+            )],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            res,
+            settings,
+            list,
+            button_hints,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Sound {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    match i {
+                        0 => {
+                            self.settings.volume_curve =
+                                VolumeCurve::from_repr(val.as_int().unwrap() as usize)
+                                    .unwrap_or_default();
+                        }
+                        1 => self.settings.max_volume = val.as_int().unwrap(),
+                        2 => {
+                            self.settings.equalizer =
+                                Equalizer::from_repr(val.as_int().unwrap() as usize)
+                                    .unwrap_or_default();
+                            let locale = self.res.get::<Locale>();
+                            commands
+                                .send(Command::Toast(
+                                    locale.t("settings-sound-equalizer-next-launch"),
+                                    Some(Duration::from_secs(5)),
+                                ))
+                                .await?;
+                        }
+                        _ => unreachable!("Invalid index"),
+                    }
+                    // alliumd applies the audio settings again when they change
+                    self.settings.save()?;
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Sound {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::geom::{Alignment, Point, Rect};
+use common::launcher::{LauncherSettings, LauncherTab, StartupBehavior};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, Row, Select, SettingsList, Toggle, View};
+use strum::IntoEnumIterator;
+use tokio::sync::mpsc::Sender;
+
+use crate::view::settings::{ChildState, SettingsChild};
+
+/// Locale keys of the labels of the rows after the row of each tab.
+pub const LABELS: &[&str] = &[
+    "settings-tabs-startup-tab",
+    "settings-tabs-startup",
+    "settings-tabs-group-consoles",
+];
+
+/// Shows, hides and reorders the launcher's tabs, and sets what's opened on boot.
+pub struct Tabs {
+    rect: Rect,
+    res: Resources,
+    settings: LauncherSettings,
+    /// Tab of each row, visible tabs first. They're followed by the startup tab, the startup
+    /// behavior and the console grouping rows.
+    rows: Vec<LauncherTab>,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Tabs {
+    pub fn new(rect: Rect, res: Resources, state: Option<ChildState>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+        let settings = LauncherSettings::load().unwrap_or_default();
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            Vec::new(),
+            Vec::new(),
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::Y,
+                    locale.t("settings-tabs-move-up"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::X,
+                    locale.t("settings-tabs-move-down"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        let mut this = Self {
+            rect,
+            res,
+            settings,
+            rows: Vec::new(),
+            list,
+            button_hints,
+        };
+        this.set_items();
+        if let Some(state) = state {
+            this.list.select(state.selected.min(this.rows.len() + 2));
+        }
+        this
+    }
+
+    fn set_items(&mut self) {
+        let locale = self.res.get::<Locale>();
+
+        self.rows = self.settings.tabs.clone();
+        self.rows
+            .extend(LauncherTab::iter().filter(|tab| !self.settings.is_visible(*tab)));
+
+        let mut left: Vec<String> = self
+            .rows
+            .iter()
+            .map(|tab| locale.t(tab.locale_key()))
+            .collect();
+        let mut right: Vec<Box<dyn View>> = self
+            .rows
+            .iter()
+            .map(|tab| {
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    self.settings.is_visible(*tab),
+                    Alignment::Right,
+                )) as Box<dyn View>
+            })
+            .collect();
+
+        left.extend(LABELS.iter().map(|key| locale.t(key)));
+        right.push(Box::new(Select::new(
+            Point::zero(),
+            self.settings.startup_tab.map_or(0, |tab| {
+                LauncherTab::iter().position(|t| t == tab).unwrap() + 1
+            }),
+            std::iter::once(locale.t("settings-tabs-startup-tab-last"))
+                .chain(LauncherTab::iter().map(|tab| locale.t(tab.locale_key())))
+                .collect(),
+            Alignment::Right,
+        )));
+
+        let startup = match &self.settings.startup {
+            StartupBehavior::LastView => locale.t("settings-tabs-startup-last-view"),
+            StartupBehavior::Folder(path) | StartupBehavior::Game(path) => path
+                .file_stem()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        right.push(Box::new(Label::new(
+            Point::zero(),
+            startup,
+            Alignment::Right,
+            None,
+        )));
+
+        right.push(Box::new(Toggle::new(
+            Point::zero(),
+            self.settings.group_consoles,
+            Alignment::Right,
+        )));
+
+        drop(locale);
+        self.list.set_items(left, right);
+    }
+
+    /// Saves the settings and shows them, keeping the tab at the row selected.
+    async fn apply(
+        &mut self,
+        selected: Option<LauncherTab>,
+        commands: &Sender<Command>,
+    ) -> Result<()> {
+        self.set_items();
+        if let Some(tab) = selected
+            && let Some(i) = self.rows.iter().position(|t| *t == tab)
+        {
+            self.list.select(i);
+        }
+        commands
+            .send(Command::SaveLauncherSettings(self.settings.clone()))
+            .await?;
+        Ok(())
+    }
+
+    async fn move_tab(&mut self, offset: isize, commands: &Sender<Command>) -> Result<()> {
+        let Some(&tab) = self.rows.get(self.list.selected()) else {
+            return Ok(());
+        };
+        if !self.settings.is_visible(tab) {
+            return Ok(());
+        }
+        self.settings.move_tab(tab, offset);
+        self.apply(Some(tab), commands).await
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Tabs {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::Y) => {
+                self.move_tab(-1, &commands).await?;
+                return Ok(true);
+            }
+            KeyEvent::Pressed(Key::X) => {
+                self.move_tab(1, &commands).await?;
+                return Ok(true);
+            }
+            // Folders and games are set to open on boot from their menu, so they're only unset here
+            KeyEvent::Pressed(Key::A)
+                if self.list.selected() == self.rows.len() + 1
+                    && self.settings.startup != StartupBehavior::LastView =>
+            {
+                self.settings.startup = StartupBehavior::LastView;
+                self.apply(None, &commands).await?;
+                return Ok(true);
+            }
+            _ => {}
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    if let Some(&tab) = self.rows.get(i) {
+                        self.settings.set_visible(tab, val.as_bool().unwrap());
+                        self.apply(Some(tab), &commands).await?;
+                    } else if i == self.rows.len() + 2 {
+                        self.settings.group_consoles = val.as_bool().unwrap();
+                        self.apply(None, &commands).await?;
+                    } else {
+                        self.settings.startup_tab = match val.as_int().unwrap() {
+                            0 => None,
+                            i => LauncherTab::iter().nth(i as usize - 1),
+                        };
+                        self.apply(None, &commands).await?;
+                    }
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl SettingsChild for Tabs {
+    fn save(&self) -> ChildState {
+        ChildState {
+            selected: self.list.selected(),
+        }
+    }
+}
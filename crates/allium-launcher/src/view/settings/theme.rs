@@ -5,11 +5,12 @@ use anyhow::Result;
 use async_trait::async_trait;
 use common::command::Command;
 use common::constants::SELECTION_MARGIN;
+use common::game_tags::Region;
 use common::geom::{Alignment, Point, Rect};
 use common::locale::Locale;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
-use common::stylesheet::{Stylesheet, StylesheetFont};
+use common::stylesheet::{ButtonIconStyle, ListDensity, Stylesheet, StylesheetFont, TagDisplay};
 use common::view::{
     ButtonHint, ButtonIcon, ColorPicker, Number, Percentage, Row, Select, SettingsList, Toggle,
     View,
@@ -18,6 +19,40 @@ use tokio::sync::mpsc::Sender;
 
 use crate::view::settings::{ChildState, SettingsChild};
 
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &[
+    "settings-theme-dark-mode",
+    "settings-theme-show-battery-level",
+    "settings-theme-show-clock",
+    "settings-theme-use-recents-carousel",
+    "settings-theme-group-favorites",
+    "settings-theme-boxart-width",
+    "settings-theme-ui-font",
+    "settings-theme-ui-font-size",
+    "settings-theme-guide-font",
+    "settings-theme-guide-font-size",
+    "settings-theme-tab-font-size",
+    "settings-theme-status-bar-font-size",
+    "settings-theme-button-hint-font-size",
+    "settings-theme-highlight-color",
+    "settings-theme-foreground-color",
+    "settings-theme-background-color",
+    "settings-theme-disabled-color",
+    "settings-theme-tab-color",
+    "settings-theme-tab-selected-color",
+    "settings-theme-button-a-color",
+    "settings-theme-button-b-color",
+    "settings-theme-button-x-color",
+    "settings-theme-button-y-color",
+    "settings-theme-list-density",
+    "settings-theme-marquee-speed",
+    "settings-theme-marquee-pause",
+    "settings-theme-tag-display",
+    "settings-theme-preferred-region",
+    "settings-theme-button-icons",
+    "settings-theme-show-button-hints",
+];
+
 pub struct Theme {
     rect: Rect,
     stylesheet: Stylesheet,
@@ -53,30 +88,7 @@ impl Theme {
                 w - 24,
                 h - 8 - ButtonIcon::diameter(&styles) - 8,
             ),
-            vec![
-                locale.t("settings-theme-dark-mode"),
-                locale.t("settings-theme-show-battery-level"),
-                locale.t("settings-theme-show-clock"),
-                locale.t("settings-theme-use-recents-carousel"),
-                locale.t("settings-theme-boxart-width"),
-                locale.t("settings-theme-ui-font"),
-                locale.t("settings-theme-ui-font-size"),
-                locale.t("settings-theme-guide-font"),
-                locale.t("settings-theme-guide-font-size"),
-                locale.t("settings-theme-tab-font-size"),
-                locale.t("settings-theme-status-bar-font-size"),
-                locale.t("settings-theme-button-hint-font-size"),
-                locale.t("settings-theme-highlight-color"),
-                locale.t("settings-theme-foreground-color"),
-                locale.t("settings-theme-background-color"),
-                locale.t("settings-theme-disabled-color"),
-                locale.t("settings-theme-tab-color"),
-                locale.t("settings-theme-tab-selected-color"),
-                locale.t("settings-theme-button-a-color"),
-                locale.t("settings-theme-button-b-color"),
-                locale.t("settings-theme-button-x-color"),
-                locale.t("settings-theme-button-y-color"),
-            ],
+            LABELS.iter().map(|key| locale.t(key)).collect(),
             vec![
                 Box::new(Toggle::new(
                     Point::zero(),
@@ -98,6 +110,11 @@ impl Theme {
                     stylesheet.use_recents_carousel,
                     Alignment::Right,
                 )),
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    stylesheet.group_favorites,
+                    Alignment::Right,
+                )),
                 Box::new(Number::new(
                     Point::zero(),
                     stylesheet.boxart_width as i32,
@@ -220,6 +237,74 @@ impl Theme {
                     stylesheet.button_y_color,
                     Alignment::Right,
                 )),
+                Box::new(Select::new(
+                    Point::zero(),
+                    stylesheet.list_density as usize,
+                    vec![
+                        locale.t("settings-theme-list-density-compact"),
+                        locale.t("settings-theme-list-density-normal"),
+                        locale.t("settings-theme-list-density-comfortable"),
+                    ],
+                    Alignment::Right,
+                )),
+                Box::new(Number::new(
+                    Point::zero(),
+                    stylesheet.marquee_speed as i32,
+                    0,
+                    20,
+                    1,
+                    |speed| {
+                        if *speed == 0 {
+                            "Disabled".to_owned()
+                        } else {
+                            format!("{}/s", speed)
+                        }
+                    },
+                    Alignment::Right,
+                )),
+                Box::new(Number::new(
+                    Point::zero(),
+                    stylesheet.marquee_pause as i32,
+                    0,
+                    5000,
+                    250,
+                    |ms| format!("{:.2}s", *ms as f32 / 1000.0),
+                    Alignment::Right,
+                )),
+                Box::new(Select::new(
+                    Point::zero(),
+                    stylesheet.tag_display as usize,
+                    vec![
+                        locale.t("settings-theme-tag-display-hidden"),
+                        locale.t("settings-theme-tag-display-region"),
+                        locale.t("settings-theme-tag-display-all"),
+                    ],
+                    Alignment::Right,
+                )),
+                Box::new(Select::new(
+                    Point::zero(),
+                    stylesheet.preferred_region.map_or(0, |r| r as usize + 1),
+                    std::iter::once(locale.t("settings-theme-preferred-region-any"))
+                        .chain(Region::all().map(|r| r.name().to_string()))
+                        .collect(),
+                    Alignment::Right,
+                )),
+                Box::new(Select::new(
+                    Point::zero(),
+                    stylesheet.button_icons as usize,
+                    vec![
+                        locale.t("settings-theme-button-icons-letters"),
+                        locale.t("settings-theme-button-icons-playstation"),
+                        locale.t("settings-theme-button-icons-xbox"),
+                        locale.t("settings-theme-button-icons-text"),
+                    ],
+                    Alignment::Right,
+                )),
+                Box::new(Toggle::new(
+                    Point::zero(),
+                    stylesheet.show_button_hints,
+                    Alignment::Right,
+                )),
             ],
             res.get::<Stylesheet>().ui_font.size + SELECTION_MARGIN,
         );
@@ -308,7 +393,7 @@ impl View for Theme {
                         0 => {
                             self.stylesheet.toggle_dark_mode();
                             self.list.set_right(
-                                11,
+                                12,
                                 Box::new(ColorPicker::new(
                                     Point::zero(),
                                     self.stylesheet.foreground_color,
@@ -316,7 +401,7 @@ impl View for Theme {
                                 )),
                             );
                             self.list.set_right(
-                                12,
+                                13,
                                 Box::new(ColorPicker::new(
                                     Point::zero(),
                                     self.stylesheet.background_color,
@@ -324,7 +409,7 @@ impl View for Theme {
                                 )),
                             );
                             self.list.set_right(
-                                13,
+                                14,
                                 Box::new(ColorPicker::new(
                                     Point::zero(),
                                     self.stylesheet.disabled_color,
@@ -332,7 +417,7 @@ impl View for Theme {
                                 )),
                             );
                             self.list.set_right(
-                                14,
+                                15,
                                 Box::new(ColorPicker::new(
                                     Point::zero(),
                                     self.stylesheet.tab_color,
@@ -340,7 +425,7 @@ impl View for Theme {
                                 )),
                             );
                             self.list.set_right(
-                                15,
+                                16,
                                 Box::new(ColorPicker::new(
                                     Point::zero(),
                                     self.stylesheet.tab_selected_color,
@@ -348,7 +433,7 @@ impl View for Theme {
                                 )),
                             );
                             self.list.set_right(
-                                16,
+                                17,
                                 Box::new(ColorPicker::new(
                                     Point::zero(),
                                     self.stylesheet.button_a_color,
@@ -356,7 +441,7 @@ impl View for Theme {
                                 )),
                             );
                             self.list.set_right(
-                                17,
+                                18,
                                 Box::new(ColorPicker::new(
                                     Point::zero(),
                                     self.stylesheet.button_b_color,
@@ -364,7 +449,7 @@ impl View for Theme {
                                 )),
                             );
                             self.list.set_right(
-                                18,
+                                19,
                                 Box::new(ColorPicker::new(
                                     Point::zero(),
                                     self.stylesheet.button_x_color,
@@ -372,7 +457,7 @@ impl View for Theme {
                                 )),
                             );
                             self.list.set_right(
-                                19,
+                                20,
                                 Box::new(ColorPicker::new(
                                     Point::zero(),
                                     self.stylesheet.button_y_color,
@@ -386,38 +471,62 @@ impl View for Theme {
                             self.stylesheet.use_recents_carousel =
                                 !self.stylesheet.use_recents_carousel
                         }
-                        4 => self.stylesheet.boxart_width = val.as_int().unwrap() as u32,
-                        5 => self
+                        4 => self.stylesheet.group_favorites = val.as_bool().unwrap(),
+                        5 => self.stylesheet.boxart_width = val.as_int().unwrap() as u32,
+                        6 => self
                             .stylesheet
                             .ui_font
                             .path
                             .clone_from(&self.fonts[val.as_int().unwrap() as usize]),
-                        6 => self.stylesheet.ui_font.size = val.as_int().unwrap() as u32,
-                        7 => self
+                        7 => self.stylesheet.ui_font.size = val.as_int().unwrap() as u32,
+                        8 => self
                             .stylesheet
                             .guide_font
                             .path
                             .clone_from(&self.fonts[val.as_int().unwrap() as usize]),
-                        8 => self.stylesheet.guide_font.size = val.as_int().unwrap() as u32,
-                        9 => self.stylesheet.tab_font_size = val.as_int().unwrap() as f32 / 100.0,
-                        10 => {
+                        9 => self.stylesheet.guide_font.size = val.as_int().unwrap() as u32,
+                        10 => self.stylesheet.tab_font_size = val.as_int().unwrap() as f32 / 100.0,
+                        11 => {
                             self.stylesheet.status_bar_font_size =
                                 val.as_int().unwrap() as f32 / 100.0
                         }
-                        11 => {
+                        12 => {
                             self.stylesheet.button_hint_font_size =
                                 val.as_int().unwrap() as f32 / 100.0
                         }
-                        12 => self.stylesheet.highlight_color = val.as_color().unwrap(),
-                        13 => self.stylesheet.foreground_color = val.as_color().unwrap(),
-                        14 => self.stylesheet.background_color = val.as_color().unwrap(),
-                        15 => self.stylesheet.disabled_color = val.as_color().unwrap(),
-                        16 => self.stylesheet.tab_color = val.as_color().unwrap(),
-                        17 => self.stylesheet.tab_selected_color = val.as_color().unwrap(),
-                        18 => self.stylesheet.button_a_color = val.as_color().unwrap(),
-                        19 => self.stylesheet.button_b_color = val.as_color().unwrap(),
-                        20 => self.stylesheet.button_x_color = val.as_color().unwrap(),
-                        21 => self.stylesheet.button_y_color = val.as_color().unwrap(),
+                        13 => self.stylesheet.highlight_color = val.as_color().unwrap(),
+                        14 => self.stylesheet.foreground_color = val.as_color().unwrap(),
+                        15 => self.stylesheet.background_color = val.as_color().unwrap(),
+                        16 => self.stylesheet.disabled_color = val.as_color().unwrap(),
+                        17 => self.stylesheet.tab_color = val.as_color().unwrap(),
+                        18 => self.stylesheet.tab_selected_color = val.as_color().unwrap(),
+                        19 => self.stylesheet.button_a_color = val.as_color().unwrap(),
+                        20 => self.stylesheet.button_b_color = val.as_color().unwrap(),
+                        21 => self.stylesheet.button_x_color = val.as_color().unwrap(),
+                        22 => self.stylesheet.button_y_color = val.as_color().unwrap(),
+                        23 => {
+                            self.stylesheet.list_density =
+                                ListDensity::from_repr(val.as_int().unwrap() as usize)
+                                    .unwrap_or_default()
+                        }
+                        24 => self.stylesheet.marquee_speed = val.as_int().unwrap() as u32,
+                        25 => self.stylesheet.marquee_pause = val.as_int().unwrap() as u32,
+                        26 => {
+                            self.stylesheet.tag_display =
+                                TagDisplay::from_repr(val.as_int().unwrap() as usize)
+                                    .unwrap_or_default()
+                        }
+                        27 => {
+                            self.stylesheet.preferred_region = (val.as_int().unwrap() as usize)
+                                .checked_sub(1)
+                                .and_then(Region::from_repr)
+                        }
+                        28 => {
+                            self.stylesheet.button_icons =
+                                ButtonIconStyle::from_repr(val.as_int().unwrap() as usize)
+                                    .unwrap_or_default()
+                        }
+                        29 => self.stylesheet.show_button_hints = val.as_bool().unwrap(),
                         _ => unreachable!("Invalid index"),
                     }
 
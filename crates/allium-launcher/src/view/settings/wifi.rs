@@ -4,27 +4,56 @@ use std::time::Duration;
 use anyhow::Result;
 use async_trait::async_trait;
 use common::command::Command;
-use common::constants::SELECTION_MARGIN;
+use common::constants::{FTP_PORT, SELECTION_MARGIN, WEB_FILE_BROWSER_PORT, WEB_REMOTE_PORT};
+use common::display::Display as DisplayTrait;
 use common::geom::{Alignment, Point, Rect};
 use common::locale::Locale;
+use common::mdns;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use common::resources::Resources;
 use common::stylesheet::Stylesheet;
-use common::view::{ButtonHint, ButtonIcon, Label, Row, SettingsList, TextBox, Toggle, View};
+use common::view::{
+    ButtonHint, ButtonIcon, Icon, Label, Row, SettingsList, TextBox, Toast, Toggle, View,
+};
 use common::wifi::{self, WiFiSettings};
 use log::warn;
 use qrcode::QrCode;
 use tokio::sync::mpsc::Sender;
 
+use crate::view::settings::wifi_networks::WifiNetworks;
 use crate::view::settings::{ChildState, SettingsChild};
 
+const NETWORKS_INDEX: usize = 2;
+
+/// Locale keys of the rows' labels.
+pub const LABELS: &[&str] = &[
+    "settings-wifi-wifi-enabled",
+    "settings-wifi-ip-address",
+    "settings-wifi-networks",
+    "settings-wifi-ntp-enabled",
+    "settings-wifi-web-file-explorer",
+    "settings-wifi-ftp-enabled",
+    "settings-wifi-syncthing",
+    "settings-wifi-ftp-address",
+    "settings-wifi-web-file-explorer-address",
+    "settings-wifi-web-remote",
+    "settings-wifi-device-name",
+    "settings-wifi-host-name",
+    "settings-wifi-web-remote-token",
+];
+
 pub struct Wifi {
     rect: Rect,
     res: Resources,
     settings: WiFiSettings,
     list: SettingsList,
-    has_ip_address: bool,
+    ip_address: Option<String>,
     check_ip_delay: Duration,
+    /// Saved and nearby networks, shown over the settings while open.
+    networks: Option<WifiNetworks>,
+    dirty: bool,
+    /// Last shown addresses of the FTP server and web file browser.
+    server_status: [String; 2],
     button_hints: Row<ButtonHint<String>>,
 }
 
@@ -44,17 +73,7 @@ impl Wifi {
                 w - 24,
                 h - 8 - ButtonIcon::diameter(&styles) - 8,
             ),
-            vec![
-                locale.t("settings-wifi-wifi-enabled"),
-                locale.t("settings-wifi-ip-address"),
-                locale.t("settings-wifi-wifi-network"),
-                locale.t("settings-wifi-wifi-password"),
-                locale.t("settings-wifi-ntp-enabled"),
-                locale.t("settings-wifi-web-file-explorer"),
-                locale.t("settings-wifi-telnet-enabled"),
-                locale.t("settings-wifi-ftp-enabled"),
-                locale.t("settings-wifi-syncthing"),
-            ],
+            LABELS.iter().map(|key| locale.t(key)).collect(),
             vec![
                 Box::new(Toggle::new(Point::zero(), settings.wifi, Alignment::Right)),
                 Box::new(Label::new(
@@ -63,19 +82,11 @@ impl Wifi {
                     Alignment::Right,
                     None,
                 )),
-                Box::new(TextBox::new(
-                    Point::zero(),
-                    res.clone(),
-                    settings.ssid.clone(),
-                    Alignment::Right,
-                    false,
-                )),
-                Box::new(TextBox::new(
+                Box::new(Label::new(
                     Point::zero(),
-                    res.clone(),
-                    settings.password.clone(),
+                    locale.t("settings-wifi-networks-manage"),
                     Alignment::Right,
-                    true,
+                    None,
                 )),
                 Box::new(Toggle::new(Point::zero(), settings.ntp, Alignment::Right)),
                 Box::new(Toggle::new(
@@ -83,16 +94,48 @@ impl Wifi {
                     settings.web_file_browser,
                     Alignment::Right,
                 )),
+                Box::new(Toggle::new(Point::zero(), settings.ftp, Alignment::Right)),
                 Box::new(Toggle::new(
                     Point::zero(),
-                    settings.telnet,
+                    settings.syncthing,
                     Alignment::Right,
                 )),
-                Box::new(Toggle::new(Point::zero(), settings.ftp, Alignment::Right)),
+                Box::new(Label::new(
+                    Point::zero(),
+                    String::new(),
+                    Alignment::Right,
+                    None,
+                )),
+                Box::new(Label::new(
+                    Point::zero(),
+                    String::new(),
+                    Alignment::Right,
+                    None,
+                )),
                 Box::new(Toggle::new(
                     Point::zero(),
-                    settings.syncthing,
+                    settings.web_remote,
+                    Alignment::Right,
+                )),
+                Box::new(TextBox::new(
+                    Point::zero(),
+                    res.clone(),
+                    settings.device_name.clone(),
+                    Alignment::Right,
+                    false,
+                )),
+                Box::new(Label::new(
+                    Point::zero(),
+                    format!("{}.local", mdns::host_name(&settings.device_name)),
+                    Alignment::Right,
+                    None,
+                )),
+                Box::new(TextBox::new(
+                    Point::zero(),
+                    res.clone(),
+                    settings.web_remote_token.clone(),
                     Alignment::Right,
+                    false,
                 )),
             ],
             res.get::<Stylesheet>().ui_font.size + SELECTION_MARGIN,
@@ -134,50 +177,87 @@ impl Wifi {
             res,
             settings,
             list,
-            has_ip_address: false,
+            ip_address: None,
             check_ip_delay: Duration::ZERO,
+            networks: None,
+            dirty: false,
+            server_status: Default::default(),
             button_hints,
         }
     }
 }
 
+impl Wifi {
+    /// Shows the addresses of the file servers if they are running.
+    fn update_server_status(&mut self) {
+        let stopped = self.res.get::<Locale>().t("settings-wifi-server-stopped");
+        let ftp = match self.ip_address.as_deref() {
+            Some(ip_address) if self.settings.ftp && wifi::ftp_running() => {
+                format!("ftp://{ip_address}:{FTP_PORT}")
+            }
+            _ => stopped.clone(),
+        };
+        let web_file_browser = match self.ip_address.as_deref() {
+            Some(ip_address)
+                if self.settings.web_file_browser && wifi::web_file_browser_running() =>
+            {
+                format!("http://{ip_address}:{WEB_FILE_BROWSER_PORT}/")
+            }
+            _ => stopped,
+        };
+
+        for (i, status) in [ftp, web_file_browser].into_iter().enumerate() {
+            if self.server_status[i] != status {
+                self.list.set_right(
+                    7 + i,
+                    Box::new(Label::new(
+                        Point::zero(),
+                        status.clone(),
+                        Alignment::Right,
+                        None,
+                    )),
+                );
+                self.server_status[i] = status;
+            }
+        }
+    }
+}
+
 #[async_trait(?Send)]
 impl View for Wifi {
     fn update(&mut self, dt: Duration) {
+        if let Some(networks) = self.networks.as_mut() {
+            networks.update(dt);
+        }
+
+        if self.check_ip_delay > dt {
+            self.check_ip_delay -= dt;
+            return;
+        }
+        self.check_ip_delay = Duration::from_secs(1);
+
         if self.settings.wifi {
-            if !self.has_ip_address {
-                if self.check_ip_delay > dt {
-                    self.check_ip_delay -= dt;
-                    return;
-                }
-                self.check_ip_delay = Duration::from_secs(1);
+            if self.ip_address.is_none() {
                 // Try to get the IP address if we don't have it yet
-                if let Some(ip_address) = wifi::ip_address() {
-                    self.has_ip_address = true;
-                    self.list.set_right(
-                        1,
-                        Box::new(Label::new(
-                            Point::zero(),
-                            ip_address,
-                            Alignment::Right,
-                            None,
-                        )),
-                    );
-                } else {
-                    let locale = self.res.get::<Locale>();
+                self.ip_address = wifi::ip_address();
+                let text = self
+                    .ip_address
+                    .clone()
+                    .unwrap_or_else(|| self.res.get::<Locale>().t("settings-wifi-connecting"));
+                self.list.set_right(
+                    1,
+                    Box::new(Label::new(Point::zero(), text, Alignment::Right, None)),
+                );
+                if self.ip_address.is_some()
+                    && let Some(ssid) = wifi::connected_ssid()
+                {
                     self.list.set_right(
-                        1,
-                        Box::new(Label::new(
-                            Point::zero(),
-                            locale.t("settings-wifi-connecting"),
-                            Alignment::Right,
-                            None,
-                        )),
+                        NETWORKS_INDEX,
+                        Box::new(Label::new(Point::zero(), ssid, Alignment::Right, None)),
                     );
                 }
             }
-        } else if self.has_ip_address {
-            self.has_ip_address = false;
+        } else if self.ip_address.take().is_some() {
             self.list.set_right(
                 1,
                 Box::new(Label::new(
@@ -188,6 +268,8 @@ impl View for Wifi {
                 )),
             );
         }
+
+        self.update_server_status();
     }
 
     fn draw(
@@ -195,6 +277,15 @@ impl View for Wifi {
         display: &mut <DefaultPlatform as Platform>::Display,
         styles: &Stylesheet,
     ) -> Result<bool> {
+        if self.dirty {
+            display.load(self.rect)?;
+            self.dirty = false;
+        }
+
+        if let Some(networks) = self.networks.as_mut() {
+            return networks.draw(display, styles);
+        }
+
         let mut drawn = false;
 
         drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
@@ -204,10 +295,17 @@ impl View for Wifi {
     }
 
     fn should_draw(&self) -> bool {
-        self.list.should_draw() || self.button_hints.should_draw()
+        if let Some(networks) = self.networks.as_ref() {
+            return self.dirty || networks.should_draw();
+        }
+        self.dirty || self.list.should_draw() || self.button_hints.should_draw()
     }
 
     fn set_should_draw(&mut self) {
+        if let Some(networks) = self.networks.as_mut() {
+            networks.set_should_draw();
+            return;
+        }
         self.list.set_should_draw();
         self.button_hints.set_should_draw();
     }
@@ -218,6 +316,37 @@ impl View for Wifi {
         commands: Sender<Command>,
         bubble: &mut VecDeque<Command>,
     ) -> Result<bool> {
+        if let Some(networks) = self.networks.as_mut() {
+            let handled = networks
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?;
+            let mut closed = false;
+            bubble.retain(|c| match c {
+                Command::CloseView => {
+                    closed = true;
+                    false
+                }
+                _ => true,
+            });
+            if closed {
+                self.networks = None;
+                // The saved networks were changed, so they mustn't be overwritten
+                self.settings = WiFiSettings::load()?;
+                // Check which network is connected again
+                self.ip_address = None;
+                self.check_ip_delay = Duration::ZERO;
+                self.dirty = true;
+                self.set_should_draw();
+            }
+            return Ok(handled);
+        }
+
+        if event == KeyEvent::Pressed(Key::A) && self.list.selected() == NETWORKS_INDEX {
+            self.networks = Some(WifiNetworks::new(self.rect, self.res.clone()));
+            self.dirty = true;
+            return Ok(true);
+        }
+
         if self
             .list
             .handle_key_event(event, commands.clone(), bubble)
@@ -227,23 +356,25 @@ impl View for Wifi {
                 if let Command::ValueChanged(i, val) = command {
                     match i {
                         0 => {
-                            self.settings.set_wifi(val.as_bool().unwrap())?;
+                            let enabled = val.as_bool().unwrap();
+                            self.settings.set_wifi(enabled)?;
+                            let text = self.res.get::<Locale>().t("settings-wifi-connected");
                             let commands = commands.clone();
                             tokio::spawn(async move {
                                 if wifi::wait_for_wifi().await.is_ok() {
                                     commands.send(Command::Redraw).await.ok();
+                                    if enabled {
+                                        let toast = Toast::new(text, Some(Duration::from_secs(3)))
+                                            .icon(Icon::Wifi);
+                                        commands.send(Command::Notify(toast)).await.ok();
+                                    }
                                 }
                             });
                         }
                         1 => {} // ip address
-                        2 => self
-                            .settings
-                            .set_ssid(val.as_string().unwrap().to_string())?,
-                        3 => self
-                            .settings
-                            .set_password(val.as_string().unwrap().to_string())?,
-                        4 => self.settings.toggle_ntp(val.as_bool().unwrap())?,
-                        5 => {
+                        NETWORKS_INDEX => {}
+                        3 => self.settings.toggle_ntp(val.as_bool().unwrap())?,
+                        4 => {
                             let enabled = val.as_bool().unwrap();
                             self.settings.toggle_web_file_browser(enabled)?;
                             if enabled {
@@ -279,9 +410,8 @@ impl View for Wifi {
                                 commands.send(Command::DismissToast).await.ok();
                             }
                         }
-                        6 => self.settings.toggle_telnet(val.as_bool().unwrap())?,
-                        7 => self.settings.toggle_ftp(val.as_bool().unwrap())?,
-                        8 => {
+                        5 => self.settings.toggle_ftp(val.as_bool().unwrap())?,
+                        6 => {
                             let enabled = val.as_bool().unwrap();
                             self.settings.toggle_syncthing(enabled)?;
                             if enabled {
@@ -317,6 +447,69 @@ impl View for Wifi {
                                 commands.send(Command::DismissToast).await.ok();
                             }
                         }
+                        7 | 8 => {} // file server addresses
+                        9 => {
+                            let enabled = val.as_bool().unwrap();
+                            self.settings.toggle_web_remote(enabled);
+                            self.list.set_right(
+                                12,
+                                Box::new(TextBox::new(
+                                    Point::zero(),
+                                    self.res.clone(),
+                                    self.settings.web_remote_token.clone(),
+                                    Alignment::Right,
+                                    false,
+                                )),
+                            );
+                            if enabled {
+                                let token = self.settings.web_remote_token.clone();
+                                let (fg_color, bg_color) = {
+                                    let styles = self.res.get::<Stylesheet>();
+                                    (styles.foreground_color, styles.highlight_color)
+                                };
+                                let commands = commands.clone();
+                                tokio::spawn(async move {
+                                    if wifi::wait_for_wifi().await.is_ok()
+                                        && let Some(ip_address) = wifi::ip_address()
+                                    {
+                                        let url = format!(
+                                            "http://{ip_address}:{WEB_REMOTE_PORT}/?token={token}"
+                                        );
+                                        let Ok(code) = QrCode::new(url.as_bytes()) else {
+                                            warn!("Failed to generate QR code for web remote");
+                                            return;
+                                        };
+                                        let image = code
+                                            .render::<image::Rgba<u8>>()
+                                            .dark_color(fg_color.into())
+                                            .light_color(bg_color.into())
+                                            .min_dimensions(300, 300)
+                                            .build();
+                                        commands
+                                            .send(Command::ImageToast(image, url, None))
+                                            .await
+                                            .ok();
+                                    }
+                                });
+                            } else {
+                                commands.send(Command::DismissToast).await.ok();
+                            }
+                        }
+                        10 => {
+                            self.settings.device_name = val.as_string().unwrap();
+                            let host_name = mdns::host_name(&self.settings.device_name);
+                            self.list.set_right(
+                                11,
+                                Box::new(Label::new(
+                                    Point::zero(),
+                                    format!("{host_name}.local"),
+                                    Alignment::Right,
+                                    None,
+                                )),
+                            );
+                        }
+                        11 => {} // host name
+                        12 => self.settings.web_remote_token = val.as_string().unwrap(),
                         _ => unreachable!("Invalid index"),
                     }
                 }
@@ -336,10 +529,16 @@ impl View for Wifi {
     }
 
     fn children(&self) -> Vec<&dyn View> {
+        if let Some(networks) = self.networks.as_ref() {
+            return vec![networks];
+        }
         vec![&self.list, &self.button_hints]
     }
 
     fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        if let Some(networks) = self.networks.as_mut() {
+            return vec![networks];
+        }
         vec![&mut self.list, &mut self.button_hints]
     }
 
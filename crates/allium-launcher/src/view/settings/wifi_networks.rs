@@ -0,0 +1,466 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::{Command, Value};
+use common::constants::SELECTION_MARGIN;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Keyboard, Label, Row, SettingsList, View};
+use common::wifi::{self, ScannedNetwork, WiFiNetwork, WiFiSettings};
+use log::warn;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+const SCAN_INDEX: usize = 0;
+/// Index of the first saved network. Networks found by scanning that aren't saved are listed
+/// after the saved ones.
+const SAVED_INDEX: usize = 1;
+/// How often the connected network is checked while the list is open.
+const CHECK_CONNECTED_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Lists the saved networks and the networks found by scanning. Opened from the Wi-Fi settings.
+pub struct WifiNetworks {
+    rect: Rect,
+    res: Resources,
+    settings: WiFiSettings,
+    list: SettingsList,
+    /// Networks found by the last scan.
+    scanned: Vec<ScannedNetwork>,
+    scanning: bool,
+    /// SSID of the network that Wi-Fi is connected to.
+    connected: Option<String>,
+    check_connected_delay: Duration,
+    checking_connected: bool,
+    /// Keyboard for the password of the network that is being connected to.
+    keyboard: Option<(String, Keyboard)>,
+    tx: Sender<Vec<ScannedNetwork>>,
+    scans: Receiver<Vec<ScannedNetwork>>,
+    connected_tx: Sender<Option<String>>,
+    connected_rx: Receiver<Option<String>>,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl WifiNetworks {
+    pub fn new(rect: Rect, res: Resources) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let settings = WiFiSettings::load().unwrap();
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            Vec::new(),
+            Vec::new(),
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                rect.x + rect.w as i32 - 12,
+                rect.y + rect.h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-select"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::Y,
+                    locale.t("settings-wifi-networks-raise-priority"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::X,
+                    locale.t("settings-wifi-networks-forget"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        let (tx, scans) = mpsc::channel(1);
+        let (connected_tx, connected_rx) = mpsc::channel(1);
+        let mut this = Self {
+            rect,
+            res,
+            settings,
+            list,
+            scanned: Vec::new(),
+            scanning: false,
+            connected: None,
+            check_connected_delay: Duration::ZERO,
+            checking_connected: false,
+            keyboard: None,
+            tx,
+            scans,
+            connected_tx,
+            connected_rx,
+            button_hints,
+        };
+        this.update_list();
+        this
+    }
+
+    /// Networks found by the last scan that aren't saved.
+    fn unsaved(&self) -> impl Iterator<Item = &ScannedNetwork> {
+        self.scanned
+            .iter()
+            .filter(|n| !self.settings.networks.iter().any(|s| s.ssid == n.ssid))
+    }
+
+    /// Rebuilds the list from the saved networks and the networks found by the last scan.
+    fn update_list(&mut self) {
+        let locale = self.res.get::<Locale>();
+
+        let mut left = vec![locale.t("settings-wifi-networks-scan")];
+        let mut right = vec![value(if self.scanning {
+            locale.t("settings-wifi-networks-scanning")
+        } else {
+            locale.t("settings-wifi-networks-scan-start")
+        })];
+        for network in &self.settings.networks {
+            left.push(network.ssid.clone());
+            right.push(value(if self.connected.as_ref() == Some(&network.ssid) {
+                locale.t("settings-wifi-networks-connected")
+            } else if let Some(scanned) = self.scanned.iter().find(|n| n.ssid == network.ssid) {
+                format!("{}%", scanned.signal)
+            } else {
+                locale.t("settings-wifi-networks-saved")
+            }));
+        }
+        for network in self.unsaved() {
+            left.push(network.ssid.clone());
+            right.push(value(format!("{}%", network.signal)));
+        }
+
+        drop(locale);
+
+        let selected = self.list.selected().min(left.len() - 1);
+        self.list.set_items(left, right);
+        self.list.select(selected);
+    }
+
+    async fn toast(&self, commands: &Sender<Command>, key: &str) -> Result<()> {
+        let locale = self.res.get::<Locale>();
+        commands
+            .send(Command::Toast(locale.t(key), Some(Duration::from_secs(3))))
+            .await?;
+        Ok(())
+    }
+
+    /// Scans for networks in the background. They're listed when the scan finishes.
+    async fn scan(&mut self, commands: &Sender<Command>) -> Result<()> {
+        if !self.settings.wifi {
+            return self
+                .toast(commands, "settings-wifi-networks-not-enabled")
+                .await;
+        }
+        if self.scanning {
+            return Ok(());
+        }
+
+        self.scanning = true;
+        self.update_list();
+
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            let networks = wifi::scan().await.unwrap_or_else(|e| {
+                warn!("failed to scan for wifi networks: {}", e);
+                Vec::new()
+            });
+            tx.send(networks).await.ok();
+        });
+        Ok(())
+    }
+
+    /// Selects a network, asking for its password first if it isn't saved and needs one.
+    async fn select(&mut self, index: usize, commands: &Sender<Command>) -> Result<()> {
+        if !self.settings.wifi {
+            return self
+                .toast(commands, "settings-wifi-networks-not-enabled")
+                .await;
+        }
+
+        let saved = self.settings.networks.len();
+        if index < SAVED_INDEX + saved {
+            let network = self.settings.networks[index - SAVED_INDEX].clone();
+            return self.connect(network, commands).await;
+        }
+
+        let Some(network) = self.unsaved().nth(index - SAVED_INDEX - saved).cloned() else {
+            return Ok(());
+        };
+        if network.secured {
+            let keyboard = Keyboard::new(self.res.clone(), String::new(), true);
+            self.keyboard = Some((network.ssid, keyboard));
+            Ok(())
+        } else {
+            self.connect(
+                WiFiNetwork {
+                    ssid: network.ssid,
+                    password: String::new(),
+                },
+                commands,
+            )
+            .await
+        }
+    }
+
+    /// Saves the network with the highest priority and connects to it in the background.
+    async fn connect(&mut self, network: WiFiNetwork, commands: &Sender<Command>) -> Result<()> {
+        let failed = {
+            let locale = self.res.get::<Locale>();
+            let mut map = HashMap::new();
+            map.insert("name".into(), network.ssid.clone().into());
+            locale.ta("settings-wifi-networks-connect-failed", &map)
+        };
+
+        self.settings.add_network(network);
+        self.settings.save()?;
+        self.update_list();
+        let connecting = self
+            .res
+            .get::<Locale>()
+            .t("settings-wifi-networks-connecting");
+        self.list.set_right(SAVED_INDEX, value(connecting));
+
+        let commands = commands.clone();
+        tokio::spawn(async move {
+            if let Err(e) = wifi::reconnect().await {
+                warn!("failed to reconnect to wifi: {}", e);
+                commands
+                    .send(Command::Toast(failed, Some(Duration::from_secs(3))))
+                    .await
+                    .ok();
+            }
+        });
+        Ok(())
+    }
+
+    /// Forgets a saved network, disconnecting from it if it's connected.
+    fn forget(&mut self, index: usize) -> Result<()> {
+        let Some(network) = self.settings.networks.get(index - SAVED_INDEX) else {
+            return Ok(());
+        };
+        let connected = self.connected.as_ref() == Some(&network.ssid);
+        self.settings.remove_network(index - SAVED_INDEX);
+        self.settings.save()?;
+        self.update_list();
+
+        if connected {
+            tokio::spawn(async move {
+                if let Err(e) = wifi::reconnect().await {
+                    warn!("failed to reconnect to wifi: {}", e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Moves a saved network above the one before it.
+    fn raise_priority(&mut self, index: usize) -> Result<()> {
+        if index <= SAVED_INDEX || index >= SAVED_INDEX + self.settings.networks.len() {
+            return Ok(());
+        }
+        self.settings.raise_priority(index - SAVED_INDEX);
+        self.settings.save()?;
+        self.update_list();
+        self.list.select(index - 1);
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl View for WifiNetworks {
+    fn update(&mut self, dt: Duration) {
+        let mut changed = false;
+        while let Ok(networks) = self.scans.try_recv() {
+            self.scanning = false;
+            self.scanned = networks;
+            changed = true;
+        }
+
+        while let Ok(connected) = self.connected_rx.try_recv() {
+            self.checking_connected = false;
+            if connected != self.connected {
+                self.connected = connected;
+                changed = true;
+            }
+        }
+
+        if self.check_connected_delay > dt {
+            self.check_connected_delay -= dt;
+        } else if !self.checking_connected {
+            self.check_connected_delay = CHECK_CONNECTED_INTERVAL;
+            self.checking_connected = true;
+            let tx = self.connected_tx.clone();
+            tokio::spawn(async move {
+                let connected = tokio::task::spawn_blocking(wifi::connected_ssid)
+                    .await
+                    .unwrap_or_default();
+                tx.send(connected).await.ok();
+            });
+        }
+
+        if changed {
+            self.update_list();
+        }
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        if let Some((_, keyboard)) = self.keyboard.as_mut() {
+            if drawn {
+                keyboard.set_should_draw();
+            }
+            drawn |= keyboard.should_draw() && keyboard.draw(display, styles)?;
+        }
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw()
+            || self.button_hints.should_draw()
+            || self.keyboard.as_ref().is_some_and(|(_, k)| k.should_draw())
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+        if let Some((_, keyboard)) = self.keyboard.as_mut() {
+            keyboard.set_should_draw();
+        }
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some((_, keyboard)) = self.keyboard.as_mut() {
+            keyboard
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?;
+            let mut password = None;
+            let mut closed = false;
+            bubble.retain_mut(|c| match c {
+                Command::ValueChanged(_, Value::String(val)) => {
+                    password = Some(val.clone());
+                    false
+                }
+                Command::CloseView => {
+                    closed = true;
+                    false
+                }
+                _ => true,
+            });
+            if closed && let Some((ssid, _)) = self.keyboard.take() {
+                self.set_should_draw();
+                match password {
+                    Some(password) if !WiFiNetwork::is_valid_password(&password) => {
+                        self.toast(&commands, "settings-wifi-networks-invalid-password")
+                            .await?;
+                    }
+                    Some(password) => {
+                        self.connect(WiFiNetwork { ssid, password }, &commands)
+                            .await?;
+                    }
+                    None => {}
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                match self.list.selected() {
+                    SCAN_INDEX => self.scan(&commands).await?,
+                    i => self.select(i, &commands).await?,
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::X) => {
+                if self.list.selected() >= SAVED_INDEX {
+                    self.forget(self.list.selected())?;
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Y) => {
+                self.raise_priority(self.list.selected())?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        let mut children: Vec<&dyn View> = vec![&self.list, &self.button_hints];
+        if let Some((_, keyboard)) = self.keyboard.as_ref() {
+            children.push(keyboard);
+        }
+        children
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        let mut children: Vec<&mut dyn View> = vec![&mut self.list, &mut self.button_hints];
+        if let Some((_, keyboard)) = self.keyboard.as_mut() {
+            children.push(keyboard);
+        }
+        children
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+fn value(text: String) -> Box<dyn View> {
+    Box::new(Label::new(Point::zero(), text, Alignment::Right, None))
+}
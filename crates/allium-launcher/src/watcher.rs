@@ -0,0 +1,145 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use anyhow::Result;
+use common::command::Command;
+use common::constants::{ALLIUM_GAMES_DIR, GAMES_WATCHER_DEBOUNCE};
+use inotify::{EventMask, Inotify, WatchDescriptor, WatchMask};
+use log::{debug, error, warn};
+use tokio::sync::mpsc::{self, Sender};
+
+use crate::entry::directory::VisitedDirs;
+
+/// Watches the games folder for added, removed or renamed files while the launcher runs, so
+/// that games copied over FTP show up without repopulating the database.
+///
+/// Once the changes settle, the directories that changed are sent as `Command::GamesChanged`.
+pub fn spawn(commands: Sender<Command>) -> Result<()> {
+    let inotify = Inotify::init()?;
+    let (tx, mut rx) = mpsc::channel(100);
+
+    thread::spawn(move || {
+        if let Err(e) = watch(inotify, tx) {
+            error!("stopped watching games folder: {}", e);
+        }
+    });
+
+    tokio::spawn(async move {
+        while let Some(dir) = rx.recv().await {
+            // Copying a game takes several events, so wait until they stop
+            let mut dirs = HashSet::from([dir]);
+            while let Ok(Some(dir)) = tokio::time::timeout(GAMES_WATCHER_DEBOUNCE, rx.recv()).await
+            {
+                dirs.insert(dir);
+            }
+            debug!("games changed in {:?}", dirs);
+            let dirs = dirs.into_iter().collect();
+            if commands.send(Command::GamesChanged(dirs)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Blocks reading events, sending the directories that changed. Removed directories are sent
+/// too, so that their games can be removed.
+fn watch(mut inotify: Inotify, tx: Sender<PathBuf>) -> Result<()> {
+    let mut watches = HashMap::new();
+    add_watches(
+        &mut inotify,
+        &mut watches,
+        &ALLIUM_GAMES_DIR,
+        &mut Vec::new(),
+        &mut VisitedDirs::new(),
+    );
+
+    let mut buffer = [0; 4096];
+    loop {
+        let mut changed = Vec::new();
+        let mut added = Vec::new();
+        for event in inotify.read_events_blocking(&mut buffer)? {
+            if event.mask.contains(EventMask::Q_OVERFLOW) {
+                warn!("too many changes to the games folder, some were missed");
+                continue;
+            }
+            if event.mask.contains(EventMask::IGNORED) {
+                watches.remove(&event.wd);
+                continue;
+            }
+            let (Some(dir), Some(name)) = (watches.get(&event.wd), event.name) else {
+                continue;
+            };
+            changed.push(dir.clone());
+
+            if event.mask.contains(EventMask::ISDIR) {
+                let path = dir.join(name);
+                if event
+                    .mask
+                    .intersects(EventMask::CREATE | EventMask::MOVED_TO)
+                {
+                    added.push(path);
+                } else {
+                    changed.push(path);
+                }
+            }
+        }
+
+        for dir in added {
+            add_watches(
+                &mut inotify,
+                &mut watches,
+                &dir,
+                &mut changed,
+                &mut VisitedDirs::new(),
+            );
+        }
+        for dir in changed {
+            tx.blocking_send(dir)?;
+        }
+    }
+}
+
+/// Watches a directory and its subdirectories, pushing them onto `added`. Symlinks to folders
+/// that were already visited are skipped, so that a symlink to a parent folder isn't followed
+/// forever.
+fn add_watches(
+    inotify: &mut Inotify,
+    watches: &mut HashMap<WatchDescriptor, PathBuf>,
+    dir: &Path,
+    added: &mut Vec<PathBuf>,
+    visited: &mut VisitedDirs,
+) {
+    if !visited.visit(dir) {
+        return;
+    }
+    let mask = WatchMask::CREATE
+        | WatchMask::CLOSE_WRITE
+        | WatchMask::DELETE
+        | WatchMask::MOVED_FROM
+        | WatchMask::MOVED_TO;
+    match inotify.watches().add(dir, mask) {
+        Ok(wd) => {
+            watches.insert(wd, dir.to_path_buf());
+        }
+        Err(e) => {
+            warn!("failed to watch {}: {}", dir.display(), e);
+            return;
+        }
+    }
+    added.push(dir.to_path_buf());
+
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in read_dir.filter_map(std::result::Result::ok) {
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if !hidden && path.is_dir() {
+            add_watches(inotify, watches, &path, added, visited);
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use base32::encode;
@@ -11,8 +12,9 @@ use common::geom;
 use common::locale::{Locale, LocaleSettings};
 use common::platform::{DefaultPlatform, Platform};
 use common::resources::Resources;
+use common::status::DeviceStatus;
 use common::stylesheet::Stylesheet;
-use common::view::View;
+use common::view::{Toast, set_redraw_sender};
 use embedded_graphics::prelude::*;
 use log::{info, warn};
 use sha2::{Digest, Sha256};
@@ -20,10 +22,34 @@ use type_map::TypeMap;
 
 use crate::retroarch_info::RetroArchInfo;
 use crate::view::ingame_menu::IngameMenu;
+use crate::view::layers::LayerStack;
 
 #[cfg(unix)]
 use tokio::signal::unix::SignalKind;
 
+/// Loads the resources shared by the menu's views.
+pub fn load_resources(size: geom::Size, styles: Stylesheet, locale: Locale) -> Result<Resources> {
+    let mut res = TypeMap::new();
+    res.insert(Database::new()?);
+    res.insert(GameInfo::load()?.unwrap_or_default());
+    res.insert(styles);
+    res.insert(locale);
+    res.insert(size);
+    Ok(Resources::new(res))
+}
+
+/// How far the game's screen is blurred behind the menu, in pixels.
+const BACKDROP_BLUR_RADIUS: u32 = 4;
+
+/// Blurs the game's screen and blends it with the background color, so that the menu stands
+/// out over it.
+pub fn draw_backdrop(display: &mut impl Display, styles: &Stylesheet) -> Result<()> {
+    // Two box blurs look close to a gaussian blur
+    display.blur(BACKDROP_BLUR_RADIUS)?;
+    display.blur(BACKDROP_BLUR_RADIUS)?;
+    display.map_pixels(|pixel| pixel.blend(styles.background_color.overlay(pixel), 192))
+}
+
 pub struct AlliumMenu<P>
 where
     P: Platform,
@@ -31,47 +57,48 @@ where
     platform: P,
     display: P::Display,
     res: Resources,
-    view: IngameMenu<P::Battery>,
+    layers: LayerStack,
 }
 
 impl AlliumMenu<DefaultPlatform> {
     pub async fn new(mut platform: DefaultPlatform, info: Option<RetroArchInfo>) -> Result<Self> {
         let display = platform.display()?;
-        let battery = platform.battery()?;
+        let battery = DeviceStatus::load()?;
         let rect = display.bounding_box().into();
 
-        let mut res = TypeMap::new();
-        res.insert(Database::new()?);
-        res.insert(GameInfo::load()?.unwrap_or_default());
-        res.insert(Stylesheet::load()?);
-        res.insert(Locale::new(&LocaleSettings::load()?.lang));
-        res.insert(Into::<geom::Size>::into(display.size()));
-        let res = Resources::new(res);
+        let styles = Stylesheet::load()?;
+        let locale = Locale::from_settings(&LocaleSettings::load()?);
+        let res = load_resources(display.size().into(), styles, locale)?;
+
+        let menu = IngameMenu::load_or_new(rect, res.clone(), battery, info).await?;
 
         Ok(AlliumMenu {
             platform,
             display,
-            res: res.clone(),
-            view: IngameMenu::load_or_new(rect, res, battery, info).await?,
+            res,
+            layers: LayerStack::new(rect, Box::new(menu)),
         })
     }
 
     pub async fn run_event_loop(&mut self) -> Result<()> {
         self.display.save()?;
-        {
-            let styles = self.res.get::<Stylesheet>();
-            self.display
-                .map_pixels(|pixel| pixel.blend(styles.background_color.overlay(pixel), 192))?;
-        }
+        draw_backdrop(&mut self.display, &self.res.get())?;
         self.display.save()?;
 
         #[cfg(unix)]
         let mut sigterm = tokio::signal::unix::signal(SignalKind::terminate())?;
 
         let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        set_redraw_sender(tx.clone());
+
+        let mut frame_interval = tokio::time::interval(Duration::from_micros(166_667));
 
+        let mut last_frame = Instant::now();
         loop {
-            if self.view.should_draw() && self.view.draw(&mut self.display, &self.res.get())? {
+            self.layers.update(last_frame.elapsed());
+            last_frame = Instant::now();
+
+            if self.layers.should_draw() && self.layers.draw(&mut self.display, &self.res.get())? {
                 self.display.flush()?;
             }
 
@@ -84,9 +111,9 @@ impl AlliumMenu<DefaultPlatform> {
                     self.handle_command(command)?;
                 }
                 event = self.platform.poll() => {
-                    let mut bubble = VecDeque::new();
-                    self.view.handle_key_event(event, tx.clone(), &mut bubble).await?;
+                    self.layers.handle_key_event(event, tx.clone()).await?;
                 }
+                _ = frame_interval.tick() => {}
                 else => {}
             }
 
@@ -96,9 +123,9 @@ impl AlliumMenu<DefaultPlatform> {
                     self.handle_command(command)?;
                 }
                 event = self.platform.poll() => {
-                    let mut bubble = VecDeque::new();
-                    self.view.handle_key_event(event, tx.clone(), &mut bubble).await?;
+                    self.layers.handle_key_event(event, tx.clone()).await?;
                 }
+                _ = frame_interval.tick() => {}
                 else => {}
             }
         }
@@ -107,7 +134,7 @@ impl AlliumMenu<DefaultPlatform> {
     fn handle_command(&mut self, command: Command) -> Result<()> {
         match command {
             Command::Exit => {
-                self.view.save()?;
+                self.layers.save()?;
                 if self.display.pop() {
                     self.display.load(self.display.bounding_box().into())?;
                     self.display.flush()?;
@@ -115,8 +142,23 @@ impl AlliumMenu<DefaultPlatform> {
                 std::process::exit(0);
             }
             Command::Redraw => {
-                self.display.load(self.display.bounding_box().into())?;
-                self.view.set_should_draw();
+                self.layers.set_should_draw();
+            }
+            Command::Toast(text, duration) => {
+                self.layers.toast(Toast::new(text, duration))?;
+            }
+            Command::ImageToast(image, text, duration) => {
+                let toast = Toast::with_image(image, text, duration);
+                self.layers.toast(toast)?;
+            }
+            Command::Notify(toast) => {
+                self.layers.toast(toast)?;
+            }
+            Command::DismissToast => {
+                self.layers.dismiss_toasts()?;
+            }
+            Command::Rumble(rumble) => {
+                rumble.play();
             }
             Command::SaveStateScreenshot { path, core, slot } => {
                 if self.display.pop() {
@@ -159,6 +201,41 @@ impl AlliumMenu<DefaultPlatform> {
                     )?;
                 }
             }
+            Command::SaveBoxArt(game_path) => {
+                if self.display.pop() {
+                    self.display.load(self.display.bounding_box().into())?;
+                    self.display.flush()?;
+
+                    let image_path = box_art_path(&game_path);
+                    if let Some(dir) = image_path.parent() {
+                        std::fs::create_dir_all(dir)?;
+                    }
+                    backup_box_art(&image_path)?;
+                    info!("saving box art to {:?}", image_path);
+
+                    #[cfg(feature = "miyoo")]
+                    {
+                        let mut command = std::process::Command::new("screenshot");
+                        command.arg(&image_path).arg("--crop");
+                        let width = self.res.get::<Stylesheet>().boxart_width;
+                        if width > 0 {
+                            command.arg(format!("--width={}", width));
+                        }
+                        // Waits for the image, so that it's there when the launcher shows it
+                        command.status()?;
+                    }
+
+                    #[cfg(feature = "simulator")]
+                    std::fs::copy(
+                        common::constants::ALLIUM_SD_ROOT.join("bg-640x480.png"),
+                        &image_path,
+                    )?;
+
+                    self.res
+                        .get::<Database>()
+                        .set_custom_image(&game_path, &image_path)?;
+                }
+            }
             command => {
                 warn!("unhandled command: {:?}", command);
             }
@@ -166,3 +243,52 @@ impl AlliumMenu<DefaultPlatform> {
         Ok(())
     }
 }
+
+/// Returns where the box art of a game is saved: in the `Imgs` folder next to it, named after
+/// the game, where the launcher looks for box art.
+fn box_art_path(game: &Path) -> PathBuf {
+    let mut path = game.with_file_name("Imgs");
+    path.push(game.file_stem().unwrap_or_default());
+    path.set_extension("png");
+    path
+}
+
+/// Keeps the box art that a game had before it was first replaced by a frame, as
+/// `<stem>.png.bak` next to it, so that it can be restored by renaming it back.
+fn backup_box_art(image: &Path) -> std::io::Result<()> {
+    let backup = image.with_extension("png.bak");
+    if image.exists() && !backup.exists() {
+        info!("backing up box art to {:?}", backup);
+        std::fs::rename(image, backup)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_box_art_keeps_original() {
+        let dir = std::env::temp_dir().join(format!("allium-box-art-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let image = dir.join("Game.png");
+        let backup = dir.join("Game.png.bak");
+
+        // Nothing to back up yet
+        backup_box_art(&image).unwrap();
+        assert!(!backup.exists());
+
+        std::fs::write(&image, "original").unwrap();
+        backup_box_art(&image).unwrap();
+        assert!(!image.exists());
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), "original");
+
+        // Saving again doesn't replace the original with an earlier frame
+        std::fs::write(&image, "frame").unwrap();
+        backup_box_art(&image).unwrap();
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), "original");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
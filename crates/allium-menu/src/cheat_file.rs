@@ -0,0 +1,208 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use common::constants::RETROARCH_CHEATS_DIR;
+
+/// A single cheat in a RetroArch cheat file.
+#[derive(Debug, Clone)]
+pub struct Cheat {
+    pub desc: String,
+    pub enabled: bool,
+}
+
+/// A RetroArch cheat file (`.cht`).
+#[derive(Debug, Clone)]
+pub struct CheatFile {
+    pub path: PathBuf,
+    pub cheats: Vec<Cheat>,
+}
+
+impl CheatFile {
+    /// Finds the cheat files in the RetroArch cheats directory that match the given game. RetroArch
+    /// keeps a directory of cheats per system, with files named after the rom or the game.
+    pub fn find(game: &Path, name: &str) -> Vec<PathBuf> {
+        Self::find_in(&RETROARCH_CHEATS_DIR, game, name)
+    }
+
+    fn find_in(cheats_dir: &Path, game: &Path, name: &str) -> Vec<PathBuf> {
+        let mut file_names = vec![format!("{name}.cht")];
+        if let Some(stem) = game.file_stem().and_then(std::ffi::OsStr::to_str) {
+            file_names.push(format!("{stem}.cht"));
+        }
+        file_names.dedup();
+
+        let Ok(entries) = fs::read_dir(cheats_dir) else {
+            return Vec::new();
+        };
+        let mut files = entries
+            .flatten()
+            .flat_map(|entry| {
+                let dir = entry.path();
+                file_names.iter().map(move |file_name| dir.join(file_name))
+            })
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+        files.sort_unstable();
+        files
+    }
+
+    /// Loads a cheat file, with the cheats enabled as they are in the file.
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let text = fs::read_to_string(&path)?;
+
+        let mut count = 0;
+        let mut descs = Vec::new();
+        let mut enabled = Vec::new();
+        for (key, value) in text.lines().filter_map(parse_line) {
+            if key == "cheats" {
+                count = value.parse().unwrap_or(0);
+            } else if let Some(index) = cheat_key(key, "_desc") {
+                descs.push((index, value.to_string()));
+            } else if let Some(index) = cheat_key(key, "_enable") {
+                enabled.push((index, value == "true"));
+            }
+        }
+
+        let mut cheats = vec![
+            Cheat {
+                desc: String::new(),
+                enabled: false,
+            };
+            count
+        ];
+        for (index, desc) in descs {
+            if let Some(cheat) = cheats.get_mut(index) {
+                cheat.desc = desc;
+            }
+        }
+        for (index, value) in enabled {
+            if let Some(cheat) = cheats.get_mut(index) {
+                cheat.enabled = value;
+            }
+        }
+        for (i, cheat) in cheats.iter_mut().enumerate() {
+            if cheat.desc.is_empty() {
+                cheat.desc = format!("Cheat {}", i + 1);
+            }
+        }
+
+        Ok(Self { path, cheats })
+    }
+
+    /// Applies the cheats that were toggled for the game, by index. The other cheats keep the
+    /// state they have in the file.
+    pub fn apply_toggled(&mut self, toggled: &[(usize, bool)]) {
+        for &(index, enabled) in toggled {
+            if let Some(cheat) = self.cheats.get_mut(index) {
+                cheat.enabled = enabled;
+            }
+        }
+    }
+
+    /// Returns the indices of the cheats whose enabled state differs from `applied`, the state
+    /// RetroArch has them in. Cheats missing from `applied` are taken to be disabled.
+    pub fn changes(&self, applied: &[bool]) -> Vec<usize> {
+        self.cheats
+            .iter()
+            .enumerate()
+            .filter(|(i, cheat)| cheat.enabled != applied.get(*i).copied().unwrap_or(false))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// Parses a `key = "value"` line.
+fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once('=')?;
+    Some((key.trim(), value.trim().trim_matches('"')))
+}
+
+/// Parses the index out of a key of the form `cheat<index><suffix>`.
+fn cheat_key(key: &str, suffix: &str) -> Option<usize> {
+    key.strip_prefix("cheat")?.strip_suffix(suffix)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHEAT_FILE: &str = include_str!("test/Game One.cht");
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("allium-cheats-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load() -> Result<()> {
+        let dir = temp_dir("load");
+        let path = dir.join("Game One.cht");
+        fs::write(&path, CHEAT_FILE)?;
+
+        let file = CheatFile::load(path)?;
+        let cheats = file
+            .cheats
+            .iter()
+            .map(|cheat| (cheat.desc.as_str(), cheat.enabled))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cheats,
+            vec![
+                ("Infinite Lives", true),
+                ("Max Money", false),
+                ("Cheat 3", false),
+            ]
+        );
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_toggled_and_changes() -> Result<()> {
+        let dir = temp_dir("toggle");
+        let path = dir.join("Game One.cht");
+        fs::write(&path, CHEAT_FILE)?;
+
+        let mut file = CheatFile::load(path.clone())?;
+        let defaults = file.cheats.iter().map(|c| c.enabled).collect::<Vec<_>>();
+        file.apply_toggled(&[(0, false), (1, true), (5, true)]);
+        assert_eq!(
+            file.cheats.iter().map(|c| c.enabled).collect::<Vec<_>>(),
+            vec![false, true, false]
+        );
+        assert_eq!(file.changes(&defaults), vec![0, 1]);
+        assert_eq!(file.changes(&[false, true]), Vec::<usize>::new());
+
+        // The cheat file itself is never written
+        assert_eq!(fs::read_to_string(&path)?, CHEAT_FILE);
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_find() -> Result<()> {
+        let dir = temp_dir("find");
+        fs::create_dir_all(dir.join("Nintendo - Game Boy"))?;
+        fs::create_dir_all(dir.join("Sega - Mega Drive"))?;
+        fs::write(dir.join("Nintendo - Game Boy/Game One.cht"), CHEAT_FILE)?;
+        fs::write(dir.join("Nintendo - Game Boy/game1.cht"), CHEAT_FILE)?;
+        fs::write(dir.join("Sega - Mega Drive/Other Game.cht"), CHEAT_FILE)?;
+
+        assert_eq!(
+            CheatFile::find_in(&dir, Path::new("Roms/GB/game1.gb"), "Game One"),
+            vec![
+                dir.join("Nintendo - Game Boy/Game One.cht"),
+                dir.join("Nintendo - Game Boy/game1.cht"),
+            ]
+        );
+        assert!(CheatFile::find_in(&dir, Path::new("Roms/GB/game2.gb"), "Game Two").is_empty());
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -2,7 +2,10 @@
 #![warn(rust_2018_idioms)]
 
 mod allium_menu;
+mod cheat_file;
 mod retroarch_info;
+#[cfg(feature = "simulator")]
+mod snapshot;
 pub mod view;
 
 use std::time::Duration;
@@ -11,16 +14,33 @@ use anyhow::Result;
 
 use allium_menu::AlliumMenu;
 use common::{
+    logging::{self, LogComponent},
     platform::{DefaultPlatform, Platform},
     retroarch::RetroArchCommand,
 };
-use simple_logger::SimpleLogger;
 
 use crate::retroarch_info::RetroArchInfo;
 
+#[cfg(feature = "simulator")]
+#[derive(clap::Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Renders every screen to PNG files in this directory, then exits
+    #[arg(long)]
+    snapshot: Option<std::path::PathBuf>,
+
+    /// Theme to render snapshots with, by name
+    #[arg(long)]
+    theme: Option<String>,
+
+    /// Language to render snapshots with
+    #[arg(long)]
+    lang: Option<String>,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    SimpleLogger::new().env().init().unwrap();
+    logging::init(LogComponent::Menu);
 
     #[cfg(not(feature = "simulator"))]
     let info = RetroArchCommand::GetInfo.send_recv().await?.map(|ret| {
@@ -44,13 +64,30 @@ async fn main() -> Result<()> {
         state_slot: Some(0),
     });
 
-    if info.is_some() {
-        RetroArchCommand::Pause.send().await?;
-        tokio::time::sleep(Duration::from_millis(50)).await;
+    #[cfg(feature = "simulator")]
+    {
+        use clap::Parser;
+
+        let cli = Cli::parse();
+        if let Some(dir) = cli.snapshot {
+            let config = common::snapshot::SnapshotConfig {
+                theme: cli.theme,
+                lang: cli.lang,
+            };
+            return snapshot::snapshot(DefaultPlatform::new()?, dir, config, info);
+        }
     }
 
+    // The game's last frame is read before pausing, as RetroArch may draw over it when paused
+    let is_running = info.is_some();
     let platform = DefaultPlatform::new()?;
     let mut app = AlliumMenu::new(platform, info).await?;
+
+    if is_running {
+        RetroArchCommand::Pause.send().await?;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
     app.run_event_loop().await?;
     Ok(())
 }
@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use common::game_info::GameInfo;
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Platform};
+use common::snapshot::{SnapshotConfig, Snapshotter};
+use common::stylesheet::Stylesheet;
+use common::view::{Keyboard, TextReader, Toast};
+use embedded_graphics::prelude::*;
+
+use crate::allium_menu::{draw_backdrop, load_resources};
+use crate::retroarch_info::RetroArchInfo;
+use crate::view::ingame_menu::IngameMenu;
+use crate::view::layers::{LayerKind, LayerStack};
+
+/// Renders the menu and the layers opened over it to `dir`, without saving any state.
+pub fn snapshot(
+    mut platform: DefaultPlatform,
+    dir: PathBuf,
+    config: SnapshotConfig,
+    info: Option<RetroArchInfo>,
+) -> Result<()> {
+    let mut display = platform.display()?;
+    let battery = platform.battery()?;
+
+    let styles = config.stylesheet()?;
+    draw_backdrop(&mut display, &styles)?;
+
+    let res = load_resources(display.size().into(), styles.clone(), config.locale()?)?;
+    let mut snapshotter = Snapshotter::new(dir, display)?;
+    let rect = snapshotter.rect();
+
+    let menu = IngameMenu::new(rect, Default::default(), res.clone(), battery, info);
+    let mut layers = LayerStack::new(rect, Box::new(menu));
+    snapshotter.capture("menu", |display| draw(&mut layers, display, &styles))?;
+
+    let text = res.get::<Locale>().t("macro-saved");
+    layers.toast(Toast::new(text, None))?;
+    snapshotter.capture("menu-toast", |display| draw(&mut layers, display, &styles))?;
+    layers.close(LayerKind::Toast);
+
+    let keyboard = Keyboard::new(res.clone(), String::new(), false);
+    layers.push(LayerKind::Keyboard, Box::new(keyboard));
+    snapshotter.capture("menu-keyboard", |display| {
+        draw(&mut layers, display, &styles)
+    })?;
+    layers.close(LayerKind::Keyboard);
+
+    let guide = res.get::<GameInfo>().guide.clone();
+    if let Some(guide) = guide {
+        let reader = TextReader::new(rect, res.clone(), guide);
+        layers.push(LayerKind::Dialog, Box::new(reader));
+        snapshotter.capture("menu-guide", |display| draw(&mut layers, display, &styles))?;
+    }
+
+    Ok(())
+}
+
+fn draw(
+    layers: &mut LayerStack,
+    display: &mut <DefaultPlatform as Platform>::Display,
+    styles: &Stylesheet,
+) -> Result<()> {
+    layers.set_should_draw();
+    layers.draw(display, styles)?;
+    Ok(())
+}
@@ -0,0 +1,318 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::{Command, Value};
+use common::constants::SELECTION_MARGIN;
+use common::database::Database;
+use common::display::Display;
+use common::game_info::GameInfo;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::retroarch::RetroArchCommand;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, NullView, Row, SettingsList, Toggle, View};
+use log::{error, warn};
+use tokio::sync::mpsc::Sender;
+
+use crate::cheat_file::CheatFile;
+use crate::view::layers::Layer;
+
+/// Lists the RetroArch cheat files matching the running game, and lets the
+/// cheats in them be toggled individually.
+pub struct Cheats {
+    rect: Rect,
+    res: Resources,
+    title: Label<String>,
+    files: Vec<PathBuf>,
+    file: Option<CheatFile>,
+    /// Enabled state of each cheat in the open file itself, which RetroArch loads them with.
+    defaults: Vec<bool>,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl Cheats {
+    pub async fn new(rect: Rect, res: Resources, files: Vec<PathBuf>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let title = Label::new(
+            Point::new(x + 12, y + 8),
+            locale.t("ingame-menu-cheats"),
+            Alignment::Left,
+            None,
+        );
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y + 8 + ButtonIcon::diameter(&styles) as i32 + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            Vec::new(),
+            Vec::new(),
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-select"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        let mut this = Self {
+            rect,
+            res,
+            title,
+            files,
+            file: None,
+            defaults: Vec::new(),
+            list,
+            button_hints,
+            dirty: true,
+        };
+        if this.files.len() == 1 {
+            this.open_file(0).await;
+        } else {
+            this.show_files();
+        }
+        this
+    }
+
+    fn show_files(&mut self) {
+        self.file = None;
+        self.title.set_text(self.res.get::<Locale>().t("ingame-menu-cheats"));
+        let labels = self
+            .files
+            .iter()
+            .map(|path| {
+                let name = path.file_stem().unwrap_or_default().to_string_lossy();
+                match path.parent().and_then(|p| p.file_name()) {
+                    Some(dir) => format!("{} ({})", name, dir.to_string_lossy()),
+                    None => name.to_string(),
+                }
+            })
+            .collect::<Vec<_>>();
+        let values = labels
+            .iter()
+            .map(|_| Box::new(NullView) as Box<dyn View>)
+            .collect();
+        self.list.set_items(labels, values);
+        self.list.select(0);
+        self.dirty = true;
+    }
+
+    async fn open_file(&mut self, index: usize) {
+        let mut file = match CheatFile::load(self.files[index].clone()) {
+            Ok(file) => file,
+            Err(e) => {
+                error!("failed to load cheat file: {}", e);
+                return;
+            }
+        };
+
+        self.defaults = file.cheats.iter().map(|c| c.enabled).collect();
+        let game = self.res.get::<GameInfo>().path.clone();
+        match self.res.get::<Database>().select_cheats(&game, &file.path) {
+            Ok(toggled) => file.apply_toggled(&toggled),
+            Err(e) => warn!("failed to load toggled cheats: {}", e),
+        }
+        // The cheats saved for the game are applied again, in case RetroArch lost them
+        if let Err(e) = sync_cheats(&file, &self.defaults).await {
+            error!("failed to apply cheats: {}", e);
+        }
+
+        self.title.set_text(
+            file.path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+        );
+        self.list.set_items(
+            file.cheats.iter().map(|c| c.desc.clone()).collect(),
+            file.cheats
+                .iter()
+                .map(|c| {
+                    Box::new(Toggle::new(Point::zero(), c.enabled, Alignment::Right))
+                        as Box<dyn View>
+                })
+                .collect(),
+        );
+        self.list.select(0);
+        self.file = Some(file);
+        self.dirty = true;
+    }
+
+    async fn toggle_cheat(&mut self, index: usize, enabled: bool) -> Result<()> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        let Some(cheat) = file.cheats.get_mut(index) else {
+            return Ok(());
+        };
+        cheat.enabled = enabled;
+
+        let game = self.res.get::<GameInfo>().path.clone();
+        self.res
+            .get::<Database>()
+            .set_cheat_enabled(&game, &file.path, index, enabled)?;
+
+        sync_cheats(file, &self.defaults).await
+    }
+}
+
+/// Toggles the cheats in RetroArch whose state differs from the file, and records the new state
+/// in the game info. Until a cheat is toggled, RetroArch has it as it is in the cheat file.
+async fn sync_cheats(file: &CheatFile, defaults: &[bool]) -> Result<()> {
+    let Some(mut game_info) = GameInfo::load()? else {
+        return Ok(());
+    };
+    let applied = game_info
+        .cheats
+        .entry(file.path.clone())
+        .or_insert_with(|| defaults.to_vec());
+    let changes = file.changes(applied);
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    // RetroArch only exposes relative cheat navigation, so rewind to the
+    // first cheat before stepping to the ones being toggled.
+    for _ in 0..file.cheats.len() {
+        RetroArchCommand::CheatIndexMinus.send().await?;
+    }
+    let mut position = 0;
+    for index in changes {
+        for _ in position..index {
+            RetroArchCommand::CheatIndexPlus.send().await?;
+        }
+        RetroArchCommand::CheatToggle.send().await?;
+        position = index;
+    }
+
+    *applied = file.cheats.iter().map(|c| c.enabled).collect();
+    game_info.save()
+}
+
+#[async_trait(?Send)]
+impl View for Cheats {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.title.set_should_draw();
+            self.list.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.title.should_draw() && self.title.draw(display, styles)?;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.title.should_draw()
+            || self.list.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::A) if self.file.is_none() => {
+                self.open_file(self.list.selected()).await;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                if self.file.is_some() && self.files.len() > 1 {
+                    self.show_files();
+                } else {
+                    bubble.push_back(Command::CloseView);
+                }
+                Ok(true)
+            }
+            _ => {
+                let handled = self.list.handle_key_event(event, commands, bubble).await?;
+                let mut changed = Vec::new();
+                bubble.retain(|cmd| match cmd {
+                    Command::ValueChanged(i, Value::Bool(enabled)) => {
+                        changed.push((*i, *enabled));
+                        false
+                    }
+                    _ => true,
+                });
+                for (i, enabled) in changed {
+                    self.toggle_cheat(i, enabled).await?;
+                }
+                Ok(handled)
+            }
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.title, &self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.title, &mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+impl Layer for Cheats {}
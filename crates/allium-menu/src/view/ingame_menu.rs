@@ -8,11 +8,11 @@ use anyhow::Result;
 use async_trait::async_trait;
 use base32::encode;
 use common::battery::Battery;
+use common::clock::ClockSettings;
 use common::command::Command;
 use common::constants::{
     ALLIUM_MENU_STATE, ALLIUM_SCREENSHOTS_DIR, SAVE_STATE_IMAGE_WIDTH, SELECTION_MARGIN,
 };
-use common::display::Display;
 use common::game_info::GameInfo;
 use common::geom::{Alignment, Point, Rect};
 use common::locale::Locale;
@@ -21,16 +21,18 @@ use common::resources::Resources;
 use common::retroarch::RetroArchCommand;
 use common::stylesheet::Stylesheet;
 use common::view::{
-    BatteryIndicator, ButtonHint, ButtonIcon, Clock, Image, ImageMode, Label, NullView, Row,
-    SettingsList, View,
+    ButtonHint, ButtonIcon, Image, ImageMode, Label, NullView, PdfViewer, Row, SettingsList,
+    StatusBar, TextReader, Toggle, View,
 };
 use log::warn;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use tokio::sync::mpsc::Sender;
 
+use crate::cheat_file::CheatFile;
 use crate::retroarch_info::RetroArchInfo;
-use crate::view::text_reader::TextReader;
+use crate::view::cheats::Cheats;
+use crate::view::layers::{Layer, LayerKind};
 
 #[derive(Serialize, Deserialize, Default)]
 pub struct IngameMenuState {
@@ -44,15 +46,17 @@ where
     rect: Rect,
     res: Resources,
     name: Label<String>,
-    row: Row<Box<dyn View>>,
+    row: StatusBar,
     menu: SettingsList,
-    child: Option<TextReader>,
+    /// Dialog to open above the menu.
+    dialog: Option<Box<dyn Layer>>,
+    is_text_reader_open: bool,
+    cheat_files: Vec<PathBuf>,
     button_hints: Row<ButtonHint<String>>,
     entries: Vec<MenuEntry>,
     retroarch_info: Option<RetroArchInfo>,
     path: PathBuf,
     image: Image,
-    dirty: bool,
     _phantom_battery: PhantomData<B>,
 }
 
@@ -80,28 +84,18 @@ where
             None,
         );
 
-        let battery_indicator = BatteryIndicator::new(
-            res.clone(),
-            Point::new(0, 0),
-            battery,
-            styles.show_battery_level,
-        );
-
-        let mut children: Vec<Box<dyn View>> = vec![Box::new(battery_indicator)];
-
-        if styles.show_clock {
-            let clock = Clock::new(res.clone(), Point::new(0, 0), Alignment::Right);
-            children.push(Box::new(clock));
-        }
+        let row = StatusBar::new(res.clone(), Point::new(w as i32 - 12, y + 8), battery);
 
-        let row: Row<Box<dyn View>> = Row::new(
-            Point::new(w as i32 - 12, y + 8),
-            children,
-            Alignment::Right,
-            8,
+        let cheat_files = if retroarch_info.is_some() {
+            CheatFile::find(&game_info.path, &game_info.name)
+        } else {
+            Vec::new()
+        };
+        let entries = MenuEntry::entries(
+            &retroarch_info,
+            !cheat_files.is_empty(),
+            game_info.manual.is_some(),
         );
-
-        let entries = MenuEntry::entries(&retroarch_info);
         let mut menu = SettingsList::new(
             Rect::new(
                 x + 12,
@@ -116,6 +110,20 @@ where
                 .collect(),
             styles.ui_font.size + SELECTION_MARGIN,
         );
+        let clock_settings = ClockSettings::load().unwrap_or_default();
+        for (i, entry) in entries.iter().enumerate() {
+            let enabled = match entry {
+                MenuEntry::FastForward => game_info.fast_forward,
+                MenuEntry::Rewind => game_info.rewind,
+                MenuEntry::Reminder => clock_settings.reminder.is_some(),
+                MenuEntry::StatusOverlay => clock_settings.status_overlay,
+                _ => continue,
+            };
+            menu.set_right(i, Box::new(Toggle::new(Point::zero(), enabled, Alignment::Right)));
+        }
+        if let Some(i) = entries.iter().position(|e| *e == MenuEntry::SleepTimer) {
+            menu.set_right(i, Box::new(sleep_timer_label(&locale, &clock_settings)));
+        }
         if let Some(info) = retroarch_info.as_ref()
             && info.max_disk_slots > 1
             && !state.is_text_reader_open
@@ -170,12 +178,12 @@ where
             12,
         );
 
-        let mut child = None;
+        let mut dialog: Option<Box<dyn Layer>> = None;
         if state.is_text_reader_open
             && let Some(guide) = game_info.guide.as_ref()
         {
             menu.select(MenuEntry::Guide as usize);
-            child = Some(TextReader::new(rect, res.clone(), guide.clone()));
+            dialog = Some(Box::new(TextReader::new(rect, res.clone(), guide.clone())));
         }
 
         let path = game_info.path.clone();
@@ -189,14 +197,15 @@ where
             res,
             name,
             row,
+            is_text_reader_open: dialog.is_some(),
             menu,
-            child,
+            dialog,
+            cheat_files,
             button_hints,
             entries,
             retroarch_info,
             path,
             image,
-            dirty: false,
             _phantom_battery: PhantomData,
         }
     }
@@ -219,18 +228,6 @@ where
         Ok(Self::new(rect, Default::default(), res, battery, info))
     }
 
-    pub fn save(&self) -> Result<()> {
-        let file = File::create(ALLIUM_MENU_STATE.as_path())?;
-        let state = IngameMenuState {
-            is_text_reader_open: self.child.is_some(),
-        };
-        if let Some(child) = self.child.as_ref() {
-            child.save_cursor();
-        }
-        serde_json::to_writer(file, &state)?;
-        Ok(())
-    }
-
     async fn select_entry(&mut self, commands: Sender<Command>) -> Result<bool> {
         let selected = self.entries[self.menu.selected()];
         match selected {
@@ -264,9 +261,73 @@ where
             }
             MenuEntry::Guide => {
                 if let Some(guide) = self.res.get::<GameInfo>().guide.as_ref() {
-                    self.child = Some(TextReader::new(self.rect, self.res.clone(), guide.clone()));
+                    let text_reader = TextReader::new(self.rect, self.res.clone(), guide.clone());
+                    self.dialog = Some(Box::new(text_reader));
+                    self.is_text_reader_open = true;
                 }
             }
+            MenuEntry::Manual => {
+                if let Some(manual) = self.res.get::<GameInfo>().manual.as_ref() {
+                    let is_pdf = manual
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+                    let dialog: Box<dyn Layer> = if is_pdf {
+                        Box::new(PdfViewer::new(self.rect, self.res.clone(), manual.clone()))
+                    } else {
+                        Box::new(TextReader::new(self.rect, self.res.clone(), manual.clone()))
+                    };
+                    self.dialog = Some(dialog);
+                }
+            }
+            MenuEntry::Cheats => {
+                self.dialog = Some(Box::new(
+                    Cheats::new(self.rect, self.res.clone(), self.cheat_files.clone()).await,
+                ));
+            }
+            MenuEntry::FastForward | MenuEntry::Rewind => {
+                // alliumd applies the change when the menu exits
+                if let Some(mut game_info) = GameInfo::load()? {
+                    if selected == MenuEntry::FastForward {
+                        game_info.fast_forward = !game_info.fast_forward;
+                    } else {
+                        game_info.rewind = !game_info.rewind;
+                    }
+                    game_info.save()?;
+                }
+                commands.send(Command::Exit).await?;
+            }
+            MenuEntry::Reminder => {
+                // alliumd picks up the reminder from the clock settings
+                let mut settings = ClockSettings::load()?;
+                settings.toggle_reminder();
+                settings.save()?;
+                commands.send(Command::Exit).await?;
+            }
+            MenuEntry::StatusOverlay => {
+                // alliumd picks up the overlay from the clock settings
+                let mut settings = ClockSettings::load()?;
+                settings.status_overlay = !settings.status_overlay;
+                settings.save()?;
+                commands.send(Command::Exit).await?;
+            }
+            MenuEntry::SleepTimer => {
+                // alliumd picks up the sleep timer from the clock settings
+                let mut settings = ClockSettings::load()?;
+                settings.cycle_sleep_timer();
+                settings.save()?;
+                let locale = self.res.get::<Locale>();
+                self.menu.set_right(
+                    self.menu.selected(),
+                    Box::new(sleep_timer_label(&locale, &settings)),
+                );
+            }
+            MenuEntry::BoxArt => {
+                // The frame is captured once the menu is cleared from the screen
+                commands
+                    .send(Command::SaveBoxArt(self.path.clone()))
+                    .await?;
+                commands.send(Command::Exit).await?;
+            }
             MenuEntry::Settings => {
                 RetroArchCommand::Unpause.send().await?;
                 RetroArchCommand::MenuToggle.send().await?;
@@ -365,46 +426,29 @@ where
     ) -> Result<bool> {
         let mut drawn = false;
 
-        if self.dirty {
-            display.load(self.rect)?;
-            self.dirty = false;
-        }
-
-        if let Some(child) = self.child.as_mut() {
-            drawn |= child.should_draw() && child.draw(display, styles)?;
-        } else {
-            drawn |= self.name.should_draw() && self.name.draw(display, styles)?;
-            drawn |= self.row.should_draw() && self.row.draw(display, styles)?;
-            drawn |= self.menu.should_draw() && self.menu.draw(display, styles)?;
-            drawn |= self.image.should_draw() && self.image.draw(display, styles)?;
-            drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
-        }
+        drawn |= self.name.should_draw() && self.name.draw(display, styles)?;
+        drawn |= self.row.should_draw() && self.row.draw(display, styles)?;
+        drawn |= self.menu.should_draw() && self.menu.draw(display, styles)?;
+        drawn |= self.image.should_draw() && self.image.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
 
         Ok(drawn)
     }
 
     fn should_draw(&self) -> bool {
-        if let Some(child) = self.child.as_ref() {
-            self.dirty || child.should_draw()
-        } else {
-            self.dirty
-                || self.name.should_draw()
-                || self.row.should_draw()
-                || self.menu.should_draw()
-                || self.button_hints.should_draw()
-        }
+        self.name.should_draw()
+            || self.row.should_draw()
+            || self.menu.should_draw()
+            || self.image.should_draw()
+            || self.button_hints.should_draw()
     }
 
     fn set_should_draw(&mut self) {
-        self.dirty = true;
-        if let Some(child) = self.child.as_mut() {
-            child.set_should_draw();
-        } else {
-            self.name.set_should_draw();
-            self.row.set_should_draw();
-            self.menu.set_should_draw();
-            self.button_hints.set_should_draw();
-        }
+        self.name.set_should_draw();
+        self.row.set_should_draw();
+        self.menu.set_should_draw();
+        self.image.set_should_draw();
+        self.button_hints.set_should_draw();
     }
 
     async fn handle_key_event(
@@ -413,22 +457,6 @@ where
         commands: Sender<Command>,
         bubble: &mut VecDeque<Command>,
     ) -> Result<bool> {
-        if let Some(child) = self.child.as_mut()
-            && child
-                .handle_key_event(event, commands.clone(), bubble)
-                .await?
-        {
-            bubble.retain(|cmd| match cmd {
-                Command::CloseView => {
-                    self.child = None;
-                    self.set_should_draw();
-                    false
-                }
-                _ => true,
-            });
-            return Ok(true);
-        }
-
         let selected = self.menu.selected();
 
         // Handle disk slot selection
@@ -576,6 +604,44 @@ where
     }
 }
 
+impl<B> Layer for IngameMenu<B>
+where
+    B: Battery + 'static,
+{
+    fn open_layer(&mut self) -> Option<(LayerKind, Box<dyn Layer>)> {
+        self.dialog.take().map(|dialog| (LayerKind::Dialog, dialog))
+    }
+
+    fn receive(&mut self, command: Command) -> Result<()> {
+        if let Command::CloseView = command {
+            self.is_text_reader_open = false;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_MENU_STATE.as_path())?;
+        let state = IngameMenuState {
+            is_text_reader_open: self.is_text_reader_open,
+        };
+        serde_json::to_writer(file, &state)?;
+        Ok(())
+    }
+}
+
+/// Shows the minutes left on the sleep timer, or that it is off.
+fn sleep_timer_label(locale: &Locale, settings: &ClockSettings) -> Label<String> {
+    let text = match settings.sleep_timer_remaining() {
+        Some(minutes) => {
+            let mut map = HashMap::new();
+            map.insert("minutes".into(), minutes.into());
+            locale.ta("ingame-menu-sleep-timer-minutes", &map)
+        }
+        None => locale.t("ingame-menu-sleep-timer-off"),
+    };
+    Label::new(Point::zero(), text, Alignment::Right, None)
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum MenuEntry {
     Continue,
@@ -583,6 +649,14 @@ pub enum MenuEntry {
     Load,
     Reset,
     Guide,
+    Manual,
+    Cheats,
+    FastForward,
+    Rewind,
+    Reminder,
+    SleepTimer,
+    StatusOverlay,
+    BoxArt,
     Settings,
     Quit,
 }
@@ -595,13 +669,21 @@ impl MenuEntry {
             MenuEntry::Load => locale.t("ingame-menu-load"),
             MenuEntry::Reset => locale.t("ingame-menu-reset"),
             MenuEntry::Guide => locale.t("ingame-menu-guide"),
+            MenuEntry::Manual => locale.t("ingame-menu-manual"),
+            MenuEntry::Cheats => locale.t("ingame-menu-cheats"),
+            MenuEntry::FastForward => locale.t("ingame-menu-fast-forward"),
+            MenuEntry::Rewind => locale.t("ingame-menu-rewind"),
+            MenuEntry::Reminder => locale.t("ingame-menu-reminder"),
+            MenuEntry::SleepTimer => locale.t("ingame-menu-sleep-timer"),
+            MenuEntry::StatusOverlay => locale.t("ingame-menu-status-overlay"),
+            MenuEntry::BoxArt => locale.t("ingame-menu-box-art"),
             MenuEntry::Settings => locale.t("ingame-menu-settings"),
             MenuEntry::Quit => locale.t("ingame-menu-quit"),
         }
     }
 
-    fn entries(info: &Option<RetroArchInfo>) -> Vec<Self> {
-        match info {
+    fn entries(info: &Option<RetroArchInfo>, has_cheats: bool, has_manual: bool) -> Vec<Self> {
+        let mut entries = match info {
             Some(RetroArchInfo {
                 state_slot: Some(_),
                 ..
@@ -610,6 +692,11 @@ impl MenuEntry {
                 MenuEntry::Save,
                 MenuEntry::Load,
                 MenuEntry::Guide,
+                MenuEntry::FastForward,
+                MenuEntry::Rewind,
+                MenuEntry::Reminder,
+                MenuEntry::SleepTimer,
+                MenuEntry::StatusOverlay,
                 MenuEntry::Settings,
                 MenuEntry::Reset,
                 MenuEntry::Quit,
@@ -618,10 +705,31 @@ impl MenuEntry {
                 MenuEntry::Continue,
                 MenuEntry::Reset,
                 MenuEntry::Guide,
+                MenuEntry::FastForward,
+                MenuEntry::Rewind,
+                MenuEntry::Reminder,
+                MenuEntry::SleepTimer,
+                MenuEntry::StatusOverlay,
                 MenuEntry::Settings,
                 MenuEntry::Quit,
             ],
-            None => vec![MenuEntry::Continue, MenuEntry::Guide, MenuEntry::Quit],
+            None => vec![
+                MenuEntry::Continue,
+                MenuEntry::Guide,
+                MenuEntry::Reminder,
+                MenuEntry::SleepTimer,
+                MenuEntry::Quit,
+            ],
+        };
+        if has_cheats && let Some(i) = entries.iter().position(|e| *e == MenuEntry::Guide) {
+            entries.insert(i + 1, MenuEntry::Cheats);
+        }
+        if has_manual && let Some(i) = entries.iter().position(|e| *e == MenuEntry::Guide) {
+            entries.insert(i + 1, MenuEntry::Manual);
+        }
+        if let Some(i) = entries.iter().position(|e| *e == MenuEntry::Quit) {
+            entries.insert(i, MenuEntry::BoxArt);
         }
+        entries
     }
 }
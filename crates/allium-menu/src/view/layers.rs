@@ -0,0 +1,320 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use anyhow::Result;
+use common::command::Command;
+use common::display::Display;
+use common::geom::Rect;
+use common::platform::{DefaultPlatform, KeyEvent, Platform};
+use common::stylesheet::Stylesheet;
+use common::view::{Keyboard, PdfViewer, TextReader, Toast, Toasts, View};
+use log::trace;
+use tokio::sync::mpsc::Sender;
+
+/// Where a layer sits in the stack. Layers are drawn from the bottom up, and key events go to the
+/// topmost layer that takes input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LayerKind {
+    /// The in-game menu itself.
+    Base,
+    /// Full-screen views opened from the menu, such as the guide or the cheat list.
+    Dialog,
+    /// Messages shown over everything but the keyboard. Toasts don't take input.
+    Toast,
+    /// The on-screen keyboard.
+    Keyboard,
+}
+
+impl LayerKind {
+    /// Whether the layer covers the whole screen, hiding the layers below it.
+    fn is_opaque(self) -> bool {
+        matches!(self, LayerKind::Base | LayerKind::Dialog)
+    }
+
+    fn takes_input(self) -> bool {
+        self != LayerKind::Toast
+    }
+}
+
+/// A view that can be shown in the layer stack.
+pub trait Layer: View {
+    /// Returns a layer to open above this one. Checked after every key event the layer handles.
+    fn open_layer(&mut self) -> Option<(LayerKind, Box<dyn Layer>)> {
+        None
+    }
+
+    /// Handles a command bubbled up by the layer above, such as the text entered on the
+    /// keyboard. `Command::CloseView` is passed on after that layer is closed.
+    fn receive(&mut self, _command: Command) -> Result<()> {
+        Ok(())
+    }
+
+    /// Whether the layer has closed by itself, such as a toast that expired.
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    /// Whether the area the layer covers changed since this was last called, in which case the
+    /// layers below it are drawn again.
+    fn has_moved(&mut self) -> bool {
+        false
+    }
+
+    /// Saves the layer's state before the menu exits.
+    fn save(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Layer for Keyboard {}
+
+impl Layer for TextReader {
+    fn open_layer(&mut self) -> Option<(LayerKind, Box<dyn Layer>)> {
+        let keyboard = self.take_keyboard()?;
+        Some((LayerKind::Keyboard, Box::new(keyboard)))
+    }
+
+    fn receive(&mut self, command: Command) -> Result<()> {
+        if let Command::ValueChanged(_, value) = command {
+            self.search(value.as_string().unwrap());
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        self.save_cursor();
+        Ok(())
+    }
+}
+
+impl Layer for PdfViewer {
+    fn save(&self) -> Result<()> {
+        self.save_page();
+        Ok(())
+    }
+}
+
+impl Layer for Toasts {
+    fn receive(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Notify(toast) => self.push(toast),
+            Command::DismissToast => self.dismiss(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn is_closed(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn has_moved(&mut self) -> bool {
+        self.update_queue()
+    }
+}
+
+struct Entry {
+    kind: LayerKind,
+    layer: Box<dyn Layer>,
+    /// Whether the layer has to be drawn again from scratch. Otherwise, only the parts of it that
+    /// changed are drawn.
+    dirty: bool,
+}
+
+/// Stack of the views shown by the menu, so that dialogs, toasts and the keyboard can be opened
+/// over each other without redrawing what's below them.
+pub struct LayerStack {
+    rect: Rect,
+    entries: Vec<Entry>,
+}
+
+impl LayerStack {
+    pub fn new(rect: Rect, base: Box<dyn Layer>) -> Self {
+        let mut this = Self {
+            rect,
+            entries: Vec::new(),
+        };
+        this.push(LayerKind::Base, base);
+        // The base layer may reopen a dialog that was open when the menu last exited
+        if let Some((kind, layer)) = this.entries[0].layer.open_layer() {
+            this.push(kind, layer);
+        }
+        this
+    }
+
+    /// Opens a layer above all the layers of the same or a lower kind.
+    pub fn push(&mut self, kind: LayerKind, layer: Box<dyn Layer>) {
+        trace!("opening {:?} layer", kind);
+        let i = self.entries.partition_point(|e| e.kind <= kind);
+        self.entries.insert(
+            i,
+            Entry {
+                kind,
+                layer,
+                dirty: true,
+            },
+        );
+        self.set_dirty_above(i);
+    }
+
+    /// Closes the topmost layer of the given kind. Returns false if there was none.
+    pub fn close(&mut self, kind: LayerKind) -> bool {
+        match self.entries.iter().rposition(|e| e.kind == kind) {
+            Some(i) if kind != LayerKind::Base => {
+                self.remove(i);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Queues a toast, stacking it with the ones already shown.
+    pub fn toast(&mut self, toast: Toast) -> Result<()> {
+        match self.entries.iter_mut().find(|e| e.kind == LayerKind::Toast) {
+            Some(entry) => entry.layer.receive(Command::Notify(toast))?,
+            None => {
+                let mut toasts = Toasts::new();
+                toasts.push(toast);
+                self.push(LayerKind::Toast, Box::new(toasts));
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the toasts that have no duration.
+    pub fn dismiss_toasts(&mut self) -> Result<()> {
+        for entry in &mut self.entries {
+            if entry.kind == LayerKind::Toast {
+                entry.layer.receive(Command::DismissToast)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Closes the layers that closed by themselves.
+    pub fn update(&mut self, dt: Duration) {
+        let mut i = 0;
+        while i < self.entries.len() {
+            if i > 0 && self.entries[i].layer.is_closed() {
+                self.remove(i);
+            } else {
+                self.entries[i].layer.update(dt);
+                if self.entries[i].layer.has_moved() {
+                    let from = self.visible_from().min(i);
+                    self.set_dirty_above(from);
+                }
+                i += 1;
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        for entry in &self.entries {
+            entry.layer.save()?;
+        }
+        Ok(())
+    }
+
+    pub fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let from = self.visible_from();
+        let mut drawn = false;
+        let mut covered = false;
+        for entry in &mut self.entries[from..] {
+            if entry.dirty {
+                if entry.kind.is_opaque() {
+                    display.load(self.rect)?;
+                }
+                entry.layer.set_should_draw();
+                entry.dirty = false;
+            } else if covered {
+                // Part of the layer may have been drawn over by a layer below it
+                entry.layer.set_should_draw();
+            }
+            if entry.layer.should_draw() && entry.layer.draw(display, styles)? {
+                drawn = true;
+                covered = true;
+            }
+        }
+        Ok(drawn)
+    }
+
+    pub fn should_draw(&self) -> bool {
+        self.entries[self.visible_from()..]
+            .iter()
+            .any(|e| e.dirty || e.layer.should_draw())
+    }
+
+    /// Draws every visible layer again from scratch.
+    pub fn set_should_draw(&mut self) {
+        let from = self.visible_from();
+        self.set_dirty_above(from);
+    }
+
+    /// Passes the key event to the topmost layer that takes input. Commands it bubbles up are
+    /// passed to the layer below it.
+    pub async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+    ) -> Result<bool> {
+        let Some(i) = self.entries.iter().rposition(|e| e.kind.takes_input()) else {
+            return Ok(false);
+        };
+
+        let mut bubble = VecDeque::new();
+        let consumed = self.entries[i]
+            .layer
+            .handle_key_event(event, commands, &mut bubble)
+            .await?;
+
+        let closed = i > 0 && bubble.iter().any(|c| matches!(c, Command::CloseView));
+        if closed {
+            self.remove(i);
+        } else if let Some((kind, layer)) = self.entries[i].layer.open_layer() {
+            self.push(kind, layer);
+        }
+
+        if let Some(below) = self.entries[..i].iter().rposition(|e| e.kind.takes_input()) {
+            // CloseView is passed on last, after the values it confirms
+            bubble.retain(|c| !matches!(c, Command::CloseView));
+            if closed {
+                bubble.push_back(Command::CloseView);
+            }
+            for command in bubble {
+                self.entries[below].layer.receive(command)?;
+            }
+            if let Some((kind, layer)) = self.entries[below].layer.open_layer() {
+                self.push(kind, layer);
+            }
+        }
+
+        Ok(consumed)
+    }
+
+    /// Index of the topmost opaque layer. Layers below it are hidden.
+    fn visible_from(&self) -> usize {
+        self.entries
+            .iter()
+            .rposition(|e| e.kind.is_opaque())
+            .unwrap_or_default()
+    }
+
+    fn set_dirty_above(&mut self, i: usize) {
+        for entry in &mut self.entries[i..] {
+            entry.dirty = true;
+        }
+    }
+
+    fn remove(&mut self, i: usize) {
+        let entry = self.entries.remove(i);
+        trace!("closing {:?} layer", entry.kind);
+        // Whatever the layer covered has to be drawn again, starting from the opaque layer
+        // below it
+        let from = self.visible_from().min(i);
+        self.set_dirty_above(from);
+    }
+}
@@ -1,2 +1,3 @@
+mod cheats;
 pub mod ingame_menu;
-mod text_reader;
+pub mod layers;
@@ -1,40 +1,136 @@
+use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::Path;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::{Instant, SystemTime};
 
-use anyhow::Result;
-use chrono::{DateTime, Duration, Utc};
+use anyhow::{Result, bail};
+use chrono::{DateTime, Duration, Local, Timelike, Utc};
+use common::audio::{self, AudioSettings};
+use common::background::DeviceActivity;
 use common::battery::Battery;
+use common::bluetooth::{self, BluetoothSettings};
+use common::clock::{ClockEvent, ClockSettings, SleepTimerAction};
 use common::constants::{
-    ALLIUM_GAME_INFO, ALLIUM_MENU, ALLIUM_SD_ROOT, ALLIUM_VERSION, ALLIUMD_STATE,
-    BATTERY_SHUTDOWN_THRESHOLD, BATTERY_UPDATE_INTERVAL, IDLE_TIMEOUT, LONG_PRESS_DURATION,
+    ALLIUM_BOOT_VAR, ALLIUM_GAME_INFO, ALLIUM_LAUNCH_STDERR, ALLIUM_MENU, ALLIUM_REMOTE_LAUNCH,
+    ALLIUM_RESUME_SCREENSHOT, ALLIUM_SD_ROOT, ALLIUM_VERSION, ALLIUMD_STATE, AUTO_SAVE_STATE_DELAY,
+    AUTO_SAVE_STATE_SLOT, BATTERY_HISTORY_RETENTION, BATTERY_SHUTDOWN_GRACE,
+    BATTERY_SHUTDOWN_THRESHOLD, BATTERY_UPDATE_INTERVAL, CLOCK_EVENT_POLL_INTERVAL,
+    CLOCK_OVERLAY_AUTO_HIDE, CLOCK_OVERLAY_INTERVAL, FAST_FORWARD_INDICATOR_INTERVAL,
+    GAME_INFO_POLL_INTERVAL, IDLE_TIMEOUT, LAUNCH_FAILURE_WINDOW, LONG_PRESS_DURATION, MAX_VOLUME,
+    PARENTAL_CHECK_INTERVAL, PLAY_TIME_LIMIT_GRACE, PLAY_TIME_WARNING, RESTART_ALLIUMD_EXIT_CODE,
 };
 use common::display::settings::DisplaySettings;
+use common::hotkeys::REWIND_KEY;
 use common::locale::{Locale, LocaleSettings};
+use common::lock::LockSettings;
+use common::macros::{MacroRecorder, MacroSettings, MacroStep};
+use common::mdns;
+use common::ntp;
+use common::parental::{self, ParentalSettings};
 use common::power::{PowerButtonAction, PowerSettings};
 use common::retroarch::RetroArchCommand;
-use common::wifi::WiFiSettings;
+use common::save_backup::{self, SaveBackupSettings};
+use common::save_sync::{self, SaveSyncSettings};
+use common::status::DeviceStatus;
+use common::storage::{self, Volume};
+use common::webhook::{Webhook, WebhookEvent, WebhookSettings};
+use common::wifi::{self, WiFiSettings};
 use enum_map::EnumMap;
 use log::{debug, error, info, trace, warn};
 use serde::{Deserialize, Serialize};
 use tokio::process::{Child, Command};
+use tokio::sync::mpsc::{self, Receiver};
+use tokio::task::JoinHandle;
 
-use common::database::Database;
+use common::database::{BatterySample, Database, PlaySession};
+use common::developer::{DeveloperSettings, RemoteShell};
 use common::game_info::GameInfo;
+use common::launch_diagnostic::LaunchDiagnostic;
+use common::platform::cpu::CpuProfile;
+use common::platform::layout::ButtonLayout;
+use common::platform::led::{Led, LedSettings, LedState};
+use common::platform::rumble::{self, Rumble};
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 
+use crate::remote::{self, RemoteCommand};
+
 #[cfg(unix)]
 use {
     nix::sys::signal::Signal, nix::sys::signal::kill, nix::unistd::Pid,
-    tokio::signal::unix::SignalKind,
+    std::os::unix::process::CommandExt, tokio::signal::unix::SignalKind,
 };
 
+/// Number of times to rumble when an alarm or timer goes off.
+const RING_PULSES: usize = 3;
+const RING_PULSE_DURATION: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Fast-forward and rewind state of the running game.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Playback {
+    fast_forward: bool,
+    rewind: bool,
+}
+
+/// A change to send to RetroArch to go from one `Playback` to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlaybackChange {
+    ToggleFastForward,
+    HoldRewind,
+    ReleaseRewind,
+}
+
+impl Playback {
+    /// Returns the changes that take RetroArch from this state to `target`. RetroArch only has a
+    /// command to toggle fast-forward, and only rewinds while the rewind key is held, so
+    /// something is only sent when the state actually changes.
+    fn changes_to(self, target: Playback) -> Vec<PlaybackChange> {
+        let mut changes = Vec::new();
+        if self.fast_forward != target.fast_forward {
+            changes.push(PlaybackChange::ToggleFastForward);
+        }
+        match (self.rewind, target.rewind) {
+            (false, true) => changes.push(PlaybackChange::HoldRewind),
+            (true, false) => changes.push(PlaybackChange::ReleaseRewind),
+            _ => {}
+        }
+        changes
+    }
+}
+
+/// Presses or releases Menu and `REWIND_KEY` on the virtual keypad, which RetroArch rewinds on.
+fn rewind_keys(pressed: bool) -> Vec<MacroStep> {
+    let keys = if pressed {
+        [Key::Menu, REWIND_KEY]
+    } else {
+        [REWIND_KEY, Key::Menu]
+    };
+    keys.into_iter()
+        .map(|key| MacroStep {
+            key,
+            pressed,
+            delay_ms: 0,
+        })
+        .collect()
+}
+
+/// Parental controls event that is due while a game is running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlayTimeEvent {
+    Warning,
+    Limit,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlliumDState {
     #[serde(default = "Utc::now")]
     time: DateTime<Utc>,
+    /// Volume of the speaker.
     volume: i32,
+    /// Volume while headphones are plugged in, remembered separately from the speaker's.
+    #[serde(default = "AlliumDState::headphones_volume")]
+    headphones_volume: i32,
     brightness: u8,
 }
 
@@ -43,6 +139,8 @@ pub struct AlliumD<P: Platform> {
     platform: P,
     main: Child,
     menu: Option<Child>,
+    /// Lock screen shown after waking up. The main process stays stopped until it's unlocked.
+    lock: Option<Child>,
     keys: EnumMap<Key, bool>,
     is_menu_pressed_alone: bool,
     pressed_menu: Instant,
@@ -50,6 +148,68 @@ pub struct AlliumD<P: Platform> {
     state: AlliumDState,
     locale: Locale,
     power_settings: PowerSettings,
+    audio_settings: AudioSettings,
+    /// Modification time and size of the audio settings file when it was last loaded.
+    audio_settings_stamp: Option<(SystemTime, u64)>,
+    remote: Receiver<RemoteCommand>,
+    /// Connection to the database, kept open for play time and battery history.
+    database: Database,
+    /// Fast-forward and rewind state that was last sent to RetroArch.
+    playback: Playback,
+    /// Last activity published for background jobs.
+    activity: Option<DeviceActivity>,
+    /// Last status published for the status bar.
+    status: Option<DeviceStatus>,
+    macro_recorder: Option<MacroRecorder>,
+    /// Whether to play the macro once Menu is released.
+    macro_queued: bool,
+    /// Macro settings as of the last check, as they're needed on every hotkey press.
+    macro_settings: MacroSettings,
+    /// Modification time and size of the macro settings file when it was last loaded.
+    macro_settings_stamp: Option<(SystemTime, u64)>,
+    /// Clock settings as of the last check, as they're needed on every loop iteration.
+    clock_settings: ClockSettings,
+    /// Modification time and size of the clock settings file when it was last loaded.
+    clock_settings_stamp: Option<(SystemTime, u64)>,
+    /// When the clock settings were last loaded.
+    clock_settings_loaded: Instant,
+    webhook: Webhook,
+    /// Webhook settings as of the last check, as they're needed on every loop iteration.
+    webhook_settings: WebhookSettings,
+    /// Modification time and size of the webhook settings file when it was last loaded.
+    webhook_settings_stamp: Option<(SystemTime, u64)>,
+    /// Game being played, as of the last check for the webhook.
+    playing: Option<GameInfo>,
+    /// Whether the battery saver is on, either automatically or with the hotkey.
+    battery_saver: bool,
+    /// Whether the battery was at or below the battery saver threshold at the last update. The
+    /// battery saver is only turned on or off automatically when this changes, so that it can
+    /// be overridden with the hotkey.
+    battery_low: bool,
+    /// Whether night mode was on when the display settings were last applied.
+    night_mode: bool,
+    /// Display settings as of the last check, for scheduled night mode.
+    display_settings: DisplaySettings,
+    /// Modification time and size of the display settings file when it was last loaded.
+    display_settings_stamp: Option<(SystemTime, u64)>,
+    /// Hour of the day when the display settings were last loaded.
+    display_settings_hour: u32,
+    /// Whether headphones are plugged in, so that their volume is used.
+    headphones: bool,
+    led: Led,
+    /// State of the battery as of the last update, shown on the LED while awake.
+    battery_led: LedState,
+    /// Developer settings as of the last check, starting with every remote shell off so that
+    /// the enabled ones are started on boot.
+    developer_settings: DeveloperSettings,
+    /// Modification time and size of the developer settings file when it was last loaded.
+    developer_settings_stamp: Option<(SystemTime, u64)>,
+    remote_shells: EnumMap<RemoteShell, Option<Child>>,
+    /// Storage device that failed to mount, which isn't retried until it's removed.
+    storage_failed: Option<PathBuf>,
+    /// Mount or unmount of a storage device in progress, which returns the device if it failed
+    /// to mount.
+    storage_task: Option<JoinHandle<Option<PathBuf>>>,
 }
 
 impl AlliumDState {
@@ -57,33 +217,29 @@ impl AlliumDState {
         Self {
             time: Utc::now(),
             volume: 0,
+            headphones_volume: Self::headphones_volume(),
             brightness: 50,
         }
     }
 
+    fn headphones_volume() -> i32 {
+        MAX_VOLUME / 2
+    }
+
     pub fn load() -> Result<AlliumDState> {
         if ALLIUMD_STATE.exists() {
             debug!("found state, loading from file");
             if let Ok(json) = fs::read_to_string(ALLIUMD_STATE.as_path())
                 && let Ok(this) = serde_json::from_str::<AlliumDState>(&json)
             {
+                // Without a working RTC, the clock at least shouldn't go backwards. NTP sets
+                // the correct time once Wi-Fi connects.
                 if Utc::now() < this.time {
                     info!(
                         "RTC is not working, advancing time to {}",
                         this.time.format("%F %T")
                     );
-                    let mut date = std::process::Command::new("date")
-                        .arg("--utc")
-                        .arg("--set")
-                        .arg(this.time.format("%F %T").to_string())
-                        .spawn()?;
-                    date.wait()?;
-                    let mut hwclock = std::process::Command::new("/sbin/hwclock")
-                        .arg("--systohc")
-                        .arg("--utc")
-                        .arg(this.time.format("%F %T").to_string())
-                        .spawn()?;
-                    hwclock.wait()?;
+                    ntp::set_clock(this.time)?;
                 }
                 return Ok(this);
             }
@@ -100,42 +256,248 @@ impl AlliumDState {
     }
 }
 
-async fn spawn_main() -> Result<Child> {
+/// Resumes the last game, or else starts the launcher. On boot, the launcher is told to apply the
+/// startup behavior.
+async fn spawn_main(boot: bool) -> Result<Child> {
     #[cfg(feature = "miyoo")]
     return Ok(match GameInfo::load()? {
         Some(mut game_info) => {
             debug!("found game info, resuming game");
-            game_info.start_time = Utc::now();
+            show_resume_screenshot().await;
+            game_info.restart_play_time();
+            game_info.fast_forward = false;
+            game_info.rewind = false;
             game_info.save()?;
+            if let Err(e) = game_info.cpu_profile.apply() {
+                error!("failed to set cpu profile: {}", e);
+            }
             game_info.command().into()
         }
         None => {
             debug!("no game info found, launching launcher");
             use common::constants::ALLIUM_LAUNCHER;
-            Command::new(ALLIUM_LAUNCHER.as_path())
+            let mut command = Command::new(ALLIUM_LAUNCHER.as_path());
+            if boot {
+                command.env(ALLIUM_BOOT_VAR, "1");
+            }
+            command
         }
     }
     .spawn()?);
 
     #[cfg(not(feature = "miyoo"))]
-    return Ok(Command::new("/bin/sh")
-        .arg("-c")
-        .arg("make simulator-launcher")
-        .spawn()?);
+    {
+        let mut command = Command::new("/bin/sh");
+        command.arg("-c").arg("make simulator-launcher");
+        if boot {
+            command.env(ALLIUM_BOOT_VAR, "1");
+        }
+        Ok(command.spawn()?)
+    }
+}
+
+/// Saves a diagnostic for the launcher to show if the game exited with an error right after it
+/// started, as it most likely never ran.
+fn check_launch_failure(game_info: &GameInfo, status: ExitStatus) {
+    if status.success()
+        || game_info
+            .play_time()
+            .to_std()
+            .is_ok_and(|play_time| play_time > LAUNCH_FAILURE_WINDOW)
+    {
+        return;
+    }
+    let stderr = fs::read_to_string(ALLIUM_LAUNCH_STDERR.as_path()).unwrap_or_default();
+    let diagnostic = LaunchDiagnostic::from_stderr(
+        game_info.name.clone(),
+        Some(game_info.core.clone()),
+        &stderr,
+    );
+    warn!(
+        "game failed to launch ({}): {:?}\n{}",
+        status, diagnostic.error, diagnostic.output
+    );
+    if let Err(e) = diagnostic.save() {
+        error!("failed to save launch diagnostic: {}", e);
+    }
+}
+
+/// Backs up the game's saves and syncs them with the server in the background after a game
+/// exits, if enabled. The backup is made first, so it's never of saves downloaded by the sync.
+fn handle_saves_on_exit(game: Option<(PathBuf, String)>) {
+    let backup_settings = SaveBackupSettings::load().unwrap_or_default();
+    let sync_settings = SaveSyncSettings::load().unwrap_or_default();
+    let backup = backup_settings.enabled && game.is_some();
+    let sync = sync_settings.is_configured() && sync_settings.sync_on_exit;
+    if !backup && !sync {
+        return;
+    }
+    tokio::spawn(async move {
+        if backup
+            && let Some((game, core)) = game
+            && let Err(e) = save_backup::backup(&game, Some(&core), &backup_settings).await
+        {
+            error!("failed to back up saves: {}", e);
+        }
+        if sync && let Err(e) = save_sync::sync(&sync_settings, |_, _| {}).await {
+            error!("failed to sync saves: {}", e);
+        }
+    });
+}
+
+/// Shows the screenshot saved when the game was suspended on shutdown, so the game
+/// appears instantly while it relaunches.
+#[cfg(feature = "miyoo")]
+async fn show_resume_screenshot() {
+    if !ALLIUM_RESUME_SCREENSHOT.exists() {
+        return;
+    }
+    match Command::new("show")
+        .arg(ALLIUM_RESUME_SCREENSHOT.as_path())
+        .spawn()
+    {
+        Ok(mut child) => {
+            child.wait().await.ok();
+        }
+        Err(e) => warn!("failed to show resume screenshot: {}", e),
+    }
+    // The screenshot is only valid for the next boot
+    fs::remove_file(ALLIUM_RESUME_SCREENSHOT.as_path()).ok();
+}
+
+/// Returns the alarm, timer or reminder event that is due after sleeping for the
+/// returned duration, if any. The clock settings may be changed by the clock app at
+/// any time, so they are reloaded at least every `CLOCK_EVENT_POLL_INTERVAL`.
+fn next_clock_event(settings: &ClockSettings) -> (Option<ClockEvent>, std::time::Duration) {
+    let now = Local::now();
+    match settings.next_event(now) {
+        Some((time, event)) => {
+            let until = (time - now).to_std().unwrap_or_default();
+            if until <= CLOCK_EVENT_POLL_INTERVAL {
+                (Some(event), until)
+            } else {
+                (None, CLOCK_EVENT_POLL_INTERVAL)
+            }
+        }
+        None => (None, CLOCK_EVENT_POLL_INTERVAL),
+    }
+}
+
+/// Returns the play time left today under parental controls, including the part of the running
+/// game's play time since midnight, or `None` if play time isn't limited today.
+fn remaining_play_time(database: &Database) -> Result<Option<Duration>> {
+    let settings = ParentalSettings::load()?;
+    if !settings.enabled {
+        return Ok(None);
+    }
+    let Some(remaining) = settings.remaining_today(database)? else {
+        return Ok(None);
+    };
+    let now = Local::now();
+    let playing = GameInfo::load()?
+        .and_then(|game_info| {
+            parental::play_time_by_day(now, game_info.play_time())
+                .into_iter()
+                .find(|(date, _)| *date == now.date_naive())
+        })
+        .map(|(_, play_time)| play_time);
+    Ok(Some(remaining - playing.unwrap_or_default()))
+}
+
+/// Returns the parental controls event that is due after sleeping for the returned duration,
+/// if any. The deadline is rechecked at least every `PARENTAL_CHECK_INTERVAL`.
+fn next_play_time_event(
+    deadline: Option<Instant>,
+    warned: bool,
+) -> (Option<PlayTimeEvent>, std::time::Duration) {
+    let Some(deadline) = deadline else {
+        return (None, std::time::Duration::MAX);
+    };
+    let until = deadline.saturating_duration_since(Instant::now());
+    let (event, until) = if warned {
+        (PlayTimeEvent::Limit, until)
+    } else {
+        let until = until.saturating_sub(PLAY_TIME_WARNING);
+        (PlayTimeEvent::Warning, until)
+    };
+    if until <= PARENTAL_CHECK_INTERVAL {
+        (Some(event), until)
+    } else {
+        (None, PARENTAL_CHECK_INTERVAL)
+    }
+}
+
+/// Returns the low battery warning threshold that the battery level has dropped to since the
+/// last warning, if any. Only the lowest threshold is returned when several are crossed at once.
+fn low_battery_warning(thresholds: &[i32], percentage: i32, warned: Option<i32>) -> Option<i32> {
+    thresholds
+        .iter()
+        .copied()
+        .filter(|&threshold| percentage <= threshold && warned.is_none_or(|w| threshold < w))
+        .min()
+}
+
+/// Returns how long to wait before showing the clock overlay again. With auto-hide, the
+/// clock is only shown for `CLOCK_OVERLAY_AUTO_HIDE` at the start of each minute.
+fn clock_overlay_sleep(settings: &ClockSettings, last_overlay: Instant) -> std::time::Duration {
+    let second = Local::now().second() as u64;
+    if !settings.overlay_auto_hide || second < CLOCK_OVERLAY_AUTO_HIDE.as_secs() {
+        CLOCK_OVERLAY_INTERVAL.saturating_sub(last_overlay.elapsed())
+    } else {
+        std::time::Duration::from_secs(60 - second)
+    }
+}
+
+/// Shows the lock screen if enabled, returning its process. It exits once unlocked.
+fn spawn_lock_screen() -> Result<Option<Child>> {
+    if !LockSettings::load().unwrap_or_default().is_active() {
+        return Ok(None);
+    }
+    info!("showing lock screen");
+    Ok(Some(Command::new("lock-screen").spawn()?))
+}
+
+/// Waits for the lock screen to be unlocked, or forever if it isn't shown.
+async fn wait_unlocked(lock: &mut Option<Child>) -> Result<ExitStatus> {
+    match lock {
+        Some(child) => Ok(child.wait().await?),
+        None => std::future::pending().await,
+    }
 }
 
 impl AlliumD<DefaultPlatform> {
     pub async fn new() -> Result<AlliumD<DefaultPlatform>> {
-        let platform = DefaultPlatform::new()?;
+        let mut platform = DefaultPlatform::new()?;
+        // Hotkeys and macros are bound to the physical buttons
+        platform.set_button_layout(ButtonLayout::Nintendo);
         let state = AlliumDState::load()?;
-        let main = spawn_main().await?;
-        let locale = Locale::new(&LocaleSettings::load()?.lang);
+        // Nothing else runs yet, so the lock screen is waited on
+        if let Some(mut lock) = spawn_lock_screen()? {
+            lock.wait().await?;
+        }
+        let main = spawn_main(true).await?;
+        let locale = Locale::from_settings(&LocaleSettings::load()?);
         let power_settings = PowerSettings::load()?;
 
+        let (tx, remote) = mpsc::channel(8);
+        if DefaultPlatform::has_wifi() {
+            tokio::spawn(async move {
+                if let Err(e) = remote::serve(tx).await {
+                    error!("web remote stopped: {}", e);
+                }
+            });
+            tokio::spawn(async move {
+                if let Err(e) = mdns::advertise().await {
+                    error!("mDNS advertisement stopped: {}", e);
+                }
+            });
+        }
+
         Ok(AlliumD {
             platform,
             main,
             menu: None,
+            lock: None,
             keys: EnumMap::default(),
             is_menu_pressed_alone: false,
             pressed_menu: Instant::now(),
@@ -143,27 +505,73 @@ impl AlliumD<DefaultPlatform> {
             state,
             locale,
             power_settings,
+            audio_settings: AudioSettings::load()?,
+            audio_settings_stamp: AudioSettings::stamp(),
+            remote,
+            database: Database::new()?,
+            playback: Playback::default(),
+            activity: None,
+            status: None,
+            macro_recorder: None,
+            macro_queued: false,
+            macro_settings: MacroSettings::load()?,
+            macro_settings_stamp: MacroSettings::stamp(),
+            clock_settings: ClockSettings::load()?,
+            clock_settings_stamp: ClockSettings::stamp(),
+            clock_settings_loaded: Instant::now(),
+            webhook: Webhook::new(),
+            webhook_settings: WebhookSettings::load()?,
+            webhook_settings_stamp: WebhookSettings::stamp(),
+            playing: None,
+            battery_saver: false,
+            battery_low: false,
+            night_mode: false,
+            display_settings: DisplaySettings::load()?,
+            display_settings_stamp: DisplaySettings::stamp(),
+            display_settings_hour: Local::now().hour(),
+            headphones: DefaultPlatform::headphones_connected(),
+            led: Led::new(),
+            battery_led: LedState::Normal,
+            developer_settings: DeveloperSettings::new(),
+            developer_settings_stamp: None,
+            remote_shells: EnumMap::default(),
+            storage_failed: None,
+            storage_task: None,
         })
     }
 
     pub async fn run_event_loop(&mut self) -> Result<()> {
         info!("hello from Allium {}", ALLIUM_VERSION);
 
-        info!("setting volume: {}", self.state.volume);
-        self.platform.set_volume(self.state.volume)?;
+        info!("setting volume: {}", self.volume());
+        self.platform
+            .set_volume(self.audio_settings.level(self.volume()))?;
+
+        info!("applying equalizer: {:?}", self.audio_settings.equalizer);
+        if let Err(e) = self.audio_settings.apply_equalizer() {
+            error!("failed to apply equalizer: {}", e);
+        }
 
         info!("setting brightness: {}", self.state.brightness);
         self.platform.set_brightness(self.state.brightness)?;
 
-        info!("loading display settings");
+        info!("applying display settings");
+        self.night_mode = self.display_settings.is_night_mode(Local::now().hour());
         self.platform
-            .set_display_settings(&mut DisplaySettings::load()?)?;
+            .set_display_settings(&mut self.display_settings)?;
 
         if DefaultPlatform::has_wifi() {
             info!("wifi detected, loading wifi settings");
             WiFiSettings::load()?.init()?;
         }
 
+        if bluetooth::is_available() {
+            info!("bluetooth detected, loading bluetooth settings");
+            BluetoothSettings::load()?.init();
+        }
+
+        self.check_storage().await;
+
         info!("starting event loop");
         #[cfg(unix)]
         {
@@ -171,6 +579,21 @@ impl AlliumD<DefaultPlatform> {
             let mut sigterm = tokio::signal::unix::signal(SignalKind::terminate())?;
 
             let mut battery_interval = Instant::now();
+            let mut last_activity = Instant::now();
+            let mut last_indicator = Instant::now();
+            let mut last_overlay = Instant::now();
+            // Last check for a game change, while the webhook is enabled
+            let mut game_info_checked: Option<Instant> = None;
+
+            // Daily play time limit of the running game, refreshed every PARENTAL_CHECK_INTERVAL
+            let mut play_time_deadline: Option<Instant> = None;
+            let mut play_time_checked: Option<Instant> = None;
+            let mut play_time_grace: Option<Instant> = None;
+            let mut play_time_warned = false;
+
+            // Lowest low battery threshold warned about since the battery was last charging
+            let mut battery_warned: Option<i32> = None;
+            let mut battery_shutdown_deadline: Option<Instant> = None;
 
             // If battery is charging, suspend.
             let mut battery = self.platform.battery()?;
@@ -178,6 +601,10 @@ impl AlliumD<DefaultPlatform> {
             if battery.charging() {
                 self.handle_charging().await?;
             }
+            self.check_battery_saver(&battery).await?;
+            self.publish_status(self.device_status(&battery));
+            self.battery_led = self.battery_led_state(&battery);
+            self.show_led(self.battery_led);
 
             loop {
                 if let Some(menu) = self.menu.as_mut()
@@ -186,6 +613,7 @@ impl AlliumD<DefaultPlatform> {
                     info!("menu process terminated, resuming game");
                     self.menu = None;
                     RetroArchCommand::Unpause.send().await?;
+                    self.sync_playback().await?;
                 }
 
                 if battery_interval.elapsed() >= BATTERY_UPDATE_INTERVAL {
@@ -194,21 +622,140 @@ impl AlliumD<DefaultPlatform> {
                     if let Err(e) = battery.update() {
                         error!("failed to update battery: {}", e);
                     }
-                    if battery.percentage() <= BATTERY_SHUTDOWN_THRESHOLD && !battery.charging() {
-                        warn!("battery is low, shutting down");
-                        self.handle_quit().await?;
+                    if let Err(e) = self.log_battery(&battery) {
+                        error!("failed to log battery: {}", e);
+                    }
+                    self.check_battery_saver(&battery).await?;
+                    self.check_storage().await;
+                    if self.is_ingame()
+                        && let Err(e) = GameInfo::trim_launch_stderr()
+                    {
+                        error!("failed to trim launch stderr: {}", e);
+                    }
+                    self.publish_status(self.device_status(&battery));
+                    self.battery_led = self.battery_led_state(&battery);
+                    self.show_led(self.battery_led);
+                    if battery.charging() {
+                        battery_warned = None;
+                        if battery_shutdown_deadline.take().is_some() {
+                            info!("battery charging, cancelling shutdown");
+                        }
+                    } else if battery.percentage() <= BATTERY_SHUTDOWN_THRESHOLD {
+                        if battery_shutdown_deadline.is_none() {
+                            warn!("battery is low, shutting down soon");
+                            battery_shutdown_deadline =
+                                Some(Instant::now() + BATTERY_SHUTDOWN_GRACE);
+                            let mut map = HashMap::new();
+                            map.insert("seconds".into(), BATTERY_SHUTDOWN_GRACE.as_secs().into());
+                            Rumble::WARNING.play();
+                            self.warn(self.locale.ta("battery-shutdown-warning", &map))
+                                .await?;
+                        }
+                    } else if let Some(threshold) = low_battery_warning(
+                        &self.power_settings.low_battery_warnings,
+                        battery.percentage(),
+                        battery_warned,
+                    ) {
+                        info!("battery below {}%, warning", threshold);
+                        battery_warned = Some(threshold);
+                        let mut map = HashMap::new();
+                        map.insert("percent".into(), battery.percentage().into());
+                        Rumble::WARNING.play();
+                        self.warn(self.locale.ta("battery-low-warning", &map))
+                            .await?;
+                    }
+                }
+
+                self.publish_activity(battery.charging());
+
+                if let Err(e) = self.check_webhook_settings() {
+                    error!("failed to load webhook settings: {}", e);
+                }
+                let game_info_sleep = if self.webhook_settings.is_active() {
+                    let elapsed =
+                        game_info_checked.map_or(GAME_INFO_POLL_INTERVAL, |t| t.elapsed());
+                    if elapsed >= GAME_INFO_POLL_INTERVAL {
+                        game_info_checked = Some(Instant::now());
+                        if let Err(e) = self.check_game_change() {
+                            error!("failed to check for game change: {}", e);
+                        }
+                        GAME_INFO_POLL_INTERVAL
+                    } else {
+                        GAME_INFO_POLL_INTERVAL - elapsed
                     }
+                } else {
+                    game_info_checked = None;
+                    self.playing = None;
+                    std::time::Duration::MAX
+                };
+
+                if !self.is_ingame() {
+                    play_time_deadline = None;
+                    play_time_checked = None;
+                    play_time_grace = None;
+                    play_time_warned = false;
+                } else if play_time_checked.is_none_or(|t| t.elapsed() >= PARENTAL_CHECK_INTERVAL) {
+                    play_time_checked = Some(Instant::now());
+                    play_time_deadline = match remaining_play_time(&self.database) {
+                        Ok(remaining) => remaining.map(|remaining| {
+                            let deadline = Instant::now() + remaining.to_std().unwrap_or_default();
+                            play_time_grace.map_or(deadline, |grace| deadline.max(grace))
+                        }),
+                        Err(e) => {
+                            error!("failed to check play time: {}", e);
+                            None
+                        }
+                    };
                 }
 
                 let auto_sleep_duration = match self.power_settings.auto_sleep_duration_minutes {
                     0 => std::time::Duration::MAX, // disabled
                     t => std::time::Duration::new(t as u64 * 60, 0),
                 };
+                let idle_sleep = auto_sleep_duration.saturating_sub(last_activity.elapsed());
+                if let Err(e) = self.check_clock_settings() {
+                    error!("failed to load clock settings: {}", e);
+                }
+                let (clock_event, clock_sleep) = next_clock_event(&self.clock_settings);
+                // Scheduled night mode is checked at least every CLOCK_EVENT_POLL_INTERVAL
+                if let Err(e) = self.check_night_mode() {
+                    error!("failed to apply night mode: {}", e);
+                }
+                if let Err(e) = self.check_audio_settings() {
+                    error!("failed to apply audio settings: {}", e);
+                }
+                if let Err(e) = self.check_developer_settings() {
+                    error!("failed to apply developer settings: {}", e);
+                }
+                let indicator_sleep = if self.playback.fast_forward && self.menu.is_none() {
+                    FAST_FORWARD_INDICATOR_INTERVAL.saturating_sub(last_indicator.elapsed())
+                } else {
+                    std::time::Duration::MAX
+                };
+                let (play_time_event, play_time_sleep) =
+                    next_play_time_event(play_time_deadline, play_time_warned);
+                let battery_shutdown_sleep = battery_shutdown_deadline
+                    .map_or(std::time::Duration::MAX, |deadline| {
+                        deadline.saturating_duration_since(Instant::now())
+                    });
+                // The fast-forward indicator takes the place of the clock while it is shown
+                let overlay_sleep = if self.clock_settings.is_overlay_shown()
+                    && !self.playback.fast_forward
+                    && self.menu.is_none()
+                    && self.is_ingame()
+                {
+                    clock_overlay_sleep(&self.clock_settings, last_overlay)
+                } else {
+                    std::time::Duration::MAX
+                };
+
                 tokio::select! {
                     key_event = self.platform.poll() => {
+                        last_activity = Instant::now();
                         self.handle_key_event(key_event).await?;
                     }
-                    _ = tokio::time::sleep(auto_sleep_duration) => {
+                    _ = tokio::time::sleep(idle_sleep) => {
+                        last_activity = Instant::now();
                         if !self.power_settings.auto_sleep_when_charging && battery.charging() {
                             info!("battery charging, don't auto sleep");
                         } else {
@@ -216,14 +763,87 @@ impl AlliumD<DefaultPlatform> {
                             self.handle_quit().await?;
                         }
                     }
-                    _ = self.main.wait() => {
+                    _ = tokio::time::sleep(clock_sleep) => {
+                        if let Some(event) = clock_event {
+                            self.handle_clock_event(event).await?;
+                        }
+                    }
+                    _ = tokio::time::sleep(play_time_sleep) => {
+                        match play_time_event {
+                            Some(PlayTimeEvent::Warning) => {
+                                info!("play time almost up for today");
+                                play_time_warned = true;
+                                self.warn(self.locale.t("parental-play-time-warning")).await?;
+                            }
+                            Some(PlayTimeEvent::Limit) => {
+                                info!("play time limit reached, suspending");
+                                self.handle_suspend().await?;
+                                // Leave a moment to save before suspending again
+                                let grace = Instant::now() + PLAY_TIME_LIMIT_GRACE;
+                                play_time_grace = Some(grace);
+                                play_time_deadline = Some(grace);
+                            }
+                            None => {}
+                        }
+                    }
+                    _ = tokio::time::sleep(battery_shutdown_sleep) => {
+                        warn!("battery is low, shutting down");
+                        battery_shutdown_deadline = None;
+                        self.handle_quit().await?;
+                    }
+                    _ = tokio::time::sleep(indicator_sleep) => {
+                        last_indicator = Instant::now();
+                        self.show_fast_forward_indicator().await?;
+                    }
+                    _ = tokio::time::sleep(game_info_sleep) => {}
+                    _ = tokio::time::sleep(overlay_sleep) => {
+                        last_overlay = Instant::now();
+                        let message = self.overlay_message(&battery);
+                        RetroArchCommand::ShowMsg(message).send().await?;
+                    }
+                    status = self.main.wait() => {
+                        if status
+                            .as_ref()
+                            .is_ok_and(|status| status.code() == Some(RESTART_ALLIUMD_EXIT_CODE))
+                        {
+                            info!("launcher reset all data, restarting");
+                            #[cfg(unix)]
+                            self.restart()?;
+                        }
                         if !self.is_terminating {
                             info!("main process terminated, recording play time");
+                            if self.is_ingame() {
+                                let game_info = GameInfo::load().ok().flatten();
+                                if let Some(game_info) = &game_info
+                                    && let Ok(status) = status
+                                {
+                                    check_launch_failure(game_info, status);
+                                }
+                                handle_saves_on_exit(game_info.map(|info| (info.path, info.core)));
+                            }
                             self.update_play_time()?;
                             GameInfo::delete()?;
-                            self.main = spawn_main().await?;
+                            let cpu_profile = CpuProfile::default().capped(self.battery_saver);
+                            if let Err(e) = cpu_profile.apply() {
+                                error!("failed to restore cpu profile: {}", e);
+                            }
+                            if self.playback.rewind {
+                                self.platform.play_keys(rewind_keys(false))?;
+                            }
+                            self.playback = Playback::default();
+                            self.macro_recorder = None;
+                            self.main = spawn_main(false).await?;
                         }
                     }
+                    _ = wait_unlocked(&mut self.lock) => {
+                        info!("unlocked, resuming");
+                        self.lock = None;
+                        signal(&self.main, Signal::SIGCONT)?;
+                    }
+                    Some(command) = self.remote.recv() => {
+                        last_activity = Instant::now();
+                        self.handle_remote_command(command).await?;
+                    }
                     _ = sigint.recv() => self.handle_quit().await?,
                     _ = sigterm.recv() => self.handle_quit().await?,
                 }
@@ -272,7 +892,28 @@ impl AlliumD<DefaultPlatform> {
             KeyEvent::Autorepeat(_) => {}
         }
 
+        // The lock screen handles the other keys, so only sleep and volume work while it's shown
+        if self.lock.is_some()
+            && !matches!(
+                key_event,
+                KeyEvent::Pressed(key) | KeyEvent::Released(key) | KeyEvent::Autorepeat(key)
+                    if matches!(key, Key::Power | Key::LidClose | Key::VolUp | Key::VolDown)
+            )
+        {
+            return Ok(());
+        }
+
+        // Hotkeys aren't part of the macro
+        if !self.keys[Key::Menu]
+            && let Some(recorder) = self.macro_recorder.as_mut()
+            && !recorder.record(key_event, Instant::now())
+        {
+            info!("macro is full, stopping recording");
+            self.toggle_macro_recording().await?;
+        }
+
         if self.keys[Key::Menu] {
+            self.check_macro_settings()?;
             // Global hotkeys
             match key_event {
                 KeyEvent::Autorepeat(Key::Menu) => {
@@ -313,22 +954,24 @@ impl AlliumD<DefaultPlatform> {
                     self.add_volume(1)?;
                 }
                 KeyEvent::Released(Key::Power) => {
-                    let game_info = GameInfo::load()?;
-                    let name = match game_info.as_ref() {
-                        Some(game_info) => game_info.name.as_str(),
-                        None => "Allium",
-                    };
-                    let file_name = format!(
-                        "{}-{}.png",
-                        chrono::Local::now().format("%Y-%m-%d_%H-%M-%S"),
-                        name,
-                    );
-                    Command::new("screenshot")
-                        .arg(ALLIUM_SD_ROOT.join("Screenshots").join(file_name))
-                        .arg("--rumble")
-                        .spawn()?
-                        .wait()
-                        .await?;
+                    self.screenshot(true).await?;
+                }
+                KeyEvent::Pressed(key)
+                    if self.power_settings.battery_saver_key(&self.macro_settings) == Some(key) =>
+                {
+                    #[cfg(unix)]
+                    self.set_battery_saver(!self.battery_saver).await?;
+                }
+                KeyEvent::Pressed(Key::R) if self.has_fast_forward_hotkeys() => {
+                    self.toggle_fast_forward().await?;
+                }
+                KeyEvent::Pressed(Key::Select) if self.is_ingame() => {
+                    self.toggle_macro_recording().await?;
+                }
+                KeyEvent::Pressed(key) if self.is_ingame() && self.macro_recorder.is_none() => {
+                    // RetroArch treats keys pressed with Menu as hotkeys, so wait until it's
+                    // released to play the macro
+                    self.macro_queued = self.macro_settings.hotkey == key;
                 }
                 _ => {}
             }
@@ -365,8 +1008,20 @@ impl AlliumD<DefaultPlatform> {
                         PowerButtonAction::Nothing => {}
                     }
                 }
+                KeyEvent::Pressed(Key::Headphones) => {
+                    #[cfg(unix)]
+                    self.set_headphones(true).await?;
+                }
+                KeyEvent::Released(Key::Headphones) => {
+                    #[cfg(unix)]
+                    self.set_headphones(false).await?;
+                }
                 KeyEvent::Released(Key::Menu) => {
                     info!("menu key released");
+                    if self.macro_queued {
+                        self.macro_queued = false;
+                        self.play_macro()?;
+                    }
                     if self.is_menu_pressed_alone {
                         info!("menu key pressed alone, toggling menu");
                         info!("is_ingame: {}", self.is_ingame());
@@ -398,6 +1053,47 @@ impl AlliumD<DefaultPlatform> {
         Ok(())
     }
 
+    async fn handle_remote_command(&mut self, command: RemoteCommand) -> Result<()> {
+        match command {
+            RemoteCommand::Launch(path) => {
+                info!("web remote: launching {}", path.display());
+                // The launcher picks up the request when it is restarted
+                fs::write(ALLIUM_REMOTE_LAUNCH.as_path(), path.as_os_str().as_encoded_bytes())?;
+                if let Some(menu) = self.menu.as_mut() {
+                    terminate(menu).await?;
+                    self.menu = None;
+                }
+                terminate(&mut self.main).await?;
+            }
+            RemoteCommand::Screenshot => self.screenshot(false).await?,
+            RemoteCommand::SetVolume(volume) => self.add_volume(volume - self.volume())?,
+            RemoteCommand::SetBrightness(brightness) => {
+                self.add_brightness(brightness as i8 - self.state.brightness as i8)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn screenshot(&self, rumble: bool) -> Result<()> {
+        let game_info = GameInfo::load()?;
+        let name = match game_info.as_ref() {
+            Some(game_info) => game_info.name.as_str(),
+            None => "Allium",
+        };
+        let file_name = format!(
+            "{}-{}.png",
+            chrono::Local::now().format("%Y-%m-%d_%H-%M-%S"),
+            name,
+        );
+        let mut command = Command::new("screenshot");
+        command.arg(ALLIUM_SD_ROOT.join("Screenshots").join(file_name));
+        if rumble {
+            command.arg("--rumble");
+        }
+        command.spawn()?.wait().await?;
+        Ok(())
+    }
+
     #[cfg(unix)]
     async fn handle_charging(&mut self) -> Result<()> {
         info!("charging...");
@@ -416,6 +1112,7 @@ impl AlliumD<DefaultPlatform> {
 
         #[allow(clippy::let_unit_value)]
         let ctx = self.platform.suspend()?;
+        self.show_led(LedState::Charging);
 
         let mut battery = self.platform.battery()?;
 
@@ -435,18 +1132,57 @@ impl AlliumD<DefaultPlatform> {
             }
         }
 
-        signal(&self.main, Signal::SIGCONT)?;
-        self.platform.unsuspend(ctx)
+        self.platform.unsuspend(ctx)?;
+        self.show_led(self.battery_led);
+        self.lock_or_resume()?;
+        Ok(())
+    }
+
+    /// Starts alliumd again in place of this process, after its settings were reset. Its state
+    /// isn't saved, as it was reset too.
+    #[cfg(unix)]
+    fn restart(&mut self) -> Result<()> {
+        for child in self.remote_shells.values_mut().flatten() {
+            child.start_kill().ok();
+        }
+        let error = std::process::Command::new(std::env::current_exe()?).exec();
+        bail!("failed to restart alliumd: {}", error)
+    }
+
+    /// Shows the lock screen after waking up if it's enabled, or resumes the main process. The
+    /// event loop resumes it once the lock screen is unlocked.
+    #[cfg(unix)]
+    fn lock_or_resume(&mut self) -> Result<()> {
+        if self.lock.is_none() {
+            self.lock = spawn_lock_screen()?;
+        }
+        if self.lock.is_none() {
+            signal(&self.main, Signal::SIGCONT)?;
+        }
+        Ok(())
     }
 
     #[cfg(unix)]
     async fn handle_suspend(&mut self) -> Result<()> {
         info!("suspending...");
+        if self.is_ingame() && self.menu.is_none() {
+            self.save_resume_screenshot().await;
+        }
+        self.auto_save_state().await;
         #[allow(clippy::let_unit_value)]
         let ctx = self.platform.suspend()?;
         signal(&self.main, Signal::SIGSTOP)?;
+        self.show_led(LedState::Suspended);
 
+        let suspended_at = Instant::now();
+        let mut wake_event = None;
         loop {
+            let idle_sleep = IDLE_TIMEOUT.saturating_sub(suspended_at.elapsed());
+            if let Err(e) = self.check_clock_settings() {
+                error!("failed to load clock settings: {}", e);
+            }
+            let (clock_event, clock_sleep) = next_clock_event(&self.clock_settings);
+
             tokio::select! {
                 key_event = self.platform.poll()=> {
                     if matches!(key_event, KeyEvent::Released(Key::Power)) || matches!(key_event, KeyEvent::Released(Key::LidClose)) {
@@ -455,7 +1191,31 @@ impl AlliumD<DefaultPlatform> {
                         break;
                     }
                 }
-                _ = tokio::time::sleep(IDLE_TIMEOUT) => {
+                _ = tokio::time::sleep(clock_sleep) => {
+                    match clock_event {
+                        Some(ClockEvent::Timer) if self.clock_settings.timer_suspend => {
+                            // Already suspended, so there's nothing left to do
+                            self.clear_clock_event(|settings| settings.timer = None)?;
+                        }
+                        Some(ClockEvent::SleepWarning) => {}
+                        Some(ClockEvent::Sleep) => {
+                            self.clear_clock_event(|settings| settings.sleep_timer = None)?;
+                            if self.clock_settings.sleep_timer_action == SleepTimerAction::Shutdown {
+                                info!("sleep timer finished, shutting down");
+                                signal(&self.main, Signal::SIGCONT)?;
+                                self.platform.unsuspend(ctx)?;
+                                self.handle_quit().await?;
+                                return Ok(());
+                            }
+                        }
+                        Some(event) => {
+                            wake_event = Some(event);
+                            break;
+                        }
+                        None => {}
+                    }
+                }
+                _ = tokio::time::sleep(idle_sleep) => {
                     info!("idle timeout, shutting down");
                     signal(&self.main, Signal::SIGCONT)?;
                     self.platform.unsuspend(ctx)?;
@@ -466,8 +1226,317 @@ impl AlliumD<DefaultPlatform> {
         }
 
         info!("waking up from suspend...");
-        signal(&self.main, Signal::SIGCONT)?;
-        self.platform.unsuspend(ctx)
+        // The game is still running, so the screenshot saved in case the battery died is stale
+        fs::remove_file(ALLIUM_RESUME_SCREENSHOT.as_path()).ok();
+        self.platform.unsuspend(ctx)?;
+        self.show_led(self.battery_led);
+        self.lock_or_resume()?;
+
+        if let Some(event) = wake_event {
+            self.handle_clock_event(event).await?;
+        }
+        Ok(())
+    }
+
+    /// Loads the clock settings again if they've been saved since they were last loaded, or once
+    /// `CLOCK_EVENT_POLL_INTERVAL` has passed, as the clock app may change them at any time.
+    fn check_clock_settings(&mut self) -> Result<()> {
+        let stamp = ClockSettings::stamp();
+        if stamp != self.clock_settings_stamp
+            || self.clock_settings_loaded.elapsed() >= CLOCK_EVENT_POLL_INTERVAL
+        {
+            // A file that can't be read is only reported once per interval
+            self.clock_settings_stamp = stamp;
+            self.clock_settings_loaded = Instant::now();
+            self.clock_settings = ClockSettings::reload()?;
+        }
+        Ok(())
+    }
+
+    /// Clears the timer, reminder or sleep timer that went off, and saves the clock settings. Changes
+    /// made in the clock app since the last check are kept.
+    fn clear_clock_event(&mut self, clear: impl FnOnce(&mut ClockSettings)) -> Result<()> {
+        match ClockSettings::reload() {
+            Ok(settings) => self.clock_settings = settings,
+            Err(e) => warn!("failed to load clock settings: {}", e),
+        }
+        clear(&mut self.clock_settings);
+        self.clock_settings.save()?;
+        self.clock_settings_stamp = ClockSettings::stamp();
+        self.clock_settings_loaded = Instant::now();
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    async fn handle_clock_event(&mut self, event: ClockEvent) -> Result<()> {
+        match event {
+            ClockEvent::Alarm => {
+                info!("alarm went off");
+                self.ring(self.locale.t("clock-alarm-ringing")).await?;
+            }
+            ClockEvent::Timer => {
+                info!("timer finished");
+                self.clear_clock_event(|settings| settings.timer = None)?;
+                if self.clock_settings.timer_suspend {
+                    Box::pin(self.handle_suspend()).await?;
+                } else {
+                    self.ring(self.locale.t("clock-timer-finished")).await?;
+                }
+            }
+            ClockEvent::Reminder => {
+                info!("reminder went off");
+                self.clear_clock_event(|settings| settings.reminder = None)?;
+                self.ring(self.locale.t("clock-reminder")).await?;
+            }
+            ClockEvent::SleepWarning => {
+                info!("sleep timer finishing soon");
+                self.warn(self.locale.t("sleep-timer-warning")).await?;
+            }
+            ClockEvent::Sleep => {
+                info!("sleep timer finished");
+                self.clear_clock_event(|settings| settings.sleep_timer = None)?;
+                match self.clock_settings.sleep_timer_action {
+                    SleepTimerAction::Suspend => Box::pin(self.handle_suspend()).await?,
+                    SleepTimerAction::Shutdown => self.handle_quit().await?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Shows the state on the power LED, with the pattern set for it in the LED settings. The
+    /// LED isn't animated while suspended, so that the device can stay asleep.
+    fn show_led(&mut self, state: LedState) {
+        let pattern = LedSettings::load().unwrap_or_default().pattern(state);
+        self.led.set_pattern(pattern, state != LedState::Suspended);
+    }
+
+    /// Returns the state of the battery to show on the LED. The battery is low once it's at or
+    /// below any of the low battery warning levels.
+    fn battery_led_state(&self, battery: &impl Battery) -> LedState {
+        if battery.charging() {
+            LedState::Charging
+        } else if self
+            .power_settings
+            .low_battery_warnings
+            .iter()
+            .any(|&threshold| battery.percentage() <= threshold)
+        {
+            LedState::LowBattery
+        } else {
+            LedState::Normal
+        }
+    }
+
+    /// Mounts a second SD card or USB drive when one is inserted, and unmounts it once it's
+    /// removed. The launcher notices through the published status. Mounting a large drive can
+    /// take a while, so it's done in the background rather than holding up the event loop.
+    async fn check_storage(&mut self) {
+        if let Some(task) = self.storage_task.take() {
+            if !task.is_finished() {
+                self.storage_task = Some(task);
+                return;
+            }
+            match task.await {
+                Ok(failed) => self.storage_failed = failed,
+                Err(e) => error!("storage task failed: {}", e),
+            }
+        }
+
+        if let Some(volume) = Volume::load() {
+            if !volume.device.exists() {
+                info!("storage device removed");
+                self.storage_task = Some(tokio::spawn(async move {
+                    if let Err(e) = volume.unmount().await {
+                        error!("failed to unmount storage device: {}", e);
+                    }
+                    None
+                }));
+            }
+            return;
+        }
+
+        let Some(device) = storage::detect() else {
+            self.storage_failed = None;
+            return;
+        };
+        if self.storage_failed.as_ref() == Some(&device) {
+            return;
+        }
+        self.storage_task = Some(tokio::spawn(async move {
+            match Volume::mount(&device).await {
+                Ok(_) => None,
+                Err(e) => {
+                    error!("failed to mount storage device: {}", e);
+                    Some(device)
+                }
+            }
+        }));
+    }
+
+    /// Turns the battery saver on or off as the battery crosses the threshold.
+    #[cfg(unix)]
+    async fn check_battery_saver(&mut self, battery: &impl Battery) -> Result<()> {
+        let low = !battery.charging()
+            && battery.percentage() <= self.power_settings.battery_saver_threshold;
+        if low != self.battery_low {
+            self.battery_low = low;
+            self.set_battery_saver(low).await?;
+        }
+        Ok(())
+    }
+
+    /// Turns the battery saver on or off. While it's on, the brightness is capped, the CPU is
+    /// held to the powersave profile, and the launcher is told to save power through the device
+    /// status.
+    #[cfg(unix)]
+    async fn set_battery_saver(&mut self, battery_saver: bool) -> Result<()> {
+        if battery_saver == self.battery_saver {
+            return Ok(());
+        }
+        info!("setting battery saver: {}", battery_saver);
+        self.battery_saver = battery_saver;
+        self.platform.set_brightness(self.brightness())?;
+
+        let cpu_profile = if self.is_ingame() {
+            GameInfo::load()?.map(|game_info| game_info.cpu_profile)
+        } else {
+            None
+        };
+        if let Err(e) = cpu_profile
+            .unwrap_or_default()
+            .capped(battery_saver)
+            .apply()
+        {
+            error!("failed to set cpu profile: {}", e);
+        }
+
+        self.publish_status(DeviceStatus {
+            battery_saver,
+            ..self.status.unwrap_or_default()
+        });
+        self.warn(self.locale.t(if battery_saver {
+            "battery-saver-on"
+        } else {
+            "battery-saver-off"
+        }))
+        .await
+    }
+
+    /// Applies the display settings again when scheduled night mode turns on or off, so that the
+    /// color temperature changes over games too. The settings are only loaded again once they've
+    /// been saved, or when the hour changes.
+    fn check_night_mode(&mut self) -> Result<()> {
+        let hour = Local::now().hour();
+        let stamp = DisplaySettings::stamp();
+        if stamp != self.display_settings_stamp || hour != self.display_settings_hour {
+            // A file that can't be read is only reported once, and read again when it's next
+            // saved or the hour changes
+            self.display_settings_stamp = stamp;
+            self.display_settings_hour = hour;
+            self.display_settings = DisplaySettings::reload()?;
+        }
+        let night_mode = self.display_settings.is_night_mode(hour);
+        if night_mode != self.night_mode {
+            info!("setting night mode: {}", night_mode);
+            self.night_mode = night_mode;
+            self.platform
+                .set_display_settings(&mut self.display_settings)?;
+        }
+        Ok(())
+    }
+
+    /// Applies the volume curve, limit and equalizer again when they're changed in the settings.
+    /// The settings are only loaded again once they've been saved.
+    fn check_audio_settings(&mut self) -> Result<()> {
+        let stamp = AudioSettings::stamp();
+        if stamp == self.audio_settings_stamp {
+            return Ok(());
+        }
+        // A file that can't be read is only reported once, and read again when it's next saved
+        self.audio_settings_stamp = stamp;
+        let settings = AudioSettings::reload()?;
+        if settings != self.audio_settings {
+            info!("audio settings changed, setting volume");
+            let equalizer_changed = settings.equalizer != self.audio_settings.equalizer;
+            self.audio_settings = settings;
+            self.set_volume()?;
+            if equalizer_changed {
+                self.audio_settings.apply_equalizer()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts or stops the remote shells when they're turned on or off in the settings. The
+    /// settings are only loaded again once they've been saved.
+    fn check_developer_settings(&mut self) -> Result<()> {
+        // Servers that exited on their own are reaped, and started again below
+        let mut exited = false;
+        for (shell, child) in self.remote_shells.iter_mut() {
+            if let Some(running) = child
+                && let Ok(Some(status)) = running.try_wait()
+            {
+                warn!("{:?} server exited: {}", shell, status);
+                *child = None;
+                exited = true;
+            }
+        }
+
+        let stamp = DeveloperSettings::stamp();
+        if !exited && stamp.is_some() && stamp == self.developer_settings_stamp {
+            return Ok(());
+        }
+        let settings = DeveloperSettings::load()?;
+        self.developer_settings_stamp = DeveloperSettings::stamp();
+        if !exited && settings == self.developer_settings {
+            return Ok(());
+        }
+        self.developer_settings = settings;
+        for (shell, child) in self.remote_shells.iter_mut() {
+            let enabled = settings.is_enabled(shell);
+            if enabled && child.is_none() {
+                info!("starting {:?} server", shell);
+                match shell.spawn() {
+                    Ok(spawned) => *child = Some(spawned),
+                    Err(e) => error!("failed to start {:?} server: {}", shell, e),
+                }
+            } else if !enabled && let Some(mut child) = child.take() {
+                info!("stopping {:?} server", shell);
+                child.start_kill()?;
+                tokio::spawn(async move { child.wait().await });
+            }
+        }
+        Ok(())
+    }
+
+    /// Shows a message and rumbles to get the user's attention.
+    #[cfg(unix)]
+    async fn ring(&self, message: String) -> Result<()> {
+        Command::new("say").arg(message).spawn()?.wait().await?;
+        for _ in 0..RING_PULSES {
+            rumble::set(true);
+            tokio::time::sleep(RING_PULSE_DURATION).await;
+            rumble::set(false);
+            tokio::time::sleep(RING_PULSE_DURATION).await;
+        }
+        Ok(())
+    }
+
+    /// Shows a warning, over the game if one is running.
+    #[cfg(unix)]
+    async fn warn(&self, message: String) -> Result<()> {
+        if self.is_ingame() && self.menu.is_none() {
+            RetroArchCommand::ShowMsg(message).send().await?;
+        } else {
+            Command::new("say")
+                .arg("--bg")
+                .arg(message)
+                .spawn()?
+                .wait()
+                .await?;
+        }
+        Ok(())
     }
 
     #[cfg(unix)]
@@ -486,7 +1555,10 @@ impl AlliumD<DefaultPlatform> {
 
             if let Some(menu) = self.menu.as_mut() {
                 terminate(menu).await?;
+            } else {
+                self.save_resume_screenshot().await;
             }
+            self.auto_save_state().await;
         }
 
         terminate(&mut self.main).await?;
@@ -506,6 +1578,56 @@ impl AlliumD<DefaultPlatform> {
         Ok(())
     }
 
+    /// Saves the game's state to the auto slot, which RetroArch loads when the game is next
+    /// launched, and flushes it to the SD card. This way, the game resumes where it was left even
+    /// if the battery dies while suspended.
+    #[cfg(unix)]
+    async fn auto_save_state(&self) {
+        if !self.power_settings.auto_save_state || !self.is_ingame() {
+            return;
+        }
+        match GameInfo::load() {
+            Ok(Some(game_info)) if game_info.has_menu => {}
+            Ok(_) => return,
+            Err(e) => {
+                warn!("failed to load game info: {}", e);
+                return;
+            }
+        }
+
+        debug!("saving state before suspending or shutting down");
+        if let Err(e) = RetroArchCommand::SaveStateSlot(AUTO_SAVE_STATE_SLOT)
+            .send()
+            .await
+        {
+            warn!("failed to save state: {}", e);
+            return;
+        }
+        // RetroArch writes the state in the background, and its SRAM along with it
+        tokio::time::sleep(AUTO_SAVE_STATE_DELAY).await;
+        match Command::new("sync").spawn() {
+            Ok(mut child) => {
+                child.wait().await.ok();
+            }
+            Err(e) => warn!("failed to flush saves: {}", e),
+        }
+    }
+
+    /// Saves the current framebuffer, to be shown on the next boot while the game relaunches.
+    /// Skipped while the menu is open, since it covers the game.
+    #[cfg(unix)]
+    async fn save_resume_screenshot(&self) {
+        match Command::new("screenshot")
+            .arg(ALLIUM_RESUME_SCREENSHOT.as_path())
+            .spawn()
+        {
+            Ok(mut child) => {
+                child.wait().await.ok();
+            }
+            Err(e) => warn!("failed to save resume screenshot: {}", e),
+        }
+    }
+
     #[allow(unused)]
     fn update_play_time(&self) -> Result<()> {
         if !self.is_ingame() {
@@ -521,29 +1643,329 @@ impl AlliumD<DefaultPlatform> {
             return Ok(());
         }
 
-        let database = Database::new()?;
+        let database = &self.database;
         database.add_play_time(game_info.path.as_path(), game_info.play_time());
+        for (date, play_time) in parental::play_time_by_day(Local::now(), game_info.play_time()) {
+            database.add_daily_play_time(date, play_time)?;
+        }
+        database.add_play_session(&PlaySession {
+            path: game_info.path.clone(),
+            start: game_info.start_time.timestamp(),
+            play_time: game_info.play_time(),
+        })?;
 
         Ok(())
     }
 
+    /// Records the battery level in the battery history, along with the game being played.
+    fn log_battery(&self, battery: &impl Battery) -> Result<()> {
+        let game = if self.is_ingame() {
+            GameInfo::load()?.map(|game_info| game_info.path)
+        } else {
+            None
+        };
+
+        self.database.add_battery_sample(
+            &BatterySample {
+                timestamp: Utc::now().timestamp(),
+                percentage: battery.percentage(),
+                charging: battery.charging(),
+                game,
+            },
+            BATTERY_HISTORY_RETENTION,
+        )
+    }
+
     fn is_ingame(&self) -> bool {
         Path::new(&*ALLIUM_GAME_INFO).exists()
     }
 
+    /// Lets background jobs in other processes know whether they may run.
+    fn publish_activity(&mut self, charging: bool) {
+        let activity = DeviceActivity {
+            in_game: self.is_ingame(),
+            charging,
+        };
+        if self.activity == Some(activity) {
+            return;
+        }
+        debug!("device activity changed: {:?}", activity);
+        if let Err(e) = activity.save() {
+            error!("failed to save device activity: {}", e);
+        }
+        self.activity = Some(activity);
+    }
+
+    fn device_status(&self, battery: &impl Battery) -> DeviceStatus {
+        DeviceStatus {
+            battery_percentage: battery.percentage(),
+            charging: battery.charging(),
+            wifi_connected: wifi::ip_address().is_some(),
+            volume: self.volume(),
+            battery_saver: self.battery_saver,
+            external_storage: storage::is_mounted(),
+        }
+    }
+
+    /// Lets the status bar in other processes know the battery, Wi-Fi and volume state, and
+    /// syncs the time from NTP when Wi-Fi connects.
+    fn publish_status(&mut self, status: DeviceStatus) {
+        if self.status == Some(status) {
+            return;
+        }
+        debug!("device status changed: {:?}", status);
+        if let Err(e) = status.save() {
+            error!("failed to save device status: {}", e);
+        }
+        if status.wifi_connected && !self.status.is_some_and(|s| s.wifi_connected) {
+            info!("wifi connected");
+            if WiFiSettings::load().is_ok_and(|s| s.ntp)
+                && let Err(e) = wifi::ntp_sync()
+            {
+                error!("failed to sync time: {}", e);
+            }
+        }
+        self.status = Some(status);
+    }
+
+    /// Returns the text of the in-game overlay: the time if the clock overlay is enabled, and
+    /// the battery level and volume if the status overlay is enabled.
+    fn overlay_message(&self, battery: &impl Battery) -> String {
+        let settings = &self.clock_settings;
+        let mut parts = Vec::new();
+        if settings.overlay_enabled {
+            parts.push(Local::now().format("%H:%M").to_string());
+        }
+        if settings.status_overlay {
+            let mut map = HashMap::new();
+            map.insert("battery".into(), battery.percentage().into());
+            map.insert("volume".into(), self.volume().into());
+            parts.push(self.locale.ta("status-overlay", &map));
+        }
+        parts.join("  ")
+    }
+
+    /// Loads the webhook settings again if they've been saved since they were last loaded.
+    fn check_webhook_settings(&mut self) -> Result<()> {
+        let stamp = WebhookSettings::stamp();
+        if stamp != self.webhook_settings_stamp {
+            // A file that can't be read is only reported once, and read again when it's next saved
+            self.webhook_settings_stamp = stamp;
+            self.webhook_settings = WebhookSettings::reload()?;
+        }
+        Ok(())
+    }
+
+    /// Posts to the webhook when a game starts or stops.
+    fn check_game_change(&mut self) -> Result<()> {
+        let game_info = if self.is_ingame() {
+            GameInfo::load()?
+        } else {
+            None
+        };
+        let changed = match (&self.playing, &game_info) {
+            (Some(playing), Some(game_info)) => {
+                playing.path != game_info.path || playing.start_time != game_info.start_time
+            }
+            (playing, game_info) => playing.is_some() != game_info.is_some(),
+        };
+        if !changed {
+            return Ok(());
+        }
+
+        if let Some(playing) = self.playing.take() {
+            let event = WebhookEvent::session_ended(&playing);
+            self.webhook
+                .post(&self.webhook_settings, event, &self.locale);
+        }
+        if let Some(game_info) = game_info.as_ref() {
+            let event = WebhookEvent::now_playing(game_info);
+            self.webhook
+                .post(&self.webhook_settings, event, &self.locale);
+        }
+        self.playing = game_info;
+        Ok(())
+    }
+
+    fn has_fast_forward_hotkeys(&self) -> bool {
+        self.power_settings.fast_forward_hotkeys && self.menu.is_none() && self.is_ingame()
+    }
+
+    async fn toggle_fast_forward(&mut self) -> Result<()> {
+        if let Some(mut game_info) = GameInfo::load()? {
+            game_info.fast_forward = !game_info.fast_forward;
+            game_info.save()?;
+        }
+        self.sync_playback().await
+    }
+
+    /// Applies the fast-forward and rewind state saved in the game info, which may have been
+    /// changed by the in-game menu.
+    async fn sync_playback(&mut self) -> Result<()> {
+        let Some(game_info) = GameInfo::load()? else {
+            return Ok(());
+        };
+        let target = Playback {
+            fast_forward: game_info.fast_forward,
+            rewind: game_info.rewind,
+        };
+
+        for change in self.playback.changes_to(target) {
+            info!("changing playback: {:?}", change);
+            match change {
+                PlaybackChange::ToggleFastForward => {
+                    RetroArchCommand::FastForward.send().await?;
+                }
+                PlaybackChange::HoldRewind => {
+                    self.platform.play_keys(rewind_keys(true))?;
+                    RetroArchCommand::ShowMsg(self.locale.t("rewind-indicator"))
+                        .send()
+                        .await?;
+                }
+                PlaybackChange::ReleaseRewind => {
+                    self.platform.play_keys(rewind_keys(false))?;
+                }
+            }
+        }
+        self.playback = target;
+        self.show_fast_forward_indicator().await
+    }
+
+    /// Loads the macro settings again if they've been saved since they were last loaded, such
+    /// as when the hotkey is changed in the launcher.
+    fn check_macro_settings(&mut self) -> Result<()> {
+        let stamp = MacroSettings::stamp();
+        if stamp != self.macro_settings_stamp {
+            self.macro_settings = MacroSettings::load()?;
+            self.macro_settings_stamp = MacroSettings::stamp();
+        }
+        Ok(())
+    }
+
+    async fn toggle_macro_recording(&mut self) -> Result<()> {
+        let message = if let Some(recorder) = self.macro_recorder.take() {
+            self.check_macro_settings()?;
+            self.macro_settings.steps = recorder.finish();
+            info!(
+                "recorded macro with {} steps",
+                self.macro_settings.steps.len()
+            );
+            self.macro_settings.save()?;
+            self.macro_settings_stamp = MacroSettings::stamp();
+            self.locale.t("macro-saved")
+        } else {
+            info!("recording macro");
+            self.macro_recorder = Some(MacroRecorder::new(Instant::now()));
+            self.locale.t("macro-recording")
+        };
+        RetroArchCommand::ShowMsg(message).send().await?;
+        Ok(())
+    }
+
+    fn play_macro(&mut self) -> Result<()> {
+        self.check_macro_settings()?;
+        if self.macro_settings.steps.is_empty() {
+            return Ok(());
+        }
+        info!(
+            "playing macro with {} steps",
+            self.macro_settings.steps.len()
+        );
+        self.platform.play_keys(self.macro_settings.steps.clone())
+    }
+
+    /// Shows a notification in RetroArch while fast-forward is enabled. Notifications expire,
+    /// so this is sent again every `FAST_FORWARD_INDICATOR_INTERVAL`.
+    async fn show_fast_forward_indicator(&self) -> Result<()> {
+        if self.playback.fast_forward {
+            RetroArchCommand::ShowMsg(self.locale.t("fast-forward-indicator"))
+                .send()
+                .await?;
+        }
+        Ok(())
+    }
+
     fn add_volume(&mut self, add: i32) -> Result<()> {
         info!("adding volume: {}", add);
-        self.state.volume = (self.state.volume + add).clamp(0, 20);
-        self.platform.set_volume(self.state.volume)?;
+        let volume = (self.volume() + add).clamp(0, MAX_VOLUME);
+        if self.headphones {
+            self.state.headphones_volume = volume;
+        } else {
+            self.state.volume = volume;
+        }
+        self.publish_status(DeviceStatus {
+            volume,
+            ..self.status.unwrap_or_default()
+        });
+        self.set_volume()
+    }
+
+    /// Returns the volume of the speaker, or of the headphones while they're plugged in.
+    fn volume(&self) -> i32 {
+        if self.headphones {
+            self.state.headphones_volume
+        } else {
+            self.state.volume
+        }
+    }
+
+    /// Switches to the volume remembered for the headphones or the speaker when headphones are
+    /// plugged in or unplugged.
+    #[cfg(unix)]
+    async fn set_headphones(&mut self, headphones: bool) -> Result<()> {
+        if headphones == self.headphones {
+            return Ok(());
+        }
+        info!("headphones connected: {}", headphones);
+        self.headphones = headphones;
+        self.add_volume(0)?;
+        self.warn(self.locale.t(if headphones {
+            "headphones-connected"
+        } else {
+            "headphones-disconnected"
+        }))
+        .await
+    }
+
+    /// Sets the output level of the volume, with the volume curve and limit applied.
+    fn set_volume(&mut self) -> Result<()> {
+        let level = self.audio_settings.level(self.volume());
+
+        // While audio is sent to a Bluetooth audio output, its volume is set instead
+        let bluetooth = BluetoothSettings::load()?;
+        if bluetooth.enabled
+            && let Some(sink) = bluetooth.sink
+            && audio::bluetooth_output().as_deref() == Some(sink.address.as_str())
+        {
+            tokio::spawn(async move {
+                if let Err(e) = bluetooth::set_volume(&sink, level).await {
+                    warn!("failed to set bluetooth volume: {}", e);
+                }
+            });
+            return Ok(());
+        }
+
+        self.platform.set_volume(level)?;
         Ok(())
     }
 
     fn add_brightness(&mut self, add: i8) -> Result<()> {
         info!("adding brightness: {}", add);
         self.state.brightness = (self.state.brightness as i8 + add).clamp(0, 100) as u8;
-        self.platform.set_brightness(self.state.brightness)?;
+        self.platform.set_brightness(self.brightness())?;
         Ok(())
     }
+
+    /// Returns the brightness to set, which is capped while the battery saver is on.
+    fn brightness(&self) -> u8 {
+        if self.battery_saver {
+            self.state
+                .brightness
+                .min(self.power_settings.battery_saver_brightness)
+        } else {
+            self.state.brightness
+        }
+    }
 }
 
 #[allow(clippy::needless_pass_by_ref_mut)]
@@ -568,3 +1990,60 @@ fn signal(child: &Child, signal: Signal) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playback_changes() {
+        let off = Playback::default();
+        let fast_forward = Playback {
+            fast_forward: true,
+            rewind: false,
+        };
+        let rewind = Playback {
+            fast_forward: false,
+            rewind: true,
+        };
+
+        assert_eq!(off.changes_to(off), vec![]);
+        assert_eq!(fast_forward.changes_to(fast_forward), vec![]);
+        assert_eq!(
+            off.changes_to(fast_forward),
+            vec![PlaybackChange::ToggleFastForward]
+        );
+        assert_eq!(
+            fast_forward.changes_to(off),
+            vec![PlaybackChange::ToggleFastForward]
+        );
+        assert_eq!(off.changes_to(rewind), vec![PlaybackChange::HoldRewind]);
+        assert_eq!(rewind.changes_to(off), vec![PlaybackChange::ReleaseRewind]);
+        assert_eq!(
+            fast_forward.changes_to(rewind),
+            vec![
+                PlaybackChange::ToggleFastForward,
+                PlaybackChange::HoldRewind
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rewind_keys() {
+        let keys = |steps: Vec<MacroStep>| {
+            steps
+                .into_iter()
+                .map(|step| (step.key, step.pressed))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            keys(rewind_keys(true)),
+            vec![(Key::Menu, true), (REWIND_KEY, true)]
+        );
+        assert_eq!(
+            keys(rewind_keys(false)),
+            vec![(REWIND_KEY, false), (Key::Menu, false)]
+        );
+    }
+}
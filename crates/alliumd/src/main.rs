@@ -2,15 +2,16 @@
 #![warn(rust_2018_idioms)]
 
 mod alliumd;
+mod remote;
 
 use anyhow::Result;
-use simple_logger::SimpleLogger;
+use common::logging::{self, LogComponent};
 
 use crate::alliumd::AlliumD;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    SimpleLogger::new().env().init().unwrap();
+    logging::init(LogComponent::Alliumd);
 
     #[cfg(feature = "console")]
     {
@@ -0,0 +1,391 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+use common::background::{self, JobPolicy};
+use common::constants::{
+    ALLIUM_EXTERNAL_GAMES_DIR, ALLIUM_GAMES_DIR, RECENT_GAMES_LIMIT, WEB_REMOTE_PORT,
+};
+use common::database::{Database, Game};
+use common::sync::{self, LibraryMetadata};
+use common::wifi::WiFiSettings;
+use log::{debug, info, warn};
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::Sender;
+
+const INDEX_HTML: &str = include_str!("remote.html");
+
+/// Maximum accepted request body size. The API only takes small JSON bodies.
+const MAX_BODY_SIZE: usize = 4096;
+
+/// Maximum length of the request line and of each header line.
+const MAX_LINE_LENGTH: u64 = 8192;
+
+/// Maximum number of headers in a request.
+const MAX_HEADERS: usize = 64;
+
+/// Commands sent from the web remote to the alliumd event loop.
+#[derive(Debug, Clone)]
+pub enum RemoteCommand {
+    /// Launch the game at the given path, quitting the running game if needed.
+    Launch(PathBuf),
+    /// Take a screenshot of the current screen.
+    Screenshot,
+    /// Set the volume (0-20).
+    SetVolume(i32),
+    /// Set the brightness (0-100).
+    SetBrightness(u8),
+}
+
+struct Request {
+    method: String,
+    path: String,
+    /// Pairing code from the `Authorization: Bearer` header.
+    token: Option<String>,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(value: Value) -> Self {
+        Self {
+            status: "200 OK",
+            content_type: "application/json",
+            body: value.to_string(),
+        }
+    }
+
+    fn error(status: &'static str, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+/// Serves the web remote. Requests are only handled while the web remote is
+/// enabled in the Wi-Fi settings, so toggling it takes effect immediately. API
+/// requests must include the pairing code from the Wi-Fi settings.
+pub async fn serve(commands: Sender<RemoteCommand>) -> Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", WEB_REMOTE_PORT)).await?;
+    info!("web remote listening on port {}", WEB_REMOTE_PORT);
+
+    loop {
+        let (stream, addr) = listener.accept().await?;
+        debug!("web remote connection from {}", addr);
+        let commands = commands.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, addr.ip(), commands).await {
+                warn!("web remote request failed: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    peer: IpAddr,
+    commands: Sender<RemoteCommand>,
+) -> Result<()> {
+    let response = match read_request(&mut stream).await {
+        Ok(request) => match WiFiSettings::load() {
+            Ok(settings) if settings.web_remote => {
+                if is_authorized(&request, &settings) {
+                    handle_request(request, peer, commands).await
+                } else {
+                    Response::error("401 Unauthorized", "invalid pairing code")
+                }
+            }
+            _ => Response::error("403 Forbidden", "web remote is disabled"),
+        },
+        Err(e) => Response::error("400 Bad Request", &e.to_string()),
+    };
+
+    let header = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len(),
+    );
+    stream.write_all(header.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<Request> {
+    let mut reader = BufReader::new(stream);
+
+    let mut line = String::new();
+    read_line(&mut reader, &mut line).await?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        bail!("malformed request line");
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut content_length = 0;
+    let mut token = None;
+    for _ in 0..=MAX_HEADERS {
+        line.clear();
+        if read_line(&mut reader, &mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.trim().parse()?;
+        } else if name.eq_ignore_ascii_case("authorization") {
+            token = value
+                .trim()
+                .strip_prefix("Bearer ")
+                .map(|token| token.trim().to_string());
+        }
+    }
+    if !line.trim().is_empty() {
+        bail!("too many headers");
+    }
+    if content_length > MAX_BODY_SIZE {
+        bail!("request body too large");
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+
+    Ok(Request {
+        method,
+        path,
+        token,
+        body,
+    })
+}
+
+/// Reads a line of at most `MAX_LINE_LENGTH` bytes, so that a client can't send an endless one.
+async fn read_line(reader: &mut BufReader<&mut TcpStream>, line: &mut String) -> Result<usize> {
+    let read = (&mut *reader).take(MAX_LINE_LENGTH).read_line(line).await?;
+    if read as u64 == MAX_LINE_LENGTH && !line.ends_with('\n') {
+        bail!("line too long");
+    }
+    Ok(read)
+}
+
+/// The page itself holds no data, so it's served to anyone. It reads the pairing code from its
+/// own URL and sends it with every API request.
+fn is_authorized(request: &Request, settings: &WiFiSettings) -> bool {
+    let path = request
+        .path
+        .split_once('?')
+        .map_or(&*request.path, |(p, _)| p);
+    path == "/"
+        || request
+            .token
+            .as_deref()
+            .is_some_and(|token| settings.is_web_remote_token(token))
+}
+
+/// Whether the path is a file in one of the games folders, so that the remote can't launch
+/// anything else.
+fn is_game_path(path: &Path) -> bool {
+    let Ok(path) = path.canonicalize() else {
+        return false;
+    };
+    [
+        ALLIUM_GAMES_DIR.as_path(),
+        ALLIUM_EXTERNAL_GAMES_DIR.as_path(),
+    ]
+    .into_iter()
+    .filter_map(|dir| dir.canonicalize().ok())
+    .any(|dir| path.starts_with(dir) && path.is_file())
+}
+
+async fn handle_request(
+    request: Request,
+    peer: IpAddr,
+    commands: Sender<RemoteCommand>,
+) -> Response {
+    let (path, query) = request
+        .path
+        .split_once('?')
+        .unwrap_or((request.path.as_str(), ""));
+    let query = parse_query(query);
+
+    let command = match (request.method.as_str(), path) {
+        ("GET", "/") => {
+            return Response {
+                status: "200 OK",
+                content_type: "text/html; charset=utf-8",
+                body: INDEX_HTML.to_string(),
+            };
+        }
+        ("GET", "/api/games") => return query_games(|db| db.select_all_games()),
+        ("GET", "/api/recents") => {
+            return query_games(|db| db.select_last_played(RECENT_GAMES_LIMIT));
+        }
+        ("GET", "/api/favorites") => {
+            return query_games(|db| db.select_favorites(RECENT_GAMES_LIMIT));
+        }
+        ("GET", "/api/search") => {
+            let query = query.get("q").cloned().unwrap_or_default();
+            return query_games(|db| db.search(&query, RECENT_GAMES_LIMIT));
+        }
+        ("GET", "/api/library") => {
+            return match Database::new().and_then(|db| LibraryMetadata::load(&db)) {
+                Ok(library) => Response::json(json!(library)),
+                Err(e) => Response::error("500 Internal Server Error", &e.to_string()),
+            };
+        }
+        ("POST", "/api/sync") => {
+            // Don't keep the other device waiting until the game is exited
+            if background::policy() == JobPolicy::Pause {
+                info!("web remote: in game, queueing library sync with {}", peer);
+                tokio::spawn(async move {
+                    if let Err(e) = sync::sync_from(peer).await {
+                        warn!("failed to sync library with {}: {}", peer, e);
+                    }
+                });
+                return Response::json(json!({ "ok": true, "queued": true }));
+            }
+            info!("web remote: syncing library with {}", peer);
+            return match sync::sync_from(peer).await {
+                Ok(count) => Response::json(json!({ "ok": true, "count": count })),
+                Err(e) => Response::error("500 Internal Server Error", &e.to_string()),
+            };
+        }
+        ("POST", "/api/launch") => match body_field(&request.body, "path") {
+            Some(Value::String(path)) if is_game_path(Path::new(&path)) => {
+                RemoteCommand::Launch(PathBuf::from(path))
+            }
+            Some(Value::String(_)) => {
+                return Response::error("403 Forbidden", "not a game in the games folder");
+            }
+            _ => return Response::error("400 Bad Request", "missing path"),
+        },
+        ("POST", "/api/screenshot") => RemoteCommand::Screenshot,
+        ("POST", "/api/volume") => match body_field(&request.body, "volume") {
+            Some(Value::Number(n)) if n.as_i64().is_some_and(|n| (0..=20).contains(&n)) => {
+                RemoteCommand::SetVolume(n.as_i64().unwrap() as i32)
+            }
+            _ => return Response::error("400 Bad Request", "volume must be between 0 and 20"),
+        },
+        ("POST", "/api/brightness") => match body_field(&request.body, "brightness") {
+            Some(Value::Number(n)) if n.as_u64().is_some_and(|n| n <= 100) => {
+                RemoteCommand::SetBrightness(n.as_u64().unwrap() as u8)
+            }
+            _ => {
+                return Response::error("400 Bad Request", "brightness must be between 0 and 100");
+            }
+        },
+        _ => return Response::error("404 Not Found", "not found"),
+    };
+
+    info!("web remote command: {:?}", command);
+    match commands.send(command).await {
+        Ok(()) => Response::json(json!({ "ok": true })),
+        Err(e) => Response::error("500 Internal Server Error", &e.to_string()),
+    }
+}
+
+/// Runs a query against a fresh database connection. The connection isn't
+/// shared with the event loop, so it must not be held across an await.
+fn query_games(query: impl FnOnce(&Database) -> Result<Vec<Game>>) -> Response {
+    match Database::new().and_then(|db| query(&db)) {
+        Ok(games) => Response::json(Value::Array(games.iter().map(game_json).collect())),
+        Err(e) => Response::error("500 Internal Server Error", &e.to_string()),
+    }
+}
+
+fn game_json(game: &Game) -> Value {
+    json!({
+        "name": game.name,
+        "path": game.path,
+        "core": game.core,
+        "play_count": game.play_count,
+        "play_time": game.play_time.num_seconds(),
+        "last_played": game.last_played,
+        "rating": game.rating,
+        "release_date": game.release_date.map(|d| d.to_string()),
+        "developer": game.developer,
+        "publisher": game.publisher,
+        "genres": game.genres,
+        "favorite": game.favorite,
+    })
+}
+
+fn body_field(body: &[u8], field: &str) -> Option<Value> {
+    serde_json::from_slice::<Value>(body)
+        .ok()?
+        .get_mut(field)
+        .map(Value::take)
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let input = s.as_bytes();
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        match input[i] {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                // Escapes that aren't two hex digits are kept as they are
+                let decoded = input
+                    .get(i + 1..i + 3)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match decoded {
+                    Some(b) => {
+                        bytes.push(b);
+                        i += 2;
+                    }
+                    None => bytes.push(b'%'),
+                }
+            }
+            b => bytes.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        let query = parse_query("q=super+mario&token=abc%3D&empty=&flag");
+        assert_eq!(query.get("q").map(String::as_str), Some("super mario"));
+        assert_eq!(query.get("token").map(String::as_str), Some("abc="));
+        assert_eq!(query.get("empty").map(String::as_str), Some(""));
+        assert_eq!(query.get("flag"), None);
+        assert!(parse_query("").is_empty());
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a%20b+c"), "a b c");
+        assert_eq!(percent_decode("%C3%A9t%C3%A9"), "été");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%4"), "%4");
+        assert_eq!(percent_decode("%zz"), "%zz");
+        assert_eq!(percent_decode("Roms%2FGB%2Ftetris.gb"), "Roms/GB/tetris.gb");
+    }
+}
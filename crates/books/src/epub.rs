@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use log::warn;
+use quick_xml::Reader;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use zip::ZipArchive;
+
+/// Elements that start a new paragraph.
+const BLOCK_ELEMENTS: [&[u8]; 13] = [
+    b"p",
+    b"div",
+    b"h1",
+    b"h2",
+    b"h3",
+    b"h4",
+    b"h5",
+    b"h6",
+    b"li",
+    b"tr",
+    b"blockquote",
+    b"section",
+    b"hr",
+];
+
+/// Elements whose text isn't part of the book.
+const HIDDEN_ELEMENTS: [&[u8]; 3] = [b"head", b"script", b"style"];
+
+/// Reads the text of an EPUB, with the chapters in reading order and paragraphs separated by
+/// blank lines. Formatting and images are left out.
+pub fn read_text(path: &Path) -> Result<String> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+
+    let container = read_entry(&mut archive, "META-INF/container.xml")?;
+    let rootfile = rootfile(&container).context("missing package document")?;
+    let package = read_entry(&mut archive, &rootfile)?;
+
+    // Chapters are relative to the package document
+    let dir = rootfile.rsplit_once('/').map_or("", |(dir, _)| dir);
+
+    let mut text = String::new();
+    for href in spine(&package) {
+        let href = href.split('#').next().unwrap_or_default();
+        let name = if dir.is_empty() {
+            href.to_string()
+        } else {
+            format!("{dir}/{href}")
+        };
+        match read_entry(&mut archive, &name) {
+            Ok(chapter) => {
+                end_paragraph(&mut text);
+                text.push_str(&html_to_text(&chapter));
+            }
+            Err(e) => warn!("failed to read chapter {}: {}", name, e),
+        }
+    }
+
+    Ok(text)
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<String> {
+    let mut entry = archive.by_name(name)?;
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+/// Returns the path of the package document from `META-INF/container.xml`.
+fn rootfile(container: &str) -> Option<String> {
+    let mut reader = Reader::from_str(container);
+    loop {
+        match reader.read_event().ok()? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"rootfile" => {
+                return attribute(&e, b"full-path");
+            }
+            Event::Eof => return None,
+            _ => {}
+        }
+    }
+}
+
+/// Returns the paths of the chapters in reading order from the package document.
+fn spine(package: &str) -> Vec<String> {
+    let mut reader = Reader::from_str(package);
+    let mut manifest = HashMap::new();
+    let mut spine = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e) | Event::Empty(e)) => match e.local_name().as_ref() {
+                b"item" => {
+                    if let (Some(id), Some(href)) = (attribute(&e, b"id"), attribute(&e, b"href")) {
+                        manifest.insert(id, href);
+                    }
+                }
+                b"itemref" => {
+                    if let Some(idref) = attribute(&e, b"idref") {
+                        spine.push(idref);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                warn!("failed to parse package document: {}", e);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    spine
+        .iter()
+        .filter_map(|id| manifest.get(id).cloned())
+        .collect()
+}
+
+fn attribute(element: &BytesStart<'_>, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attribute| attribute.key.local_name().as_ref() == name)
+        .map(|attribute| String::from_utf8_lossy(&attribute.value).into_owned())
+}
+
+/// Converts a chapter to plain text. Whitespace is collapsed like a browser would.
+fn html_to_text(html: &str) -> String {
+    let mut reader = Reader::from_str(html);
+    // Chapters aren't always well-formed
+    reader.config_mut().check_end_names = false;
+
+    let mut text = String::new();
+    let mut hidden = 0usize;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = e.local_name();
+                if HIDDEN_ELEMENTS.contains(&name.as_ref()) {
+                    hidden += 1;
+                } else if BLOCK_ELEMENTS.contains(&name.as_ref()) {
+                    end_paragraph(&mut text);
+                }
+            }
+            Ok(Event::End(e)) => {
+                let name = e.local_name();
+                if HIDDEN_ELEMENTS.contains(&name.as_ref()) {
+                    hidden = hidden.saturating_sub(1);
+                } else if BLOCK_ELEMENTS.contains(&name.as_ref()) {
+                    end_paragraph(&mut text);
+                }
+            }
+            Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                b"br" => {
+                    text.truncate(text.trim_end_matches(' ').len());
+                    text.push('\n');
+                }
+                b"hr" => end_paragraph(&mut text),
+                _ => {}
+            },
+            Ok(Event::Text(e)) if hidden == 0 => {
+                if let Ok(content) = e.decode() {
+                    push_text(&mut text, &content);
+                }
+            }
+            Ok(Event::CData(e)) if hidden == 0 => {
+                push_text(&mut text, &String::from_utf8_lossy(&e));
+            }
+            Ok(Event::GeneralRef(e)) if hidden == 0 => {
+                if let Ok(Some(c)) = e.resolve_char_ref() {
+                    push_text(&mut text, c.encode_utf8(&mut [0; 4]));
+                } else if let Ok(name) = e.decode() {
+                    match resolve_predefined_entity(&name) {
+                        Some(entity) => push_text(&mut text, entity),
+                        None if name == "nbsp" => push_text(&mut text, " "),
+                        None => {}
+                    }
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                warn!("failed to parse chapter: {}", e);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    text.truncate(text.trim_end().len());
+    text
+}
+
+fn push_text(text: &mut String, content: &str) {
+    for c in content.chars() {
+        if c.is_whitespace() {
+            if !text.is_empty() && !text.ends_with([' ', '\n']) {
+                text.push(' ');
+            }
+        } else {
+            text.push(c);
+        }
+    }
+}
+
+/// Ends the current paragraph with a blank line, unless there isn't one.
+fn end_paragraph(text: &mut String) {
+    text.truncate(text.trim_end().len());
+    if !text.is_empty() {
+        text.push_str("\n\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rootfile() {
+        let container = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>"#;
+        assert_eq!(rootfile(container), Some("OEBPS/content.opf".to_string()));
+    }
+
+    #[test]
+    fn test_spine() {
+        let package = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <manifest>
+    <item id="cover" href="cover.xhtml" media-type="application/xhtml+xml"/>
+    <item id="ch2" href="text/chapter2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="ch1" href="text/chapter1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="css" href="style.css" media-type="text/css"/>
+  </manifest>
+  <spine>
+    <itemref idref="cover"/>
+    <itemref idref="ch1"/>
+    <itemref idref="ch2"/>
+    <itemref idref="missing"/>
+  </spine>
+</package>"#;
+        assert_eq!(
+            spine(package),
+            vec!["cover.xhtml", "text/chapter1.xhtml", "text/chapter2.xhtml"]
+        );
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        let html = r#"<html xmlns="http://www.w3.org/1999/xhtml">
+<head><title>Chapter 1</title><style>p { margin: 0; }</style></head>
+<body>
+  <h1>Chapter   1</h1>
+  <p>It was a
+     dark &amp; <em>stormy</em><br/>night.</p>
+  <p>The&#160;end</p>
+</body>
+</html>"#;
+        assert_eq!(
+            html_to_text(html),
+            "Chapter 1\n\nIt was a dark & stormy\nnight.\n\nThe end"
+        );
+    }
+}
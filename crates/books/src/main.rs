@@ -0,0 +1,20 @@
+mod books;
+mod epub;
+mod view;
+
+use anyhow::Result;
+
+use common::platform::{DefaultPlatform, Platform};
+use simple_logger::SimpleLogger;
+
+use crate::books::BooksApp;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    SimpleLogger::new().env().init().unwrap();
+
+    let platform = DefaultPlatform::new()?;
+    let mut app = BooksApp::new(platform)?;
+    app.run_event_loop().await?;
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::display::Display;
+use common::geom::{Point, Rect};
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{Keyboard, TextReader, View};
+use log::error;
+use tokio::sync::mpsc::Sender;
+
+use crate::epub;
+
+/// Font sizes that the book can be read at.
+const MIN_FONT_SIZE: u32 = 12;
+const MAX_FONT_SIZE: u32 = 48;
+const FONT_SIZE_STEP: u32 = 2;
+
+/// A book being read. Pages are turned with L and R, and the font size is changed with Left and
+/// Right, which sets the guide font size in the stylesheet.
+///
+/// Bubbles `Command::CloseView` when closed.
+#[derive(Debug)]
+pub struct Book {
+    rect: Rect,
+    res: Resources,
+    reader: TextReader,
+    keyboard: Option<Keyboard>,
+    dirty: bool,
+}
+
+impl Book {
+    pub fn open(rect: Rect, res: Resources, path: PathBuf) -> Result<Self> {
+        let is_epub = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("epub"));
+        let text = if is_epub {
+            epub::read_text(&path)?
+        } else {
+            fs::read_to_string(&path)?
+        };
+
+        Ok(Self {
+            rect,
+            res: res.clone(),
+            reader: TextReader::with_text(rect, res, path, text),
+            keyboard: None,
+            dirty: true,
+        })
+    }
+
+    fn set_font_size(&mut self, size: u32) {
+        let mut styles = self.res.get::<Stylesheet>().clone();
+        let size = size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+        if styles.guide_font.size == size {
+            return;
+        }
+        styles.guide_font.size = size;
+        if let Err(e) = styles.save() {
+            error!("failed to save stylesheet: {}", e);
+        }
+        self.res.insert(styles);
+        self.dirty = true;
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Book {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        // Clears what was drawn at the previous font size
+        if self.dirty {
+            display.load(self.rect)?;
+            self.reader.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        if self.reader.should_draw() {
+            drawn |= self.reader.draw(display, styles)?;
+            if let Some(keyboard) = self.keyboard.as_mut() {
+                keyboard.set_should_draw();
+            }
+        }
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            drawn |= keyboard.should_draw() && keyboard.draw(display, styles)?;
+        }
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.reader.should_draw()
+            || self.keyboard.as_ref().is_some_and(View::should_draw)
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            keyboard
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?;
+            while let Some(command) = bubble.pop_front() {
+                match command {
+                    Command::ValueChanged(_, value) => {
+                        self.reader.search(value.as_string().unwrap());
+                    }
+                    Command::CloseView => {
+                        self.keyboard = None;
+                        self.reader.set_should_draw();
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::L) | KeyEvent::Autorepeat(Key::L) => {
+                self.reader.turn_page(false);
+                self.reader.save_cursor();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::R) | KeyEvent::Autorepeat(Key::R) => {
+                self.reader.turn_page(true);
+                self.reader.save_cursor();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Left) | KeyEvent::Autorepeat(Key::Left) => {
+                let size = self.res.get::<Stylesheet>().guide_font.size;
+                self.set_font_size(size.saturating_sub(FONT_SIZE_STEP));
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Right) | KeyEvent::Autorepeat(Key::Right) => {
+                let size = self.res.get::<Stylesheet>().guide_font.size;
+                self.set_font_size(size + FONT_SIZE_STEP);
+                Ok(true)
+            }
+            _ => {
+                let handled = self
+                    .reader
+                    .handle_key_event(event, commands, bubble)
+                    .await?;
+                self.keyboard = self.reader.take_keyboard();
+                Ok(handled)
+            }
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.reader]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.reader]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::{ALLIUM_BOOKS_DIR, SELECTION_MARGIN};
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, NullView, Row, SettingsList, View};
+use log::warn;
+use tokio::sync::mpsc::Sender;
+
+use crate::view::Book;
+
+/// Extensions of the files listed as books.
+const BOOK_EXTENSIONS: [&str; 2] = ["txt", "epub"];
+
+/// Lists the books in the Books folder, and opens them where they were left off.
+#[derive(Debug)]
+pub struct Books {
+    rect: Rect,
+    res: Resources,
+    books: Vec<PathBuf>,
+    list: SettingsList,
+    status: Label<String>,
+    button_hints: Row<ButtonHint<String>>,
+    book: Option<Book>,
+    dirty: bool,
+}
+
+impl Books {
+    pub fn new(rect: Rect, res: Resources) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let books = find_books(&ALLIUM_BOOKS_DIR);
+        let labels = books
+            .iter()
+            .map(|book| {
+                book.file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        let values = books
+            .iter()
+            .map(|_| Box::new(NullView) as Box<dyn View>)
+            .collect();
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y,
+                w - 24,
+                h - 8 - styles.ui_font.size - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            labels,
+            values,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let status = Label::new(
+            Point::new(
+                x + 12,
+                y + h as i32
+                    - 8
+                    - ButtonIcon::diameter(&styles) as i32
+                    - 8
+                    - styles.ui_font.size as i32,
+            ),
+            if books.is_empty() {
+                locale.t("books-empty")
+            } else {
+                String::new()
+            },
+            Alignment::Left,
+            Some(w - 24),
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("books-button-read"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+
+        Ok(Self {
+            rect,
+            res,
+            books,
+            list,
+            status,
+            button_hints,
+            book: None,
+            dirty: true,
+        })
+    }
+
+    fn open(&mut self) {
+        let Some(path) = self.books.get(self.list.selected()).cloned() else {
+            return;
+        };
+
+        match Book::open(self.rect, self.res.clone(), path.clone()) {
+            Ok(book) => {
+                self.book = Some(book);
+                self.status.set_text(String::new());
+            }
+            Err(e) => {
+                warn!("failed to open {}: {}", path.display(), e);
+                let mut map = HashMap::new();
+                map.insert(
+                    "name".into(),
+                    path.file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string()
+                        .into(),
+                );
+                let text = self.res.get::<Locale>().ta("books-error", &map);
+                self.status.set_text(text);
+            }
+        }
+    }
+}
+
+/// Returns the books in the folder and its subfolders, sorted by path.
+fn find_books(dir: &Path) -> Vec<PathBuf> {
+    let mut books = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(read_dir) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in read_dir.filter_map(std::result::Result::ok) {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if is_book(&path) {
+                books.push(path);
+            }
+        }
+    }
+    books.sort_unstable_by_key(|path| path.to_string_lossy().to_lowercase());
+    books
+}
+
+fn is_book(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            BOOK_EXTENSIONS
+                .iter()
+                .any(|book| ext.eq_ignore_ascii_case(book))
+        })
+}
+
+#[async_trait(?Send)]
+impl View for Books {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if let Some(book) = self.book.as_mut() {
+            return Ok(book.should_draw() && book.draw(display, styles)?);
+        }
+
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.list.set_should_draw();
+            self.status.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        if self.status.should_draw() {
+            display.load(Rect::new(
+                self.rect.x,
+                self.status.bounding_box(styles).y,
+                self.rect.w,
+                styles.ui_font.size,
+            ))?;
+            drawn |= self.status.draw(display, styles)?;
+        }
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        if let Some(book) = self.book.as_ref() {
+            return book.should_draw();
+        }
+        self.dirty
+            || self.list.should_draw()
+            || self.status.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        if let Some(book) = self.book.as_mut() {
+            book.set_should_draw();
+        }
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(book) = self.book.as_mut() {
+            let handled = book.handle_key_event(event, commands, bubble).await?;
+            if let Some(index) = bubble
+                .iter()
+                .position(|command| matches!(command, Command::CloseView))
+            {
+                bubble.remove(index);
+                self.book = None;
+                self.dirty = true;
+            }
+            return Ok(handled);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                self.open();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                commands.send(Command::Exit).await?;
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.status, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.status, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
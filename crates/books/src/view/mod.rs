@@ -0,0 +1,7 @@
+mod app;
+mod book;
+mod books;
+
+pub use app::App;
+pub use book::Book;
+pub use books::Books;
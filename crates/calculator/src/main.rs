@@ -0,0 +1,21 @@
+mod calculator;
+mod expr;
+mod units;
+mod view;
+
+use anyhow::Result;
+
+use common::platform::{DefaultPlatform, Platform};
+use simple_logger::SimpleLogger;
+
+use crate::calculator::CalculatorApp;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    SimpleLogger::new().env().init().unwrap();
+
+    let platform = DefaultPlatform::new()?;
+    let mut app = CalculatorApp::new(platform)?;
+    app.run_event_loop().await?;
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use common::locale::Locale;
+use strum::{EnumIter, FromRepr};
+
+/// A kind of quantity that units can be converted between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, FromRepr)]
+pub enum Category {
+    Length,
+    Mass,
+    Temperature,
+    Volume,
+    Speed,
+    Data,
+}
+
+/// A unit of measure. Values are converted through the category's base unit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unit {
+    pub name: &'static str,
+    /// Multiplier from this unit to the base unit.
+    scale: f64,
+    /// Offset added after scaling to the base unit.
+    offset: f64,
+}
+
+const fn unit(name: &'static str, scale: f64) -> Unit {
+    Unit {
+        name,
+        scale,
+        offset: 0.0,
+    }
+}
+
+const LENGTH: &[Unit] = &[
+    unit("mm", 0.001),
+    unit("cm", 0.01),
+    unit("m", 1.0),
+    unit("km", 1000.0),
+    unit("in", 0.0254),
+    unit("ft", 0.3048),
+    unit("yd", 0.9144),
+    unit("mi", 1609.344),
+];
+
+const MASS: &[Unit] = &[
+    unit("g", 0.001),
+    unit("kg", 1.0),
+    unit("t", 1000.0),
+    unit("oz", 0.028_349_523_125),
+    unit("lb", 0.453_592_37),
+    unit("st", 6.350_293_18),
+];
+
+const TEMPERATURE: &[Unit] = &[
+    unit("°C", 1.0),
+    Unit {
+        name: "°F",
+        scale: 5.0 / 9.0,
+        offset: -32.0 * 5.0 / 9.0,
+    },
+    Unit {
+        name: "K",
+        scale: 1.0,
+        offset: -273.15,
+    },
+];
+
+const VOLUME: &[Unit] = &[
+    unit("ml", 0.001),
+    unit("l", 1.0),
+    unit("tsp", 0.004_928_921_593_75),
+    unit("tbsp", 0.014_786_764_781_25),
+    unit("cup", 0.236_588_236_5),
+    unit("fl oz", 0.029_573_529_562_5),
+    unit("gal", 3.785_411_784),
+];
+
+const SPEED: &[Unit] = &[
+    unit("m/s", 1.0),
+    unit("km/h", 1.0 / 3.6),
+    unit("mph", 0.447_04),
+    unit("kn", 1852.0 / 3600.0),
+];
+
+const DATA: &[Unit] = &[
+    unit("B", 1.0),
+    unit("KB", 1024.0),
+    unit("MB", 1024.0 * 1024.0),
+    unit("GB", 1024.0 * 1024.0 * 1024.0),
+    unit("TB", 1024.0 * 1024.0 * 1024.0 * 1024.0),
+];
+
+impl Category {
+    pub fn name(self, locale: &Locale) -> String {
+        locale.t(match self {
+            Category::Length => "calculator-length",
+            Category::Mass => "calculator-mass",
+            Category::Temperature => "calculator-temperature",
+            Category::Volume => "calculator-volume",
+            Category::Speed => "calculator-speed",
+            Category::Data => "calculator-data",
+        })
+    }
+
+    pub fn units(self) -> &'static [Unit] {
+        match self {
+            Category::Length => LENGTH,
+            Category::Mass => MASS,
+            Category::Temperature => TEMPERATURE,
+            Category::Volume => VOLUME,
+            Category::Speed => SPEED,
+            Category::Data => DATA,
+        }
+    }
+}
+
+/// Converts a value between two units of the same category.
+pub fn convert(value: f64, from: &Unit, to: &Unit) -> f64 {
+    let base = value * from.scale + from.offset;
+    (base - to.offset) / to.scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(category: Category, name: &str) -> Unit {
+        *category.units().iter().find(|u| u.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_convert() {
+        let km = find(Category::Length, "km");
+        let mi = find(Category::Length, "mi");
+        assert!((convert(1.0, &mi, &km) - 1.609_344).abs() < 1e-9);
+
+        let c = find(Category::Temperature, "°C");
+        let f = find(Category::Temperature, "°F");
+        let k = find(Category::Temperature, "K");
+        assert!((convert(100.0, &c, &f) - 212.0).abs() < 1e-9);
+        assert!((convert(32.0, &f, &c)).abs() < 1e-9);
+        assert!((convert(0.0, &k, &c) + 273.15).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,324 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::{Command, Value};
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Keypad, Label, Row, View};
+use tokio::sync::mpsc::Sender;
+
+use crate::expr::{evaluate, format_number};
+use crate::view::Converter;
+
+const KEYS: [&str; 20] = [
+    "7", "8", "9", "/", "C", //
+    "4", "5", "6", "*", "(", //
+    "1", "2", "3", "-", ")", //
+    "0", ".", "=", "+", "^",
+];
+const COLUMNS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Calculator,
+    Converter,
+}
+
+#[derive(Debug)]
+pub struct Calculator {
+    rect: Rect,
+    res: Resources,
+    mode: Mode,
+    expression: String,
+    expression_label: Label<String>,
+    result_label: Label<String>,
+    keypad: Keypad,
+    converter: Converter,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl Calculator {
+    pub fn new(rect: Rect, res: Resources) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let expression_label = Label::new(
+            Point::new(x + w as i32 - 12, y + 8),
+            "0".to_string(),
+            Alignment::Right,
+            Some(w - 24),
+        );
+        let result_label = Label::new(
+            Point::new(x + w as i32 - 12, y + 8 + styles.ui_font.size as i32 + 8),
+            String::new(),
+            Alignment::Right,
+            Some(w - 24),
+        );
+
+        let content_y = y + 8 + 2 * (styles.ui_font.size as i32 + 8);
+        let content = Rect::new(
+            x + 12,
+            content_y,
+            w - 24,
+            (y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8 - content_y) as u32,
+        );
+        let keypad = Keypad::new(
+            content,
+            KEYS.iter().map(ToString::to_string).collect(),
+            COLUMNS,
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::R,
+                    locale.t("keyboard-button-backspace"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::Y,
+                    locale.t("calculator-converter"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+
+        let converter = Converter::new(content, res.clone(), 0.0);
+
+        Ok(Self {
+            rect,
+            res,
+            mode: Mode::Calculator,
+            expression: String::new(),
+            expression_label,
+            result_label,
+            keypad,
+            converter,
+            button_hints,
+            dirty: true,
+        })
+    }
+
+    fn press(&mut self, key: &str) {
+        match key {
+            "C" => self.expression.clear(),
+            "=" => match evaluate(&self.expression) {
+                Ok(value) => self.expression = format_number(value),
+                Err(_) => {
+                    let error = self.res.get::<Locale>().t("calculator-error");
+                    self.result_label.set_text(error);
+                    return;
+                }
+            },
+            key => self.expression.push_str(key),
+        }
+        self.update();
+    }
+
+    fn backspace(&mut self) {
+        self.expression.pop();
+        self.update();
+    }
+
+    fn update(&mut self) {
+        if self.expression.is_empty() {
+            self.expression_label.set_text("0".to_string());
+        } else {
+            self.expression_label.set_text(self.expression.clone());
+        }
+
+        // Show the result as the expression is typed, if it's complete
+        match evaluate(&self.expression) {
+            Ok(value) => {
+                self.converter.set_value(value);
+                self.result_label.set_text(format!("= {}", format_number(value)));
+            }
+            Err(_) => {
+                self.result_label.set_text(String::new());
+            }
+        }
+    }
+
+    fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            Mode::Calculator => Mode::Converter,
+            Mode::Converter => Mode::Calculator,
+        };
+        let hint = match self.mode {
+            Mode::Calculator => "calculator-converter",
+            Mode::Converter => "calculator-calculator",
+        };
+        let hint = self.res.get::<Locale>().t(hint);
+        self.button_hints.get_mut(2).unwrap().set_text(hint);
+        self.dirty = true;
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Calculator {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.expression_label.set_should_draw();
+            self.result_label.set_should_draw();
+            self.keypad.set_should_draw();
+            self.converter.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        for label in [&mut self.expression_label, &mut self.result_label] {
+            if label.should_draw() {
+                display.load(Rect::new(
+                    self.rect.x,
+                    label.bounding_box(styles).y,
+                    self.rect.w,
+                    styles.ui_font.size,
+                ))?;
+                drawn |= label.draw(display, styles)?;
+            }
+        }
+
+        match self.mode {
+            Mode::Calculator => {
+                drawn |= self.keypad.should_draw() && self.keypad.draw(display, styles)?;
+            }
+            Mode::Converter => {
+                drawn |= self.converter.should_draw() && self.converter.draw(display, styles)?;
+            }
+        }
+
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.expression_label.should_draw()
+            || self.result_label.should_draw()
+            || match self.mode {
+                Mode::Calculator => self.keypad.should_draw(),
+                Mode::Converter => self.converter.should_draw(),
+            }
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self.mode == Mode::Converter
+            && self
+                .converter
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+        {
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::Y) => {
+                self.toggle_mode();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                commands.send(Command::Exit).await?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::L | Key::R) | KeyEvent::Autorepeat(Key::L | Key::R) => {
+                self.backspace();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Start) => {
+                self.press("=");
+                Ok(true)
+            }
+            _ if self.mode == Mode::Calculator => {
+                let handled = self.keypad.handle_key_event(event, commands, bubble).await?;
+                let mut pressed = Vec::new();
+                bubble.retain(|cmd| match cmd {
+                    Command::ValueChanged(_, Value::String(key)) => {
+                        pressed.push(key.clone());
+                        false
+                    }
+                    _ => true,
+                });
+                for key in pressed {
+                    self.press(&key);
+                }
+                Ok(handled)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![
+            &self.expression_label,
+            &self.result_label,
+            &self.keypad,
+            &self.converter,
+            &self.button_hints,
+        ]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![
+            &mut self.expression_label,
+            &mut self.result_label,
+            &mut self.keypad,
+            &mut self.converter,
+            &mut self.button_hints,
+        ]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::{Command, Value};
+use common::constants::SELECTION_MARGIN;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{Label, Select, SettingsList, View};
+use strum::IntoEnumIterator;
+use tokio::sync::mpsc::Sender;
+
+use crate::expr::format_number;
+use crate::units::{Category, convert};
+
+const CATEGORY: usize = 0;
+const FROM: usize = 1;
+const TO: usize = 2;
+const VALUE: usize = 3;
+const RESULT: usize = 4;
+
+/// Converts the current calculator value between units.
+#[derive(Debug)]
+pub struct Converter {
+    rect: Rect,
+    value: f64,
+    category: Category,
+    from: usize,
+    to: usize,
+    list: SettingsList,
+}
+
+impl Converter {
+    pub fn new(rect: Rect, res: Resources, value: f64) -> Self {
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let list = SettingsList::new(
+            rect,
+            vec![
+                locale.t("calculator-category"),
+                locale.t("calculator-from"),
+                locale.t("calculator-to"),
+                locale.t("calculator-value"),
+                locale.t("calculator-result"),
+            ],
+            vec![
+                Box::new(Select::new(
+                    Point::zero(),
+                    0,
+                    Category::iter().map(|c| c.name(&locale)).collect(),
+                    Alignment::Right,
+                )),
+                Box::new(Label::new(Point::zero(), String::new(), Alignment::Right, None)),
+                Box::new(Label::new(Point::zero(), String::new(), Alignment::Right, None)),
+                Box::new(Label::new(Point::zero(), String::new(), Alignment::Right, None)),
+                Box::new(Label::new(Point::zero(), String::new(), Alignment::Right, None)),
+            ],
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        let mut this = Self {
+            rect,
+            value,
+            category: Category::Length,
+            from: 0,
+            to: 1,
+            list,
+        };
+        this.set_category(Category::Length);
+        this
+    }
+
+    pub fn set_value(&mut self, value: f64) {
+        self.value = value;
+        self.update_result();
+    }
+
+    fn set_category(&mut self, category: Category) {
+        self.category = category;
+        self.from = 0;
+        self.to = 1;
+
+        let names = category
+            .units()
+            .iter()
+            .map(|u| u.name.to_string())
+            .collect::<Vec<_>>();
+        self.list.set_right(
+            FROM,
+            Box::new(Select::new(Point::zero(), self.from, names.clone(), Alignment::Right)),
+        );
+        self.list.set_right(
+            TO,
+            Box::new(Select::new(Point::zero(), self.to, names, Alignment::Right)),
+        );
+        self.update_result();
+    }
+
+    fn update_result(&mut self) {
+        let units = self.category.units();
+        let (from, to) = (&units[self.from], &units[self.to]);
+        let result = convert(self.value, from, to);
+        self.list.set_right(
+            VALUE,
+            Box::new(Label::new(
+                Point::zero(),
+                format!("{} {}", format_number(self.value), from.name),
+                Alignment::Right,
+                None,
+            )),
+        );
+        self.list.set_right(
+            RESULT,
+            Box::new(Label::new(
+                Point::zero(),
+                format!("{} {}", format_number(result), to.name),
+                Alignment::Right,
+                None,
+            )),
+        );
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Converter {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        self.list.draw(display, styles)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if !self.list.handle_key_event(event, commands, bubble).await? {
+            return Ok(false);
+        }
+
+        let mut changed = Vec::new();
+        bubble.retain(|cmd| match cmd {
+            Command::ValueChanged(i, Value::Int(value)) => {
+                changed.push((*i, *value as usize));
+                false
+            }
+            _ => true,
+        });
+        for (i, value) in changed {
+            match i {
+                CATEGORY => {
+                    if let Some(category) = Category::from_repr(value) {
+                        self.set_category(category);
+                    }
+                }
+                FROM => {
+                    self.from = value;
+                    self.update_result();
+                }
+                TO => {
+                    self.to = value;
+                    self.update_result();
+                }
+                _ => {}
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
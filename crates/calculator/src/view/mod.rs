@@ -0,0 +1,7 @@
+mod app;
+mod calculator;
+mod converter;
+
+pub use app::App;
+pub use calculator::Calculator;
+pub use converter::Converter;
@@ -0,0 +1,19 @@
+mod clock;
+mod view;
+
+use anyhow::Result;
+
+use common::platform::{DefaultPlatform, Platform};
+use simple_logger::SimpleLogger;
+
+use crate::clock::ClockApp;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    SimpleLogger::new().env().init().unwrap();
+
+    let platform = DefaultPlatform::new()?;
+    let mut app = ClockApp::new(platform)?;
+    app.run_event_loop().await?;
+    Ok(())
+}
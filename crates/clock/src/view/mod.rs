@@ -0,0 +1,5 @@
+mod app;
+mod timers;
+
+pub use app::App;
+pub use timers::Timers;
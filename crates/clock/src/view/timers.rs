@@ -0,0 +1,382 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{NaiveTime, Timelike, Utc};
+use common::clock::{ClockSettings, OverlayCorner};
+use common::command::Command;
+use common::constants::SELECTION_MARGIN;
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{
+    ButtonHint, ButtonIcon, Label, Number, Percentage, Row, Select, SettingsList, Toggle, View,
+};
+use embedded_graphics::prelude::OriginDimensions;
+use log::warn;
+use tokio::sync::mpsc::Sender;
+
+const STOPWATCH: usize = 3;
+const TIMER: usize = 5;
+
+/// Alarm, stopwatch and timer. The alarm and timer are saved to the clock
+/// settings and handled by alliumd, so they keep running after the app exits.
+#[derive(Debug)]
+pub struct Timers {
+    rect: Rect,
+    res: Resources,
+    settings: ClockSettings,
+    stopwatch: Duration,
+    stopwatch_started: Option<Instant>,
+    stopwatch_text: String,
+    timer_text: String,
+    list: SettingsList,
+    button_hints: Row<ButtonHint<String>>,
+}
+
+impl Timers {
+    pub fn new(rect: Rect, res: Resources) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let settings = ClockSettings::load()?;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let list = SettingsList::new(
+            Rect::new(x + 12, y, w - 24, h - 8 - ButtonIcon::diameter(&styles)),
+            vec![
+                locale.t("clock-alarm"),
+                locale.t("clock-alarm-hour"),
+                locale.t("clock-alarm-minute"),
+                locale.t("clock-stopwatch"),
+                locale.t("clock-timer-minutes"),
+                locale.t("clock-timer"),
+                locale.t("clock-timer-suspend"),
+                locale.t("clock-overlay"),
+                locale.t("clock-overlay-corner"),
+                locale.t("clock-overlay-opacity"),
+                locale.t("clock-overlay-auto-hide"),
+            ],
+            vec![
+                Box::new(Toggle::new(Point::zero(), settings.alarm_enabled, Alignment::Right)),
+                Box::new(Number::new(
+                    Point::zero(),
+                    settings.alarm.hour() as i32,
+                    0,
+                    23,
+                    1,
+                    |x: &i32| format!("{x:02}"),
+                    Alignment::Right,
+                )),
+                Box::new(Number::new(
+                    Point::zero(),
+                    settings.alarm.minute() as i32,
+                    0,
+                    59,
+                    1,
+                    |x: &i32| format!("{x:02}"),
+                    Alignment::Right,
+                )),
+                Box::new(Label::new(
+                    Point::zero(),
+                    format_duration(Duration::ZERO),
+                    Alignment::Right,
+                    None,
+                )),
+                Box::new(Number::new(
+                    Point::zero(),
+                    settings.timer_minutes,
+                    1,
+                    180,
+                    1,
+                    |x: &i32| x.to_string(),
+                    Alignment::Right,
+                )),
+                Box::new(Label::new(
+                    Point::zero(),
+                    locale.t("clock-start"),
+                    Alignment::Right,
+                    None,
+                )),
+                Box::new(Toggle::new(Point::zero(), settings.timer_suspend, Alignment::Right)),
+                Box::new(Toggle::new(Point::zero(), settings.overlay_enabled, Alignment::Right)),
+                Box::new(Select::new(
+                    Point::zero(),
+                    settings.overlay_corner as usize,
+                    vec![
+                        locale.t("clock-overlay-corner-top-left"),
+                        locale.t("clock-overlay-corner-top-right"),
+                        locale.t("clock-overlay-corner-bottom-left"),
+                        locale.t("clock-overlay-corner-bottom-right"),
+                    ],
+                    Alignment::Right,
+                )),
+                Box::new(Percentage::new(
+                    Point::zero(),
+                    settings.overlay_opacity,
+                    0,
+                    100,
+                    Alignment::Right,
+                )),
+                Box::new(Toggle::new(Point::zero(), settings.overlay_auto_hide, Alignment::Right)),
+            ],
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::Y,
+                    locale.t("clock-reset"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+
+        let mut this = Self {
+            rect,
+            res,
+            settings,
+            stopwatch: Duration::ZERO,
+            stopwatch_started: None,
+            stopwatch_text: String::new(),
+            timer_text: String::new(),
+            list,
+            button_hints,
+        };
+        this.update_labels();
+
+        Ok(this)
+    }
+
+    fn stopwatch_elapsed(&self) -> Duration {
+        self.stopwatch + self.stopwatch_started.map_or(Duration::ZERO, |t| t.elapsed())
+    }
+
+    fn toggle_stopwatch(&mut self) {
+        match self.stopwatch_started.take() {
+            Some(started) => self.stopwatch += started.elapsed(),
+            None => self.stopwatch_started = Some(Instant::now()),
+        }
+    }
+
+    fn toggle_timer(&mut self) -> Result<()> {
+        let duration = chrono::Duration::minutes(self.settings.timer_minutes as i64);
+        self.settings.timer = match self.settings.timer {
+            Some(_) => None,
+            None => Some(Utc::now() + duration),
+        };
+        self.settings.save()
+    }
+
+    /// Updates the stopwatch and timer labels, if their text has changed.
+    fn update_labels(&mut self) {
+        let stopwatch_text = format_duration(self.stopwatch_elapsed());
+        if stopwatch_text != self.stopwatch_text {
+            self.stopwatch_text = stopwatch_text;
+            self.list.set_right(
+                STOPWATCH,
+                Box::new(Label::new(
+                    Point::zero(),
+                    self.stopwatch_text.clone(),
+                    Alignment::Right,
+                    None,
+                )),
+            );
+        }
+
+        // The timer is cleared by alliumd when it finishes
+        let remaining = self
+            .settings
+            .timer
+            .and_then(|timer| (timer - Utc::now()).to_std().ok());
+        if remaining.is_none() {
+            self.settings.timer = None;
+        }
+        let timer_text = match remaining {
+            Some(remaining) => format_duration(remaining),
+            None => self.res.get::<Locale>().t("clock-start"),
+        };
+        if timer_text != self.timer_text {
+            self.timer_text = timer_text;
+            self.list.set_right(
+                TIMER,
+                Box::new(Label::new(
+                    Point::zero(),
+                    self.timer_text.clone(),
+                    Alignment::Right,
+                    None,
+                )),
+            );
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Timers {
+    fn update(&mut self, dt: Duration) {
+        self.update_labels();
+        self.children_mut().iter_mut().for_each(|c| c.update(dt));
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+
+        if self.button_hints.should_draw() {
+            display.load(Rect::new(
+                0,
+                display.size().height as i32 - 48,
+                display.size().width,
+                48,
+            ))?;
+            self.button_hints.set_should_draw();
+            if self.button_hints.draw(display, styles)? {
+                drawn = true;
+            }
+        }
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.list.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match (event, self.list.selected()) {
+            (KeyEvent::Pressed(Key::A), STOPWATCH) => {
+                self.toggle_stopwatch();
+                return Ok(true);
+            }
+            (KeyEvent::Pressed(Key::A), TIMER) => {
+                self.toggle_timer()?;
+                self.update_labels();
+                return Ok(true);
+            }
+            (KeyEvent::Pressed(Key::Y), _) => {
+                self.stopwatch = Duration::ZERO;
+                self.stopwatch_started = self.stopwatch_started.map(|_| Instant::now());
+                self.update_labels();
+                return Ok(true);
+            }
+            _ => {}
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            let mut changed = false;
+            while let Some(command) = bubble.pop_front() {
+                if let Command::ValueChanged(i, val) = command {
+                    changed = true;
+                    match i {
+                        0 => self.settings.alarm_enabled = val.as_bool().unwrap(),
+                        1 => {
+                            let hour = val.as_int().unwrap() as u32;
+                            self.settings.alarm = NaiveTime::from_hms_opt(
+                                hour,
+                                self.settings.alarm.minute(),
+                                0,
+                            )
+                            .unwrap_or(self.settings.alarm);
+                        }
+                        2 => {
+                            let minute = val.as_int().unwrap() as u32;
+                            self.settings.alarm = NaiveTime::from_hms_opt(
+                                self.settings.alarm.hour(),
+                                minute,
+                                0,
+                            )
+                            .unwrap_or(self.settings.alarm);
+                        }
+                        4 => self.settings.timer_minutes = val.as_int().unwrap(),
+                        6 => self.settings.timer_suspend = val.as_bool().unwrap(),
+                        7 => self.settings.overlay_enabled = val.as_bool().unwrap(),
+                        8 => {
+                            self.settings.overlay_corner =
+                                OverlayCorner::from_repr(val.as_int().unwrap() as usize)
+                                    .unwrap_or_default()
+                        }
+                        9 => self.settings.overlay_opacity = val.as_int().unwrap(),
+                        10 => self.settings.overlay_auto_hide = val.as_bool().unwrap(),
+                        _ => unreachable!("Invalid index"),
+                    }
+                }
+            }
+            if changed && let Err(e) = self.settings.save() {
+                warn!("failed to save clock settings: {}", e);
+            }
+            return Ok(true);
+        }
+
+        // B cancels editing a number before exiting
+        if let KeyEvent::Pressed(Key::B) = event {
+            commands.send(Command::Exit).await?;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
@@ -0,0 +1,273 @@
+use std::fs::{self, File};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::ALLIUM_ANALOG_SETTINGS;
+use crate::geom::Point;
+use crate::platform::{Key, KeyEvent};
+
+/// Settings for navigating menus with the analog stick.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalogSettings {
+    pub enabled: bool,
+    /// Percentage of the stick's range around the center that is ignored.
+    pub deadzone: i32,
+    /// Time between repeated moves while the stick is held, in milliseconds.
+    pub repeat_interval_ms: i32,
+    /// Range of the stick measured in the calibration screen. If not calibrated, the range
+    /// reported by the device is used.
+    #[serde(default)]
+    pub calibration: Option<StickCalibration>,
+}
+
+impl Default for AnalogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            deadzone: 30,
+            repeat_interval_ms: 150,
+            calibration: None,
+        }
+    }
+}
+
+impl AnalogSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_ANALOG_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_ANALOG_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read analog file, removing");
+            fs::remove_file(ALLIUM_ANALOG_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_ANALOG_SETTINGS.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+
+    pub fn repeat_interval(&self) -> Duration {
+        Duration::from_millis(self.repeat_interval_ms.max(1) as u64)
+    }
+}
+
+/// Fraction of the deadzone that the stick has to return within to release a held direction.
+/// Jitter around the edge of the deadzone would otherwise release and press the key again,
+/// restarting the repeat.
+const RELEASE_DEADZONE: f32 = 0.75;
+
+/// Raw values of an axis at either end and at rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AxisRange {
+    pub min: i32,
+    pub center: i32,
+    pub max: i32,
+}
+
+impl AxisRange {
+    /// Maps a raw value to -1.0..=1.0, with 0.0 at the center.
+    pub fn normalize(&self, value: i32) -> f32 {
+        let range = if value >= self.center {
+            self.max - self.center
+        } else {
+            self.center - self.min
+        };
+        if range <= 0 {
+            return 0.0;
+        }
+        ((value - self.center) as f32 / range as f32).clamp(-1.0, 1.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StickCalibration {
+    pub x: AxisRange,
+    pub y: AxisRange,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+/// Turns analog stick movements into d-pad key events. Holding the stick out of the deadzone
+/// presses the key it points towards, which autorepeats until the stick is released.
+#[derive(Debug)]
+pub struct AnalogNavigator {
+    calibration: StickCalibration,
+    deadzone: f32,
+    repeat_interval: Duration,
+    position: Point,
+    held: Option<Key>,
+    next_repeat: Option<Instant>,
+}
+
+impl AnalogNavigator {
+    pub fn new(settings: &AnalogSettings, calibration: StickCalibration) -> Self {
+        Self {
+            calibration,
+            deadzone: settings.deadzone.clamp(0, 100) as f32 / 100.0,
+            repeat_interval: settings.repeat_interval(),
+            position: Point::new(calibration.x.center, calibration.y.center),
+            held: None,
+            next_repeat: None,
+        }
+    }
+
+    /// Updates the raw value of an axis, returning the key events caused by the move.
+    pub fn set_axis(&mut self, axis: Axis, value: i32, now: Instant) -> Vec<KeyEvent> {
+        match axis {
+            Axis::X => self.position.x = value,
+            Axis::Y => self.position.y = value,
+        }
+
+        let direction = self.direction();
+        if direction == self.held {
+            return Vec::new();
+        }
+
+        let mut events = Vec::with_capacity(2);
+        if let Some(key) = self.held.take() {
+            events.push(KeyEvent::Released(key));
+        }
+        if let Some(key) = direction {
+            events.push(KeyEvent::Pressed(key));
+            // Like keys, the first repeat takes longer so that single moves are easy
+            self.next_repeat = Some(now + self.repeat_interval * 2);
+        } else {
+            self.next_repeat = None;
+        }
+        self.held = direction;
+        events
+    }
+
+    /// When the held key should next autorepeat.
+    pub fn next_repeat(&self) -> Option<Instant> {
+        self.next_repeat
+    }
+
+    /// Returns an autorepeat of the held key, if it's due.
+    pub fn repeat(&mut self, now: Instant) -> Option<KeyEvent> {
+        let key = self.held?;
+        if now < self.next_repeat? {
+            return None;
+        }
+        self.next_repeat = Some(now + self.repeat_interval);
+        Some(KeyEvent::Autorepeat(key))
+    }
+
+    fn direction(&self) -> Option<Key> {
+        let x = self.calibration.x.normalize(self.position.x);
+        let y = self.calibration.y.normalize(self.position.y);
+        let magnitude = x.abs().max(y.abs());
+        if let Some(held) = self.held {
+            if magnitude < self.deadzone * RELEASE_DEADZONE {
+                return None;
+            }
+            // Only move to the other axis once it leaves the deadzone itself
+            let other = match held {
+                Key::Left | Key::Right => y,
+                _ => x,
+            };
+            if other.abs() < self.deadzone {
+                return Some(held);
+            }
+        } else if magnitude < self.deadzone {
+            return None;
+        }
+        Some(if x.abs() > y.abs() {
+            if x > 0.0 { Key::Right } else { Key::Left }
+        } else if y > 0.0 {
+            Key::Down
+        } else {
+            Key::Up
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn navigator() -> AnalogNavigator {
+        let range = AxisRange {
+            min: 0,
+            center: 128,
+            max: 255,
+        };
+        let settings = AnalogSettings {
+            deadzone: 25,
+            repeat_interval_ms: 100,
+            ..Default::default()
+        };
+        AnalogNavigator::new(&settings, StickCalibration { x: range, y: range })
+    }
+
+    #[test]
+    fn test_deadzone() {
+        let mut analog = navigator();
+        let now = Instant::now();
+        assert!(analog.set_axis(Axis::X, 150, now).is_empty());
+        assert_eq!(
+            analog.set_axis(Axis::X, 250, now),
+            vec![KeyEvent::Pressed(Key::Right)]
+        );
+        assert_eq!(
+            analog.set_axis(Axis::Y, 0, now),
+            vec![KeyEvent::Released(Key::Right), KeyEvent::Pressed(Key::Up)]
+        );
+        analog.set_axis(Axis::X, 128, now);
+        assert_eq!(
+            analog.set_axis(Axis::Y, 120, now),
+            vec![KeyEvent::Released(Key::Up)]
+        );
+    }
+
+    #[test]
+    fn test_repeat() {
+        let mut analog = navigator();
+        let now = Instant::now();
+        analog.set_axis(Axis::Y, 255, now);
+        assert_eq!(analog.repeat(now + Duration::from_millis(100)), None);
+        assert_eq!(
+            analog.repeat(now + Duration::from_millis(200)),
+            Some(KeyEvent::Autorepeat(Key::Down))
+        );
+        assert_eq!(analog.next_repeat(), Some(now + Duration::from_millis(300)));
+        analog.set_axis(Axis::Y, 128, now);
+        assert_eq!(analog.repeat(now + Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn test_jitter() {
+        let mut analog = navigator();
+        let now = Instant::now();
+        analog.set_axis(Axis::X, 200, now);
+        let next_repeat = analog.next_repeat();
+
+        // Around the edge of the deadzone
+        assert!(analog.set_axis(Axis::X, 155, now).is_empty());
+        assert!(analog.set_axis(Axis::X, 165, now).is_empty());
+        // On the other axis, inside the deadzone
+        assert!(analog.set_axis(Axis::Y, 110, now).is_empty());
+        assert_eq!(analog.next_repeat(), next_repeat);
+
+        assert_eq!(
+            analog.set_axis(Axis::X, 128, now),
+            vec![KeyEvent::Released(Key::Right)]
+        );
+    }
+}
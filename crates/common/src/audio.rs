@@ -0,0 +1,381 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Result, bail};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use strum::FromRepr;
+
+use crate::constants::{ALLIUM_ALSA_CONFIG, ALLIUM_AUDIO_SETTINGS, MAX_VOLUME};
+
+/// How the volume steps map to the output level.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, FromRepr)]
+pub enum VolumeCurve {
+    /// Steps are bigger at low volume, so that each one sounds about as loud as the last.
+    #[default]
+    Logarithmic,
+    /// Every step changes the output level by the same amount, for finer control at low volume.
+    Linear,
+}
+
+/// Folder of the LADSPA plugins, which has the equalizer.
+const LADSPA_PATH: &str = "/usr/lib/ladspa";
+
+/// CAPS LADSPA plugins, which have the equalizer.
+const CAPS_PLUGIN: &str = "caps.so";
+
+/// First line of the ALSA configuration written for the equalizer, so that a configuration that
+/// Allium didn't write is never overwritten or removed.
+const ALSA_CONFIG_HEADER: &str = "# Written by Allium for the equalizer\n";
+
+/// Equalizer presets, applied to the default ALSA output.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, FromRepr)]
+pub enum Equalizer {
+    #[default]
+    Flat,
+    BassBoost,
+    /// Brings out voices, for dialogue-heavy games and videos.
+    Speech,
+}
+
+impl Equalizer {
+    /// Whether the equalizer's plugin is installed. Presets other than `Flat` can't be applied
+    /// without it.
+    pub fn is_available() -> bool {
+        Path::new(LADSPA_PATH).join(CAPS_PLUGIN).exists()
+    }
+
+    /// Returns the gain, in dB, of each band of the equalizer, in octaves from 31 Hz to 16 kHz.
+    fn gains(self) -> [i32; 10] {
+        match self {
+            Equalizer::Flat => [0; 10],
+            Equalizer::BassBoost => [6, 6, 4, 2, 0, 0, 0, 0, 0, 0],
+            Equalizer::Speech => [-6, -6, -3, 0, 2, 4, 4, 2, 0, -2],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioSettings {
+    #[serde(default)]
+    pub volume_curve: VolumeCurve,
+    /// Output level, in percent, at the highest volume step. 100 disables the limit.
+    #[serde(default = "AudioSettings::max_volume")]
+    pub max_volume: i32,
+    #[serde(default)]
+    pub equalizer: Equalizer,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            volume_curve: VolumeCurve::default(),
+            max_volume: Self::max_volume(),
+            equalizer: Equalizer::default(),
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn max_volume() -> i32 {
+        100
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_AUDIO_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_AUDIO_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read audio file, removing");
+            fs::remove_file(ALLIUM_AUDIO_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    /// Loads the settings again for alliumd, which polls them while the launcher may be saving
+    /// them. Unlike `load`, a file that can't be read is left alone and an error is returned.
+    pub fn reload() -> Result<Self> {
+        if !ALLIUM_AUDIO_SETTINGS.exists() {
+            return Ok(Self::new());
+        }
+        let file = File::open(ALLIUM_AUDIO_SETTINGS.as_path())?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Saves the settings to a temporary file that then replaces the old one, so that alliumd
+    /// never reads a partly written file.
+    pub fn save(&self) -> Result<()> {
+        let temp = ALLIUM_AUDIO_SETTINGS.with_extension("tmp");
+        let file = File::create(&temp)?;
+        serde_json::to_writer(file, &self)?;
+        fs::rename(&temp, ALLIUM_AUDIO_SETTINGS.as_path())?;
+        Ok(())
+    }
+
+    /// Returns the modification time and size of the settings file, which change whenever the
+    /// settings are saved, so that they're only loaded again when they do.
+    pub fn stamp() -> Option<(SystemTime, u64)> {
+        let metadata = fs::metadata(ALLIUM_AUDIO_SETTINGS.as_path()).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+
+    /// Returns the output level, from 0 to 100, of a volume step from 0 to `MAX_VOLUME`. The
+    /// limit scales the curve, so that every step still changes the volume.
+    pub fn level(&self, volume: i32) -> i32 {
+        let volume = volume.clamp(0, MAX_VOLUME) as f32;
+        let max = MAX_VOLUME as f32;
+        let level = match self.volume_curve {
+            VolumeCurve::Logarithmic => (volume + 1.0).log10() / (max + 1.0).log10(),
+            VolumeCurve::Linear => volume / max,
+        };
+        (level * self.max_volume.clamp(0, 100) as f32).round() as i32
+    }
+
+    /// Writes the ALSA configuration that runs the default output through the equalizer, keeping
+    /// the Bluetooth audio output it's sent to, if any. Audio that's already playing isn't
+    /// affected.
+    pub fn apply_equalizer(&self) -> Result<()> {
+        self.apply_output(bluetooth_output().as_deref())
+    }
+
+    /// Writes the ALSA configuration that runs the default output through the equalizer and sends
+    /// it to the Bluetooth audio output with the address, or to the speaker when `None`. The
+    /// configuration is removed when neither is needed.
+    pub fn apply_output(&self, bluetooth: Option<&str>) -> Result<()> {
+        let gains = (self.equalizer != Equalizer::Flat).then(|| self.equalizer.gains());
+        if gains.is_some() && !Equalizer::is_available() {
+            bail!(
+                "equalizer plugin {} not found in {}",
+                CAPS_PLUGIN,
+                LADSPA_PATH
+            );
+        }
+        let config =
+            (gains.is_some() || bluetooth.is_some()).then(|| alsa_config(gains, bluetooth));
+        write_alsa_config(&ALLIUM_ALSA_CONFIG, config.as_deref())
+    }
+}
+
+/// Returns the address of the Bluetooth audio output that the default output is sent to.
+pub fn bluetooth_output() -> Option<String> {
+    alsa_config_device(&ALLIUM_ALSA_CONFIG)
+}
+
+/// Returns the Bluetooth device set in the ALSA configuration at the path, if Allium wrote it.
+fn alsa_config_device(path: &Path) -> Option<String> {
+    let config = fs::read_to_string(path).ok()?;
+    if !config.starts_with(ALSA_CONFIG_HEADER) {
+        return None;
+    }
+    config.lines().find_map(|line| {
+        let device = line.trim().strip_prefix("device \"")?.strip_suffix('"')?;
+        Some(device.to_string())
+    })
+}
+
+/// Returns where an ALSA configuration that Allium didn't write is kept while the equalizer is on.
+fn alsa_config_backup(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".allium-backup");
+    PathBuf::from(backup)
+}
+
+/// Whether the ALSA configuration at the path was written by Allium.
+fn is_own_alsa_config(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|config| config.starts_with(ALSA_CONFIG_HEADER))
+}
+
+/// Writes Allium's ALSA configuration to the path, backing up a configuration that Allium didn't
+/// write, or removes it and restores the backup when `config` is `None`.
+fn write_alsa_config(path: &Path, config: Option<&str>) -> Result<()> {
+    let backup = alsa_config_backup(path);
+    let own = is_own_alsa_config(path);
+
+    let Some(config) = config else {
+        if own {
+            fs::remove_file(path)?;
+            if backup.exists() {
+                fs::rename(&backup, path)?;
+            }
+        }
+        return Ok(());
+    };
+
+    if path.exists() && !own {
+        if backup.exists() {
+            bail!(
+                "{} wasn't written by Allium, and {} already exists",
+                path.display(),
+                backup.display()
+            );
+        }
+        fs::rename(path, &backup)?;
+    }
+    fs::write(path, config)?;
+    Ok(())
+}
+
+/// Returns an ALSA configuration that makes the default output go through the 10 band equalizer
+/// of the CAPS LADSPA plugins when there are gains, and to the Bluetooth audio output with the
+/// address instead of the speaker.
+fn alsa_config(gains: Option<[i32; 10]>, bluetooth: Option<&str>) -> String {
+    let mut config = ALSA_CONFIG_HEADER.to_string();
+    let default = if gains.is_some() {
+        "allium_equalizer"
+    } else {
+        "allium_output"
+    };
+    config.push_str(&format!(
+        r#"pcm.!default {{
+    type plug
+    slave.pcm "{default}"
+}}
+"#
+    ));
+
+    if let Some(gains) = gains {
+        let controls = gains.map(|gain| gain.to_string()).join(" ");
+        config.push_str(&format!(
+            r#"
+pcm.allium_equalizer {{
+    type ladspa
+    slave.pcm "allium_output"
+    path "{LADSPA_PATH}"
+    plugins [{{
+        label Eq10
+        input {{
+            controls [ {controls} ]
+        }}
+    }}]
+}}
+"#
+        ));
+    }
+
+    let output = match bluetooth {
+        Some(address) => format!(
+            r#"{{
+        type bluealsa
+        device "{address}"
+        profile "a2dp"
+    }}"#
+        ),
+        None => "\"plughw:0,0\"".to_string(),
+    };
+    config.push_str(&format!(
+        r#"
+pcm.allium_output {{
+    type plug
+    slave.pcm {output}
+}}
+"#
+    ));
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level() {
+        let settings = AudioSettings::new();
+        assert_eq!(settings.level(0), 0);
+        assert_eq!(settings.level(1), 23);
+        assert_eq!(settings.level(10), 79);
+        assert_eq!(settings.level(MAX_VOLUME), 100);
+        assert_eq!(settings.level(MAX_VOLUME + 5), 100);
+
+        let settings = AudioSettings {
+            volume_curve: VolumeCurve::Linear,
+            max_volume: 60,
+            ..AudioSettings::new()
+        };
+        assert_eq!(settings.level(0), 0);
+        assert_eq!(settings.level(10), 30);
+        assert_eq!(settings.level(MAX_VOLUME), 60);
+    }
+
+    #[test]
+    fn test_alsa_config() {
+        let config = alsa_config(Some(Equalizer::BassBoost.gains()), None);
+        assert!(config.contains("slave.pcm \"allium_equalizer\""));
+        assert!(config.contains("controls [ 6 6 4 2 0 0 0 0 0 0 ]"));
+        assert!(config.contains("slave.pcm \"plughw:0,0\""));
+        assert!(config.starts_with(ALSA_CONFIG_HEADER));
+
+        let config = alsa_config(None, Some("00:11:22:33:44:55"));
+        assert!(config.contains("slave.pcm \"allium_output\""));
+        assert!(!config.contains("allium_equalizer"));
+        assert!(config.contains("type bluealsa"));
+        assert!(!config.contains("plughw"));
+    }
+
+    #[test]
+    fn test_alsa_config_device() {
+        let dir = std::env::temp_dir().join(format!("allium-bluealsa-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".asoundrc");
+
+        let address = "00:11:22:33:44:55";
+        let config = alsa_config(Some(Equalizer::Speech.gains()), Some(address));
+        write_alsa_config(&path, Some(&config)).unwrap();
+        assert_eq!(alsa_config_device(&path).as_deref(), Some(address));
+
+        write_alsa_config(
+            &path,
+            Some(&alsa_config(Some(Equalizer::Speech.gains()), None)),
+        )
+        .unwrap();
+        assert_eq!(alsa_config_device(&path), None);
+
+        // A device in a configuration Allium didn't write isn't one it sends audio to
+        fs::remove_file(&path).unwrap();
+        fs::write(
+            &path,
+            format!("pcm.!default {{\n    type bluealsa\n    device \"{address}\"\n}}\n"),
+        )
+        .unwrap();
+        assert_eq!(alsa_config_device(&path), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_alsa_config() {
+        let dir = std::env::temp_dir().join(format!("allium-asoundrc-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".asoundrc");
+        let config = alsa_config(Some(Equalizer::Speech.gains()), None);
+
+        // The user's own configuration is kept, and restored when the equalizer is turned off
+        fs::write(&path, "pcm.!default { type hw card 1 }\n").unwrap();
+        write_alsa_config(&path, Some(&config)).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), config);
+        write_alsa_config(&path, Some(&config)).unwrap();
+        write_alsa_config(&path, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "pcm.!default { type hw card 1 }\n"
+        );
+        assert!(!alsa_config_backup(&path).exists());
+
+        // Turning the equalizer off never removes a configuration Allium didn't write
+        write_alsa_config(&path, None).unwrap();
+        assert!(path.exists());
+
+        fs::remove_file(&path).unwrap();
+        write_alsa_config(&path, Some(&config)).unwrap();
+        write_alsa_config(&path, None).unwrap();
+        assert!(!path.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,95 @@
+use std::fs::{self, File};
+
+use anyhow::Result;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{
+    ALLIUM_DEVICE_ACTIVITY, BACKGROUND_JOB_POLL_INTERVAL, BACKGROUND_JOB_THROTTLE_DELAY,
+};
+
+/// How background jobs such as library syncing should run, so that they never cause frame drops
+/// in games.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobPolicy {
+    /// Run at full speed.
+    Run,
+    /// Run slowly, waiting between steps.
+    Throttle,
+    /// Wait until the policy changes.
+    Pause,
+}
+
+/// What the device is doing, published by alliumd for background jobs in other processes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceActivity {
+    pub in_game: bool,
+    pub charging: bool,
+}
+
+impl DeviceActivity {
+    /// Loads the activity published by alliumd. If alliumd isn't running, the device is
+    /// assumed to be idle.
+    pub fn load() -> Result<Self> {
+        if ALLIUM_DEVICE_ACTIVITY.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_DEVICE_ACTIVITY.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read activity file, removing");
+            fs::remove_file(ALLIUM_DEVICE_ACTIVITY.as_path())?;
+        }
+        Ok(Self::default())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_DEVICE_ACTIVITY.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+
+    /// Background jobs are paused while a game is running, unless the device is charging, in
+    /// which case they're throttled instead.
+    pub fn policy(&self) -> JobPolicy {
+        match (self.in_game, self.charging) {
+            (false, _) => JobPolicy::Run,
+            (true, true) => JobPolicy::Throttle,
+            (true, false) => JobPolicy::Pause,
+        }
+    }
+}
+
+/// Returns how background jobs should run right now.
+pub fn policy() -> JobPolicy {
+    DeviceActivity::load().unwrap_or_default().policy()
+}
+
+/// Called by background jobs between steps. Waits until the job may continue: until the game
+/// is exited or the device is plugged in if paused, or a moment if throttled.
+pub async fn checkpoint() {
+    loop {
+        match policy() {
+            JobPolicy::Run => return,
+            JobPolicy::Throttle => {
+                tokio::time::sleep(BACKGROUND_JOB_THROTTLE_DELAY).await;
+                return;
+            }
+            JobPolicy::Pause => tokio::time::sleep(BACKGROUND_JOB_POLL_INTERVAL).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy() {
+        let activity = |in_game, charging| DeviceActivity { in_game, charging };
+        assert_eq!(activity(false, false).policy(), JobPolicy::Run);
+        assert_eq!(activity(false, true).policy(), JobPolicy::Run);
+        assert_eq!(activity(true, true).policy(), JobPolicy::Throttle);
+        assert_eq!(activity(true, false).policy(), JobPolicy::Pause);
+    }
+}
@@ -1,5 +1,12 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
 use anyhow::Result;
 
+use crate::constants::BATTERY_UPDATE_INTERVAL;
+use crate::database::BatterySample;
+
 pub trait Battery: Send {
     fn update(&mut self) -> Result<()>;
     fn percentage(&self) -> i32;
@@ -19,3 +26,122 @@ impl Battery for Box<dyn Battery> {
         (**self).charging()
     }
 }
+
+/// Samples further apart than this span a period where the device was off or asleep.
+const MAX_SAMPLE_GAP: i64 = BATTERY_UPDATE_INTERVAL.as_secs() as i64 * 6;
+
+/// Returns the total percentage drop and duration in seconds of each discharging period between
+/// consecutive samples, grouped by the game being played.
+fn discharge_periods(
+    samples: &[BatterySample],
+) -> impl Iterator<Item = (Option<&Path>, i32, i64)> + '_ {
+    samples.windows(2).filter_map(|pair| {
+        let (a, b) = (&pair[0], &pair[1]);
+        let dt = b.timestamp - a.timestamp;
+        if a.charging || b.charging || a.game != b.game || dt <= 0 || dt > MAX_SAMPLE_GAP {
+            return None;
+        }
+        Some((a.game.as_deref(), a.percentage - b.percentage, dt))
+    })
+}
+
+/// Returns the battery drain rate in percent per hour of each game in the battery history, highest
+/// first. Time spent outside of games is grouped under `None`. Games played for less than
+/// `min_duration` are left out, as their rate would be mostly noise.
+pub fn drain_rates(
+    samples: &[BatterySample],
+    min_duration: Duration,
+) -> Vec<(Option<PathBuf>, f32)> {
+    let mut totals: HashMap<Option<&Path>, (i32, i64)> = HashMap::new();
+    for (game, drop, dt) in discharge_periods(samples) {
+        let total = totals.entry(game).or_default();
+        total.0 += drop;
+        total.1 += dt;
+    }
+
+    let mut rates: Vec<_> = totals
+        .into_iter()
+        .filter(|(_, (_, dt))| *dt as u64 >= min_duration.as_secs())
+        .map(|(game, (drop, dt))| (game.map(Path::to_path_buf), drop as f32 * 3600.0 / dt as f32))
+        .collect();
+    rates.sort_by(|a, b| b.1.total_cmp(&a.1));
+    rates
+}
+
+/// Estimates how long the battery will last, from the drain rate since it was last charged.
+/// Returns `None` while charging, or if there is less than `min_duration` of data.
+pub fn estimate_runtime(samples: &[BatterySample], min_duration: Duration) -> Option<Duration> {
+    let last = samples.last()?;
+    if last.charging {
+        return None;
+    }
+
+    let start = samples.iter().rposition(|s| s.charging).map_or(0, |i| i + 1);
+    let (drop, dt) = discharge_periods(&samples[start..])
+        .fold((0, 0), |(drop, dt), (_, d, t)| (drop + d, dt + t));
+    if drop <= 0 || (dt as u64) < min_duration.as_secs() {
+        return None;
+    }
+
+    Some(Duration::from_secs((last.percentage as i64 * dt / drop as i64) as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: i64, percentage: i32, game: Option<&str>) -> BatterySample {
+        BatterySample {
+            timestamp,
+            percentage,
+            charging: false,
+            game: game.map(PathBuf::from),
+        }
+    }
+
+    #[test]
+    fn test_drain_rates() {
+        let samples = vec![
+            sample(0, 100, None),
+            sample(10, 99, None),
+            // Device was asleep
+            sample(1000, 99, Some("Game.rom")),
+            sample(1010, 97, Some("Game.rom")),
+            sample(1020, 95, Some("Game.rom")),
+        ];
+
+        assert_eq!(
+            drain_rates(&samples, Duration::ZERO),
+            vec![(Some(PathBuf::from("Game.rom")), 720.0), (None, 360.0)]
+        );
+        assert_eq!(
+            drain_rates(&samples, Duration::from_secs(20)),
+            vec![(Some(PathBuf::from("Game.rom")), 720.0)]
+        );
+    }
+
+    #[test]
+    fn test_estimate_runtime() {
+        let mut samples = vec![
+            sample(0, 50, None),
+            sample(10, 60, None),
+            sample(20, 60, None),
+            sample(30, 59, None),
+            sample(40, 58, None),
+        ];
+        samples[0].charging = true;
+        samples[1].charging = true;
+
+        assert_eq!(
+            estimate_runtime(&samples, Duration::ZERO),
+            Some(Duration::from_secs(580))
+        );
+        assert_eq!(estimate_runtime(&samples, Duration::from_secs(30)), None);
+
+        samples.push(BatterySample {
+            charging: true,
+            ..sample(50, 58, None)
+        });
+        assert_eq!(estimate_runtime(&samples, Duration::ZERO), None);
+    }
+}
@@ -0,0 +1,281 @@
+use std::fs::{self, File};
+#[cfg(feature = "miyoo")]
+use std::path::Path;
+
+use anyhow::Result;
+#[cfg(feature = "miyoo")]
+use anyhow::bail;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "miyoo")]
+use tokio::process::Command;
+
+use crate::audio::{self, AudioSettings};
+use crate::constants::ALLIUM_BLUETOOTH_SETTINGS;
+#[cfg(feature = "miyoo")]
+use crate::constants::{ALLIUM_SCRIPTS_DIR, BLUETOOTH_SCAN_DURATION};
+
+/// A Bluetooth device, as listed by `bluetoothctl devices`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BluetoothDevice {
+    pub address: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BluetoothSettings {
+    pub enabled: bool,
+    /// Audio output that is reconnected on boot, and whose volume is set instead of the
+    /// speaker's while it's connected.
+    pub sink: Option<BluetoothDevice>,
+}
+
+impl BluetoothSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_BLUETOOTH_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_BLUETOOTH_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read bluetooth file, removing");
+            fs::remove_file(ALLIUM_BLUETOOTH_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_BLUETOOTH_SETTINGS.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+
+    /// Turns on Bluetooth and reconnects the audio output, if enabled. Audio is sent to the
+    /// speaker until the audio output is connected again.
+    pub fn init(&self) {
+        if audio::bluetooth_output().is_some()
+            && let Err(e) = route_audio(None)
+        {
+            warn!("failed to send audio to the speaker: {}", e);
+        }
+        if !self.enabled {
+            return;
+        }
+        let sink = self.sink.clone();
+        tokio::spawn(async move {
+            if let Err(e) = bluetooth_on().await {
+                warn!("failed to turn on bluetooth: {}", e);
+                return;
+            }
+            if let Some(sink) = sink
+                && let Err(e) = connect(&sink).await
+            {
+                warn!("failed to reconnect to {}: {}", sink.name, e);
+            }
+        });
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        tokio::spawn(async move {
+            let result = if enabled {
+                bluetooth_on().await
+            } else {
+                route_audio(None).and(bluetooth_off().await)
+            };
+            if let Err(e) = result {
+                warn!("failed to toggle bluetooth: {}", e);
+            }
+        });
+    }
+}
+
+/// Returns whether the device has a Bluetooth adapter.
+pub fn is_available() -> bool {
+    #[cfg(feature = "miyoo")]
+    return Path::new("/sys/class/bluetooth/hci0").exists();
+
+    #[cfg(not(feature = "miyoo"))]
+    return false;
+}
+
+pub async fn bluetooth_on() -> Result<()> {
+    #[cfg(feature = "miyoo")]
+    run_script("bluetooth-on.sh").await?;
+    Ok(())
+}
+
+pub async fn bluetooth_off() -> Result<()> {
+    #[cfg(feature = "miyoo")]
+    run_script("bluetooth-off.sh").await?;
+    Ok(())
+}
+
+/// Looks for nearby devices, returning every device found so far, including paired ones.
+pub async fn scan() -> Result<Vec<BluetoothDevice>> {
+    #[cfg(feature = "miyoo")]
+    {
+        bluetoothctl(&[
+            "--timeout",
+            &BLUETOOTH_SCAN_DURATION.as_secs().to_string(),
+            "scan",
+            "on",
+        ])
+        .await?;
+        let output = bluetoothctl(&["devices"]).await?;
+        return Ok(parse_devices(&output));
+    }
+
+    #[cfg(not(feature = "miyoo"))]
+    Ok(Vec::new())
+}
+
+/// Pairs with the device if needed, connects to it, and sends the default audio output to it.
+#[cfg_attr(not(feature = "miyoo"), allow(unused_variables))]
+pub async fn connect(device: &BluetoothDevice) -> Result<()> {
+    #[cfg(feature = "miyoo")]
+    {
+        let info = bluetoothctl(&["info", &device.address]).await?;
+        if has_property(&info, "Connected") {
+            return route_audio(Some(device));
+        }
+        if !has_property(&info, "Paired") {
+            bluetoothctl(&["pair", &device.address]).await?;
+            bluetoothctl(&["trust", &device.address]).await?;
+        }
+        let output = bluetoothctl(&["connect", &device.address]).await?;
+        if !output.contains("Connection successful") {
+            bail!("failed to connect to {}", device.address);
+        }
+        route_audio(Some(device))?;
+    }
+    Ok(())
+}
+
+/// Sends the default audio output back to the speaker, and disconnects from the device.
+#[cfg_attr(not(feature = "miyoo"), allow(unused_variables))]
+pub async fn disconnect(device: &BluetoothDevice) -> Result<()> {
+    #[cfg(feature = "miyoo")]
+    {
+        route_audio(None)?;
+        bluetoothctl(&["disconnect", &device.address]).await?;
+    }
+    Ok(())
+}
+
+/// Sets the volume of a connected audio output, as an output level between 0 and 100 like the
+/// speaker.
+#[cfg_attr(not(feature = "miyoo"), allow(unused_variables))]
+pub async fn set_volume(device: &BluetoothDevice, level: i32) -> Result<()> {
+    #[cfg(feature = "miyoo")]
+    {
+        let percent = level.clamp(0, 100);
+        let status = Command::new("amixer")
+            .args(["-D", "bluealsa", "sset"])
+            .arg(format!("{} - A2DP", device.name))
+            .arg(format!("{percent}%"))
+            .status()
+            .await?;
+        if !status.success() {
+            bail!("failed to set volume of {}: {}", device.name, status);
+        }
+    }
+    Ok(())
+}
+
+/// Sends the default audio output to the device, or to the speaker when `None`, keeping the
+/// equalizer.
+fn route_audio(device: Option<&BluetoothDevice>) -> Result<()> {
+    AudioSettings::load()?.apply_output(device.map(|device| device.address.as_str()))
+}
+
+#[cfg(feature = "miyoo")]
+async fn run_script(name: &str) -> Result<()> {
+    let status = Command::new(ALLIUM_SCRIPTS_DIR.join(name)).status().await?;
+    if !status.success() {
+        bail!("{} failed: {}", name, status);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "miyoo")]
+async fn bluetoothctl(args: &[&str]) -> Result<String> {
+    let output = Command::new("bluetoothctl").args(args).output().await?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Returns whether `bluetoothctl info` shows the property, such as `Paired` or `Connected`, as
+/// `yes`.
+#[cfg_attr(not(any(feature = "miyoo", test)), allow(dead_code))]
+fn has_property(info: &str, property: &str) -> bool {
+    info.lines().any(|line| {
+        line.trim()
+            .strip_prefix(property)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .is_some_and(|value| value.trim() == "yes")
+    })
+}
+
+/// Parses the output of `bluetoothctl devices`, which has a line like
+/// `Device 00:11:22:33:44:55 Name` for each device.
+#[cfg_attr(not(any(feature = "miyoo", test)), allow(dead_code))]
+fn parse_devices(output: &str) -> Vec<BluetoothDevice> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("Device ")?;
+            let (address, name) = rest.split_once(' ').unwrap_or((rest, rest));
+            Some(BluetoothDevice {
+                address: address.to_string(),
+                name: name.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_devices() {
+        let output = "Device 00:11:22:33:44:55 My Headphones\n\
+                      [CHG] Controller AA:BB:CC:DD:EE:FF Discovering: yes\n\
+                      Device 66:77:88:99:AA:BB 66-77-88-99-AA-BB\n";
+        assert_eq!(
+            parse_devices(output),
+            vec![
+                BluetoothDevice {
+                    address: "00:11:22:33:44:55".to_string(),
+                    name: "My Headphones".to_string(),
+                },
+                BluetoothDevice {
+                    address: "66:77:88:99:AA:BB".to_string(),
+                    name: "66-77-88-99-AA-BB".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_has_property() {
+        let info = "Device 00:11:22:33:44:55 (public)\n\
+                    \tName: My Headphones\n\
+                    \tPaired: yes\n\
+                    \tTrusted: yes\n\
+                    \tConnected: no\n\
+                    \tUUID: Audio Sink (0000110b-0000-1000-8000-00805f9b34fb)\n";
+        assert!(has_property(info, "Paired"));
+        assert!(has_property(info, "Trusted"));
+        assert!(!has_property(info, "Connected"));
+        assert!(!has_property(info, "Blocked"));
+        assert!(!has_property(
+            "Device 00:11:22:33:44:55 not available",
+            "Paired"
+        ));
+    }
+}
@@ -0,0 +1,289 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use chrono::{DateTime, Days, Duration, Local, NaiveTime, Utc};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use strum::FromRepr;
+
+use crate::constants::{
+    ALLIUM_CLOCK_OVERLAY_CONFIG, ALLIUM_CLOCK_SETTINGS, REMINDER_DURATION, SLEEP_TIMER_PRESETS,
+    SLEEP_TIMER_WARNING,
+};
+
+/// Alarm, timer and in-game clock set in the clock app, and the reminder and sleep timer
+/// set from the in-game menu or settings. These are handled by alliumd, so that they go off
+/// even when the clock app isn't running or the device is suspended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClockSettings {
+    pub alarm_enabled: bool,
+    /// Time of day at which the alarm goes off.
+    pub alarm: NaiveTime,
+    /// When the running timer finishes.
+    pub timer: Option<DateTime<Utc>>,
+    pub timer_minutes: i32,
+    /// Whether to suspend the device when the timer finishes, instead of ringing.
+    pub timer_suspend: bool,
+    /// When the reminder set from the in-game menu goes off.
+    pub reminder: Option<DateTime<Utc>>,
+    /// When the sleep timer finishes.
+    pub sleep_timer: Option<DateTime<Utc>>,
+    /// Duration the sleep timer was last set to.
+    pub sleep_timer_minutes: i32,
+    pub sleep_timer_action: SleepTimerAction,
+    /// Whether to show the time while playing.
+    pub overlay_enabled: bool,
+    pub overlay_corner: OverlayCorner,
+    /// Opacity of the clock overlay background, in percent.
+    pub overlay_opacity: i32,
+    /// Whether to only show the clock overlay briefly at the start of each minute.
+    pub overlay_auto_hide: bool,
+    /// Whether to show the battery level and volume while playing, toggled from the in-game
+    /// menu. It is shown alongside the clock overlay.
+    pub status_overlay: bool,
+}
+
+/// What to do when the sleep timer finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, FromRepr)]
+pub enum SleepTimerAction {
+    #[default]
+    Suspend,
+    Shutdown,
+}
+
+/// Corner of the screen the in-game clock overlay is shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, FromRepr)]
+pub enum OverlayCorner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Default for ClockSettings {
+    fn default() -> Self {
+        Self {
+            alarm_enabled: false,
+            alarm: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            timer: None,
+            timer_minutes: 5,
+            timer_suspend: false,
+            reminder: None,
+            sleep_timer: None,
+            sleep_timer_minutes: SLEEP_TIMER_PRESETS[1],
+            sleep_timer_action: SleepTimerAction::default(),
+            overlay_enabled: false,
+            overlay_corner: OverlayCorner::default(),
+            overlay_opacity: 50,
+            overlay_auto_hide: false,
+            status_overlay: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockEvent {
+    Alarm,
+    Timer,
+    Reminder,
+    /// The sleep timer finishes in `SLEEP_TIMER_WARNING`.
+    SleepWarning,
+    Sleep,
+}
+
+impl ClockSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_CLOCK_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_CLOCK_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read clock file, removing");
+            fs::remove_file(ALLIUM_CLOCK_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    /// Loads the settings again for alliumd, which polls them while the clock app may be saving
+    /// them. Unlike `load`, a file that can't be read is left alone and an error is returned.
+    pub fn reload() -> Result<Self> {
+        if !ALLIUM_CLOCK_SETTINGS.exists() {
+            return Ok(Self::new());
+        }
+        let file = File::open(ALLIUM_CLOCK_SETTINGS.as_path())?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Saves the settings to a temporary file that then replaces the old one, so that alliumd
+    /// never reads a partly written file.
+    pub fn save(&self) -> Result<()> {
+        let temp = ALLIUM_CLOCK_SETTINGS.with_extension("tmp");
+        let file = File::create(&temp)?;
+        serde_json::to_writer(file, &self)?;
+        fs::rename(&temp, ALLIUM_CLOCK_SETTINGS.as_path())?;
+        if let Err(e) = self.write_overlay_config() {
+            warn!("failed to write clock overlay config: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Returns the modification time and size of the settings file, which change whenever the
+    /// settings are saved, so that they're only loaded again when they do.
+    pub fn stamp() -> Option<(SystemTime, u64)> {
+        let metadata = fs::metadata(ALLIUM_CLOCK_SETTINGS.as_path()).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+
+    /// Writes the RetroArch config that positions on-screen notifications, which the clock
+    /// overlay is shown with. It is appended to the RetroArch config when a game is launched.
+    fn write_overlay_config(&self) -> Result<()> {
+        if !self.is_overlay_shown() {
+            if ALLIUM_CLOCK_OVERLAY_CONFIG.exists() {
+                fs::remove_file(ALLIUM_CLOCK_OVERLAY_CONFIG.as_path())?;
+            }
+            return Ok(());
+        }
+
+        let (x, y) = match self.overlay_corner {
+            OverlayCorner::TopLeft => (0.02, 0.92),
+            OverlayCorner::TopRight => (0.85, 0.92),
+            OverlayCorner::BottomLeft => (0.02, 0.05),
+            OverlayCorner::BottomRight => (0.85, 0.05),
+        };
+        let mut file = File::create(ALLIUM_CLOCK_OVERLAY_CONFIG.as_path())?;
+        write!(
+            file,
+            r#"video_msg_pos_x = "{x:.2}"
+video_msg_pos_y = "{y:.2}"
+video_msg_bgcolor_enable = "true"
+video_msg_bgcolor_opacity = "{opacity:.2}"
+"#,
+            opacity = self.overlay_opacity.clamp(0, 100) as f32 / 100.0,
+        )?;
+        Ok(())
+    }
+
+    /// Whether anything is shown in the in-game overlay.
+    pub fn is_overlay_shown(&self) -> bool {
+        self.overlay_enabled || self.status_overlay
+    }
+
+    /// Sets the reminder to go off in `REMINDER_DURATION`, or cancels it if it is already set.
+    pub fn toggle_reminder(&mut self) {
+        self.reminder = match self.reminder {
+            Some(_) => None,
+            None => Some(Utc::now() + REMINDER_DURATION),
+        };
+    }
+
+    /// Starts the sleep timer for the given number of minutes, or cancels it.
+    pub fn set_sleep_timer(&mut self, minutes: Option<i32>) {
+        self.sleep_timer = minutes.map(|minutes| {
+            self.sleep_timer_minutes = minutes;
+            Utc::now() + Duration::minutes(minutes as i64)
+        });
+    }
+
+    /// Sets the sleep timer to the next longer preset, or cancels it after the longest one.
+    pub fn cycle_sleep_timer(&mut self) {
+        let next = match self.sleep_timer {
+            Some(_) => SLEEP_TIMER_PRESETS
+                .iter()
+                .find(|&&m| m > self.sleep_timer_minutes),
+            None => SLEEP_TIMER_PRESETS.first(),
+        };
+        self.set_sleep_timer(next.copied());
+    }
+
+    /// Minutes left until the sleep timer finishes, rounded up.
+    pub fn sleep_timer_remaining(&self) -> Option<i64> {
+        self.sleep_timer
+            .map(|t| ((t - Utc::now()).num_seconds().max(0) + 59) / 60)
+    }
+
+    /// The next time the alarm goes off after `now`.
+    pub fn next_alarm(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        if !self.alarm_enabled {
+            return None;
+        }
+        let today = now.date_naive().and_time(self.alarm);
+        let next = if today > now.naive_local() {
+            today
+        } else {
+            today.checked_add_days(Days::new(1))?
+        };
+        next.and_local_timezone(Local).earliest()
+    }
+
+    /// The next alarm, timer, reminder or sleep timer event after `now`, and when it happens.
+    /// The sleep timer warns `SLEEP_TIMER_WARNING` before it finishes.
+    pub fn next_event(&self, now: DateTime<Local>) -> Option<(DateTime<Local>, ClockEvent)> {
+        let alarm = self.next_alarm(now).map(|t| (t, ClockEvent::Alarm));
+        let timer = self
+            .timer
+            .map(|t| (t.with_timezone(&Local), ClockEvent::Timer));
+        let reminder = self
+            .reminder
+            .map(|t| (t.with_timezone(&Local), ClockEvent::Reminder));
+        let sleep = self.sleep_timer.map(|t| {
+            let t = t.with_timezone(&Local);
+            let warning = t - SLEEP_TIMER_WARNING;
+            if warning > now {
+                (warning, ClockEvent::SleepWarning)
+            } else {
+                (t, ClockEvent::Sleep)
+            }
+        });
+        [timer, reminder, sleep, alarm]
+            .into_iter()
+            .flatten()
+            .min_by_key(|(t, _)| *t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_next_event() {
+        let now = Local.with_ymd_and_hms(2024, 5, 1, 8, 0, 0).unwrap();
+        let mut settings = ClockSettings::new();
+        assert_eq!(settings.next_event(now), None);
+
+        settings.alarm_enabled = true;
+        settings.alarm = NaiveTime::from_hms_opt(7, 30, 0).unwrap();
+        let tomorrow = Local.with_ymd_and_hms(2024, 5, 2, 7, 30, 0).unwrap();
+        assert_eq!(settings.next_event(now), Some((tomorrow, ClockEvent::Alarm)));
+
+        settings.alarm = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        let today = Local.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+        assert_eq!(settings.next_event(now), Some((today, ClockEvent::Alarm)));
+
+        let timer = now + Duration::minutes(5);
+        settings.timer = Some(timer.with_timezone(&Utc));
+        assert_eq!(settings.next_event(now), Some((timer, ClockEvent::Timer)));
+
+        let reminder = now + Duration::minutes(1);
+        settings.reminder = Some(reminder.with_timezone(&Utc));
+        assert_eq!(settings.next_event(now), Some((reminder, ClockEvent::Reminder)));
+
+        settings.reminder = None;
+        settings.sleep_timer = Some((now + Duration::minutes(2)).with_timezone(&Utc));
+        let warning = now + Duration::minutes(1);
+        assert_eq!(settings.next_event(now), Some((warning, ClockEvent::SleepWarning)));
+        let sleep = now + Duration::minutes(2);
+        assert_eq!(settings.next_event(warning), Some((sleep, ClockEvent::Sleep)));
+    }
+}
@@ -1,9 +1,19 @@
+use std::path::PathBuf;
 use std::time::Duration;
 
 use image::{ImageBuffer, Rgba};
 
+use crate::database::UndoAction;
 use crate::display::color::Color;
+use crate::game_info::GameInfo;
+use crate::launch_diagnostic::LaunchDiagnostic;
+use crate::launcher::LauncherSettings;
 use crate::locale::LocaleSettings;
+use crate::parental::ParentalBlock;
+use crate::platform::Key;
+use crate::platform::rumble::Rumble;
+use crate::reset::ResetTarget;
+use crate::view::Toast;
 use crate::{display::settings::DisplaySettings, stylesheet::Stylesheet};
 
 #[derive(Debug)]
@@ -13,22 +23,76 @@ pub enum Command {
     SaveStylesheet(Box<Stylesheet>),
     SaveDisplaySettings(Box<DisplaySettings>),
     SaveLocaleSettings(LocaleSettings),
+    /// Saves which tabs are shown, and reloads the launcher to show them.
+    SaveLauncherSettings(LauncherSettings),
     CloseView,
     ValueChanged(usize, Value),
     TrapFocus,
     Unfocus,
     Redraw,
+    /// Presses and releases a key, e.g. when its button hint is tapped.
+    PressKey(Key),
     StartSearch,
     Search(String),
+    /// Opens the games tab in the folder of the game at the path, with the game selected.
+    ShowInFolder(PathBuf),
+    /// Opens the manual at the path, a PDF or text file.
+    ViewManual(PathBuf),
     Toast(String, Option<Duration>),
     ImageToast(ImageBuffer<Rgba<u8>, Vec<u8>>, String, Option<Duration>),
+    /// Queues a toast with an icon or a priority.
+    Notify(Toast),
+    /// Removes the toasts that have no duration.
     DismissToast,
+    /// Gives haptic feedback.
+    Rumble(Rumble),
     PopulateDb,
+    /// Repopulates the database with directories of the games folder that changed.
+    GamesChanged(Vec<PathBuf>),
+    /// Games were hidden or deleted, so the games list should be loaded again.
+    LibraryChanged,
+    /// A change to the library was made that can be undone, shown as a toast with a button to
+    /// undo it.
+    Undoable(UndoAction),
+    ImportOnionData,
+    ExportBundle,
+    ImportBundle,
+    /// Backs up favorites, play stats, cores and collections to `ALLIUM_LIBRARY_BACKUP`.
+    BackupLibrary,
+    /// Restores the backup at `ALLIUM_LIBRARY_BACKUP`.
+    RestoreLibrary,
+    SyncLibrary,
+    /// Library metadata was merged from another device, with the number of games merged.
+    LibrarySynced(usize),
+    /// Backs up and then wipes part of Allium's data.
+    Reset(ResetTarget),
+    /// Syncs save files and states with the server configured in the save sync settings.
+    SyncSaves,
+    /// Save sync copied a file, with the number of files copied so far and the number to copy.
+    SyncSavesProgress(usize, usize),
+    /// Save sync finished, with the number of files copied each way and the number of conflicts.
+    SavesSynced {
+        uploaded: usize,
+        downloaded: usize,
+        conflicts: usize,
+    },
+    ParentalBlock(ParentalBlock),
+    /// Launches a game whose info was saved, after the launch transition.
+    LaunchGame(Box<GameInfo>),
+    /// Shows why a game couldn't be launched.
+    LaunchFailed(Box<LaunchDiagnostic>),
+    /// Extracts an archived ROM, then launches the game.
+    ExtractArchive {
+        archive: PathBuf,
+        game_info: Box<GameInfo>,
+    },
     SaveStateScreenshot {
         path: String,
         core: String,
         slot: i8,
     },
+    /// Saves the game's current frame as the box art of the game at the path.
+    SaveBoxArt(PathBuf),
 }
 
 #[derive(Debug, Clone)]
@@ -4,11 +4,19 @@ use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use lazy_static::lazy_static;
+use log::LevelFilter;
 
 use crate::geom::Size;
 
 pub const ALLIUM_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Set by alliumd on the launcher it starts on boot, so the startup behavior is only applied then.
+pub const ALLIUM_BOOT_VAR: &str = "ALLIUM_BOOT";
+
+/// Exit code of the launcher that makes alliumd restart, so that it loads its settings again
+/// after they were reset.
+pub const RESTART_ALLIUMD_EXIT_CODE: i32 = 75;
+
 lazy_static! {
     pub static ref ALLIUM_SD_ROOT: PathBuf = PathBuf::from(
         &env::var("ALLIUM_SD_ROOT").unwrap_or_else(|_| "/mnt/SDCARD/".to_string())
@@ -22,14 +30,33 @@ lazy_static! {
     pub static ref ALLIUM_APPS_DIR: PathBuf = PathBuf::from(
         &env::var("ALLIUM_APPS_DIR").map_or_else(|_| ALLIUM_SD_ROOT.join("Apps"), PathBuf::from)
     );
+    /// Mount point of a second SD card or USB drive.
+    pub static ref ALLIUM_EXTERNAL_STORAGE_DIR: PathBuf = PathBuf::from(
+        &env::var("ALLIUM_EXTERNAL_STORAGE_DIR").unwrap_or_else(|_| "/mnt/EXTERNAL".to_string())
+    );
+    pub static ref ALLIUM_EXTERNAL_GAMES_DIR: PathBuf = ALLIUM_EXTERNAL_STORAGE_DIR.join("Roms");
 
     // Folders
     pub static ref ALLIUM_SCRIPTS_DIR: PathBuf = ALLIUM_BASE_DIR.join("scripts");
     pub static ref ALLIUM_TOOLS_DIR: PathBuf = ALLIUM_BASE_DIR.join("tools");
+    pub static ref ALLIUM_LOGS_DIR: PathBuf = ALLIUM_BASE_DIR.join("logs");
+    pub static ref ALLIUM_STATE_DIR: PathBuf = ALLIUM_BASE_DIR.join("state");
+    pub static ref ALLIUM_RESET_BACKUPS_DIR: PathBuf = ALLIUM_BASE_DIR.join("reset-backups");
+    /// Deleted games on the SD card, kept until their deletion can't be undone anymore.
+    pub static ref ALLIUM_TRASH_DIR: PathBuf = ALLIUM_BASE_DIR.join("trash");
     pub static ref ALLIUM_FONTS_DIR: PathBuf = ALLIUM_BASE_DIR.join("fonts");
     pub static ref ALLIUM_LOCALES_DIR: PathBuf = ALLIUM_BASE_DIR.join("locales");
     pub static ref ALLIUM_IMAGES_DIR: PathBuf = ALLIUM_BASE_DIR.join("images");
+    pub static ref ALLIUM_FFPLAY_DIR: PathBuf = ALLIUM_BASE_DIR.join("cores/ffplay");
     pub static ref ALLIUM_SCREENSHOTS_DIR: PathBuf = ALLIUM_SD_ROOT.join("Saves/CurrentProfile/screenshots");
+    pub static ref ALLIUM_RECORDINGS_DIR: PathBuf = ALLIUM_SD_ROOT.join("Saves/CurrentProfile/recordings");
+    pub static ref ALLIUM_THEMES_DIR: PathBuf = ALLIUM_SD_ROOT.join("Themes");
+    pub static ref ALLIUM_WALLPAPERS_DIR: PathBuf = ALLIUM_SD_ROOT.join("Wallpapers");
+    pub static ref ALLIUM_CUSTOM_LOCALES_DIR: PathBuf = ALLIUM_SD_ROOT.join("Locales");
+    pub static ref ALLIUM_BIOS_DIR: PathBuf = ALLIUM_SD_ROOT.join("BIOS");
+    pub static ref ALLIUM_VIDEOS_DIR: PathBuf = ALLIUM_SD_ROOT.join("Videos");
+    pub static ref ALLIUM_BOOKS_DIR: PathBuf = ALLIUM_SD_ROOT.join("Books");
+    pub static ref ALLIUM_PORTS_DIR: PathBuf = ALLIUM_GAMES_DIR.join("PORTS");
 
     // Config
     pub static ref ALLIUM_CONFIG_CONSOLES: PathBuf = ALLIUM_BASE_DIR.join("config/consoles.toml");
@@ -37,17 +64,61 @@ lazy_static! {
 
     // State
     pub static ref ALLIUMD_STATE: PathBuf = ALLIUM_BASE_DIR.join("state/alliumd.json");
+    pub static ref ALLIUM_CONSOLE_ALIASES: PathBuf =
+        ALLIUM_BASE_DIR.join("state/console-aliases.toml");
     pub static ref ALLIUM_LAUNCHER_STATE: PathBuf =
         ALLIUM_BASE_DIR.join("state/allium-launcher.json");
+    pub static ref ALLIUM_LAUNCHER_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/launcher.json");
+    pub static ref ALLIUM_EXTERNAL_STORAGE: PathBuf = ALLIUM_BASE_DIR.join("state/storage.json");
     pub static ref ALLIUM_MENU_STATE: PathBuf =
         ALLIUM_BASE_DIR.join("state/allium-menu.json");
     pub static ref ALLIUM_GAME_INFO: PathBuf = ALLIUM_BASE_DIR.join("state/current_game");
+    pub static ref ALLIUM_RESUME_SCREENSHOT: PathBuf = ALLIUM_BASE_DIR.join("state/resume.png");
     pub static ref ALLIUM_STYLESHEET: PathBuf = ALLIUM_BASE_DIR.join("state/stylesheet.json");
     pub static ref ALLIUM_DISPLAY_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/display.json");
     pub static ref ALLIUM_LOCALE_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/locale.json");
+    pub static ref ALLIUM_ALSA_CONFIG: PathBuf = PathBuf::from(
+        &env::var("HOME").unwrap_or_else(|_| "/root".to_string())
+    ).join(".asoundrc");
+    pub static ref ALLIUM_AUDIO_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/audio.json");
     pub static ref ALLIUM_POWER_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/power.json");
+    pub static ref ALLIUM_ANALOG_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/analog.json");
+    pub static ref ALLIUM_BUTTON_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/buttons.json");
+    pub static ref ALLIUM_MACRO_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/macro.json");
+    pub static ref ALLIUM_HOTKEY_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/hotkeys.json");
+    pub static ref ALLIUM_LED_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/led.json");
+    pub static ref ALLIUM_RUMBLE_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/rumble.json");
+    pub static ref ALLIUM_DEVELOPER_SETTINGS: PathBuf =
+        ALLIUM_BASE_DIR.join("state/developer.json");
+    /// Folder with the `authorized_keys` file that the SSH server accepts logins from.
+    pub static ref ALLIUM_SSH_KEYS_DIR: PathBuf = ALLIUM_SD_ROOT.join(".ssh");
+    pub static ref ALLIUM_CLOCK_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/clock.json");
+    pub static ref ALLIUM_LOCK_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/lock.json");
+    pub static ref ALLIUM_PARENTAL_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/parental.json");
+    pub static ref ALLIUM_CLOCK_OVERLAY_CONFIG: PathBuf = ALLIUM_BASE_DIR.join("state/clock-overlay.cfg");
+    pub static ref ALLIUM_RETROARCH_HOTKEYS_CONFIG: PathBuf = ALLIUM_BASE_DIR.join("state/retroarch-hotkeys.cfg");
     pub static ref ALLIUM_WIFI_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/wifi.json");
+    pub static ref ALLIUM_BLUETOOTH_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/bluetooth.json");
+    pub static ref ALLIUM_WEBHOOK_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/webhook.json");
+    pub static ref ALLIUM_SAVE_SYNC_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/save-sync.json");
+    pub static ref ALLIUM_SAVE_SYNC_STATE: PathBuf = ALLIUM_BASE_DIR.join("state/save-sync-state.json");
+    pub static ref ALLIUM_SAVE_BACKUP_SETTINGS: PathBuf = ALLIUM_BASE_DIR.join("state/save-backup.json");
     pub static ref ALLIUM_TIMEZONE: PathBuf = ALLIUM_BASE_DIR.join("state/timezone");
+    pub static ref ALLIUM_REMOTE_LAUNCH: PathBuf = ALLIUM_BASE_DIR.join("state/remote_launch");
+    pub static ref ALLIUM_BUNDLE_LIBRARY: PathBuf = ALLIUM_BASE_DIR.join("state/library.json");
+    pub static ref ALLIUM_DEVICE_ACTIVITY: PathBuf = ALLIUM_BASE_DIR.join("state/activity.json");
+    pub static ref ALLIUM_DEVICE_STATUS: PathBuf = ALLIUM_BASE_DIR.join("state/status.json");
+    pub static ref ALLIUM_LAUNCH_STDERR: PathBuf = ALLIUM_BASE_DIR.join("state/launch-stderr.log");
+    pub static ref ALLIUM_LAUNCH_DIAGNOSTIC: PathBuf = ALLIUM_BASE_DIR.join("state/launch-diagnostic.json");
+    // Extracted to the SD card, as disc images don't fit in /tmp
+    pub static ref ALLIUM_ARCHIVE_DIR: PathBuf = ALLIUM_BASE_DIR.join("state/archive");
+    pub static ref ALLIUM_IMAGE_CACHE_DIR: PathBuf = ALLIUM_BASE_DIR.join("state/image-cache");
+
+    // Settings bundle
+    pub static ref ALLIUM_BUNDLE: PathBuf = ALLIUM_SD_ROOT.join("allium-bundle.tar.gz");
+
+    // Library backup
+    pub static ref ALLIUM_LIBRARY_BACKUP: PathBuf = ALLIUM_SD_ROOT.join("allium-library.json");
 
     // Database
     pub static ref ALLIUM_DATABASE: PathBuf = env::var("ALLIUM_DATABASE")
@@ -58,6 +129,14 @@ lazy_static! {
     pub static ref ALLIUM_LAUNCHER: PathBuf = ALLIUM_BASE_DIR.join("bin/allium-launcher");
     pub static ref ALLIUM_MENU: PathBuf = ALLIUM_BASE_DIR.join("bin/allium-menu");
     pub static ref ALLIUM_RETROARCH: PathBuf = ALLIUM_BASE_DIR.join("cores/retroarch/launch.sh");
+    pub static ref ALLIUM_RCLONE: PathBuf = ALLIUM_BASE_DIR.join("bin/rclone");
+
+    // RetroArch
+    pub static ref RETROARCH_CHEATS_DIR: PathBuf = ALLIUM_SD_ROOT.join("RetroArch/.retroarch/cheats");
+    pub static ref RETROARCH_PLAYLISTS_DIR: PathBuf = ALLIUM_SD_ROOT.join("RetroArch/.retroarch/playlists");
+    // Save files and states are in the `saves` and `states` folders of the profile
+    pub static ref RETROARCH_PROFILE_DIR: PathBuf = ALLIUM_SD_ROOT.join("Saves/CurrentProfile");
+    pub static ref ALLIUM_SAVE_BACKUPS_DIR: PathBuf = RETROARCH_PROFILE_DIR.join("backups");
 }
 
 // Styles
@@ -65,29 +144,229 @@ pub const IMAGE_WIDTH: u32 = 250;
 pub const SELECTION_MARGIN: u32 = 8;
 pub const SAVE_STATE_IMAGE_WIDTH: u32 = 333;
 
+/// Number of volume steps, from 0 (muted) to this.
+pub const MAX_VOLUME: i32 = 20;
+
 /// After the battery level drops below this threshold, the device will shut down.
 pub const BATTERY_SHUTDOWN_THRESHOLD: i32 = 5;
 
+/// How long after the battery drops below `BATTERY_SHUTDOWN_THRESHOLD` the device shuts down,
+/// so that progress can be saved.
+pub const BATTERY_SHUTDOWN_GRACE: Duration = Duration::from_secs(30);
+
 /// The interval at which the battery level is updated.
 pub const BATTERY_UPDATE_INTERVAL: Duration = Duration::from_secs(10);
 
+/// How long battery samples are kept in the database for the battery history.
+pub const BATTERY_HISTORY_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The interval at which the status bar reloads the device status published by alliumd.
+pub const DEVICE_STATUS_UPDATE_INTERVAL: Duration = Duration::from_secs(2);
+
 /// The interval at which the clock is updated.
 pub const CLOCK_UPDATE_INTERVAL: Duration = Duration::from_secs(60);
 
+/// The interval at which alliumd reloads the alarm and timer set in the clock app.
+pub const CLOCK_EVENT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often the in-game clock overlay is re-sent to RetroArch, so it stays on screen.
+pub const CLOCK_OVERLAY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long the in-game clock overlay is shown at the start of each minute when auto-hide is on.
+pub const CLOCK_OVERLAY_AUTO_HIDE: Duration = Duration::from_secs(5);
+
+/// How long until the reminder set from the in-game menu goes off.
+pub const REMINDER_DURATION: Duration = Duration::from_secs(30 * 60);
+
+/// How long a change to the library, such as deleting a game, can be undone for.
+pub const UNDO_DURATION: Duration = Duration::from_secs(5);
+
+/// Durations the sleep timer can be set to, in minutes.
+pub const SLEEP_TIMER_PRESETS: [i32; 6] = [15, 30, 45, 60, 90, 120];
+
+/// How long before the sleep timer finishes to warn that the device is going to sleep.
+pub const SLEEP_TIMER_WARNING: Duration = Duration::from_secs(60);
+
+/// How often alliumd checks the play time left today while a game is running.
+pub const PARENTAL_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long before the daily play time limit to warn that the device is going to sleep.
+pub const PLAY_TIME_WARNING: Duration = Duration::from_secs(10 * 60);
+
+/// How long a game may keep running after waking from sleep past the daily play time limit,
+/// so that progress can be saved.
+pub const PLAY_TIME_LIMIT_GRACE: Duration = Duration::from_secs(60);
+
 /// How long to wait until the device is considered idle.
 pub const IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
 
+/// How long each box art or screenshot is shown in attract mode.
+pub const ATTRACT_MODE_SLIDE_DURATION: Duration = Duration::from_secs(8);
+
+/// How long each frame of a gameplay recording is shown in attract mode.
+pub const ATTRACT_MODE_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often the launch transition is drawn.
+pub const LAUNCH_SPLASH_FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+/// How long the seasonal boot splash of the theme is shown when the launcher starts on boot.
+pub const SEASONAL_SPLASH_DURATION: Duration = Duration::from_millis(1500);
+
+/// A game that exits with an error within this long of starting is considered to have failed to
+/// launch, and the launcher shows why.
+pub const LAUNCH_FAILURE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Size above which a running game's stderr log is truncated. Only the output of the first
+/// moments of a game is needed to tell why it failed to launch.
+pub const LAUNCH_STDERR_MAX_SIZE: u64 = 256 * 1024;
+
+/// Number of lines at the end of a failed game's stderr shown by the launcher.
+pub const LAUNCH_DIAGNOSTIC_LINES: usize = 3;
+
+/// How often the launcher checks whether the custom translations changed.
+pub const LOCALE_RELOAD_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often the launcher draws and handles events.
+pub const LAUNCHER_FRAME_INTERVAL: Duration = Duration::from_micros(166_667);
+
+/// How often the launcher draws and handles events while the battery saver is on.
+pub const BATTERY_SAVER_FRAME_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Maximum number of games to cycle through in attract mode.
+pub const ATTRACT_MODE_GAMES_LIMIT: i64 = 20;
+
+/// How long the screensaver stays in place before moving, so that nothing is burned in.
+pub const SCREENSAVER_MOVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Brightness that the backlight is dimmed to while the launcher is idle.
+pub const IDLE_DIM_BRIGHTNESS: u8 = 10;
+
+/// How long the wipe shown when opening or leaving a folder in the launcher lasts.
+pub const NAVIGATION_TRANSITION_DURATION: Duration = Duration::from_millis(250);
+
+/// How long the console list has to be idle before the most played consoles are prefetched.
+pub const PREFETCH_IDLE_DELAY: Duration = Duration::from_secs(2);
+
+/// Number of most played consoles to prefetch.
+pub const PREFETCH_CONSOLE_COUNT: usize = 3;
+
+/// How long the games folder has to stop changing before the launcher picks up the changes.
+pub const GAMES_WATCHER_DEBOUNCE: Duration = Duration::from_secs(2);
+
 /// The number of items to jump when pressing left/right in a listing.
 pub const LISTING_JUMP_SIZE: i32 = 5;
 
+/// How far a touch can move before it's a swipe instead of a tap, in pixels.
+pub const TOUCH_TAP_SLOP: i32 = 16;
+
+/// How often input devices are scanned for newly connected controllers.
+pub const CONTROLLER_SCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Maximum number of key presses and releases in a recorded macro.
+pub const MACRO_MAX_STEPS: usize = 200;
+
 /// If a key autorepeat is received after this duration, it will be ignored.
 pub const MAXIMUM_FRAME_TIME: Duration = Duration::from_millis(100);
 
+/// Size of the image cache, in bytes, above which the oldest images are deleted.
+pub const IMAGE_CACHE_MAX_SIZE: u64 = 64 * 1024 * 1024;
+
 /// Maximum number of recent games to retrieve from the database.
 pub const RECENT_GAMES_LIMIT: i64 = 100;
 
+/// Number of consoles and games shown in the play time statistics.
+pub const PLAY_STATS_LIMIT: usize = 8;
+
+/// Number of weeks shown in the weekly play time statistics.
+pub const PLAY_STATS_WEEKS: usize = 8;
+
 /// RetroArch network command interface.
 pub const RETROARCH_UDP_SOCKET: &str = "127.0.0.1:55355";
 
 /// Long press duration for the menu button.
 pub const LONG_PRESS_DURATION: Duration = Duration::from_millis(1000);
+
+/// How often the fast-forward indicator is re-sent to RetroArch, so it stays on screen.
+pub const FAST_FORWARD_INDICATOR_INTERVAL: Duration = Duration::from_secs(2);
+
+/// RetroArch's auto save state slot, which is loaded when a game is launched.
+pub const AUTO_SAVE_STATE_SLOT: i8 = -1;
+
+/// How long RetroArch is given to write the save state before suspending or shutting down.
+pub const AUTO_SAVE_STATE_DELAY: Duration = Duration::from_millis(500);
+
+/// Host pinged to check for internet connectivity.
+pub const NETWORK_INTERNET_HOST: &str = "1.1.1.1";
+
+/// Host name resolved to check that DNS is working.
+pub const NETWORK_DNS_HOST: &str = "pool.ntp.org";
+
+/// NTP servers queried in turn to set the clock once Wi-Fi connects.
+pub const NTP_SERVERS: [&str; 3] = ["0.pool.ntp.org", "1.pool.ntp.org", "2.pool.ntp.org"];
+
+/// How long to wait for an NTP server to answer.
+pub const NTP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// File downloaded to measure download speed, unless another one is set in the Wi-Fi settings.
+pub const NETWORK_SPEED_TEST_URL: &str = "http://speedtest.tele2.net/1MB.zip";
+
+/// How long the speed test download may take before it's given up on.
+pub const NETWORK_SPEED_TEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Port the FTP server listens on.
+pub const FTP_PORT: u16 = 21;
+
+/// Port the SSH server listens on.
+pub const SSH_PORT: u16 = 22;
+
+/// Port the telnet server listens on.
+pub const TELNET_PORT: u16 = 23;
+
+/// Port the web file browser listens on. It also serves the upload page.
+pub const WEB_FILE_BROWSER_PORT: u16 = 80;
+
+/// Port the web remote listens on.
+pub const WEB_REMOTE_PORT: u16 = 8080;
+
+/// Length of the generated pairing code for the web remote.
+pub const WEB_REMOTE_TOKEN_LENGTH: usize = 8;
+
+/// mDNS service type that devices advertise for LAN sync. It is served by the web remote.
+pub const SYNC_SERVICE_TYPE: &str = "_allium._tcp.local.";
+
+/// How often the Wi-Fi settings are reloaded to update the services advertised over mDNS.
+pub const MDNS_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long to look for another device to sync with.
+pub const SYNC_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to look for Bluetooth devices.
+pub const BLUETOOTH_SCAN_DURATION: Duration = Duration::from_secs(10);
+
+/// Lowest level of log records that are written to the log files.
+pub const LOG_FILE_LEVEL: LevelFilter = LevelFilter::Info;
+
+/// Size, in bytes, at which a log file is rotated.
+pub const LOG_FILE_MAX_SIZE: u64 = 256 * 1024;
+
+/// Number of rotated log files kept for each component, besides the current one.
+pub const LOG_FILE_ROTATIONS: usize = 2;
+
+/// How long to wait for a Wi-Fi scan to find nearby networks.
+pub const WIFI_SCAN_DURATION: Duration = Duration::from_secs(3);
+
+/// How long to wait for an HTTP server to respond.
+pub const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Most posts sent to the webhook per `WEBHOOK_RATE_WINDOW`. Further posts are dropped.
+pub const WEBHOOK_RATE_LIMIT: usize = 5;
+pub const WEBHOOK_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// How often to check whether a game started or stopped, while the webhook is enabled.
+pub const GAME_INFO_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often paused background jobs check whether they may resume.
+pub const BACKGROUND_JOB_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long throttled background jobs wait between steps.
+pub const BACKGROUND_JOB_THROTTLE_DELAY: Duration = Duration::from_secs(1);
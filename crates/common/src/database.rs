@@ -1,15 +1,21 @@
 use std::{
+    collections::{HashMap, HashSet},
+    fs,
     path::{Path, PathBuf},
     rc::Rc,
 };
 
 use anyhow::{Context, Result};
-use chrono::{Duration, NaiveDate};
+use chrono::{Duration, NaiveDate, Weekday};
 use log::{info, trace};
 use rusqlite::{Connection, OptionalExtension, Row, params};
 use rusqlite_migration::{M, Migrations};
+use serde::{Deserialize, Serialize};
+use strum::FromRepr;
 
 use crate::constants::{ALLIUM_BASE_DIR, ALLIUM_DATABASE};
+use crate::platform::cpu::CpuProfile;
+use crate::storage::{self, Volume};
 
 #[derive(Debug, Clone, Default)]
 pub struct Database {
@@ -32,6 +38,149 @@ pub struct Game {
     pub genres: Vec<String>,
     pub favorite: bool,
     pub screenshot_path: Option<PathBuf>,
+    /// ID of the second storage device the game is on, or `None` if it's on the SD card.
+    pub volume: Option<String>,
+}
+
+/// A podcast feed or audio stream that the user has subscribed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Podcast {
+    pub url: String,
+    pub title: String,
+    /// Whether the URL is an audio stream rather than an RSS feed.
+    pub is_stream: bool,
+}
+
+/// A battery level sample logged by alliumd.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatterySample {
+    /// Unix timestamp, in seconds.
+    pub timestamp: i64,
+    pub percentage: i32,
+    pub charging: bool,
+    /// Path of the game being played when the sample was taken, if any.
+    pub game: Option<PathBuf>,
+}
+
+/// A session of playing a game, logged by alliumd when the game exits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaySession {
+    pub path: PathBuf,
+    /// Unix timestamp of the start of the session, in seconds.
+    pub start: i64,
+    pub play_time: Duration,
+}
+
+/// Library data that only exists in the database, written by `Database::export_json` so that
+/// it survives reflashing or moving to another SD card.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct Backup {
+    /// Favorites first, in the order set by the user.
+    games: Vec<GameBackup>,
+    collections: Vec<CollectionBackup>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct GameBackup {
+    path: PathBuf,
+    name: String,
+    favorite: bool,
+    play_count: i64,
+    /// Play time, in seconds.
+    play_time: i64,
+    last_played: i64,
+    core: Option<String>,
+}
+
+impl From<Game> for GameBackup {
+    fn from(game: Game) -> Self {
+        Self {
+            path: game.path,
+            name: game.name,
+            favorite: game.favorite,
+            play_count: game.play_count,
+            play_time: game.play_time.num_seconds(),
+            last_played: game.last_played,
+            core: game.core,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CollectionBackup {
+    name: String,
+    /// Game paths, in collection order.
+    paths: Vec<PathBuf>,
+}
+
+/// When a game in the recents was last played, relative to the current day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, FromRepr)]
+pub enum PlayedPeriod {
+    Today,
+    Yesterday,
+    /// In the past week, before yesterday.
+    ThisWeek,
+    /// Over a week ago, or before play times were recorded.
+    Earlier,
+}
+
+impl PlayedPeriod {
+    pub fn locale_key(self) -> &'static str {
+        match self {
+            PlayedPeriod::Today => "recents-today",
+            PlayedPeriod::Yesterday => "recents-yesterday",
+            PlayedPeriod::ThisWeek => "recents-this-week",
+            PlayedPeriod::Earlier => "recents-earlier",
+        }
+    }
+}
+
+/// A change to the library that can be undone for a while after it's made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoAction {
+    RemoveFromRecents,
+    Hide,
+    /// Games were deleted, and their files moved to the trash.
+    Delete,
+}
+
+impl UndoAction {
+    fn key(self) -> &'static str {
+        match self {
+            UndoAction::RemoveFromRecents => "remove-from-recents",
+            UndoAction::Hide => "hide",
+            UndoAction::Delete => "delete",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        [
+            UndoAction::RemoveFromRecents,
+            UndoAction::Hide,
+            UndoAction::Delete,
+        ]
+        .into_iter()
+        .find(|action| action.key() == key)
+    }
+
+    /// Text of the toast shown after the change.
+    pub fn locale_key(self) -> &'static str {
+        match self {
+            UndoAction::RemoveFromRecents => "undo-removed-from-recents",
+            UndoAction::Hide => "undo-hidden",
+            UndoAction::Delete => "undo-deleted",
+        }
+    }
+}
+
+/// A change that was undone. Its rows are restored already, but its files are still in the
+/// trash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Undo {
+    pub action: UndoAction,
+    /// Original path and trash path of each file that was moved to the trash.
+    pub files: Vec<(PathBuf, PathBuf)>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -155,12 +304,129 @@ ALTER TABLE games ADD COLUMN favorite INTEGER NOT NULL DEFAULT 0;
         M::up("
 ALTER TABLE games ADD COLUMN screenshot_path TEXT;
 "),
+        M::up("
+ALTER TABLE games ADD COLUMN favorite_order INTEGER NOT NULL DEFAULT 0;
+"),
+        M::up("
+CREATE TABLE IF NOT EXISTS queue (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL UNIQUE,
+    position INTEGER NOT NULL
+);"),
+        M::up("
+CREATE TABLE IF NOT EXISTS cheats (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL,
+    cheat_file TEXT NOT NULL,
+    cheat_index INTEGER NOT NULL,
+    UNIQUE(path, cheat_file, cheat_index)
+);"),
+        M::up("
+CREATE TABLE IF NOT EXISTS collections (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE IF NOT EXISTS collection_games (
+    id INTEGER PRIMARY KEY,
+    collection_id INTEGER NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    UNIQUE(collection_id, path)
+);"),
+        M::up("
+CREATE TABLE IF NOT EXISTS podcasts (
+    id INTEGER PRIMARY KEY,
+    url TEXT NOT NULL UNIQUE,
+    title TEXT NOT NULL,
+    is_stream INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS media_positions (
+    url TEXT PRIMARY KEY,
+    position INTEGER NOT NULL
+);"),
+        M::up("
+CREATE TABLE IF NOT EXISTS battery_history (
+    id INTEGER PRIMARY KEY,
+    timestamp INTEGER NOT NULL,
+    percentage INTEGER NOT NULL,
+    charging INTEGER NOT NULL,
+    game TEXT
+);
+CREATE INDEX IF NOT EXISTS battery_history_timestamp ON battery_history(timestamp);"),
+        M::up("ALTER TABLE games ADD COLUMN cpu_profile INTEGER;"),
+        M::up("
+CREATE TABLE IF NOT EXISTS play_time_budgets (
+    weekday INTEGER PRIMARY KEY,
+    minutes INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS daily_play_time (
+    date TEXT PRIMARY KEY,
+    play_time INTEGER NOT NULL
+);"),
+        M::up("
+CREATE TABLE IF NOT EXISTS play_sessions (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL,
+    start INTEGER NOT NULL,
+    play_time INTEGER NOT NULL
+);"),
+        M::up("ALTER TABLE directories ADD COLUMN fingerprint INTEGER;"),
+        M::up("
+CREATE TABLE IF NOT EXISTS checksums (
+    path TEXT PRIMARY KEY,
+    size INTEGER NOT NULL,
+    modified INTEGER NOT NULL,
+    crc32 INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS hidden_games (
+    path TEXT PRIMARY KEY
+);"),
+        M::up("
+CREATE TABLE IF NOT EXISTS folder_sorts (
+    path TEXT PRIMARY KEY,
+    sort TEXT NOT NULL
+);"),
+        M::up("
+CREATE TABLE IF NOT EXISTS custom_images (
+    path TEXT PRIMARY KEY,
+    image TEXT NOT NULL
+);"),
+        M::up("ALTER TABLE games ADD COLUMN volume TEXT;"),
+        M::up("
+CREATE TABLE IF NOT EXISTS undo_actions (
+    id INTEGER PRIMARY KEY,
+    action TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS undo_paths (
+    undo_id INTEGER NOT NULL,
+    path TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS undo_rows (
+    undo_id INTEGER NOT NULL,
+    table_name TEXT NOT NULL,
+    row TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS trashed_files (
+    undo_id INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    trash_path TEXT NOT NULL
+);"),
+        // Games played before this are dated by their last play session, if it was logged
+        M::up("
+ALTER TABLE games ADD COLUMN last_played_at INTEGER NOT NULL DEFAULT 0;
+UPDATE games SET last_played_at = COALESCE(
+    (SELECT MAX(start + play_time) FROM play_sessions WHERE play_sessions.path = games.path),
+    0
+) WHERE last_played > 0;"),
+        // Cheats that were recorded before this were all enabled
+        M::up("ALTER TABLE cheats ADD COLUMN enabled INTEGER NOT NULL DEFAULT 1;"),
                 ])
     }
 
     pub fn reset_game(&self, path: &Path) -> Result<()> {
         self.conn.as_ref().unwrap().execute(
-            "UPDATE games SET play_count = 0, play_time = 0, last_played = 0 WHERE path = ?",
+            "UPDATE games SET play_count = 0, play_time = 0, last_played = 0, last_played_at = 0 WHERE path = ?",
             params![path.display().to_string()],
         )?;
         Ok(())
@@ -181,33 +447,248 @@ ALTER TABLE games ADD COLUMN screenshot_path TEXT;
         Ok(())
     }
 
+    /// Moves a game to a new path, along with everything referring to it, such as its queue
+    /// position, collections and custom image.
     pub fn update_game_path(&self, old: &Path, new: &Path) -> Result<()> {
-        let mut stmt = self
-            .conn
-            .as_ref()
-            .unwrap()
-            .prepare("UPDATE games SET path = ? WHERE path = ?")?;
-        stmt.execute(params![
-            new.display().to_string(),
-            old.display().to_string()
-        ])?;
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?; // safe because single-threaded
+        update_game_path(&tx, old, new)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Gives the games on the mounted storage device `id` their paths back, after moving aside
+    /// the games of other devices that were mounted at the same place. Games at the same path on
+    /// different devices keep their own play time, collections and so on.
+    pub fn switch_volume(&self, id: &str) -> Result<()> {
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?; // safe because single-threaded
+        let games = {
+            let mut stmt = tx.prepare("SELECT path, volume FROM games WHERE volume IS NOT NULL")?;
+            stmt.query_map([], |row| {
+                Ok((
+                    PathBuf::from(row.get::<_, String>(0)?),
+                    row.get::<_, String>(1)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+        };
+
+        // Others' games are moved first, so that the paths are free for the mounted device's
+        for (path, volume) in games.iter().filter(|(_, volume)| volume != id) {
+            if let Some(parked) = storage::parked_path(volume, path) {
+                update_game_path(&tx, path, &parked)?;
+            }
+        }
+        for (path, volume) in games.iter().filter(|(_, volume)| volume == id) {
+            if let Some(unparked) = storage::unparked_path(volume, path) {
+                update_game_path(&tx, path, &unparked)?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Moves a game to a new path, like `update_game_path`, along with its other files that
+    /// were moved with it, such as its guide.
+    pub fn move_game(&self, old: &Path, new: &Path, files: &[(PathBuf, PathBuf)]) -> Result<()> {
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?; // safe because single-threaded
+        update_game_path(&tx, old, new)?;
+        for (old, new) in files {
+            update_game_path(&tx, old, new)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Moves a game to a new path and renames it, along with its other files that were renamed
+    /// with it. Everything is done at once, so that a failure leaves the game as it was.
+    pub fn rename_game(
+        &self,
+        old: &Path,
+        new: &Path,
+        name: &str,
+        files: &[(PathBuf, PathBuf)],
+    ) -> Result<()> {
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?; // safe because single-threaded
+        tx.execute(
+            "UPDATE games SET name = ? WHERE path = ?",
+            params![name, old.display().to_string()],
+        )?;
+        update_game_path(&tx, old, new)?;
+        for (old, new) in files {
+            update_game_path(&tx, old, new)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Deletes a game along with everything referring to it. Unlike `delete_game`, which is for
+    /// games that went missing and may come back, this is for games the user deleted.
+    pub fn purge_game(&self, path: &Path) -> Result<()> {
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?; // safe because single-threaded
+        purge_game(&tx, path)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Deletes a game that was moved to the trash, along with everything referring to it. The
+    /// trashed files are remembered, to be put back if the deletion is undone.
+    pub fn trash_game(
+        &self,
+        undo_id: i64,
+        path: &Path,
+        files: &[(PathBuf, PathBuf)],
+    ) -> Result<()> {
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?; // safe because single-threaded
+        for (file, trash_path) in files {
+            tx.execute(
+                "INSERT INTO trashed_files (undo_id, path, trash_path) VALUES (?, ?, ?)",
+                params![
+                    undo_id,
+                    file.display().to_string(),
+                    trash_path.display().to_string()
+                ],
+            )?;
+        }
+        purge_game(&tx, path)?;
+        tx.commit()?;
         Ok(())
     }
 
+    /// Remembers everything referring to the games, before a change to them that can be undone.
+    /// Rows aren't deleted for good until the change expires, as they're kept here.
+    pub fn push_undo(&self, action: UndoAction, paths: &[&Path]) -> Result<i64> {
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?; // safe because single-threaded
+        tx.execute(
+            "INSERT INTO undo_actions (action, created_at) VALUES (?, strftime('%s', 'now'))",
+            [action.key()],
+        )?;
+        let undo_id = tx.last_insert_rowid();
+
+        for table in GAME_PATH_TABLES {
+            let columns = table_columns(&tx, table)?;
+            let row = columns
+                .iter()
+                .map(|column| format!("'{column}', {column}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let mut stmt = tx.prepare(&format!(
+                "INSERT INTO undo_rows (undo_id, table_name, row)
+SELECT ?, '{table}', json_object({row}) FROM {table} WHERE path = ?"
+            ))?;
+            for path in paths {
+                stmt.execute(params![undo_id, path.display().to_string()])?;
+            }
+        }
+
+        let mut stmt = tx.prepare("INSERT INTO undo_paths (undo_id, path) VALUES (?, ?)")?;
+        for path in paths {
+            stmt.execute(params![undo_id, path.display().to_string()])?;
+        }
+        drop(stmt);
+
+        tx.commit()?;
+        Ok(undo_id)
+    }
+
+    /// Undoes the last change, putting back the rows referring to its games as they were before
+    /// it. Returns `None` if there's no change to undo.
+    pub fn pop_undo(&self) -> Result<Option<Undo>> {
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?; // safe because single-threaded
+        let Some((undo_id, action)) = tx
+            .query_row(
+                "SELECT id, action FROM undo_actions ORDER BY id DESC LIMIT 1",
+                [],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        for table in GAME_PATH_TABLES {
+            tx.execute(
+                &format!(
+                    "DELETE FROM {table} WHERE path IN (SELECT path FROM undo_paths WHERE undo_id = ?)"
+                ),
+                [undo_id],
+            )?;
+            let columns = table_columns(&tx, table)?;
+            let values = columns
+                .iter()
+                .map(|column| format!("json_extract(row, '$.{column}')"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            tx.execute(
+                &format!(
+                    "INSERT INTO {table} ({}) SELECT {values} FROM undo_rows WHERE undo_id = ? AND table_name = ?",
+                    columns.join(", ")
+                ),
+                params![undo_id, table],
+            )?;
+        }
+
+        let files = {
+            let mut stmt =
+                tx.prepare("SELECT path, trash_path FROM trashed_files WHERE undo_id = ?")?;
+            stmt.query_map([undo_id], |row| {
+                Ok((
+                    PathBuf::from(row.get::<_, String>(0)?),
+                    PathBuf::from(row.get::<_, String>(1)?),
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+        };
+
+        delete_undo(&tx, undo_id)?;
+        tx.commit()?;
+
+        let action = UndoAction::from_key(&action)
+            .with_context(|| format!("unknown undo action: {action}"))?;
+        Ok(Some(Undo { action, files }))
+    }
+
+    /// Forgets the changes made before `max_age` ago, so that they can't be undone anymore.
+    /// Returns their IDs, whose files can be removed from the trash.
+    pub fn expire_undo(&self, max_age: std::time::Duration) -> Result<Vec<i64>> {
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?; // safe because single-threaded
+        let ids = {
+            let mut stmt = tx.prepare(
+                "SELECT id FROM undo_actions WHERE created_at <= strftime('%s', 'now') - ?",
+            )?;
+            stmt.query_map([max_age.as_secs() as i64], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<i64>>>()?
+        };
+        for undo_id in &ids {
+            delete_undo(&tx, *undo_id)?;
+        }
+        tx.commit()?;
+        Ok(ids)
+    }
+
     pub fn update_games(&self, games: &[NewGame]) -> Result<()> {
+        // Images chosen on the device take precedence over box art found when populating
+        let custom_images = self.select_custom_images()?;
+        // Games on the second storage device are tagged with it, so they're kept while it's removed
+        let volume = Volume::load().map(|volume| volume.id);
+
         let tx = self.conn.as_ref().unwrap().unchecked_transaction()?; // safe because single-threaded
 
         let mut stmt = tx.prepare(
             "
-INSERT INTO games (name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres)
-VALUES (?, ?, ?, 0, 0, 0, ?, ?, ?, ?, ?, ?)
-ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, release_date = ?, developer = ?, publisher = ?, genres = ?",
+INSERT INTO games (name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, volume)
+VALUES (?, ?, ?, 0, 0, 0, ?, ?, ?, ?, ?, ?, ?)
+ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, release_date = ?, developer = ?, publisher = ?, genres = ?, volume = ?",
         )?;
 
         for game in games {
             let path = game.path.display().to_string();
-            let image = game.image.as_ref().map(|p| p.display().to_string());
+            let image = custom_images
+                .get(&game.path)
+                .or(game.image.as_ref())
+                .map(|p| p.display().to_string());
             let genres = serde_json::to_string(&game.genres)?;
+            let volume = volume.as_ref().filter(|_| storage::is_external(&game.path));
             stmt.execute(params![
                 game.name,
                 path,
@@ -218,6 +699,7 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
                 game.developer,
                 game.publisher,
                 genres,
+                volume,
                 game.name,
                 image,
                 game.core,
@@ -226,6 +708,7 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
                 game.developer,
                 game.publisher,
                 genres,
+                volume,
             ])?;
         }
 
@@ -242,7 +725,7 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
             .conn
             .as_ref()
             .unwrap()
-            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path FROM games WHERE last_played > 0 ORDER BY play_time DESC LIMIT ?")?;
+            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path, volume FROM games WHERE last_played > 0 ORDER BY play_time DESC LIMIT ?")?;
 
         let results = stmt
             .query_map([limit], map_game)?
@@ -258,7 +741,7 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
             .conn
             .as_ref()
             .unwrap()
-            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path FROM games WHERE last_played > 0 ORDER BY last_played DESC LIMIT ?")?;
+            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path, volume FROM games WHERE last_played > 0 ORDER BY last_played DESC LIMIT ?")?;
 
         let results = stmt
             .query_map([limit], map_game)?
@@ -268,13 +751,55 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
         Ok(results)
     }
 
+    /// Selects played games sorted by most recently played first, each with when it was last
+    /// played in local time. They're sorted by the same time they're grouped by, so that each
+    /// period is listed once; games played in the future after a clock change count as today.
+    pub fn select_last_played_by_period(&self, limit: i64) -> Result<Vec<(Game, PlayedPeriod)>> {
+        let mut stmt = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .prepare("
+SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path, volume,
+    CASE
+        WHEN last_played_at = 0 THEN 3
+        WHEN date(last_played_at, 'unixepoch', 'localtime') >= date('now', 'localtime') THEN 0
+        WHEN date(last_played_at, 'unixepoch', 'localtime') = date('now', 'localtime', '-1 day') THEN 1
+        WHEN date(last_played_at, 'unixepoch', 'localtime') > date('now', 'localtime', '-7 days') THEN 2
+        ELSE 3
+    END
+FROM games WHERE last_played > 0 ORDER BY last_played_at DESC, last_played DESC LIMIT ?")?;
+
+        let results = stmt
+            .query_map([limit], |row| {
+                let period = PlayedPeriod::from_repr(row.get::<_, i64>(15)? as usize)
+                    .unwrap_or(PlayedPeriod::Earlier);
+                Ok((map_game(row)?, period))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Removes games from the recents that haven't been played in the number of days, keeping
+    /// their play counts and times. Returns how many were removed.
+    pub fn prune_recents(&self, max_age_days: u32) -> Result<usize> {
+        let removed = self.conn.as_ref().unwrap().execute(
+            "UPDATE games SET last_played = 0 WHERE last_played > 0 AND last_played_at > 0 AND last_played_at <= strftime('%s', 'now') - ?",
+            [i64::from(max_age_days) * 24 * 60 * 60],
+        )?;
+        info!("pruned {} games from the recents", removed);
+        Ok(removed)
+    }
+
     /// Selects played games sorted by highest rating first.
     pub fn select_by_rating(&self, limit: i64) -> Result<Vec<Game>> {
         let mut stmt = self
             .conn
             .as_ref()
             .unwrap()
-            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path FROM games ORDER BY rating DESC LIMIT ?")?;
+            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path, volume FROM games ORDER BY rating DESC LIMIT ?")?;
 
         let results = stmt
             .query_map([limit], map_game)?
@@ -290,7 +815,7 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
             .conn
             .as_ref()
             .unwrap()
-            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path FROM games ORDER BY release_date DESC LIMIT ?")?;
+            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path, volume FROM games ORDER BY release_date DESC LIMIT ?")?;
 
         let results = stmt
             .query_map([limit], map_game)?
@@ -306,7 +831,7 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
             .conn
             .as_ref()
             .unwrap()
-            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path FROM games WHERE id IN (SELECT id FROM games ORDER BY RANDOM() LIMIT ?)")?;
+            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path, volume FROM games WHERE id IN (SELECT id FROM games ORDER BY RANDOM() LIMIT ?)")?;
 
         let results = stmt
             .query_map([limit], map_game)?
@@ -316,13 +841,13 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
         Ok(results)
     }
 
-    /// Selects favorite games.
+    /// Selects favorite games, in the order set by the user.
     pub fn select_favorites(&self, limit: i64) -> Result<Vec<Game>> {
         let mut stmt = self
             .conn
             .as_ref()
             .unwrap()
-            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path FROM games WHERE favorite = 1 ORDER BY last_played DESC LIMIT ?")?;
+            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path, volume FROM games WHERE favorite = 1 ORDER BY favorite_order ASC, last_played DESC LIMIT ?")?;
 
         let results = stmt
             .query_map([limit], map_game)?
@@ -332,6 +857,181 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
         Ok(results)
     }
 
+    /// Selects games in the "Up next" queue, in queue order.
+    pub fn select_queue(&self, limit: i64) -> Result<Vec<Game>> {
+        let mut stmt = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .prepare("SELECT games.name, games.path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path, volume FROM queue JOIN games ON games.path = queue.path ORDER BY queue.position ASC LIMIT ?")?;
+
+        let results = stmt
+            .query_map([limit], map_game)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Selects the games in a collection, in collection order.
+    pub fn select_collection(&self, name: &str, limit: i64) -> Result<Vec<Game>> {
+        let mut stmt = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .prepare("SELECT games.name, games.path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path, volume FROM collection_games JOIN collections ON collections.id = collection_games.collection_id JOIN games ON games.path = collection_games.path WHERE collections.name = ? ORDER BY collection_games.position ASC LIMIT ?")?;
+
+        let results = stmt
+            .query_map(params![name, limit], map_game)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Returns the names of all collections, sorted by name.
+    pub fn select_collections(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .prepare("SELECT name FROM collections ORDER BY name COLLATE NOCASE ASC")?;
+
+        let results = stmt
+            .query_map([], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Replaces the games in a collection, creating the collection if it doesn't exist.
+    pub fn set_collection(&self, name: &str, paths: &[&Path]) -> Result<()> {
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?; // safe because single-threaded
+
+        tx.execute(
+            "INSERT INTO collections (name) VALUES (?) ON CONFLICT(name) DO NOTHING",
+            [name],
+        )?;
+        let id: i64 = tx.query_row("SELECT id FROM collections WHERE name = ?", [name], |row| {
+            row.get(0)
+        })?;
+        tx.execute("DELETE FROM collection_games WHERE collection_id = ?", [id])?;
+
+        let mut stmt = tx.prepare(
+            "INSERT INTO collection_games (collection_id, path, position) VALUES (?, ?, ?) ON CONFLICT DO NOTHING",
+        )?;
+        for (i, path) in paths.iter().enumerate() {
+            stmt.execute(params![id, path.display().to_string(), i as i64])?;
+        }
+
+        drop(stmt);
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Writes favorites, play stats, the cores chosen for games and collections to a JSON file.
+    pub fn export_json(&self, path: &Path) -> Result<()> {
+        let mut games: Vec<GameBackup> = self
+            .select_favorites(i64::MAX)?
+            .into_iter()
+            .map(GameBackup::from)
+            .collect();
+        games.extend(
+            self.select_all_games()?
+                .into_iter()
+                .filter(|game| {
+                    !game.favorite
+                        && (game.play_count > 0
+                            || game.play_time > Duration::zero()
+                            || game.core.is_some())
+                })
+                .map(GameBackup::from),
+        );
+
+        let mut collections = Vec::new();
+        let mut stmt = self.conn.as_ref().unwrap().prepare(
+            "SELECT path FROM collection_games JOIN collections ON collections.id = collection_games.collection_id WHERE collections.name = ? ORDER BY position ASC",
+        )?;
+        for name in self.select_collections()? {
+            let paths = stmt
+                .query_map([&name], |row| row.get::<_, String>(0))?
+                .map(|path| path.map(PathBuf::from))
+                .collect::<rusqlite::Result<_>>()?;
+            collections.push(CollectionBackup { name, paths });
+        }
+
+        let backup = Backup { games, collections };
+        fs::write(path, serde_json::to_string_pretty(&backup)?)
+            .with_context(|| format!("failed to write backup to {}", path.display()))?;
+        info!(
+            "backed up {} games to {}",
+            backup.games.len(),
+            path.display()
+        );
+
+        Ok(())
+    }
+
+    /// Restores a backup written by `export_json`, overwriting the play stats and favorites of
+    /// the games in it. Games missing from both the database and the SD card are skipped.
+    /// Returns the number of games restored.
+    pub fn import_json(&self, path: &Path) -> Result<usize> {
+        let backup: Backup = serde_json::from_str(
+            &fs::read_to_string(path)
+                .with_context(|| format!("failed to read backup from {}", path.display()))?,
+        )?;
+
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?; // safe because single-threaded
+
+        let mut stmt = tx.prepare(
+            "
+INSERT INTO games (name, path, play_count, play_time, last_played, core, favorite, favorite_order)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+ON CONFLICT(path) DO UPDATE SET play_count = ?, play_time = ?, last_played = ?, core = COALESCE(?, core), favorite = ?, favorite_order = ?",
+        )?;
+        let mut restored = 0;
+        for (i, game) in backup.games.iter().enumerate() {
+            if self.select_game(&game.path)?.is_none() && !game.path.exists() {
+                continue;
+            }
+            stmt.execute(params![
+                game.name,
+                game.path.display().to_string(),
+                game.play_count,
+                game.play_time,
+                game.last_played,
+                game.core,
+                game.favorite,
+                i as i64,
+                game.play_count,
+                game.play_time,
+                game.last_played,
+                game.core,
+                game.favorite,
+                i as i64,
+            ])?;
+            restored += 1;
+        }
+        drop(stmt);
+
+        tx.commit()?;
+
+        for collection in &backup.collections {
+            let paths = collection
+                .paths
+                .iter()
+                .map(PathBuf::as_path)
+                .collect::<Vec<_>>();
+            self.set_collection(&collection.name, &paths)?;
+        }
+
+        info!("restored {} games from {}", restored, path.display());
+        Ok(restored)
+    }
+
     /// Search for games by name. The query is a prefix search on words, so "Fi" will match both "Fire Emblem" and "Pokemon Fire Red".
     pub fn search(&self, query: &str, limit: i64) -> Result<Vec<Game>> {
         if query.is_empty() {
@@ -340,7 +1040,7 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
 
         let conn = self.conn.as_ref().unwrap();
 
-        let mut stmt = conn.prepare("SELECT games.name, games.path, image, play_count, play_time, last_played, core, rating, release_date, games.developer, games.publisher, genres, favorite, screenshot_path FROM games JOIN games_fts ON games.id = games_fts.rowid WHERE games_fts MATCH ? LIMIT ?")?;
+        let mut stmt = conn.prepare("SELECT games.name, games.path, image, play_count, play_time, last_played, core, rating, release_date, games.developer, games.publisher, genres, favorite, screenshot_path, volume FROM games JOIN games_fts ON games.id = games_fts.rowid WHERE games_fts MATCH ? LIMIT ?")?;
 
         let query =
             format!("name:\"{query}\" * OR developer:\"{query}\" * OR publisher:\"{query}\" *");
@@ -356,7 +1056,7 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
         trace!("select_games_in_directory({:?})", path);
         let conn = self.conn.as_ref().unwrap();
 
-        let mut stmt = conn.prepare("SELECT games.name, games.path, image, play_count, play_time, last_played, core, rating, release_date, games.developer, games.publisher, genres, favorite, screenshot_path FROM games JOIN games_fts ON games.id = games_fts.rowid WHERE games_fts.path LIKE ? AND games_fts.path NOT LIKE ?")?;
+        let mut stmt = conn.prepare("SELECT games.name, games.path, image, play_count, play_time, last_played, core, rating, release_date, games.developer, games.publisher, genres, favorite, screenshot_path, volume FROM games JOIN games_fts ON games.id = games_fts.rowid WHERE games_fts.path LIKE ? AND games_fts.path NOT LIKE ?")?;
 
         let results = stmt
             .query_map(
@@ -377,7 +1077,7 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
             .conn
             .as_ref()
             .unwrap()
-            .query_row("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path FROM games WHERE path = ? LIMIT 1", [path.display().to_string()], map_game)
+            .query_row("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path, volume FROM games WHERE path = ? LIMIT 1", [path.display().to_string()], map_game)
             .optional()?;
 
         Ok(game)
@@ -388,7 +1088,7 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
             .conn
             .as_ref()
             .unwrap()
-            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path FROM games WHERE path = ? ORDER BY favorite DESC")?;
+            .prepare("SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path, volume FROM games WHERE path = ? ORDER BY favorite DESC")?;
 
         let mut results = vec![None; paths.len()];
         for (i, path) in paths.iter().enumerate() {
@@ -404,7 +1104,7 @@ ON CONFLICT(path) DO UPDATE SET name = ?, image = ?, core = ?, rating = ?, relea
 
     pub fn select_all_games(&self) -> Result<Vec<Game>> {
         let mut stmt = self.conn.as_ref().unwrap().prepare(
-            "SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path FROM games",
+            "SELECT name, path, image, play_count, play_time, last_played, core, rating, release_date, developer, publisher, genres, favorite, screenshot_path, volume FROM games",
         )?;
 
         let results = stmt
@@ -436,7 +1136,7 @@ ON CONFLICT(path) DO UPDATE SET play_count = play_count + 1;",
         )?;
 
         self.conn.as_ref().unwrap().execute(
-            "UPDATE games SET last_played = (SELECT MAX(last_played) FROM games) + 1 WHERE path = ?",
+            "UPDATE games SET last_played = (SELECT MAX(last_played) FROM games) + 1, last_played_at = strftime('%s', 'now') WHERE path = ?",
         [game.path.display().to_string()])?;
 
         Ok(())
@@ -452,15 +1152,228 @@ ON CONFLICT(path) DO UPDATE SET play_count = play_count + 1;",
         Ok(())
     }
 
-    /// Sets whether a game is a favorite.
+    /// Raises the play count and play time of a game to at least the given values. Does nothing
+    /// if the game doesn't exist.
+    pub fn merge_play_stats(
+        &self,
+        path: &Path,
+        play_count: i64,
+        play_time: Duration,
+    ) -> Result<()> {
+        self.conn.as_ref().unwrap().execute(
+            "UPDATE games SET play_count = MAX(play_count, ?), play_time = MAX(play_time, ?) WHERE path = ?",
+            params![play_count, play_time.num_seconds(), path.display().to_string()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Sets whether a game is a favorite. New favorites are placed at the end of the favorites order.
     pub fn set_favorite(&self, path: &Path, favorite: bool) -> Result<()> {
         self.conn.as_ref().unwrap().execute(
-            "UPDATE games SET favorite = ? WHERE path = ?",
+            "UPDATE games SET favorite = ?, favorite_order = (SELECT COALESCE(MAX(favorite_order), 0) + 1 FROM games WHERE favorite = 1) WHERE path = ?",
             params![if favorite { 1 } else { 0 }, path.display().to_string()],
         )?;
         Ok(())
     }
 
+    /// Sets the order of favorite games. Games are ordered by their position in `paths`.
+    pub fn set_favorite_order(&self, paths: &[&Path]) -> Result<()> {
+        let tx = self.conn.as_ref().unwrap().unchecked_transaction()?; // safe because single-threaded
+
+        let mut stmt = tx.prepare("UPDATE games SET favorite_order = ? WHERE path = ?")?;
+        for (i, path) in paths.iter().enumerate() {
+            stmt.execute(params![i as i64, path.display().to_string()])?;
+        }
+
+        drop(stmt);
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Adds a game to the end of the "Up next" queue. Does nothing if the game is already queued.
+    pub fn add_to_queue(&self, path: &Path) -> Result<()> {
+        self.conn.as_ref().unwrap().execute(
+            "INSERT INTO queue (path, position) VALUES (?, (SELECT COALESCE(MAX(position), 0) + 1 FROM queue)) ON CONFLICT(path) DO NOTHING",
+            [path.display().to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Removes a game from the "Up next" queue, advancing the queue if it was at the head.
+    pub fn remove_from_queue(&self, path: &Path) -> Result<()> {
+        self.conn.as_ref().unwrap().execute(
+            "DELETE FROM queue WHERE path = ?",
+            [path.display().to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Returns whether a game is in the "Up next" queue.
+    pub fn is_queued(&self, path: &Path) -> Result<bool> {
+        let queued = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .query_row(
+                "SELECT 1 FROM queue WHERE path = ?",
+                [path.display().to_string()],
+                |_| Ok(()),
+            )
+            .optional()?;
+
+        Ok(queued.is_some())
+    }
+
+    /// Returns the cheats in a cheat file that were toggled for a game, by index, with whether
+    /// they are enabled. Cheats that were never toggled aren't returned.
+    pub fn select_cheats(&self, path: &Path, cheat_file: &Path) -> Result<Vec<(usize, bool)>> {
+        let conn = self.conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT cheat_index, enabled FROM cheats WHERE path = ? AND cheat_file = ? ORDER BY cheat_index",
+        )?;
+        let cheats = stmt
+            .query_map(
+                [path.display().to_string(), cheat_file.display().to_string()],
+                |row| Ok((row.get::<_, i64>(0)? as usize, row.get(1)?)),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(cheats)
+    }
+
+    /// Records whether a cheat in a cheat file is enabled for a game.
+    pub fn set_cheat_enabled(
+        &self,
+        path: &Path,
+        cheat_file: &Path,
+        index: usize,
+        enabled: bool,
+    ) -> Result<()> {
+        self.conn.as_ref().unwrap().execute(
+            "INSERT INTO cheats (path, cheat_file, cheat_index, enabled) VALUES (?, ?, ?, ?)
+            ON CONFLICT(path, cheat_file, cheat_index) DO UPDATE SET enabled = excluded.enabled",
+            params![
+                path.display().to_string(),
+                cheat_file.display().to_string(),
+                index as i64,
+                enabled
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Returns all subscribed podcasts and streams, sorted by title.
+    pub fn select_podcasts(&self) -> Result<Vec<Podcast>> {
+        let mut stmt = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .prepare("SELECT url, title, is_stream FROM podcasts ORDER BY title COLLATE NOCASE ASC")?;
+
+        let results = stmt
+            .query_map([], |row| {
+                Ok(Podcast {
+                    url: row.get(0)?,
+                    title: row.get(1)?,
+                    is_stream: row.get(2)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Subscribes to a podcast or stream, updating the title if already subscribed.
+    pub fn add_podcast(&self, podcast: &Podcast) -> Result<()> {
+        self.conn.as_ref().unwrap().execute(
+            "INSERT INTO podcasts (url, title, is_stream) VALUES (?, ?, ?) ON CONFLICT(url) DO UPDATE SET title = excluded.title, is_stream = excluded.is_stream",
+            params![podcast.url, podcast.title, podcast.is_stream],
+        )?;
+        Ok(())
+    }
+
+    /// Unsubscribes from a podcast or stream.
+    pub fn delete_podcast(&self, url: &str) -> Result<()> {
+        self.conn
+            .as_ref()
+            .unwrap()
+            .execute("DELETE FROM podcasts WHERE url = ?", [url])?;
+        Ok(())
+    }
+
+    /// Returns the saved playback position of a media URL, or zero if never played.
+    pub fn get_media_position(&self, url: &str) -> Result<Duration> {
+        let position: Option<i64> = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .query_row(
+                "SELECT position FROM media_positions WHERE url = ?",
+                [url],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(Duration::seconds(position.unwrap_or(0)))
+    }
+
+    /// Saves the playback position of a media URL.
+    pub fn set_media_position(&self, url: &str, position: Duration) -> Result<()> {
+        self.conn.as_ref().unwrap().execute(
+            "INSERT INTO media_positions (url, position) VALUES (?, ?) ON CONFLICT(url) DO UPDATE SET position = excluded.position",
+            params![url, position.num_seconds()],
+        )?;
+        Ok(())
+    }
+
+    /// Logs a battery sample, deleting samples older than `retention`.
+    pub fn add_battery_sample(
+        &self,
+        sample: &BatterySample,
+        retention: std::time::Duration,
+    ) -> Result<()> {
+        let conn = self.conn.as_ref().unwrap();
+        conn.execute(
+            "INSERT INTO battery_history (timestamp, percentage, charging, game) VALUES (?, ?, ?, ?)",
+            params![
+                sample.timestamp,
+                sample.percentage,
+                sample.charging,
+                sample.game.as_ref().map(|p| p.display().to_string()),
+            ],
+        )?;
+        conn.execute(
+            "DELETE FROM battery_history WHERE timestamp < ?",
+            [sample.timestamp - retention.as_secs() as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Returns the battery samples taken since the given Unix timestamp, oldest first.
+    pub fn select_battery_history(&self, since: i64) -> Result<Vec<BatterySample>> {
+        let mut stmt = self.conn.as_ref().unwrap().prepare(
+            "SELECT timestamp, percentage, charging, game FROM battery_history WHERE timestamp >= ? ORDER BY timestamp ASC",
+        )?;
+
+        let results = stmt
+            .query_map([since], |row| {
+                Ok(BatterySample {
+                    timestamp: row.get(0)?,
+                    percentage: row.get(1)?,
+                    charging: row.get(2)?,
+                    game: row.get::<_, Option<String>>(3)?.map(PathBuf::from),
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(results)
+    }
+
     pub fn get_guide_cursor(&self, path: &Path) -> Result<u64> {
         let cursor = self
             .conn
@@ -516,6 +1429,59 @@ ON CONFLICT(path) DO UPDATE SET play_count = play_count + 1;",
         Ok(())
     }
 
+    /// Deletes the games in a directory, but not its subdirectories, that have no play time,
+    /// play count.
+    pub fn delete_unplayed_games_in_directory(&self, path: &Path) -> Result<()> {
+        trace!("delete_unplayed_games_in_directory({:?})", path);
+        // Paths are compared by prefix, as LIKE treats `_` and `%` in them as wildcards and
+        // ignores case
+        let prefix = format!("{}/", path.display());
+        self.conn.as_ref().unwrap().execute(
+            "DELETE FROM games WHERE last_played = 0 AND play_time = 0 AND substr(path, 1, ?1) = ?2 AND instr(substr(path, ?1 + 1), '/') = 0",
+            params![prefix.chars().count() as i64, prefix],
+        )?;
+
+        Ok(())
+    }
+
+    /// Deletes a directory, along with its gamelist fingerprint.
+    pub fn delete_directory(&self, path: &Path) -> Result<()> {
+        trace!("delete_directory({:?})", path);
+        self.conn.as_ref().unwrap().execute(
+            "DELETE FROM directories WHERE path = ?",
+            [path.display().to_string()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns every known directory, with the fingerprint of its contents from the last scan.
+    pub fn select_directory_fingerprints(&self) -> Result<HashMap<PathBuf, Option<u64>>> {
+        let conn = self.conn.as_ref().unwrap();
+        let mut stmt = conn.prepare("SELECT path, fingerprint FROM directories")?;
+        let directories = stmt
+            .query_map([], |row| {
+                Ok((
+                    PathBuf::from(row.get::<_, String>(0)?),
+                    // Stored as a signed integer, as SQLite has no unsigned integers
+                    row.get::<_, Option<i64>>(1)?.map(|f| f as u64),
+                ))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+
+        Ok(directories)
+    }
+
+    pub fn set_directory_fingerprint(&self, path: &Path, fingerprint: u64) -> Result<()> {
+        trace!("set_directory_fingerprint({:?}, {})", path, fingerprint);
+        self.conn.as_ref().unwrap().execute(
+            "INSERT INTO directories (path, fingerprint) VALUES (?, ?) ON CONFLICT(path) DO UPDATE SET fingerprint = ?",
+            params![path.display().to_string(), fingerprint as i64, fingerprint as i64],
+        )?;
+
+        Ok(())
+    }
+
     pub fn set_has_indexed(&self, has_indexed: bool) -> Result<()> {
         self
             .conn
@@ -568,6 +1534,122 @@ ON CONFLICT(path) DO UPDATE SET play_count = play_count + 1;",
         Ok(fingerprint)
     }
 
+    /// Returns the cached CRC32 of a file, if it was computed when the file had the same size
+    /// and modification time.
+    pub fn get_crc32(&self, path: &Path, size: u64, modified: i64) -> Result<Option<u32>> {
+        let crc32 = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .query_row(
+                "SELECT crc32 FROM checksums WHERE path = ? AND size = ? AND modified = ?",
+                params![path.display().to_string(), size as i64, modified],
+                |row| row.get::<_, u32>(0),
+            )
+            .optional()?;
+
+        Ok(crc32)
+    }
+
+    pub fn set_crc32(&self, path: &Path, size: u64, modified: i64, crc32: u32) -> Result<()> {
+        self.conn.as_ref().unwrap().execute(
+            "INSERT INTO checksums (path, size, modified, crc32) VALUES (?, ?, ?, ?) ON CONFLICT(path) DO UPDATE SET size = ?, modified = ?, crc32 = ?",
+            params![
+                path.display().to_string(),
+                size as i64,
+                modified,
+                crc32,
+                size as i64,
+                modified,
+                crc32
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the games hidden from the games list.
+    pub fn select_hidden_games(&self) -> Result<HashSet<PathBuf>> {
+        let conn = self.conn.as_ref().unwrap();
+        let mut stmt = conn.prepare("SELECT path FROM hidden_games")?;
+        let paths = stmt
+            .query_map([], |row| Ok(PathBuf::from(row.get::<_, String>(0)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        Ok(paths)
+    }
+
+    pub fn set_hidden(&self, path: &Path, hidden: bool) -> Result<()> {
+        let query = if hidden {
+            "INSERT OR IGNORE INTO hidden_games (path) VALUES (?)"
+        } else {
+            "DELETE FROM hidden_games WHERE path = ?"
+        };
+        self.conn
+            .as_ref()
+            .unwrap()
+            .execute(query, [path.display().to_string()])?;
+
+        Ok(())
+    }
+
+    /// Returns the sort last used in a folder, if it was sorted differently from its parent.
+    pub fn get_folder_sort(&self, path: &Path) -> Result<Option<String>> {
+        let sort = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .query_row(
+                "SELECT sort FROM folder_sorts WHERE path = ?",
+                [path.display().to_string()],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?;
+
+        Ok(sort)
+    }
+
+    pub fn set_folder_sort(&self, path: &Path, sort: &str) -> Result<()> {
+        self.conn.as_ref().unwrap().execute(
+            "INSERT INTO folder_sorts (path, sort) VALUES (?, ?) ON CONFLICT(path) DO UPDATE SET sort = ?",
+            params![path.display().to_string(), sort, sort],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the images chosen for games on the device, by game path.
+    pub fn select_custom_images(&self) -> Result<HashMap<PathBuf, PathBuf>> {
+        let conn = self.conn.as_ref().unwrap();
+        let mut stmt = conn.prepare("SELECT path, image FROM custom_images")?;
+        let images = stmt
+            .query_map([], |row| {
+                Ok((
+                    PathBuf::from(row.get::<_, String>(0)?),
+                    PathBuf::from(row.get::<_, String>(1)?),
+                ))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+
+        Ok(images)
+    }
+
+    /// Sets the image shown for a game, which is kept when the database is repopulated.
+    pub fn set_custom_image(&self, path: &Path, image: &Path) -> Result<()> {
+        let path = path.display().to_string();
+        let image = image.display().to_string();
+        self.conn.as_ref().unwrap().execute(
+            "INSERT INTO custom_images (path, image) VALUES (?, ?) ON CONFLICT(path) DO UPDATE SET image = ?",
+            params![path, image, image],
+        )?;
+        self.conn.as_ref().unwrap().execute(
+            "UPDATE games SET image = ? WHERE path = ?",
+            params![image, path],
+        )?;
+
+        Ok(())
+    }
+
     pub fn get_core(&self, path: &Path) -> Result<Option<String>> {
         let core = self
             .conn
@@ -592,71 +1674,265 @@ ON CONFLICT(path) DO UPDATE SET play_count = play_count + 1;",
 
         Ok(())
     }
-}
-
-fn map_game(row: &Row<'_>) -> rusqlite::Result<Game> {
-    Ok(Game {
-        name: row.get(0)?,
-        path: PathBuf::from(row.get::<_, String>(1)?),
-        image: row.get::<_, Option<String>>(2)?.map(PathBuf::from),
-        play_count: row.get(3)?,
-        play_time: Duration::seconds(row.get(4)?),
-        last_played: row.get(5)?,
-        core: row.get(6)?,
-        rating: row.get(7)?,
-        release_date: row.get(8)?,
-        developer: row.get(9)?,
-        publisher: row.get(10)?,
-        genres: serde_json::from_str(&row.get::<_, String>(11)?).unwrap(),
-        favorite: row.get::<_, i64>(12)? != 0,
-        screenshot_path: row.get::<_, Option<String>>(13)?.map(PathBuf::from),
-    })
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Returns the CPU profile the game overrides its console's with, if any.
+    pub fn get_cpu_profile(&self, path: &Path) -> Result<Option<CpuProfile>> {
+        let profile = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .query_row(
+                "SELECT cpu_profile FROM games WHERE path = ?",
+                [path.display().to_string()],
+                |row| row.get::<_, Option<usize>>(0),
+            )
+            .optional()?
+            .flatten()
+            .and_then(CpuProfile::from_repr);
 
-    #[test]
-    fn test_migrations() {
-        Database::migrations().validate().unwrap();
+        Ok(profile)
     }
 
-    #[test]
-    fn test_most_played() {
-        let database = Database::in_memory().unwrap();
-
-        let games = vec![
-            NewGame {
-                name: "Game One".to_owned(),
-                path: PathBuf::from("test_directory/Game One.rom"),
-                image: Some(PathBuf::from("test_directory/Imgs/Game One.png")),
-                core: None,
-                rating: None,
-                release_date: None,
-                developer: None,
-                publisher: None,
-                genres: Vec::new(),
-                favorite: false,
-            },
-            NewGame {
-                name: "Game Two".to_owned(),
-                path: PathBuf::from("test_directory/Game Two.rom"),
-                image: Some(PathBuf::from("test_directory/Imgs/Game Two.png")),
-                core: None,
-                rating: None,
-                release_date: None,
-                developer: None,
-                publisher: None,
-                genres: Vec::new(),
-                favorite: false,
-            },
-        ];
+    pub fn set_cpu_profile(&self, path: &Path, profile: Option<CpuProfile>) -> Result<()> {
+        self.conn.as_ref().unwrap().execute(
+            "UPDATE games SET cpu_profile = ? WHERE path = ?",
+            params![profile.map(|p| p as usize), path.display().to_string()],
+        )?;
 
-        database.update_games(&games).unwrap();
+        Ok(())
+    }
 
-        database.increment_play_count(&games[1]).unwrap();
-        database
+    /// Returns the play time allowed by parental controls on the given day of the week, if
+    /// play time is limited on that day.
+    pub fn get_play_time_budget(&self, weekday: Weekday) -> Result<Option<Duration>> {
+        let minutes = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .query_row(
+                "SELECT minutes FROM play_time_budgets WHERE weekday = ?",
+                [weekday.num_days_from_monday()],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(minutes.map(Duration::minutes))
+    }
+
+    /// Sets the play time allowed on the given day of the week, or removes the limit.
+    pub fn set_play_time_budget(&self, weekday: Weekday, budget: Option<Duration>) -> Result<()> {
+        let conn = self.conn.as_ref().unwrap();
+        match budget {
+            Some(budget) => conn.execute(
+                "INSERT INTO play_time_budgets (weekday, minutes) VALUES (?, ?) ON CONFLICT(weekday) DO UPDATE SET minutes = excluded.minutes",
+                params![weekday.num_days_from_monday(), budget.num_minutes()],
+            )?,
+            None => conn.execute(
+                "DELETE FROM play_time_budgets WHERE weekday = ?",
+                [weekday.num_days_from_monday()],
+            )?,
+        };
+
+        Ok(())
+    }
+
+    /// Returns the total play time on the given date.
+    pub fn get_daily_play_time(&self, date: NaiveDate) -> Result<Duration> {
+        let play_time = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .query_row(
+                "SELECT play_time FROM daily_play_time WHERE date = ?",
+                [date],
+                |row| row.get(0),
+            )
+            .optional()?
+            .unwrap_or_default();
+
+        Ok(Duration::seconds(play_time))
+    }
+
+    /// Adds to the total play time on the given date.
+    pub fn add_daily_play_time(&self, date: NaiveDate, play_time: Duration) -> Result<()> {
+        self.conn.as_ref().unwrap().execute(
+            "INSERT INTO daily_play_time (date, play_time) VALUES (?, ?) ON CONFLICT(date) DO UPDATE SET play_time = play_time + excluded.play_time",
+            params![date, play_time.num_seconds()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the total play time of every day that has any, oldest first.
+    pub fn select_daily_play_time(&self) -> Result<Vec<(NaiveDate, Duration)>> {
+        let mut stmt = self.conn.as_ref().unwrap().prepare(
+            "SELECT date, play_time FROM daily_play_time WHERE play_time > 0 ORDER BY date ASC",
+        )?;
+
+        let results = stmt
+            .query_map([], |row| Ok((row.get(0)?, Duration::seconds(row.get(1)?))))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Logs a play session.
+    pub fn add_play_session(&self, session: &PlaySession) -> Result<()> {
+        self.conn.as_ref().unwrap().execute(
+            "INSERT INTO play_sessions (path, start, play_time) VALUES (?, ?, ?)",
+            params![
+                session.path.display().to_string(),
+                session.start,
+                session.play_time.num_seconds(),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the longest play session, if any have been logged.
+    pub fn select_longest_play_session(&self) -> Result<Option<PlaySession>> {
+        let session = self
+            .conn
+            .as_ref()
+            .unwrap()
+            .query_row(
+                "SELECT path, start, play_time FROM play_sessions ORDER BY play_time DESC LIMIT 1",
+                [],
+                |row| {
+                    Ok(PlaySession {
+                        path: PathBuf::from(row.get::<_, String>(0)?),
+                        start: row.get(1)?,
+                        play_time: Duration::seconds(row.get(2)?),
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(session)
+    }
+}
+
+/// Tables that refer to games by path. Guides are referred to by the guide's own path, which is
+/// moved along with the game's.
+const GAME_PATH_TABLES: [&str; 9] = [
+    "games",
+    "queue",
+    "cheats",
+    "collection_games",
+    "hidden_games",
+    "custom_images",
+    "play_sessions",
+    "checksums",
+    "guides",
+];
+
+fn table_columns(conn: &Connection, table: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    stmt.query_map([], |row| row.get(1))?.collect()
+}
+
+fn delete_undo(conn: &Connection, undo_id: i64) -> rusqlite::Result<()> {
+    for table in ["undo_actions", "undo_paths", "undo_rows", "trashed_files"] {
+        let column = if table == "undo_actions" {
+            "id"
+        } else {
+            "undo_id"
+        };
+        conn.execute(
+            &format!("DELETE FROM {table} WHERE {column} = ?"),
+            [undo_id],
+        )?;
+    }
+    Ok(())
+}
+
+fn purge_game(conn: &Connection, path: &Path) -> rusqlite::Result<()> {
+    for table in GAME_PATH_TABLES {
+        conn.execute(
+            &format!("DELETE FROM {table} WHERE path = ?"),
+            [path.display().to_string()],
+        )?;
+    }
+    Ok(())
+}
+
+fn update_game_path(conn: &Connection, old: &Path, new: &Path) -> rusqlite::Result<()> {
+    for table in GAME_PATH_TABLES {
+        conn.execute(
+            // Rows left behind by a file that was at the new path before are replaced
+            &format!("UPDATE OR REPLACE {table} SET path = ? WHERE path = ?"),
+            params![new.display().to_string(), old.display().to_string()],
+        )?;
+    }
+    Ok(())
+}
+
+fn map_game(row: &Row<'_>) -> rusqlite::Result<Game> {
+    Ok(Game {
+        name: row.get(0)?,
+        path: PathBuf::from(row.get::<_, String>(1)?),
+        image: row.get::<_, Option<String>>(2)?.map(PathBuf::from),
+        play_count: row.get(3)?,
+        play_time: Duration::seconds(row.get(4)?),
+        last_played: row.get(5)?,
+        core: row.get(6)?,
+        rating: row.get(7)?,
+        release_date: row.get(8)?,
+        developer: row.get(9)?,
+        publisher: row.get(10)?,
+        genres: serde_json::from_str(&row.get::<_, String>(11)?).unwrap(),
+        favorite: row.get::<_, i64>(12)? != 0,
+        screenshot_path: row.get::<_, Option<String>>(13)?.map(PathBuf::from),
+        volume: row.get(14)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations() {
+        Database::migrations().validate().unwrap();
+    }
+
+    #[test]
+    fn test_most_played() {
+        let database = Database::in_memory().unwrap();
+
+        let games = vec![
+            NewGame {
+                name: "Game One".to_owned(),
+                path: PathBuf::from("test_directory/Game One.rom"),
+                image: Some(PathBuf::from("test_directory/Imgs/Game One.png")),
+                core: None,
+                rating: None,
+                release_date: None,
+                developer: None,
+                publisher: None,
+                genres: Vec::new(),
+                favorite: false,
+            },
+            NewGame {
+                name: "Game Two".to_owned(),
+                path: PathBuf::from("test_directory/Game Two.rom"),
+                image: Some(PathBuf::from("test_directory/Imgs/Game Two.png")),
+                core: None,
+                rating: None,
+                release_date: None,
+                developer: None,
+                publisher: None,
+                genres: Vec::new(),
+                favorite: false,
+            },
+        ];
+
+        database.update_games(&games).unwrap();
+
+        database.increment_play_count(&games[1]).unwrap();
+        database
             .add_play_time(games[1].path.as_path(), Duration::seconds(1))
             .unwrap();
         let most_played = database.select_most_played(2).unwrap();
@@ -1018,6 +2294,320 @@ mod tests {
         assert_eq!(results.len(), 0);
     }
 
+    #[test]
+    fn test_directory_fingerprints() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let game = |path: &str| NewGame {
+            name: path.to_owned(),
+            path: PathBuf::from(path),
+            image: None,
+            core: None,
+            rating: None,
+            release_date: None,
+            developer: None,
+            publisher: None,
+            genres: Vec::new(),
+            favorite: false,
+        };
+        db.update_games(&[
+            game("test_directory/Game One.rom"),
+            game("test_directory/Game Two.rom"),
+            game("test_directory/Hacks/Game Three.rom"),
+            // Neither a wildcard nor a different case matches the directory
+            game("test-directory/Game Four.rom"),
+            game("TEST_DIRECTORY/Game Five.rom"),
+        ])?;
+        db.add_play_time(
+            Path::new("test_directory/Game Two.rom"),
+            Duration::minutes(5),
+        )?;
+
+        db.set_gamelist_fingerprint(Path::new("test_directory"), 1024)?;
+        db.set_directory_fingerprint(Path::new("test_directory"), u64::MAX)?;
+        db.set_directory_fingerprint(Path::new("test_directory/Hacks"), 42)?;
+        let fingerprints = db.select_directory_fingerprints()?;
+        assert_eq!(fingerprints.len(), 2);
+        assert_eq!(fingerprints[Path::new("test_directory")], Some(u64::MAX));
+        assert_eq!(
+            db.get_gamelist_fingerprint(Path::new("test_directory"))?,
+            Some(1024)
+        );
+
+        db.delete_unplayed_games_in_directory(Path::new("test_directory"))?;
+        db.delete_directory(Path::new("test_directory"))?;
+        let mut paths = db
+            .select_all_games()?
+            .into_iter()
+            .map(|game| game.path)
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("TEST_DIRECTORY/Game Five.rom"),
+                PathBuf::from("test-directory/Game Four.rom"),
+                PathBuf::from("test_directory/Game Two.rom"),
+                PathBuf::from("test_directory/Hacks/Game Three.rom"),
+            ]
+        );
+        assert_eq!(
+            db.get_gamelist_fingerprint(Path::new("test_directory"))?,
+            None
+        );
+        assert_eq!(db.select_directory_fingerprints()?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_checksums() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let path = Path::new("test_directory/Game One.rom");
+        assert_eq!(db.get_crc32(path, 1024, 100)?, None);
+
+        db.set_crc32(path, 1024, 100, 0xDEADBEEF)?;
+        assert_eq!(db.get_crc32(path, 1024, 100)?, Some(0xDEADBEEF));
+        assert_eq!(db.get_crc32(path, 1024, 200)?, None);
+        assert_eq!(db.get_crc32(path, 2048, 100)?, None);
+
+        db.set_crc32(path, 2048, 200, 42)?;
+        assert_eq!(db.get_crc32(path, 2048, 200)?, Some(42));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let game = NewGame {
+            name: "Game One".to_owned(),
+            path: PathBuf::from("test_directory/Game One.rom"),
+            image: None,
+            core: None,
+            rating: None,
+            release_date: None,
+            developer: None,
+            publisher: None,
+            genres: vec!["RPG".to_owned()],
+            favorite: true,
+        };
+        db.update_games(std::slice::from_ref(&game))?;
+        db.increment_play_count(&game)?;
+        db.add_to_queue(&game.path)?;
+        let before = db.select_game(&game.path)?.unwrap();
+
+        // Undoing a reset puts back the play stats
+        db.push_undo(UndoAction::RemoveFromRecents, &[&game.path])?;
+        db.reset_game(&game.path)?;
+        assert_eq!(db.select_game(&game.path)?.unwrap().play_count, 0);
+        let undo = db.pop_undo()?.unwrap();
+        assert_eq!(undo.action, UndoAction::RemoveFromRecents);
+        assert_eq!(db.select_game(&game.path)?, Some(before.clone()));
+
+        // Undoing a hide removes the rows that were added
+        db.push_undo(UndoAction::Hide, &[&game.path])?;
+        db.set_hidden(&game.path, true)?;
+        db.pop_undo()?;
+        assert!(db.select_hidden_games()?.is_empty());
+
+        // Undoing a delete puts back everything referring to the game, and its files
+        let undo_id = db.push_undo(UndoAction::Delete, &[&game.path])?;
+        let files = vec![(game.path.clone(), PathBuf::from("trash/Game One.rom"))];
+        db.trash_game(undo_id, &game.path, &files)?;
+        assert_eq!(db.select_game(&game.path)?, None);
+        assert!(!db.is_queued(&game.path)?);
+        let undo = db.pop_undo()?.unwrap();
+        assert_eq!(undo.files, files);
+        assert_eq!(db.select_game(&game.path)?, Some(before));
+        assert!(db.is_queued(&game.path)?);
+
+        assert_eq!(db.pop_undo()?, None);
+
+        let undo_id = db.push_undo(UndoAction::Hide, &[&game.path])?;
+        assert_eq!(
+            db.expire_undo(std::time::Duration::from_secs(60))?,
+            Vec::<i64>::new()
+        );
+        assert_eq!(db.expire_undo(std::time::Duration::ZERO)?, vec![undo_id]);
+        assert_eq!(db.pop_undo()?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_switch_volume() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let path = crate::constants::ALLIUM_EXTERNAL_GAMES_DIR.join("GBA/Game.gba");
+        let game = |name: &str| NewGame {
+            name: name.to_owned(),
+            path: path.clone(),
+            image: None,
+            core: None,
+            rating: None,
+            release_date: None,
+            developer: None,
+            publisher: None,
+            genres: Vec::new(),
+            favorite: false,
+        };
+        let tag = |volume: &str| {
+            db.conn.as_ref().unwrap().execute(
+                "UPDATE games SET volume = ? WHERE path = ?",
+                params![volume, path.display().to_string()],
+            )
+        };
+
+        // A game on one card is played, then another card with a game at the same path is
+        // inserted
+        db.update_games(&[game("Game One")])?;
+        tag("AAAA-AAAA")?;
+        db.add_to_queue(&path)?;
+        db.switch_volume("BBBB-BBBB")?;
+        assert_eq!(db.select_game(&path)?, None);
+        db.update_games(&[game("Game Two")])?;
+        tag("BBBB-BBBB")?;
+        assert!(!db.is_queued(&path)?);
+
+        db.switch_volume("AAAA-AAAA")?;
+        assert_eq!(db.select_game(&path)?.unwrap().name, "Game One");
+        assert!(db.is_queued(&path)?);
+
+        db.switch_volume("BBBB-BBBB")?;
+        assert_eq!(db.select_game(&path)?.unwrap().name, "Game Two");
+        assert!(!db.is_queued(&path)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hidden_games() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let one = Path::new("test_directory/Game One.rom");
+        let two = Path::new("test_directory/Game Two.rom");
+        db.set_hidden(one, true)?;
+        db.set_hidden(one, true)?;
+        db.set_hidden(two, true)?;
+        db.set_hidden(two, false)?;
+        assert_eq!(
+            db.select_hidden_games()?,
+            HashSet::from([one.to_path_buf()])
+        );
+
+        db.update_game_path(one, two)?;
+        assert_eq!(
+            db.select_hidden_games()?,
+            HashSet::from([two.to_path_buf()])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_game() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let game = NewGame {
+            name: "Game One".to_owned(),
+            path: PathBuf::from("test_directory/Game One.rom"),
+            image: None,
+            core: None,
+            rating: None,
+            release_date: None,
+            developer: None,
+            publisher: None,
+            genres: Vec::new(),
+            favorite: false,
+        };
+        db.update_games(std::slice::from_ref(&game))?;
+        db.add_to_queue(&game.path)?;
+
+        let renamed = Path::new("test_directory/Game Two.rom");
+        let guide = PathBuf::from("Guides/Game One.txt");
+        let renamed_guide = PathBuf::from("Guides/Game Two.txt");
+        db.update_guide_cursor(&guide, 42)?;
+        db.update_guide_cursor(&renamed_guide, 7)?;
+        db.rename_game(
+            &game.path,
+            renamed,
+            "Game Two",
+            &[(guide.clone(), renamed_guide.clone())],
+        )?;
+        assert_eq!(db.select_game(&game.path)?, None);
+        assert_eq!(db.select_game(renamed)?.unwrap().name, "Game Two");
+        assert!(db.is_queued(renamed)?);
+        assert_eq!(db.get_guide_cursor(&renamed_guide)?, 42);
+
+        db.purge_game(renamed)?;
+        assert_eq!(db.select_game(renamed)?, None);
+        assert!(!db.is_queued(renamed)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_folder_sorts() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let ps = Path::new("Roms/PS");
+        let gb = Path::new("Roms/GB");
+        assert_eq!(db.get_folder_sort(ps)?, None);
+
+        db.set_folder_sort(ps, "alphabetical")?;
+        db.set_folder_sort(ps, "last-played")?;
+        assert_eq!(db.get_folder_sort(ps)?.as_deref(), Some("last-played"));
+        assert_eq!(db.get_folder_sort(gb)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_images() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let mut game = NewGame {
+            name: "Game One".to_owned(),
+            path: PathBuf::from("test_directory/Game One.rom"),
+            image: Some(PathBuf::from("test_directory/Imgs/Game One.png")),
+            core: None,
+            rating: None,
+            release_date: None,
+            developer: None,
+            publisher: None,
+            genres: Vec::new(),
+            favorite: false,
+        };
+        db.update_games(std::slice::from_ref(&game))?;
+
+        let custom = Path::new("Screenshots/Game One.png");
+        db.set_custom_image(&game.path, custom)?;
+        assert_eq!(
+            db.select_game(&game.path)?.unwrap().image.as_deref(),
+            Some(custom)
+        );
+
+        // Repopulating keeps the chosen image
+        db.update_games(std::slice::from_ref(&game))?;
+        assert_eq!(
+            db.select_game(&game.path)?.unwrap().image.as_deref(),
+            Some(custom)
+        );
+
+        let new = PathBuf::from("test_directory/Game Two.rom");
+        db.update_game_path(&game.path, &new)?;
+        game.path = new;
+        assert_eq!(
+            db.select_custom_images()?,
+            HashMap::from([(game.path.clone(), custom.to_path_buf())])
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_set_core() -> Result<()> {
         let db = Database::in_memory().unwrap();
@@ -1062,6 +2652,87 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_set_cpu_profile() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let game = NewGame {
+            name: "Game One".to_owned(),
+            path: PathBuf::from("test_directory/Game One.rom"),
+            image: None,
+            core: None,
+            rating: None,
+            release_date: None,
+            developer: None,
+            publisher: None,
+            genres: Vec::new(),
+            favorite: false,
+        };
+        db.update_games(std::slice::from_ref(&game))?;
+
+        assert_eq!(db.get_cpu_profile(&game.path)?, None);
+
+        db.set_cpu_profile(&game.path, Some(CpuProfile::Overclock))?;
+        assert_eq!(db.get_cpu_profile(&game.path)?, Some(CpuProfile::Overclock));
+
+        db.set_cpu_profile(&game.path, None)?;
+        assert_eq!(db.get_cpu_profile(&game.path)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_play_time_budget() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        assert_eq!(db.get_play_time_budget(Weekday::Mon)?, None);
+        db.set_play_time_budget(Weekday::Mon, Some(Duration::minutes(60)))?;
+        assert_eq!(
+            db.get_play_time_budget(Weekday::Mon)?,
+            Some(Duration::minutes(60))
+        );
+        assert_eq!(db.get_play_time_budget(Weekday::Tue)?, None);
+        db.set_play_time_budget(Weekday::Mon, None)?;
+        assert_eq!(db.get_play_time_budget(Weekday::Mon)?, None);
+
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        assert_eq!(db.get_daily_play_time(date)?, Duration::zero());
+        db.add_daily_play_time(date, Duration::minutes(10))?;
+        db.add_daily_play_time(date, Duration::minutes(5))?;
+        assert_eq!(db.get_daily_play_time(date)?, Duration::minutes(15));
+
+        let earlier = NaiveDate::from_ymd_opt(2024, 4, 30).unwrap();
+        db.add_daily_play_time(earlier, Duration::minutes(20))?;
+        assert_eq!(
+            db.select_daily_play_time()?,
+            vec![
+                (earlier, Duration::minutes(20)),
+                (date, Duration::minutes(15)),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_play_sessions() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        assert_eq!(db.select_longest_play_session()?, None);
+
+        let session = |start, minutes| PlaySession {
+            path: PathBuf::from("test_directory/Game One.rom"),
+            start,
+            play_time: Duration::minutes(minutes),
+        };
+        db.add_play_session(&session(0, 30))?;
+        db.add_play_session(&session(3600, 90))?;
+        db.add_play_session(&session(7200, 10))?;
+        assert_eq!(db.select_longest_play_session()?, Some(session(3600, 90)));
+
+        Ok(())
+    }
+
     #[test]
     fn test_set_genres() -> Result<()> {
         let db = Database::in_memory().unwrap();
@@ -1098,4 +2769,353 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_export_import_json() -> Result<()> {
+        let games = ["Game One", "Game Two", "Game Three"]
+            .into_iter()
+            .map(|name| NewGame {
+                name: name.to_owned(),
+                path: PathBuf::from(format!("test_directory/{name}.rom")),
+                image: None,
+                core: None,
+                rating: None,
+                release_date: None,
+                developer: None,
+                publisher: None,
+                genres: Vec::new(),
+                favorite: false,
+            })
+            .collect::<Vec<_>>();
+
+        let db = Database::in_memory().unwrap();
+        db.update_games(&games)?;
+        db.set_favorite(&games[1].path, true)?;
+        db.set_favorite(&games[0].path, true)?;
+        db.add_play_time(&games[2].path, Duration::minutes(90))?;
+        db.set_core(&games[2].path, "gpsp")?;
+        db.set_collection(
+            "RPGs",
+            &[&games[2].path, Path::new("missing/Game Four.rom")],
+        )?;
+
+        let path = std::env::temp_dir().join(format!("allium-backup-{}.json", std::process::id()));
+        db.export_json(&path)?;
+
+        // Games missing from the database and the SD card aren't restored
+        let restored = Database::in_memory().unwrap();
+        assert_eq!(restored.import_json(&path)?, 0);
+
+        let restored = Database::in_memory().unwrap();
+        restored.update_games(&games)?;
+        assert_eq!(restored.import_json(&path)?, 3);
+        fs::remove_file(&path)?;
+
+        let favorites = restored.select_favorites(10)?;
+        assert_eq!(
+            favorites.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(),
+            vec!["Game Two", "Game One"]
+        );
+        let game = restored.select_game(&games[2].path)?.unwrap();
+        assert_eq!(game.play_time, Duration::minutes(90));
+        assert_eq!(game.core.as_deref(), Some("gpsp"));
+        assert_eq!(restored.select_collections()?, vec!["RPGs".to_owned()]);
+        assert_eq!(
+            restored.select_collection("RPGs", 10)?[0].path,
+            games[2].path
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_favorite_order() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let games = ["Game One", "Game Two", "Game Three"]
+            .into_iter()
+            .map(|name| NewGame {
+                name: name.to_owned(),
+                path: PathBuf::from(format!("test_directory/{name}.rom")),
+                image: None,
+                core: None,
+                rating: None,
+                release_date: None,
+                developer: None,
+                publisher: None,
+                genres: Vec::new(),
+                favorite: false,
+            })
+            .collect::<Vec<_>>();
+        db.update_games(&games)?;
+
+        for game in &games {
+            db.set_favorite(&game.path, true)?;
+        }
+        let favorites = db.select_favorites(10)?;
+        assert_eq!(
+            favorites.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(),
+            vec!["Game One", "Game Two", "Game Three"]
+        );
+
+        db.set_favorite_order(&[&games[2].path, &games[0].path, &games[1].path])?;
+        let favorites = db.select_favorites(10)?;
+        assert_eq!(
+            favorites.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(),
+            vec!["Game Three", "Game One", "Game Two"]
+        );
+
+        db.set_favorite(&games[0].path, false)?;
+        db.set_favorite(&games[0].path, true)?;
+        let favorites = db.select_favorites(10)?;
+        assert_eq!(
+            favorites.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(),
+            vec!["Game Three", "Game Two", "Game One"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_queue() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let games = ["Game One", "Game Two", "Game Three"]
+            .into_iter()
+            .map(|name| NewGame {
+                name: name.to_owned(),
+                path: PathBuf::from(format!("test_directory/{name}.rom")),
+                image: None,
+                core: None,
+                rating: None,
+                release_date: None,
+                developer: None,
+                publisher: None,
+                genres: Vec::new(),
+                favorite: false,
+            })
+            .collect::<Vec<_>>();
+        db.update_games(&games)?;
+
+        db.add_to_queue(&games[1].path)?;
+        db.add_to_queue(&games[0].path)?;
+        db.add_to_queue(&games[1].path)?;
+        assert!(db.is_queued(&games[0].path)?);
+        assert!(!db.is_queued(&games[2].path)?);
+
+        let queue = db.select_queue(10)?;
+        assert_eq!(
+            queue.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(),
+            vec!["Game Two", "Game One"]
+        );
+
+        db.remove_from_queue(&games[1].path)?;
+        let queue = db.select_queue(10)?;
+        assert_eq!(
+            queue.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(),
+            vec!["Game One"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recents_periods() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let games = [
+            "Clock Ahead",
+            "Today",
+            "Yesterday",
+            "This Week",
+            "Last Month",
+            "Undated",
+        ]
+        .into_iter()
+        .map(|name| NewGame {
+            name: name.to_owned(),
+            path: PathBuf::from(format!("test_directory/{name}.rom")),
+            image: None,
+            core: None,
+            rating: None,
+            release_date: None,
+            developer: None,
+            publisher: None,
+            genres: Vec::new(),
+            favorite: false,
+        })
+        .collect::<Vec<_>>();
+        db.update_games(&games)?;
+        // Dated in a different order than they were played, as after the clock changed
+        for game in &games {
+            db.increment_play_count(game)?;
+        }
+        let now = chrono::Utc::now().timestamp();
+        let days_ago = [Some(-2), Some(0), Some(1), Some(3), Some(30), None];
+        for (game, days_ago) in games.iter().zip(days_ago) {
+            db.conn.as_ref().unwrap().execute(
+                "UPDATE games SET last_played_at = ? WHERE path = ?",
+                params![
+                    days_ago.map_or(0, |days: i64| now - days * 24 * 60 * 60),
+                    game.path.display().to_string()
+                ],
+            )?;
+        }
+
+        let recents = db.select_last_played_by_period(10)?;
+        assert_eq!(
+            recents
+                .iter()
+                .map(|(game, period)| (game.name.as_str(), *period))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Clock Ahead", PlayedPeriod::Today),
+                ("Today", PlayedPeriod::Today),
+                ("Yesterday", PlayedPeriod::Yesterday),
+                ("This Week", PlayedPeriod::ThisWeek),
+                ("Last Month", PlayedPeriod::Earlier),
+                ("Undated", PlayedPeriod::Earlier),
+            ]
+        );
+
+        // Undated games are kept, as it's not known when they were played
+        assert_eq!(db.prune_recents(7)?, 1);
+        let recents = db.select_last_played(10)?;
+        assert_eq!(
+            recents.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(),
+            vec!["Undated", "This Week", "Yesterday", "Today", "Clock Ahead"]
+        );
+        assert_eq!(db.select_game(&games[4].path)?.unwrap().play_count, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_collections() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let games = ["Game One", "Game Two", "Game Three"]
+            .into_iter()
+            .map(|name| NewGame {
+                name: name.to_owned(),
+                path: PathBuf::from(format!("test_directory/{name}.rom")),
+                image: None,
+                core: None,
+                rating: None,
+                release_date: None,
+                developer: None,
+                publisher: None,
+                genres: Vec::new(),
+                favorite: false,
+            })
+            .collect::<Vec<_>>();
+        db.update_games(&games)?;
+
+        db.set_collection("RPGs", &[&games[2].path, &games[0].path])?;
+        db.set_collection("Action", &[&games[1].path])?;
+        assert_eq!(db.select_collections()?, vec!["Action", "RPGs"]);
+
+        let collection = db.select_collection("RPGs", 10)?;
+        assert_eq!(
+            collection.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(),
+            vec!["Game Three", "Game One"]
+        );
+
+        db.set_collection("RPGs", &[&games[0].path])?;
+        let collection = db.select_collection("RPGs", 10)?;
+        assert_eq!(
+            collection.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(),
+            vec!["Game One"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_podcasts() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let podcast = Podcast {
+            url: "http://example.com/feed.rss".to_owned(),
+            title: "Podcast".to_owned(),
+            is_stream: false,
+        };
+        let stream = Podcast {
+            url: "http://example.com/stream.mp3".to_owned(),
+            title: "A Stream".to_owned(),
+            is_stream: true,
+        };
+        db.add_podcast(&podcast)?;
+        db.add_podcast(&stream)?;
+        assert_eq!(db.select_podcasts()?, vec![stream.clone(), podcast.clone()]);
+
+        db.delete_podcast(&stream.url)?;
+        assert_eq!(db.select_podcasts()?, vec![podcast]);
+
+        let episode = "http://example.com/episode.mp3";
+        assert_eq!(db.get_media_position(episode)?, Duration::zero());
+        db.set_media_position(episode, Duration::seconds(90))?;
+        assert_eq!(db.get_media_position(episode)?, Duration::seconds(90));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_battery_history() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let sample = |timestamp, percentage, game: Option<&str>| BatterySample {
+            timestamp,
+            percentage,
+            charging: false,
+            game: game.map(PathBuf::from),
+        };
+        let retention = std::time::Duration::from_secs(100);
+        db.add_battery_sample(&sample(0, 90, None), retention)?;
+        db.add_battery_sample(&sample(60, 89, Some("Game One.rom")), retention)?;
+        db.add_battery_sample(&sample(120, 88, Some("Game One.rom")), retention)?;
+
+        // The first sample is older than the retention period
+        assert_eq!(
+            db.select_battery_history(0)?,
+            vec![
+                sample(60, 89, Some("Game One.rom")),
+                sample(120, 88, Some("Game One.rom")),
+            ]
+        );
+        assert_eq!(
+            db.select_battery_history(100)?,
+            vec![sample(120, 88, Some("Game One.rom"))]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cheats() -> Result<()> {
+        let db = Database::in_memory().unwrap();
+
+        let game = Path::new("test_directory/Game One.rom");
+        let cheat_file = Path::new("cheats/Game One.cht");
+
+        db.set_cheat_enabled(game, cheat_file, 2, true)?;
+        db.set_cheat_enabled(game, cheat_file, 0, true)?;
+        db.set_cheat_enabled(game, cheat_file, 2, true)?;
+        assert_eq!(
+            db.select_cheats(game, cheat_file)?,
+            vec![(0, true), (2, true)]
+        );
+
+        db.set_cheat_enabled(game, cheat_file, 0, false)?;
+        assert_eq!(
+            db.select_cheats(game, cheat_file)?,
+            vec![(0, false), (2, true)]
+        );
+        assert!(
+            db.select_cheats(Path::new("other.rom"), cheat_file)?
+                .is_empty()
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,190 @@
+use std::env;
+use std::fs::{self, File};
+use std::path::Path;
+use std::time::SystemTime;
+
+use anyhow::Result;
+#[cfg(not(feature = "miyoo"))]
+use anyhow::bail;
+use enum_map::Enum;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use tokio::process::Child;
+#[cfg(feature = "miyoo")]
+use tokio::process::Command;
+
+#[cfg(feature = "miyoo")]
+use crate::constants::ALLIUM_SD_ROOT;
+use crate::constants::{ALLIUM_DEVELOPER_SETTINGS, ALLIUM_SSH_KEYS_DIR, SSH_PORT, TELNET_PORT};
+use crate::wifi::WiFiSettings;
+
+/// A server that gives a shell on the device over the network.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Enum)]
+pub enum RemoteShell {
+    Ssh,
+    Telnet,
+}
+
+impl RemoteShell {
+    pub fn port(self) -> u16 {
+        match self {
+            RemoteShell::Ssh => SSH_PORT,
+            RemoteShell::Telnet => TELNET_PORT,
+        }
+    }
+
+    /// Returns the command that connects to the server from another computer.
+    pub fn connect_command(self, ip_address: &str) -> String {
+        match self {
+            RemoteShell::Ssh => format!("ssh root@{ip_address}"),
+            RemoteShell::Telnet => format!("telnet {ip_address}"),
+        }
+    }
+
+    fn program(self) -> &'static str {
+        match self {
+            RemoteShell::Ssh => "dropbear",
+            RemoteShell::Telnet => "telnetd",
+        }
+    }
+
+    /// Arguments to run the server in the foreground, so that it stops when the child is killed.
+    #[cfg_attr(not(any(feature = "miyoo", test)), allow(dead_code))]
+    fn args(self) -> Vec<String> {
+        let port = self.port().to_string();
+        match self {
+            // -R generates the host keys on first start. Root has no password, so -s only allows
+            // logins with a key in ALLIUM_SSH_KEYS_DIR/authorized_keys
+            RemoteShell::Ssh => vec![
+                "-F".into(),
+                "-R".into(),
+                "-s".into(),
+                "-D".into(),
+                ALLIUM_SSH_KEYS_DIR.display().to_string(),
+                "-p".into(),
+                port,
+            ],
+            RemoteShell::Telnet => vec!["-F".into(), "-l".into(), "sh".into(), "-p".into(), port],
+        }
+    }
+
+    /// Returns the locale key of the reason the server can't be started, or `None` if it can.
+    pub fn unavailable_reason(self) -> Option<&'static str> {
+        if !is_installed(self.program()) {
+            return Some("settings-developer-not-installed");
+        }
+        if self == RemoteShell::Ssh && !ALLIUM_SSH_KEYS_DIR.join("authorized_keys").exists() {
+            return Some("settings-developer-no-keys");
+        }
+        None
+    }
+
+    /// Starts the server. The child must be waited on once it's killed.
+    pub fn spawn(self) -> Result<Child> {
+        #[cfg(feature = "miyoo")]
+        {
+            if let Some(reason) = self.unavailable_reason() {
+                anyhow::bail!("{:?} server can't be started: {}", self, reason);
+            }
+            return Ok(Command::new(self.program())
+                .args(self.args())
+                .current_dir(ALLIUM_SD_ROOT.as_path())
+                .kill_on_drop(true)
+                .spawn()?);
+        }
+
+        #[cfg(not(feature = "miyoo"))]
+        bail!("{:?} is only available on the device", self)
+    }
+}
+
+/// Whether a program is in one of the folders in `PATH`.
+pub fn is_installed(program: &str) -> bool {
+    env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths).any(|dir| Path::new(&dir).join(program).is_file())
+    })
+}
+
+/// Remote shells that alliumd runs, so that they're started again after a reboot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeveloperSettings {
+    #[serde(default)]
+    pub ssh: bool,
+    #[serde(default)]
+    pub telnet: bool,
+}
+
+impl DeveloperSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_DEVELOPER_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_DEVELOPER_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read developer file, removing");
+            fs::remove_file(ALLIUM_DEVELOPER_SETTINGS.as_path())?;
+        }
+        // Telnet used to be turned on in the Wi-Fi settings, which no longer save it
+        let settings = Self {
+            telnet: WiFiSettings::load().is_ok_and(|s| s.telnet),
+            ..Self::new()
+        };
+        settings.save()?;
+        Ok(settings)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_DEVELOPER_SETTINGS.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+
+    /// Returns the modification time and size of the settings file, which change whenever the
+    /// settings are saved, so that they're only loaded again when they do.
+    pub fn stamp() -> Option<(SystemTime, u64)> {
+        let metadata = fs::metadata(ALLIUM_DEVELOPER_SETTINGS.as_path()).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+
+    pub fn is_enabled(&self, shell: RemoteShell) -> bool {
+        match shell {
+            RemoteShell::Ssh => self.ssh,
+            RemoteShell::Telnet => self.telnet,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ssh_requires_key() {
+        let args = RemoteShell::Ssh.args();
+        assert!(!args.contains(&"-B".to_owned()));
+        assert!(args.contains(&"-s".to_owned()));
+        assert!(args.contains(&ALLIUM_SSH_KEYS_DIR.display().to_string()));
+        assert!(args.ends_with(&["-p".to_owned(), SSH_PORT.to_string()]));
+    }
+
+    #[test]
+    fn test_is_installed() {
+        assert!(is_installed("sh"));
+        assert!(!is_installed("not-a-real-program"));
+    }
+
+    #[test]
+    fn test_is_enabled() {
+        let settings = DeveloperSettings {
+            ssh: true,
+            telnet: false,
+        };
+        assert!(settings.is_enabled(RemoteShell::Ssh));
+        assert!(!settings.is_enabled(RemoteShell::Telnet));
+    }
+}
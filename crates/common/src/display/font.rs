@@ -2,6 +2,8 @@
 
 use std::f32;
 use std::fmt;
+use std::iter;
+use std::ptr;
 use std::vec::Vec;
 
 use embedded_graphics::{
@@ -15,8 +17,8 @@ use embedded_graphics::{
 };
 
 use rusttype::Font;
-use rusttype::GlyphId;
 use rusttype::vector;
+use rusttype::{Glyph, GlyphId, PositionedGlyph};
 
 use crate::display::color::Color;
 
@@ -47,8 +49,8 @@ pub struct FontTextStyle<C: PixelColor> {
     /// Font.
     font: Font<'static>,
 
-    /// Font fallback.
-    font_fallback: Option<Font<'static>>,
+    /// Fonts that characters missing from the font are taken from, in order.
+    font_fallbacks: Vec<Font<'static>>,
 }
 
 impl<C: PixelColor> FontTextStyle<C> {
@@ -74,6 +76,43 @@ impl<C: PixelColor> FontTextStyle<C> {
             .build()
     }
 
+    /// Returns the glyph for a character from the first font that has it, along with that font.
+    fn glyph(&self, c: char) -> (&Font<'static>, Glyph<'static>) {
+        for font in iter::once(&self.font).chain(&self.font_fallbacks) {
+            let g = font.glyph(c);
+            if g.id() != GlyphId(0) {
+                return (font, g);
+            }
+        }
+        (&self.font, self.font.glyph(c))
+    }
+
+    /// Lays out the glyphs of a line of text, starting at the origin.
+    fn layout(&self, text: &str) -> Vec<PositionedGlyph<'static>> {
+        let scale = rusttype::Scale::uniform(self.font_size as f32);
+        let v_metrics = self.font.v_metrics(scale);
+        let start = rusttype::point(0.0, v_metrics.ascent);
+
+        let mut glyphs = Vec::new();
+        let mut last: Option<(&Font<'static>, GlyphId)> = None;
+        let mut x = 0.0;
+        for c in text.chars() {
+            let (font, g) = self.glyph(c);
+            let g = g.scaled(scale);
+            // Kerning pairs only apply to glyphs of the same font
+            if let Some((last_font, last_id)) = last
+                && ptr::eq(last_font, font)
+            {
+                x += font.pair_kerning(scale, last_id, g.id());
+            }
+            let w = g.h_metrics().advance_width;
+            last = Some((font, g.id()));
+            glyphs.push(g.positioned(start + vector(x, 0.0)));
+            x += w;
+        }
+        glyphs
+    }
+
     /// Resolves a decoration color.
     fn resolve_decoration_color(&self, color: DecorationColor<C>) -> Option<C> {
         match color {
@@ -176,34 +215,7 @@ where
     where
         D: DrawTarget<Color = Self::Color>,
     {
-        let scale = rusttype::Scale::uniform(self.font_size as f32);
-
-        let v_metrics = self.font.v_metrics(scale);
-        let start = rusttype::point(0.0, v_metrics.ascent);
-
-        let glyphs: Vec<rusttype::PositionedGlyph<'_>> = text
-            .chars()
-            .map(|c| {
-                let mut g = self.font.glyph(c);
-                if g.id() == GlyphId(0)
-                    && let Some(font_fallback) = self.font_fallback.as_ref()
-                {
-                    g = font_fallback.glyph(c);
-                }
-                g
-            })
-            .scan((None, 0.0), |(last, x), g| {
-                let g = g.scaled(scale);
-                if let Some(last) = last {
-                    *x += self.font.pair_kerning(scale, *last, g.id());
-                }
-                let w = g.h_metrics().advance_width;
-                let next = g.positioned(start + vector(*x, 0.0));
-                *last = Some(next.id());
-                *x += w;
-                Some(next)
-            })
-            .collect();
+        let glyphs = self.layout(text);
 
         let width = glyphs
             .iter()
@@ -274,33 +286,7 @@ where
     }
 
     fn measure_string(&self, text: &str, position: Point, _baseline: Baseline) -> TextMetrics {
-        let scale = rusttype::Scale::uniform(self.font_size as f32);
-        let v_metrics = self.font.v_metrics(scale);
-        let start = rusttype::point(0.0, v_metrics.ascent);
-
-        let glyphs: Vec<rusttype::PositionedGlyph<'_>> = text
-            .chars()
-            .map(|c| {
-                let mut g = self.font.glyph(c);
-                if g.id() == GlyphId(0)
-                    && let Some(font_fallback) = self.font_fallback.as_ref()
-                {
-                    g = font_fallback.glyph(c);
-                }
-                g
-            })
-            .scan((None, 0.0), |(last, x), g| {
-                let g = g.scaled(scale);
-                if let Some(last) = last {
-                    *x += self.font.pair_kerning(scale, *last, g.id());
-                }
-                let w = g.h_metrics().advance_width;
-                let next = g.positioned(start + vector(*x, 0.0));
-                *last = Some(next.id());
-                *x += w;
-                Some(next)
-            })
-            .collect();
+        let glyphs = self.layout(text);
 
         let width = glyphs
             .iter()
@@ -336,7 +322,7 @@ impl<C: PixelColor> FontTextStyleBuilder<C> {
         Self {
             style: FontTextStyle {
                 font,
-                font_fallback: None,
+                font_fallbacks: Vec::new(),
                 background_color: None,
                 font_size: 12,
                 text_color: None,
@@ -353,9 +339,16 @@ impl<C: PixelColor> FontTextStyleBuilder<C> {
         self
     }
 
-    /// Builder method used to set the font fallback of the style.
+    /// Builder method used to add a font that characters missing from the font are taken from.
     pub fn font_fallback(mut self, font_fallback: Font<'static>) -> Self {
-        self.style.font_fallback = Some(font_fallback);
+        self.style.font_fallbacks.push(font_fallback);
+        self
+    }
+
+    /// Builder method used to add fonts that characters missing from the font are taken from.
+    /// Each character is taken from the first font that has it.
+    pub fn font_fallbacks(mut self, font_fallbacks: Vec<Font<'static>>) -> Self {
+        self.style.font_fallbacks.extend(font_fallbacks);
         self
     }
 
@@ -85,3 +85,70 @@ pub fn round(image: &mut RgbaImage, radius: u32) {
         }
     }
 }
+
+/// Blurs raw pixels in place with a box blur, run across rows and then down columns. Each
+/// pixel is `channels` bytes, and every channel is blurred. Edges are extended past the image.
+pub fn box_blur(pixels: &mut [u8], width: usize, channels: usize, radius: usize) {
+    if radius == 0 || width == 0 || channels == 0 {
+        return;
+    }
+    let stride = width * channels;
+    let height = pixels.len() / stride;
+
+    let mut line = vec![0; stride];
+    for row in pixels.chunks_exact_mut(stride) {
+        line.copy_from_slice(row);
+        blur_line(&line, row, channels, radius);
+    }
+
+    let mut column = vec![0; height * channels];
+    let mut blurred = vec![0; height * channels];
+    for x in 0..width {
+        for y in 0..height {
+            let i = y * stride + x * channels;
+            column[y * channels..(y + 1) * channels].copy_from_slice(&pixels[i..i + channels]);
+        }
+        blur_line(&column, &mut blurred, channels, radius);
+        for y in 0..height {
+            let i = y * stride + x * channels;
+            pixels[i..i + channels].copy_from_slice(&blurred[y * channels..(y + 1) * channels]);
+        }
+    }
+}
+
+/// Averages each pixel of `src` with the `radius` pixels either side of it, into `dst`.
+fn blur_line(src: &[u8], dst: &mut [u8], channels: usize, radius: usize) {
+    let len = (src.len() / channels) as isize;
+    let radius = radius as isize;
+    let window = (2 * radius + 1) as u32;
+    for c in 0..channels {
+        let at = |i: isize| src[i.clamp(0, len - 1) as usize * channels + c] as u32;
+        let mut sum: u32 = (-radius..=radius).map(at).sum();
+        for i in 0..len {
+            dst[i as usize * channels + c] = (sum / window) as u8;
+            sum = sum + at(i + radius + 1) - at(i - radius);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_box_blur() {
+        // A white pixel in the middle of a black 3x3 image, with 1 byte per pixel
+        let mut pixels = [0, 0, 0, 0, 255, 0, 0, 0, 0];
+        box_blur(&mut pixels, 3, 1, 1);
+        assert_eq!(pixels, [28, 28, 28, 28, 28, 28, 28, 28, 28]);
+
+        let mut pixels = [10, 20, 30, 40];
+        box_blur(&mut pixels, 2, 2, 0);
+        assert_eq!(pixels, [10, 20, 30, 40]);
+
+        // Flat images stay the same
+        let mut pixels = [100; 12];
+        box_blur(&mut pixels, 2, 3, 2);
+        assert_eq!(pixels, [100; 12]);
+    }
+}
@@ -0,0 +1,189 @@
+use std::fs::{self, File};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Result, bail};
+use image::{Rgba, RgbaImage};
+use log::{debug, warn};
+
+use crate::constants::{ALLIUM_IMAGE_CACHE_DIR, IMAGE_CACHE_MAX_SIZE};
+
+/// Identifies the cached copy of an image, as scaled for a view.
+///
+/// Thumbnails are stored decoded and scaled, as RGB565 with an alpha channel, so that showing
+/// box art again doesn't decode and resize the original image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheKey(u64);
+
+impl CacheKey {
+    /// Returns the key of an image, from its path, modification time and size, and the
+    /// parameters it's scaled with. Returns `None` if the image can't be read.
+    pub fn new(path: &Path, params: impl Hash) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        metadata.modified().ok()?.hash(&mut hasher);
+        metadata.len().hash(&mut hasher);
+        params.hash(&mut hasher);
+        Some(Self(hasher.finish()))
+    }
+
+    fn path(&self) -> PathBuf {
+        ALLIUM_IMAGE_CACHE_DIR.join(format!("{:016x}", self.0))
+    }
+}
+
+/// Loads a cached image, if there is one.
+pub fn load(key: CacheKey) -> Option<RgbaImage> {
+    let mut file = File::open(key.path()).ok()?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data).ok()?;
+    match decode(&data) {
+        Ok(image) => Some(image),
+        Err(e) => {
+            warn!("invalid cached image {}: {}", key.path().display(), e);
+            fs::remove_file(key.path()).ok();
+            None
+        }
+    }
+}
+
+/// Saves an image to the cache.
+pub fn save(key: CacheKey, image: &RgbaImage) -> Result<()> {
+    fs::create_dir_all(ALLIUM_IMAGE_CACHE_DIR.as_path())?;
+    File::create(key.path())?.write_all(&encode(image))?;
+    Ok(())
+}
+
+/// Deletes the least recently written images once the cache is larger than
+/// `IMAGE_CACHE_MAX_SIZE`.
+pub fn prune() -> Result<()> {
+    if !ALLIUM_IMAGE_CACHE_DIR.exists() {
+        return Ok(());
+    }
+
+    let mut entries: Vec<(SystemTime, u64, PathBuf)> =
+        fs::read_dir(ALLIUM_IMAGE_CACHE_DIR.as_path())?
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let metadata = entry.metadata().ok()?;
+                Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+            })
+            .collect();
+
+    let mut size: u64 = entries.iter().map(|(_, len, _)| len).sum();
+    if size <= IMAGE_CACHE_MAX_SIZE {
+        return Ok(());
+    }
+
+    entries.sort_unstable_by_key(|(modified, _, _)| *modified);
+    for (_, len, path) in entries {
+        if size <= IMAGE_CACHE_MAX_SIZE {
+            break;
+        }
+        debug!("pruning cached image {}", path.display());
+        fs::remove_file(&path)?;
+        size -= len;
+    }
+
+    Ok(())
+}
+
+const MAGIC: &[u8; 4] = b"AIC1";
+const HEADER_SIZE: usize = 12;
+const BYTES_PER_PIXEL: usize = 3;
+
+/// Encodes an image as a header, then each pixel as RGB565 followed by its alpha.
+fn encode(image: &RgbaImage) -> Vec<u8> {
+    let mut data = Vec::with_capacity(HEADER_SIZE + image.as_raw().len() / 4 * BYTES_PER_PIXEL);
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&image.width().to_le_bytes());
+    data.extend_from_slice(&image.height().to_le_bytes());
+    for Rgba([r, g, b, a]) in image.pixels() {
+        let rgb565 = ((*r as u16 >> 3) << 11) | ((*g as u16 >> 2) << 5) | (*b as u16 >> 3);
+        data.extend_from_slice(&rgb565.to_le_bytes());
+        data.push(*a);
+    }
+    data
+}
+
+fn decode(data: &[u8]) -> Result<RgbaImage> {
+    if data.len() < HEADER_SIZE || &data[..4] != MAGIC {
+        bail!("missing header");
+    }
+    let width = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+    let height = u32::from_le_bytes([data[8], data[9], data[10], data[11]]);
+    let pixels = &data[HEADER_SIZE..];
+    if pixels.len() != width as usize * height as usize * BYTES_PER_PIXEL {
+        bail!(
+            "expected {}x{} pixels, got {} bytes",
+            width,
+            height,
+            pixels.len()
+        );
+    }
+
+    let raw = pixels
+        .chunks_exact(BYTES_PER_PIXEL)
+        .flat_map(|pixel| {
+            let rgb565 = u16::from_le_bytes([pixel[0], pixel[1]]);
+            let r = (rgb565 >> 11) as u8;
+            let g = ((rgb565 >> 5) & 0x3F) as u8;
+            let b = (rgb565 & 0x1F) as u8;
+            [
+                (r << 3) | (r >> 2),
+                (g << 2) | (g >> 4),
+                (b << 3) | (b >> 2),
+                pixel[2],
+            ]
+        })
+        .collect();
+    match RgbaImage::from_raw(width, height, raw) {
+        Some(image) => Ok(image),
+        None => bail!("invalid image size"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let mut image = RgbaImage::new(3, 2);
+        image.put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        image.put_pixel(1, 0, Rgba([255, 0, 0, 128]));
+        image.put_pixel(2, 1, Rgba([0, 0, 255, 0]));
+
+        let data = encode(&image);
+        assert_eq!(data.len(), HEADER_SIZE + 3 * 2 * BYTES_PER_PIXEL);
+
+        let decoded = decode(&data).unwrap();
+        assert_eq!(decoded.dimensions(), (3, 2));
+        assert_eq!(decoded.get_pixel(0, 0), &Rgba([255, 255, 255, 255]));
+        assert_eq!(decoded.get_pixel(1, 0), &Rgba([255, 0, 0, 128]));
+        assert_eq!(decoded.get_pixel(2, 1), &Rgba([0, 0, 255, 0]));
+        assert_eq!(decoded.get_pixel(0, 1), &Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_encode_is_rgb565() {
+        let mut image = RgbaImage::new(1, 1);
+        image.put_pixel(0, 0, Rgba([0x12, 0x34, 0x56, 255]));
+
+        let decoded = decode(&encode(&image)).unwrap();
+        assert_eq!(decoded.get_pixel(0, 0), &Rgba([0x10, 0x34, 0x52, 255]));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert!(decode(b"").is_err());
+        assert!(decode(b"PNG\0\0\0\0\0\0\0\0\0").is_err());
+
+        let mut data = encode(&RgbaImage::new(2, 2));
+        data.pop();
+        assert!(decode(&data).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 pub mod color;
 pub mod font;
 pub mod image;
+pub mod image_cache;
 pub mod settings;
 
 use anyhow::Result;
@@ -18,6 +19,9 @@ pub trait Display:
     where
         F: FnMut(Color) -> Color;
 
+    /// Blurs the whole display with a box blur of the given radius, in pixels.
+    fn blur(&mut self, radius: u32) -> Result<()>;
+
     fn flush(&mut self) -> Result<()> {
         Ok(())
     }
@@ -1,11 +1,13 @@
 use std::{
     fs::{self, File},
     io::Write,
+    time::SystemTime,
 };
 
 use anyhow::Result;
 use log::{debug, warn};
 use serde::{Deserialize, Serialize};
+use strum::FromRepr;
 
 use crate::constants::ALLIUM_DISPLAY_SETTINGS;
 
@@ -18,6 +20,47 @@ pub struct DisplaySettings {
     pub r: u8,
     pub g: u8,
     pub b: u8,
+    /// Seconds without input after which the launcher dims the backlight. 0 disables dimming.
+    #[serde(default)]
+    pub idle_dim_seconds: u32,
+    /// What the launcher shows after `screensaver_minutes` without input.
+    #[serde(default)]
+    pub screensaver: Screensaver,
+    #[serde(default = "DisplaySettings::screensaver_minutes")]
+    pub screensaver_minutes: u32,
+    /// Blue light filter, which shifts the color temperature towards red.
+    #[serde(default)]
+    pub night_mode: NightMode,
+    /// How much blue light is filtered in night mode, in percent.
+    #[serde(default = "DisplaySettings::night_mode_strength")]
+    pub night_mode_strength: u8,
+    /// Hour of the day at which scheduled night mode turns on.
+    #[serde(default = "DisplaySettings::night_mode_start")]
+    pub night_mode_start: u32,
+    /// Hour of the day at which scheduled night mode turns off.
+    #[serde(default = "DisplaySettings::night_mode_end")]
+    pub night_mode_end: u32,
+}
+
+/// Screensaver shown by the launcher while the device is idle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, FromRepr)]
+pub enum Screensaver {
+    #[default]
+    Off,
+    /// The time, moving around the screen.
+    Clock,
+    /// Box art of recently played games, with the time below.
+    BoxArt,
+}
+
+/// When night mode is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, FromRepr)]
+pub enum NightMode {
+    #[default]
+    Off,
+    On,
+    /// Between `night_mode_start` and `night_mode_end`.
+    Scheduled,
 }
 
 impl DisplaySettings {
@@ -25,6 +68,50 @@ impl DisplaySettings {
         Self::default()
     }
 
+    fn screensaver_minutes() -> u32 {
+        5
+    }
+
+    fn night_mode_strength() -> u8 {
+        50
+    }
+
+    fn night_mode_start() -> u32 {
+        21
+    }
+
+    fn night_mode_end() -> u32 {
+        7
+    }
+
+    /// Whether night mode is on at the hour of the day. The schedule may wrap around midnight.
+    pub fn is_night_mode(&self, hour: u32) -> bool {
+        match self.night_mode {
+            NightMode::Off => false,
+            NightMode::On => true,
+            NightMode::Scheduled => {
+                let (start, end) = (self.night_mode_start, self.night_mode_end);
+                if start <= end {
+                    start <= hour && hour < end
+                } else {
+                    start <= hour || hour < end
+                }
+            }
+        }
+    }
+
+    /// Returns the red, green and blue gains, in percent, with night mode applied if it's on at
+    /// the hour of the day. Night mode mostly dims blue, and green less so, for a warmer image.
+    pub fn color_gains(&self, hour: u32) -> (u8, u8, u8) {
+        if !self.is_night_mode(hour) {
+            return (self.r, self.g, self.b);
+        }
+        let strength = f32::from(self.night_mode_strength.min(100)) / 100.0;
+        let g = f32::from(self.g) * (1.0 - 0.3 * strength);
+        let b = f32::from(self.b) * (1.0 - 0.7 * strength);
+        (self.r, g.round() as u8, b.round() as u8)
+    }
+
     pub fn load() -> Result<Self> {
         if ALLIUM_DISPLAY_SETTINGS.exists() {
             debug!("found state, loading from file");
@@ -39,11 +126,32 @@ impl DisplaySettings {
         Ok(Self::new())
     }
 
+    /// Loads the settings again for alliumd, which polls them while the launcher may be saving
+    /// them. Unlike `load`, a file that can't be read is left alone and an error is returned.
+    pub fn reload() -> Result<Self> {
+        if !ALLIUM_DISPLAY_SETTINGS.exists() {
+            return Ok(Self::new());
+        }
+        let json = fs::read_to_string(ALLIUM_DISPLAY_SETTINGS.as_path())?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Saves the settings to a temporary file that then replaces the old one, so that alliumd
+    /// never reads a partly written file.
     pub fn save(&self) -> Result<()> {
         let json = serde_json::to_string(&self).unwrap();
-        File::create(ALLIUM_DISPLAY_SETTINGS.as_path())?.write_all(json.as_bytes())?;
+        let temp = ALLIUM_DISPLAY_SETTINGS.with_extension("tmp");
+        File::create(&temp)?.write_all(json.as_bytes())?;
+        fs::rename(&temp, ALLIUM_DISPLAY_SETTINGS.as_path())?;
         Ok(())
     }
+
+    /// Returns the modification time and size of the settings file, which change whenever the
+    /// settings are saved, so that they're only loaded again when they do.
+    pub fn stamp() -> Option<(SystemTime, u64)> {
+        let metadata = fs::metadata(ALLIUM_DISPLAY_SETTINGS.as_path()).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
 }
 
 impl Default for DisplaySettings {
@@ -56,6 +164,54 @@ impl Default for DisplaySettings {
             r: 50,
             g: 50,
             b: 50,
+            idle_dim_seconds: 0,
+            screensaver: Screensaver::Off,
+            screensaver_minutes: Self::screensaver_minutes(),
+            night_mode: NightMode::Off,
+            night_mode_strength: Self::night_mode_strength(),
+            night_mode_start: Self::night_mode_start(),
+            night_mode_end: Self::night_mode_end(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_night_mode_schedule() {
+        let mut settings = DisplaySettings {
+            night_mode: NightMode::Scheduled,
+            night_mode_start: 21,
+            night_mode_end: 7,
+            ..Default::default()
+        };
+        assert!(settings.is_night_mode(23));
+        assert!(settings.is_night_mode(0));
+        assert!(!settings.is_night_mode(7));
+        assert!(!settings.is_night_mode(12));
+
+        settings.night_mode_start = 13;
+        settings.night_mode_end = 15;
+        assert!(settings.is_night_mode(14));
+        assert!(!settings.is_night_mode(15));
+        assert!(!settings.is_night_mode(23));
+
+        settings.night_mode = NightMode::On;
+        assert!(settings.is_night_mode(23));
+    }
+
+    #[test]
+    fn test_night_mode_color_gains() {
+        let mut settings = DisplaySettings {
+            night_mode: NightMode::On,
+            night_mode_strength: 100,
+            ..Default::default()
+        };
+        assert_eq!(settings.color_gains(12), (50, 35, 15));
+
+        settings.night_mode = NightMode::Off;
+        assert_eq!(settings.color_gains(12), (50, 50, 50));
+    }
+}
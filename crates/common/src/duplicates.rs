@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::Result;
+use log::warn;
+
+use crate::constants::ALLIUM_GAMES_DIR;
+use crate::database::{Database, Game};
+use crate::game_tags::GameTags;
+
+/// Games that are likely copies of the same game: they have the same name apart from tags such
+/// as the region or revision, and are for the same console, or have identical contents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateGroup {
+    /// Name of the game, without tags.
+    pub name: String,
+    /// Sorted by path.
+    pub games: Vec<Game>,
+}
+
+/// A file to compute the checksum of, with the size and modification time that the cached
+/// checksum is stored with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumFile {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Unix timestamp, in seconds.
+    pub modified: i64,
+}
+
+impl ChecksumFile {
+    pub fn new(path: PathBuf) -> Result<Self> {
+        let metadata = fs::metadata(&path)?;
+        let modified = metadata.modified()?.duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        Ok(Self {
+            path,
+            size: metadata.len(),
+            modified,
+        })
+    }
+
+    /// Returns the checksum cached in the database, if the file hasn't changed since.
+    pub fn cached(&self, database: &Database) -> Result<Option<u32>> {
+        database.get_crc32(&self.path, self.size, self.modified)
+    }
+
+    pub fn cache(&self, database: &Database, crc32: u32) -> Result<()> {
+        database.set_crc32(&self.path, self.size, self.modified, crc32)
+    }
+}
+
+/// Computes the CRC32 of a file.
+pub fn crc32(path: &Path) -> Result<u32> {
+    let mut file = File::open(path)?;
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let len = file.read(&mut buf)?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buf[..len]);
+    }
+    Ok(hasher.finalize())
+}
+
+/// Returns the CRC32 of a file, from the database if it was computed before.
+pub fn cached_crc32(database: &Database, path: &Path) -> Result<u32> {
+    let file = ChecksumFile::new(path.to_path_buf())?;
+    if let Some(crc32) = file.cached(database)? {
+        return Ok(crc32);
+    }
+    let crc32 = crc32(path)?;
+    file.cache(database, crc32)?;
+    Ok(crc32)
+}
+
+/// Looks for duplicates among the games in the database. Reading every game would be slow, so
+/// only the games with the same size as another game are checksummed, starting with
+/// `DuplicateScan::uncached`.
+#[derive(Debug)]
+pub struct DuplicateScan {
+    games: Vec<Game>,
+    checksums: HashMap<PathBuf, u32>,
+    uncached: Vec<ChecksumFile>,
+}
+
+impl DuplicateScan {
+    /// Finds the games that still exist, and the cached checksums of those that need one.
+    pub fn new(database: &Database) -> Result<Self> {
+        let mut games = Vec::new();
+        let mut files: HashMap<u64, Vec<ChecksumFile>> = HashMap::new();
+        for game in database.select_all_games()? {
+            let Ok(file) = ChecksumFile::new(game.path.clone()) else {
+                continue;
+            };
+            if file.size > 0 {
+                files.entry(file.size).or_default().push(file);
+            }
+            games.push(game);
+        }
+
+        let mut checksums = HashMap::new();
+        let mut uncached = Vec::new();
+        for file in files
+            .into_values()
+            .filter(|files| files.len() > 1)
+            .flatten()
+        {
+            match file.cached(database)? {
+                Some(crc32) => {
+                    checksums.insert(file.path, crc32);
+                }
+                None => uncached.push(file),
+            }
+        }
+
+        Ok(Self {
+            games,
+            checksums,
+            uncached,
+        })
+    }
+
+    /// Takes the files whose checksums still need to be computed, with `compute_checksums`.
+    pub fn take_uncached(&mut self) -> Vec<ChecksumFile> {
+        std::mem::take(&mut self.uncached)
+    }
+
+    /// Caches the computed checksums, and groups the duplicates.
+    pub fn finish(
+        mut self,
+        database: &Database,
+        computed: Vec<(ChecksumFile, u32)>,
+    ) -> Result<Vec<DuplicateGroup>> {
+        for (file, crc32) in computed {
+            file.cache(database, crc32)?;
+            self.checksums.insert(file.path, crc32);
+        }
+        Ok(find_duplicates(
+            self.games,
+            &self.checksums,
+            &ALLIUM_GAMES_DIR,
+        ))
+    }
+}
+
+/// Computes the checksums of files, skipping those that can't be read. This reads every file, so
+/// it should run on a blocking task.
+pub fn compute_checksums(files: Vec<ChecksumFile>) -> Vec<(ChecksumFile, u32)> {
+    files
+        .into_iter()
+        .filter_map(|file| match crc32(&file.path) {
+            Ok(crc32) => Some((file, crc32)),
+            Err(e) => {
+                warn!("failed to checksum {}: {}", file.path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Deletes a duplicate game's file, and forgets it.
+pub fn delete_game(database: &Database, path: &Path) -> Result<()> {
+    fs::remove_file(path)?;
+    database.delete_game(path)?;
+    database.set_hidden(path, false)?;
+    Ok(())
+}
+
+/// Groups games with the same name for the same console, or the same checksum.
+fn find_duplicates(
+    games: Vec<Game>,
+    checksums: &HashMap<PathBuf, u32>,
+    games_dir: &Path,
+) -> Vec<DuplicateGroup> {
+    /// Returns the first game of the group that game `i` is in.
+    fn root(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+
+    let mut parents: Vec<usize> = (0..games.len()).collect();
+    let mut by_name: HashMap<(PathBuf, String), usize> = HashMap::new();
+    let mut by_checksum: HashMap<u32, usize> = HashMap::new();
+    for (i, game) in games.iter().enumerate() {
+        let stem = game.path.file_stem().unwrap_or_default().to_string_lossy();
+        let mut firsts = vec![
+            *by_name
+                .entry((console(&game.path, games_dir), base_name(&stem)))
+                .or_insert(i),
+        ];
+        if let Some(crc32) = checksums.get(&game.path) {
+            firsts.push(*by_checksum.entry(*crc32).or_insert(i));
+        }
+        for first in firsts {
+            let (a, b) = (root(&mut parents, first), root(&mut parents, i));
+            parents[b] = a;
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<Game>> = HashMap::new();
+    for (i, game) in games.into_iter().enumerate() {
+        groups.entry(root(&mut parents, i)).or_default().push(game);
+    }
+
+    let mut groups: Vec<_> = groups
+        .into_values()
+        .filter(|games| games.len() > 1)
+        .map(|mut games| {
+            games.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+            DuplicateGroup {
+                name: strip_tags(&games[0].name),
+                games,
+            }
+        })
+        .collect();
+    groups.sort_unstable_by(|a, b| {
+        (a.name.to_lowercase(), &a.games[0].path).cmp(&(b.name.to_lowercase(), &b.games[0].path))
+    });
+    groups
+}
+
+/// Returns the console folder that a game is in, or its parent if it's not in the games folder.
+fn console(path: &Path, games_dir: &Path) -> PathBuf {
+    match path
+        .strip_prefix(games_dir)
+        .ok()
+        .and_then(|p| p.components().next())
+    {
+        Some(console) if path.parent() != Some(games_dir) => games_dir.join(console),
+        _ => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+    }
+}
+
+/// Returns the name that duplicates share: without tags, and lowercased.
+fn base_name(name: &str) -> String {
+    strip_tags(name).to_lowercase()
+}
+
+/// Removes tags in parentheses or brackets, such as `(USA)` or `[!]`, from a name, as
+/// `GameTags::parse` finds them. Disc numbers are kept, as each disc of a game isn't a duplicate
+/// of the others.
+fn strip_tags(name: &str) -> String {
+    let (title, tags) = GameTags::parse(name);
+    let discs = tags
+        .other
+        .iter()
+        .filter(|tag| is_disc_tag(&tag[1..tag.len() - 1]));
+    std::iter::once(title)
+        .chain(discs.map(String::as_str))
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_disc_tag(tag: &str) -> bool {
+    let tag = tag.to_lowercase();
+    tag.starts_with("disc") || tag.starts_with("disk") || tag.starts_with("side")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn game(path: &str) -> Game {
+        Game {
+            name: Path::new(path)
+                .file_stem()
+                .unwrap()
+                .to_string_lossy()
+                .to_string(),
+            path: PathBuf::from(path),
+            image: None,
+            play_count: 0,
+            play_time: Duration::zero(),
+            last_played: 0,
+            core: None,
+            rating: None,
+            release_date: None,
+            developer: None,
+            publisher: None,
+            genres: Vec::new(),
+            favorite: false,
+            screenshot_path: None,
+            volume: None,
+        }
+    }
+
+    #[test]
+    fn test_strip_tags() {
+        assert_eq!(strip_tags("Super Mario World (USA)"), "Super Mario World");
+        assert_eq!(
+            strip_tags("Pokemon - Red Version (USA, Europe) (SGB Enhanced) [!]"),
+            "Pokemon - Red Version"
+        );
+        assert_eq!(
+            strip_tags("Final Fantasy VII (USA) (Disc 1)"),
+            "Final Fantasy VII (Disc 1)"
+        );
+        assert_eq!(strip_tags("Tetris (Rev 1"), "Tetris (Rev 1");
+        // Only tags at the end are stripped, as the launcher shows them
+        assert_eq!(
+            strip_tags("Kirby (Beta) Deluxe  (Japan)"),
+            "Kirby (Beta) Deluxe"
+        );
+        assert_eq!(base_name("Tetris (World) (Rev A)"), "tetris");
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let games = vec![
+            game("/Roms/SNES/Super Mario World (USA).sfc"),
+            game("/Roms/SNES/Super Mario World (Europe).smc"),
+            game("/Roms/SNES/Hacks/super mario world [!].sfc"),
+            game("/Roms/GB/Tetris (World).gb"),
+            game("/Roms/NES/Tetris (USA).nes"),
+            game("/Roms/GBA/Game One.gba"),
+            game("/Roms/GBA/Copy of Game One.gba"),
+            game("/Roms/GBA/Game Two.gba"),
+            game("/Roms/PS/Final Fantasy VII (Disc 1).chd"),
+            game("/Roms/PS/Final Fantasy VII (Disc 2).chd"),
+        ];
+        let checksums = HashMap::from([
+            (PathBuf::from("/Roms/GBA/Game One.gba"), 1),
+            (PathBuf::from("/Roms/GBA/Copy of Game One.gba"), 1),
+            (PathBuf::from("/Roms/GBA/Game Two.gba"), 2),
+        ]);
+
+        let groups = find_duplicates(games, &checksums, Path::new("/Roms"));
+        let groups: Vec<_> = groups
+            .iter()
+            .map(|group| {
+                (
+                    group.name.as_str(),
+                    group
+                        .games
+                        .iter()
+                        .map(|g| g.path.to_str().unwrap())
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (
+                    "Copy of Game One",
+                    vec!["/Roms/GBA/Copy of Game One.gba", "/Roms/GBA/Game One.gba"]
+                ),
+                (
+                    "super mario world",
+                    vec![
+                        "/Roms/SNES/Hacks/super mario world [!].sfc",
+                        "/Roms/SNES/Super Mario World (Europe).smc",
+                        "/Roms/SNES/Super Mario World (USA).sfc",
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_crc32() -> Result<()> {
+        let path = std::env::temp_dir().join("allium-test-crc32.rom");
+        fs::write(&path, b"123456789")?;
+        let result = crc32(&path);
+        fs::remove_file(&path)?;
+        assert_eq!(result?, 0xCBF43926);
+        Ok(())
+    }
+}
@@ -1,15 +1,22 @@
 use std::{
-    fs::{self, File},
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
     path::{Path, PathBuf},
     process::Command,
 };
 
 use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
-use log::debug;
+use log::{debug, warn};
+use nix::time::{ClockId, clock_gettime};
 use serde::{Deserialize, Serialize};
 
-use crate::constants::{ALLIUM_GAME_INFO, ALLIUM_GAMES_DIR, ALLIUM_SCRIPTS_DIR};
+use crate::constants::{
+    ALLIUM_GAME_INFO, ALLIUM_GAMES_DIR, ALLIUM_LAUNCH_STDERR, ALLIUM_SCRIPTS_DIR,
+    LAUNCH_STDERR_MAX_SIZE,
+};
+use crate::platform::cpu::CpuProfile;
+use crate::view::PdfViewer;
 
 #[derive(Debug, Serialize, Deserialize)]
 /// Information about a game. Used to restore a game after a restart, and to calculate playtime.
@@ -24,7 +31,14 @@ pub struct GameInfo {
     pub command: String,
     /// Arguments to pass to the core to run the game.
     pub args: Vec<String>,
-    /// Do we enable the menu? Currently only enabled if RetroArch is used.
+    /// Directory to run the command in, if not the current one.
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+    /// Environment variables to set for the command.
+    #[serde(default)]
+    pub env: Vec<(String, String)>,
+    /// Whether the in-game menu can be opened. Always enabled for RetroArch, and for standalone
+    /// emulators that are configured to allow it.
     pub has_menu: bool,
     /// Whether swap should be enabled.
     pub needs_swap: bool,
@@ -32,8 +46,28 @@ pub struct GameInfo {
     pub image: Option<PathBuf>,
     /// Path to the guide text file.
     pub guide: Option<PathBuf>,
+    /// Path to the manual, a PDF or text file.
+    #[serde(default)]
+    pub manual: Option<PathBuf>,
     /// Start time. Used to measure playtime.
     pub start_time: DateTime<Utc>,
+    /// Time since boot on the monotonic clock when the game started. Unlike `start_time`, it
+    /// isn't changed by setting the clock, so it's used to measure playtime when known.
+    #[serde(default)]
+    pub start_uptime: Option<std::time::Duration>,
+    /// Whether fast-forward is enabled.
+    #[serde(default)]
+    pub fast_forward: bool,
+    /// Whether rewind is enabled.
+    #[serde(default)]
+    pub rewind: bool,
+    /// CPU profile to run the game with.
+    #[serde(default)]
+    pub cpu_profile: CpuProfile,
+    /// Enabled state of each cheat in RetroArch, by cheat file, as last set from the in-game
+    /// menu. RetroArch can only toggle cheats, so this is needed to set them explicitly.
+    #[serde(default)]
+    pub cheats: HashMap<PathBuf, Vec<bool>>,
 }
 
 impl Default for GameInfo {
@@ -44,11 +78,19 @@ impl Default for GameInfo {
             core: String::new(),
             command: String::new(),
             args: Vec::new(),
+            working_dir: None,
+            env: Vec::new(),
             has_menu: false,
             needs_swap: false,
             image: None,
             guide: None,
+            manual: None,
             start_time: Utc::now(),
+            start_uptime: uptime(),
+            fast_forward: false,
+            rewind: false,
+            cpu_profile: CpuProfile::default(),
+            cheats: HashMap::new(),
         }
     }
 }
@@ -67,6 +109,7 @@ impl GameInfo {
         needs_swap: bool,
     ) -> Self {
         let guide = find_guide(&path);
+        let manual = find_manual(&path).filter(|manual| PdfViewer::can_open(manual));
 
         Self {
             name,
@@ -74,11 +117,19 @@ impl GameInfo {
             core,
             command,
             args,
+            working_dir: None,
+            env: Vec::new(),
             has_menu,
             needs_swap,
             image,
             guide,
+            manual,
             start_time: Utc::now(),
+            start_uptime: uptime(),
+            fast_forward: false,
+            rewind: false,
+            cpu_profile: CpuProfile::default(),
+            cheats: HashMap::new(),
         }
     }
 
@@ -117,39 +168,81 @@ impl GameInfo {
         Ok(())
     }
 
-    /// Returns a command to run the game.
+    /// Returns a command to run the game. Its stderr is written to `ALLIUM_LAUNCH_STDERR`, so
+    /// that alliumd can tell why it exited if it fails to start. The log starts empty at each
+    /// launch, and is appended to so that `trim_launch_stderr` can truncate it while it runs.
     pub fn command(self) -> Command {
         let mut command = Command::new(self.command);
         command.args(self.args);
+        if let Some(working_dir) = self.working_dir {
+            command.current_dir(working_dir);
+        }
+        command.envs(self.env);
+        match open_launch_stderr(&ALLIUM_LAUNCH_STDERR) {
+            Ok(file) => {
+                command.stderr(file);
+            }
+            Err(e) => warn!("failed to create launch stderr file: {}", e),
+        }
         command
     }
 
+    /// Truncates the running game's stderr log once it's larger than `LAUNCH_STDERR_MAX_SIZE`.
+    pub fn trim_launch_stderr() -> Result<()> {
+        trim_log(&ALLIUM_LAUNCH_STDERR, LAUNCH_STDERR_MAX_SIZE)
+    }
+
     /// How long the game has been running.
     pub fn play_time(&self) -> Duration {
+        if let Some(start) = self.start_uptime
+            && let Some(now) = uptime()
+            && let Some(play_time) = now.checked_sub(start)
+        {
+            return Duration::from_std(play_time).unwrap_or_default();
+        }
         Utc::now().signed_duration_since(self.start_time)
     }
 
+    /// Starts measuring play time again, for a game resumed after a restart.
+    pub fn restart_play_time(&mut self) {
+        self.start_time = Utc::now();
+        self.start_uptime = uptime();
+    }
+
     /// Whether swap should be enabled.
     pub fn needs_swap(&self) -> bool {
         self.needs_swap
     }
 }
 
+/// Returns the time since boot on the monotonic clock, which doesn't count time asleep and isn't
+/// changed by setting the clock.
+fn uptime() -> Option<std::time::Duration> {
+    clock_gettime(ClockId::CLOCK_MONOTONIC).ok().map(Into::into)
+}
+
 /// Searches for the guide path, caches it, and returns it
 pub fn find_guide(path: &Path) -> Option<PathBuf> {
-    // Search for Imgs folder upwards, recursively
+    find_document(path, "Guides", &["txt"])
+}
+
+/// Searches for the manual, in a `Manuals` folder next to the rom or in a parent folder.
+pub fn find_manual(path: &Path) -> Option<PathBuf> {
+    find_document(path, "Manuals", &["pdf", "txt"])
+}
+
+/// Searches upwards for a folder named `folder` containing a file with the same relative path as
+/// the rom, and one of the given extensions.
+fn find_document(path: &Path, folder: &str, extensions: &[&str]) -> Option<PathBuf> {
     let mut parent = path.to_path_buf();
-    let mut guide = None;
-    'image: while parent.pop() {
-        let mut guide_path = parent.join("Guides");
-        if guide_path.is_dir() {
-            guide_path.extend(path.strip_prefix(&parent).unwrap());
-            const GUIDE_EXTENSIONS: [&str; 1] = ["txt"];
-            for ext in &GUIDE_EXTENSIONS {
-                guide_path.set_extension(ext);
-                if guide_path.is_file() {
-                    guide = Some(guide_path);
-                    break 'image;
+    while parent.pop() {
+        let mut document_path = parent.join(folder);
+        if document_path.is_dir() {
+            document_path.extend(path.strip_prefix(&parent).unwrap());
+            for ext in extensions {
+                document_path.set_extension(ext);
+                if document_path.is_file() {
+                    return Some(document_path);
                 }
             }
         }
@@ -157,5 +250,68 @@ pub fn find_guide(path: &Path) -> Option<PathBuf> {
             break;
         }
     }
-    guide
+    None
+}
+
+/// Opens an empty log in append mode, so that writes after it's truncated start at its
+/// beginning again instead of leaving a hole.
+fn open_launch_stderr(path: &Path) -> std::io::Result<File> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.set_len(0)?;
+    Ok(file)
+}
+
+fn trim_log(path: &Path, max_size: u64) -> Result<()> {
+    let file = match OpenOptions::new().write(true).open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    if file.metadata()?.len() > max_size {
+        debug!("truncating {:?}", path);
+        file.set_len(0)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn test_trim_log() {
+        let path = std::env::temp_dir().join(format!("allium-stderr-{}.log", std::process::id()));
+        fs::write(&path, "stale").unwrap();
+        let mut file = open_launch_stderr(&path).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+
+        file.write_all(b"0123456789").unwrap();
+        trim_log(&path, 16).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().len(), 10);
+
+        file.write_all(b"0123456789").unwrap();
+        trim_log(&path, 16).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+
+        // Writes after truncation start at the beginning again
+        file.write_all(b"error").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "error");
+
+        fs::remove_file(&path).unwrap();
+        trim_log(&path, 16).unwrap();
+    }
+
+    #[test]
+    fn test_play_time_ignores_clock_changes() {
+        let mut game_info = GameInfo::default();
+        assert!(game_info.start_uptime.is_some());
+        game_info.start_time = Utc::now() - Duration::hours(5);
+        assert!(game_info.play_time() < Duration::minutes(1));
+
+        // Game info saved before play time was measured on the monotonic clock
+        game_info.start_uptime = None;
+        assert!(game_info.play_time() >= Duration::hours(5));
+    }
 }
@@ -0,0 +1,301 @@
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use strum::{EnumCount, FromRepr};
+
+/// Region of a game, from the tags in its file name.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    EnumCount,
+)]
+pub enum Region {
+    World,
+    Usa,
+    Europe,
+    Japan,
+    Asia,
+    Australia,
+    Brazil,
+    Canada,
+    China,
+    France,
+    Germany,
+    Italy,
+    Korea,
+    Netherlands,
+    Spain,
+    Sweden,
+    Taiwan,
+    Uk,
+}
+
+impl Region {
+    /// Returns every region, in order.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..Self::COUNT).filter_map(Self::from_repr)
+    }
+
+    /// Parses a region as written in No-Intro or GoodTools file names.
+    pub fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "World" | "W" => Region::World,
+            "USA" | "U" => Region::Usa,
+            "Europe" | "E" => Region::Europe,
+            "Japan" | "J" => Region::Japan,
+            "Asia" => Region::Asia,
+            "Australia" | "A" => Region::Australia,
+            "Brazil" | "B" => Region::Brazil,
+            "Canada" | "C" => Region::Canada,
+            "China" | "Ch" => Region::China,
+            "France" | "F" => Region::France,
+            "Germany" | "G" => Region::Germany,
+            "Italy" | "I" => Region::Italy,
+            "Korea" | "K" => Region::Korea,
+            "Netherlands" | "Nl" => Region::Netherlands,
+            "Spain" | "S" => Region::Spain,
+            "Sweden" | "Sw" => Region::Sweden,
+            "Taiwan" => Region::Taiwan,
+            "UK" => Region::Uk,
+            _ => return None,
+        })
+    }
+
+    /// Name of the region, as written in No-Intro file names.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Region::World => "World",
+            Region::Usa => "USA",
+            Region::Europe => "Europe",
+            Region::Japan => "Japan",
+            Region::Asia => "Asia",
+            Region::Australia => "Australia",
+            Region::Brazil => "Brazil",
+            Region::Canada => "Canada",
+            Region::China => "China",
+            Region::France => "France",
+            Region::Germany => "Germany",
+            Region::Italy => "Italy",
+            Region::Korea => "Korea",
+            Region::Netherlands => "Netherlands",
+            Region::Spain => "Spain",
+            Region::Sweden => "Sweden",
+            Region::Taiwan => "Taiwan",
+            Region::Uk => "UK",
+        }
+    }
+
+    /// Short code shown next to game names, in place of a flag.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Region::World => "W",
+            Region::Usa => "US",
+            Region::Europe => "EU",
+            Region::Japan => "JP",
+            Region::Asia => "AS",
+            Region::Australia => "AU",
+            Region::Brazil => "BR",
+            Region::Canada => "CA",
+            Region::China => "CN",
+            Region::France => "FR",
+            Region::Germany => "DE",
+            Region::Italy => "IT",
+            Region::Korea => "KR",
+            Region::Netherlands => "NL",
+            Region::Spain => "ES",
+            Region::Sweden => "SE",
+            Region::Taiwan => "TW",
+            Region::Uk => "UK",
+        }
+    }
+}
+
+/// Tags in parentheses or brackets at the end of a No-Intro style file name, such as
+/// `Game (USA, Europe) (En,Fr) (Rev 1) [!]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameTags {
+    pub regions: Vec<Region>,
+    /// Language codes, such as `En`.
+    pub languages: Vec<String>,
+    /// Revision or version, such as `Rev 1` or `v1.1`.
+    pub revision: Option<String>,
+    /// Whether the dump is verified good, tagged `[!]`.
+    pub verified: bool,
+    /// Any other tags, such as `Beta` or `Disc 1`, with their brackets.
+    pub other: Vec<String>,
+}
+
+impl GameTags {
+    /// Splits a file name into the title and its tags. Tags are only parsed at the end of the
+    /// name, so titles with brackets in the middle are kept whole.
+    pub fn parse(name: &str) -> (&str, Self) {
+        let mut tags = Self::default();
+        let mut groups = Vec::new();
+        let mut title = name.trim_end();
+        loop {
+            let open = if title.ends_with(')') {
+                '('
+            } else if title.ends_with(']') {
+                '['
+            } else {
+                break;
+            };
+            let Some(start) = title.rfind(open) else {
+                break;
+            };
+            groups.push(&title[start..]);
+            title = title[..start].trim_end();
+        }
+        if title.is_empty() {
+            return (name.trim(), Self::default());
+        }
+
+        for group in groups.into_iter().rev() {
+            tags.add(group);
+        }
+        (title, tags)
+    }
+
+    /// Parses the tags in a game's file name.
+    pub fn from_path(path: &Path) -> Self {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        Self::parse(&stem).1
+    }
+
+    fn add(&mut self, group: &str) {
+        let inner = &group[1..group.len() - 1];
+        if group == "[!]" {
+            self.verified = true;
+            return;
+        }
+        if group.starts_with('(') {
+            let parts: Vec<_> = inner.split(',').map(str::trim).collect();
+            if let Some(regions) = parts
+                .iter()
+                .map(|p| Region::parse(p))
+                .collect::<Option<Vec<_>>>()
+            {
+                self.regions.extend(regions);
+                return;
+            }
+            if parts.iter().all(|p| is_language(p)) {
+                self.languages.extend(parts.iter().map(|p| p.to_string()));
+                return;
+            }
+            if inner == "Rev" || inner.starts_with("Rev ") || is_version(inner) {
+                self.revision = Some(inner.to_string());
+                return;
+            }
+        }
+        self.other.push(group.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns the region codes, such as `US/EU`.
+    pub fn region_codes(&self) -> Option<String> {
+        if self.regions.is_empty() {
+            return None;
+        }
+        Some(
+            self.regions
+                .iter()
+                .map(Region::code)
+                .collect::<Vec<_>>()
+                .join("/"),
+        )
+    }
+}
+
+impl fmt::Display for GameTags {
+    /// Formats the tags the way they're written in file names.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut groups = Vec::new();
+        if !self.regions.is_empty() {
+            let regions: Vec<_> = self.regions.iter().map(Region::name).collect();
+            groups.push(format!("({})", regions.join(", ")));
+        }
+        if !self.languages.is_empty() {
+            groups.push(format!("({})", self.languages.join(",")));
+        }
+        if let Some(revision) = &self.revision {
+            groups.push(format!("({revision})"));
+        }
+        groups.extend(self.other.iter().cloned());
+        if self.verified {
+            groups.push("[!]".to_string());
+        }
+        write!(f, "{}", groups.join(" "))
+    }
+}
+
+/// Whether a tag is a language code, such as `En` or `Pt-BR`.
+fn is_language(s: &str) -> bool {
+    let code = s.split_once('-').map_or(s, |(code, _)| code);
+    let mut chars = code.chars();
+    code.len() == 2
+        && chars.next().is_some_and(|c| c.is_ascii_uppercase())
+        && chars.next().is_some_and(|c| c.is_ascii_lowercase())
+}
+
+/// Whether a tag is a version, such as `v1.1`.
+fn is_version(s: &str) -> bool {
+    s.strip_prefix('v')
+        .is_some_and(|v| v.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let (title, tags) =
+            GameTags::parse("Pokemon - Red Version (USA, Europe) (SGB Enhanced) [!]");
+        assert_eq!(title, "Pokemon - Red Version");
+        assert_eq!(tags.regions, vec![Region::Usa, Region::Europe]);
+        assert_eq!(tags.other, vec!["(SGB Enhanced)"]);
+        assert!(tags.verified);
+        assert_eq!(tags.region_codes().as_deref(), Some("US/EU"));
+
+        let (title, tags) = GameTags::parse("Legend of Zelda, The (Europe) (En,Fr,De) (Rev 1)");
+        assert_eq!(title, "Legend of Zelda, The");
+        assert_eq!(tags.regions, vec![Region::Europe]);
+        assert_eq!(tags.languages, vec!["En", "Fr", "De"]);
+        assert_eq!(tags.revision.as_deref(), Some("Rev 1"));
+        assert_eq!(tags.to_string(), "(Europe) (En,Fr,De) (Rev 1)");
+
+        let (title, tags) = GameTags::parse("Super Mario Bros. 3 (U) (PRG1) [h1]");
+        assert_eq!(title, "Super Mario Bros. 3");
+        assert_eq!(tags.regions, vec![Region::Usa]);
+        assert_eq!(tags.other, vec!["(PRG1)", "[h1]"]);
+
+        let (title, tags) = GameTags::parse("Game (v1.1)");
+        assert_eq!(title, "Game");
+        assert_eq!(tags.revision.as_deref(), Some("v1.1"));
+    }
+
+    #[test]
+    fn test_parse_untagged() {
+        let (title, tags) = GameTags::parse("Mega Man (Rockman) X");
+        assert_eq!(title, "Mega Man (Rockman) X");
+        assert!(tags.is_empty());
+
+        let (title, tags) = GameTags::parse("(Homebrew)");
+        assert_eq!(title, "(Homebrew)");
+        assert!(tags.is_empty());
+    }
+}
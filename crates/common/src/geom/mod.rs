@@ -106,6 +106,10 @@ impl Rect {
         self.y + self.h as i32
     }
 
+    pub const fn contains(&self, point: Point) -> bool {
+        point.x >= self.x && point.x < self.right() && point.y >= self.y && point.y < self.bottom()
+    }
+
     pub fn union(&self, other: &Self) -> Self {
         if self.w == 0 || self.h == 0 {
             return *other;
@@ -155,7 +159,7 @@ impl From<Rect> for embedded_graphics::primitives::Rectangle {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Alignment {
     Left,
     Center,
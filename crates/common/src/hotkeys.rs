@@ -0,0 +1,212 @@
+use std::fs::{self, File};
+use std::io::Write;
+
+use anyhow::Result;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{ALLIUM_HOTKEY_SETTINGS, ALLIUM_RETROARCH_HOTKEYS_CONFIG};
+use crate::macros::MacroSettings;
+use crate::platform::Key;
+use crate::power::PowerSettings;
+
+/// Keys that can be bound to RetroArch hotkeys.
+pub const HOTKEYS: [Key; 7] = [Key::A, Key::B, Key::Y, Key::L, Key::R, Key::L2, Key::R2];
+
+/// The key that rewinds while it is held with Menu. It's bound in RetroArch's config, so that
+/// alliumd can rewind by holding it down on its virtual keypad.
+pub const REWIND_KEY: Key = Key::L;
+
+/// Keys that are always taken while Menu is held, by alliumd or by the hotkeys in RetroArch's
+/// own config.
+const RESERVED_KEYS: [Key; 11] = [
+    Key::Up,
+    Key::Down,
+    Key::Left,
+    Key::Right,
+    Key::Power,
+    Key::VolUp,
+    Key::VolDown,
+    Key::Select,
+    Key::Start,
+    Key::X,
+    REWIND_KEY,
+];
+
+/// RetroArch hotkeys that Allium binds, pressed while holding Menu like alliumd's hotkeys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HotkeySettings {
+    /// Whether the hotkeys are written into RetroArch's config when a game is launched.
+    /// Otherwise, the hotkeys set in RetroArch are used.
+    pub enabled: bool,
+    pub menu: Option<Key>,
+    pub save_state: Option<Key>,
+    pub load_state: Option<Key>,
+    pub fast_forward: Option<Key>,
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            menu: Some(Key::A),
+            save_state: Some(Key::R2),
+            load_state: Some(Key::L2),
+            fast_forward: Some(Key::R),
+        }
+    }
+}
+
+impl HotkeySettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_HOTKEY_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_HOTKEY_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read hotkey file, removing");
+            fs::remove_file(ALLIUM_HOTKEY_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_HOTKEY_SETTINGS.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+
+    /// Returns the keys that can't be bound, because something else happens when they're
+    /// pressed while holding Menu.
+    pub fn reserved_keys(power: &PowerSettings, macros: &MacroSettings) -> Vec<Key> {
+        let mut keys = RESERVED_KEYS.to_vec();
+        if power.fast_forward_hotkeys {
+            keys.push(Key::R);
+        }
+        keys.push(macros.hotkey);
+        keys.extend(power.battery_saver_key(macros));
+        keys
+    }
+
+    /// Returns the RetroArch config options with the keys bound to them. A key that is reserved
+    /// or already bound to an earlier hotkey is left unbound, so that each chord does one thing.
+    pub fn bindings(&self, reserved: &[Key]) -> Vec<(&'static str, Option<Key>)> {
+        let mut taken = reserved.to_vec();
+        [
+            ("input_menu_toggle", self.menu),
+            ("input_save_state", self.save_state),
+            ("input_load_state", self.load_state),
+            ("input_toggle_fast_forward", self.fast_forward),
+        ]
+        .into_iter()
+        .map(|(option, key)| {
+            let key = key.filter(|key| !taken.contains(key));
+            taken.extend(key);
+            (option, key)
+        })
+        .collect()
+    }
+
+    /// Writes the RetroArch config that binds the hotkeys, which is appended to the RetroArch
+    /// config when a game is launched. Rewind is always bound, the other hotkeys only if they
+    /// are enabled.
+    pub fn write_retroarch_config(&self) -> Result<()> {
+        let mut file = File::create(ALLIUM_RETROARCH_HOTKEYS_CONFIG.as_path())?;
+        writeln!(
+            file,
+            r#"input_enable_hotkey = "{}""#,
+            retroarch_key(Key::Menu)
+        )?;
+        writeln!(file, r#"input_rewind = "{}""#, retroarch_key(REWIND_KEY))?;
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let reserved = Self::reserved_keys(
+            &PowerSettings::load().unwrap_or_default(),
+            &MacroSettings::load().unwrap_or_default(),
+        );
+        for (option, key) in self.bindings(&reserved) {
+            writeln!(file, r#"{option} = "{}""#, key.map_or("nul", retroarch_key))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns RetroArch's name for the keyboard key that the button sends.
+fn retroarch_key(key: Key) -> &'static str {
+    match key {
+        Key::Up => "up",
+        Key::Down => "down",
+        Key::Left => "left",
+        Key::Right => "right",
+        Key::A => "space",
+        Key::B => "ctrl",
+        Key::X => "shift",
+        Key::Y => "alt",
+        Key::Start => "enter",
+        Key::Select => "rctrl",
+        Key::L => "e",
+        Key::R => "t",
+        Key::Menu => "escape",
+        Key::L2 => "tab",
+        Key::R2 => "backspace",
+        Key::Power | Key::VolDown | Key::VolUp | Key::LidClose | Key::Headphones | Key::Unknown => {
+            "nul"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindings() {
+        let settings = HotkeySettings {
+            enabled: true,
+            menu: Some(Key::Y),
+            save_state: Some(Key::R2),
+            load_state: Some(Key::R2),
+            fast_forward: Some(Key::R),
+        };
+        let power = PowerSettings {
+            fast_forward_hotkeys: true,
+            ..Default::default()
+        };
+        let reserved = HotkeySettings::reserved_keys(&power, &MacroSettings::default());
+
+        assert_eq!(
+            settings.bindings(&reserved),
+            vec![
+                ("input_menu_toggle", None),
+                ("input_save_state", Some(Key::R2)),
+                ("input_load_state", None),
+                ("input_toggle_fast_forward", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_battery_saver_hotkey() {
+        let macros = MacroSettings {
+            hotkey: Key::A,
+            ..Default::default()
+        };
+        let mut power = PowerSettings::default();
+        assert!(!HotkeySettings::reserved_keys(&power, &macros).contains(&Key::B));
+
+        power.battery_saver_hotkey = Some(Key::B);
+        assert_eq!(power.battery_saver_key(&macros), Some(Key::B));
+        assert!(HotkeySettings::reserved_keys(&power, &macros).contains(&Key::B));
+
+        // The macro hotkey comes first
+        power.battery_saver_hotkey = Some(Key::A);
+        assert_eq!(power.battery_saver_key(&macros), None);
+    }
+}
@@ -0,0 +1,61 @@
+use anyhow::{Result, bail};
+use serde::Serialize;
+use tokio::process::Command;
+
+use crate::constants::HTTP_TIMEOUT;
+
+/// Fetches a URL with a bearer token, returning the response body.
+pub async fn get_authorized(url: &str, token: &str) -> Result<Vec<u8>> {
+    let output = wget()
+        .arg("--header")
+        .arg(format!("Authorization: Bearer {token}"))
+        .args(["-O", "-", url])
+        .output()
+        .await?;
+    if !output.status.success() {
+        bail!("GET {} failed: {}", url, output.status);
+    }
+    Ok(output.stdout)
+}
+
+/// Posts a JSON body to a URL, discarding the response.
+pub async fn post_json<T: Serialize>(url: &str, body: &T) -> Result<()> {
+    post(wget(), url, body).await
+}
+
+/// Posts a JSON body to a URL with a bearer token, discarding the response.
+pub async fn post_json_authorized<T: Serialize>(url: &str, body: &T, token: &str) -> Result<()> {
+    let mut command = wget();
+    command
+        .arg("--header")
+        .arg(format!("Authorization: Bearer {token}"));
+    post(command, url, body).await
+}
+
+async fn post<T: Serialize>(mut command: Command, url: &str, body: &T) -> Result<()> {
+    let status = command
+        .args([
+            "-O",
+            "/dev/null",
+            "--header",
+            "Content-Type: application/json",
+        ])
+        .arg("--post-data")
+        .arg(serde_json::to_string(body)?)
+        .arg(url)
+        .status()
+        .await?;
+    if !status.success() {
+        bail!("POST {} failed: {}", url, status);
+    }
+    Ok(())
+}
+
+fn wget() -> Command {
+    let mut command = Command::new("wget");
+    command
+        .arg("-q")
+        .arg("-T")
+        .arg(HTTP_TIMEOUT.as_secs().to_string());
+    command
+}
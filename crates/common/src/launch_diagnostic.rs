@@ -0,0 +1,164 @@
+use std::fs::{self, File};
+
+use anyhow::Result;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{ALLIUM_LAUNCH_DIAGNOSTIC, LAUNCH_DIAGNOSTIC_LINES};
+
+/// Why a game failed to launch.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LaunchError {
+    /// No console matches the game's folder.
+    MissingConsole,
+    /// The core is missing, or couldn't be run.
+    BadCore,
+    /// The core needs BIOS files that aren't in the BIOS folder.
+    MissingBios,
+    /// The core doesn't support the game's file extension.
+    UnsupportedExtension,
+    /// The game is on a second storage device that was removed.
+    StorageUnavailable,
+    Unknown,
+}
+
+impl LaunchError {
+    /// Guesses why a core failed from what it wrote to stderr.
+    pub fn classify(stderr: &str) -> Self {
+        let stderr = stderr.to_lowercase();
+        let contains_any = |patterns: &[&str]| patterns.iter().any(|p| stderr.contains(p));
+
+        if contains_any(&["bios", "firmware", "system file"]) {
+            LaunchError::MissingBios
+        } else if contains_any(&["extension", "unsupported file", "not supported"]) {
+            LaunchError::UnsupportedExtension
+        } else if contains_any(&[
+            "libretro core",
+            "shared object",
+            "exec format error",
+            "permission denied",
+            "not found",
+        ]) {
+            LaunchError::BadCore
+        } else {
+            LaunchError::Unknown
+        }
+    }
+
+    /// Locale key of the message explaining the error.
+    pub fn locale_key(self) -> &'static str {
+        match self {
+            LaunchError::MissingConsole => "launch-error-missing-console",
+            LaunchError::BadCore => "launch-error-bad-core",
+            LaunchError::MissingBios => "launch-error-missing-bios",
+            LaunchError::UnsupportedExtension => "launch-error-unsupported-extension",
+            LaunchError::StorageUnavailable => "launch-error-storage-unavailable",
+            LaunchError::Unknown => "launch-error-unknown",
+        }
+    }
+}
+
+/// Why the last game failed to launch. alliumd saves it when a game exits with an error right
+/// after starting, and the launcher shows it once it's started again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchDiagnostic {
+    /// Display name of the game.
+    pub name: String,
+    /// Core used to run the game, if one was found.
+    pub core: Option<String>,
+    pub error: LaunchError,
+    /// Last lines the core wrote to stderr.
+    pub output: String,
+}
+
+impl LaunchDiagnostic {
+    pub fn new(name: String, core: Option<String>, error: LaunchError) -> Self {
+        Self {
+            name,
+            core,
+            error,
+            output: String::new(),
+        }
+    }
+
+    /// Creates a diagnostic from what the core wrote to stderr before it exited.
+    pub fn from_stderr(name: String, core: Option<String>, stderr: &str) -> Self {
+        Self {
+            output: tail(stderr, LAUNCH_DIAGNOSTIC_LINES),
+            ..Self::new(name, core, LaunchError::classify(stderr))
+        }
+    }
+
+    /// Loads the diagnostic saved by alliumd, if any, and removes it so that it's only shown once.
+    pub fn take() -> Result<Option<Self>> {
+        if !ALLIUM_LAUNCH_DIAGNOSTIC.exists() {
+            return Ok(None);
+        }
+        debug!("found launch diagnostic, loading from file");
+        let file = File::open(ALLIUM_LAUNCH_DIAGNOSTIC.as_path())?;
+        let diagnostic = serde_json::from_reader(file)
+            .map_err(|e| warn!("failed to read launch diagnostic: {}", e))
+            .ok();
+        fs::remove_file(ALLIUM_LAUNCH_DIAGNOSTIC.as_path())?;
+        Ok(diagnostic)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_LAUNCH_DIAGNOSTIC.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+}
+
+/// Returns the last `n` non-empty lines of the text.
+fn tail(text: &str, n: usize) -> String {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    lines[lines.len().saturating_sub(n)..].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            LaunchError::classify("[ERROR] Firmware scph1001.bin not found"),
+            LaunchError::MissingBios
+        );
+        assert_eq!(
+            LaunchError::classify("[ERROR] Failed to open libretro core: \"gpsp_libretro.so\""),
+            LaunchError::BadCore
+        );
+        assert_eq!(
+            LaunchError::classify("sh: /mnt/SDCARD/Emu/PICO/launch.sh: not found"),
+            LaunchError::BadCore
+        );
+        assert_eq!(
+            LaunchError::classify("[ERROR] File extension \"zip\" is not supported by this core"),
+            LaunchError::UnsupportedExtension
+        );
+        assert_eq!(
+            LaunchError::classify("Segmentation fault"),
+            LaunchError::Unknown
+        );
+    }
+
+    #[test]
+    fn test_from_stderr() {
+        let diagnostic = LaunchDiagnostic::from_stderr(
+            "Tetris".to_string(),
+            Some("gambatte".to_string()),
+            "[INFO] Loading core\n\n[INFO] a\n[INFO] b\n[ERROR] Missing BIOS gb_bios.bin\n",
+        );
+        assert_eq!(diagnostic.error, LaunchError::MissingBios);
+        assert_eq!(
+            diagnostic.output,
+            "[INFO] a\n[INFO] b\n[ERROR] Missing BIOS gb_bios.bin"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use std::env;
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+use anyhow::Result;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::constants::{ALLIUM_BOOT_VAR, ALLIUM_LAUNCHER_SETTINGS, RECENT_GAMES_LIMIT};
+
+/// A top-level tab of the launcher.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, EnumIter)]
+pub enum LauncherTab {
+    Recents,
+    Games,
+    Apps,
+    Ports,
+    Settings,
+}
+
+impl LauncherTab {
+    /// Index of the tab's view in the launcher.
+    pub fn index(self) -> usize {
+        match self {
+            LauncherTab::Recents => 0,
+            LauncherTab::Games => 1,
+            LauncherTab::Apps => 2,
+            LauncherTab::Ports => 3,
+            LauncherTab::Settings => 4,
+        }
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::iter().find(|tab| tab.index() == index)
+    }
+
+    pub fn locale_key(self) -> &'static str {
+        match self {
+            LauncherTab::Recents => "tab-recents",
+            LauncherTab::Games => "tab-games",
+            LauncherTab::Apps => "tab-apps",
+            LauncherTab::Ports => "tab-ports",
+            LauncherTab::Settings => "tab-settings",
+        }
+    }
+}
+
+/// What the launcher opens when the device boots.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StartupBehavior {
+    /// The tab and folders that were open last, or the startup tab if one is set.
+    #[default]
+    LastView,
+    /// A folder of the games folder, e.g. a console's folder.
+    Folder(PathBuf),
+    /// Launches a game, e.g. to use the device as a kiosk for a single game.
+    Game(PathBuf),
+}
+
+/// Which of the launcher's tabs are shown and in what order, and what's opened on boot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LauncherSettings {
+    /// Visible tabs, in the order they're shown. Settings is always visible, or the tabs could
+    /// never be changed back.
+    pub tabs: Vec<LauncherTab>,
+    /// Tab to open on boot, or `None` to open the last opened tab.
+    pub startup_tab: Option<LauncherTab>,
+    /// What to open on boot.
+    pub startup: StartupBehavior,
+    /// Whether the Games tab lists consoles as sections with their games below, rather than as
+    /// folders.
+    pub group_consoles: bool,
+    /// Maximum number of games listed in the recents.
+    pub recents_limit: i64,
+    /// Games not played in this many days are removed from the recents on boot, or `None` to
+    /// keep them.
+    pub prune_recents_days: Option<u32>,
+    /// Whether the recents are grouped under headers by the day they were last played.
+    pub group_recents_by_day: bool,
+}
+
+impl Default for LauncherSettings {
+    fn default() -> Self {
+        Self {
+            tabs: LauncherTab::iter().collect(),
+            startup_tab: None,
+            startup: StartupBehavior::LastView,
+            group_consoles: false,
+            recents_limit: RECENT_GAMES_LIMIT,
+            prune_recents_days: None,
+            group_recents_by_day: false,
+        }
+    }
+}
+
+impl LauncherSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_LAUNCHER_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_LAUNCHER_SETTINGS.as_path())?;
+            if let Ok(mut json) = serde_json::from_reader::<_, Self>(file) {
+                json.normalize();
+                return Ok(json);
+            }
+            warn!("failed to read launcher file, removing");
+            fs::remove_file(ALLIUM_LAUNCHER_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_LAUNCHER_SETTINGS.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+
+    /// Whether the launcher was started by alliumd on boot, rather than after a game exited.
+    pub fn is_boot() -> bool {
+        env::var_os(ALLIUM_BOOT_VAR).is_some()
+    }
+
+    pub fn is_visible(&self, tab: LauncherTab) -> bool {
+        self.tabs.contains(&tab)
+    }
+
+    /// Shows or hides a tab. Shown tabs are added to the end.
+    pub fn set_visible(&mut self, tab: LauncherTab, visible: bool) {
+        if visible {
+            if !self.is_visible(tab) {
+                self.tabs.push(tab);
+            }
+        } else if tab != LauncherTab::Settings {
+            self.tabs.retain(|t| *t != tab);
+        }
+    }
+
+    /// Moves a visible tab left (negative offset) or right (positive offset).
+    pub fn move_tab(&mut self, tab: LauncherTab, offset: isize) {
+        let Some(i) = self.tabs.iter().position(|t| *t == tab) else {
+            return;
+        };
+        let j = (i as isize + offset).clamp(0, self.tabs.len() as isize - 1) as usize;
+        let tab = self.tabs.remove(i);
+        self.tabs.insert(j, tab);
+    }
+
+    /// View indices of the visible tabs, in order.
+    pub fn order(&self) -> Vec<usize> {
+        self.tabs.iter().map(|tab| tab.index()).collect()
+    }
+
+    /// Removes duplicate tabs, and makes sure the settings tab is visible.
+    fn normalize(&mut self) {
+        let mut seen = Vec::with_capacity(self.tabs.len());
+        self.tabs.retain(|tab| {
+            if seen.contains(tab) {
+                false
+            } else {
+                seen.push(*tab);
+                true
+            }
+        });
+        if !self.is_visible(LauncherTab::Settings) {
+            self.tabs.push(LauncherTab::Settings);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_visible() {
+        let mut settings = LauncherSettings::new();
+        settings.set_visible(LauncherTab::Recents, false);
+        settings.set_visible(LauncherTab::Settings, false);
+        assert_eq!(settings.order(), vec![1, 2, 3, 4]);
+
+        settings.set_visible(LauncherTab::Recents, true);
+        assert_eq!(settings.order(), vec![1, 2, 3, 4, 0]);
+    }
+
+    #[test]
+    fn test_move_tab() {
+        let mut settings = LauncherSettings::new();
+        settings.move_tab(LauncherTab::Settings, -1);
+        assert_eq!(settings.order(), vec![0, 1, 2, 4, 3]);
+
+        settings.move_tab(LauncherTab::Recents, -1);
+        assert_eq!(settings.order(), vec![0, 1, 2, 4, 3]);
+
+        settings.move_tab(LauncherTab::Recents, 1);
+        assert_eq!(settings.order(), vec![1, 0, 2, 4, 3]);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut settings = LauncherSettings {
+            tabs: vec![LauncherTab::Games, LauncherTab::Apps, LauncherTab::Games],
+            ..Default::default()
+        };
+        settings.normalize();
+        assert_eq!(settings.order(), vec![1, 2, 4]);
+    }
+}
@@ -1,18 +1,48 @@
 #![deny(clippy::all, unsafe_op_in_unsafe_fn)]
 #![warn(rust_2018_idioms)]
 
+pub mod analog;
+pub mod audio;
+pub mod background;
 pub mod battery;
+pub mod bluetooth;
+pub mod clock;
 pub mod command;
 pub mod constants;
 pub mod database;
+pub mod developer;
 pub mod display;
+pub mod duplicates;
 pub mod game_info;
+pub mod game_tags;
 pub mod geom;
+pub mod hotkeys;
+pub mod http;
+pub mod launch_diagnostic;
+pub mod launcher;
 pub mod locale;
+pub mod lock;
+pub mod logging;
+pub mod macros;
+pub mod mdns;
+pub mod network;
+pub mod ntp;
+pub mod parental;
+pub mod pin;
 pub mod platform;
+pub mod play_stats;
 pub mod power;
+pub mod reset;
 pub mod resources;
 pub mod retroarch;
+pub mod save_backup;
+pub mod save_sync;
+#[cfg(feature = "simulator")]
+pub mod snapshot;
+pub mod status;
+pub mod storage;
 pub mod stylesheet;
+pub mod sync;
 pub mod view;
+pub mod webhook;
 pub mod wifi;
@@ -1,8 +1,10 @@
 use std::{
     borrow::Cow,
-    collections::HashMap,
-    fmt,
+    collections::{HashMap, HashSet},
+    env, fmt,
     fs::{self, File},
+    path::Path,
+    time::SystemTime,
 };
 
 use anyhow::Result;
@@ -12,21 +14,40 @@ use fluent_templates::{
 use log::{debug, warn};
 use serde::{Deserialize, Serialize};
 
-use crate::constants::{ALLIUM_LOCALE_SETTINGS, ALLIUM_LOCALES_DIR};
+use crate::constants::{
+    ALLIUM_CUSTOM_LOCALES_DIR, ALLIUM_LOCALE_SETTINGS, ALLIUM_LOCALES_DIR, ALLIUM_TIMEZONE,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocaleSettings {
     pub lang: String,
+    /// POSIX TZ value, which has the opposite sign to the UTC offset, e.g. "UTC-8" for UTC+8.
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// Shows keys without a translation instead of falling back to English.
+    #[serde(default)]
+    pub translation_debug: bool,
 }
 
 impl Default for LocaleSettings {
     fn default() -> Self {
         Self {
             lang: "en-US".into(),
+            timezone: default_timezone(),
+            translation_debug: false,
         }
     }
 }
 
+/// Timezones used to be saved only to the timezone file, so it's used if present.
+fn default_timezone() -> String {
+    fs::read_to_string(ALLIUM_TIMEZONE.as_path())
+        .map(|tz| tz.trim().to_string())
+        .ok()
+        .filter(|tz| !tz.is_empty())
+        .unwrap_or_else(|| "UTC-0".into())
+}
+
 impl LocaleSettings {
     pub fn new() -> Self {
         Default::default()
@@ -45,16 +66,33 @@ impl LocaleSettings {
         Ok(Self::new())
     }
 
+    /// Saves the settings. The timezone is also written to the timezone file, which is read
+    /// on boot to set `TZ` for every process.
     pub fn save(&self) -> Result<()> {
         let file = File::create(ALLIUM_LOCALE_SETTINGS.as_path())?;
         serde_json::to_writer(file, &self)?;
+        fs::write(ALLIUM_TIMEZONE.as_path(), &self.timezone)?;
         Ok(())
     }
+
+    /// Sets the timezone of this process. Other processes pick it up when restarted.
+    pub fn set_timezone(&mut self, timezone: &str) {
+        self.timezone = timezone.to_string();
+        unsafe { env::set_var("TZ", timezone) };
+    }
 }
 
 pub struct Locale {
     pub loader: ArcLoader,
+    /// Translations in `ALLIUM_CUSTOM_LOCALES_DIR`, which override the built-in ones.
+    custom: Option<ArcLoader>,
     pub lang: LanguageIdentifier,
+    /// Keys that `lang` has no translation for, and that fall back to English.
+    missing: HashSet<String>,
+    /// Whether missing keys are shown as the key itself, for translators to spot them.
+    debug: bool,
+    /// When the custom translations were last changed, when they were loaded.
+    custom_modified: Option<SystemTime>,
 }
 
 impl Locale {
@@ -63,16 +101,63 @@ impl Locale {
             .customize(|b| b.set_use_isolating(false))
             .build()
             .unwrap();
-        let lang = lang.parse().unwrap();
-        Self { loader, lang }
+        let lang: LanguageIdentifier = lang.parse().unwrap();
+
+        let custom_modified = custom_modified();
+        // Falling back to the same language, so that the built-in translation is used next
+        let custom = custom_modified.and_then(|_| {
+            ArcLoader::builder(ALLIUM_CUSTOM_LOCALES_DIR.as_path(), lang.clone())
+                .customize(|b| b.set_use_isolating(false))
+                .build()
+                .map_err(|e| warn!("failed to load custom translations: {}", e))
+                .ok()
+        });
+
+        let missing = missing_keys(&lang.to_string());
+        if !missing.is_empty() {
+            debug!("{} keys are missing in {}", missing.len(), lang);
+        }
+
+        Self {
+            loader,
+            custom,
+            lang,
+            missing,
+            debug: false,
+            custom_modified,
+        }
+    }
+
+    /// Loads the language of the settings, with missing keys shown if translation debug is on.
+    pub fn from_settings(settings: &LocaleSettings) -> Self {
+        let mut locale = Self::new(&settings.lang);
+        locale.debug = settings.translation_debug;
+        if locale.debug {
+            let mut missing: Vec<_> = locale.missing.iter().collect();
+            missing.sort_unstable();
+            warn!("missing keys in {}: {:?}", locale.lang, missing);
+        }
+        locale
     }
 
     pub fn t(&self, key: &str) -> String {
-        self.loader.lookup(&self.lang, key)
+        if self.debug && self.missing.contains(key) {
+            return format!("[{key}]");
+        }
+        self.custom
+            .as_ref()
+            .and_then(|custom| custom.try_lookup(&self.lang, key))
+            .unwrap_or_else(|| self.loader.lookup(&self.lang, key))
     }
 
     pub fn ta(&self, key: &str, args: &HashMap<Cow<'static, str>, FluentValue<'_>>) -> String {
-        self.loader.lookup_with_args(&self.lang, key, args)
+        if self.debug && self.missing.contains(key) {
+            return format!("[{key}]");
+        }
+        self.custom
+            .as_ref()
+            .and_then(|custom| custom.try_lookup_with_args(&self.lang, key, args))
+            .unwrap_or_else(|| self.loader.lookup_with_args(&self.lang, key, args))
     }
 
     pub fn language(&self) -> String {
@@ -80,10 +165,74 @@ impl Locale {
     }
 
     pub fn languages(&self) -> Vec<String> {
-        let mut vec: Vec<_> = self.loader.locales().map(|i| i.to_string()).collect();
+        let mut vec: Vec<_> = self
+            .loader
+            .locales()
+            .chain(self.custom.iter().flat_map(|custom| custom.locales()))
+            .map(|i| i.to_string())
+            .collect();
         vec.sort_unstable();
+        vec.dedup();
         vec
     }
+
+    /// Returns how many keys the language has no translation for.
+    pub fn missing_count(&self) -> usize {
+        self.missing.len()
+    }
+
+    /// Whether the custom translations changed since they were loaded, so that translators see
+    /// their changes without restarting.
+    pub fn is_outdated(&self) -> bool {
+        custom_modified() != self.custom_modified
+    }
+}
+
+/// Returns when a file in `ALLIUM_CUSTOM_LOCALES_DIR` was last changed, or `None` if there are
+/// no custom translations.
+fn custom_modified() -> Option<SystemTime> {
+    let mut modified = None;
+    for dir in fs::read_dir(ALLIUM_CUSTOM_LOCALES_DIR.as_path())
+        .ok()?
+        .flatten()
+    {
+        for file in fs::read_dir(dir.path()).into_iter().flatten().flatten() {
+            if let Ok(time) = file.metadata().and_then(|m| m.modified()) {
+                modified = modified.max(Some(time));
+            }
+        }
+    }
+    modified
+}
+
+/// Returns the keys of the built-in English translation that neither the built-in nor the custom
+/// translation of the language have.
+fn missing_keys(lang: &str) -> HashSet<String> {
+    let english = message_ids_in(&ALLIUM_LOCALES_DIR.join("en-US"));
+    let mut translated = message_ids_in(&ALLIUM_LOCALES_DIR.join(lang));
+    translated.extend(message_ids_in(&ALLIUM_CUSTOM_LOCALES_DIR.join(lang)));
+    english.difference(&translated).cloned().collect()
+}
+
+/// Returns the IDs of the messages in the Fluent files of a folder.
+fn message_ids_in(dir: &Path) -> HashSet<String> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "ftl"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .flat_map(|ftl| message_ids(&ftl))
+        .collect()
+}
+
+/// Returns the IDs of the messages in a Fluent file.
+fn message_ids(ftl: &str) -> Vec<String> {
+    ftl.lines()
+        .filter(|line| line.starts_with(|c: char| c.is_ascii_alphabetic()))
+        .filter_map(|line| line.split_once('='))
+        .map(|(id, _)| id.trim().to_string())
+        .collect()
 }
 
 impl fmt::Debug for Locale {
@@ -91,3 +240,14 @@ impl fmt::Debug for Locale {
         f.debug_struct("Locale").field("lang", &self.lang).finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_ids() {
+        let ftl = "# Comment\nbutton-back = Back\n-brand = Allium\nmulti =\n    Line one\n    key = not a key\n";
+        assert_eq!(message_ids(ftl), vec!["button-back", "multi"]);
+    }
+}
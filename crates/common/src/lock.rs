@@ -0,0 +1,97 @@
+use std::fs::{self, File};
+
+use anyhow::Result;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::ALLIUM_LOCK_SETTINGS;
+use crate::pin::KeyPin;
+use crate::platform::Key;
+
+/// Lock screen shown by alliumd on boot and when waking from sleep.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LockSettings {
+    pub enabled: bool,
+    /// Buttons to press in order to unlock.
+    pub pattern: KeyPin,
+}
+
+impl LockSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_LOCK_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_LOCK_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader::<_, Self>(file) {
+                if json.pattern.is_unhashed() {
+                    json.save()?;
+                }
+                return Ok(json);
+            }
+            warn!("failed to read lock file, removing");
+            fs::remove_file(ALLIUM_LOCK_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_LOCK_SETTINGS.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+
+    /// Whether the lock screen should be shown. A pattern must be set, or the device could
+    /// never be unlocked.
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.pattern.is_empty()
+    }
+
+    /// Whether the key can be part of a pattern. System keys are handled by alliumd, and the
+    /// start and select keys are used to save or cancel recording a pattern.
+    pub fn is_pattern_key(key: Key) -> bool {
+        !matches!(
+            key,
+            Key::Start
+                | Key::Select
+                | Key::Menu
+                | Key::Power
+                | Key::VolDown
+                | Key::VolUp
+                | Key::LidClose
+                | Key::Headphones
+                | Key::Unknown
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_active() {
+        let mut settings = LockSettings::new();
+        assert!(!settings.is_active());
+
+        settings.enabled = true;
+        assert!(!settings.is_active());
+
+        settings.pattern = KeyPin::new(&[Key::Up, Key::A]);
+        assert!(settings.is_active());
+
+        settings.enabled = false;
+        assert!(!settings.is_active());
+    }
+
+    #[test]
+    fn test_load_plain_pattern() {
+        let settings: LockSettings =
+            serde_json::from_str(r#"{"enabled":true,"pattern":["Up","A"]}"#).unwrap();
+        assert!(settings.is_active());
+        assert!(settings.pattern.verify(&[Key::Up, Key::A]));
+    }
+}
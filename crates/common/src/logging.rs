@@ -0,0 +1,187 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use chrono::Local;
+use log::{LevelFilter, Log, Metadata, Record};
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::constants::{ALLIUM_LOGS_DIR, LOG_FILE_LEVEL, LOG_FILE_MAX_SIZE, LOG_FILE_ROTATIONS};
+
+/// A program whose log is written to its own file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter)]
+pub enum LogComponent {
+    Alliumd,
+    Launcher,
+    Menu,
+}
+
+impl LogComponent {
+    pub fn name(self) -> &'static str {
+        match self {
+            LogComponent::Alliumd => "alliumd",
+            LogComponent::Launcher => "allium-launcher",
+            LogComponent::Menu => "allium-menu",
+        }
+    }
+
+    /// Returns the path of the current log file, or of an older one that was rotated out. 0 is
+    /// the current one.
+    pub fn path(self, rotation: usize) -> PathBuf {
+        if rotation == 0 {
+            ALLIUM_LOGS_DIR.join(format!("{}.log", self.name()))
+        } else {
+            ALLIUM_LOGS_DIR.join(format!("{}.{}.log", self.name(), rotation))
+        }
+    }
+
+    /// Returns the log files that exist, from the newest to the oldest.
+    pub fn files(self) -> Vec<PathBuf> {
+        (0..=LOG_FILE_ROTATIONS)
+            .map(|rotation| self.path(rotation))
+            .filter(|path| path.exists())
+            .collect()
+    }
+
+    /// Returns the log files of every component that exist.
+    pub fn all_files() -> Vec<PathBuf> {
+        Self::iter().flat_map(Self::files).collect()
+    }
+
+    /// Renames each log file to the next older one, dropping the oldest.
+    fn rotate(self) -> Result<()> {
+        for rotation in (0..LOG_FILE_ROTATIONS).rev() {
+            let path = self.path(rotation);
+            if path.exists() {
+                fs::rename(path, self.path(rotation + 1))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes log records to stderr, like `simple_logger`, and to the component's log file, which
+/// is rotated once it gets too big.
+///
+/// `RUST_LOG` sets the level written to stderr. The log file always gets `LOG_FILE_LEVEL` and
+/// above, so that there is something to troubleshoot with on the device.
+struct Logger {
+    component: LogComponent,
+    stderr_level: LevelFilter,
+    file: Mutex<Option<LogFile>>,
+}
+
+struct LogFile {
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(component: LogComponent) -> Result<Self> {
+        fs::create_dir_all(ALLIUM_LOGS_DIR.as_path())?;
+        let path = component.path(0);
+        if fs::metadata(&path).is_ok_and(|m| m.len() >= LOG_FILE_MAX_SIZE) {
+            component.rotate()?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { file, size })
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.stderr_level || metadata.level() <= LOG_FILE_LEVEL
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!(
+            "{} {:<5} {}[{}]: [{}] {}\n",
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            self.component.name(),
+            std::process::id(),
+            record.target(),
+            record.args(),
+        );
+
+        if record.level() <= self.stderr_level {
+            eprint!("{line}");
+        }
+
+        if record.level() > LOG_FILE_LEVEL {
+            return;
+        }
+        let Ok(mut file) = self.file.lock() else {
+            return;
+        };
+        if file.as_ref().is_some_and(|f| f.size >= LOG_FILE_MAX_SIZE) {
+            // Reopening rotates the full file out
+            *file = None;
+        }
+        if file.is_none() {
+            *file = LogFile::open(self.component)
+                .map_err(|e| eprintln!("failed to open log file: {e}"))
+                .ok();
+        }
+        if let Some(file) = file.as_mut()
+            && file.file.write_all(line.as_bytes()).is_ok()
+        {
+            file.size += line.len() as u64;
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock()
+            && let Some(file) = file.as_mut()
+        {
+            file.file.flush().ok();
+        }
+    }
+}
+
+/// Sets up logging for a component. Panics if a logger was already set up.
+pub fn init(component: LogComponent) {
+    let stderr_level = std::env::var("RUST_LOG")
+        .ok()
+        .map_or(LevelFilter::Trace, |level| {
+            LevelFilter::from_str(&level).unwrap_or(LevelFilter::Off)
+        });
+    let logger = Logger {
+        component,
+        stderr_level,
+        file: Mutex::new(None),
+    };
+    log::set_max_level(stderr_level.max(LOG_FILE_LEVEL));
+    log::set_boxed_logger(Box::new(logger)).unwrap();
+
+    // Crashes are what the log files are most often needed for
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        log::error!("{}", info);
+        log::logger().flush();
+        default_hook(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path() {
+        assert!(LogComponent::Alliumd.path(0).ends_with("logs/alliumd.log"));
+        assert!(
+            LogComponent::Menu
+                .path(2)
+                .ends_with("logs/allium-menu.2.log")
+        );
+    }
+}
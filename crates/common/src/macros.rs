@@ -0,0 +1,168 @@
+use std::fs::{self, File};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::Result;
+use enum_map::EnumMap;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{ALLIUM_MACRO_SETTINGS, MACRO_MAX_STEPS};
+use crate::platform::{Key, KeyEvent};
+
+/// A key press or release in a macro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MacroStep {
+    pub key: Key,
+    pub pressed: bool,
+    /// Time since the previous step, in milliseconds.
+    pub delay_ms: u64,
+}
+
+impl MacroStep {
+    pub fn delay(&self) -> Duration {
+        Duration::from_millis(self.delay_ms)
+    }
+
+    pub fn key_event(&self) -> KeyEvent {
+        if self.pressed {
+            KeyEvent::Pressed(self.key)
+        } else {
+            KeyEvent::Released(self.key)
+        }
+    }
+}
+
+/// A recorded input sequence, replayed into the running game with a hotkey.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroSettings {
+    /// Key that plays the macro when pressed while holding Menu.
+    pub hotkey: Key,
+    pub steps: Vec<MacroStep>,
+}
+
+impl Default for MacroSettings {
+    fn default() -> Self {
+        Self {
+            hotkey: Key::Y,
+            steps: Vec::new(),
+        }
+    }
+}
+
+impl MacroSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_MACRO_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_MACRO_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read macro file, removing");
+            fs::remove_file(ALLIUM_MACRO_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_MACRO_SETTINGS.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+
+    /// Returns the modification time and size of the settings file, which change whenever the
+    /// settings are saved, so that they're only loaded again when they do.
+    pub fn stamp() -> Option<(SystemTime, u64)> {
+        let metadata = fs::metadata(ALLIUM_MACRO_SETTINGS.as_path()).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+}
+
+/// Records key events into macro steps.
+#[derive(Debug)]
+pub struct MacroRecorder {
+    steps: Vec<MacroStep>,
+    last: Instant,
+    /// Keys pressed since recording started. Releases of other keys, such as the hotkey that
+    /// started recording, are left out.
+    held: EnumMap<Key, bool>,
+}
+
+impl MacroRecorder {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            steps: Vec::new(),
+            last: now,
+            held: EnumMap::default(),
+        }
+    }
+
+    /// Records a key event. Returns false once the macro is full.
+    pub fn record(&mut self, event: KeyEvent, now: Instant) -> bool {
+        if self.steps.len() >= MACRO_MAX_STEPS {
+            return false;
+        }
+        let (key, pressed) = match event {
+            KeyEvent::Pressed(key) => (key, true),
+            KeyEvent::Released(key) if self.held[key] => (key, false),
+            KeyEvent::Released(_) | KeyEvent::Autorepeat(_) => return true,
+        };
+        self.held[key] = pressed;
+        self.steps.push(MacroStep {
+            key,
+            pressed,
+            delay_ms: now.duration_since(self.last).as_millis() as u64,
+        });
+        self.last = now;
+        true
+    }
+
+    /// Finishes recording, releasing keys that are still held.
+    pub fn finish(mut self) -> Vec<MacroStep> {
+        for (key, held) in self.held {
+            if held {
+                self.steps.push(MacroStep {
+                    key,
+                    pressed: false,
+                    delay_ms: 0,
+                });
+            }
+        }
+        self.steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_macro_recorder() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut recorder = MacroRecorder::new(start);
+        recorder.record(KeyEvent::Released(Key::Select), at(10));
+        recorder.record(KeyEvent::Pressed(Key::Down), at(100));
+        recorder.record(KeyEvent::Autorepeat(Key::Down), at(150));
+        recorder.record(KeyEvent::Pressed(Key::A), at(200));
+        recorder.record(KeyEvent::Released(Key::Down), at(250));
+
+        let step = |key, pressed, delay_ms| MacroStep {
+            key,
+            pressed,
+            delay_ms,
+        };
+        assert_eq!(
+            recorder.finish(),
+            vec![
+                step(Key::Down, true, 100),
+                step(Key::A, true, 100),
+                step(Key::Down, false, 50),
+                step(Key::A, false, 0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use log::{debug, info, warn};
+use mdns_sd::{ServiceDaemon, ServiceInfo};
+
+use crate::constants::{
+    ALLIUM_VERSION, FTP_PORT, MDNS_REFRESH_INTERVAL, SYNC_SERVICE_TYPE, WEB_FILE_BROWSER_PORT,
+    WEB_REMOTE_PORT,
+};
+use crate::wifi::WiFiSettings;
+
+/// Returns the host name the device is advertised as, without the `.local` suffix. The device
+/// name is lowercased and stripped of characters that aren't allowed in host names.
+pub fn host_name(device_name: &str) -> String {
+    let name = device_name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if name.is_empty() {
+        "allium".to_string()
+    } else {
+        format!("allium-{name}")
+    }
+}
+
+/// A service advertised over mDNS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Service {
+    ty: &'static str,
+    name: String,
+    port: u16,
+    properties: Vec<(&'static str, String)>,
+}
+
+/// Returns the services to advertise with the given settings. Nothing is advertised while
+/// Wi-Fi is off.
+fn services(settings: &WiFiSettings) -> Vec<Service> {
+    if !settings.wifi {
+        return Vec::new();
+    }
+
+    let name = if settings.device_name.trim().is_empty() {
+        "Allium".to_string()
+    } else {
+        settings.device_name.trim().to_string()
+    };
+
+    // Always advertised, so the host name resolves even without any servers running
+    let mut services = vec![Service {
+        ty: "_device-info._tcp.local.",
+        name: name.clone(),
+        port: 0,
+        properties: vec![("model", "Allium".to_string())],
+    }];
+    if settings.web_remote {
+        services.push(Service {
+            ty: SYNC_SERVICE_TYPE,
+            name: name.clone(),
+            port: WEB_REMOTE_PORT,
+            properties: vec![("version", ALLIUM_VERSION.to_string())],
+        });
+        services.push(Service {
+            ty: "_http._tcp.local.",
+            name: format!("{name} Remote"),
+            port: WEB_REMOTE_PORT,
+            properties: vec![("path", "/".to_string())],
+        });
+    }
+    if settings.web_file_browser {
+        services.push(Service {
+            ty: "_http._tcp.local.",
+            name: format!("{name} Files"),
+            port: WEB_FILE_BROWSER_PORT,
+            properties: vec![("path", "/".to_string())],
+        });
+    }
+    if settings.ftp {
+        services.push(Service {
+            ty: "_ftp._tcp.local.",
+            name,
+            port: FTP_PORT,
+            properties: Vec::new(),
+        });
+    }
+    services
+}
+
+/// Advertises the device and its services on the local network as `<host name>.local`, so
+/// that companion tools and other devices can find it without an IP address. Wi-Fi settings
+/// are reloaded every `MDNS_REFRESH_INTERVAL`, so changes to the device name and services are
+/// picked up while running.
+pub async fn advertise() -> Result<()> {
+    let daemon = ServiceDaemon::new()?;
+    let mut advertised: Vec<Service> = Vec::new();
+    let mut host_name_advertised = String::new();
+    let mut registered: Vec<String> = Vec::new();
+
+    loop {
+        let settings = WiFiSettings::load()?;
+        let services = services(&settings);
+        let host = host_name(&settings.device_name);
+
+        if services != advertised || host != host_name_advertised {
+            for fullname in registered.drain(..) {
+                if let Err(e) = daemon.unregister(&fullname) {
+                    warn!("failed to unregister {}: {}", fullname, e);
+                }
+            }
+            for service in &services {
+                let properties = service
+                    .properties
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.clone()))
+                    .collect::<HashMap<_, _>>();
+                let info = ServiceInfo::new(
+                    service.ty,
+                    &service.name,
+                    &format!("{host}.local."),
+                    "",
+                    service.port,
+                    properties,
+                )?
+                .enable_addr_auto();
+                debug!("advertising {}", info.get_fullname());
+                registered.push(info.get_fullname().to_string());
+                daemon.register(info)?;
+            }
+            if !services.is_empty() {
+                info!("advertising {} services as {}.local", services.len(), host);
+            }
+            advertised = services;
+            host_name_advertised = host;
+        }
+
+        tokio::time::sleep(MDNS_REFRESH_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_name() {
+        assert_eq!(host_name(""), "allium");
+        assert_eq!(host_name("  "), "allium");
+        assert_eq!(host_name("Living Room"), "allium-living-room");
+        assert_eq!(host_name("Kid's Miyoo #2"), "allium-kid-s-miyoo-2");
+    }
+}
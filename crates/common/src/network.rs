@@ -0,0 +1,132 @@
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::constants::{NETWORK_DNS_HOST, NETWORK_INTERNET_HOST, NETWORK_SPEED_TEST_TIMEOUT};
+
+/// Rough quality of the network connection, based on latency to the internet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum NetworkQuality {
+    Offline,
+    Poor,
+    Fair,
+    Good,
+}
+
+impl NetworkQuality {
+    pub fn from_latency(latency: Option<Duration>) -> Self {
+        match latency {
+            None => NetworkQuality::Offline,
+            Some(latency) if latency < Duration::from_millis(100) => NetworkQuality::Good,
+            Some(latency) if latency < Duration::from_millis(300) => NetworkQuality::Fair,
+            Some(_) => NetworkQuality::Poor,
+        }
+    }
+
+    /// Whether bandwidth-heavy jobs should warn the user before starting.
+    pub fn should_warn(&self) -> bool {
+        *self <= NetworkQuality::Poor
+    }
+}
+
+/// Measures the current network quality by pinging the internet, for jobs that go over it.
+pub async fn quality() -> NetworkQuality {
+    NetworkQuality::from_latency(ping(NETWORK_INTERNET_HOST).await)
+}
+
+/// Measures the current network quality by pinging the default gateway, for jobs that stay on
+/// the local network.
+pub async fn lan_quality() -> NetworkQuality {
+    let latency = match gateway().await {
+        Some(gateway) => ping(&gateway).await,
+        None => None,
+    };
+    NetworkQuality::from_latency(latency)
+}
+
+/// Returns the IP address of the default gateway.
+pub async fn gateway() -> Option<String> {
+    let output = Command::new("ip")
+        .args(["route", "show", "default"])
+        .output()
+        .await
+        .ok()?;
+    let output = String::from_utf8(output.stdout).ok()?;
+
+    // default via 192.168.1.1 dev wlan0
+    let mut words = output.split_whitespace();
+    words.find(|w| *w == "via")?;
+    words.next().map(str::to_string)
+}
+
+/// Pings a host once, returning the round trip time.
+pub async fn ping(host: &str) -> Option<Duration> {
+    let output = Command::new("ping")
+        .args(["-c", "1", "-w", "2", host])
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        debug!("ping {} failed", host);
+        return None;
+    }
+    let output = String::from_utf8(output.stdout).ok()?;
+
+    // 64 bytes from 1.1.1.1: seq=0 ttl=57 time=12.345 ms
+    let time = output
+        .split_whitespace()
+        .find_map(|w| w.strip_prefix("time="))?;
+    let millis = time.parse::<f64>().ok()?;
+    Some(Duration::from_secs_f64(millis / 1000.0))
+}
+
+/// Resolves a host name, returning how long the lookup took.
+pub async fn dns_lookup() -> Option<Duration> {
+    let start = Instant::now();
+    match tokio::net::lookup_host((NETWORK_DNS_HOST, 80)).await {
+        Ok(mut addrs) if addrs.next().is_some() => Some(start.elapsed()),
+        Ok(_) => None,
+        Err(e) => {
+            warn!("failed to resolve {}: {}", NETWORK_DNS_HOST, e);
+            None
+        }
+    }
+}
+
+/// Downloads a test file, returning the download speed in bytes per second. The download is
+/// given up on after `NETWORK_SPEED_TEST_TIMEOUT`.
+pub async fn download_speed(url: &str) -> Option<f64> {
+    match tokio::time::timeout(NETWORK_SPEED_TEST_TIMEOUT, download(url)).await {
+        Ok(speed) => speed,
+        Err(_) => {
+            warn!("speed test download timed out");
+            None
+        }
+    }
+}
+
+/// Downloads a file and discards it, returning the download speed in bytes per second. wget is
+/// killed if this is dropped before it finishes.
+async fn download(url: &str) -> Option<f64> {
+    let start = Instant::now();
+    let mut child = Command::new("wget")
+        .args(["-q", "-O", "-", url])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .ok()?;
+    let mut stdout = child.stdout.take()?;
+    let size = tokio::io::copy(&mut stdout, &mut tokio::io::sink())
+        .await
+        .ok()?;
+    let status = child.wait().await.ok()?;
+    if !status.success() {
+        warn!("speed test download failed: {}", status);
+        return None;
+    }
+    Some(size as f64 / start.elapsed().as_secs_f64())
+}
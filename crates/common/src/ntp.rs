@@ -0,0 +1,143 @@
+use std::time::Instant;
+
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, Duration, Utc};
+use log::{info, warn};
+use tokio::net::UdpSocket;
+
+use crate::constants::{NTP_SERVERS, NTP_TIMEOUT};
+
+const NTP_PORT: u16 = 123;
+const NTP_PACKET_SIZE: usize = 48;
+
+/// Seconds between the NTP epoch (1900) and the Unix epoch (1970).
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+/// Queries the NTP servers in turn, setting the system clock and the RTC from the first one
+/// that answers.
+pub async fn sync() -> Result<DateTime<Utc>> {
+    for server in NTP_SERVERS {
+        match query(server).await {
+            Ok(time) => {
+                info!("setting time from {}: {}", server, time.format("%F %T"));
+                set_clock(time)?;
+                return Ok(time);
+            }
+            Err(e) => warn!("failed to query NTP server {}: {}", server, e),
+        }
+    }
+    bail!("no NTP server answered")
+}
+
+/// Asks an NTP server for the current time, accounting for half of the round trip.
+pub async fn query(server: &str) -> Result<DateTime<Utc>> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect((server, NTP_PORT)).await?;
+
+    let sent = Instant::now();
+    socket.send(&request()).await?;
+    let mut buf = [0; NTP_PACKET_SIZE];
+    let len = tokio::time::timeout(NTP_TIMEOUT, socket.recv(&mut buf)).await??;
+
+    let time = parse_response(&buf[..len])?;
+    Ok(time + Duration::from_std(sent.elapsed() / 2)?)
+}
+
+/// Sets the system clock, then saves it to the RTC so it survives a reboot.
+pub fn set_clock(time: DateTime<Utc>) -> Result<()> {
+    let status = std::process::Command::new("date")
+        .arg("--utc")
+        .arg("--set")
+        .arg(time.format("%F %T").to_string())
+        .status()?;
+    if !status.success() {
+        bail!("date failed: {}", status);
+    }
+    let status = std::process::Command::new("/sbin/hwclock")
+        .arg("--systohc")
+        .arg("--utc")
+        .status()?;
+    if !status.success() {
+        bail!("hwclock failed: {}", status);
+    }
+    Ok(())
+}
+
+/// A client request: no leap second warning, version 3, client mode.
+fn request() -> [u8; NTP_PACKET_SIZE] {
+    let mut packet = [0; NTP_PACKET_SIZE];
+    packet[0] = 0x1b;
+    packet
+}
+
+/// Returns the transmit timestamp of a server response.
+fn parse_response(packet: &[u8]) -> Result<DateTime<Utc>> {
+    if packet.len() < NTP_PACKET_SIZE {
+        bail!("NTP response is too short: {} bytes", packet.len());
+    }
+    if packet[0] & 0x7 != 4 {
+        bail!("NTP response is not from a server");
+    }
+    // Stratum 0 is a "kiss-o'-death", telling the client to back off
+    if packet[1] == 0 {
+        bail!("NTP server refused the request");
+    }
+
+    let seconds = u32::from_be_bytes([packet[40], packet[41], packet[42], packet[43]]) as i64;
+    let fraction = u32::from_be_bytes([packet[44], packet[45], packet[46], packet[47]]) as i64;
+
+    // Timestamps before the Unix epoch belong to the next era, which starts in 2036
+    let seconds = if seconds < NTP_UNIX_OFFSET {
+        seconds + (1 << 32)
+    } else {
+        seconds
+    };
+    let nanos = (fraction * 1_000_000_000) >> 32;
+
+    DateTime::from_timestamp(seconds - NTP_UNIX_OFFSET, nanos as u32)
+        .ok_or_else(|| anyhow!("invalid NTP timestamp"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(seconds: u32, fraction: u32) -> [u8; NTP_PACKET_SIZE] {
+        let mut packet = [0; NTP_PACKET_SIZE];
+        packet[0] = 0x1c;
+        packet[1] = 2;
+        packet[40..44].copy_from_slice(&seconds.to_be_bytes());
+        packet[44..48].copy_from_slice(&fraction.to_be_bytes());
+        packet
+    }
+
+    #[test]
+    fn test_parse_response() {
+        // 2024-01-01 00:00:00.5 UTC
+        let packet = response(3_913_056_000, 1 << 31);
+        let time = parse_response(&packet).unwrap();
+        assert_eq!(time.timestamp(), 1_704_067_200);
+        assert_eq!(time.timestamp_subsec_millis(), 500);
+    }
+
+    #[test]
+    fn test_parse_response_next_era() {
+        // 2040-01-01 00:00:00 UTC, after the NTP timestamp wraps around in 2036
+        let packet = response((2_208_988_800i64 + 2_208_988_800 - (1 << 32)) as u32, 0);
+        let time = parse_response(&packet).unwrap();
+        assert_eq!(time.timestamp(), 2_208_988_800);
+    }
+
+    #[test]
+    fn test_parse_response_invalid() {
+        assert!(parse_response(&[0x1c; 12]).is_err());
+
+        let mut packet = response(3_913_056_000, 0);
+        packet[0] = 0x1b;
+        assert!(parse_response(&packet).is_err());
+
+        let mut packet = response(3_913_056_000, 0);
+        packet[1] = 0;
+        assert!(parse_response(&packet).is_err());
+    }
+}
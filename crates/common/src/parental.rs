@@ -0,0 +1,178 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::ALLIUM_PARENTAL_SETTINGS;
+use crate::database::Database;
+use crate::pin::KeyPin;
+
+/// Parental controls. Daily play time budgets are stored in the database, alongside the play
+/// time recorded each day.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParentalSettings {
+    pub enabled: bool,
+    /// Buttons to press in order to open the parental controls settings.
+    pub pin: KeyPin,
+    /// Console folders that games can't be launched from.
+    pub restricted: Vec<PathBuf>,
+}
+
+/// Why parental controls blocked a game from launching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParentalBlock {
+    /// No play time is left today.
+    PlayTimeLimit,
+    /// The game is in a restricted console folder.
+    Restricted,
+}
+
+impl ParentalSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_PARENTAL_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_PARENTAL_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader::<_, Self>(file) {
+                if json.pin.is_unhashed() {
+                    json.save()?;
+                }
+                return Ok(json);
+            }
+            warn!("failed to read parental file, removing");
+            fs::remove_file(ALLIUM_PARENTAL_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_PARENTAL_SETTINGS.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+
+    /// Whether the game at `path` is in a restricted console folder.
+    pub fn is_restricted(&self, path: &Path) -> bool {
+        self.enabled && self.restricted.iter().any(|dir| path.starts_with(dir))
+    }
+
+    /// Returns the play time left today, or `None` if play time isn't limited today. Play time
+    /// of the game currently running isn't included until it exits.
+    pub fn remaining_today(&self, database: &Database) -> Result<Option<Duration>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+
+        let today = Local::now().date_naive();
+        let Some(budget) = database.get_play_time_budget(today.weekday())? else {
+            return Ok(None);
+        };
+        let played = database.get_daily_play_time(today)?;
+        Ok(Some((budget - played).max(Duration::zero())))
+    }
+
+    /// Checks whether parental controls allow the game at `path` to be launched.
+    pub fn check_launch(&self, database: &Database, path: &Path) -> Result<Option<ParentalBlock>> {
+        if self.is_restricted(path) {
+            return Ok(Some(ParentalBlock::Restricted));
+        }
+        if self.remaining_today(database)? == Some(Duration::zero()) {
+            return Ok(Some(ParentalBlock::PlayTimeLimit));
+        }
+        Ok(None)
+    }
+}
+
+/// Splits play time that ended at `end` into the play time on each day, latest first, so that a
+/// session that runs past midnight counts towards both days.
+pub fn play_time_by_day<Tz: TimeZone>(
+    end: DateTime<Tz>,
+    play_time: Duration,
+) -> Vec<(NaiveDate, Duration)> {
+    let start = end.clone() - play_time.max(Duration::zero());
+    let mut days = Vec::new();
+    let mut date = end.date_naive();
+    let mut day_end = end;
+    while day_end > start {
+        let midnight = day_end
+            .timezone()
+            .from_local_datetime(&date.and_time(NaiveTime::MIN))
+            .earliest()
+            .filter(|midnight| *midnight > start);
+        let day_start = midnight.unwrap_or_else(|| start.clone());
+        let played = day_end.signed_duration_since(day_start.clone());
+        if !played.is_zero() {
+            days.push((date, played));
+        }
+        day_end = day_start;
+        let Some(previous) = date.pred_opt() else {
+            break;
+        };
+        date = previous;
+    }
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::platform::Key;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+    }
+
+    #[test]
+    fn test_play_time_by_day() {
+        let end = Utc.with_ymd_and_hms(2024, 5, 2, 0, 30, 0).unwrap();
+        assert_eq!(
+            play_time_by_day(end, Duration::minutes(90)),
+            vec![
+                (date(2), Duration::minutes(30)),
+                (date(1), Duration::hours(1))
+            ]
+        );
+        assert_eq!(
+            play_time_by_day(end, Duration::minutes(20)),
+            vec![(date(2), Duration::minutes(20))]
+        );
+        assert_eq!(
+            play_time_by_day(end, Duration::hours(25)),
+            vec![
+                (date(2), Duration::minutes(30)),
+                (date(1), Duration::hours(24)),
+                (
+                    NaiveDate::from_ymd_opt(2024, 4, 30).unwrap(),
+                    Duration::minutes(30)
+                ),
+            ]
+        );
+
+        // Ending right at midnight counts only towards the day before
+        let midnight = Utc.with_ymd_and_hms(2024, 5, 2, 0, 0, 0).unwrap();
+        assert_eq!(
+            play_time_by_day(midnight, Duration::minutes(10)),
+            vec![(date(1), Duration::minutes(10))]
+        );
+        assert!(play_time_by_day(end, Duration::zero()).is_empty());
+    }
+
+    #[test]
+    fn test_legacy_pin() {
+        let settings: ParentalSettings =
+            serde_json::from_str(r#"{"enabled": true, "pin": ["A", "B"]}"#).unwrap();
+        assert!(settings.pin.is_unhashed());
+        assert!(settings.pin.verify(&[Key::A, Key::B]));
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(!json.contains("\"A\""));
+    }
+}
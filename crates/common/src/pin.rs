@@ -0,0 +1,143 @@
+use rand::Rng;
+use rand::distr::Alphanumeric;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::platform::Key;
+
+/// Length of the random salt hashed with each PIN.
+const SALT_LENGTH: usize = 16;
+
+/// A sequence of buttons that unlocks something, such as the lock screen. Only a salted hash is
+/// saved, so the buttons can't be read back from the SD card.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "SavedPin")]
+pub struct KeyPin {
+    salt: String,
+    hash: String,
+    /// Number of buttons, so that entering one can be checked once enough have been pressed.
+    len: usize,
+    /// Whether the PIN was loaded from a list of buttons, and should be saved again hashed.
+    #[serde(skip)]
+    unhashed: bool,
+}
+
+/// PINs used to be saved as the list of buttons, which are hashed when loaded.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedPin {
+    Hashed {
+        salt: String,
+        hash: String,
+        len: usize,
+    },
+    Keys(Vec<Key>),
+}
+
+impl From<SavedPin> for KeyPin {
+    fn from(pin: SavedPin) -> Self {
+        match pin {
+            SavedPin::Hashed { salt, hash, len } => Self {
+                salt,
+                hash,
+                len,
+                unhashed: false,
+            },
+            SavedPin::Keys(keys) => Self {
+                unhashed: !keys.is_empty(),
+                ..Self::new(&keys)
+            },
+        }
+    }
+}
+
+impl KeyPin {
+    /// Hashes the buttons with a new salt. No buttons makes an empty PIN, which nothing matches.
+    pub fn new(keys: &[Key]) -> Self {
+        if keys.is_empty() {
+            return Self::default();
+        }
+        let salt = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(SALT_LENGTH)
+            .map(char::from)
+            .collect::<String>();
+        let hash = hash(&salt, keys);
+        Self {
+            salt,
+            hash,
+            len: keys.len(),
+            unhashed: false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the PIN was saved as a list of buttons, before PINs were hashed.
+    pub fn is_unhashed(&self) -> bool {
+        self.unhashed
+    }
+
+    /// Whether the buttons entered are the PIN's.
+    pub fn verify(&self, keys: &[Key]) -> bool {
+        !self.is_empty() && keys.len() == self.len && hash(&self.salt, keys) == self.hash
+    }
+}
+
+fn hash(salt: &str, keys: &[Key]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    for key in keys {
+        hasher.update(format!("{key:?},"));
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let pin = KeyPin::new(&[Key::A, Key::B, Key::Up]);
+        assert_eq!(pin.len(), 3);
+        assert!(pin.verify(&[Key::A, Key::B, Key::Up]));
+        assert!(!pin.verify(&[Key::A, Key::B]));
+        assert!(!pin.verify(&[Key::A, Key::B, Key::Down]));
+        assert!(!pin.verify(&[Key::B, Key::A, Key::Up]));
+
+        let empty = KeyPin::new(&[]);
+        assert!(empty.is_empty());
+        assert!(!empty.verify(&[]));
+    }
+
+    #[test]
+    fn test_salted() {
+        let a = KeyPin::new(&[Key::A, Key::X]);
+        let b = KeyPin::new(&[Key::A, Key::X]);
+        assert_ne!(a.hash, b.hash);
+        assert!(b.verify(&[Key::A, Key::X]));
+    }
+
+    #[test]
+    fn test_saved() {
+        let pin = KeyPin::new(&[Key::L, Key::R]);
+        let json = serde_json::to_string(&pin).unwrap();
+        assert!(!json.contains("\"L\""));
+        let loaded: KeyPin = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, pin);
+
+        // Buttons saved before PINs were hashed
+        let legacy: KeyPin = serde_json::from_str(r#"["L","R"]"#).unwrap();
+        assert!(legacy.is_unhashed());
+        assert!(legacy.verify(&[Key::L, Key::R]));
+        let legacy: KeyPin = serde_json::from_str("[]").unwrap();
+        assert!(legacy.is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+use anyhow::Result;
+use log::info;
+use serde::{Deserialize, Serialize};
+use strum::{EnumCount, FromRepr};
+
+/// CPU performance profile applied while a game is running. Profiles are set through the
+/// cpufreq interface, so frequencies above what the kernel supports are capped by it.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumCount, FromRepr,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum CpuProfile {
+    /// Underclocked, for consoles that need little power, e.g. Game Boy.
+    Powersave,
+    /// The stock behaviour.
+    #[default]
+    Balanced,
+    /// Runs at the stock maximum frequency at all times.
+    Performance,
+    /// Runs above the stock maximum frequency, for demanding consoles, e.g. PlayStation.
+    Overclock,
+}
+
+#[cfg(feature = "miyoo")]
+const CPUFREQ_DIR: &str = "/sys/devices/system/cpu/cpu0/cpufreq";
+
+impl CpuProfile {
+    fn governor(self) -> &'static str {
+        match self {
+            CpuProfile::Powersave | CpuProfile::Balanced => "ondemand",
+            CpuProfile::Performance | CpuProfile::Overclock => "performance",
+        }
+    }
+
+    /// Maximum frequency, in kHz.
+    fn max_frequency(self) -> u32 {
+        match self {
+            CpuProfile::Powersave => 800_000,
+            CpuProfile::Balanced | CpuProfile::Performance => 1_200_000,
+            CpuProfile::Overclock => 1_500_000,
+        }
+    }
+
+    /// Returns the profile to apply, which is `Powersave` while the battery saver is on.
+    pub fn capped(self, battery_saver: bool) -> Self {
+        if battery_saver {
+            CpuProfile::Powersave
+        } else {
+            self
+        }
+    }
+
+    /// Sets the CPU governor and maximum frequency.
+    pub fn apply(self) -> Result<()> {
+        info!(
+            "setting cpu profile: {:?} ({}, {} kHz)",
+            self,
+            self.governor(),
+            self.max_frequency()
+        );
+
+        #[cfg(feature = "miyoo")]
+        {
+            use std::fs;
+            use std::path::Path;
+
+            let dir = Path::new(CPUFREQ_DIR);
+            fs::write(dir.join("scaling_governor"), self.governor())?;
+            fs::write(dir.join("scaling_max_freq"), self.max_frequency().to_string())?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use std::fs::{self, File};
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use strum::FromRepr;
+
+use crate::constants::ALLIUM_BUTTON_SETTINGS;
+use crate::platform::{Key, KeyEvent};
+
+lazy_static! {
+    /// Layout saved in the settings. Changes take effect when the process is restarted.
+    static ref BUTTON_LAYOUT: ButtonLayout = ButtonSettings::load().unwrap_or_default().layout;
+}
+
+/// Which face buttons confirm and cancel in menus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, FromRepr)]
+pub enum ButtonLayout {
+    /// A on the right confirms and B at the bottom cancels, as labelled on the device.
+    #[default]
+    Nintendo,
+    /// The bottom button confirms and the right one cancels, with X and Y swapped too.
+    Xbox,
+}
+
+impl ButtonLayout {
+    /// Returns the saved layout.
+    pub fn current() -> Self {
+        *BUTTON_LAYOUT
+    }
+
+    /// Maps a physical button to the button it acts as in menus. Swapping is its own inverse,
+    /// so this also maps a button shown in a hint to the physical button to press.
+    pub fn map(self, key: Key) -> Key {
+        match (self, key) {
+            (ButtonLayout::Nintendo, key) => key,
+            (ButtonLayout::Xbox, Key::A) => Key::B,
+            (ButtonLayout::Xbox, Key::B) => Key::A,
+            (ButtonLayout::Xbox, Key::X) => Key::Y,
+            (ButtonLayout::Xbox, Key::Y) => Key::X,
+            (ButtonLayout::Xbox, key) => key,
+        }
+    }
+
+    pub fn map_event(self, event: KeyEvent) -> KeyEvent {
+        match event {
+            KeyEvent::Pressed(key) => KeyEvent::Pressed(self.map(key)),
+            KeyEvent::Released(key) => KeyEvent::Released(self.map(key)),
+            KeyEvent::Autorepeat(key) => KeyEvent::Autorepeat(self.map(key)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ButtonSettings {
+    pub layout: ButtonLayout,
+}
+
+impl ButtonSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_BUTTON_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_BUTTON_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read button file, removing");
+            fs::remove_file(ALLIUM_BUTTON_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_BUTTON_SETTINGS.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map() {
+        assert_eq!(ButtonLayout::Nintendo.map(Key::A), Key::A);
+        assert_eq!(ButtonLayout::Xbox.map(Key::A), Key::B);
+        assert_eq!(ButtonLayout::Xbox.map(Key::Y), Key::X);
+        assert_eq!(ButtonLayout::Xbox.map(Key::Start), Key::Start);
+        for key in [Key::A, Key::B, Key::X, Key::Y, Key::L] {
+            assert_eq!(ButtonLayout::Xbox.map(ButtonLayout::Xbox.map(key)), key);
+        }
+        assert_eq!(
+            ButtonLayout::Xbox.map_event(KeyEvent::Released(Key::B)),
+            KeyEvent::Released(Key::A)
+        );
+    }
+}
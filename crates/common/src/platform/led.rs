@@ -0,0 +1,427 @@
+use std::f32::consts::PI;
+use std::fs::{self, File};
+use std::time::Duration;
+
+use anyhow::Result;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use strum::FromRepr;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::constants::ALLIUM_LED_SETTINGS;
+
+/// How often an animated pattern updates the LED.
+const LED_FRAME_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Period of a blink, half of which the LED is on.
+const BLINK_PERIOD: Duration = Duration::from_secs(1);
+
+/// Period of a breath, from off to fully on and back.
+const BREATHE_PERIOD: Duration = Duration::from_secs(3);
+
+/// How the power LED lights up.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, FromRepr)]
+pub enum LedPattern {
+    #[default]
+    Off,
+    Solid,
+    Blink,
+    Breathe,
+}
+
+impl LedPattern {
+    /// Returns the brightness of the LED, from 0 to 1, at a time since the pattern started.
+    pub fn brightness(self, elapsed: Duration) -> f32 {
+        match self {
+            LedPattern::Off => 0.0,
+            LedPattern::Solid => 1.0,
+            LedPattern::Blink => {
+                let t = elapsed.as_secs_f32() % BLINK_PERIOD.as_secs_f32();
+                if t < BLINK_PERIOD.as_secs_f32() / 2.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            LedPattern::Breathe => {
+                let t = elapsed.as_secs_f32() / BREATHE_PERIOD.as_secs_f32();
+                (1.0 - (2.0 * PI * t).cos()) / 2.0
+            }
+        }
+    }
+
+    fn is_animated(self) -> bool {
+        matches!(self, LedPattern::Blink | LedPattern::Breathe)
+    }
+
+    /// Period of the pattern when it's shown by the kernel's timer trigger instead.
+    fn blink_period(self) -> Duration {
+        match self {
+            LedPattern::Breathe => BREATHE_PERIOD,
+            _ => BLINK_PERIOD,
+        }
+    }
+}
+
+/// State of the device that is shown on the power LED.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum LedState {
+    #[default]
+    Normal,
+    Charging,
+    LowBattery,
+    Suspended,
+}
+
+/// Patterns of the power LED for each state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedSettings {
+    /// Whether the LED shows anything. When disabled, it's always off.
+    #[serde(default = "LedSettings::enabled")]
+    pub enabled: bool,
+    #[serde(default = "LedSettings::suspended")]
+    pub suspended: LedPattern,
+    #[serde(default = "LedSettings::low_battery")]
+    pub low_battery: LedPattern,
+    #[serde(default = "LedSettings::charging")]
+    pub charging: LedPattern,
+}
+
+impl Default for LedSettings {
+    fn default() -> Self {
+        Self {
+            enabled: Self::enabled(),
+            suspended: Self::suspended(),
+            low_battery: Self::low_battery(),
+            charging: Self::charging(),
+        }
+    }
+}
+
+impl LedSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn enabled() -> bool {
+        true
+    }
+
+    fn suspended() -> LedPattern {
+        LedPattern::Breathe
+    }
+
+    fn low_battery() -> LedPattern {
+        LedPattern::Blink
+    }
+
+    fn charging() -> LedPattern {
+        LedPattern::Solid
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_LED_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_LED_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read led file, removing");
+            fs::remove_file(ALLIUM_LED_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_LED_SETTINGS.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+
+    /// Returns the pattern that shows the state, or `None` if the LED is left as the system set
+    /// it.
+    pub fn pattern(&self, state: LedState) -> Option<LedPattern> {
+        if !self.enabled {
+            return Some(LedPattern::Off);
+        }
+        match state {
+            LedState::Normal => None,
+            LedState::Charging => Some(self.charging),
+            LedState::LowBattery => Some(self.low_battery),
+            LedState::Suspended => Some(self.suspended),
+        }
+    }
+}
+
+/// Controls the power LED. Animated patterns are played in the background until the pattern is
+/// changed. Blinking is left to the kernel's timer trigger where it's supported, so that the
+/// device doesn't have to wake up to animate the LED while it's suspended.
+#[derive(Debug)]
+pub struct Led {
+    pattern: Option<LedPattern>,
+    animate: bool,
+    /// Trigger and brightness of the LED before it was changed, restored by `LedState::Normal`.
+    original: Option<light::Original>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl Default for Led {
+    fn default() -> Self {
+        Self {
+            pattern: None,
+            animate: true,
+            original: light::original(),
+            task: None,
+        }
+    }
+}
+
+impl Led {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Shows the pattern, replacing the current one, or restores the LED if it's `None`. If
+    /// `animate` is false, breathing is shown as a slow blink.
+    pub fn set_pattern(&mut self, pattern: Option<LedPattern>, animate: bool) {
+        if pattern == self.pattern && animate == self.animate {
+            return;
+        }
+        debug!("setting led pattern: {:?}", pattern);
+        self.pattern = pattern;
+        self.animate = animate;
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+
+        let Some(pattern) = pattern else {
+            if let Some(original) = self.original.as_ref() {
+                light::restore(original);
+            }
+            return;
+        };
+        if (pattern == LedPattern::Blink || pattern.is_animated() && !animate)
+            && light::blink(pattern.blink_period())
+        {
+            return;
+        }
+
+        light::set_trigger("none");
+        if !pattern.is_animated() {
+            light::set(pattern.brightness(Duration::ZERO));
+            return;
+        }
+        if !animate {
+            // Without a timer trigger, the LED stays on rather than waking the device up
+            light::set(1.0);
+            return;
+        }
+        self.task = Some(tokio::spawn(async move {
+            let start = Instant::now();
+            let mut interval = tokio::time::interval(LED_FRAME_INTERVAL);
+            let mut last = None;
+            loop {
+                interval.tick().await;
+                let brightness = pattern.brightness(start.elapsed());
+                if last != Some(brightness) {
+                    light::set(brightness);
+                    last = Some(brightness);
+                }
+            }
+        }));
+    }
+}
+
+impl Drop for Led {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        if let Some(original) = self.original.as_ref() {
+            light::restore(original);
+        }
+    }
+}
+
+/// Returns the selected trigger in the `trigger` file of an LED, which lists the triggers with
+/// the selected one in brackets, e.g. `none [timer] heartbeat`.
+#[cfg_attr(not(any(feature = "miyoo", test)), allow(dead_code))]
+fn selected_trigger(triggers: &str) -> Option<&str> {
+    triggers
+        .split_whitespace()
+        .find_map(|trigger| trigger.strip_prefix('[')?.strip_suffix(']'))
+}
+
+/// The power LED, in the LED class of sysfs. The LED is found by name, as it differs between
+/// devices.
+#[cfg(feature = "miyoo")]
+mod light {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use lazy_static::lazy_static;
+    use log::{debug, warn};
+
+    const LEDS_DIR: &str = "/sys/class/leds";
+
+    lazy_static! {
+        static ref LED: Option<PathBuf> = find();
+    }
+
+    /// Trigger and brightness of the LED set by the system.
+    #[derive(Debug)]
+    pub struct Original {
+        trigger: Option<String>,
+        brightness: String,
+    }
+
+    /// Finds the LED that shows power, falling back to the only LED.
+    fn find() -> Option<PathBuf> {
+        let mut leds: Vec<_> = fs::read_dir(LEDS_DIR)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.join("brightness").exists())
+            .collect();
+        leds.sort();
+        let led = leds
+            .iter()
+            .position(|led| led.to_string_lossy().contains("power"))
+            .map(|i| leds.swap_remove(i))
+            .or_else(|| leds.into_iter().next());
+        debug!("power led: {:?}", led);
+        led
+    }
+
+    pub fn original() -> Option<Original> {
+        let led = LED.as_ref()?;
+        let trigger = fs::read_to_string(led.join("trigger")).ok();
+        Some(Original {
+            trigger: trigger
+                .as_deref()
+                .and_then(super::selected_trigger)
+                .map(str::to_owned),
+            brightness: fs::read_to_string(led.join("brightness"))
+                .ok()?
+                .trim()
+                .to_owned(),
+        })
+    }
+
+    pub fn restore(original: &Original) {
+        if let Some(trigger) = original.trigger.as_deref() {
+            set_trigger(trigger);
+        }
+        write("brightness", &original.brightness);
+    }
+
+    pub fn set_trigger(trigger: &str) {
+        write("trigger", trigger);
+    }
+
+    /// Blinks the LED with the kernel's timer trigger. Returns false if it isn't supported.
+    pub fn blink(period: Duration) -> bool {
+        let half = (period.as_millis() / 2).to_string();
+        write("trigger", "timer")
+            && write("brightness", &max_brightness().to_string())
+            && write("delay_on", &half)
+            && write("delay_off", &half)
+    }
+
+    pub fn set(brightness: f32) {
+        let value = (brightness.clamp(0.0, 1.0) * max_brightness() as f32).round() as u32;
+        write("brightness", &value.to_string());
+    }
+
+    fn max_brightness() -> u32 {
+        LED.as_ref()
+            .and_then(|led| fs::read_to_string(led.join("max_brightness")).ok())
+            .and_then(|max| max.trim().parse().ok())
+            .unwrap_or(1)
+    }
+
+    fn write(file: &str, value: &str) -> bool {
+        let Some(led) = LED.as_ref() else {
+            return false;
+        };
+        match fs::write(led.join(file), value) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("failed to set led {}: {}", file, e);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "miyoo"))]
+mod light {
+    use std::time::Duration;
+
+    #[derive(Debug)]
+    pub struct Original;
+
+    pub fn original() -> Option<Original> {
+        None
+    }
+
+    pub fn restore(_original: &Original) {}
+
+    pub fn set_trigger(_trigger: &str) {}
+
+    pub fn blink(_period: Duration) -> bool {
+        false
+    }
+
+    pub fn set(_brightness: f32) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_brightness() {
+        assert_eq!(LedPattern::Off.brightness(Duration::from_secs(1)), 0.0);
+        assert_eq!(LedPattern::Solid.brightness(Duration::from_secs(1)), 1.0);
+
+        let blink = |ms| LedPattern::Blink.brightness(Duration::from_millis(ms));
+        assert_eq!(blink(200), 1.0);
+        assert_eq!(blink(700), 0.0);
+        assert_eq!(blink(1200), 1.0);
+
+        assert!(LedPattern::Breathe.brightness(Duration::ZERO) < 0.01);
+        assert!(LedPattern::Breathe.brightness(BREATHE_PERIOD / 2) > 0.99);
+        assert!(LedPattern::Breathe.brightness(BREATHE_PERIOD) < 0.01);
+    }
+
+    #[test]
+    fn test_settings_pattern() {
+        let mut settings = LedSettings::new();
+        assert_eq!(settings.pattern(LedState::Normal), None);
+        assert_eq!(
+            settings.pattern(LedState::Charging),
+            Some(LedPattern::Solid)
+        );
+        assert_eq!(
+            settings.pattern(LedState::LowBattery),
+            Some(LedPattern::Blink)
+        );
+        assert_eq!(
+            settings.pattern(LedState::Suspended),
+            Some(LedPattern::Breathe)
+        );
+
+        settings.enabled = false;
+        assert_eq!(settings.pattern(LedState::Suspended), Some(LedPattern::Off));
+    }
+
+    #[test]
+    fn test_selected_trigger() {
+        assert_eq!(selected_trigger("none [timer] heartbeat\n"), Some("timer"));
+        assert_eq!(selected_trigger("[none] timer\n"), Some("none"));
+        assert_eq!(selected_trigger("none timer\n"), None);
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use evdev::{AbsoluteAxisCode, Device, EventType, KeyCode};
+use log::{info, warn};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+use crate::constants::CONTROLLER_SCAN_INTERVAL;
+use crate::platform::{Key, KeyEvent};
+
+/// Names of the connected controllers, by device path.
+static CONNECTED: Mutex<BTreeMap<PathBuf, String>> = Mutex::new(BTreeMap::new());
+
+/// External USB or Bluetooth controllers. Input devices are scanned for new controllers in the
+/// background, and their buttons are reported as the device's own keys.
+pub struct Controllers {
+    events: Receiver<KeyEvent>,
+}
+
+impl Controllers {
+    /// Starts scanning for controllers, ignoring the device's built-in buttons at `internal`.
+    pub fn new(internal: &'static str) -> Self {
+        let (tx, events) = mpsc::channel(32);
+        tokio::spawn(scan(Path::new(internal), tx));
+        Self { events }
+    }
+
+    /// Waits for a key event from any controller.
+    pub async fn recv(&mut self) -> Option<KeyEvent> {
+        self.events.recv().await
+    }
+}
+
+/// Returns the names of the connected controllers.
+pub fn connected() -> Vec<String> {
+    CONNECTED.lock().unwrap().values().cloned().collect()
+}
+
+async fn scan(internal: &'static Path, tx: Sender<KeyEvent>) {
+    let mut interval = tokio::time::interval(CONTROLLER_SCAN_INTERVAL);
+    loop {
+        interval.tick().await;
+        if tx.is_closed() {
+            return;
+        }
+
+        for (path, device) in evdev::enumerate() {
+            if path == internal
+                || CONNECTED.lock().unwrap().contains_key(&path)
+                || !is_gamepad(&device)
+            {
+                continue;
+            }
+            let name = device.name().unwrap_or("Controller").to_string();
+            info!("controller connected: {} ({})", name, path.display());
+            CONNECTED.lock().unwrap().insert(path.clone(), name);
+            tokio::spawn(forward(path, device, tx.clone()));
+        }
+    }
+}
+
+fn is_gamepad(device: &Device) -> bool {
+    device
+        .supported_keys()
+        .is_some_and(|keys| keys.contains(KeyCode::BTN_SOUTH))
+}
+
+/// Forwards the key events of a controller until it's disconnected.
+async fn forward(path: PathBuf, device: Device, tx: Sender<KeyEvent>) {
+    let mut events = match device.into_event_stream() {
+        Ok(events) => events,
+        Err(e) => {
+            warn!("failed to read controller {}: {}", path.display(), e);
+            CONNECTED.lock().unwrap().remove(&path);
+            return;
+        }
+    };
+
+    // The d-pad of most controllers is reported as a hat, with -1, 0 or 1 on each axis
+    let mut hat_x = None;
+    let mut hat_y = None;
+    while let Ok(event) = events.next_event().await {
+        let key_events = match event.event_type() {
+            EventType::KEY => {
+                let key = gamepad_key(KeyCode(event.code()));
+                match event.value() {
+                    0 => vec![KeyEvent::Released(key)],
+                    1 => vec![KeyEvent::Pressed(key)],
+                    _ => vec![KeyEvent::Autorepeat(key)],
+                }
+            }
+            EventType::ABSOLUTE => match AbsoluteAxisCode(event.code()) {
+                AbsoluteAxisCode::ABS_HAT0X => {
+                    move_hat(&mut hat_x, event.value(), Key::Left, Key::Right)
+                }
+                AbsoluteAxisCode::ABS_HAT0Y => {
+                    move_hat(&mut hat_y, event.value(), Key::Up, Key::Down)
+                }
+                _ => continue,
+            },
+            _ => continue,
+        };
+        for key_event in key_events {
+            if tx.send(key_event).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    info!("controller disconnected: {}", path.display());
+    CONNECTED.lock().unwrap().remove(&path);
+}
+
+fn move_hat(held: &mut Option<Key>, value: i32, negative: Key, positive: Key) -> Vec<KeyEvent> {
+    let key = match value.signum() {
+        -1 => Some(negative),
+        1 => Some(positive),
+        _ => None,
+    };
+    if key == *held {
+        return Vec::new();
+    }
+    let events = held
+        .take()
+        .map(KeyEvent::Released)
+        .into_iter()
+        .chain(key.map(KeyEvent::Pressed))
+        .collect();
+    *held = key;
+    events
+}
+
+/// Maps controller buttons to keys by position, so that A is the right face button like on the
+/// device itself.
+fn gamepad_key(code: KeyCode) -> Key {
+    match code {
+        KeyCode::BTN_EAST => Key::A,
+        KeyCode::BTN_SOUTH => Key::B,
+        KeyCode::BTN_NORTH => Key::X,
+        KeyCode::BTN_WEST => Key::Y,
+        KeyCode::BTN_TL => Key::L,
+        KeyCode::BTN_TR => Key::R,
+        KeyCode::BTN_TL2 => Key::L2,
+        KeyCode::BTN_TR2 => Key::R2,
+        KeyCode::BTN_SELECT => Key::Select,
+        KeyCode::BTN_START => Key::Start,
+        KeyCode::BTN_MODE => Key::Menu,
+        KeyCode::BTN_DPAD_UP => Key::Up,
+        KeyCode::BTN_DPAD_DOWN => Key::Down,
+        KeyCode::BTN_DPAD_LEFT => Key::Left,
+        KeyCode::BTN_DPAD_RIGHT => Key::Right,
+        _ => Key::Unknown,
+    }
+}
@@ -1,17 +1,22 @@
+use std::collections::VecDeque;
 use std::fs::File;
 use std::io::Read;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use evdev::{Device, EventStream, EventType};
+use evdev::{AbsoluteAxisCode, Device, EventStream, EventType, KeyCode, SwitchCode};
 use log::info;
 
+use crate::analog::{AnalogNavigator, AnalogSettings, Axis, AxisRange, StickCalibration};
 use crate::constants::MAXIMUM_FRAME_TIME;
+use crate::geom::Point;
+use crate::platform::miyoo::controller::Controllers;
 use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 
+const INPUT_DEVICE: &str = "/dev/input/event0";
+
 impl From<u16> for Key {
     fn from(code: u16) -> Self {
-        use evdev::KeyCode;
         match KeyCode(code) {
             KeyCode::KEY_UP => Key::Up,
             KeyCode::KEY_DOWN => Key::Down,
@@ -36,31 +41,93 @@ impl From<u16> for Key {
     }
 }
 
+/// Returns the key code of the device's own button for a key.
+pub fn key_code(key: Key) -> Option<KeyCode> {
+    Some(match key {
+        Key::Up => KeyCode::KEY_UP,
+        Key::Down => KeyCode::KEY_DOWN,
+        Key::Left => KeyCode::KEY_LEFT,
+        Key::Right => KeyCode::KEY_RIGHT,
+        Key::A => KeyCode::KEY_SPACE,
+        Key::B => KeyCode::KEY_LEFTCTRL,
+        Key::X => KeyCode::KEY_LEFTSHIFT,
+        Key::Y => KeyCode::KEY_LEFTALT,
+        Key::Start => KeyCode::KEY_ENTER,
+        Key::Select => KeyCode::KEY_RIGHTCTRL,
+        Key::L => KeyCode::KEY_E,
+        Key::R => KeyCode::KEY_T,
+        Key::Menu => KeyCode::KEY_ESC,
+        Key::L2 => KeyCode::KEY_TAB,
+        Key::R2 => KeyCode::KEY_BACKSPACE,
+        Key::Power => KeyCode::KEY_POWER,
+        Key::VolDown => KeyCode::KEY_VOLUMEDOWN,
+        Key::VolUp => KeyCode::KEY_VOLUMEUP,
+        Key::LidClose | Key::Headphones | Key::Unknown => return None,
+    })
+}
+
 pub struct EvdevKeys {
     pub events: EventStream,
     lid_switch_poller: Option<LidSwitchPoller>,
+    /// Translates the analog stick to d-pad keys, if the device has one and it's enabled.
+    analog: Option<AnalogNavigator>,
+    /// Key events waiting to be returned, as a stick move can release one key and press another.
+    pending: VecDeque<KeyEvent>,
+    controllers: Controllers,
 }
 
 impl EvdevKeys {
     pub fn new() -> Result<Self> {
+        let device = Device::open(INPUT_DEVICE).unwrap();
+        let settings = AnalogSettings::load()?;
+        let analog = if settings.enabled {
+            settings
+                .calibration
+                .or_else(|| device_calibration(&device))
+                .map(|calibration| AnalogNavigator::new(&settings, calibration))
+        } else {
+            None
+        };
         Ok(Self {
-            events: Device::open("/dev/input/event0")
-                .unwrap()
-                .into_event_stream()?,
+            events: device.into_event_stream()?,
             lid_switch_poller: DefaultPlatform::has_lid().then(|| LidSwitchPoller::new()),
+            analog,
+            pending: VecDeque::new(),
+            controllers: Controllers::new(INPUT_DEVICE),
         })
     }
 
     pub async fn poll(&mut self) -> KeyEvent {
         loop {
+            if let Some(event) = self.pending.pop_front() {
+                return event;
+            }
+
+            // Checked before waiting, so that a stream of axis events can't starve the repeat
+            if let Some(event) = self.analog.as_mut().and_then(|a| a.repeat(Instant::now())) {
+                return event;
+            }
+
             if let Some(lid_event) = self.lid_switch_poller.as_mut().and_then(|lid| lid.poll()) {
                 info!("Lid event detected: {:?}", lid_event);
                 return lid_event;
             }
 
-            let timeout =
-                tokio::time::timeout(Duration::from_millis(500), self.events.next_event());
-            let Ok(result) = timeout.await else {
+            // Wake up in time to autorepeat the held stick direction
+            let wait = self
+                .analog
+                .as_ref()
+                .and_then(AnalogNavigator::next_repeat)
+                .map_or(Duration::from_millis(500), |at| {
+                    at.saturating_duration_since(Instant::now())
+                        .min(Duration::from_millis(500))
+                });
+            let timeout = tokio::time::timeout(wait, self.events.next_event());
+            let result = tokio::select! {
+                result = timeout => result,
+                Some(event) = self.controllers.recv() => return event,
+            };
+            let Ok(result) = result else {
                 continue;
             };
             let event = result.unwrap();
@@ -78,12 +145,78 @@ impl EvdevKeys {
                         _ => unreachable!(),
                     };
                 }
+                EventType::ABSOLUTE => {
+                    let Some(analog) = self.analog.as_mut() else {
+                        continue;
+                    };
+                    let axis = match AbsoluteAxisCode(event.code()) {
+                        AbsoluteAxisCode::ABS_X => Axis::X,
+                        AbsoluteAxisCode::ABS_Y => Axis::Y,
+                        _ => continue,
+                    };
+                    self.pending
+                        .extend(analog.set_axis(axis, event.value(), Instant::now()));
+                }
+                EventType::SWITCH if event.code() == SwitchCode::SW_HEADPHONE_INSERT.0 => {
+                    info!("headphone event detected: {}", event.value());
+                    return if event.value() == 0 {
+                        KeyEvent::Released(Key::Headphones)
+                    } else {
+                        KeyEvent::Pressed(Key::Headphones)
+                    };
+                }
                 _ => {}
             }
         }
     }
 }
 
+/// Reads the raw position of the analog stick.
+pub fn read_analog_stick() -> Option<Point> {
+    let device = Device::open(INPUT_DEVICE).ok()?;
+    if !has_analog_stick(&device) {
+        return None;
+    }
+    let state = device.get_abs_state().ok()?;
+    Some(Point::new(
+        state[AbsoluteAxisCode::ABS_X.0 as usize].value,
+        state[AbsoluteAxisCode::ABS_Y.0 as usize].value,
+    ))
+}
+
+/// Reads whether headphones are plugged in, from the headphone switch of the input device.
+pub fn read_headphones_connected() -> bool {
+    Device::open(INPUT_DEVICE)
+        .and_then(|device| device.get_switch_state())
+        .is_ok_and(|state| state.contains(SwitchCode::SW_HEADPHONE_INSERT))
+}
+
+fn has_analog_stick(device: &Device) -> bool {
+    device.supported_absolute_axes().is_some_and(|axes| {
+        axes.contains(AbsoluteAxisCode::ABS_X) && axes.contains(AbsoluteAxisCode::ABS_Y)
+    })
+}
+
+/// Returns the range of the analog stick reported by the device, for when it isn't calibrated.
+fn device_calibration(device: &Device) -> Option<StickCalibration> {
+    if !has_analog_stick(device) {
+        return None;
+    }
+    let state = device.get_abs_state().ok()?;
+    let range = |axis: AbsoluteAxisCode| {
+        let info = state[axis.0 as usize];
+        AxisRange {
+            min: info.minimum,
+            center: (info.minimum + info.maximum) / 2,
+            max: info.maximum,
+        }
+    };
+    Some(StickCalibration {
+        x: range(AbsoluteAxisCode::ABS_X),
+        y: range(AbsoluteAxisCode::ABS_Y),
+    })
+}
+
 struct LidSwitchPoller {
     is_lid_open: bool,
 }
@@ -7,6 +7,7 @@ use log::{trace, warn};
 
 use crate::display::Display;
 use crate::display::color::Color;
+use crate::display::image::box_blur;
 use crate::geom::Rect;
 
 pub struct Buffer {
@@ -74,6 +75,16 @@ impl Display for FramebufferDisplay {
         Ok(())
     }
 
+    fn blur(&mut self, radius: u32) -> Result<()> {
+        box_blur(
+            &mut self.framebuffer.buffer,
+            self.framebuffer.size.width as usize,
+            self.framebuffer.bytes_per_pixel as usize,
+            radius as usize,
+        );
+        Ok(())
+    }
+
     fn flush(&mut self) -> Result<()> {
         let (xoffset, yoffset) = (
             self.iface.var_screen_info.xoffset as usize,
@@ -1,7 +1,9 @@
 mod battery;
+mod controller;
 mod evdev;
 mod framebuffer;
 mod screen;
+mod uinput;
 mod volume;
 
 use std::fmt;
@@ -12,20 +14,28 @@ use std::process::Command;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use chrono::{Local, Timelike};
 use log::warn;
 
 use crate::battery::Battery;
 use crate::display::settings::DisplaySettings;
+use crate::geom::Point;
+use crate::macros::MacroStep;
 use crate::platform::KeyEvent;
 use crate::platform::Platform;
+use crate::platform::layout::ButtonLayout;
 use crate::platform::miyoo::evdev::EvdevKeys;
 use crate::platform::miyoo::framebuffer::FramebufferDisplay;
+use crate::platform::miyoo::uinput::VirtualKeys;
 
 use self::battery::{Miyoo283Battery, Miyoo354Battery};
 
 pub struct MiyooPlatform {
     model: MiyooDeviceModel,
     keys: EvdevKeys,
+    layout: ButtonLayout,
+    /// Created the first time a macro is played.
+    virtual_keys: Option<VirtualKeys>,
 }
 
 pub struct SuspendContext {
@@ -61,11 +71,17 @@ impl Platform for MiyooPlatform {
         Ok(MiyooPlatform {
             model,
             keys: EvdevKeys::new()?,
+            layout: ButtonLayout::current(),
+            virtual_keys: None,
         })
     }
 
     async fn poll(&mut self) -> KeyEvent {
-        self.keys.poll().await
+        self.layout.map_event(self.keys.poll().await)
+    }
+
+    fn set_button_layout(&mut self, layout: ButtonLayout) {
+        self.layout = layout;
     }
 
     fn display(&mut self) -> Result<FramebufferDisplay> {
@@ -111,10 +127,10 @@ impl Platform for MiyooPlatform {
         Ok(())
     }
 
-    fn set_volume(&mut self, volume: i32) -> Result<()> {
+    fn set_volume(&mut self, level: i32) -> Result<()> {
         match self.model {
             MiyooDeviceModel::Miyoo283 => Ok(()),
-            MiyooDeviceModel::Miyoo285 | MiyooDeviceModel::Miyoo354 => volume::set_volume(volume),
+            MiyooDeviceModel::Miyoo285 | MiyooDeviceModel::Miyoo354 => volume::set_volume(level),
         }
     }
 
@@ -144,6 +160,7 @@ impl Platform for MiyooPlatform {
             settings.g = 15;
             settings.b = 15;
         }
+        let (r, g, b) = settings.color_gains(Local::now().hour());
 
         file.write_all(
             format!(
@@ -155,9 +172,9 @@ impl Platform for MiyooPlatform {
         file.write_all(
             format!(
                 "colortemp 0 0 0 0 {:.0} {:.0} {:.0}\n",
-                settings.b as f32 * 255.0 / 100.0,
-                settings.g as f32 * 255.0 / 100.0,
-                settings.r as f32 * 255.0 / 100.0,
+                b as f32 * 255.0 / 100.0,
+                g as f32 * 255.0 / 100.0,
+                r as f32 * 255.0 / 100.0,
             )
             .as_bytes(),
         )?;
@@ -165,6 +182,16 @@ impl Platform for MiyooPlatform {
         Ok(())
     }
 
+    fn play_keys(&mut self, steps: Vec<MacroStep>) -> Result<()> {
+        if self.virtual_keys.is_none() {
+            self.virtual_keys = Some(VirtualKeys::new()?);
+        }
+        if let Some(virtual_keys) = self.virtual_keys.as_ref() {
+            virtual_keys.play(steps);
+        }
+        Ok(())
+    }
+
     fn device_model() -> String {
         detect_model().to_string()
     }
@@ -186,6 +213,22 @@ impl Platform for MiyooPlatform {
             MiyooDeviceModel::Miyoo285 => true,
         }
     }
+
+    fn has_touchscreen() -> bool {
+        false
+    }
+
+    fn headphones_connected() -> bool {
+        evdev::read_headphones_connected()
+    }
+
+    fn analog_stick() -> Option<Point> {
+        evdev::read_analog_stick()
+    }
+
+    fn controllers() -> Vec<String> {
+        controller::connected()
+    }
 }
 
 impl Default for MiyooPlatform {
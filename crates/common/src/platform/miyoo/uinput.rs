@@ -0,0 +1,50 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use enum_map::Enum;
+use evdev::uinput::VirtualDevice;
+use evdev::{AttributeSet, EventType, InputEvent, KeyCode};
+use log::warn;
+
+use crate::macros::MacroStep;
+use crate::platform::Key;
+use crate::platform::miyoo::evdev::key_code;
+
+/// A virtual input device with the same keys as the device, used to play back macros into the
+/// running game.
+pub struct VirtualKeys {
+    device: Arc<Mutex<VirtualDevice>>,
+}
+
+impl VirtualKeys {
+    pub fn new() -> Result<Self> {
+        let keys = (0..Key::LENGTH)
+            .filter_map(|i| key_code(Key::from_usize(i)))
+            .collect::<AttributeSet<KeyCode>>();
+        let device = VirtualDevice::builder()?
+            .name("Allium Macro")
+            .with_keys(&keys)?
+            .build()?;
+        Ok(Self {
+            device: Arc::new(Mutex::new(device)),
+        })
+    }
+
+    /// Plays back the steps in the background.
+    pub fn play(&self, steps: Vec<MacroStep>) {
+        let device = Arc::clone(&self.device);
+        tokio::spawn(async move {
+            for step in steps {
+                tokio::time::sleep(step.delay()).await;
+                let Some(code) = key_code(step.key) else {
+                    continue;
+                };
+                let event = InputEvent::new(EventType::KEY.0, code.0, step.pressed as i32);
+                if let Err(e) = device.lock().unwrap().emit(&[event]) {
+                    warn!("failed to play macro: {}", e);
+                    return;
+                }
+            }
+        });
+    }
+}
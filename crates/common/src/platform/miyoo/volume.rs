@@ -2,8 +2,8 @@ use anyhow::Result;
 use log::debug;
 use std::process::Command;
 
-const MIN_VOLUME: i32 = 0;
-const MAX_VOLUME: i32 = 20;
+const MIN_VOLUME_RAW: i32 = -60;
+const MAX_VOLUME_RAW: i32 = 0;
 
 /// Set volume output between -60 and 30
 fn set_volume_raw(volume: i32) -> Result<()> {
@@ -15,13 +15,12 @@ fn set_volume_raw(volume: i32) -> Result<()> {
     Ok(())
 }
 
-// Volume curve:
-// |   0 |   1 |   2 |   3 |   4 |   5 |   6 |   7 |   8 |   9 |  10 |  11 |  12 |  13 |  14 |  15 |  16 |  17 |  18 |  19 |  20 |
-// | -60 | -46 | -38 | -33 | -28 | -25 | -22 | -19 | -17 | -15 | -13 | -11 |  -9 |  -8 |  -7 |  -5 |  -4 |  -3 |  -2 |  -1 |   0 |
-pub fn set_volume(volume: i32) -> Result<()> {
-    let volume = volume.clamp(MIN_VOLUME, MAX_VOLUME);
-    let volume_raw = (volume as f32 + 1.0).log10() / 21f32.log10() * 60.0 - 60.0;
-    debug!("set volume: {}", volume_raw as i32);
-    set_volume_raw(volume_raw as i32)?;
+/// Set the output level between 0 and 100, which maps linearly to -60 and 0. The volume curve is
+/// applied to the level by `AudioSettings`.
+pub fn set_volume(level: i32) -> Result<()> {
+    let level = level.clamp(0, 100);
+    let volume_raw = MIN_VOLUME_RAW + (MAX_VOLUME_RAW - MIN_VOLUME_RAW) * level / 100;
+    debug!("set volume: {}", volume_raw);
+    set_volume_raw(volume_raw)?;
     Ok(())
 }
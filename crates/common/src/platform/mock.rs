@@ -6,7 +6,9 @@ use crate::battery::Battery;
 use crate::display::Display;
 use crate::display::color::Color;
 use crate::display::settings::DisplaySettings;
-use crate::geom::Rect;
+use crate::geom::{self, Rect};
+use crate::macros::MacroStep;
+use crate::platform::layout::ButtonLayout;
 use crate::platform::{KeyEvent, Platform};
 
 pub const SCREEN_WIDTH: u32 = 640;
@@ -32,6 +34,8 @@ impl Platform for MockPlatform {
         Ok(MockDisplay)
     }
 
+    fn set_button_layout(&mut self, _layout: ButtonLayout) {}
+
     fn battery(&self) -> Result<Self::Battery> {
         Ok(MockBattery)
     }
@@ -48,7 +52,7 @@ impl Platform for MockPlatform {
         Ok(())
     }
 
-    fn set_volume(&mut self, _volume: i32) -> Result<()> {
+    fn set_volume(&mut self, _level: i32) -> Result<()> {
         Ok(())
     }
 
@@ -64,6 +68,10 @@ impl Platform for MockPlatform {
         Ok(())
     }
 
+    fn play_keys(&mut self, _steps: Vec<MacroStep>) -> Result<()> {
+        Ok(())
+    }
+
     fn device_model() -> String {
         "Mock".into()
     }
@@ -79,6 +87,22 @@ impl Platform for MockPlatform {
     fn has_lid() -> bool {
         false
     }
+
+    fn has_touchscreen() -> bool {
+        false
+    }
+
+    fn headphones_connected() -> bool {
+        false
+    }
+
+    fn analog_stick() -> Option<geom::Point> {
+        None
+    }
+
+    fn controllers() -> Vec<String> {
+        Vec::new()
+    }
 }
 
 impl Default for MockPlatform {
@@ -97,6 +121,10 @@ impl Display for MockDisplay {
         Ok(())
     }
 
+    fn blur(&mut self, _radius: u32) -> Result<()> {
+        Ok(())
+    }
+
     fn save(&mut self) -> Result<()> {
         Ok(())
     }
@@ -6,6 +6,12 @@ mod miyoo;
 #[cfg(feature = "simulator")]
 mod simulator;
 
+pub mod cpu;
+pub mod layout;
+pub mod led;
+pub mod rumble;
+pub mod touch;
+
 use anyhow::Result;
 use async_trait::async_trait;
 use enum_map::Enum;
@@ -14,6 +20,9 @@ use serde::{Deserialize, Serialize};
 use crate::{
     battery::Battery,
     display::{Display, settings::DisplaySettings},
+    geom::Point,
+    macros::MacroStep,
+    platform::layout::ButtonLayout,
 };
 
 #[cfg(feature = "miyoo")]
@@ -40,15 +49,26 @@ pub trait Platform {
 
     fn battery(&self) -> Result<Self::Battery>;
 
+    /// Polls for key events. Face buttons are remapped to the saved `ButtonLayout`.
     async fn poll(&mut self) -> KeyEvent;
 
+    /// Polls for key events and touches. Platforms without a touchscreen only report keys, which
+    /// is currently all of them but the simulator.
+    async fn poll_input(&mut self) -> InputEvent {
+        InputEvent::Key(self.poll().await)
+    }
+
+    /// Sets how face buttons are remapped by `poll`.
+    fn set_button_layout(&mut self, layout: ButtonLayout);
+
     fn shutdown(&self) -> Result<()>;
 
     fn suspend(&self) -> Result<Self::SuspendContext>;
 
     fn unsuspend(&self, ctx: Self::SuspendContext) -> Result<()>;
 
-    fn set_volume(&mut self, volume: i32) -> Result<()>;
+    /// Sets the output level, from 0 to 100.
+    fn set_volume(&mut self, level: i32) -> Result<()>;
 
     fn get_brightness(&self) -> Result<u8>;
 
@@ -56,6 +76,10 @@ pub trait Platform {
 
     fn set_display_settings(&mut self, settings: &mut DisplaySettings) -> Result<()>;
 
+    /// Plays back key presses into the running game as if they were pressed on the device. Returns
+    /// once playback has started.
+    fn play_keys(&mut self, steps: Vec<MacroStep>) -> Result<()>;
+
     fn device_model() -> String;
 
     fn firmware() -> String;
@@ -63,6 +87,28 @@ pub trait Platform {
     fn has_wifi() -> bool;
 
     fn has_lid() -> bool;
+
+    /// Whether `poll_input` reports touches. Only the simulator does, from mouse clicks.
+    fn has_touchscreen() -> bool;
+
+    /// Whether headphones are plugged into the headphone jack.
+    fn headphones_connected() -> bool;
+
+    /// Reads the raw position of the analog stick, or `None` if the device has no stick.
+    fn analog_stick() -> Option<Point>;
+
+    /// Names of the connected external controllers.
+    fn controllers() -> Vec<String>;
+}
+
+/// An event from the buttons or the touchscreen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputEvent {
+    Key(KeyEvent),
+    /// A finger touched the screen at the point.
+    TouchDown(Point),
+    /// A finger was lifted off the screen at the point.
+    TouchUp(Point),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -93,5 +139,7 @@ pub enum Key {
     VolDown,
     VolUp,
     LidClose,
+    /// Pressed when headphones are plugged in, and released when they're unplugged.
+    Headphones,
     Unknown,
 }
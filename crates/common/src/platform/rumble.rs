@@ -0,0 +1,193 @@
+use std::fs::{self, File};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::ALLIUM_RUMBLE_SETTINGS;
+
+/// How often the motor is switched while rumbling at less than full intensity. The motor can
+/// only be on or off, so intensity is the fraction of each period that it's on.
+const PWM_PERIOD: Duration = Duration::from_millis(10);
+
+/// Set when the settings are saved by this process, as the file's modified time may not change
+/// if it's saved again within the same second or two.
+static SETTINGS_SAVED: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    /// Queue of the worker thread that plays all rumbles of the process, one at a time.
+    static ref WORKER: Sender<Rumble> = {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || worker(rx));
+        tx
+    };
+}
+
+/// Strength of all haptic feedback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RumbleSettings {
+    /// Percentage of each rumble's intensity. 0 disables haptic feedback.
+    pub strength: u8,
+}
+
+impl Default for RumbleSettings {
+    fn default() -> Self {
+        Self { strength: 50 }
+    }
+}
+
+impl RumbleSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_RUMBLE_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_RUMBLE_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read rumble file, removing");
+            fs::remove_file(ALLIUM_RUMBLE_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_RUMBLE_SETTINGS.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        SETTINGS_SAVED.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// A single rumble of the motor, used for haptic feedback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rumble {
+    /// Intensity, in percent, before the strength in the rumble settings is applied.
+    pub intensity: u8,
+    pub duration: Duration,
+}
+
+impl Rumble {
+    /// A short, light tick, e.g. when the end of a list is hit.
+    pub const TICK: Rumble = Rumble::new(60, Duration::from_millis(30));
+    /// Draws attention to a notification.
+    pub const NOTIFICATION: Rumble = Rumble::new(80, Duration::from_millis(120));
+    /// Warns about something that needs attention, e.g. a low battery.
+    pub const WARNING: Rumble = Rumble::new(100, Duration::from_millis(400));
+
+    pub const fn new(intensity: u8, duration: Duration) -> Self {
+        Self {
+            intensity,
+            duration,
+        }
+    }
+
+    /// Returns the fraction of time that the motor is on, with the strength applied.
+    fn duty_cycle(self, settings: &RumbleSettings) -> f32 {
+        let intensity = f32::from(self.intensity.min(100)) / 100.0;
+        let strength = f32::from(settings.strength.min(100)) / 100.0;
+        intensity * strength
+    }
+
+    /// Rumbles in the background, at the strength set in the rumble settings.
+    pub fn play(self) {
+        if self.duration.is_zero() {
+            return;
+        }
+        WORKER.send(self).ok();
+    }
+}
+
+/// Plays the queued rumbles. The settings are loaded once, and again only when they were saved
+/// since, here or by another process. Rumbles queued while one plays are skipped but for the
+/// latest, so that they don't pile up.
+fn worker(rx: Receiver<Rumble>) {
+    let mut settings = RumbleSettings::default();
+    let mut loaded: Option<Option<SystemTime>> = None;
+    while let Ok(mut rumble) = rx.recv() {
+        while let Ok(next) = rx.try_recv() {
+            rumble = next;
+        }
+
+        let modified = fs::metadata(ALLIUM_RUMBLE_SETTINGS.as_path())
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if SETTINGS_SAVED.swap(false, Ordering::Relaxed) || loaded != Some(modified) {
+            settings = RumbleSettings::load().unwrap_or_default();
+            loaded = Some(modified);
+        }
+
+        let duty_cycle = rumble.duty_cycle(&settings);
+        if duty_cycle <= 0.0 {
+            continue;
+        }
+        let on = PWM_PERIOD.mul_f32(duty_cycle);
+        let off = PWM_PERIOD - on;
+        motor::export();
+        let mut elapsed = Duration::ZERO;
+        while elapsed < rumble.duration {
+            motor::write(true);
+            std::thread::sleep(on);
+            if !off.is_zero() {
+                motor::write(false);
+                std::thread::sleep(off);
+            }
+            elapsed += PWM_PERIOD;
+        }
+        motor::write(false);
+    }
+}
+
+/// Turns the rumble motor on or off.
+pub fn set(on: bool) {
+    motor::export();
+    motor::write(on);
+}
+
+/// The rumble motor, which is active low on GPIO 48.
+#[cfg(feature = "miyoo")]
+mod motor {
+    use std::fs;
+
+    use log::warn;
+
+    pub fn export() {
+        fs::write("/sys/class/gpio/export", "48").ok();
+        fs::write("/sys/class/gpio/gpio48/direction", "out").ok();
+    }
+
+    pub fn write(on: bool) {
+        if let Err(e) = fs::write("/sys/class/gpio/gpio48/value", if on { "0" } else { "1" }) {
+            warn!("failed to rumble: {}", e);
+        }
+    }
+}
+
+#[cfg(not(feature = "miyoo"))]
+mod motor {
+    pub fn export() {}
+
+    pub fn write(_on: bool) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duty_cycle() {
+        let settings = RumbleSettings { strength: 50 };
+        assert_eq!(Rumble::WARNING.duty_cycle(&settings), 0.5);
+        assert_eq!(Rumble::new(150, Duration::ZERO).duty_cycle(&settings), 0.5);
+
+        let settings = RumbleSettings { strength: 0 };
+        assert_eq!(Rumble::WARNING.duty_cycle(&settings), 0.0);
+    }
+}
@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::path::Path;
 use std::process;
 use std::rc::Rc;
 use std::time::Duration;
@@ -20,15 +21,19 @@ use sdl2::keyboard::Keycode;
 use crate::battery::Battery;
 use crate::display::Display;
 use crate::display::color::Color;
+use crate::display::image::box_blur;
 use crate::display::settings::DisplaySettings;
-use crate::geom::Rect;
-use crate::platform::{Key, KeyEvent, Platform};
+use crate::geom::{self, Rect};
+use crate::macros::MacroStep;
+use crate::platform::layout::ButtonLayout;
+use crate::platform::{InputEvent, Key, KeyEvent, Platform};
 
 pub const SCREEN_WIDTH: u32 = 640;
 pub const SCREEN_HEIGHT: u32 = 480;
 
 pub struct SimulatorPlatform {
     window: Rc<RefCell<Window>>,
+    layout: ButtonLayout,
 }
 
 #[async_trait(?Send)]
@@ -42,10 +47,20 @@ impl Platform for SimulatorPlatform {
         let window = Window::new("Allium Simulator", &output_settings);
         Ok(SimulatorPlatform {
             window: Rc::new(RefCell::new(window)),
+            layout: ButtonLayout::current(),
         })
     }
 
     async fn poll(&mut self) -> KeyEvent {
+        loop {
+            if let InputEvent::Key(event) = self.poll_input().await {
+                return event;
+            }
+        }
+    }
+
+    // Mouse clicks are reported as touches
+    async fn poll_input(&mut self) -> InputEvent {
         loop {
             let event = self.window.borrow_mut().events().next();
             if let Some(event) = event {
@@ -56,14 +71,21 @@ impl Platform for SimulatorPlatform {
                         if keycode == Keycode::Q {
                             process::exit(0);
                         }
-                        return if repeat {
+                        return InputEvent::Key(self.layout.map_event(if repeat {
                             KeyEvent::Autorepeat(Key::from(keycode))
                         } else {
                             KeyEvent::Pressed(Key::from(keycode))
-                        };
+                        }));
                     }
                     SimulatorEvent::KeyUp { keycode, .. } => {
-                        return KeyEvent::Released(Key::from(keycode));
+                        let event = KeyEvent::Released(Key::from(keycode));
+                        return InputEvent::Key(self.layout.map_event(event));
+                    }
+                    SimulatorEvent::MouseButtonDown { point, .. } => {
+                        return InputEvent::TouchDown(point.into());
+                    }
+                    SimulatorEvent::MouseButtonUp { point, .. } => {
+                        return InputEvent::TouchUp(point.into());
                     }
                     SimulatorEvent::Quit => {
                         process::exit(0);
@@ -90,6 +112,10 @@ impl Platform for SimulatorPlatform {
         })
     }
 
+    fn set_button_layout(&mut self, layout: ButtonLayout) {
+        self.layout = layout;
+    }
+
     fn battery(&self) -> Result<SimulatorBattery> {
         Ok(SimulatorBattery::new())
     }
@@ -106,7 +132,7 @@ impl Platform for SimulatorPlatform {
         Ok(())
     }
 
-    fn set_volume(&mut self, _volume: i32) -> Result<()> {
+    fn set_volume(&mut self, _level: i32) -> Result<()> {
         Ok(())
     }
 
@@ -122,6 +148,10 @@ impl Platform for SimulatorPlatform {
         Ok(())
     }
 
+    fn play_keys(&mut self, _steps: Vec<MacroStep>) -> Result<()> {
+        Ok(())
+    }
+
     fn device_model() -> String {
         "Simulator".into()
     }
@@ -137,6 +167,22 @@ impl Platform for SimulatorPlatform {
     fn has_lid() -> bool {
         true
     }
+
+    fn has_touchscreen() -> bool {
+        true
+    }
+
+    fn headphones_connected() -> bool {
+        false
+    }
+
+    fn analog_stick() -> Option<geom::Point> {
+        None
+    }
+
+    fn controllers() -> Vec<String> {
+        Vec::new()
+    }
 }
 
 impl Default for SimulatorPlatform {
@@ -151,6 +197,16 @@ pub struct SimulatorWindow {
     saved: Vec<(Vec<u8>, u32)>,
 }
 
+impl SimulatorWindow {
+    /// Saves what has been drawn as a PNG file, without flushing it to the window.
+    pub fn save_png(&self, path: &Path) -> Result<()> {
+        self.display
+            .to_rgb_output_image(&OutputSettingsBuilder::new().build())
+            .save_png(path)?;
+        Ok(())
+    }
+}
+
 impl Display for SimulatorWindow {
     fn map_pixels<F>(&mut self, mut f: F) -> Result<()>
     where
@@ -168,6 +224,22 @@ impl Display for SimulatorWindow {
         Ok(())
     }
 
+    fn blur(&mut self, radius: u32) -> Result<()> {
+        let Size { width, height } = self.display.size();
+        let mut rgb = Vec::with_capacity((width * height * 3) as usize);
+        for (y, x) in iproduct!(0..height as i32, 0..width as i32) {
+            let color = self.display.get_pixel(Point::new(x, y));
+            rgb.extend([color.r(), color.g(), color.b()]);
+        }
+        box_blur(&mut rgb, width as usize, 3, radius as usize);
+        let pixels = iproduct!(0..height as i32, 0..width as i32)
+            .zip(rgb.chunks_exact(3))
+            .map(|((y, x), c)| Pixel(Point::new(x, y), Color::new(c[0], c[1], c[2])))
+            .collect::<Vec<_>>();
+        self.display.draw_iter(pixels)?;
+        Ok(())
+    }
+
     fn flush(&mut self) -> Result<()> {
         self.window.borrow_mut().update(&self.display);
         Ok(())
@@ -0,0 +1,90 @@
+use crate::constants::TOUCH_TAP_SLOP;
+use crate::geom::Point;
+use crate::platform::{InputEvent, Key};
+
+/// A tap or swipe on the touchscreen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    Tap(Point),
+    Swipe { start: Point, end: Point },
+}
+
+impl Gesture {
+    /// The point where the finger first touched the screen.
+    pub fn start(&self) -> Point {
+        match *self {
+            Gesture::Tap(point) => point,
+            Gesture::Swipe { start, .. } => start,
+        }
+    }
+
+    /// The direction the finger moved in for a swipe, as the matching d-pad key.
+    pub fn direction(&self) -> Option<Key> {
+        let Gesture::Swipe { start, end } = *self else {
+            return None;
+        };
+        let dx = end.x - start.x;
+        let dy = end.y - start.y;
+        Some(if dx.abs() > dy.abs() {
+            if dx > 0 { Key::Right } else { Key::Left }
+        } else if dy > 0 {
+            Key::Down
+        } else {
+            Key::Up
+        })
+    }
+}
+
+/// Turns touches into taps and swipes.
+#[derive(Debug, Default)]
+pub struct GestureRecognizer {
+    start: Option<Point>,
+}
+
+impl GestureRecognizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the gesture completed by the event, if any.
+    pub fn handle(&mut self, event: InputEvent) -> Option<Gesture> {
+        match event {
+            InputEvent::TouchDown(point) => {
+                self.start = Some(point);
+                None
+            }
+            InputEvent::TouchUp(end) => {
+                let start = self.start.take()?;
+                if (end.x - start.x).abs() <= TOUCH_TAP_SLOP
+                    && (end.y - start.y).abs() <= TOUCH_TAP_SLOP
+                {
+                    Some(Gesture::Tap(start))
+                } else {
+                    Some(Gesture::Swipe { start, end })
+                }
+            }
+            InputEvent::Key(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gesture_recognizer() {
+        let mut gestures = GestureRecognizer::new();
+        assert_eq!(gestures.handle(InputEvent::TouchUp(Point::zero())), None);
+
+        gestures.handle(InputEvent::TouchDown(Point::new(100, 100)));
+        assert_eq!(
+            gestures.handle(InputEvent::TouchUp(Point::new(105, 110))),
+            Some(Gesture::Tap(Point::new(100, 100)))
+        );
+
+        gestures.handle(InputEvent::TouchDown(Point::new(100, 300)));
+        let swipe = gestures.handle(InputEvent::TouchUp(Point::new(120, 100)));
+        assert_eq!(swipe.and_then(|g| g.direction()), Some(Key::Up));
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::constants::{ALLIUM_GAMES_DIR, PLAY_STATS_LIMIT, PLAY_STATS_WEEKS};
+use crate::database::{Database, Game};
+
+/// Play time totals shown by the activity tracker's statistics page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayStats {
+    /// Lifetime play time of all games.
+    pub total: Duration,
+    /// Play time per console folder, most played first.
+    pub consoles: Vec<(String, Duration)>,
+    /// Play time of the most played games, most played first.
+    pub games: Vec<(String, Duration)>,
+    /// Play time per week, starting on Monday, oldest first. Ends with the current week.
+    pub weeks: Vec<(NaiveDate, Duration)>,
+    /// Name of the game and length of the longest play session.
+    pub longest_session: Option<(String, Duration)>,
+    /// Number of consecutive days played up to today, or yesterday if not played yet today.
+    pub current_streak: u32,
+    pub longest_streak: u32,
+}
+
+impl PlayStats {
+    pub fn load(database: &Database, today: NaiveDate) -> Result<Self> {
+        let games = database.select_all_games()?;
+        let days = database.select_daily_play_time()?;
+
+        let longest_session = match database.select_longest_play_session()? {
+            Some(session) => {
+                let name = match database.select_game(&session.path)? {
+                    Some(game) => game.name,
+                    None => file_stem(&session.path),
+                };
+                Some((name, session.play_time))
+            }
+            None => None,
+        };
+
+        let (current_streak, longest_streak) = streaks(&days, today);
+
+        Ok(Self {
+            total: games
+                .iter()
+                .fold(Duration::zero(), |total, game| total + game.play_time),
+            consoles: console_totals(&games, &ALLIUM_GAMES_DIR),
+            games: game_totals(games),
+            weeks: weekly_totals(&days, today),
+            longest_session,
+            current_streak,
+            longest_streak,
+        })
+    }
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Sums the play time of games by the console folder they're in.
+fn console_totals(games: &[Game], games_dir: &Path) -> Vec<(String, Duration)> {
+    let mut totals: HashMap<String, Duration> = HashMap::new();
+    for game in games.iter().filter(|g| g.play_time > Duration::zero()) {
+        let Some(console) = game
+            .path
+            .strip_prefix(games_dir)
+            .ok()
+            .and_then(|p| p.components().next())
+        else {
+            continue;
+        };
+        let console = console.as_os_str().to_string_lossy().to_string();
+        *totals.entry(console).or_insert_with(Duration::zero) += game.play_time;
+    }
+    sorted(totals.into_iter().collect())
+}
+
+fn game_totals(games: Vec<Game>) -> Vec<(String, Duration)> {
+    sorted(
+        games
+            .into_iter()
+            .filter(|g| g.play_time > Duration::zero())
+            .map(|g| (g.name, g.play_time))
+            .collect(),
+    )
+}
+
+/// Sorts by play time, most played first, keeping the top `PLAY_STATS_LIMIT`.
+fn sorted(mut totals: Vec<(String, Duration)>) -> Vec<(String, Duration)> {
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    totals.truncate(PLAY_STATS_LIMIT);
+    totals
+}
+
+/// Sums the daily play time of the last `PLAY_STATS_WEEKS` weeks, including empty weeks.
+fn weekly_totals(days: &[(NaiveDate, Duration)], today: NaiveDate) -> Vec<(NaiveDate, Duration)> {
+    let this_week = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let mut weeks: Vec<_> = (0..PLAY_STATS_WEEKS as i64)
+        .rev()
+        .map(|i| (this_week - Duration::weeks(i), Duration::zero()))
+        .collect();
+    for (date, play_time) in days {
+        if let Some((_, total)) = weeks
+            .iter_mut()
+            .find(|(start, _)| (*start..*start + Duration::weeks(1)).contains(date))
+        {
+            *total += *play_time;
+        }
+    }
+    weeks
+}
+
+/// Returns the current and longest runs of consecutive days played. `days` must be sorted.
+fn streaks(days: &[(NaiveDate, Duration)], today: NaiveDate) -> (u32, u32) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut last: Option<NaiveDate> = None;
+    for (date, _) in days.iter().filter(|(_, t)| *t > Duration::zero()) {
+        run = match last {
+            Some(last) if *date - last == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        last = Some(*date);
+    }
+
+    let current = match last {
+        Some(last) if today - last <= Duration::days(1) => run,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn game(path: &str, minutes: i64) -> Game {
+        Game {
+            name: file_stem(Path::new(path)),
+            path: PathBuf::from(path),
+            image: None,
+            play_count: 1,
+            play_time: Duration::minutes(minutes),
+            last_played: 0,
+            core: None,
+            rating: None,
+            release_date: None,
+            developer: None,
+            publisher: None,
+            genres: Vec::new(),
+            favorite: false,
+            screenshot_path: None,
+            volume: None,
+        }
+    }
+
+    #[test]
+    fn test_console_totals() {
+        let games = vec![
+            game("/Roms/GBA/Game One.gba", 30),
+            game("/Roms/SNES/Game Two.sfc", 20),
+            game("/Roms/GBA/RPGs/Game Three.gba", 15),
+            game("/Roms/SNES/Game Four.sfc", 0),
+            game("/Other/Game Five.gba", 60),
+        ];
+        assert_eq!(
+            console_totals(&games, Path::new("/Roms")),
+            vec![
+                ("GBA".to_string(), Duration::minutes(45)),
+                ("SNES".to_string(), Duration::minutes(20)),
+            ]
+        );
+        assert_eq!(
+            game_totals(games),
+            vec![
+                ("Game Five".to_string(), Duration::minutes(60)),
+                ("Game One".to_string(), Duration::minutes(30)),
+                ("Game Two".to_string(), Duration::minutes(20)),
+                ("Game Three".to_string(), Duration::minutes(15)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_weekly_totals() {
+        // Wednesday
+        let today = date(5, 15);
+        let days = vec![
+            (date(1, 1), Duration::minutes(5)),
+            (date(5, 5), Duration::minutes(10)),
+            (date(5, 6), Duration::minutes(20)),
+            (date(5, 12), Duration::minutes(30)),
+            (date(5, 13), Duration::minutes(40)),
+            (date(5, 15), Duration::minutes(50)),
+        ];
+        let weeks = weekly_totals(&days, today);
+        assert_eq!(weeks.len(), PLAY_STATS_WEEKS);
+        assert_eq!(
+            &weeks[PLAY_STATS_WEEKS - 3..],
+            &[
+                (date(4, 29), Duration::minutes(10)),
+                (date(5, 6), Duration::minutes(50)),
+                (date(5, 13), Duration::minutes(90)),
+            ]
+        );
+        assert_eq!(weeks[0].1, Duration::zero());
+    }
+
+    #[test]
+    fn test_streaks() {
+        let days = vec![
+            (date(5, 1), Duration::minutes(5)),
+            (date(5, 2), Duration::minutes(5)),
+            (date(5, 3), Duration::minutes(5)),
+            (date(5, 5), Duration::minutes(5)),
+            (date(5, 6), Duration::minutes(5)),
+        ];
+        assert_eq!(streaks(&days, date(5, 6)), (2, 3));
+        assert_eq!(streaks(&days, date(5, 7)), (2, 3));
+        assert_eq!(streaks(&days, date(5, 8)), (0, 3));
+        assert_eq!(streaks(&[], date(5, 8)), (0, 0));
+    }
+}
@@ -6,6 +6,8 @@ use serde::{Deserialize, Serialize};
 use strum::FromRepr;
 
 use crate::constants::ALLIUM_POWER_SETTINGS;
+use crate::macros::MacroSettings;
+use crate::platform::Key;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PowerSettings {
@@ -13,6 +15,30 @@ pub struct PowerSettings {
     pub lid_close_action: PowerButtonAction,
     pub auto_sleep_when_charging: bool,
     pub auto_sleep_duration_minutes: i32,
+    #[serde(default)]
+    pub fast_forward_hotkeys: bool,
+    #[serde(default)]
+    pub attract_mode: bool,
+    #[serde(default = "PowerSettings::attract_mode_delay_minutes")]
+    pub attract_mode_delay_minutes: i32,
+    /// Whether the game's state is saved before suspending or shutting down, so that it's
+    /// resumed even if the battery dies while suspended.
+    #[serde(default = "PowerSettings::auto_save_state")]
+    pub auto_save_state: bool,
+    /// Battery levels, in percent, at which a warning is shown over the running game.
+    #[serde(default = "PowerSettings::low_battery_warnings")]
+    pub low_battery_warnings: Vec<i32>,
+    /// Battery level, in percent, at or below which the battery saver is turned on. 0 disables
+    /// turning it on automatically.
+    #[serde(default = "PowerSettings::battery_saver_threshold")]
+    pub battery_saver_threshold: i32,
+    /// Maximum brightness while the battery saver is on.
+    #[serde(default = "PowerSettings::battery_saver_brightness")]
+    pub battery_saver_brightness: u8,
+    /// Key that toggles the battery saver while holding Menu. There's none by default, as the
+    /// keys that are left are shared with the macro and RetroArch hotkeys.
+    #[serde(default)]
+    pub battery_saver_hotkey: Option<Key>,
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, FromRepr, Default)]
@@ -42,6 +68,14 @@ impl Default for PowerSettings {
             power_button_action: PowerButtonAction::Suspend,
             auto_sleep_when_charging: true,
             auto_sleep_duration_minutes: 5,
+            fast_forward_hotkeys: false,
+            attract_mode: false,
+            attract_mode_delay_minutes: Self::attract_mode_delay_minutes(),
+            auto_save_state: Self::auto_save_state(),
+            low_battery_warnings: Self::low_battery_warnings(),
+            battery_saver_threshold: Self::battery_saver_threshold(),
+            battery_saver_brightness: Self::battery_saver_brightness(),
+            battery_saver_hotkey: None,
         }
     }
 }
@@ -51,6 +85,33 @@ impl PowerSettings {
         Default::default()
     }
 
+    fn attract_mode_delay_minutes() -> i32 {
+        2
+    }
+
+    fn auto_save_state() -> bool {
+        true
+    }
+
+    fn low_battery_warnings() -> Vec<i32> {
+        vec![15, 5]
+    }
+
+    fn battery_saver_threshold() -> i32 {
+        20
+    }
+
+    fn battery_saver_brightness() -> u8 {
+        30
+    }
+
+    /// Returns the key that toggles the battery saver with Menu. It's ignored if it's also the
+    /// macro hotkey, so that the macro keeps working.
+    pub fn battery_saver_key(&self, macros: &MacroSettings) -> Option<Key> {
+        self.battery_saver_hotkey
+            .filter(|&key| key != macros.hotkey)
+    }
+
     pub fn load() -> Result<Self> {
         if ALLIUM_POWER_SETTINGS.exists() {
             debug!("found state, loading from file");
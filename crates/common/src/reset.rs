@@ -0,0 +1,246 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Local};
+use log::{debug, info, warn};
+use strum::EnumIter;
+use tokio::process::Command;
+
+use crate::constants::{
+    ALLIUM_BASE_DIR, ALLIUM_DATABASE, ALLIUM_LOCK_SETTINGS, ALLIUM_PARENTAL_SETTINGS,
+    ALLIUM_RESET_BACKUPS_DIR, ALLIUM_SD_ROOT, ALLIUM_STATE_DIR, ALLIUM_STYLESHEET,
+    ALLIUM_WIFI_SETTINGS,
+};
+use crate::wifi::{WPA_SUPPLICANT_CONF, WiFiSettings};
+
+/// Part of Allium's data that can be reset on its own.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter)]
+pub enum ResetTarget {
+    /// The library database: play time, favorites, collections and scanned games.
+    Database,
+    /// The theme and stylesheet.
+    Theme,
+    /// Saved Wi-Fi networks and passwords.
+    WiFi,
+    /// All settings and state, and the database. The lock screen and parental controls are
+    /// kept, so that a reset can't be used to get around them.
+    All,
+}
+
+impl ResetTarget {
+    pub fn name(self) -> &'static str {
+        match self {
+            ResetTarget::Database => "database",
+            ResetTarget::Theme => "theme",
+            ResetTarget::WiFi => "wifi",
+            ResetTarget::All => "all",
+        }
+    }
+
+    /// Files and folders that are backed up and then deleted.
+    fn paths(self) -> Vec<PathBuf> {
+        match self {
+            ResetTarget::Database => vec![
+                ALLIUM_DATABASE.clone(),
+                // Migrated to `ALLIUM_DATABASE` if it's still around, so it's deleted too
+                ALLIUM_BASE_DIR.join("state/allium.db"),
+            ],
+            ResetTarget::Theme => vec![ALLIUM_STYLESHEET.clone()],
+            ResetTarget::WiFi => vec![ALLIUM_WIFI_SETTINGS.clone()],
+            ResetTarget::All => {
+                let kept = [
+                    ALLIUM_LOCK_SETTINGS.as_path(),
+                    ALLIUM_PARENTAL_SETTINGS.as_path(),
+                ];
+                let mut paths = state_paths(&ALLIUM_STATE_DIR, &kept);
+                paths.push(ALLIUM_DATABASE.clone());
+                paths
+            }
+        }
+    }
+
+    /// Files that are backed up but not deleted, as the reset overwrites them.
+    fn overwritten_paths(self) -> Vec<PathBuf> {
+        match self {
+            ResetTarget::WiFi | ResetTarget::All => vec![PathBuf::from(WPA_SUPPLICANT_CONF)],
+            ResetTarget::Database | ResetTarget::Theme => Vec::new(),
+        }
+    }
+
+    /// Backs up everything the reset deletes to `ALLIUM_RESET_BACKUPS_DIR`, then deletes it.
+    /// Returns the path of the backup, or `None` if there was nothing to back up.
+    pub async fn reset(self) -> Result<Option<PathBuf>> {
+        let paths = self
+            .paths()
+            .into_iter()
+            .filter(|path| path.exists())
+            .collect::<Vec<_>>();
+
+        let backup = if paths.is_empty() {
+            None
+        } else {
+            let backed_up = paths
+                .iter()
+                .cloned()
+                .chain(self.overwritten_paths())
+                .filter(|path| path.exists())
+                .collect::<Vec<_>>();
+            Some(backup(self, &backed_up, Local::now()).await?)
+        };
+
+        for path in &paths {
+            debug!("resetting {}", path.display());
+            if path.is_dir() {
+                fs::remove_dir_all(path)?;
+            } else {
+                fs::remove_file(path)?;
+            }
+        }
+        if self == ResetTarget::All {
+            fs::create_dir_all(ALLIUM_STATE_DIR.as_path())?;
+        }
+
+        // Without its own settings file, Wi-Fi falls back to the networks in wpa_supplicant.conf
+        if matches!(self, ResetTarget::WiFi | ResetTarget::All) {
+            WiFiSettings::new().save()?;
+        }
+
+        info!("reset {}", self.name());
+        Ok(backup)
+    }
+}
+
+/// Returns the files and folders in the state folder, except those that are kept.
+fn state_paths(dir: &Path, kept: &[&Path]) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| !kept.contains(&path.as_path()))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+/// Returns the arguments that add the paths to an archive. Paths on the SD card are stored
+/// relative to it, and others, such as the firmware's Wi-Fi configuration, relative to `/`.
+fn tar_args(paths: &[PathBuf], sd_root: &Path) -> Vec<OsString> {
+    let (inside, outside): (Vec<_>, Vec<_>) =
+        paths.iter().partition(|path| path.starts_with(sd_root));
+
+    let mut args = Vec::new();
+    for (root, paths) in [(sd_root, inside), (Path::new("/"), outside)] {
+        if paths.is_empty() {
+            continue;
+        }
+        args.push("-C".into());
+        args.push(root.into());
+        for path in paths {
+            match path.strip_prefix(root) {
+                Ok(path) => args.push(path.into()),
+                Err(_) => warn!("not backing up {}, not an absolute path", path.display()),
+            }
+        }
+    }
+    args
+}
+
+/// Archives the paths into a new file in `ALLIUM_RESET_BACKUPS_DIR`.
+async fn backup(target: ResetTarget, paths: &[PathBuf], time: DateTime<Local>) -> Result<PathBuf> {
+    fs::create_dir_all(ALLIUM_RESET_BACKUPS_DIR.as_path())?;
+    let backup = ALLIUM_RESET_BACKUPS_DIR.join(backup_name(target, time));
+
+    let args = tar_args(paths, &ALLIUM_SD_ROOT);
+    debug!("backing up before reset: {:?}", args);
+
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(&backup)
+        .args(args)
+        .status()
+        .await?;
+    if !status.success() {
+        bail!("failed to back up before reset: {}", status);
+    }
+
+    info!("backed up to {}", backup.display());
+    Ok(backup)
+}
+
+fn backup_name(target: ResetTarget, time: DateTime<Local>) -> String {
+    format!("{}-{}.tar.gz", target.name(), time.format("%Y%m%d-%H%M%S"))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_state_paths() {
+        let dir = std::env::temp_dir().join(format!("allium-reset-{}", std::process::id()));
+        fs::create_dir_all(dir.join("cache")).unwrap();
+        for name in ["lock.json", "parental.json", "power.json"] {
+            fs::write(dir.join(name), "{}").unwrap();
+        }
+
+        let lock = dir.join("lock.json");
+        let parental = dir.join("parental.json");
+        assert_eq!(
+            state_paths(&dir, &[lock.as_path(), parental.as_path()]),
+            vec![dir.join("cache"), dir.join("power.json")],
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(state_paths(&dir, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_tar_args() {
+        let sd_root = Path::new("/mnt/SDCARD");
+        let paths = [
+            PathBuf::from("/mnt/SDCARD/.allium/state/wifi.json"),
+            PathBuf::from("/appconfigs/wpa_supplicant.conf"),
+        ];
+        assert_eq!(
+            tar_args(&paths, sd_root),
+            [
+                "-C",
+                "/mnt/SDCARD",
+                ".allium/state/wifi.json",
+                "-C",
+                "/",
+                "appconfigs/wpa_supplicant.conf",
+            ]
+            .map(OsString::from),
+        );
+        assert!(tar_args(&[], sd_root).is_empty());
+    }
+
+    #[test]
+    fn test_kept_paths() {
+        let paths = ResetTarget::All.paths();
+        assert!(!paths.contains(&*ALLIUM_LOCK_SETTINGS));
+        assert!(!paths.contains(&*ALLIUM_PARENTAL_SETTINGS));
+        assert!(paths.contains(&*ALLIUM_DATABASE));
+        assert!(
+            !ResetTarget::WiFi
+                .paths()
+                .contains(&PathBuf::from(WPA_SUPPLICANT_CONF))
+        );
+    }
+
+    #[test]
+    fn test_backup_name() {
+        let time = Local.with_ymd_and_hms(2024, 3, 9, 14, 5, 7).unwrap();
+        assert_eq!(
+            backup_name(ResetTarget::WiFi, time),
+            "wifi-20240309-140507.tar.gz"
+        );
+    }
+}
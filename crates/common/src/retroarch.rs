@@ -49,6 +49,7 @@ pub enum RetroArchCommand {
     SetStateSlot(i8),
     SaveStateSlot(i8),
     LoadStateSlot(i8),
+    ShowMsg(String),
 }
 
 impl RetroArchCommand {
@@ -136,6 +137,7 @@ impl RetroArchCommand {
             RetroArchCommand::SetStateSlot(slot) => Cow::Owned(format!("SET_STATE_SLOT {slot}")),
             RetroArchCommand::SaveStateSlot(slot) => Cow::Owned(format!("SAVE_STATE_SLOT {slot}")),
             RetroArchCommand::LoadStateSlot(slot) => Cow::Owned(format!("LOAD_STATE_SLOT {slot}")),
+            RetroArchCommand::ShowMsg(msg) => Cow::Owned(format!("SHOW_MSG {msg}")),
         }
     }
 }
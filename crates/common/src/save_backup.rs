@@ -0,0 +1,390 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Local, NaiveDateTime};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::constants::{
+    ALLIUM_GAMES_DIR, ALLIUM_SAVE_BACKUP_SETTINGS, ALLIUM_SAVE_BACKUPS_DIR, RETROARCH_PROFILE_DIR,
+};
+
+/// Folders of the RetroArch profile that are backed up.
+const BACKED_UP_DIRS: [&str; 2] = ["saves", "states"];
+
+/// Extensions of the save files that RetroArch and its cores write, besides states.
+const SAVE_EXTENSIONS: [&str; 4] = ["srm", "sav", "rtc", "ldci"];
+
+/// Format of the backup file names, in local time.
+const BACKUP_NAME_FORMAT: &str = "%Y%m%d-%H%M%S";
+const BACKUP_EXTENSION: &str = ".tar.gz";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveBackupSettings {
+    /// Whether to back up a game's saves when it exits.
+    pub enabled: bool,
+    /// Number of backups to keep for each game. Older ones are deleted.
+    pub versions: i32,
+}
+
+impl Default for SaveBackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            versions: 10,
+        }
+    }
+}
+
+impl SaveBackupSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_SAVE_BACKUP_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_SAVE_BACKUP_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read save backup file, removing");
+            fs::remove_file(ALLIUM_SAVE_BACKUP_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_SAVE_BACKUP_SETTINGS.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+}
+
+/// An archive of a game's save files and states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveBackup {
+    pub path: PathBuf,
+    /// When the backup was made, in local time.
+    pub time: NaiveDateTime,
+}
+
+/// Folder that a game's backups are kept in. Named after the game's path in the games folder,
+/// so that games with the same name on different consoles don't share backups.
+fn backup_dir(game: &Path) -> PathBuf {
+    match game.strip_prefix(ALLIUM_GAMES_DIR.as_path()) {
+        Ok(relative) => ALLIUM_SAVE_BACKUPS_DIR.join(relative),
+        Err(_) => ALLIUM_SAVE_BACKUPS_DIR.join(game.file_name().unwrap_or_default()),
+    }
+}
+
+/// Returns the save files and states of a game, relative to the profile folder. RetroArch names
+/// them after the game, such as `Game.srm` and `Game.state1`, and sorts them into a folder named
+/// after the core. Only the folder of the core that runs the game is looked in, as saves of a
+/// game with the same name in another core's folder belong to a game on another console. Saves
+/// are only looked for in the profile's own folder when there's no folder for the core, as when
+/// RetroArch doesn't sort saves by core.
+pub fn save_files(game: &Path, core: Option<&str>) -> Result<Vec<PathBuf>> {
+    save_files_in(&RETROARCH_PROFILE_DIR, game, core)
+}
+
+fn save_files_in(profile: &Path, game: &Path, core: Option<&str>) -> Result<Vec<PathBuf>> {
+    let Some(stem) = game.file_stem().and_then(|stem| stem.to_str()) else {
+        return Ok(Vec::new());
+    };
+
+    let mut files = Vec::new();
+    for dir in BACKED_UP_DIRS {
+        let dir = profile.join(dir);
+        if !dir.is_dir() {
+            continue;
+        }
+        let mut dirs = Vec::new();
+        if let Some(core) = core {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_dir()
+                    && path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| is_core_dir(name, core))
+                {
+                    dirs.push(path);
+                }
+            }
+        }
+        if dirs.is_empty() {
+            dirs.push(dir);
+        }
+        for dir in dirs {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                let matches = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| is_save_of(name, stem));
+                if matches
+                    && path.is_file()
+                    && let Ok(relative) = path.strip_prefix(profile)
+                {
+                    files.push(relative.to_path_buf());
+                }
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Whether the folder is the one RetroArch sorts the core's saves into. It's named after the
+/// core's display name, such as `PCSX-ReARMed` for `pcsx_rearmed`, so only letters and digits
+/// are compared.
+fn is_core_dir(name: &str, core: &str) -> bool {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|c| c.to_ascii_lowercase())
+            .collect::<String>()
+    };
+    let name = normalize(name);
+    !name.is_empty() && name == normalize(core)
+}
+
+/// Whether the file is a save or state of the game named `stem`: `Game.srm`, `Game.state`,
+/// `Game.state1`, `Game.state.auto`, or a state's screenshot such as `Game.state1.png`. Files of
+/// other games that start with the same name, such as `Game.Part 2.srm`, aren't.
+fn is_save_of(name: &str, stem: &str) -> bool {
+    let Some(extension) = name
+        .strip_prefix(stem)
+        .and_then(|rest| rest.strip_prefix('.'))
+    else {
+        return false;
+    };
+    let extension = extension.strip_suffix(".png").unwrap_or(extension);
+    match extension.strip_prefix("state") {
+        Some(slot) => slot == ".auto" || slot.bytes().all(|b| b.is_ascii_digit()),
+        None => SAVE_EXTENSIONS.contains(&extension),
+    }
+}
+
+/// Lists a game's backups, newest first.
+pub fn versions(game: &Path) -> Result<Vec<SaveBackup>> {
+    let dir = backup_dir(game);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let time = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(BACKUP_EXTENSION))
+            .and_then(|name| NaiveDateTime::parse_from_str(name, BACKUP_NAME_FORMAT).ok());
+        if let Some(time) = time {
+            backups.push(SaveBackup { path, time });
+        }
+    }
+    backups.sort_by(|a, b| b.time.cmp(&a.time));
+    Ok(backups)
+}
+
+/// Lists the games that have backups.
+pub fn games() -> Result<Vec<PathBuf>> {
+    let mut games = Vec::new();
+    if ALLIUM_SAVE_BACKUPS_DIR.is_dir() {
+        find_games(&ALLIUM_SAVE_BACKUPS_DIR, &mut games)?;
+    }
+    games.sort();
+    Ok(games)
+}
+
+fn find_games(dir: &Path, games: &mut Vec<PathBuf>) -> Result<()> {
+    let mut has_backups = false;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_games(&path, games)?;
+        } else if path.to_string_lossy().ends_with(BACKUP_EXTENSION) {
+            has_backups = true;
+        }
+    }
+    if has_backups && let Ok(relative) = dir.strip_prefix(ALLIUM_SAVE_BACKUPS_DIR.as_path()) {
+        games.push(ALLIUM_GAMES_DIR.join(relative));
+    }
+    Ok(())
+}
+
+/// Backs up a game's saves, unless they haven't changed since the last backup. Deletes the
+/// oldest backups beyond the number of versions to keep.
+pub async fn backup(
+    game: &Path,
+    core: Option<&str>,
+    settings: &SaveBackupSettings,
+) -> Result<Option<SaveBackup>> {
+    let files = save_files(game, core)?;
+    let mut modified = None;
+    for file in &files {
+        let time =
+            DateTime::<Local>::from(RETROARCH_PROFILE_DIR.join(file).metadata()?.modified()?);
+        modified = modified.max(Some(time.naive_local()));
+    }
+    let Some(modified) = modified else {
+        debug!("no saves to back up for {}", game.display());
+        return Ok(None);
+    };
+
+    let mut versions = versions(game)?;
+    if versions
+        .first()
+        .is_some_and(|latest| latest.time >= modified)
+    {
+        debug!(
+            "saves of {} haven't changed since the last backup",
+            game.display()
+        );
+        return Ok(None);
+    }
+
+    let dir = backup_dir(game);
+    fs::create_dir_all(&dir)?;
+    let time = Local::now().naive_local();
+    let path = dir.join(format!(
+        "{}{BACKUP_EXTENSION}",
+        time.format(BACKUP_NAME_FORMAT)
+    ));
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(&path)
+        .arg("-C")
+        .arg(RETROARCH_PROFILE_DIR.as_path())
+        .args(&files)
+        .status()
+        .await?;
+    if !status.success() {
+        fs::remove_file(&path).ok();
+        bail!("failed to back up saves of {}: {}", game.display(), status);
+    }
+    info!(
+        "backed up saves of {} to {}",
+        game.display(),
+        path.display()
+    );
+
+    let backup = SaveBackup { path, time };
+    versions.insert(0, backup.clone());
+    for old in versions.iter().skip(settings.versions.max(1) as usize) {
+        debug!("deleting old backup {}", old.path.display());
+        fs::remove_file(&old.path)?;
+    }
+    Ok(Some(backup))
+}
+
+/// Restores a game's saves from a backup. The current saves are backed up first, so that the
+/// restore can be undone.
+pub async fn restore(game: &Path, core: Option<&str>, backup: &SaveBackup) -> Result<()> {
+    let mut settings = SaveBackupSettings::load()?;
+    // Never delete the backup that is being restored to make room for the current saves
+    settings.versions = settings.versions.max(1) + 1;
+    self::backup(game, core, &settings).await?;
+
+    let status = Command::new("tar")
+        .arg("-xzf")
+        .arg(&backup.path)
+        .arg("-C")
+        .arg(RETROARCH_PROFILE_DIR.as_path())
+        .status()
+        .await?;
+    if !status.success() {
+        bail!("failed to restore {}: {}", backup.path.display(), status);
+    }
+    info!(
+        "restored saves of {} from {}",
+        game.display(),
+        backup.path.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_core_dir() {
+        assert!(is_core_dir("PCSX-ReARMed", "pcsx_rearmed"));
+        assert!(is_core_dir("Gambatte", "gambatte"));
+        assert!(!is_core_dir("mGBA", "gpsp"));
+        assert!(!is_core_dir("---", "_"));
+    }
+
+    #[test]
+    fn test_is_save_of() {
+        for name in [
+            "Game.srm",
+            "Game.sav",
+            "Game.rtc",
+            "Game.state",
+            "Game.state1",
+            "Game.state12",
+            "Game.state.auto",
+            "Game.state1.png",
+        ] {
+            assert!(is_save_of(name, "Game"), "{name}");
+        }
+        for name in [
+            "Game.Part 2.srm",
+            "Game 2.srm",
+            "Game.statex",
+            "Game.cfg",
+            "Game",
+            "Other.srm",
+        ] {
+            assert!(!is_save_of(name, "Game"), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_save_files_in_core_dir() {
+        let profile =
+            std::env::temp_dir().join(format!("allium-save-backup-{}", std::process::id()));
+        for file in [
+            "saves/Game.srm",
+            "saves/Gambatte/Game.srm",
+            "saves/mGBA/Game.srm",
+            "states/Gambatte/Game.state1",
+            "states/Gambatte/Game.state1.png",
+            "states/Gambatte/Game 2.state",
+        ] {
+            let path = profile.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        let game = Path::new("Roms/GB/Game.gb");
+
+        // Saves of the game on other consoles are left out
+        assert_eq!(
+            save_files_in(&profile, game, Some("gambatte")).unwrap(),
+            vec![
+                PathBuf::from("saves/Gambatte/Game.srm"),
+                PathBuf::from("states/Gambatte/Game.state1"),
+                PathBuf::from("states/Gambatte/Game.state1.png"),
+            ]
+        );
+        // Without a folder for the core, saves aren't sorted by core
+        assert_eq!(
+            save_files_in(&profile, game, Some("gpsp")).unwrap(),
+            vec![PathBuf::from("saves/Game.srm")]
+        );
+        assert_eq!(
+            save_files_in(&profile, game, None).unwrap(),
+            vec![PathBuf::from("saves/Game.srm")]
+        );
+
+        fs::remove_dir_all(&profile).unwrap();
+    }
+}
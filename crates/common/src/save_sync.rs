@@ -0,0 +1,510 @@
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use strum::FromRepr;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::background;
+use crate::constants::{
+    ALLIUM_RCLONE, ALLIUM_SAVE_SYNC_SETTINGS, ALLIUM_SAVE_SYNC_STATE, RETROARCH_PROFILE_DIR,
+};
+
+/// Name of the rclone remote, configured through environment variables.
+const REMOTE: &str = "allium";
+
+/// Folders of the RetroArch profile that are synced.
+const SYNCED_DIRS: [&str; 2] = ["saves", "states"];
+
+/// Exit code of rclone when the folder doesn't exist.
+const RCLONE_DIRECTORY_NOT_FOUND: i32 = 3;
+
+/// Extension of the copy of a save that lost a conflict, which is kept next to the save and
+/// never synced.
+const CONFLICT_EXTENSION: &str = "conflict";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, FromRepr)]
+pub enum SaveSyncBackend {
+    #[default]
+    WebDav,
+    Sftp,
+}
+
+/// Syncs save files and states with a server. Off by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveSyncSettings {
+    pub enabled: bool,
+    pub backend: SaveSyncBackend,
+    /// URL of the WebDAV server, or `host[:port]` of the SFTP server.
+    pub server: String,
+    /// Folder on the server to sync with.
+    pub folder: String,
+    pub username: String,
+    /// Password, obscured the way rclone expects it in its config.
+    pub obscured_password: String,
+    /// Password saved in plain text by older versions, which is obscured on the next sync.
+    #[serde(skip_serializing)]
+    password: String,
+    /// Whether to sync when a game exits.
+    pub sync_on_exit: bool,
+}
+
+impl Default for SaveSyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: SaveSyncBackend::default(),
+            server: String::new(),
+            folder: "Allium".to_string(),
+            username: String::new(),
+            obscured_password: String::new(),
+            password: String::new(),
+            sync_on_exit: true,
+        }
+    }
+}
+
+impl SaveSyncSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_SAVE_SYNC_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_SAVE_SYNC_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read save sync file, removing");
+            fs::remove_file(ALLIUM_SAVE_SYNC_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_SAVE_SYNC_SETTINGS.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.enabled && !self.server.is_empty()
+    }
+
+    /// Sets the password, which is kept obscured. An empty password removes it.
+    pub async fn set_password(&mut self, password: &str) -> Result<()> {
+        self.obscured_password = if password.is_empty() {
+            String::new()
+        } else {
+            obscure(password).await?
+        };
+        self.password.clear();
+        Ok(())
+    }
+
+    /// Obscures a password saved in plain text by older versions. Returns whether there was one.
+    async fn obscure_legacy_password(&mut self) -> Result<bool> {
+        if self.password.is_empty() {
+            return Ok(false);
+        }
+        let password = std::mem::take(&mut self.password);
+        self.set_password(&password).await?;
+        Ok(true)
+    }
+}
+
+/// Modification times of a file on both sides when it was last synced, in seconds. Used to tell
+/// which side changed since.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct SyncedFile {
+    local: i64,
+    remote: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncState {
+    /// Synced files, by path relative to the profile folder.
+    files: HashMap<PathBuf, SyncedFile>,
+}
+
+impl SyncState {
+    fn load() -> Result<Self> {
+        if ALLIUM_SAVE_SYNC_STATE.exists() {
+            let file = File::open(ALLIUM_SAVE_SYNC_STATE.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read save sync state, starting over");
+        }
+        Ok(Self::default())
+    }
+
+    fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_SAVE_SYNC_STATE.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+}
+
+/// What a sync did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub uploaded: usize,
+    pub downloaded: usize,
+    /// Files that changed on both sides since the last sync. The newest copy is kept on both
+    /// sides, and the other copy is kept next to the local file with the `.conflict` extension.
+    pub conflicts: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Upload,
+    Download,
+    Conflict,
+    Skip,
+}
+
+/// Decides what to do with a file from its modification times on each side, and when it was
+/// last synced. Deletions aren't synced.
+fn action(local: Option<i64>, remote: Option<i64>, synced: Option<SyncedFile>) -> Action {
+    let local_changed = local.is_some() && synced.is_none_or(|s| local != Some(s.local));
+    let remote_changed = remote.is_some() && synced.is_none_or(|s| remote != Some(s.remote));
+    match (local_changed, remote_changed) {
+        // Files uploaded by another device keep their modification time
+        (true, true) if local == remote => Action::Skip,
+        (true, true) => Action::Conflict,
+        (true, false) => Action::Upload,
+        (false, true) => Action::Download,
+        (false, false) => Action::Skip,
+    }
+}
+
+/// Syncs save files and states with the server. Runs as a background job. `progress` is called
+/// with the number of files copied so far and the number of files to copy.
+pub async fn sync(
+    settings: &SaveSyncSettings,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<SyncReport> {
+    let mut settings = settings.clone();
+    if settings.obscure_legacy_password().await? {
+        settings.save()?;
+    }
+
+    let rclone = Rclone::new(&settings);
+    let local = list_local()?;
+    let remote = rclone.list().await?;
+    let mut state = SyncState::load()?;
+
+    let paths: BTreeSet<&PathBuf> = local.keys().chain(remote.keys()).collect();
+    let mut copies = Vec::new();
+    for path in paths {
+        let local_time = local.get(path).copied();
+        let remote_time = remote.get(path).copied();
+        match action(local_time, remote_time, state.files.get(path).copied()) {
+            Action::Skip => {
+                if let (Some(local), Some(remote)) = (local_time, remote_time) {
+                    state
+                        .files
+                        .insert(path.clone(), SyncedFile { local, remote });
+                }
+            }
+            action => copies.push((path.clone(), action, local_time, remote_time)),
+        }
+    }
+
+    let mut report = SyncReport::default();
+    let total = copies.len();
+    progress(0, total);
+    for (i, (path, action, local_time, remote_time)) in copies.iter().enumerate() {
+        background::checkpoint().await;
+        match action {
+            Action::Upload => {
+                rclone.upload(path).await?;
+                report.uploaded += 1;
+            }
+            Action::Download => {
+                rclone.download(path).await?;
+                report.downloaded += 1;
+            }
+            Action::Conflict => {
+                warn!(
+                    "{} changed on both sides, keeping the newest",
+                    path.display()
+                );
+                let conflict = conflict_path(path);
+                if local_time >= remote_time {
+                    rclone.download_to(path, &conflict).await?;
+                    rclone.upload(path).await?;
+                } else {
+                    fs::copy(
+                        RETROARCH_PROFILE_DIR.join(path),
+                        RETROARCH_PROFILE_DIR.join(&conflict),
+                    )?;
+                    rclone.download(path).await?;
+                }
+                report.conflicts.push(path.clone());
+            }
+            Action::Skip => unreachable!("skipped files aren't copied"),
+        }
+        progress(i + 1, total);
+    }
+
+    if !copies.is_empty() {
+        // Transferred files may get new modification times on the other side
+        let local = list_local()?;
+        let remote = rclone.list().await?;
+        for (path, ..) in copies {
+            if let (Some(&local), Some(&remote)) = (local.get(&path), remote.get(&path)) {
+                state.files.insert(path, SyncedFile { local, remote });
+            }
+        }
+    }
+    state.save()?;
+
+    info!("synced saves: {:?}", report);
+    Ok(report)
+}
+
+/// Returns where the copy of a save that lost a conflict is kept, relative to the profile folder.
+fn conflict_path(path: &Path) -> PathBuf {
+    let mut conflict = path.as_os_str().to_owned();
+    conflict.push(".");
+    conflict.push(CONFLICT_EXTENSION);
+    PathBuf::from(conflict)
+}
+
+/// Lists the synced files in the profile folder, with their modification times.
+fn list_local() -> Result<HashMap<PathBuf, i64>> {
+    let mut files = HashMap::new();
+    for dir in SYNCED_DIRS {
+        list_dir(&RETROARCH_PROFILE_DIR.join(dir), &mut files)?;
+    }
+    Ok(files)
+}
+
+fn list_dir(dir: &Path, files: &mut HashMap<PathBuf, i64>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            list_dir(&path, files)?;
+        } else if path.extension() != Some(OsStr::new(CONFLICT_EXTENSION))
+            && let Ok(relative) = path.strip_prefix(RETROARCH_PROFILE_DIR.as_path())
+        {
+            let modified = DateTime::<Utc>::from(metadata.modified()?);
+            files.insert(relative.to_path_buf(), modified.timestamp());
+        }
+    }
+    Ok(())
+}
+
+/// A file listed by `rclone lsjson`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RemoteFile {
+    path: PathBuf,
+    mod_time: DateTime<Utc>,
+}
+
+/// Runs rclone with the server configured through environment variables, so that the
+/// credentials are never written to an rclone config file.
+struct Rclone {
+    envs: Vec<(String, String)>,
+    folder: String,
+}
+
+impl Rclone {
+    fn new(settings: &SaveSyncSettings) -> Self {
+        let prefix = format!("RCLONE_CONFIG_{}_", REMOTE.to_uppercase());
+        let mut envs = Vec::new();
+        match settings.backend {
+            SaveSyncBackend::WebDav => {
+                envs.push(("TYPE", "webdav".to_string()));
+                envs.push(("URL", settings.server.clone()));
+            }
+            SaveSyncBackend::Sftp => {
+                envs.push(("TYPE", "sftp".to_string()));
+                match settings.server.split_once(':') {
+                    Some((host, port)) => {
+                        envs.push(("HOST", host.to_string()));
+                        envs.push(("PORT", port.to_string()));
+                    }
+                    None => envs.push(("HOST", settings.server.clone())),
+                }
+            }
+        }
+        if !settings.username.is_empty() {
+            envs.push(("USER", settings.username.clone()));
+        }
+        if !settings.obscured_password.is_empty() {
+            envs.push(("PASS", settings.obscured_password.clone()));
+        }
+
+        Self {
+            envs: envs
+                .into_iter()
+                .map(|(key, value)| (format!("{prefix}{key}"), value))
+                .collect(),
+            folder: settings.folder.trim_matches('/').to_string(),
+        }
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new(ALLIUM_RCLONE.as_path());
+        command.envs(self.envs.iter().map(|(key, value)| (key, value)));
+        command
+    }
+
+    fn remote_path(&self, path: &Path) -> String {
+        if self.folder.is_empty() {
+            format!("{REMOTE}:{}", path.display())
+        } else {
+            format!("{REMOTE}:{}/{}", self.folder, path.display())
+        }
+    }
+
+    /// Lists the synced files on the server, with their modification times.
+    async fn list(&self) -> Result<HashMap<PathBuf, i64>> {
+        let mut files = HashMap::new();
+        for dir in SYNCED_DIRS {
+            let output = self
+                .command()
+                .args(["lsjson", "--recursive", "--files-only"])
+                .arg(self.remote_path(Path::new(dir)))
+                .output()
+                .await?;
+            if output.status.code() == Some(RCLONE_DIRECTORY_NOT_FOUND) {
+                continue;
+            }
+            if !output.status.success() {
+                bail!(
+                    "failed to list {} on server: {}",
+                    dir,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            let listed: Vec<RemoteFile> = serde_json::from_slice(&output.stdout)?;
+            for file in listed {
+                files.insert(Path::new(dir).join(file.path), file.mod_time.timestamp());
+            }
+        }
+        Ok(files)
+    }
+
+    async fn upload(&self, path: &Path) -> Result<()> {
+        debug!("uploading {}", path.display());
+        let local = RETROARCH_PROFILE_DIR.join(path);
+        self.copy(path, local.as_os_str(), self.remote_path(path).as_ref())
+            .await
+    }
+
+    async fn download(&self, path: &Path) -> Result<()> {
+        self.download_to(path, path).await
+    }
+
+    /// Downloads the file at the path on the server to another path in the profile folder.
+    async fn download_to(&self, path: &Path, to: &Path) -> Result<()> {
+        debug!("downloading {} to {}", path.display(), to.display());
+        let local = RETROARCH_PROFILE_DIR.join(to);
+        self.copy(path, self.remote_path(path).as_ref(), local.as_os_str())
+            .await
+    }
+
+    async fn copy(&self, path: &Path, from: &OsStr, to: &OsStr) -> Result<()> {
+        let output = self
+            .command()
+            .arg("copyto")
+            .arg(from)
+            .arg(to)
+            .output()
+            .await?;
+        if !output.status.success() {
+            bail!(
+                "failed to copy {}: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Obscures a password the way rclone expects it in its config. The password is passed through
+/// stdin, so that it doesn't show up in the process list.
+async fn obscure(password: &str) -> Result<String> {
+    let mut child = Command::new(ALLIUM_RCLONE.as_path())
+        .args(["obscure", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(password.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        bail!("failed to obscure password: {}", output.status);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action() {
+        let synced = Some(SyncedFile {
+            local: 10,
+            remote: 20,
+        });
+        assert_eq!(action(Some(10), Some(20), synced), Action::Skip);
+        assert_eq!(action(Some(30), Some(20), synced), Action::Upload);
+        assert_eq!(action(Some(10), Some(30), synced), Action::Download);
+        assert_eq!(action(Some(30), Some(30), synced), Action::Skip);
+        assert_eq!(action(Some(30), Some(40), synced), Action::Conflict);
+        // Deletions aren't synced
+        assert_eq!(action(None, Some(20), synced), Action::Skip);
+        assert_eq!(action(Some(10), None, synced), Action::Skip);
+
+        assert_eq!(action(Some(10), None, None), Action::Upload);
+        assert_eq!(action(None, Some(10), None), Action::Download);
+        assert_eq!(action(Some(10), Some(20), None), Action::Conflict);
+    }
+
+    #[test]
+    fn test_conflict_path() {
+        assert_eq!(
+            conflict_path(Path::new("saves/Game.srm")),
+            Path::new("saves/Game.srm.conflict")
+        );
+        assert_eq!(
+            conflict_path(Path::new("states/Game.state1")).extension(),
+            Some(OsStr::new(CONFLICT_EXTENSION))
+        );
+    }
+
+    #[test]
+    fn test_legacy_password() {
+        let settings: SaveSyncSettings =
+            serde_json::from_str(r#"{"server": "example.com", "password": "hunter2"}"#).unwrap();
+        assert_eq!(settings.password, "hunter2");
+        assert!(settings.obscured_password.is_empty());
+
+        // The plain text password is never written back
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(!json.contains("hunter2"));
+    }
+}
@@ -0,0 +1,83 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use embedded_graphics::prelude::*;
+use log::info;
+
+use crate::constants::ALLIUM_THEMES_DIR;
+use crate::display::Display;
+use crate::geom::Rect;
+use crate::locale::{Locale, LocaleSettings};
+use crate::platform::{DefaultPlatform, Platform};
+use crate::stylesheet::Stylesheet;
+
+/// Theme and language to render snapshots with.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotConfig {
+    /// Name of a theme in the themes directory. Uses the current theme if unset.
+    pub theme: Option<String>,
+    /// Uses the current language if unset.
+    pub lang: Option<String>,
+}
+
+impl SnapshotConfig {
+    pub fn stylesheet(&self) -> Result<Stylesheet> {
+        let Some(theme) = self.theme.as_deref() else {
+            return Stylesheet::load();
+        };
+        let dir = ALLIUM_THEMES_DIR.join(theme);
+        if !dir.join("theme.json").is_file() {
+            bail!("theme not found: {}", dir.display());
+        }
+        Stylesheet::load_theme(&dir)
+    }
+
+    pub fn locale(&self) -> Result<Locale> {
+        let lang = match self.lang.clone() {
+            Some(lang) => lang,
+            None => LocaleSettings::load()?.lang,
+        };
+        Ok(Locale::new(&lang))
+    }
+}
+
+/// Renders screens to PNG files, so that theme and translation authors can review every screen
+/// without clicking through the UI. Nothing is shown in the simulator window.
+pub struct Snapshotter {
+    dir: PathBuf,
+    display: <DefaultPlatform as Platform>::Display,
+}
+
+impl Snapshotter {
+    /// Snapshots are drawn over what's on the display when this is called.
+    pub fn new(dir: PathBuf, mut display: <DefaultPlatform as Platform>::Display) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+        display.save()?;
+        Ok(Self { dir, display })
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.display.bounding_box().into()
+    }
+
+    /// Gives access to the display, such as to draw a different background. Call `save` on it
+    /// to keep the background for the next snapshots.
+    pub fn display_mut(&mut self) -> &mut <DefaultPlatform as Platform>::Display {
+        &mut self.display
+    }
+
+    /// Restores the background, draws a screen over it and saves it as `<name>.png`.
+    pub fn capture<F>(&mut self, name: &str, draw: F) -> Result<()>
+    where
+        F: FnOnce(&mut <DefaultPlatform as Platform>::Display) -> Result<()>,
+    {
+        let rect = self.rect();
+        self.display.load(rect)?;
+        draw(&mut self.display)?;
+
+        let path = self.dir.join(format!("{name}.png"));
+        info!("saving snapshot to {}", path.display());
+        self.display.save_png(&path)
+    }
+}
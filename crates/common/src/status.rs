@@ -0,0 +1,78 @@
+use std::fs::{self, File};
+
+use anyhow::Result;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::battery::Battery;
+use crate::constants::ALLIUM_DEVICE_STATUS;
+
+/// Battery, Wi-Fi, volume and storage state, published by alliumd for the status bar in other processes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceStatus {
+    pub battery_percentage: i32,
+    pub charging: bool,
+    pub wifi_connected: bool,
+    pub volume: i32,
+    /// Whether the battery saver is on, so that the launcher saves power too.
+    pub battery_saver: bool,
+    /// Whether a second storage device is mounted, so that the launcher can refresh the library.
+    pub external_storage: bool,
+}
+
+impl Default for DeviceStatus {
+    fn default() -> Self {
+        Self {
+            battery_percentage: 100,
+            charging: false,
+            wifi_connected: false,
+            volume: 0,
+            battery_saver: false,
+            external_storage: false,
+        }
+    }
+}
+
+impl DeviceStatus {
+    /// Loads the status published by alliumd. If alliumd isn't running, or the file can't be
+    /// read, a full battery is assumed. The file is left alone, as alliumd rewrites it.
+    pub fn load() -> Result<Self> {
+        if ALLIUM_DEVICE_STATUS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_DEVICE_STATUS.as_path())?;
+            match serde_json::from_reader(file) {
+                Ok(json) => return Ok(json),
+                Err(e) => warn!("failed to read status file: {}", e),
+            }
+        }
+        Ok(Self::default())
+    }
+
+    /// Saves the status to a temporary file that then replaces the old one, so that other
+    /// processes never read a partly written file.
+    pub fn save(&self) -> Result<()> {
+        let temp = ALLIUM_DEVICE_STATUS.with_extension("tmp");
+        let file = File::create(&temp)?;
+        serde_json::to_writer(file, &self)?;
+        fs::rename(&temp, ALLIUM_DEVICE_STATUS.as_path())?;
+        Ok(())
+    }
+}
+
+/// Reading the battery through the published status avoids polling the battery from several
+/// processes at once.
+impl Battery for DeviceStatus {
+    fn update(&mut self) -> Result<()> {
+        *self = Self::load()?;
+        Ok(())
+    }
+
+    fn percentage(&self) -> i32 {
+        self.battery_percentage
+    }
+
+    fn charging(&self) -> bool {
+        self.charging
+    }
+}
@@ -0,0 +1,364 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::constants::{
+    ALLIUM_EXTERNAL_GAMES_DIR, ALLIUM_EXTERNAL_STORAGE, ALLIUM_EXTERNAL_STORAGE_DIR,
+    ALLIUM_GAMES_DIR, ALLIUM_SD_ROOT, ALLIUM_TRASH_DIR,
+};
+
+/// Folder on the mount point that the database keeps the games of other devices in while they
+/// aren't mounted. Nothing is ever written there.
+const PARKED_DIR: &str = ".allium-volumes";
+
+/// A second SD card or USB drive, mounted by alliumd at `ALLIUM_EXTERNAL_STORAGE_DIR`. The `Roms`
+/// folder on it is merged into the games folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Volume {
+    /// Block device of the partition, e.g. `/dev/sda1`.
+    pub device: PathBuf,
+    /// Filesystem UUID or label, which the games on the volume are tagged with in the database.
+    pub id: String,
+}
+
+impl Volume {
+    /// Loads the mounted volume, or `None` if no volume is mounted.
+    pub fn load() -> Option<Self> {
+        if !is_mounted() {
+            return None;
+        }
+        let file = File::open(ALLIUM_EXTERNAL_STORAGE.as_path()).ok()?;
+        match serde_json::from_reader(file) {
+            Ok(volume) => Some(volume),
+            Err(e) => {
+                warn!("failed to read storage file: {}", e);
+                None
+            }
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let file = File::create(ALLIUM_EXTERNAL_STORAGE.as_path())?;
+        serde_json::to_writer(file, &self)?;
+        Ok(())
+    }
+
+    /// Mounts a partition at `ALLIUM_EXTERNAL_STORAGE_DIR`.
+    pub async fn mount(device: &Path) -> Result<Self> {
+        info!("mounting {:?}", device);
+        fs::create_dir_all(ALLIUM_EXTERNAL_STORAGE_DIR.as_path())?;
+        let status = Command::new("mount")
+            .arg(device)
+            .arg(ALLIUM_EXTERNAL_STORAGE_DIR.as_path())
+            .status()
+            .await?;
+        if !status.success() {
+            bail!("failed to mount {:?}: {}", device, status);
+        }
+
+        let id = match Command::new("blkid").arg(device).output().await {
+            Ok(output) => parse_blkid(&String::from_utf8_lossy(&output.stdout)),
+            Err(e) => {
+                warn!("failed to run blkid: {}", e);
+                None
+            }
+        };
+        // Without a UUID or label, the device name is the best guess at telling volumes apart
+        let id = id.unwrap_or_else(|| {
+            device
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        });
+
+        let volume = Self {
+            device: device.to_path_buf(),
+            id,
+        };
+        volume.save()?;
+        debug!("mounted volume: {:?}", volume);
+        Ok(volume)
+    }
+
+    /// Unmounts the volume. The unmount is lazy, as the device is usually gone already.
+    pub async fn unmount(&self) -> Result<()> {
+        info!("unmounting {:?}", self.device);
+        let status = Command::new("umount")
+            .arg("-l")
+            .arg(ALLIUM_EXTERNAL_STORAGE_DIR.as_path())
+            .status()
+            .await?;
+        if !status.success() {
+            bail!("failed to unmount {:?}: {}", self.device, status);
+        }
+        if ALLIUM_EXTERNAL_STORAGE.exists() {
+            fs::remove_file(ALLIUM_EXTERNAL_STORAGE.as_path())?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds a partition of a second SD card or USB drive.
+#[cfg(feature = "miyoo")]
+pub fn detect() -> Option<PathBuf> {
+    let partitions = fs::read_to_string("/proc/partitions").ok()?;
+    let device = Path::new("/dev").join(find_partition(&partitions)?);
+    debug!("found storage device {:?}", device);
+    device.exists().then_some(device)
+}
+
+/// Storage devices aren't detected off the device, so that the computer's drives are left alone.
+#[cfg(not(feature = "miyoo"))]
+pub fn detect() -> Option<PathBuf> {
+    None
+}
+
+/// Whether a volume is mounted at `ALLIUM_EXTERNAL_STORAGE_DIR`.
+pub fn is_mounted() -> bool {
+    let Ok(mounts) = fs::read_to_string("/proc/mounts") else {
+        return false;
+    };
+    let mount_point = ALLIUM_EXTERNAL_STORAGE_DIR.display().to_string();
+    let mount_point = mount_point.trim_end_matches('/');
+    mounts
+        .lines()
+        .any(|line| line.split_whitespace().nth(1) == Some(mount_point))
+}
+
+/// Whether the path is on the second storage device.
+pub fn is_external(path: &Path) -> bool {
+    path.starts_with(ALLIUM_EXTERNAL_STORAGE_DIR.as_path())
+}
+
+/// Whether the path can be opened. Paths on the second storage device can't be while it's
+/// removed.
+pub fn is_available(path: &Path) -> bool {
+    !is_external(path) || path.exists()
+}
+
+/// The trash folder on the same volume as the path, so that files are moved to it without being
+/// copied.
+pub fn trash_dir(path: &Path) -> PathBuf {
+    let [trash_dir, external_trash_dir] = trash_dirs();
+    if is_external(path) {
+        external_trash_dir
+    } else {
+        trash_dir
+    }
+}
+
+/// Trash folders on the SD card and on the second storage device.
+pub fn trash_dirs() -> [PathBuf; 2] {
+    [
+        ALLIUM_TRASH_DIR.clone(),
+        ALLIUM_EXTERNAL_STORAGE_DIR.join(".allium-trash"),
+    ]
+}
+
+/// Where a file is moved to in the trash, keeping its path relative to the root of its volume so
+/// that files with the same name don't clash.
+pub fn trash_path(trash_id: i64, path: &Path) -> PathBuf {
+    let root = if is_external(path) {
+        ALLIUM_EXTERNAL_STORAGE_DIR.as_path()
+    } else {
+        ALLIUM_SD_ROOT.as_path()
+    };
+    let relative = path
+        .strip_prefix(root)
+        .ok()
+        .or_else(|| path.file_name().map(Path::new))
+        .unwrap_or(path);
+    trash_dir(path).join(trash_id.to_string()).join(relative)
+}
+
+/// The folder on the other volume that's merged with this games folder, e.g. `Roms/GBA` on the
+/// second storage device for `Roms/GBA` on the SD card.
+pub fn mirror(path: &Path) -> Option<PathBuf> {
+    mirror_between(path, &ALLIUM_GAMES_DIR, &ALLIUM_EXTERNAL_GAMES_DIR)
+}
+
+fn mirror_between(path: &Path, games_dir: &Path, external_games_dir: &Path) -> Option<PathBuf> {
+    if let Ok(relative) = path.strip_prefix(external_games_dir) {
+        Some(games_dir.join(relative))
+    } else if let Ok(relative) = path.strip_prefix(games_dir) {
+        Some(external_games_dir.join(relative))
+    } else {
+        None
+    }
+}
+
+/// Where the database keeps a game of the device `id` while another device is mounted, so that
+/// games at the same path on different devices are told apart. Returns `None` if the game isn't
+/// on a storage device, or is kept aside already.
+pub fn parked_path(id: &str, path: &Path) -> Option<PathBuf> {
+    park_between(id, path, &ALLIUM_EXTERNAL_STORAGE_DIR)
+}
+
+/// Where a game of the device `id` that was kept aside goes back to once the device is mounted
+/// again, or `None` if it wasn't kept aside.
+pub fn unparked_path(id: &str, path: &Path) -> Option<PathBuf> {
+    unpark_between(id, path, &ALLIUM_EXTERNAL_STORAGE_DIR)
+}
+
+fn park_between(id: &str, path: &Path, mount_point: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(mount_point).ok()?;
+    if relative.starts_with(PARKED_DIR) {
+        return None;
+    }
+    Some(mount_point.join(PARKED_DIR).join(id).join(relative))
+}
+
+fn unpark_between(id: &str, path: &Path, mount_point: &Path) -> Option<PathBuf> {
+    let relative = path
+        .strip_prefix(mount_point.join(PARKED_DIR).join(id))
+        .ok()?;
+    Some(mount_point.join(relative))
+}
+
+/// Finds the first partition in `/proc/partitions` that isn't on the device's own SD card.
+/// Partitionless drives are used whole.
+#[cfg_attr(not(any(feature = "miyoo", test)), allow(dead_code))]
+fn find_partition(partitions: &str) -> Option<String> {
+    let names: Vec<&str> = partitions
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(3))
+        .filter(|name| {
+            name.starts_with("sd") || (name.starts_with("mmcblk") && !name.starts_with("mmcblk0"))
+        })
+        .collect();
+
+    names
+        .iter()
+        .find(|name| name.ends_with(|c: char| c.is_ascii_digit()) && !is_disk(name))
+        .or_else(|| names.iter().find(|name| is_disk(name)))
+        .map(|name| name.to_string())
+}
+
+/// Whether the block device name is a whole disk, rather than a partition.
+#[cfg_attr(not(any(feature = "miyoo", test)), allow(dead_code))]
+fn is_disk(name: &str) -> bool {
+    match name.strip_prefix("mmcblk") {
+        Some(rest) => !rest.contains('p'),
+        None => !name.ends_with(|c: char| c.is_ascii_digit()),
+    }
+}
+
+/// Reads the UUID, or the label if there's no UUID, from `blkid` output such as
+/// `/dev/sda1: LABEL="GAMES" UUID="1234-ABCD" TYPE="vfat"`.
+fn parse_blkid(output: &str) -> Option<String> {
+    let value = |key: &str| {
+        let start = output.find(&format!(" {key}=\""))? + key.len() + 3;
+        let end = output[start..].find('"')? + start;
+        Some(output[start..end].to_string()).filter(|v| !v.is_empty())
+    };
+    value("UUID").or_else(|| value("LABEL"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_partition() {
+        let partitions = "major minor  #blocks  name
+
+ 179        0   62367744 mmcblk0
+ 179        1   62363648 mmcblk0p1
+   8        0   31266816 sda
+   8        1   31262720 sda1
+";
+        assert_eq!(find_partition(partitions), Some("sda1".to_string()));
+
+        let partitions = "major minor  #blocks  name
+
+ 179        0   62367744 mmcblk0
+ 179        1   62363648 mmcblk0p1
+ 179        8   31266816 mmcblk1
+";
+        assert_eq!(find_partition(partitions), Some("mmcblk1".to_string()));
+
+        let partitions = "major minor  #blocks  name
+
+ 179        0   62367744 mmcblk0
+ 179        1   62363648 mmcblk0p1
+";
+        assert_eq!(find_partition(partitions), None);
+    }
+
+    #[test]
+    fn test_parse_blkid() {
+        assert_eq!(
+            parse_blkid(r#"/dev/sda1: LABEL="GAMES" UUID="1234-ABCD" TYPE="vfat""#),
+            Some("1234-ABCD".to_string())
+        );
+        assert_eq!(
+            parse_blkid(r#"/dev/sda1: LABEL="GAMES" TYPE="vfat""#),
+            Some("GAMES".to_string())
+        );
+        assert_eq!(parse_blkid(r#"/dev/sda1: TYPE="vfat""#), None);
+    }
+
+    #[test]
+    fn test_trash_path() {
+        let path = ALLIUM_SD_ROOT.join("Roms/GBA/Game.gba");
+        assert_eq!(
+            trash_path(3, &path),
+            ALLIUM_TRASH_DIR.join("3/Roms/GBA/Game.gba")
+        );
+        let path = ALLIUM_EXTERNAL_GAMES_DIR.join("GBA/Game.gba");
+        assert_eq!(
+            trash_path(3, &path),
+            ALLIUM_EXTERNAL_STORAGE_DIR.join(".allium-trash/3/Roms/GBA/Game.gba")
+        );
+    }
+
+    #[test]
+    fn test_parked_path() {
+        let mount_point = Path::new("/mnt/EXTERNAL");
+        let game = Path::new("/mnt/EXTERNAL/Roms/GBA/Game.gba");
+        let parked = park_between("1234-ABCD", game, mount_point).unwrap();
+        assert_eq!(
+            parked,
+            PathBuf::from("/mnt/EXTERNAL/.allium-volumes/1234-ABCD/Roms/GBA/Game.gba")
+        );
+        assert_eq!(park_between("5678-EF01", &parked, mount_point), None);
+        assert_eq!(
+            park_between(
+                "1234-ABCD",
+                Path::new("/mnt/SDCARD/Roms/GBA/Game.gba"),
+                mount_point
+            ),
+            None
+        );
+
+        assert_eq!(
+            unpark_between("1234-ABCD", &parked, mount_point),
+            Some(game.to_path_buf())
+        );
+        assert_eq!(unpark_between("5678-EF01", &parked, mount_point), None);
+        assert_eq!(unpark_between("1234-ABCD", game, mount_point), None);
+    }
+
+    #[test]
+    fn test_mirror() {
+        let games = Path::new("/mnt/SDCARD/Roms");
+        let external = Path::new("/mnt/EXTERNAL/Roms");
+        assert_eq!(
+            mirror_between(Path::new("/mnt/SDCARD/Roms/GBA"), games, external),
+            Some(PathBuf::from("/mnt/EXTERNAL/Roms/GBA"))
+        );
+        assert_eq!(
+            mirror_between(Path::new("/mnt/EXTERNAL/Roms/GBA"), games, external),
+            Some(PathBuf::from("/mnt/SDCARD/Roms/GBA"))
+        );
+        assert_eq!(
+            mirror_between(Path::new("/mnt/SDCARD/Apps"), games, external),
+            None
+        );
+    }
+}
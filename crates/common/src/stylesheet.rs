@@ -1,16 +1,25 @@
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
 use std::fs::{self, File};
 use std::io::Write;
 use std::mem;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use anyhow::Result;
-use log::{debug, error, warn};
+use anyhow::{Context, Result};
+use chrono::{Datelike, NaiveDate};
+use log::{debug, error, info, warn};
 use rusttype::Font;
 use serde::{Deserialize, Serialize};
+use strum::FromRepr;
 
 use crate::{
-    constants::{ALLIUM_FONTS_DIR, ALLIUM_STYLESHEET},
+    constants::{
+        ALLIUM_FONTS_DIR, ALLIUM_SD_ROOT, ALLIUM_STYLESHEET, ALLIUM_THEMES_DIR,
+        ALLIUM_WALLPAPERS_DIR, SELECTION_MARGIN,
+    },
     display::color::Color,
+    game_tags::Region,
 };
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -26,6 +35,8 @@ pub enum StylesheetColor {
     ButtonX,
     ButtonY,
     BackgroundHighlightBlend,
+    /// A color that isn't part of the stylesheet, e.g. a console's accent color.
+    Custom(Color),
 }
 
 impl StylesheetColor {
@@ -44,6 +55,7 @@ impl StylesheetColor {
             Self::BackgroundHighlightBlend => stylesheet
                 .background_color
                 .blend(stylesheet.highlight_color, 128),
+            Self::Custom(color) => *color,
         }
     }
 }
@@ -116,22 +128,161 @@ impl StylesheetFont {
     pub fn guide_font() -> Self {
         Self::new(ALLIUM_FONTS_DIR.join("Nunito.ttf"), 28)
     }
+}
+
+/// A date-based overlay applied on top of the stylesheet, e.g. a Halloween accent color during October.
+/// Overlays are read from `seasonal.json` in the active theme package, and the first active one
+/// is applied. Images are relative to the theme package.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonalOverlay {
+    /// First day the overlay is active, formatted as "MM-DD".
+    pub start: String,
+    /// Last day the overlay is active, formatted as "MM-DD". May be before `start` to wrap around the new year.
+    pub end: String,
+    #[serde(default)]
+    pub wallpaper: Option<PathBuf>,
+    /// Image shown when the launcher starts on boot.
+    #[serde(default)]
+    pub splash: Option<PathBuf>,
+    #[serde(default)]
+    pub highlight_color: Option<Color>,
+    #[serde(default)]
+    pub tab_selected_color: Option<Color>,
+}
+
+impl SeasonalOverlay {
+    /// Loads the overlays scheduled by a theme package.
+    pub fn load(theme: &Path) -> Vec<Self> {
+        let path = theme.join("seasonal.json");
+        if !path.exists() {
+            return Vec::new();
+        }
+        match fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(serde_json::from_str(&json)?))
+        {
+            Ok(overlays) => overlays,
+            Err(e) => {
+                warn!("failed to read seasonal overlays: {}", e);
+                Vec::new()
+            }
+        }
+    }
 
-    /// Default CJK font.
-    pub fn cjk_font() -> Self {
-        Self::new(ALLIUM_FONTS_DIR.join("NotoSansCJK.otf"), 32)
+    /// Whether the overlay is active on the given date.
+    pub fn is_active(&self, date: NaiveDate) -> bool {
+        let start = parse_month_day(&self.start);
+        let end = parse_month_day(&self.end);
+        let (Some(start), Some(end)) = (start, end) else {
+            warn!("invalid seasonal overlay dates: {} - {}", self.start, self.end);
+            return false;
+        };
+        let today = (date.month(), date.day());
+        if start <= end {
+            start <= today && today <= end
+        } else {
+            today >= start || today <= end
+        }
     }
 }
 
+/// Returns the path relative to the SD card root, as paths in the stylesheet are saved, or the
+/// path itself if it's outside the SD card.
+fn relative_to_sd_root(path: PathBuf) -> PathBuf {
+    path.strip_prefix(ALLIUM_SD_ROOT.as_path())
+        .map(Path::to_path_buf)
+        .unwrap_or(path)
+}
+
+fn parse_month_day(s: &str) -> Option<(u32, u32)> {
+    let (month, day) = s.split_once('-')?;
+    Some((month.parse().ok()?, day.parse().ok()?))
+}
+
+/// Icon and accent color of a console, shown next to its folders in lists.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsoleStyle {
+    #[serde(default)]
+    pub icon: Option<PathBuf>,
+    #[serde(default)]
+    pub color: Option<Color>,
+}
+
+/// How tightly list entries are packed together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, FromRepr)]
+pub enum ListDensity {
+    Compact,
+    #[default]
+    Normal,
+    Comfortable,
+}
+
+/// How the tags in game file names, such as `(USA)` or `(Rev 1)`, are shown in lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, FromRepr)]
+pub enum TagDisplay {
+    #[default]
+    Hidden,
+    /// Only the region, as a short code such as `US`.
+    Region,
+    All,
+}
+
+/// How the A, B, X and Y buttons are drawn in button hints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, FromRepr)]
+pub enum ButtonIconStyle {
+    /// Colored circles with the letters printed on the device.
+    #[default]
+    Letters,
+    /// PlayStation shapes: circle, cross, triangle and square.
+    PlayStation,
+    /// Letters where they are on an Xbox controller, with A at the bottom.
+    Xbox,
+    /// Plain text labels, without the colored circles.
+    Text,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Stylesheet {
     pub wallpaper: Option<PathBuf>,
+    /// Theme package the stylesheet was last loaded from, relative to the SD card root. Its
+    /// seasonal overlays are applied.
+    #[serde(default)]
+    pub theme: Option<PathBuf>,
+    /// Image shown when the launcher starts on boot, from the active seasonal overlay.
+    #[serde(skip)]
+    pub splash: Option<PathBuf>,
     pub show_battery_level: bool,
     pub show_clock: bool,
     #[serde(default)]
     pub use_recents_carousel: bool,
+    #[serde(default = "Stylesheet::default_group_favorites")]
+    pub group_favorites: bool,
     #[serde(default = "Stylesheet::default_boxart_width")]
     pub boxart_width: u32,
+    #[serde(default)]
+    pub list_density: ListDensity,
+    /// Characters per second that long selected entries scroll by. Scrolling is disabled at 0.
+    #[serde(default = "Stylesheet::default_marquee_speed")]
+    pub marquee_speed: u32,
+    /// Milliseconds that long selected entries wait before scrolling.
+    #[serde(default = "Stylesheet::default_marquee_pause")]
+    pub marquee_pause: u32,
+    /// How the tags in game file names, such as the region, are shown.
+    #[serde(default)]
+    pub tag_display: TagDisplay,
+    /// When a folder has the same game for several regions, only this region's is shown.
+    #[serde(default)]
+    pub preferred_region: Option<Region>,
+    /// How face buttons are drawn in button hints.
+    #[serde(default)]
+    pub button_icons: ButtonIconStyle,
+    /// Whether button hints are shown. When hidden, lists extend into the space they took.
+    #[serde(default = "Stylesheet::default_show_button_hints")]
+    pub show_button_hints: bool,
+    /// Icons and accent colors of consoles by name, overriding the console definitions. Icons
+    /// are relative to the SD card root.
+    #[serde(default)]
+    pub consoles: BTreeMap<String, ConsoleStyle>,
     #[serde(default = "Stylesheet::default_foreground_color")]
     pub foreground_color: Color,
     #[serde(default = "Stylesheet::default_background_color")]
@@ -156,8 +307,12 @@ pub struct Stylesheet {
     pub ui_font: StylesheetFont,
     #[serde(default = "StylesheetFont::guide_font")]
     pub guide_font: StylesheetFont,
-    #[serde(skip, default = "StylesheetFont::cjk_font")]
-    pub cjk_font: StylesheetFont,
+    /// Fonts that characters missing from the UI and guide fonts are taken from, such as CJK
+    /// characters. Each character is taken from the first font that has it.
+    #[serde(default = "Stylesheet::default_fallback_fonts")]
+    pub fallback_fonts: Vec<PathBuf>,
+    #[serde(skip)]
+    fallbacks: Vec<Font<'static>>,
     #[serde(default = "Stylesheet::default_tab_font_size")]
     pub tab_font_size: f32,
     #[serde(default = "Stylesheet::default_status_bar_font_size")]
@@ -228,18 +383,24 @@ impl Stylesheet {
             self.guide_font = StylesheetFont::guide_font();
             self.guide_font.load()?;
         }
-        if let Err(e) = self.cjk_font.load() {
-            error!(
-                "failed to load CJK font: {} ({})",
-                self.cjk_font.path.display(),
-                e
-            );
-            self.cjk_font = StylesheetFont::guide_font();
-            self.cjk_font.load()?;
+        self.fallbacks.clear();
+        for path in &self.fallback_fonts {
+            let font = fs::read(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|bytes| Font::try_from_vec(bytes).context("invalid font"));
+            match font {
+                Ok(font) => self.fallbacks.push(font),
+                Err(e) => error!("failed to load fallback font: {} ({})", path.display(), e),
+            }
         }
         Ok(())
     }
 
+    /// Returns the loaded fallback fonts, in order.
+    pub fn fallbacks(&self) -> Vec<Font<'static>> {
+        self.fallbacks.clone()
+    }
+
     pub fn save(&self) -> Result<()> {
         let json = serde_json::to_string(&self).unwrap();
         File::create(ALLIUM_STYLESHEET.as_path())?.write_all(json.as_bytes())?;
@@ -249,6 +410,125 @@ impl Stylesheet {
         Ok(())
     }
 
+    /// Saves the stylesheet as a theme package in `Themes/<name>`, returning its directory.
+    /// The wallpaper and console icons are copied into the package so that they can be shared
+    /// with the theme.
+    pub fn save_theme(&self, name: &str) -> Result<PathBuf> {
+        let dir = ALLIUM_THEMES_DIR.join(name.replace(['/', '\\'], "-"));
+        fs::create_dir_all(&dir)?;
+
+        let mut theme = self.clone();
+        theme.theme = None;
+        if let Some(wallpaper) = self.wallpaper.as_ref() {
+            let src = ALLIUM_SD_ROOT.join(wallpaper);
+            let ext = src.extension().and_then(OsStr::to_str).unwrap_or("png");
+            let dst = dir.join(format!("wallpaper.{ext}"));
+            if src != dst {
+                fs::copy(&src, &dst)?;
+            }
+            theme.wallpaper = Some(
+                dst.strip_prefix(ALLIUM_SD_ROOT.as_path())
+                    .map(Path::to_path_buf)
+                    .unwrap_or(dst),
+            );
+        }
+        for (name, style) in theme.consoles.iter_mut() {
+            let Some(icon) = style.icon.as_mut() else {
+                continue;
+            };
+            let src = ALLIUM_SD_ROOT.join(&*icon);
+            let ext = src.extension().and_then(OsStr::to_str).unwrap_or("png");
+            let dst = dir
+                .join("consoles")
+                .join(format!("{}.{ext}", name.replace(['/', '\\'], "-")));
+            if src != dst {
+                fs::create_dir_all(dir.join("consoles"))?;
+                fs::copy(&src, &dst)?;
+            }
+            *icon = dst
+                .strip_prefix(ALLIUM_SD_ROOT.as_path())
+                .map(Path::to_path_buf)
+                .unwrap_or(dst);
+        }
+
+        let json = serde_json::to_string_pretty(&theme)?;
+        fs::write(dir.join("theme.json"), json)?;
+        Ok(dir)
+    }
+
+    /// Loads a theme package saved with `save_theme`.
+    pub fn load_theme(dir: &Path) -> Result<Self> {
+        let json = fs::read_to_string(dir.join("theme.json"))?;
+        let mut styles = serde_json::from_str::<Self>(&json)?;
+        styles.theme = Some(relative_to_sd_root(dir.to_path_buf()));
+        styles.load_fonts()?;
+        Ok(styles)
+    }
+
+    /// Returns the directories of installed theme packages, sorted by name.
+    pub fn available_themes() -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(ALLIUM_THEMES_DIR.as_path()) else {
+            return Vec::new();
+        };
+        let mut themes: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.join("theme.json").is_file())
+            .collect();
+        themes.sort();
+        themes
+    }
+
+    /// Returns the images in the wallpapers directory, relative to the SD card root.
+    pub fn available_wallpapers() -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(ALLIUM_WALLPAPERS_DIR.as_path()) else {
+            return Vec::new();
+        };
+        let mut wallpapers: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.extension()
+                    .and_then(OsStr::to_str)
+                    .is_some_and(|ext| matches!(ext, "png" | "jpg" | "jpeg"))
+            })
+            .filter_map(|path| {
+                path.strip_prefix(ALLIUM_SD_ROOT.as_path())
+                    .ok()
+                    .map(Path::to_path_buf)
+            })
+            .collect();
+        wallpapers.sort();
+        wallpapers
+    }
+
+    /// Applies the first seasonal overlay of the active theme that is active on the given date.
+    /// The overlay is not saved.
+    pub fn apply_seasonal_overlay(&mut self, date: NaiveDate) {
+        let Some(theme) = self.theme.as_deref() else {
+            return;
+        };
+        let theme = ALLIUM_SD_ROOT.join(theme);
+        let Some(overlay) = SeasonalOverlay::load(&theme)
+            .into_iter()
+            .find(|o| o.is_active(date))
+        else {
+            return;
+        };
+
+        info!("applying seasonal overlay: {} - {}", overlay.start, overlay.end);
+        if let Some(wallpaper) = overlay.wallpaper {
+            self.wallpaper = Some(relative_to_sd_root(theme.join(wallpaper)));
+        }
+        if let Some(splash) = overlay.splash {
+            self.splash = Some(relative_to_sd_root(theme.join(splash)));
+        }
+        if let Some(color) = overlay.highlight_color {
+            self.highlight_color = color;
+        }
+        if let Some(color) = overlay.tab_selected_color {
+            self.tab_selected_color = color;
+        }
+    }
+
     pub fn toggle_dark_mode(&mut self) {
         mem::swap(&mut self.foreground_color, &mut self.alt_foreground_color);
         mem::swap(&mut self.background_color, &mut self.alt_background_color);
@@ -288,6 +568,42 @@ impl Stylesheet {
         self.ui_font.size as f32 * self.status_bar_font_size
     }
 
+    /// Font size of list entries, relative to the UI font size.
+    #[inline]
+    pub fn list_font_size(&self) -> f32 {
+        match self.list_density {
+            ListDensity::Compact => 0.85,
+            ListDensity::Normal => 1.0,
+            ListDensity::Comfortable => 1.15,
+        }
+    }
+
+    /// Space between list entries, which surrounds the selection highlight.
+    #[inline]
+    pub fn list_selection_margin(&self) -> u32 {
+        match self.list_density {
+            ListDensity::Compact => SELECTION_MARGIN / 2,
+            ListDensity::Normal => SELECTION_MARGIN,
+            ListDensity::Comfortable => SELECTION_MARGIN * 2,
+        }
+    }
+
+    #[inline]
+    pub fn list_entry_height(&self) -> u32 {
+        (self.ui_font.size as f32 * self.list_font_size()) as u32 + self.list_selection_margin()
+    }
+
+    /// Time to scroll long selected entries by one character, or `None` if they don't scroll.
+    #[inline]
+    pub fn marquee_interval(&self) -> Option<Duration> {
+        (self.marquee_speed > 0).then(|| Duration::from_secs(1) / self.marquee_speed)
+    }
+
+    #[inline]
+    pub fn marquee_pause(&self) -> Duration {
+        Duration::from_millis(self.marquee_pause as u64)
+    }
+
     fn patch_ra_config(&self) -> Result<()> {
         let mut file = File::create("/mnt/SDCARD/RetroArch/.retroarch/assets/rgui/Allium.cfg")?;
         write!(
@@ -324,11 +640,35 @@ rgui_particle_color = "0xFF{highlight:X}"
         0.9
     }
 
+    #[inline]
+    fn default_group_favorites() -> bool {
+        true
+    }
+
+    fn default_show_button_hints() -> bool {
+        true
+    }
+
     #[inline]
     fn default_boxart_width() -> u32 {
         250
     }
 
+    #[inline]
+    fn default_fallback_fonts() -> Vec<PathBuf> {
+        vec![ALLIUM_FONTS_DIR.join("NotoSansCJK.otf")]
+    }
+
+    #[inline]
+    fn default_marquee_speed() -> u32 {
+        6
+    }
+
+    #[inline]
+    fn default_marquee_pause() -> u32 {
+        1000
+    }
+
     #[inline]
     fn default_foreground_color() -> Color {
         Color::new(255, 255, 255)
@@ -434,10 +774,21 @@ impl Default for Stylesheet {
     fn default() -> Self {
         Self {
             wallpaper: None,
+            theme: None,
+            splash: None,
             show_battery_level: false,
             show_clock: true,
             use_recents_carousel: false,
+            group_favorites: Self::default_group_favorites(),
             boxart_width: Self::default_boxart_width(),
+            list_density: ListDensity::default(),
+            marquee_speed: Self::default_marquee_speed(),
+            marquee_pause: Self::default_marquee_pause(),
+            tag_display: TagDisplay::default(),
+            preferred_region: None,
+            button_icons: ButtonIconStyle::default(),
+            show_button_hints: Self::default_show_button_hints(),
+            consoles: BTreeMap::new(),
             foreground_color: Self::default_foreground_color(),
             background_color: Self::default_background_color(),
             highlight_color: Self::default_highlight_color(),
@@ -450,7 +801,8 @@ impl Default for Stylesheet {
             button_y_color: Self::default_button_y_color(),
             ui_font: StylesheetFont::ui_font(),
             guide_font: StylesheetFont::guide_font(),
-            cjk_font: StylesheetFont::cjk_font(),
+            fallback_fonts: Self::default_fallback_fonts(),
+            fallbacks: Vec::new(),
             tab_font_size: Self::default_tab_font_size(),
             status_bar_font_size: Self::default_status_bar_font_size(),
             button_hint_font_size: Self::default_button_hint_font_size(),
@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Component, Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{Result, bail};
+use chrono::Duration;
+use log::{debug, info};
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::background;
+use crate::constants::{
+    ALLIUM_BUNDLE, ALLIUM_BUNDLE_LIBRARY, ALLIUM_DISPLAY_SETTINGS, ALLIUM_LOCALE_SETTINGS,
+    ALLIUM_POWER_SETTINGS, ALLIUM_SD_ROOT, ALLIUM_STYLESHEET, ALLIUM_THEMES_DIR, ALLIUM_TIMEZONE,
+    SYNC_DISCOVERY_TIMEOUT, SYNC_SERVICE_TYPE, WEB_REMOTE_PORT,
+};
+use crate::database::Database;
+use crate::http;
+use crate::wifi::{self, WiFiSettings};
+
+/// Play stats of a game, shared between devices.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameMetadata {
+    pub path: PathBuf,
+    pub favorite: bool,
+    pub play_count: i64,
+    /// Play time, in seconds.
+    pub play_time: i64,
+}
+
+/// Library metadata shared between devices. ROMs aren't included, so only games that are on
+/// both devices are merged.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LibraryMetadata {
+    pub games: Vec<GameMetadata>,
+    /// Game paths in each collection, in order.
+    pub collections: HashMap<String, Vec<PathBuf>>,
+}
+
+impl LibraryMetadata {
+    /// Reads the metadata of games that have been played or favorited, and all collections.
+    pub fn load(database: &Database) -> Result<Self> {
+        let games = database
+            .select_all_games()?
+            .into_iter()
+            .filter(|game| game.favorite || game.play_count > 0)
+            .map(|game| GameMetadata {
+                path: game.path,
+                favorite: game.favorite,
+                play_count: game.play_count,
+                play_time: game.play_time.num_seconds(),
+            })
+            .collect();
+
+        let mut collections = HashMap::new();
+        for name in database.select_collections()? {
+            let paths = collection_paths(database, &name)?;
+            collections.insert(name, paths);
+        }
+
+        Ok(Self { games, collections })
+    }
+
+    /// Merges the metadata into the database, returning the number of games merged. Play counts
+    /// and times keep the larger value, games favorited on either device become favorites, and
+    /// games only in the other device's collections are added to the end of the collection.
+    pub fn merge_into(&self, database: &Database) -> Result<usize> {
+        let mut merged = 0;
+        for game in &self.games {
+            let Some(local) = database.select_game(&game.path)? else {
+                continue;
+            };
+            database.merge_play_stats(
+                &game.path,
+                game.play_count,
+                Duration::seconds(game.play_time),
+            )?;
+            if game.favorite && !local.favorite {
+                database.set_favorite(&game.path, true)?;
+            }
+            merged += 1;
+        }
+
+        for (name, paths) in &self.collections {
+            let mut collection = collection_paths(database, name)?;
+            for path in paths {
+                if !collection.contains(path) && database.select_game(path)?.is_some() {
+                    collection.push(path.clone());
+                }
+            }
+            let collection = collection.iter().map(PathBuf::as_path).collect::<Vec<_>>();
+            database.set_collection(name, &collection)?;
+        }
+
+        info!("merged {} games from another device", merged);
+        Ok(merged)
+    }
+}
+
+fn collection_paths(database: &Database, name: &str) -> Result<Vec<PathBuf>> {
+    Ok(database
+        .select_collection(name, i64::MAX)?
+        .into_iter()
+        .map(|game| game.path)
+        .collect())
+}
+
+/// Settings included in a bundle. Wi-Fi settings are left out, as they hold the network
+/// password, and so is the clock, as its alarm and timers belong to the device. The lock screen
+/// and parental controls are left out too, so that importing a bundle can't change their PINs.
+fn bundle_settings() -> [&'static Path; 5] {
+    [
+        ALLIUM_STYLESHEET.as_path(),
+        ALLIUM_DISPLAY_SETTINGS.as_path(),
+        ALLIUM_LOCALE_SETTINGS.as_path(),
+        ALLIUM_POWER_SETTINGS.as_path(),
+        ALLIUM_TIMEZONE.as_path(),
+    ]
+}
+
+/// Paths relative to `ALLIUM_SD_ROOT` that a bundle may contain: the settings files and library
+/// metadata, and anything in the themes folder.
+fn bundle_paths() -> (Vec<PathBuf>, PathBuf) {
+    let relative = |path: &Path| {
+        path.strip_prefix(ALLIUM_SD_ROOT.as_path())
+            .unwrap_or(path)
+            .to_path_buf()
+    };
+    let files = bundle_settings()
+        .into_iter()
+        .chain([ALLIUM_BUNDLE_LIBRARY.as_path()])
+        .map(relative)
+        .collect();
+    (files, relative(&ALLIUM_THEMES_DIR))
+}
+
+/// Whether an archive member can be extracted from a bundle. Members must be relative paths
+/// without `..` that name one of the bundled files, or something in the themes folder.
+fn is_bundle_member(member: &str, files: &[PathBuf], themes: &Path) -> bool {
+    let path = Path::new(member);
+    let normal = path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !normal || member.is_empty() {
+        return false;
+    }
+    // tar lists members as "./path" when the archive was created from "."
+    let path = path.strip_prefix(".").unwrap_or(path);
+    files.iter().any(|file| path == file) || path.starts_with(themes)
+}
+
+/// Exports settings, themes and library metadata to a single archive at `ALLIUM_BUNDLE`, to be
+/// imported on another device.
+pub async fn export_bundle(library: &LibraryMetadata) -> Result<()> {
+    serde_json::to_writer(File::create(ALLIUM_BUNDLE_LIBRARY.as_path())?, library)?;
+
+    let paths = bundle_settings()
+        .into_iter()
+        .chain([ALLIUM_THEMES_DIR.as_path(), ALLIUM_BUNDLE_LIBRARY.as_path()])
+        .filter(|path| path.exists())
+        .filter_map(|path| path.strip_prefix(ALLIUM_SD_ROOT.as_path()).ok())
+        .collect::<Vec<_>>();
+    debug!("exporting bundle: {:?}", paths);
+
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(ALLIUM_BUNDLE.as_path())
+        .arg("-C")
+        .arg(ALLIUM_SD_ROOT.as_path())
+        .args(paths)
+        .status()
+        .await?;
+    fs::remove_file(ALLIUM_BUNDLE_LIBRARY.as_path())?;
+    if !status.success() {
+        bail!("failed to create bundle: {}", status);
+    }
+
+    info!("exported bundle to {}", ALLIUM_BUNDLE.display());
+    Ok(())
+}
+
+/// Imports the archive at `ALLIUM_BUNDLE`, overwriting settings and themes. Returns the
+/// library metadata in the bundle, to be merged into the database.
+pub async fn import_bundle() -> Result<LibraryMetadata> {
+    if !ALLIUM_BUNDLE.exists() {
+        bail!("no bundle found at {}", ALLIUM_BUNDLE.display());
+    }
+
+    // Bundles come from other devices, so every member is checked before anything is extracted
+    let output = Command::new("tar")
+        .arg("-tzf")
+        .arg(ALLIUM_BUNDLE.as_path())
+        .output()
+        .await?;
+    if !output.status.success() {
+        bail!("failed to list bundle: {}", output.status);
+    }
+    let (files, themes) = bundle_paths();
+    let listing = String::from_utf8_lossy(&output.stdout);
+    for member in listing.lines().filter(|line| !line.is_empty()) {
+        if !is_bundle_member(member, &files, &themes) {
+            bail!("bundle contains unexpected file: {}", member);
+        }
+    }
+
+    let status = Command::new("tar")
+        .arg("-xzf")
+        .arg(ALLIUM_BUNDLE.as_path())
+        .arg("-C")
+        .arg(ALLIUM_SD_ROOT.as_path())
+        .status()
+        .await?;
+    if !status.success() {
+        bail!("failed to extract bundle: {}", status);
+    }
+
+    if !ALLIUM_BUNDLE_LIBRARY.exists() {
+        return Ok(LibraryMetadata::default());
+    }
+    let library = serde_json::from_reader(File::open(ALLIUM_BUNDLE_LIBRARY.as_path())?)?;
+    fs::remove_file(ALLIUM_BUNDLE_LIBRARY.as_path())?;
+
+    info!("imported bundle from {}", ALLIUM_BUNDLE.display());
+    Ok(library)
+}
+
+/// Looks for another device advertising LAN sync, returning the address of its web remote.
+pub async fn discover() -> Result<Option<SocketAddr>> {
+    let own_address = wifi::ip_address();
+
+    tokio::task::spawn_blocking(move || {
+        let daemon = ServiceDaemon::new()?;
+        let receiver = daemon.browse(SYNC_SERVICE_TYPE)?;
+
+        let start = Instant::now();
+        let mut peer = None;
+        while let Some(timeout) = SYNC_DISCOVERY_TIMEOUT.checked_sub(start.elapsed()) {
+            let Ok(event) = receiver.recv_timeout(timeout) else {
+                break;
+            };
+            let ServiceEvent::ServiceResolved(service) = event else {
+                continue;
+            };
+
+            // This device's own service is found too
+            let addresses = service.get_addresses();
+            if addresses
+                .iter()
+                .any(|ip| own_address.as_ref() == Some(&ip.to_string()))
+            {
+                continue;
+            }
+            if let Some(ip) = addresses.iter().find(|ip| ip.is_ipv4()) {
+                peer = Some(SocketAddr::new(*ip, service.get_port()));
+                break;
+            }
+        }
+
+        daemon.shutdown().ok();
+        debug!("discovered sync peer: {:?}", peer);
+        Ok::<_, anyhow::Error>(peer)
+    })
+    .await?
+}
+
+/// Fetches the library metadata of another device. Both devices must have the same pairing
+/// code.
+pub async fn fetch_library(peer: SocketAddr) -> Result<LibraryMetadata> {
+    let token = WiFiSettings::load()?.web_remote_token;
+    let body = http::get_authorized(&format!("http://{peer}/api/library"), &token).await?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+/// Asks another device to fetch this device's library metadata and merge it into its own, so
+/// that both devices end up with the same metadata.
+pub async fn request_sync(peer: SocketAddr) -> Result<()> {
+    let token = WiFiSettings::load()?.web_remote_token;
+    http::post_json_authorized(
+        &format!("http://{peer}/api/sync"),
+        &serde_json::json!({}),
+        &token,
+    )
+    .await
+}
+
+/// Fetches the library metadata of the device at `ip` and merges it into the database. This
+/// handles sync requests from other devices, so it runs as a background job.
+pub async fn sync_from(ip: IpAddr) -> Result<usize> {
+    background::checkpoint().await;
+    let library = fetch_library(SocketAddr::new(ip, WEB_REMOTE_PORT)).await?;
+    background::checkpoint().await;
+    library.merge_into(&Database::new()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::database::NewGame;
+
+    fn new_game(path: &str) -> NewGame {
+        NewGame {
+            name: path.to_owned(),
+            path: PathBuf::from(path),
+            image: None,
+            core: None,
+            rating: None,
+            release_date: None,
+            developer: None,
+            publisher: None,
+            genres: Vec::new(),
+            favorite: false,
+        }
+    }
+
+    #[test]
+    fn test_merge_into() {
+        let database = Database::in_memory().unwrap();
+        database
+            .update_games(&[new_game("a.rom"), new_game("b.rom")])
+            .unwrap();
+        database
+            .set_collection("RPGs", &[Path::new("a.rom")])
+            .unwrap();
+
+        let library = LibraryMetadata {
+            games: vec![
+                GameMetadata {
+                    path: PathBuf::from("b.rom"),
+                    favorite: true,
+                    play_count: 3,
+                    play_time: 60,
+                },
+                GameMetadata {
+                    path: PathBuf::from("missing.rom"),
+                    favorite: true,
+                    play_count: 1,
+                    play_time: 60,
+                },
+            ],
+            collections: HashMap::from([(
+                "RPGs".to_owned(),
+                vec![PathBuf::from("b.rom"), PathBuf::from("missing.rom")],
+            )]),
+        };
+        assert_eq!(library.merge_into(&database).unwrap(), 1);
+
+        let b = database.select_game(Path::new("b.rom")).unwrap().unwrap();
+        assert!(b.favorite);
+        assert_eq!(b.play_count, 3);
+        assert_eq!(b.play_time, Duration::seconds(60));
+        assert_eq!(
+            collection_paths(&database, "RPGs").unwrap(),
+            vec![PathBuf::from("a.rom"), PathBuf::from("b.rom")],
+        );
+    }
+
+    #[test]
+    fn test_is_bundle_member() {
+        let files = vec![
+            PathBuf::from(".allium/config/display.json"),
+            PathBuf::from(".allium/bundle-library.json"),
+        ];
+        let themes = Path::new(".allium/themes");
+
+        assert!(is_bundle_member(
+            ".allium/config/display.json",
+            &files,
+            themes
+        ));
+        assert!(is_bundle_member(
+            "./.allium/bundle-library.json",
+            &files,
+            themes
+        ));
+        assert!(is_bundle_member(".allium/themes/", &files, themes));
+        assert!(is_bundle_member(
+            ".allium/themes/Dark/theme.json",
+            &files,
+            themes
+        ));
+
+        assert!(!is_bundle_member("", &files, themes));
+        assert!(!is_bundle_member("/etc/passwd", &files, themes));
+        assert!(!is_bundle_member(
+            ".allium/themes/../state/lock.json",
+            &files,
+            themes
+        ));
+        assert!(!is_bundle_member(".allium/state/lock.json", &files, themes));
+        assert!(!is_bundle_member("Saves/CurrentProfile", &files, themes));
+    }
+}
@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use crate::display::color::Color;
+use crate::geom::Point;
+
+/// How a tween eases between its start and end values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Starts slowly, then speeds up.
+    EaseIn,
+    /// Starts quickly, then slows down.
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps the progress `t`, from 0 to 1, to the eased progress.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// A value that can be animated by a `Tween`.
+pub trait Lerp: Copy {
+    /// Interpolates between `self` and `to`, where `t` is from 0 to 1.
+    fn lerp(self, to: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for i32 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + ((to - self) as f32 * t).round() as i32
+    }
+}
+
+impl Lerp for u8 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        (self as f32).lerp(to as f32, t).round() as u8
+    }
+}
+
+impl Lerp for Point {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        Point::new(self.x.lerp(to.x, t), self.y.lerp(to.y, t))
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        Color::rgba(
+            self.r().lerp(to.r(), t),
+            self.g().lerp(to.g(), t),
+            self.b().lerp(to.b(), t),
+            self.a().lerp(to.a(), t),
+        )
+    }
+}
+
+/// Animates a property from one value to another over a duration. Views advance their tweens
+/// from `View::update`, and draw with `Tween::value`.
+#[derive(Debug, Clone)]
+pub struct Tween<T> {
+    from: T,
+    to: T,
+    duration: Duration,
+    elapsed: Duration,
+    easing: Easing,
+}
+
+impl<T> Tween<T>
+where
+    T: Lerp,
+{
+    pub fn new(from: T, to: T, duration: Duration) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            elapsed: Duration::ZERO,
+            easing: Easing::default(),
+        }
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Advances the tween, returning whether it was still running, so the value changed.
+    pub fn update(&mut self, dt: Duration) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+        true
+    }
+
+    pub fn value(&self) -> T {
+        if self.duration.is_zero() {
+            return self.to;
+        }
+        let t = self.elapsed.as_secs_f32() / self.duration.as_secs_f32();
+        self.from.lerp(self.to, self.easing.apply(t))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easing() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            assert_eq!(easing.apply(2.0), 1.0);
+        }
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn test_tween() {
+        let mut tween = Tween::new(0, 100, Duration::from_millis(200));
+        assert_eq!(tween.value(), 0);
+
+        assert!(tween.update(Duration::from_millis(50)));
+        assert_eq!(tween.value(), 25);
+        assert!(!tween.is_finished());
+
+        assert!(tween.update(Duration::from_millis(500)));
+        assert_eq!(tween.value(), 100);
+        assert!(tween.is_finished());
+        assert!(!tween.update(Duration::from_millis(50)));
+
+        let tween = Tween::new(Point::new(0, 0), Point::new(10, -10), Duration::ZERO);
+        assert_eq!(tween.value(), Point::new(10, -10));
+    }
+}
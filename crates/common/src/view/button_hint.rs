@@ -7,6 +7,7 @@ use tokio::sync::mpsc::Sender;
 
 use crate::display::Display;
 use crate::geom::{Alignment, Point, Rect};
+use crate::platform::touch::Gesture;
 use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use crate::resources::Resources;
 use crate::stylesheet::Stylesheet;
@@ -21,6 +22,8 @@ where
     button: ButtonIcon,
     label: Label<S>,
     alignment: Alignment,
+    /// Whether button hints are hidden by the theme.
+    hidden: bool,
     has_layout: bool,
     dirty: bool,
 }
@@ -40,11 +43,22 @@ where
             button,
             label,
             alignment,
+            hidden: !styles.show_button_hints,
             has_layout: false,
             dirty: true,
         }
     }
 
+    /// Height taken by the row of button hints at the bottom of a view, including its margin.
+    /// It's 0 when the theme hides button hints, so that the view can use the space.
+    pub fn bar_height(styles: &Stylesheet) -> u32 {
+        if styles.show_button_hints {
+            ButtonIcon::diameter(styles) + 8
+        } else {
+            0
+        }
+    }
+
     pub fn set_text(&mut self, text: S) {
         self.label.set_text(text);
         self.has_layout = false;
@@ -89,6 +103,9 @@ where
         display: &mut <DefaultPlatform as Platform>::Display,
         styles: &Stylesheet,
     ) -> Result<bool> {
+        if self.hidden {
+            return Ok(false);
+        }
         if !self.has_layout {
             self.layout(styles);
         }
@@ -107,7 +124,7 @@ where
     }
 
     fn should_draw(&self) -> bool {
-        self.dirty || self.button.should_draw() || self.label.should_draw()
+        !self.hidden && (self.dirty || self.button.should_draw() || self.label.should_draw())
     }
 
     fn set_should_draw(&mut self) {
@@ -125,6 +142,25 @@ where
         Ok(false)
     }
 
+    async fn handle_gesture(
+        &mut self,
+        gesture: Gesture,
+        _styles: &Stylesheet,
+        _commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self.hidden {
+            return Ok(false);
+        }
+        match gesture {
+            Gesture::Tap(_) => {
+                bubble.push_back(Command::PressKey(self.button.button()));
+                Ok(true)
+            }
+            Gesture::Swipe { .. } => Ok(false),
+        }
+    }
+
     fn children(&self) -> Vec<&dyn View> {
         vec![&self.button, &self.label]
     }
@@ -5,7 +5,8 @@ use async_trait::async_trait;
 use embedded_graphics::Drawable;
 use embedded_graphics::prelude::{Dimensions, Size};
 use embedded_graphics::primitives::{
-    Circle, CornerRadii, CornerRadiiBuilder, Primitive, PrimitiveStyle, Rectangle, RoundedRectangle,
+    Circle, CornerRadii, CornerRadiiBuilder, Line, Primitive, PrimitiveStyle, Rectangle,
+    RoundedRectangle, Triangle,
 };
 use embedded_graphics::text::{Text, TextStyleBuilder};
 use serde::{Deserialize, Serialize};
@@ -13,8 +14,9 @@ use tokio::sync::mpsc::Sender;
 
 use crate::display::font::FontTextStyleBuilder;
 use crate::geom::{Alignment, Point, Rect};
+use crate::platform::layout::ButtonLayout;
 use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
-use crate::stylesheet::Stylesheet;
+use crate::stylesheet::{ButtonIconStyle, Stylesheet};
 use crate::view::{Command, View};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -35,11 +37,76 @@ impl ButtonIcon {
         }
     }
 
+    pub fn button(&self) -> Key {
+        self.button
+    }
+
     pub fn diameter(styles: &Stylesheet) -> u32 {
         styles.button_hint_font_size() as u32
     }
 }
 
+fn is_face_button(button: Key) -> bool {
+    matches!(button, Key::A | Key::B | Key::X | Key::Y)
+}
+
+/// Returns the text drawn on a face button in the given style, if it differs from the letter.
+fn face_label(button: Key, style: ButtonIconStyle) -> Option<&'static str> {
+    let label = match (style, button) {
+        (ButtonIconStyle::PlayStation, button) if is_face_button(button) => "",
+        // Xbox controllers have A and B, and X and Y, the other way around
+        (ButtonIconStyle::Xbox, Key::A) => "B",
+        (ButtonIconStyle::Xbox, Key::B) => "A",
+        (ButtonIconStyle::Xbox, Key::X) => "Y",
+        (ButtonIconStyle::Xbox, Key::Y) => "X",
+        _ => return None,
+    };
+    Some(label)
+}
+
+/// Draws the PlayStation shape of a face button inside its circle: a circle for the right
+/// button, a cross for the bottom, a triangle for the top and a square for the left.
+fn draw_shape(
+    display: &mut <DefaultPlatform as Platform>::Display,
+    button: Key,
+    point: embedded_graphics::prelude::Point,
+    diameter: u32,
+    styles: &Stylesheet,
+) -> Result<()> {
+    let size = diameter / 2;
+    let top_left = point + Size::new_equal(diameter / 4);
+    let bottom_right = top_left + Size::new_equal(size);
+    let style = PrimitiveStyle::with_stroke(styles.foreground_color, 2);
+    match button {
+        Key::A => Circle::new(top_left, size)
+            .into_styled(style)
+            .draw(display)?,
+        Key::B => {
+            Line::new(top_left, bottom_right)
+                .into_styled(style)
+                .draw(display)?;
+            Line::new(
+                embedded_graphics::prelude::Point::new(bottom_right.x, top_left.y),
+                embedded_graphics::prelude::Point::new(top_left.x, bottom_right.y),
+            )
+            .into_styled(style)
+            .draw(display)?;
+        }
+        Key::X => Triangle::new(
+            embedded_graphics::prelude::Point::new(top_left.x + size as i32 / 2, top_left.y),
+            embedded_graphics::prelude::Point::new(top_left.x, bottom_right.y),
+            bottom_right,
+        )
+        .into_styled(style)
+        .draw(display)?,
+        Key::Y => Rectangle::new(top_left, Size::new_equal(size))
+            .into_styled(style)
+            .draw(display)?,
+        _ => {}
+    }
+    Ok(())
+}
+
 #[async_trait(?Send)]
 impl View for ButtonIcon {
     fn draw(
@@ -47,7 +114,9 @@ impl View for ButtonIcon {
         display: &mut <DefaultPlatform as Platform>::Display,
         styles: &Stylesheet,
     ) -> Result<bool> {
-        let (color, text) = match self.button {
+        // Show the physical button to press, which differs if the face buttons are swapped
+        let button = ButtonLayout::current().map(self.button);
+        let (color, text) = match button {
             Key::A => (styles.button_a_color, "A"),
             Key::B => (styles.button_b_color, "B"),
             Key::X => (styles.button_x_color, "X"),
@@ -67,8 +136,16 @@ impl View for ButtonIcon {
             Key::VolDown => (styles.disabled_color, "VOL-"),
             Key::VolUp => (styles.disabled_color, "VOL+"),
             Key::LidClose => (styles.disabled_color, "LID"),
+            Key::Headphones => (styles.disabled_color, "PHONES"),
             Key::Unknown => unimplemented!("unknown button"),
         };
+        let text = face_label(button, styles.button_icons).unwrap_or(text);
+        let is_text = styles.button_icons == ButtonIconStyle::Text;
+        let color = if is_text && is_face_button(button) {
+            styles.disabled_color
+        } else {
+            color
+        };
 
         let diameter = Self::diameter(styles);
 
@@ -85,7 +162,7 @@ impl View for ButtonIcon {
         };
 
         let text_style = FontTextStyleBuilder::new(styles.ui_font.font())
-            .font_fallback(styles.cjk_font.font())
+            .font_fallbacks(styles.fallbacks())
             .font_size(diameter * 3 / 4)
             .text_color(styles.foreground_color)
             .build();
@@ -102,11 +179,14 @@ impl View for ButtonIcon {
         );
 
         let mut draw_bg = false;
-        let rect = match self.button {
-            Key::A | Key::B | Key::X | Key::Y => {
+        let rect = match button {
+            Key::A | Key::B | Key::X | Key::Y if !is_text => {
                 Circle::new(point, diameter)
                     .into_styled(PrimitiveStyle::with_fill(color))
                     .draw(display)?;
+                if styles.button_icons == ButtonIconStyle::PlayStation {
+                    draw_shape(display, button, point, diameter, styles)?;
+                }
                 Rect::new(point.x, point.y, diameter, diameter)
             }
             Key::Up | Key::Right | Key::Down | Key::Left => {
@@ -128,7 +208,7 @@ impl View for ButtonIcon {
                 )
                 .into_styled(PrimitiveStyle::with_fill(color))
                 .draw(display)?;
-                match self.button {
+                match button {
                     Key::Up => RoundedRectangle::new(
                         Rectangle::new(
                             Point::new(
@@ -263,7 +343,8 @@ impl View for ButtonIcon {
     }
 
     fn bounding_box(&mut self, styles: &Stylesheet) -> Rect {
-        let text = match self.button {
+        let button = ButtonLayout::current().map(self.button);
+        let text = match button {
             Key::A => "A",
             Key::B => "B",
             Key::X => "X",
@@ -283,25 +364,21 @@ impl View for ButtonIcon {
             Key::VolDown => "VOL-",
             Key::VolUp => "VOL+",
             Key::LidClose => "LID",
+            Key::Headphones => "PHONES",
             Key::Unknown => unimplemented!("unknown button"),
         };
+        let text = face_label(button, styles.button_icons).unwrap_or(text);
 
-        let w = match self.button {
-            Key::A
-            | Key::B
-            | Key::X
-            | Key::Y
-            | Key::L
-            | Key::L2
-            | Key::R
-            | Key::R2
-            | Key::Up
-            | Key::Right
-            | Key::Down
-            | Key::Left => Self::diameter(styles),
+        let w = match button {
+            Key::A | Key::B | Key::X | Key::Y if styles.button_icons != ButtonIconStyle::Text => {
+                Self::diameter(styles)
+            }
+            Key::L | Key::L2 | Key::R | Key::R2 | Key::Up | Key::Right | Key::Down | Key::Left => {
+                Self::diameter(styles)
+            }
             _ => {
                 let text_style = FontTextStyleBuilder::new(styles.ui_font.font())
-                    .font_fallback(styles.cjk_font.font())
+                    .font_fallbacks(styles.fallbacks())
                     .font_size(Self::diameter(styles) * 3 / 4)
                     .text_color(styles.background_color)
                     .build();
@@ -0,0 +1,256 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use embedded_graphics::Drawable;
+use embedded_graphics::prelude::Size;
+use embedded_graphics::primitives::{CornerRadii, Primitive, PrimitiveStyle, RoundedRectangle};
+use tokio::sync::mpsc::Sender;
+
+use crate::command::{Command, Value};
+use crate::geom::{Alignment, Point, Rect};
+use crate::locale::Locale;
+use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use crate::resources::Resources;
+use crate::stylesheet::{Stylesheet, StylesheetColor};
+use crate::view::{ButtonHint, ButtonIcon, Label, Row, ScrollList, View};
+
+/// A modal that asks to confirm an action, or to pick one of a few choices. Pressing A bubbles
+/// `Command::ValueChanged(0, Value::Int(choice))` and then `Command::CloseView`, while B only
+/// bubbles `Command::CloseView`. The dialog traps all key events while it's open. The message is
+/// shown one line per `\n`.
+#[derive(Debug)]
+pub struct Dialog {
+    rect: Rect,
+    title: Label<String>,
+    message: Vec<Label<String>>,
+    choices: Option<ScrollList>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl Dialog {
+    /// Creates a dialog centered in `rect`. Without `choices`, confirming picks choice 0.
+    pub fn new(
+        rect: Rect,
+        res: Resources,
+        title: String,
+        message: Option<String>,
+        choices: Vec<String>,
+    ) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let line_height = styles.ui_font.size;
+        let entry_height = styles.list_entry_height();
+        let width = (w - 24) * 2 / 3;
+        // Long lists of choices scroll instead of overflowing the screen
+        let choices_height = (choices.len() as u32 * entry_height).min(h / 2);
+
+        let message: Vec<String> = message
+            .iter()
+            .flat_map(|message| message.lines())
+            .map(str::to_string)
+            .collect();
+
+        let mut height = line_height + 8 + ButtonIcon::diameter(&styles);
+        height += message.len() as u32 * (line_height + 8);
+        if !choices.is_empty() {
+            height += choices_height + 8;
+        }
+
+        let left = x + (w - width) as i32 / 2;
+        let rect = Rect::new(
+            left - 24,
+            y + (h as i32 - height as i32) / 2 - 12,
+            width + 48,
+            height + 24,
+        );
+        let mut top = rect.y + 12;
+
+        let title = Label::new(Point::new(left, top), title, Alignment::Left, Some(width));
+        top += (line_height + 8) as i32;
+
+        let message = message
+            .into_iter()
+            .map(|line| {
+                let label = Label::new(Point::new(left, top), line, Alignment::Left, Some(width));
+                top += (line_height + 8) as i32;
+                label
+            })
+            .collect();
+
+        let confirm = if choices.is_empty() {
+            "button-confirm"
+        } else {
+            "button-select"
+        };
+        let choices = (!choices.is_empty()).then(|| {
+            let mut list = ScrollList::new(
+                Rect::new(left, top, width, choices_height),
+                choices,
+                Alignment::Left,
+                entry_height,
+            );
+            list.set_background_color(Some(StylesheetColor::BackgroundHighlightBlend));
+            top += (choices_height + 8) as i32;
+            list
+        });
+
+        let button_hints = Row::new(
+            Point::new(left + width as i32, top),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t(confirm),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        Self {
+            rect,
+            title,
+            message,
+            choices,
+            button_hints,
+            dirty: true,
+        }
+    }
+
+    /// Creates a dialog asking to confirm a destructive action.
+    pub fn confirm(rect: Rect, res: Resources, title: String, message: Option<String>) -> Self {
+        Self::new(rect, res, title, message, Vec::new())
+    }
+
+    /// Replaces the text of the A and B button hints.
+    pub fn set_button_text(&mut self, confirm: String, cancel: String) -> &mut Self {
+        if let Some(hint) = self.button_hints.get_mut(0) {
+            hint.set_text(confirm);
+        }
+        if let Some(hint) = self.button_hints.get_mut(1) {
+            hint.set_text(cancel);
+        }
+        self.dirty = true;
+        self
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Dialog {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            RoundedRectangle::new(
+                self.rect.into(),
+                CornerRadii::new(Size::new_equal((styles.ui_font.size + 8) / 2)),
+            )
+            .into_styled(PrimitiveStyle::with_fill(
+                StylesheetColor::BackgroundHighlightBlend.to_color(styles),
+            ))
+            .draw(display)?;
+            for child in self.children_mut() {
+                child.set_should_draw();
+            }
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.title.should_draw() && self.title.draw(display, styles)?;
+        for line in &mut self.message {
+            drawn |= line.should_draw() && line.draw(display, styles)?;
+        }
+        if let Some(choices) = self.choices.as_mut() {
+            drawn |= choices.should_draw() && choices.draw(display, styles)?;
+        }
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.title.should_draw()
+            || self.message.iter().any(View::should_draw)
+            || self.choices.as_ref().is_some_and(View::should_draw)
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                let choice = self.choices.as_ref().map_or(0, ScrollList::selected);
+                bubble.push_back(Command::ValueChanged(0, Value::Int(choice as i32)));
+                bubble.push_back(Command::CloseView);
+            }
+            KeyEvent::Pressed(Key::B) => {
+                bubble.push_back(Command::CloseView);
+            }
+            _ => {
+                if let Some(choices) = self.choices.as_mut() {
+                    choices.handle_key_event(event, commands, bubble).await?;
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        let mut children: Vec<&dyn View> = vec![&self.title];
+        for line in &self.message {
+            children.push(line);
+        }
+        if let Some(choices) = self.choices.as_ref() {
+            children.push(choices);
+        }
+        children.push(&self.button_hints);
+        children
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        let mut children: Vec<&mut dyn View> = vec![&mut self.title];
+        for line in &mut self.message {
+            children.push(line);
+        }
+        if let Some(choices) = self.choices.as_mut() {
+            children.push(choices);
+        }
+        children.push(&mut self.button_hints);
+        children
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::Result;
+use embedded_graphics::Drawable;
+use embedded_graphics::prelude::{AngleUnit, Size};
+use embedded_graphics::primitives::{
+    Arc, Circle, CornerRadii, Primitive, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle,
+    RoundedRectangle, StrokeAlignment, Triangle,
+};
+
+use crate::geom::{Point, Rect};
+use crate::platform::{DefaultPlatform, Platform};
+use crate::stylesheet::Stylesheet;
+
+/// Small icons drawn next to text, in toasts and the status bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Icon {
+    BatteryLow,
+    Wifi,
+    Volume,
+    Achievement,
+}
+
+impl Icon {
+    /// Draws the icon with primitives inside `rect`, so that it scales with the font size.
+    pub fn draw(
+        self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+        rect: Rect,
+    ) -> Result<()> {
+        let Rect { x, y, w, h } = rect;
+        let stroke = (w * 3 / 28).max(1);
+        let stroke_style = PrimitiveStyleBuilder::new()
+            .stroke_color(styles.foreground_color)
+            .stroke_alignment(StrokeAlignment::Inside)
+            .stroke_width(stroke)
+            .build();
+        let fill_style = PrimitiveStyle::with_fill(styles.foreground_color);
+
+        match self {
+            Icon::BatteryLow => {
+                let body_h = h * 3 / 5;
+                let body_y = y + (h - body_h) as i32 / 2;
+                RoundedRectangle::new(
+                    Rectangle::new(
+                        Point::new(x, body_y).into(),
+                        Size::new(w - stroke * 2, body_h),
+                    ),
+                    CornerRadii::new(Size::new_equal(stroke * 2)),
+                )
+                .into_styled(stroke_style)
+                .draw(display)?;
+                // Terminal
+                Rectangle::new(
+                    Point::new(x + (w - stroke * 2) as i32, body_y + body_h as i32 / 4).into(),
+                    Size::new(stroke * 2, body_h / 2),
+                )
+                .into_styled(fill_style)
+                .draw(display)?;
+                // Nearly empty charge
+                Rectangle::new(
+                    Point::new(x + stroke as i32 * 2, body_y + stroke as i32 * 2).into(),
+                    Size::new(stroke, body_h - stroke * 4),
+                )
+                .into_styled(PrimitiveStyle::with_fill(styles.button_a_color))
+                .draw(display)?;
+            }
+            Icon::Wifi => {
+                let center = Point::new(x + w as i32 / 2, y + h as i32 - stroke as i32 * 2);
+                let arc_style = PrimitiveStyle::with_stroke(styles.foreground_color, stroke);
+                for i in 1..=3 {
+                    let diameter = w * i * 2 / 3;
+                    Arc::with_center(center.into(), diameter, (-135.0f32).deg(), 90.0f32.deg())
+                        .into_styled(arc_style)
+                        .draw(display)?;
+                }
+                Circle::with_center(center.into(), stroke * 2)
+                    .into_styled(fill_style)
+                    .draw(display)?;
+            }
+            Icon::Volume => {
+                let cy = y + h as i32 / 2;
+                // Speaker
+                Rectangle::new(
+                    Point::new(x, cy - h as i32 / 6).into(),
+                    Size::new(w / 4, h / 3),
+                )
+                .into_styled(fill_style)
+                .draw(display)?;
+                Triangle::new(
+                    Point::new(x + w as i32 / 8, cy).into(),
+                    Point::new(x + w as i32 / 2, y + h as i32 / 8).into(),
+                    Point::new(x + w as i32 / 2, y + h as i32 * 7 / 8).into(),
+                )
+                .into_styled(fill_style)
+                .draw(display)?;
+                // Sound waves
+                let center = Point::new(x + w as i32 / 2, cy);
+                let arc_style = PrimitiveStyle::with_stroke(styles.foreground_color, stroke);
+                for i in 1..=2 {
+                    let diameter = w * i * 2 / 3;
+                    Arc::with_center(center.into(), diameter, (-45.0f32).deg(), 90.0f32.deg())
+                        .into_styled(arc_style)
+                        .draw(display)?;
+                }
+            }
+            Icon::Achievement => {
+                let diameter = w * 3 / 5;
+                let center = Point::new(x + w as i32 / 2, y + (h - diameter / 2) as i32);
+                // Ribbon
+                Triangle::new(
+                    Point::new(x + w as i32 / 5, y).into(),
+                    Point::new(x + w as i32 * 4 / 5, y).into(),
+                    Point::new(center.x, center.y).into(),
+                )
+                .into_styled(fill_style)
+                .draw(display)?;
+                Circle::with_center(center.into(), diameter)
+                    .into_styled(
+                        PrimitiveStyleBuilder::new()
+                            .fill_color(styles.button_y_color)
+                            .stroke_color(styles.foreground_color)
+                            .stroke_width(stroke)
+                            .build(),
+                    )
+                    .draw(display)?;
+            }
+        }
+
+        Ok(())
+    }
+}
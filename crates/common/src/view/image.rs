@@ -1,25 +1,28 @@
 use std::collections::VecDeque;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use embedded_graphics::Drawable;
 use embedded_graphics::image::ImageRaw;
 use image::{RgbaImage, imageops};
-use log::{error, trace};
+use log::{error, trace, warn};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::Sender;
+use tokio::sync::oneshot::{self, error::TryRecvError};
 
 use crate::command::Command;
 use crate::display::Display;
 use crate::display::color::Color;
 use crate::display::image::round;
+use crate::display::image_cache::{self, CacheKey};
 use crate::geom::{Alignment, Point, Rect};
 use crate::platform::{DefaultPlatform, KeyEvent, Platform};
 use crate::stylesheet::Stylesheet;
 use crate::view::View;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ImageMode {
     /// Don't scale the image
     Raw,
@@ -29,12 +32,44 @@ pub enum ImageMode {
     Contain,
 }
 
+/// Where image views ask for a redraw once an image is decoded. Apps without one draw their
+/// loading images every frame until they're decoded.
+static REDRAW: OnceLock<Sender<Command>> = OnceLock::new();
+
+/// Makes image views send `Command::Redraw` to the app once an image is decoded, so that they
+/// don't need to be drawn while it's decoding.
+pub fn set_redraw_sender(commands: Sender<Command>) {
+    REDRAW.set(commands).ok();
+}
+
+/// Decoding state of an image. Images are decoded on a blocking task, so that drawing
+/// doesn't wait for them.
+#[derive(Debug, Default)]
+enum ImageState {
+    #[default]
+    Unloaded,
+    Loading(oneshot::Receiver<Option<RgbaImage>>),
+    /// The decoded image, or `None` if it failed to load. Images that failed to load aren't
+    /// decoded again until the path changes.
+    Loaded(Option<RgbaImage>),
+}
+
+impl Clone for ImageState {
+    fn clone(&self) -> Self {
+        match self {
+            // A decode in progress can't be shared, so the clone decodes the image again
+            ImageState::Unloaded | ImageState::Loading(_) => ImageState::Unloaded,
+            ImageState::Loaded(image) => ImageState::Loaded(image.clone()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Image {
     rect: Rect,
     path: Option<PathBuf>,
     #[serde(skip)]
-    image: Option<RgbaImage>,
+    state: ImageState,
     mode: ImageMode,
     border_radius: u32,
     alignment: Alignment,
@@ -46,7 +81,7 @@ impl Image {
         Self {
             rect,
             path: Some(path),
-            image: None,
+            state: ImageState::Unloaded,
             mode,
             border_radius: 0,
             alignment: Alignment::Left,
@@ -64,7 +99,7 @@ impl Image {
         Self {
             rect,
             path: None,
-            image: None,
+            state: ImageState::Unloaded,
             mode,
             border_radius: 0,
             alignment: Alignment::Left,
@@ -74,7 +109,7 @@ impl Image {
 
     pub fn set_path(&mut self, path: Option<PathBuf>) -> &mut Self {
         if path != self.path {
-            self.image = None;
+            self.state = ImageState::Unloaded;
             self.dirty = true;
             self.path = path;
         }
@@ -86,61 +121,117 @@ impl Image {
         self
     }
 
-    fn image(
-        &self,
-        path: &Path,
-        rect: Rect,
-        mode: ImageMode,
-        border_radius: u32,
-    ) -> Option<RgbaImage> {
-        let image = ::image::open(path)
-            .map_err(|e| error!("Failed to load image at {}: {}", path.display(), e))
-            .ok()?;
-        let mut image = match mode {
-            ImageMode::Raw => image.to_rgba8(),
-            ImageMode::Cover => {
-                if image.width() == rect.w && image.height() == rect.h {
-                    image.to_rgba8()
-                } else {
-                    imageops::resize(&image, rect.w, rect.h, imageops::FilterType::Lanczos3)
-                }
+    /// Returns true while the image is being decoded.
+    pub fn is_loading(&self) -> bool {
+        matches!(self.state, ImageState::Loading(_))
+    }
+
+    /// Starts decoding the image on a blocking task, which asks for a redraw once it's done.
+    /// Outside of a tokio runtime, the image is decoded immediately instead.
+    fn load(&self, path: PathBuf) -> ImageState {
+        let (rect, mode, border_radius, alignment) =
+            (self.rect, self.mode, self.border_radius, self.alignment);
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                let (tx, rx) = oneshot::channel();
+                handle.spawn_blocking(move || {
+                    let image = decode(&path, rect, mode, border_radius, alignment);
+                    if tx.send(image).is_ok()
+                        && let Some(commands) = REDRAW.get()
+                    {
+                        commands.blocking_send(Command::Redraw).ok();
+                    }
+                });
+                ImageState::Loading(rx)
             }
-            ImageMode::Contain => {
-                if image.width() == rect.w && image.height() == rect.h {
-                    image.to_rgba8()
-                } else {
-                    let new_height = rect.h.min(rect.w * image.height() / image.width());
-                    let new_width = rect.w.min(rect.h * image.width() / image.height());
-                    imageops::resize(
-                        &image,
-                        new_width,
-                        new_height,
-                        imageops::FilterType::Lanczos3,
-                    )
-                }
+            Err(_) => ImageState::Loaded(decode(&path, rect, mode, border_radius, alignment)),
+        }
+    }
+
+    /// Starts decoding the image if it isn't yet, and takes it once it's decoded.
+    fn poll(&mut self) {
+        if let ImageState::Unloaded = self.state
+            && let Some(ref path) = self.path
+        {
+            self.state = self.load(path.clone());
+        }
+
+        if let ImageState::Loading(ref mut rx) = self.state {
+            match rx.try_recv() {
+                Ok(image) => self.state = ImageState::Loaded(image),
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Closed) => self.state = ImageState::Loaded(None),
             }
-        };
-        let (w, h) = image.dimensions();
-        if border_radius != 0 {
-            let border_radius = border_radius.min(w / 2).min(h / 2);
-            round(&mut image, border_radius);
         }
-        let image = if w != rect.w || h != rect.h {
-            let mut bg = RgbaImage::new(rect.w, rect.h);
-            let x = match self.alignment {
-                Alignment::Left => 0,
-                Alignment::Center => rect.w.saturating_sub(w) / 2,
-                Alignment::Right => rect.w.saturating_sub(w),
-            };
-            // vertical align top
-            imageops::overlay(&mut bg, &image, x as i64, 0);
-            bg
-        } else {
-            image
+    }
+}
+
+/// Decodes and scales an image to the rect, using the image cache.
+fn decode(
+    path: &Path,
+    rect: Rect,
+    mode: ImageMode,
+    border_radius: u32,
+    alignment: Alignment,
+) -> Option<RgbaImage> {
+    let key = CacheKey::new(path, (rect.w, rect.h, mode, border_radius, alignment));
+    if let Some(image) = key.and_then(image_cache::load) {
+        return Some(image);
+    }
+
+    let image = ::image::open(path)
+        .map_err(|e| error!("Failed to load image at {}: {}", path.display(), e))
+        .ok()?;
+    let mut image = match mode {
+        ImageMode::Raw => image.to_rgba8(),
+        ImageMode::Cover => {
+            if image.width() == rect.w && image.height() == rect.h {
+                image.to_rgba8()
+            } else {
+                imageops::resize(&image, rect.w, rect.h, imageops::FilterType::Lanczos3)
+            }
+        }
+        ImageMode::Contain => {
+            if image.width() == rect.w && image.height() == rect.h {
+                image.to_rgba8()
+            } else {
+                let new_height = rect.h.min(rect.w * image.height() / image.width());
+                let new_width = rect.w.min(rect.h * image.width() / image.height());
+                imageops::resize(
+                    &image,
+                    new_width,
+                    new_height,
+                    imageops::FilterType::Lanczos3,
+                )
+            }
+        }
+    };
+    let (w, h) = image.dimensions();
+    if border_radius != 0 {
+        let border_radius = border_radius.min(w / 2).min(h / 2);
+        round(&mut image, border_radius);
+    }
+    let image = if w != rect.w || h != rect.h {
+        let mut bg = RgbaImage::new(rect.w, rect.h);
+        let x = match alignment {
+            Alignment::Left => 0,
+            Alignment::Center => rect.w.saturating_sub(w) / 2,
+            Alignment::Right => rect.w.saturating_sub(w),
         };
+        // vertical align top
+        imageops::overlay(&mut bg, &image, x as i64, 0);
+        bg
+    } else {
+        image
+    };
 
-        Some(image)
+    if let Some(key) = key
+        && let Err(e) = image_cache::save(key, &image)
+    {
+        warn!("Failed to cache image at {}: {}", path.display(), e);
     }
+
+    Some(image)
 }
 
 #[async_trait(?Send)]
@@ -150,24 +241,20 @@ impl View for Image {
         display: &mut <DefaultPlatform as Platform>::Display,
         _styles: &Stylesheet,
     ) -> Result<bool> {
-        let image_loaded = if self.image.is_none()
-            && let Some(ref path) = self.path
-        {
-            self.image = self.image(path, self.rect, self.mode, self.border_radius);
-            self.image.is_some()
-        } else {
-            self.image.is_some()
-        };
+        self.poll();
 
+        // The background is left as a placeholder while the image is decoded. It's drawn again
+        // on the redraw requested once the decode completes, or every frame if there's no app to
+        // request it from.
         display.load(self.rect)?;
-        if let Some(ref image) = self.image {
+        if let ImageState::Loaded(Some(ref image)) = self.state {
             let image: ImageRaw<'_, Color> = ImageRaw::new(image, self.rect.w);
             let image = embedded_graphics::image::Image::new(&image, self.rect.top_left().into());
             trace!("drawing image: {:?}", self.rect);
             image.draw(display)?;
         }
 
-        self.dirty = !image_loaded && self.path.is_some();
+        self.dirty = self.is_loading() && REDRAW.get().is_none();
         Ok(true)
     }
 
@@ -206,3 +293,41 @@ impl View for Image {
         self.dirty = true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn missing_image() -> Image {
+        let path = std::env::temp_dir().join(format!("allium-missing-{}.png", std::process::id()));
+        Image::new(Rect::new(0, 0, 10, 10), path, ImageMode::Contain)
+    }
+
+    #[test]
+    fn test_failed_decode_is_cached() {
+        let mut image = missing_image();
+        image.poll();
+        assert!(matches!(image.state, ImageState::Loaded(None)));
+
+        // Redrawing doesn't decode it again
+        image.set_should_draw();
+        image.poll();
+        assert!(matches!(image.state, ImageState::Loaded(None)));
+
+        image.set_path(Some(PathBuf::from("other.png")));
+        assert!(matches!(image.state, ImageState::Unloaded));
+    }
+
+    #[tokio::test]
+    async fn test_decode_in_background() {
+        let mut image = missing_image();
+        image.poll();
+        assert!(image.is_loading());
+
+        while image.is_loading() {
+            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+            image.poll();
+        }
+        assert!(matches!(image.state, ImageState::Loaded(None)));
+    }
+}
@@ -93,7 +93,7 @@ impl View for ColorPicker {
         .draw(display)?;
 
         let text_style = FontTextStyleBuilder::new(styles.ui_font.font())
-            .font_fallback(styles.cjk_font.font())
+            .font_fallbacks(styles.fallbacks())
             .font_size(styles.ui_font.size)
             .text_color(styles.foreground_color)
             .build();
@@ -286,7 +286,7 @@ impl View for ColorPicker {
 
     fn bounding_box(&mut self, styles: &Stylesheet) -> Rect {
         let text_style: FontTextStyle<Color> = FontTextStyleBuilder::new(styles.ui_font.font())
-            .font_fallback(styles.cjk_font.font())
+            .font_fallbacks(styles.fallbacks())
             .font_size(styles.ui_font.size)
             .draw_background()
             .build();
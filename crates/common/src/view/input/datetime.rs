@@ -75,7 +75,7 @@ impl View for DateTime {
         let edit_index = self.edit_state.as_ref().map(|s| s.selected);
 
         let text_style = FontTextStyleBuilder::new(styles.ui_font.font())
-            .font_fallback(styles.cjk_font.font())
+            .font_fallbacks(styles.fallbacks())
             .font_size(styles.ui_font.size)
             .text_color(styles.foreground_color)
             .build();
@@ -272,7 +272,7 @@ impl View for DateTime {
 
     fn bounding_box(&mut self, styles: &Stylesheet) -> Rect {
         let text_style: FontTextStyle<Color> = FontTextStyleBuilder::new(styles.ui_font.font())
-            .font_fallback(styles.cjk_font.font())
+            .font_fallbacks(styles.fallbacks())
             .font_size(styles.ui_font.size)
             .draw_background()
             .build();
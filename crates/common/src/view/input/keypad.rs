@@ -0,0 +1,187 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use embedded_graphics::{
+    Drawable,
+    prelude::Size,
+    primitives::{Primitive, PrimitiveStyleBuilder, RoundedRectangle},
+    text::Text,
+};
+use tokio::sync::mpsc::Sender;
+
+use crate::command::{Command, Value};
+use crate::display::{Display, font::FontTextStyleBuilder};
+use crate::geom::{Alignment, Point, Rect};
+use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use crate::stylesheet::Stylesheet;
+use crate::view::View;
+
+/// A grid of keys navigated with the d-pad. Pressing A on a key bubbles a
+/// `ValueChanged` with the index and label of the key.
+#[derive(Debug, Clone)]
+pub struct Keypad {
+    rect: Rect,
+    keys: Vec<String>,
+    columns: usize,
+    selected: usize,
+    dirty: bool,
+}
+
+impl Keypad {
+    pub fn new(rect: Rect, keys: Vec<String>, columns: usize) -> Self {
+        Self {
+            rect,
+            keys,
+            columns,
+            selected: 0,
+            dirty: true,
+        }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    fn rows(&self) -> usize {
+        self.keys.len().div_ceil(self.columns)
+    }
+
+    /// Moves the cursor by the given number of columns and rows, wrapping around.
+    fn move_cursor(&mut self, dx: isize, dy: isize) {
+        let columns = self.columns as isize;
+        let rows = self.rows() as isize;
+        let mut x = self.selected as isize % columns;
+        let mut y = self.selected as isize / columns;
+        loop {
+            x = (x + dx).rem_euclid(columns);
+            y = (y + dy).rem_euclid(rows);
+            // Skip the empty cells of an incomplete last row
+            if ((y * columns + x) as usize) < self.keys.len() {
+                break;
+            }
+        }
+        self.selected = (y * columns + x) as usize;
+        self.dirty = true;
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Keypad {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if !self.dirty {
+            return Ok(false);
+        }
+
+        display.load(self.rect)?;
+
+        let text_style = FontTextStyleBuilder::new(styles.ui_font.font())
+            .font_fallbacks(styles.fallbacks())
+            .font_size(styles.ui_font.size)
+            .text_color(styles.foreground_color)
+            .build();
+
+        let key_style = PrimitiveStyleBuilder::new()
+            .stroke_color(styles.foreground_color)
+            .stroke_width(1)
+            .build();
+
+        let selected_key_style = PrimitiveStyleBuilder::new()
+            .fill_color(styles.highlight_color)
+            .stroke_color(styles.highlight_color)
+            .stroke_width(1)
+            .build();
+
+        let key_w = self.rect.w / self.columns as u32;
+        let key_h = self.rect.h / self.rows() as u32;
+
+        for (i, key) in self.keys.iter().enumerate() {
+            let x = self.rect.x + (i % self.columns) as i32 * key_w as i32;
+            let y = self.rect.y + (i / self.columns) as i32 * key_h as i32;
+
+            RoundedRectangle::with_equal_corners(
+                Rect::new(x + 4, y + 4, key_w - 8, key_h - 8).into(),
+                Size::new(12, 12),
+            )
+            .into_styled(if i == self.selected {
+                selected_key_style
+            } else {
+                key_style
+            })
+            .draw(display)?;
+
+            Text::with_alignment(
+                key,
+                Point::new(
+                    x + key_w as i32 / 2,
+                    y + key_h as i32 / 2 - styles.ui_font.size as i32 / 2,
+                )
+                .into(),
+                text_style.clone(),
+                Alignment::Center.into(),
+            )
+            .draw(display)?;
+        }
+
+        self.dirty = false;
+        Ok(true)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        _commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::Up) | KeyEvent::Autorepeat(Key::Up) => self.move_cursor(0, -1),
+            KeyEvent::Pressed(Key::Down) | KeyEvent::Autorepeat(Key::Down) => {
+                self.move_cursor(0, 1)
+            }
+            KeyEvent::Pressed(Key::Left) | KeyEvent::Autorepeat(Key::Left) => {
+                self.move_cursor(-1, 0)
+            }
+            KeyEvent::Pressed(Key::Right) | KeyEvent::Autorepeat(Key::Right) => {
+                self.move_cursor(1, 0)
+            }
+            KeyEvent::Pressed(Key::A) | KeyEvent::Autorepeat(Key::A) => {
+                bubble.push_back(Command::ValueChanged(
+                    self.selected,
+                    Value::String(self.keys[self.selected].clone()),
+                ));
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, point: Point) {
+        self.rect.x = point.x;
+        self.rect.y = point.y;
+        self.dirty = true;
+    }
+}
@@ -2,6 +2,7 @@ pub mod button;
 pub mod color_picker;
 pub mod datetime;
 pub mod keyboard;
+pub mod keypad;
 pub mod number;
 pub mod percentage;
 pub mod select;
@@ -20,7 +20,12 @@ use crate::view::View;
 #[derive(Debug, Clone)]
 struct Scrolling {
     offset: usize,
+    /// Time since the text last started scrolling from the start.
     dt: Duration,
+    /// Time to scroll by one character, taken from the stylesheet when the label is drawn.
+    interval: Option<Duration>,
+    /// Time to wait before scrolling, taken from the stylesheet when the label is drawn.
+    pause: Duration,
 }
 
 #[derive(Debug, Clone)]
@@ -40,8 +45,8 @@ where
     dirty: bool,
 }
 
-const SCROLL_DELAY: Duration = Duration::from_millis(1000);
-const SCROLL_INTERVAL: Duration = Duration::from_micros(166_667);
+/// Spaces between the end of scrolling text and its start coming around again.
+const SCROLL_GAP: &str = "     ";
 
 impl<S> Label<S>
 where
@@ -66,7 +71,9 @@ where
         if enabled && self.width.is_some() {
             self.scrolling = Some(Scrolling {
                 offset: 0,
-                dt: Duration::from_millis(0),
+                dt: Duration::ZERO,
+                interval: None,
+                pause: Duration::ZERO,
             });
             self.truncated_text = None;
         } else {
@@ -97,7 +104,12 @@ where
     }
 
     pub fn font_size(&mut self, font_size: f32) -> &mut Self {
-        self.font_size = font_size;
+        if self.font_size != font_size {
+            self.font_size = font_size;
+            self.truncated_text = None;
+            self.rect = None;
+            self.dirty = true;
+        }
         self
     }
 
@@ -109,7 +121,7 @@ where
         self.dirty = true;
 
         let text_style = FontTextStyleBuilder::<Color>::new(styles.ui_font.font())
-            .font_fallback(styles.cjk_font.font())
+            .font_fallbacks(styles.fallbacks())
             .font_size((styles.ui_font.size as f32 * self.font_size) as u32)
             .build();
 
@@ -128,7 +140,7 @@ where
                     .text
                     .as_ref()
                     .chars()
-                    .chain("     ".chars())
+                    .chain(SCROLL_GAP.chars())
                     .chain(self.text.as_ref().chars().take(scrolling.offset))
                     .skip(scrolling.offset)
                     .collect::<String>();
@@ -203,19 +215,25 @@ where
             return;
         }
 
-        scrolling.dt += dt;
+        let Some(interval) = scrolling.interval else {
+            trace!("haven't been drawn yet, skip for now");
+            return;
+        };
 
-        let offset = scrolling.offset;
-        while scrolling.dt > SCROLL_DELAY {
-            scrolling.dt -= SCROLL_INTERVAL;
-            scrolling.offset += 1;
-        }
+        scrolling.dt += dt;
 
-        if offset >= self.text.as_ref().chars().count() + 5 {
-            scrolling.offset = 0;
+        // Pause at the start, and again each time the text has scrolled all the way around
+        let Some(elapsed) = scrolling.dt.checked_sub(scrolling.pause) else {
+            return;
+        };
+        let mut offset = (elapsed.as_micros() / interval.as_micros().max(1)) as usize;
+        if offset >= self.text.as_ref().chars().count() + SCROLL_GAP.len() {
+            scrolling.dt = Duration::ZERO;
+            offset = 0;
         }
 
         if scrolling.offset != offset {
+            scrolling.offset = offset;
             self.truncated_text = None;
             self.set_should_draw();
         }
@@ -227,11 +245,23 @@ where
         styles: &Stylesheet,
     ) -> Result<bool> {
         let text_style = FontTextStyleBuilder::new(styles.ui_font.font())
-            .font_fallback(styles.cjk_font.font())
+            .font_fallbacks(styles.fallbacks())
             .text_color(self.color.to_color(styles))
             .font_size((styles.ui_font.size as f32 * self.font_size) as u32)
             .build();
 
+        if let Some(scrolling) = self.scrolling.as_mut() {
+            match styles.marquee_interval() {
+                Some(interval) => {
+                    scrolling.interval = Some(interval);
+                    scrolling.pause = styles.marquee_pause();
+                }
+                None => {
+                    self.scroll(false);
+                }
+            }
+        }
+
         if self.truncated_text.is_none() {
             self.layout(styles);
         }
@@ -276,7 +306,7 @@ where
 
     fn bounding_box(&mut self, styles: &Stylesheet) -> Rect {
         let text_style = FontTextStyleBuilder::<Color>::new(styles.ui_font.font())
-            .font_fallback(styles.cjk_font.font())
+            .font_fallbacks(styles.fallbacks())
             .font_size((styles.ui_font.size as f32 * self.font_size) as u32)
             .build();
 
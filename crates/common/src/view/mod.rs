@@ -1,29 +1,40 @@
+mod animation;
 mod battery_indicator;
 mod button_hint;
 mod button_icon;
 mod clock;
+mod dialog;
+mod icon;
 mod image;
 mod input;
 mod label;
 mod list;
 mod null;
+mod pdf_viewer;
 mod row;
 mod scroll_list;
 mod settings_list;
+mod status_bar;
+mod text_reader;
+mod toast;
 
 use std::collections::VecDeque;
 use std::fmt;
 use std::time::Duration;
 
+pub use self::animation::{Easing, Lerp, Tween};
 pub use self::battery_indicator::BatteryIndicator;
 pub use self::button_hint::ButtonHint;
 pub use self::button_icon::ButtonIcon;
 pub use self::clock::Clock;
-pub use self::image::{Image, ImageMode};
+pub use self::dialog::Dialog;
+pub use self::icon::Icon;
+pub use self::image::{Image, ImageMode, set_redraw_sender};
 pub use self::input::button::Button;
 pub use self::input::color_picker::ColorPicker;
 pub use self::input::datetime::DateTime;
 pub use self::input::keyboard::Keyboard;
+pub use self::input::keypad::Keypad;
 pub use self::input::number::Number;
 pub use self::input::percentage::Percentage;
 pub use self::input::select::Select;
@@ -32,9 +43,13 @@ pub use self::input::toggle::Toggle;
 pub use self::label::Label;
 pub use self::list::List;
 pub use self::null::NullView;
+pub use self::pdf_viewer::PdfViewer;
 pub use self::row::Row;
 pub use self::scroll_list::ScrollList;
 pub use self::settings_list::SettingsList;
+pub use self::status_bar::{StatusBar, StatusIndicator};
+pub use self::text_reader::TextReader;
+pub use self::toast::{Toast, ToastPriority, Toasts};
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -42,6 +57,7 @@ use tokio::sync::mpsc::Sender;
 
 use crate::command::Command;
 use crate::geom::{Point, Rect};
+use crate::platform::touch::Gesture;
 use crate::platform::{DefaultPlatform, KeyEvent, Platform};
 use crate::stylesheet::Stylesheet;
 
@@ -75,6 +91,28 @@ pub trait View {
         bubble: &mut VecDeque<Command>,
     ) -> Result<bool>;
 
+    /// Handle a tap or swipe on the touchscreen. Returns true if the gesture was consumed. By
+    /// default, the gesture is passed to the children it started in.
+    async fn handle_gesture(
+        &mut self,
+        gesture: Gesture,
+        styles: &Stylesheet,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        let start = gesture.start();
+        for child in self.children_mut() {
+            if child.bounding_box(styles).contains(start)
+                && child
+                    .handle_gesture(gesture, styles, commands.clone(), bubble)
+                    .await?
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     /// Returns a list of references to the children of the view.
     fn children(&self) -> Vec<&dyn View>;
 
@@ -133,6 +171,18 @@ impl View for Box<dyn View> {
         (**self).handle_key_event(event, commands, bubble).await
     }
 
+    async fn handle_gesture(
+        &mut self,
+        gesture: Gesture,
+        styles: &Stylesheet,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        (**self)
+            .handle_gesture(gesture, styles, commands, bubble)
+            .await
+    }
+
     /// Returns a list of references to the children of the view.
     fn children(&self) -> Vec<&dyn View> {
         (**self).children()
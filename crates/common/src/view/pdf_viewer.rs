@@ -0,0 +1,511 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use embedded_graphics::Drawable;
+use embedded_graphics::image::ImageRaw;
+use embedded_graphics::primitives::{Primitive, PrimitiveStyle, Rectangle};
+use image::{RgbaImage, imageops};
+use lazy_static::lazy_static;
+use log::{error, trace};
+use tokio::sync::mpsc::Sender;
+use tokio::sync::oneshot::{self, error::TryRecvError};
+
+use crate::command::Command;
+use crate::database::Database;
+use crate::developer::is_installed;
+use crate::display::color::Color;
+use crate::geom::{Alignment, Point, Rect};
+use crate::locale::Locale;
+use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use crate::resources::Resources;
+use crate::stylesheet::Stylesheet;
+use crate::view::{ButtonHint, ButtonIcon, Label, Row, View};
+
+/// Zoom levels, as multiples of the width that fits the screen.
+const ZOOM_LEVELS: [f32; 4] = [1.0, 1.5, 2.0, 3.0];
+/// How far the d-pad pans the page, in pixels.
+const PAN_STEP: i32 = 48;
+/// How many rendered pages are kept, so that going back and forth doesn't render them again.
+const PAGE_CACHE_SIZE: usize = 4;
+
+lazy_static! {
+    static ref AVAILABLE: bool = is_installed("mutool");
+}
+
+/// A page at a zoom level.
+type PageKey = (usize, usize);
+
+/// Shows a PDF one page at a time, with pan and zoom. Pages are rendered with `mutool` on a
+/// blocking task, and the last few are cached. The page that was last read is saved in the
+/// database, like the position in a guide.
+#[derive(Debug)]
+pub struct PdfViewer {
+    rect: Rect,
+    res: Resources,
+    path: PathBuf,
+    /// Area the page is drawn in, above the button hints.
+    view: Rect,
+    page: usize,
+    page_count: usize,
+    zoom: usize,
+    /// Top-left of the visible area of the page. It's clamped to the page once it's rendered.
+    offset: Point,
+    /// Recently rendered pages, oldest first.
+    pages: VecDeque<(PageKey, RgbaImage)>,
+    /// The page being rendered, one at a time.
+    rendering: Option<(PageKey, oneshot::Receiver<Result<RgbaImage>>)>,
+    /// Whether the current page failed to render.
+    failed: bool,
+    page_label: Label<String>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl PdfViewer {
+    pub fn new(rect: Rect, res: Resources, path: PathBuf) -> Self {
+        let page_count = page_count(&path)
+            .map_err(|e| error!("failed to read manual {}: {}", path.display(), e))
+            .unwrap_or(1);
+        let page = res
+            .get::<Database>()
+            .get_guide_cursor(&path)
+            .map_err(|e| error!("failed to load manual page from database: {}", e))
+            .unwrap_or_default() as usize;
+        let page = page.min(page_count - 1);
+
+        let Rect { x, y, w, h } = rect;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let view = Rect::new(x, y, w, h - ButtonHint::bar_height(&styles));
+        let hints_y = y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8;
+
+        let page_label = Label::new(
+            Point::new(x + 12, hints_y),
+            String::new(),
+            Alignment::Left,
+            None,
+        );
+
+        let button_hints = Row::new(
+            Point::new(x + w as i32 - 12, hints_y),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::X,
+                    locale.t("manual-zoom-in"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::Y,
+                    locale.t("manual-zoom-out"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(locale);
+        drop(styles);
+
+        let mut viewer = Self {
+            rect,
+            res,
+            path,
+            view,
+            page,
+            page_count,
+            zoom: 0,
+            offset: Point::zero(),
+            pages: VecDeque::new(),
+            rendering: None,
+            failed: false,
+            page_label,
+            button_hints,
+            dirty: true,
+        };
+        viewer.update_page_label();
+        viewer
+    }
+
+    /// Whether PDFs can be shown, which needs `mutool`. Other files can always be opened.
+    pub fn can_open(path: &Path) -> bool {
+        *AVAILABLE
+            || !path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+    }
+
+    pub fn save_page(&self) {
+        self.res
+            .get::<Database>()
+            .update_guide_cursor(&self.path, self.page as u64)
+            .map_err(|e| error!("failed to update manual page to database: {}", e))
+            .ok();
+    }
+
+    fn set_page(&mut self, page: usize) {
+        self.page = page;
+        self.failed = false;
+        self.update_page_label();
+        self.dirty = true;
+    }
+
+    fn update_page_label(&mut self) {
+        self.page_label
+            .set_text(format!("{} / {}", self.page + 1, self.page_count));
+    }
+
+    /// Zooms in or out, keeping the center of the visible area in place.
+    fn set_zoom(&mut self, zoom: usize) {
+        if zoom == self.zoom {
+            return;
+        }
+        let ratio = ZOOM_LEVELS[zoom] / ZOOM_LEVELS[self.zoom];
+        let center = Point::new(
+            self.offset.x + self.view.w as i32 / 2,
+            self.offset.y + self.view.h as i32 / 2,
+        );
+        self.offset = Point::new(
+            (center.x as f32 * ratio) as i32 - self.view.w as i32 / 2,
+            (center.y as f32 * ratio) as i32 - self.view.h as i32 / 2,
+        );
+        self.zoom = zoom;
+        self.failed = false;
+        self.dirty = true;
+    }
+
+    fn key(&self) -> PageKey {
+        (self.page, self.zoom)
+    }
+
+    /// The current page at the current zoom, if it has been rendered.
+    fn image(&self) -> Option<&RgbaImage> {
+        let key = self.key();
+        self.pages
+            .iter()
+            .find_map(|(k, image)| (*k == key).then_some(image))
+    }
+
+    /// Starts rendering the current page, unless it's already rendered or another page is still
+    /// being rendered. Outside of a tokio runtime, the page is rendered immediately instead.
+    fn render(&mut self) {
+        if self.rendering.is_some() || self.failed || self.image().is_some() {
+            return;
+        }
+        let key = self.key();
+        let path = self.path.clone();
+        let width = (self.view.w as f32 * ZOOM_LEVELS[self.zoom]) as u32;
+        trace!("rendering page {} at width {}", self.page, width);
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                let (tx, rx) = oneshot::channel();
+                handle.spawn_blocking(move || {
+                    tx.send(render_page(&path, key.0, width)).ok();
+                });
+                self.rendering = Some((key, rx));
+            }
+            Err(_) => self.rendered(key, render_page(&path, key.0, width)),
+        }
+    }
+
+    /// Caches a rendered page, and redraws if it's the current one.
+    fn rendered(&mut self, key: PageKey, result: Result<RgbaImage>) {
+        match result {
+            Ok(image) => {
+                if self.pages.len() == PAGE_CACHE_SIZE {
+                    self.pages.pop_front();
+                }
+                self.pages.push_back((key, image));
+            }
+            Err(e) => {
+                error!("failed to render manual page: {}", e);
+                if key == self.key() {
+                    self.offset = Point::zero();
+                    self.failed = true;
+                }
+            }
+        }
+        if key == self.key() {
+            self.dirty = true;
+        }
+    }
+
+    /// Furthest the visible area can be moved on the current page.
+    fn max_offset(&self) -> Point {
+        self.image().map_or(Point::zero(), |image| {
+            max_offset(image.dimensions(), self.view)
+        })
+    }
+
+    /// Scrolls the page down, or up if `dy` is negative, moving on to the next or previous page
+    /// at its end.
+    fn scroll(&mut self, dy: i32) {
+        let max = self.max_offset();
+        if dy > 0 && self.offset.y >= max.y {
+            if self.page + 1 < self.page_count {
+                self.set_page(self.page + 1);
+                self.offset.y = 0;
+            }
+        } else if dy < 0 && self.offset.y <= 0 {
+            if self.page > 0 {
+                self.set_page(self.page - 1);
+                // Clamped to the bottom of the page once it's rendered
+                self.offset.y = i32::MAX;
+            }
+        } else {
+            self.offset.y = (self.offset.y + dy).clamp(0, max.y);
+            self.dirty = true;
+        }
+    }
+
+    /// Pans the page right, or left if `dx` is negative. Turns the page if it fits the width.
+    fn pan(&mut self, dx: i32) {
+        let max = self.max_offset();
+        if max.x == 0 {
+            if dx > 0 && self.page + 1 < self.page_count {
+                self.set_page(self.page + 1);
+                self.offset.y = 0;
+            } else if dx < 0 && self.page > 0 {
+                self.set_page(self.page - 1);
+                self.offset.y = 0;
+            }
+        } else {
+            self.offset.x = (self.offset.x + dx).clamp(0, max.x);
+            self.dirty = true;
+        }
+    }
+}
+
+/// Returns the number of pages in the PDF, as listed by `mutool info`.
+fn page_count(path: &Path) -> Result<usize> {
+    let output = process::Command::new("mutool")
+        .arg("info")
+        .arg(path)
+        .output()?;
+    parse_page_count(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| anyhow!("no page count in mutool output"))
+}
+
+fn parse_page_count(info: &str) -> Option<usize> {
+    info.lines()
+        .find_map(|line| line.strip_prefix("Pages:"))
+        .and_then(|count| count.trim().parse().ok())
+        .filter(|&count| count > 0)
+}
+
+/// Renders a page of the PDF to the given width, with `mutool draw`.
+fn render_page(path: &Path, page: usize, width: u32) -> Result<RgbaImage> {
+    let output = process::Command::new("mutool")
+        .args(["draw", "-q", "-F", "png", "-o", "-", "-w"])
+        .arg(width.to_string())
+        .arg(path)
+        .arg((page + 1).to_string())
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "mutool exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(image::load_from_memory(&output.stdout)?.to_rgba8())
+}
+
+fn max_offset((w, h): (u32, u32), view: Rect) -> Point {
+    Point::new(
+        w.saturating_sub(view.w) as i32,
+        h.saturating_sub(view.h) as i32,
+    )
+}
+
+/// Clamps the top-left of the visible area, so that it stays on the page.
+fn clamp_offset(offset: Point, size: (u32, u32), view: Rect) -> Point {
+    let max = max_offset(size, view);
+    Point::new(offset.x.clamp(0, max.x), offset.y.clamp(0, max.y))
+}
+
+#[async_trait(?Send)]
+impl View for PdfViewer {
+    fn update(&mut self, _dt: Duration) {
+        if let Some((key, rx)) = self.rendering.as_mut() {
+            let key = *key;
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.rendering = None;
+                    self.rendered(key, result);
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Closed) => {
+                    self.rendering = None;
+                    self.rendered(key, Err(anyhow!("rendering was interrupted")));
+                }
+            }
+        }
+        self.render();
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            if let Some(size) = self.image().map(RgbaImage::dimensions) {
+                self.offset = clamp_offset(self.offset, size, self.view);
+            }
+
+            Rectangle::from(self.rect)
+                .into_styled(PrimitiveStyle::with_fill(styles.background_color))
+                .draw(display)?;
+
+            if let Some(image) = self.image() {
+                let visible = imageops::crop_imm(
+                    image,
+                    self.offset.x as u32,
+                    self.offset.y as u32,
+                    self.view.w,
+                    self.view.h,
+                )
+                .to_image();
+                // Pages narrower or shorter than the screen are centered
+                let point = Point::new(
+                    self.view.x + self.view.w.saturating_sub(visible.width()) as i32 / 2,
+                    self.view.y + self.view.h.saturating_sub(visible.height()) as i32 / 2,
+                );
+                let raw: ImageRaw<'_, Color> = ImageRaw::new(&visible, visible.width());
+                embedded_graphics::image::Image::new(&raw, point.into()).draw(display)?;
+            } else {
+                let key = if self.failed {
+                    "manual-render-failed"
+                } else {
+                    "manual-rendering"
+                };
+                let mut label = Label::new(
+                    Point::new(
+                        self.view.x + self.view.w as i32 / 2,
+                        self.view.y + self.view.h as i32 / 2,
+                    ),
+                    self.res.get::<Locale>().t(key),
+                    Alignment::Center,
+                    Some(self.view.w - 24),
+                );
+                label.draw(display, styles)?;
+            }
+
+            self.page_label.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.page_label.should_draw() && self.page_label.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty || self.page_label.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        self.page_label.set_should_draw();
+        self.button_hints.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        _commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::Up) | KeyEvent::Autorepeat(Key::Up) => self.scroll(-PAN_STEP),
+            KeyEvent::Pressed(Key::Down) | KeyEvent::Autorepeat(Key::Down) => self.scroll(PAN_STEP),
+            KeyEvent::Pressed(Key::Left) | KeyEvent::Autorepeat(Key::Left) => self.pan(-PAN_STEP),
+            KeyEvent::Pressed(Key::Right) | KeyEvent::Autorepeat(Key::Right) => self.pan(PAN_STEP),
+            KeyEvent::Pressed(Key::L) | KeyEvent::Autorepeat(Key::L) => {
+                if self.page > 0 {
+                    self.set_page(self.page - 1);
+                    self.offset.y = 0;
+                }
+            }
+            KeyEvent::Pressed(Key::R) | KeyEvent::Autorepeat(Key::R) => {
+                if self.page + 1 < self.page_count {
+                    self.set_page(self.page + 1);
+                    self.offset.y = 0;
+                }
+            }
+            KeyEvent::Pressed(Key::X) => self.set_zoom((self.zoom + 1).min(ZOOM_LEVELS.len() - 1)),
+            KeyEvent::Pressed(Key::Y) => self.set_zoom(self.zoom.saturating_sub(1)),
+            KeyEvent::Pressed(Key::B) => {
+                self.save_page();
+                bubble.push_back(Command::CloseView);
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.page_label, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.page_label, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_page_count() {
+        let info = "PDF-1.4\nInfo object (1 0 R):\n<</Title(Manual)>>\nPages: 42\n\nRetrieving info from pages 1-42...\n";
+        assert_eq!(parse_page_count(info), Some(42));
+        assert_eq!(parse_page_count("Pages: 0\n"), None);
+        assert_eq!(parse_page_count("error: cannot open document\n"), None);
+    }
+
+    #[test]
+    fn test_clamp_offset() {
+        let view = Rect::new(0, 0, 640, 420);
+        assert_eq!(
+            clamp_offset(Point::new(-10, i32::MAX), (960, 1240), view),
+            Point::new(0, 820)
+        );
+        assert_eq!(
+            clamp_offset(Point::new(100, 100), (640, 300), view),
+            Point::new(0, 0)
+        );
+    }
+}
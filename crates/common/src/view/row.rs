@@ -9,6 +9,7 @@ use tokio::sync::mpsc::Sender;
 use crate::command::Command;
 use crate::display::Display;
 use crate::geom::{Alignment, Point, Rect};
+use crate::platform::touch::Gesture;
 use crate::platform::{DefaultPlatform, KeyEvent, Platform};
 use crate::stylesheet::Stylesheet;
 use crate::view::View;
@@ -87,6 +88,16 @@ where
         self.has_layout = false;
     }
 
+    /// Returns the index of the child at the point.
+    pub fn child_at(&mut self, point: Point, styles: &Stylesheet) -> Option<usize> {
+        if !self.has_layout {
+            self.layout(styles);
+        }
+        self.children
+            .iter_mut()
+            .position(|c| c.bounding_box(styles).contains(point))
+    }
+
     fn layout(&mut self, styles: &Stylesheet) {
         match self.alignment {
             Alignment::Left => self.layout_left(styles),
@@ -174,6 +185,23 @@ where
         Ok(false)
     }
 
+    async fn handle_gesture(
+        &mut self,
+        gesture: Gesture,
+        styles: &Stylesheet,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match self.child_at(gesture.start(), styles) {
+            Some(i) => {
+                self.children[i]
+                    .handle_gesture(gesture, styles, commands, bubble)
+                    .await
+            }
+            None => Ok(false),
+        }
+    }
+
     fn children(&self) -> Vec<&dyn View> {
         self.children.iter().map(|c| c as &dyn View).collect()
     }
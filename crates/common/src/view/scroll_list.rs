@@ -1,4 +1,5 @@
 use std::collections::VecDeque;
+use std::path::PathBuf;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -11,10 +12,13 @@ use embedded_graphics::primitives::{
 use tokio::sync::mpsc::Sender;
 
 use crate::display::Display;
+use crate::display::color::Color;
 use crate::geom::{Alignment, Point, Rect};
+use crate::platform::rumble::Rumble;
+use crate::platform::touch::Gesture;
 use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
 use crate::stylesheet::{Stylesheet, StylesheetColor};
-use crate::view::{Command, Label, View};
+use crate::view::{Command, Image, ImageMode, Label, View};
 
 /// A listing of selectable entries. Assumes that all entries have the same size.
 #[derive(Debug, Clone)]
@@ -22,8 +26,20 @@ pub struct ScrollList {
     rect: Rect,
     /// All entries.
     items: Vec<String>,
+    /// Whether each entry is greyed out.
+    disabled: Vec<bool>,
+    /// Whether each entry is a section header, which can't be selected.
+    headers: Vec<bool>,
+    /// Whether each entry is checked, if checkboxes are shown.
+    checked: Option<Vec<bool>>,
+    /// Icon of each entry. Icons are only shown in left-aligned lists.
+    icons: Vec<Option<PathBuf>>,
+    /// Accent color of each entry's text, instead of the foreground color.
+    colors: Vec<Option<Color>>,
     /// Visible entries.
     children: Vec<Label<String>>,
+    /// Icons of the visible entries, if any entry has an icon.
+    icon_views: Vec<Image>,
     alignment: Alignment,
     entry_height: u32,
     top: usize,
@@ -51,7 +67,13 @@ impl ScrollList {
         let mut this = Self {
             rect,
             items: Vec::new(),
+            disabled: Vec::new(),
+            headers: Vec::new(),
+            checked: None,
+            icons: Vec::new(),
+            colors: Vec::new(),
             children: Vec::new(),
+            icon_views: Vec::new(),
             alignment,
             entry_height,
             top: 0,
@@ -83,7 +105,15 @@ impl ScrollList {
     pub fn set_items(&mut self, items: Vec<String>, preserve_selection: bool) {
         if items.is_empty() {
             self.items = items;
+            self.disabled.clear();
+            self.headers.clear();
+            self.icons.clear();
+            self.colors.clear();
+            if let Some(checked) = self.checked.as_mut() {
+                checked.clear();
+            }
             self.children.clear();
+            self.icon_views.clear();
             self.dirty = true;
             return;
         }
@@ -96,26 +126,138 @@ impl ScrollList {
         } else {
             0
         };
+        self.disabled = vec![false; items.len()];
+        self.headers = vec![false; items.len()];
+        self.icons = vec![None; items.len()];
+        self.colors = vec![None; items.len()];
+        if let Some(checked) = self.checked.as_mut() {
+            *checked = vec![false; items.len()];
+        }
         self.items = items;
 
-        self.children.clear();
-        let mut y = self.rect.y + 4;
-        for i in 0..self.visible_count() {
-            self.children.push(Label::new(
-                Point::new(self.rect.x + 12 * self.alignment.sign(), y),
-                self.items[i].to_owned(),
-                self.alignment,
-                Some(self.rect.w - 24),
-            ));
-            y += self.entry_height as i32;
+        self.layout_children();
+        self.select(selected);
+        self.update_children();
+
+        self.dirty = true;
+    }
+
+    /// Shows or hides a checkbox next to each entry. Entries are unchecked when the checkboxes
+    /// are shown, and when the items are set.
+    pub fn set_checkboxes(&mut self, shown: bool) {
+        self.checked = shown.then(|| vec![false; self.items.len()]);
+        if self.items.is_empty() {
+            return;
         }
 
-        self.select(selected);
+        self.layout_children();
+        self.select(self.selected);
         self.update_children();
+        self.dirty = true;
+    }
+
+    pub fn set_checked(&mut self, index: usize, checked: bool) {
+        if let Some(c) = self.checked.as_mut().and_then(|c| c.get_mut(index)) {
+            *c = checked;
+            self.dirty = true;
+        }
+    }
+
+    pub fn is_checked(&self, index: usize) -> bool {
+        self.checked
+            .as_ref()
+            .is_some_and(|c| c.get(index).copied().unwrap_or(false))
+    }
+
+    /// Indices of the checked entries.
+    pub fn checked(&self) -> Vec<usize> {
+        self.checked
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter_map(|(i, checked)| checked.then_some(i))
+            .collect()
+    }
+
+    /// Greys out an entry, e.g. a game that can't be launched right now. Entries are enabled
+    /// again when the items are set.
+    pub fn set_disabled(&mut self, index: usize, disabled: bool) {
+        if index >= self.items.len() {
+            return;
+        }
 
+        self.disabled[index] = disabled;
+        self.update_children();
+        self.dirty = true;
+    }
+
+    /// Sets the icon of each entry, shown before its text. Entries have no icons when the items
+    /// are set.
+    pub fn set_icons(&mut self, icons: Vec<Option<PathBuf>>) {
+        if icons.len() != self.items.len() {
+            return;
+        }
+
+        self.icons = icons;
+        self.layout_children();
+        self.select(self.selected);
+        self.update_children();
+        self.dirty = true;
+    }
+
+    /// Draws an entry's text in an accent color. Entries have no accent color when the items
+    /// are set.
+    pub fn set_color(&mut self, index: usize, color: Option<Color>) {
+        if index >= self.items.len() {
+            return;
+        }
+
+        self.colors[index] = color;
+        self.update_children();
         self.dirty = true;
     }
 
+    /// Marks an entry as a section header. Headers are skipped over when moving the selection.
+    /// Entries are no longer headers when the items are set.
+    pub fn set_header(&mut self, index: usize, header: bool) {
+        if index >= self.items.len() {
+            return;
+        }
+
+        self.headers[index] = header;
+        if self.selected == index {
+            self.select(index);
+        }
+        self.update_children();
+        self.dirty = true;
+    }
+
+    pub fn is_header(&self, index: usize) -> bool {
+        self.headers.get(index).copied().unwrap_or(false)
+    }
+
+    /// The closest entry to `index` that isn't a header, looking forward first if `forward`.
+    fn nearest_selectable(&self, index: usize, forward: bool) -> usize {
+        let after = index..self.items.len();
+        let before = (0..=index).rev();
+        let found = if forward {
+            after.chain(before).find(|&i| !self.headers[i])
+        } else {
+            before.chain(after).find(|&i| !self.headers[i])
+        };
+        found.unwrap_or(index)
+    }
+
+    /// The next entry that isn't a header, wrapping around the ends of the list.
+    fn step(&self, forward: bool) -> usize {
+        let len = self.items.len() as isize;
+        let delta = if forward { 1 } else { -1 };
+        (1..len)
+            .map(|i| (self.selected as isize + delta * i).rem_euclid(len) as usize)
+            .find(|&i| !self.headers[i])
+            .unwrap_or(self.selected)
+    }
+
     pub fn select(&mut self, mut index: usize) {
         if self.visible_count() == 0 {
             return;
@@ -126,6 +268,7 @@ impl ScrollList {
             .map(|v| v.scroll(false));
 
         index = index.clamp(0, self.items.len() - 1);
+        index = self.nearest_selectable(index, index >= self.selected);
         if index >= self.top + self.visible_count() {
             self.top = (index - self.visible_count() + 1).min(self.items.len() - 1);
         } else if index < self.top {
@@ -149,9 +292,78 @@ impl ScrollList {
         (self.rect.h as usize / self.entry_height as usize).min(self.items.len())
     }
 
+    /// Size of the checkboxes, which are drawn before the entries' text.
+    fn checkbox_size(&self) -> u32 {
+        self.entry_height / 2
+    }
+
+    /// Space taken up by a checkbox and its margin.
+    fn checkbox_width(&self) -> u32 {
+        if self.checked.is_some() {
+            self.checkbox_size() + 12
+        } else {
+            0
+        }
+    }
+
+    /// Size of the icons, which are drawn before the highlight.
+    fn icon_size(&self) -> u32 {
+        self.entry_height * 3 / 4
+    }
+
+    /// Space taken up by an icon and its margin.
+    fn icon_width(&self) -> u32 {
+        if self.alignment == Alignment::Left && self.icons.iter().any(Option::is_some) {
+            self.icon_size() + 4
+        } else {
+            0
+        }
+    }
+
+    fn layout_children(&mut self) {
+        self.children.clear();
+        self.icon_views.clear();
+        let icon_width = self.icon_width();
+        let indent = 12 + self.checkbox_width() + icon_width;
+        let mut y = self.rect.y + 4;
+        for i in 0..self.visible_count() {
+            if icon_width > 0 {
+                let size = self.icon_size();
+                self.icon_views.push(Image::empty(
+                    Rect::new(
+                        self.rect.x,
+                        y - 4 + (self.entry_height as i32 - size as i32) / 2,
+                        size,
+                        size,
+                    ),
+                    ImageMode::Contain,
+                ));
+            }
+            self.children.push(Label::new(
+                Point::new(self.rect.x + indent as i32 * self.alignment.sign(), y),
+                self.items[i].to_owned(),
+                self.alignment,
+                Some(self.rect.w - 12 - indent),
+            ));
+            y += self.entry_height as i32;
+        }
+    }
+
     fn update_children(&mut self) {
         for (i, child) in self.children.iter_mut().enumerate() {
             child.set_text(self.items[self.top + i].to_owned());
+            child.color(if self.disabled[self.top + i] {
+                StylesheetColor::Disabled
+            } else if let Some(color) = self.colors[self.top + i] {
+                StylesheetColor::Custom(color)
+            } else if self.headers[self.top + i] {
+                StylesheetColor::Highlight
+            } else {
+                StylesheetColor::Foreground
+            });
+        }
+        for (i, icon) in self.icon_views.iter_mut().enumerate() {
+            icon.set_path(self.icons[self.top + i].clone());
         }
     }
 }
@@ -164,6 +376,10 @@ impl View for ScrollList {
         styles: &Stylesheet,
     ) -> Result<bool> {
         if self.should_draw() {
+            for child in self.children.iter_mut() {
+                child.font_size(styles.list_font_size());
+            }
+
             if let Some(color) = self.background_color {
                 let mut rect = self
                     .children_mut()
@@ -185,14 +401,23 @@ impl View for ScrollList {
                 display.load(self.bounding_box(styles))?;
             }
 
+            let checkbox_width = self.checkbox_width();
             if let Some(selected) = self.children.get_mut(self.selected - self.top) {
-                let rect = selected.bounding_box(styles);
+                let mut rect = selected.bounding_box(styles);
+                // Keep the highlight from overlapping neighbouring entries in compact lists
+                let margin = (styles.list_selection_margin() / 2).min(4);
+
+                // The highlight covers the checkbox too
+                if self.alignment == Alignment::Left {
+                    rect.x -= checkbox_width as i32;
+                }
+                rect.w += checkbox_width;
 
                 let fill_style = PrimitiveStyle::with_fill(styles.highlight_color);
                 RoundedRectangle::with_equal_corners(
                     Rectangle::new(
-                        embedded_graphics::prelude::Point::new(rect.x - 12, rect.y - 4),
-                        Size::new(rect.w + 24, rect.h + 8),
+                        embedded_graphics::prelude::Point::new(rect.x - 12, rect.y - margin as i32),
+                        Size::new(rect.w + 24, rect.h + margin * 2),
                     ),
                     Size::new_equal(rect.h),
                 )
@@ -200,6 +425,37 @@ impl View for ScrollList {
                 .draw(display)?;
             }
 
+            if let Some(checked) = self.checked.as_ref() {
+                let size = self.checkbox_size();
+                for (i, child) in self.children.iter_mut().enumerate() {
+                    if self.headers[self.top + i] {
+                        continue;
+                    }
+                    let rect = child.bounding_box(styles);
+                    let x = match self.alignment {
+                        Alignment::Right => rect.x + rect.w as i32 + 12,
+                        _ => rect.x - checkbox_width as i32,
+                    };
+                    let y = rect.y + (rect.h as i32 - size as i32) / 2;
+                    let style = if checked[self.top + i] {
+                        PrimitiveStyle::with_fill(styles.foreground_color)
+                    } else {
+                        PrimitiveStyle::with_stroke(styles.foreground_color, 2)
+                    };
+                    RoundedRectangle::with_equal_corners(
+                        Rect::new(x, y, size, size).into(),
+                        Size::new_equal(size / 4),
+                    )
+                    .into_styled(style)
+                    .draw(display)?;
+                }
+            }
+
+            // Icons are left of the highlight, as they clear the background behind them
+            for icon in self.icon_views.iter_mut() {
+                icon.draw(display, styles)?;
+            }
+
             for child in self.children.iter_mut() {
                 child.draw(display, styles)?;
             }
@@ -215,12 +471,19 @@ impl View for ScrollList {
                 drawn = true;
             }
         }
+        for icon in self.icon_views.iter_mut() {
+            if icon.should_draw() && icon.draw(display, styles)? {
+                drawn = true;
+            }
+        }
 
         Ok(drawn)
     }
 
     fn should_draw(&self) -> bool {
-        self.dirty || self.children.iter().any(|v| v.should_draw())
+        self.dirty
+            || self.children.iter().any(|v| v.should_draw())
+            || self.icon_views.iter().any(|v| v.should_draw())
     }
 
     fn set_should_draw(&mut self) {
@@ -228,25 +491,41 @@ impl View for ScrollList {
         for entry in &mut self.children {
             entry.set_should_draw();
         }
+        for icon in &mut self.icon_views {
+            icon.set_should_draw();
+        }
     }
 
     async fn handle_key_event(
         &mut self,
         event: KeyEvent,
-        _command: Sender<Command>,
+        commands: Sender<Command>,
         _bubble: &mut VecDeque<Command>,
     ) -> Result<bool> {
         if !self.items.is_empty() {
+            // Ticks when the end of the list is hit, whether it wraps around or stops
+            let at_start = self.nearest_selectable(0, true) == self.selected;
+            let at_end = self.nearest_selectable(self.items.len() - 1, false) == self.selected;
+            let edge = match event {
+                KeyEvent::Pressed(Key::Up | Key::L) | KeyEvent::Autorepeat(Key::Up | Key::L) => {
+                    at_start
+                }
+                KeyEvent::Pressed(Key::Down | Key::R)
+                | KeyEvent::Autorepeat(Key::Down | Key::R) => at_end,
+                _ => false,
+            };
+            if edge {
+                commands.send(Command::Rumble(Rumble::TICK)).await?;
+            }
+
             match event {
                 KeyEvent::Pressed(Key::Up) | KeyEvent::Autorepeat(Key::Up) => {
-                    self.select(
-                        (self.selected as isize - 1).rem_euclid(self.items.len() as isize) as usize,
-                    );
+                    self.select(self.step(false));
                     self.dirty = true;
                     Ok(true)
                 }
                 KeyEvent::Pressed(Key::Down) | KeyEvent::Autorepeat(Key::Down) => {
-                    self.select((self.selected + 1).rem_euclid(self.items.len()));
+                    self.select(self.step(true));
                     self.dirty = true;
                     Ok(true)
                 }
@@ -270,14 +549,57 @@ impl View for ScrollList {
         }
     }
 
+    async fn handle_gesture(
+        &mut self,
+        gesture: Gesture,
+        _styles: &Stylesheet,
+        _commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self.items.is_empty() {
+            return Ok(false);
+        }
+        match gesture {
+            Gesture::Tap(point) => {
+                let row = (point.y - self.rect.y).max(0) as u32 / self.entry_height;
+                let index = self.top + row as usize;
+                if index >= self.items.len() || self.headers[index] {
+                    return Ok(false);
+                }
+                // Tapping the selected entry selects it, like pressing A
+                if index == self.selected {
+                    bubble.push_back(Command::PressKey(Key::A));
+                } else {
+                    self.select(index);
+                }
+                Ok(true)
+            }
+            Gesture::Swipe { .. } => {
+                // Swiping up scrolls down a page, as if the list was dragged
+                let page = self.visible_count();
+                match gesture.direction() {
+                    Some(Key::Up) => self.select(self.selected + page),
+                    Some(Key::Down) => self.select(self.selected.saturating_sub(page)),
+                    _ => return Ok(false),
+                }
+                Ok(true)
+            }
+        }
+    }
+
     fn children(&self) -> Vec<&dyn View> {
-        self.children.iter().map(|c| c as &dyn View).collect()
+        self.children
+            .iter()
+            .map(|c| c as &dyn View)
+            .chain(self.icon_views.iter().map(|c| c as &dyn View))
+            .collect()
     }
 
     fn children_mut(&mut self) -> Vec<&mut dyn View> {
         self.children
             .iter_mut()
             .map(|c| c as &mut dyn View)
+            .chain(self.icon_views.iter_mut().map(|c| c as &mut dyn View))
             .collect()
     }
 
@@ -308,6 +630,13 @@ impl View for ScrollList {
                 point.y + 8 + i as i32 * self.entry_height as i32,
             ));
         }
+        let offset = (self.entry_height - self.icon_size()) as i32 / 2;
+        for (i, icon) in self.icon_views.iter_mut().enumerate() {
+            icon.set_position(Point::new(
+                point.x,
+                point.y + offset + i as i32 * self.entry_height as i32,
+            ));
+        }
 
         self.dirty = true;
     }
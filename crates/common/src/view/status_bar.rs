@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use log::error;
+use tokio::sync::mpsc::Sender;
+
+use crate::battery::Battery;
+use crate::constants::DEVICE_STATUS_UPDATE_INTERVAL;
+use crate::display::Display;
+use crate::geom::{Alignment, Point, Rect};
+use crate::platform::{DefaultPlatform, KeyEvent, Platform};
+use crate::resources::Resources;
+use crate::status::DeviceStatus;
+use crate::stylesheet::Stylesheet;
+use crate::view::{BatteryIndicator, Clock, Command, Icon, Label, Row, View};
+
+/// The Wi-Fi state and volume published by alliumd. The view is positioned by its right edge.
+#[derive(Debug, Clone)]
+pub struct StatusIndicator {
+    last_updated: Instant,
+    status: DeviceStatus,
+    volume: Label<String>,
+    dirty: bool,
+}
+
+impl StatusIndicator {
+    pub fn new(res: Resources, point: Point) -> Self {
+        let styles = res.get::<Stylesheet>();
+        let status = DeviceStatus::load().unwrap_or_default();
+        let mut volume = Label::new(point, status.volume.to_string(), Alignment::Right, None);
+        volume.font_size(styles.status_bar_font_size);
+
+        Self {
+            last_updated: Instant::now(),
+            status,
+            volume,
+            dirty: true,
+        }
+    }
+
+    fn icon_size(styles: &Stylesheet) -> u32 {
+        (styles.status_bar_font_size() * 4.0 / 5.0) as u32
+    }
+}
+
+#[async_trait(?Send)]
+impl View for StatusIndicator {
+    fn update(&mut self, _dt: Duration) {
+        if self.last_updated.elapsed() < DEVICE_STATUS_UPDATE_INTERVAL {
+            return;
+        }
+        self.last_updated = Instant::now();
+        let status = match DeviceStatus::load() {
+            Ok(status) => status,
+            Err(e) => {
+                error!("Failed to load device status: {}", e);
+                return;
+            }
+        };
+        if status.volume != self.status.volume {
+            self.volume.set_text(status.volume.to_string());
+        }
+        if status.wifi_connected != self.status.wifi_connected {
+            self.dirty = true;
+        }
+        self.status = status;
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if !self.should_draw() {
+            return Ok(false);
+        }
+
+        display.load(self.bounding_box(styles))?;
+
+        let label = self.volume.bounding_box(styles);
+        let size = Self::icon_size(styles);
+        let y = label.y + (label.h as i32 - size as i32) / 2;
+
+        let mut x = label.x - size as i32 - 4;
+        Icon::Volume.draw(display, styles, Rect::new(x, y, size, size))?;
+        if self.status.wifi_connected {
+            x -= size as i32 + 8;
+            Icon::Wifi.draw(display, styles, Rect::new(x, y, size, size))?;
+        }
+
+        self.volume.set_should_draw();
+        self.volume.draw(display, styles)?;
+        self.dirty = false;
+
+        Ok(true)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty || self.volume.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        self.volume.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        _event: KeyEvent,
+        _commands: Sender<Command>,
+        _bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![]
+    }
+
+    fn bounding_box(&mut self, styles: &Stylesheet) -> Rect {
+        let label = self.volume.bounding_box(styles);
+        let size = Self::icon_size(styles);
+        let mut icons_w = size + 4;
+        if self.status.wifi_connected {
+            icons_w += size + 8;
+        }
+        Rect::new(
+            label.x - icons_w as i32,
+            label.y,
+            label.w + icons_w,
+            label.h.max(size),
+        )
+    }
+
+    fn set_position(&mut self, point: Point) {
+        self.volume.set_position(point);
+    }
+}
+
+/// The battery, clock, Wi-Fi state and volume, shown in the top right corner of the launcher and
+/// the in-game menu.
+#[derive(Debug)]
+pub struct StatusBar {
+    row: Row<Box<dyn View>>,
+}
+
+impl StatusBar {
+    /// Creates a status bar with its right edge at `point`.
+    pub fn new<B>(res: Resources, point: Point, battery: B) -> Self
+    where
+        B: Battery + 'static,
+    {
+        let styles = res.get::<Stylesheet>();
+
+        let status = StatusIndicator::new(res.clone(), Point::zero());
+        let battery_indicator = BatteryIndicator::new(
+            res.clone(),
+            Point::zero(),
+            battery,
+            styles.show_battery_level,
+        );
+
+        let mut children: Vec<Box<dyn View>> = vec![Box::new(status), Box::new(battery_indicator)];
+
+        if styles.show_clock {
+            let clock = Clock::new(res.clone(), Point::zero(), Alignment::Right);
+            children.push(Box::new(clock));
+        }
+
+        Self {
+            row: Row::new(point, children, Alignment::Right, 8),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl View for StatusBar {
+    fn update(&mut self, dt: Duration) {
+        self.row.update(dt);
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        self.row.draw(display, styles)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.row.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.row.set_should_draw();
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        _event: KeyEvent,
+        _commands: Sender<Command>,
+        _bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.row]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.row]
+    }
+
+    fn bounding_box(&mut self, styles: &Stylesheet) -> Rect {
+        self.row.bounding_box(styles)
+    }
+
+    fn set_position(&mut self, point: Point) {
+        self.row.set_position(point);
+    }
+}
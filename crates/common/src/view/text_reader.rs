@@ -4,16 +4,6 @@ use std::{fs, mem};
 
 use anyhow::Result;
 use async_trait::async_trait;
-use common::command::Command;
-use common::database::Database;
-use common::display::font::FontTextStyleBuilder;
-use common::geom::{Alignment, Point, Rect};
-use common::locale::Locale;
-use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
-use common::resources::Resources;
-use common::stylesheet::Stylesheet;
-use common::view::{ButtonHint, Row, View};
-use common::view::{ButtonIcon, Keyboard};
 use embedded_graphics::Drawable;
 use embedded_graphics::prelude::{Dimensions, Size};
 use embedded_graphics::primitives::{Primitive, PrimitiveStyle, Rectangle, RoundedRectangle};
@@ -21,6 +11,21 @@ use embedded_graphics::text::Text;
 use log::{error, trace};
 use tokio::sync::mpsc::Sender;
 
+use crate::command::Command;
+use crate::database::Database;
+use crate::display::font::FontTextStyleBuilder;
+use crate::geom::{Alignment, Point, Rect};
+use crate::locale::Locale;
+use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use crate::resources::Resources;
+use crate::stylesheet::Stylesheet;
+use crate::view::{ButtonHint, ButtonIcon, Keyboard, Row, View};
+
+/// Reads a text file, such as a guide or a manual. The position is saved in the database.
+///
+/// Pressing X asks for a search, which the owner of the reader opens a keyboard for. See
+/// `TextReader::take_keyboard`.
+#[derive(Debug)]
 pub struct TextReader {
     rect: Rect,
     res: Resources,
@@ -29,7 +34,8 @@ pub struct TextReader {
     lowercase_text: String,
     cursor: usize,
     button_hints: Row<ButtonHint<String>>,
-    keyboard: Option<Keyboard>,
+    /// Whether the keyboard should be opened to search.
+    open_keyboard: bool,
     last_searched: String,
     dirty: bool,
 }
@@ -40,6 +46,13 @@ impl TextReader {
         let text = fs::read_to_string(&path)
             .map_err(|e| error!("failed to load guide file: {}", e))
             .unwrap_or_default();
+        Self::with_text(rect, res, path, text)
+    }
+
+    /// Reads text that was already loaded, such as the text extracted from an ebook. The
+    /// position is saved under `path`.
+    #[must_use]
+    pub fn with_text(rect: Rect, res: Resources, path: PathBuf, text: String) -> Self {
         let lowercase_text = text.to_lowercase();
 
         let mut cursor = if text.is_empty() {
@@ -92,7 +105,7 @@ impl TextReader {
             lowercase_text,
             cursor,
             button_hints,
-            keyboard: None,
+            open_keyboard: false,
             dirty: true,
             last_searched: String::new(),
         }
@@ -106,9 +119,22 @@ impl TextReader {
             .ok();
     }
 
+    /// Moves forward or back by the number of lines that fit on the screen.
+    pub fn turn_page(&mut self, forward: bool) {
+        let lines = self.line_count(&self.res.get::<Stylesheet>()) as usize;
+        if forward {
+            self.move_forward_lines(lines);
+        } else {
+            self.move_back_lines(lines);
+        }
+    }
+
+    fn line_count(&self, styles: &Stylesheet) -> u32 {
+        (self.rect.h - 12 - 8 - ButtonIcon::diameter(styles) - 8) / styles.guide_font.size
+    }
+
     fn visible_text(&self, styles: &Stylesheet) -> Vec<&str> {
-        let line_count =
-            (self.rect.h - 12 - 8 - ButtonIcon::diameter(styles) - 8) / styles.guide_font.size;
+        let line_count = self.line_count(styles);
         let mut lines = Vec::with_capacity(line_count as usize);
         let mut cursor = self.cursor;
         for _ in 0..line_count {
@@ -132,7 +158,7 @@ impl TextReader {
     fn get_line(&self, styles: &Stylesheet, cursor: usize) -> &str {
         let line_width = self.rect.w - 24 - 24;
         let text_style = FontTextStyleBuilder::new(styles.guide_font.font())
-            .font_fallback(styles.cjk_font.font())
+            .font_fallbacks(styles.fallbacks())
             .font_size(styles.guide_font.size)
             .background_color(styles.background_color)
             .text_color(styles.foreground_color)
@@ -203,6 +229,22 @@ impl TextReader {
         &self.text[cursor..cursor + offset]
     }
 
+    /// Returns a keyboard to enter a search with, if X was pressed. It's prefilled with the last
+    /// search, and the text entered should be passed to `TextReader::search`.
+    pub fn take_keyboard(&mut self) -> Option<Keyboard> {
+        if !mem::take(&mut self.open_keyboard) {
+            return None;
+        }
+        let keyboard = Keyboard::new(self.res.clone(), mem::take(&mut self.last_searched), false);
+        Some(keyboard)
+    }
+
+    /// Jumps to the next line containing `needle`, wrapping around at the end.
+    pub fn search(&mut self, needle: String) {
+        self.search_forward(needle);
+        self.dirty = true;
+    }
+
     fn search_forward(&mut self, needle: String) {
         // Skip the current line
         self.cursor += self.text[self.cursor..].find('\n').unwrap_or_default();
@@ -371,7 +413,7 @@ impl View for TextReader {
             .draw(display)?;
 
             let text_style = FontTextStyleBuilder::new(styles.guide_font.font())
-                .font_fallback(styles.cjk_font.font())
+                .font_fallbacks(styles.fallbacks())
                 .font_size(styles.guide_font.size)
                 .background_color(styles.background_color)
                 .text_color(styles.foreground_color)
@@ -416,92 +458,57 @@ impl View for TextReader {
 
         drawn |= self.button_hints.draw(display, styles)?;
 
-        if let Some(keyboard) = self.keyboard.as_mut() {
-            drawn |= keyboard.draw(display, styles)?;
-        }
-
         Ok(drawn)
     }
 
     fn should_draw(&self) -> bool {
-        self.dirty
-            || self.button_hints.should_draw()
-            || self
-                .keyboard
-                .as_ref()
-                .is_some_and(common::view::View::should_draw)
+        self.dirty || self.button_hints.should_draw()
     }
 
     fn set_should_draw(&mut self) {
         self.dirty = true;
         self.button_hints.set_should_draw();
-        if let Some(keyboard) = self.keyboard.as_mut() {
-            keyboard.set_should_draw();
-        }
     }
 
     async fn handle_key_event(
         &mut self,
         event: KeyEvent,
-        commands: Sender<Command>,
+        _commands: Sender<Command>,
         bubble: &mut VecDeque<Command>,
     ) -> Result<bool> {
-        if let Some(keyboard) = self.keyboard.as_mut() {
-            if keyboard.handle_key_event(event, commands, bubble).await? {
-                bubble.retain_mut(|cmd| match cmd {
-                    Command::CloseView => {
-                        self.keyboard = None;
-                        false
-                    }
-                    Command::ValueChanged(_, value) => {
-                        self.search_forward(std::mem::take(value).as_string().unwrap());
-                        false
-                    }
-                    _ => true,
-                });
-                Ok(true)
-            } else {
-                Ok(false)
+        match event {
+            KeyEvent::Pressed(Key::Up) | KeyEvent::Autorepeat(Key::Up) => {
+                self.move_back_lines(1);
             }
-        } else {
-            match event {
-                KeyEvent::Pressed(Key::Up) | KeyEvent::Autorepeat(Key::Up) => {
-                    self.move_back_lines(1);
-                }
-                KeyEvent::Pressed(Key::Down) | KeyEvent::Autorepeat(Key::Down) => {
-                    self.move_forward_lines(1);
-                }
-                KeyEvent::Pressed(Key::L) | KeyEvent::Autorepeat(Key::L) => {
-                    self.move_back_lines(10);
-                }
-                KeyEvent::Pressed(Key::R) | KeyEvent::Autorepeat(Key::R) => {
-                    self.move_forward_lines(10);
-                }
-                KeyEvent::Pressed(Key::L2) => {
-                    let last_searched = mem::take(&mut self.last_searched);
-                    self.search_backward(last_searched);
-                    self.dirty = true;
-                }
-                KeyEvent::Pressed(Key::R2) => {
-                    let last_searched = mem::take(&mut self.last_searched);
-                    self.search_forward(last_searched);
-                    self.dirty = true;
-                }
-                KeyEvent::Pressed(Key::B) => {
-                    self.save_cursor();
-                    bubble.push_back(Command::CloseView);
-                }
-                KeyEvent::Pressed(Key::X) => {
-                    self.keyboard = Some(Keyboard::new(
-                        self.res.clone(),
-                        mem::take(&mut self.last_searched),
-                        false,
-                    ));
-                }
-                _ => return Ok(false),
+            KeyEvent::Pressed(Key::Down) | KeyEvent::Autorepeat(Key::Down) => {
+                self.move_forward_lines(1);
+            }
+            KeyEvent::Pressed(Key::L) | KeyEvent::Autorepeat(Key::L) => {
+                self.move_back_lines(10);
+            }
+            KeyEvent::Pressed(Key::R) | KeyEvent::Autorepeat(Key::R) => {
+                self.move_forward_lines(10);
+            }
+            KeyEvent::Pressed(Key::L2) => {
+                let last_searched = mem::take(&mut self.last_searched);
+                self.search_backward(last_searched);
+                self.dirty = true;
+            }
+            KeyEvent::Pressed(Key::R2) => {
+                let last_searched = mem::take(&mut self.last_searched);
+                self.search_forward(last_searched);
+                self.dirty = true;
+            }
+            KeyEvent::Pressed(Key::B) => {
+                self.save_cursor();
+                bubble.push_back(Command::CloseView);
+            }
+            KeyEvent::Pressed(Key::X) => {
+                self.open_keyboard = true;
             }
-            Ok(true)
+            _ => return Ok(false),
         }
+        Ok(true)
     }
 
     fn children(&self) -> Vec<&dyn View> {
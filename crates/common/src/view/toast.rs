@@ -0,0 +1,429 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use embedded_graphics::Drawable;
+use embedded_graphics::image::ImageRaw;
+use embedded_graphics::prelude::{Dimensions, OriginDimensions, Size, Transform};
+use embedded_graphics::primitives::{
+    CornerRadii, Primitive, PrimitiveStyle, Rectangle, RoundedRectangle,
+};
+use embedded_graphics::text::{Alignment, Text};
+use image::{ImageBuffer, Rgba};
+use tokio::sync::mpsc::Sender;
+
+use crate::command::Command;
+use crate::display::color::Color;
+use crate::display::font::{FontTextStyle, FontTextStyleBuilder};
+use crate::geom::{Point, Rect};
+use crate::platform::rumble::Rumble;
+use crate::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use crate::stylesheet::Stylesheet;
+use crate::view::{ButtonIcon, Icon, View};
+
+/// How many toasts are shown at once. The others wait in the queue.
+const MAX_VISIBLE_TOASTS: usize = 3;
+
+/// Toasts with a higher priority are shown before the ones with a lower priority.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ToastPriority {
+    Low,
+    #[default]
+    Normal,
+    /// Also rumbles when queued, to draw attention to it.
+    High,
+}
+
+impl ToastPriority {
+    /// Rumble played when a toast of this priority is queued, if any.
+    pub fn rumble(self) -> Option<Rumble> {
+        match self {
+            ToastPriority::Low | ToastPriority::Normal => None,
+            ToastPriority::High => Some(Rumble::NOTIFICATION),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    image: Option<ImageBuffer<Rgba<u8>, Vec<u8>>>,
+    icon: Option<Icon>,
+    text: String,
+    priority: ToastPriority,
+    /// Button that runs an action while the toast is shown, and the action's name, e.g. Select to
+    /// undo.
+    action: Option<(Key, String)>,
+    duration: Option<Duration>,
+    /// Set when the toast is first drawn, so that queued toasts don't expire before being shown.
+    expires: Option<Instant>,
+}
+
+impl Toast {
+    pub fn new(text: String, duration: Option<Duration>) -> Self {
+        Self {
+            image: None,
+            icon: None,
+            text,
+            priority: ToastPriority::default(),
+            action: None,
+            duration,
+            expires: None,
+        }
+    }
+
+    pub fn with_image(
+        image: ImageBuffer<Rgba<u8>, Vec<u8>>,
+        text: String,
+        duration: Option<Duration>,
+    ) -> Self {
+        Self {
+            image: Some(image),
+            ..Self::new(text, duration)
+        }
+    }
+
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    pub fn priority(mut self, priority: ToastPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Shows a button hint for an action, which whoever shows the toast runs when the button is
+    /// pressed.
+    pub fn action(mut self, button: Key, label: String) -> Self {
+        self.action = Some((button, label));
+        self
+    }
+
+    pub fn has_expired(&self) -> bool {
+        if let Some(expires) = self.expires {
+            Instant::now() > expires
+        } else {
+            false
+        }
+    }
+
+    /// Whether the toast stays until it's dismissed.
+    pub fn is_persistent(&self) -> bool {
+        self.duration.is_none()
+    }
+
+    /// Returns the size of the toast, including its background.
+    fn size(&self, styles: &Stylesheet) -> Size {
+        let text = self.text(styles).bounding_box().size;
+        let icon = if self.icon.is_some() {
+            styles.ui_font.size + 8
+        } else {
+            0
+        };
+        let mut width = text.width + icon;
+        let mut height = text.height;
+        if let Some((_, label)) = &self.action {
+            let label = Self::action_text(label, styles).bounding_box().size;
+            width += 16 + ButtonIcon::diameter(styles) + 8 + label.width;
+            height = height.max(ButtonIcon::diameter(styles));
+        }
+        if let Some(image) = &self.image {
+            width = width.max(image.width());
+            height += image.height() + 8;
+        }
+        Size::new(width + 24, height + 16)
+    }
+
+    fn text(&self, styles: &Stylesheet) -> Text<'_, FontTextStyle<Color>> {
+        Text::with_alignment(
+            &self.text,
+            Point::zero().into(),
+            Self::text_style(styles),
+            Alignment::Center,
+        )
+    }
+
+    fn action_text<'a>(label: &'a str, styles: &Stylesheet) -> Text<'a, FontTextStyle<Color>> {
+        Text::with_alignment(
+            label,
+            Point::zero().into(),
+            Self::text_style(styles),
+            Alignment::Left,
+        )
+    }
+
+    fn text_style(styles: &Stylesheet) -> FontTextStyle<Color> {
+        FontTextStyleBuilder::new(styles.ui_font.font())
+            .font_fallbacks(styles.fallbacks())
+            .font_size(styles.ui_font.size)
+            .background_color(styles.highlight_color)
+            .text_color(styles.foreground_color)
+            .build()
+    }
+
+    /// Draws the toast horizontally centered on `center_x`, with its top edge at `top`.
+    fn draw_at(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+        center_x: i32,
+        top: i32,
+    ) -> Result<Size> {
+        if self.expires.is_none() {
+            self.expires = self.duration.map(|duration| Instant::now() + duration);
+        }
+
+        let size = self.size(styles);
+        RoundedRectangle::new(
+            Rectangle::new(
+                Point::new(center_x - size.width as i32 / 2, top).into(),
+                size,
+            ),
+            CornerRadii::new(Size::new_equal(12)),
+        )
+        .into_styled(PrimitiveStyle::with_fill(styles.highlight_color))
+        .draw(display)?;
+
+        let mut y = top + 8;
+        if let Some(image) = &self.image {
+            let x = center_x - image.width() as i32 / 2;
+            let image_raw: ImageRaw<'_, Color> = ImageRaw::new(image, image.width());
+            embedded_graphics::image::Image::new(&image_raw, Point::new(x, y).into())
+                .draw(display)?;
+            y += image.height() as i32 + 8;
+        }
+
+        let text = self.text(styles);
+        let text_rect = text.bounding_box();
+        let icon_size = styles.ui_font.size;
+        let icon_w = if self.icon.is_some() {
+            icon_size as i32 + 8
+        } else {
+            0
+        };
+        let action_w = self.action.as_ref().map_or(0, |(_, label)| {
+            let label = Self::action_text(label, styles).bounding_box().size;
+            16 + ButtonIcon::diameter(styles) as i32 + 8 + label.width as i32
+        });
+        let left = center_x - (text_rect.size.width as i32 + icon_w + action_w) / 2;
+
+        if let Some(icon) = self.icon {
+            let rect = Rect::new(left, y, icon_size, icon_size);
+            icon.draw(display, styles, rect)?;
+        }
+
+        text.translate(
+            Point::new(
+                left + icon_w - text_rect.top_left.x,
+                y - text_rect.top_left.y,
+            )
+            .into(),
+        )
+        .draw(display)?;
+
+        if let Some((button, label)) = &self.action {
+            let x = left + icon_w + text_rect.size.width as i32 + 16;
+            ButtonIcon::new(Point::new(x, y), *button, crate::geom::Alignment::Left)
+                .draw(display, styles)?;
+            let label = Self::action_text(label, styles);
+            let label_rect = label.bounding_box();
+            label
+                .translate(
+                    Point::new(
+                        x + ButtonIcon::diameter(styles) as i32 + 8 - label_rect.top_left.x,
+                        y - label_rect.top_left.y,
+                    )
+                    .into(),
+                )
+                .draw(display)?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Toast {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let w = display.size().width;
+        let h = display.size().height;
+
+        let size = self.size(styles);
+        self.draw_at(
+            display,
+            styles,
+            w as i32 / 2,
+            (h as i32 - size.height as i32) / 2,
+        )?;
+
+        Ok(true)
+    }
+
+    fn should_draw(&self) -> bool {
+        true
+    }
+
+    fn set_should_draw(&mut self) {}
+
+    async fn handle_key_event(
+        &mut self,
+        _event: KeyEvent,
+        _commands: Sender<Command>,
+        _bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        Rect::zero()
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+/// Queue of toasts. The ones with the highest priority are shown stacked in the middle of the
+/// screen, each until its own duration runs out, while the others wait for their turn.
+#[derive(Debug, Clone, Default)]
+pub struct Toasts {
+    /// Sorted by priority, then by the order the toasts were pushed in.
+    queue: Vec<Toast>,
+    /// Whether the toasts shown changed since the last call to `update_queue`.
+    changed: bool,
+}
+
+impl Toasts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a toast, rumbling if its priority calls for it. A toast with the same text, or with
+    /// an action for the same button, replaces the queued one instead.
+    pub fn push(&mut self, toast: Toast) {
+        // Only the latest action can be run with a button
+        let button = toast.action.as_ref().map(|(button, _)| *button);
+        self.queue.retain(|t| {
+            t.text != toast.text
+                && (button.is_none() || t.action.as_ref().map(|(b, _)| *b) != button)
+        });
+        if let Some(rumble) = toast.priority.rumble() {
+            rumble.play();
+        }
+        let i = self.queue.partition_point(|t| t.priority >= toast.priority);
+        self.queue.insert(i, toast);
+        self.changed = true;
+    }
+
+    /// Removes the shown toast whose action runs when the button is pressed, returning whether
+    /// there was one.
+    pub fn take_action(&mut self, button: Key) -> bool {
+        let visible = self.queue.len().min(MAX_VISIBLE_TOASTS);
+        let Some(i) = self.queue[..visible]
+            .iter()
+            .position(|t| matches!(t.action, Some((b, _)) if b == button))
+        else {
+            return false;
+        };
+        self.queue.remove(i);
+        self.changed = true;
+        true
+    }
+
+    /// Removes the persistent toasts, such as progress messages. Toasts with a duration stay until
+    /// they expire.
+    pub fn dismiss(&mut self) {
+        let len = self.queue.len();
+        self.queue.retain(|t| !t.is_persistent());
+        self.changed |= self.queue.len() != len;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Removes the toasts that expired. Returns whether the toasts shown changed since the last
+    /// call, in which case whatever is below them has to be drawn again.
+    pub fn update_queue(&mut self) -> bool {
+        let len = self.queue.len();
+        self.queue.retain(|t| !t.has_expired());
+        let changed = self.changed || self.queue.len() != len;
+        self.changed = false;
+        changed
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Toasts {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if self.queue.is_empty() {
+            return Ok(false);
+        }
+
+        let w = display.size().width;
+        let h = display.size().height;
+
+        let visible = self.queue.len().min(MAX_VISIBLE_TOASTS);
+        let height = self.queue[..visible]
+            .iter()
+            .map(|t| t.size(styles).height + 8)
+            .sum::<u32>()
+            - 8;
+
+        let mut top = (h as i32 - height as i32) / 2;
+        for toast in &mut self.queue[..visible] {
+            let size = toast.draw_at(display, styles, w as i32 / 2, top)?;
+            top += size.height as i32 + 8;
+        }
+
+        Ok(true)
+    }
+
+    fn should_draw(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
+    fn set_should_draw(&mut self) {}
+
+    async fn handle_key_event(
+        &mut self,
+        _event: KeyEvent,
+        _commands: Sender<Command>,
+        _bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        Rect::zero()
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::time::{Instant, SystemTime};
+
+use anyhow::Result;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use strum::FromRepr;
+
+use crate::constants::{ALLIUM_WEBHOOK_SETTINGS, WEBHOOK_RATE_LIMIT, WEBHOOK_RATE_WINDOW};
+use crate::game_info::GameInfo;
+use crate::http;
+use crate::locale::Locale;
+
+/// Shape of the posts sent to the webhook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, FromRepr)]
+pub enum WebhookFormat {
+    /// A chat message, as expected by Discord webhooks.
+    #[default]
+    Discord,
+    /// The event as JSON, for any other HTTP endpoint.
+    Generic,
+}
+
+/// Posts what's being played to a webhook. Off by default, and only the game's name, core and
+/// play time are ever sent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookSettings {
+    pub enabled: bool,
+    pub url: String,
+    pub format: WebhookFormat,
+    /// Whether to post when a game starts.
+    pub now_playing: bool,
+    /// Whether to post the play time when a game stops.
+    pub session_summary: bool,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            format: WebhookFormat::default(),
+            now_playing: true,
+            session_summary: true,
+        }
+    }
+}
+
+impl WebhookSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn load() -> Result<Self> {
+        if ALLIUM_WEBHOOK_SETTINGS.exists() {
+            debug!("found state, loading from file");
+            let file = File::open(ALLIUM_WEBHOOK_SETTINGS.as_path())?;
+            if let Ok(json) = serde_json::from_reader(file) {
+                return Ok(json);
+            }
+            warn!("failed to read webhook file, removing");
+            fs::remove_file(ALLIUM_WEBHOOK_SETTINGS.as_path())?;
+        }
+        Ok(Self::new())
+    }
+
+    /// Loads the settings again for alliumd, which polls them while the launcher may be saving
+    /// them. Unlike `load`, a file that can't be read is left alone and an error is returned.
+    pub fn reload() -> Result<Self> {
+        if !ALLIUM_WEBHOOK_SETTINGS.exists() {
+            return Ok(Self::new());
+        }
+        let file = File::open(ALLIUM_WEBHOOK_SETTINGS.as_path())?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Saves the settings to a temporary file that then replaces the old one, so that alliumd
+    /// never reads a partly written file.
+    pub fn save(&self) -> Result<()> {
+        let temp = ALLIUM_WEBHOOK_SETTINGS.with_extension("tmp");
+        let file = File::create(&temp)?;
+        serde_json::to_writer(file, &self)?;
+        fs::rename(&temp, ALLIUM_WEBHOOK_SETTINGS.as_path())?;
+        Ok(())
+    }
+
+    /// Returns the modification time and size of the settings file, which change whenever the
+    /// settings are saved, so that they're only loaded again when they do.
+    pub fn stamp() -> Option<(SystemTime, u64)> {
+        let metadata = fs::metadata(ALLIUM_WEBHOOK_SETTINGS.as_path()).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.url.is_empty()
+    }
+
+    fn wants(&self, event: &WebhookEvent) -> bool {
+        match event {
+            WebhookEvent::NowPlaying { .. } => self.now_playing,
+            WebhookEvent::SessionEnded { .. } => self.session_summary,
+        }
+    }
+}
+
+/// Something to post to the webhook. Serialized as is for the generic format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    NowPlaying {
+        game: String,
+        core: String,
+    },
+    SessionEnded {
+        game: String,
+        core: String,
+        /// Play time, in seconds.
+        play_time: i64,
+    },
+}
+
+impl WebhookEvent {
+    pub fn now_playing(game_info: &GameInfo) -> Self {
+        WebhookEvent::NowPlaying {
+            game: game_info.name.clone(),
+            core: game_info.core.clone(),
+        }
+    }
+
+    pub fn session_ended(game_info: &GameInfo) -> Self {
+        WebhookEvent::SessionEnded {
+            game: game_info.name.clone(),
+            core: game_info.core.clone(),
+            play_time: game_info.play_time().num_seconds(),
+        }
+    }
+
+    fn message(&self, locale: &Locale) -> String {
+        let mut map = HashMap::new();
+        match self {
+            WebhookEvent::NowPlaying { game, .. } => {
+                map.insert("game".into(), game.clone().into());
+                locale.ta("webhook-now-playing", &map)
+            }
+            WebhookEvent::SessionEnded {
+                game, play_time, ..
+            } => {
+                map.insert("game".into(), game.clone().into());
+                map.insert("minutes".into(), (play_time / 60).into());
+                locale.ta("webhook-session-ended", &map)
+            }
+        }
+    }
+}
+
+/// Posts events to the webhook in the background, dropping them if posted too often.
+#[derive(Debug, Default)]
+pub struct Webhook {
+    /// When the posts in the current rate limit window were sent.
+    sent: VecDeque<Instant>,
+}
+
+impl Webhook {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn post(&mut self, settings: &WebhookSettings, event: WebhookEvent, locale: &Locale) {
+        if !settings.is_active() || !settings.wants(&event) {
+            return;
+        }
+
+        while self
+            .sent
+            .front()
+            .is_some_and(|sent| sent.elapsed() >= WEBHOOK_RATE_WINDOW)
+        {
+            self.sent.pop_front();
+        }
+        if self.sent.len() >= WEBHOOK_RATE_LIMIT {
+            warn!("webhook rate limit reached, dropping {:?}", event);
+            return;
+        }
+        self.sent.push_back(Instant::now());
+
+        let body = match settings.format {
+            WebhookFormat::Discord => json!({ "content": event.message(locale) }),
+            WebhookFormat::Generic => json!(event),
+        };
+        let url = settings.url.clone();
+        tokio::spawn(async move {
+            info!("posting {:?} to webhook", event);
+            if let Err(e) = http::post_json(&url, &body).await {
+                warn!("failed to post to webhook: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generic_payload() {
+        let event = WebhookEvent::SessionEnded {
+            game: "Tetris".to_string(),
+            core: "gambatte".to_string(),
+            play_time: 90,
+        };
+        assert_eq!(
+            json!(event),
+            json!({
+                "event": "session_ended",
+                "game": "Tetris",
+                "core": "gambatte",
+                "play_time": 90,
+            })
+        );
+    }
+}
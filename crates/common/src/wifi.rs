@@ -4,27 +4,90 @@ use std::io::Write;
 use tokio::process::Command;
 
 use anyhow::Result;
+#[cfg(feature = "miyoo")]
+use anyhow::bail;
 use log::{debug, info, warn};
+use rand::Rng;
+use rand::distr::Alphanumeric;
 use serde::{Deserialize, Serialize};
 
-use crate::constants::ALLIUM_WIFI_SETTINGS;
+#[cfg(feature = "miyoo")]
+use crate::constants::WIFI_SCAN_DURATION;
+use crate::constants::{ALLIUM_WIFI_SETTINGS, NETWORK_SPEED_TEST_URL, WEB_REMOTE_TOKEN_LENGTH};
+
+/// Network configuration of the device's own firmware, kept in sync with the saved networks.
+pub const WPA_SUPPLICANT_CONF: &str = "/appconfigs/wpa_supplicant.conf";
+
+/// Socket directory that wpa_cli talks to wpa_supplicant through.
+#[cfg_attr(not(any(feature = "miyoo", test)), allow(dead_code))]
+const WPA_CTRL_INTERFACE: &str = "/var/run/wpa_supplicant";
+
+/// A saved network, with its password.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WiFiNetwork {
+    pub ssid: String,
+    /// Empty for open networks.
+    pub password: String,
+}
+
+impl WiFiNetwork {
+    /// Whether a password can be used for a secured network. WPA passphrases are 8 to 63
+    /// printable ASCII characters.
+    pub fn is_valid_password(password: &str) -> bool {
+        (8..=63).contains(&password.len())
+            && password.chars().all(|c| c.is_ascii_graphic() || c == ' ')
+    }
+}
+
+/// A network found by scanning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedNetwork {
+    pub ssid: String,
+    /// Signal strength, from 0 to 100.
+    pub signal: u8,
+    /// Whether the network needs a password.
+    pub secured: bool,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WiFiSettings {
     pub wifi: bool,
-    pub ssid: String,
-    pub password: String,
+    /// Saved networks, from the highest priority to the lowest. When more than one is in range,
+    /// the one with the highest priority is connected to.
+    #[serde(default)]
+    pub networks: Vec<WiFiNetwork>,
+    /// The only network, from before multiple networks could be saved. It's moved to `networks`
+    /// when loaded.
+    #[serde(default, skip_serializing)]
+    ssid: String,
+    #[serde(default, skip_serializing)]
+    password: String,
     pub ntp: bool,
     pub web_file_browser: bool,
+    /// Moved to the developer settings, and only read to migrate it.
+    #[serde(default, skip_serializing)]
     pub telnet: bool,
     pub ftp: bool,
     pub syncthing: bool,
+    #[serde(default)]
+    pub web_remote: bool,
+    /// Pairing code that web remote requests must include. Devices sync their libraries over the
+    /// LAN only if they have the same code.
+    #[serde(default)]
+    pub web_remote_token: String,
+    /// Name the device is advertised as on the local network.
+    #[serde(default)]
+    pub device_name: String,
+    /// File downloaded by the network diagnostics to measure download speed.
+    #[serde(default = "WiFiSettings::speed_test_url")]
+    pub speed_test_url: String,
 }
 
 impl WiFiSettings {
     pub fn new() -> Self {
         Self {
             wifi: false,
+            networks: Vec::new(),
             ssid: String::new(),
             password: String::new(),
             ntp: false,
@@ -32,16 +95,25 @@ impl WiFiSettings {
             telnet: false,
             ftp: false,
             syncthing: false,
+            web_remote: false,
+            web_remote_token: String::new(),
+            device_name: String::new(),
+            speed_test_url: Self::speed_test_url(),
         }
     }
 
+    fn speed_test_url() -> String {
+        NETWORK_SPEED_TEST_URL.to_string()
+    }
+
     pub fn load() -> Result<Self> {
         if ALLIUM_WIFI_SETTINGS.exists() {
             debug!("found state, loading from file");
             if let Ok(json) = fs::read_to_string(ALLIUM_WIFI_SETTINGS.as_path())
-                && let Ok(json) = serde_json::from_str(&json)
+                && let Ok(mut settings) = serde_json::from_str::<Self>(&json)
             {
-                return Ok(json);
+                settings.migrate_network();
+                return Ok(settings);
             }
             warn!("failed to read state file, removing");
             fs::remove_file(ALLIUM_WIFI_SETTINGS.as_path())?;
@@ -52,15 +124,6 @@ impl WiFiSettings {
     pub fn init(&self) -> Result<()> {
         if self.wifi {
             wifi_on()?;
-            telnet_off()?;
-            if self.ntp {
-                info!("Starting NTP...");
-                ntp_sync()?;
-            }
-            if self.telnet {
-                info!("Starting Telnet...");
-                telnet_on()?;
-            }
             if self.ftp {
                 info!("Starting FTP...");
                 ftp_on()?;
@@ -86,24 +149,25 @@ impl WiFiSettings {
         Ok(())
     }
 
+    /// Moves the network from before multiple networks could be saved into `networks`.
+    fn migrate_network(&mut self) {
+        let ssid = std::mem::take(&mut self.ssid);
+        let password = std::mem::take(&mut self.password);
+        if !ssid.is_empty() && !self.networks.iter().any(|n| n.ssid == ssid) {
+            self.networks.push(WiFiNetwork { ssid, password });
+        }
+    }
+
     fn load_wpa_supplicant_conf() -> Option<Self> {
         #[cfg(feature = "miyoo")]
         {
-            let data = fs::read_to_string("/appconfigs/wpa_supplicant.conf").ok()?;
-
-            let ssid_index = data.find("ssid=\"")?;
-            let ssid = &data[ssid_index + 6..];
-            let ssid_end = ssid.find('"')?;
-            let ssid = &ssid[..ssid_end];
-
-            let psk_index = data.find("psk=\"")?;
-            let psk = &data[psk_index + 5..];
-            let psk_end = psk.find('"')?;
-            let psk = &psk[..psk_end];
-
+            let data = fs::read_to_string(WPA_SUPPLICANT_CONF).ok()?;
+            let networks = parse_wpa_supplicant_conf(&data);
+            if networks.is_empty() {
+                return None;
+            }
             Some(Self {
-                ssid: ssid.to_string(),
-                password: psk.to_string(),
+                networks,
                 ..Default::default()
             })
         }
@@ -114,21 +178,7 @@ impl WiFiSettings {
 
     fn update_wpa_supplicant_conf(&self) -> Result<()> {
         #[cfg(feature = "miyoo")]
-        {
-            let mut file = File::create("/appconfigs/wpa_supplicant.conf")?;
-            write!(
-                file,
-                r#"ctrl_interface=/var/run/wpa_supplicant
-update_config=1
-
-network={{
-	ssid="{ssid}"
-	psk="{password}"
-}}"#,
-                ssid = self.ssid.replace('"', "\\\""),
-                password = self.password.replace('"', "\\\""),
-            )?;
-        }
+        fs::write(WPA_SUPPLICANT_CONF, wpa_supplicant_conf(&self.networks))?;
         Ok(())
     }
 
@@ -136,23 +186,14 @@ network={{
         self.wifi = enabled;
         if self.wifi {
             wifi_on()?;
-            let telnet = self.telnet;
             let ftp = self.ftp;
             tokio::spawn(async move {
-                if wait_for_wifi().await.is_ok() {
-                    if telnet {
-                        telnet_on().ok();
-                    }
-                    if ftp {
-                        ftp_on().ok();
-                    }
+                if wait_for_wifi().await.is_ok() && ftp {
+                    ftp_on().ok();
                 }
             });
         } else {
             wifi_off()?;
-            if self.telnet {
-                telnet_off().ok();
-            }
             if self.ftp {
                 ftp_off().ok();
             }
@@ -160,20 +201,25 @@ network={{
         Ok(())
     }
 
-    pub fn set_ssid(&mut self, ssid: String) -> Result<()> {
-        self.ssid = ssid;
-        if self.wifi {
-            self.set_wifi(self.wifi)?;
+    /// Saves the network with the highest priority, replacing it if it was already saved, so
+    /// that it's connected to next.
+    pub fn add_network(&mut self, network: WiFiNetwork) {
+        self.networks.retain(|n| n.ssid != network.ssid);
+        self.networks.insert(0, network);
+    }
+
+    /// Forgets a saved network.
+    pub fn remove_network(&mut self, index: usize) {
+        if index < self.networks.len() {
+            self.networks.remove(index);
         }
-        Ok(())
     }
 
-    pub fn set_password(&mut self, password: String) -> Result<()> {
-        self.password = password;
-        if self.wifi {
-            self.set_wifi(self.wifi)?;
+    /// Gives a saved network a higher priority than the one before it.
+    pub fn raise_priority(&mut self, index: usize) {
+        if index > 0 && index < self.networks.len() {
+            self.networks.swap(index - 1, index);
         }
-        Ok(())
     }
 
     pub fn toggle_ntp(&mut self, enabled: bool) -> Result<()> {
@@ -194,16 +240,6 @@ network={{
         Ok(())
     }
 
-    pub fn toggle_telnet(&mut self, enabled: bool) -> Result<()> {
-        self.telnet = enabled;
-        if self.telnet {
-            telnet_on()?;
-        } else {
-            telnet_off()?;
-        }
-        Ok(())
-    }
-
     pub fn toggle_ftp(&mut self, enabled: bool) -> Result<()> {
         self.ftp = enabled;
         if self.ftp {
@@ -223,6 +259,32 @@ network={{
         }
         Ok(())
     }
+
+    /// The web remote is served by alliumd, which checks this setting on every request. A
+    /// pairing code is generated the first time it's enabled.
+    pub fn toggle_web_remote(&mut self, enabled: bool) {
+        self.web_remote = enabled;
+        if enabled && self.web_remote_token.is_empty() {
+            self.web_remote_token = rand::rng()
+                .sample_iter(&Alphanumeric)
+                .take(WEB_REMOTE_TOKEN_LENGTH)
+                .map(char::from)
+                .collect();
+        }
+    }
+
+    /// Whether a request's pairing code matches this device's. Requests are refused until a
+    /// code is set.
+    pub fn is_web_remote_token(&self, token: &str) -> bool {
+        !self.web_remote_token.is_empty()
+            && self.web_remote_token.len() == token.len()
+            && self
+                .web_remote_token
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
 }
 
 impl Default for WiFiSettings {
@@ -271,46 +333,6 @@ pub fn wifi_off() -> Result<()> {
     Ok(())
 }
 
-pub fn telnet_on() -> Result<()> {
-    #[cfg(feature = "miyoo")]
-    tokio::spawn(async {
-        Command::new(crate::constants::ALLIUM_SCRIPTS_DIR.join("telnet-on.sh"))
-            .spawn()
-            .map_err(|e| {
-                log::error!("failed to spawn telnet-on.sh: {}", e);
-                e
-            })
-            .unwrap()
-            .wait()
-            .await
-            .map_err(|e| {
-                log::error!("telnet-on.sh failed: {}", e);
-                e
-            })
-    });
-    Ok(())
-}
-
-pub fn telnet_off() -> Result<()> {
-    #[cfg(feature = "miyoo")]
-    tokio::spawn(async {
-        Command::new(crate::constants::ALLIUM_SCRIPTS_DIR.join("telnet-off.sh"))
-            .spawn()
-            .map_err(|e| {
-                log::error!("failed to spawn telnet-off.sh: {}", e);
-                e
-            })
-            .unwrap()
-            .wait()
-            .await
-            .map_err(|e| {
-                log::error!("telnet-off.sh failed: {}", e);
-                e
-            })
-    });
-    Ok(())
-}
-
 pub fn ftp_on() -> Result<()> {
     #[cfg(feature = "miyoo")]
     tokio::spawn(async {
@@ -351,28 +373,27 @@ pub fn ftp_off() -> Result<()> {
     Ok(())
 }
 
+/// Sets the clock from NTP in the background once Wi-Fi is connected.
 pub fn ntp_sync() -> Result<()> {
     #[cfg(feature = "miyoo")]
     tokio::spawn(async {
-        Command::new(crate::constants::ALLIUM_SCRIPTS_DIR.join("ntp-sync.sh"))
-            .spawn()
-            .map_err(|e| {
-                log::error!("failed to spawn ntp-sync.sh: {}", e);
-                e
-            })
-            .unwrap()
-            .wait()
-            .await
-            .map_err(|e| {
-                log::error!("ntp-sync.sh failed: {}", e);
-                e
-            })
-            .ok();
+        if wait_for_wifi().await.is_err() {
+            return;
+        }
+        if let Err(e) = crate::ntp::sync().await {
+            log::error!("failed to sync time: {}", e);
+            return;
+        }
 
-        // Reset start time if time changed
+        // Move the start time along with the clock. Play time is measured on the monotonic
+        // clock when it's known, so it's only reset otherwise
         match crate::game_info::GameInfo::load() {
             Ok(Some(mut game_info)) => {
-                game_info.start_time = chrono::Utc::now();
+                game_info.start_time = if game_info.start_uptime.is_some() {
+                    chrono::Utc::now() - game_info.play_time()
+                } else {
+                    chrono::Utc::now()
+                };
                 game_info
                     .save()
                     .map_err(|e| {
@@ -486,6 +507,182 @@ pub async fn wait_for_wifi() -> Result<()> {
     Ok(())
 }
 
+/// Looks for nearby networks, returning them from the strongest signal to the weakest.
+pub async fn scan() -> Result<Vec<ScannedNetwork>> {
+    #[cfg(feature = "miyoo")]
+    {
+        wpa_cli(&["scan"]).await?;
+        tokio::time::sleep(WIFI_SCAN_DURATION).await;
+        let output = wpa_cli(&["scan_results"]).await?;
+        return Ok(parse_scan_results(&output));
+    }
+
+    #[cfg(not(feature = "miyoo"))]
+    Ok(Vec::new())
+}
+
+/// Makes wpa_supplicant reload the saved networks and connect to the best one in range.
+pub async fn reconnect() -> Result<()> {
+    #[cfg(feature = "miyoo")]
+    wpa_cli(&["reconfigure"]).await?;
+    Ok(())
+}
+
+/// Returns the SSID of the network that Wi-Fi is connected to.
+pub fn connected_ssid() -> Option<String> {
+    #[cfg(feature = "miyoo")]
+    {
+        let output = std::process::Command::new("wpa_cli")
+            .args(["-p", WPA_CTRL_INTERFACE, "-i", "wlan0", "status"])
+            .output()
+            .ok()?;
+        let output = String::from_utf8_lossy(&output.stdout);
+        if !output.lines().any(|line| line == "wpa_state=COMPLETED") {
+            return None;
+        }
+        return output
+            .lines()
+            .find_map(|line| line.strip_prefix("ssid="))
+            .map(str::to_string);
+    }
+
+    #[cfg(not(feature = "miyoo"))]
+    None
+}
+
+#[cfg(feature = "miyoo")]
+async fn wpa_cli(args: &[&str]) -> Result<String> {
+    let output = Command::new("wpa_cli")
+        .args(["-p", WPA_CTRL_INTERFACE, "-i", "wlan0"])
+        .args(args)
+        .output()
+        .await?;
+    if !output.status.success() {
+        bail!("wpa_cli {} failed: {}", args.join(" "), output.status);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Returns a wpa_supplicant configuration with the networks, the first having the highest
+/// priority.
+#[cfg_attr(not(any(feature = "miyoo", test)), allow(dead_code))]
+fn wpa_supplicant_conf(networks: &[WiFiNetwork]) -> String {
+    let mut conf = format!("ctrl_interface={WPA_CTRL_INTERFACE}\nupdate_config=1\n");
+    for (i, network) in networks.iter().enumerate() {
+        let priority = networks.len() - i;
+        let ssid = network.ssid.replace('"', "\\\"");
+        let auth = if network.password.is_empty() {
+            "key_mgmt=NONE".to_string()
+        } else {
+            format!("psk=\"{}\"", network.password.replace('"', "\\\""))
+        };
+        conf.push_str(&format!(
+            "\nnetwork={{\n\tssid=\"{ssid}\"\n\t{auth}\n\tpriority={priority}\n}}\n"
+        ));
+    }
+    conf
+}
+
+/// Parses the networks of a wpa_supplicant configuration, from the highest priority to the
+/// lowest.
+#[cfg_attr(not(any(feature = "miyoo", test)), allow(dead_code))]
+fn parse_wpa_supplicant_conf(data: &str) -> Vec<WiFiNetwork> {
+    let quoted = |line: &str, key: &str| {
+        let value = line.strip_prefix(key)?.strip_prefix("=\"")?;
+        Some(
+            value
+                .strip_suffix('"')
+                .unwrap_or(value)
+                .replace("\\\"", "\""),
+        )
+    };
+
+    let mut networks = Vec::new();
+    for block in data.split("network={").skip(1) {
+        let block = block.split('}').next().unwrap_or_default();
+        let mut ssid = None;
+        let mut password = String::new();
+        let mut priority = 0;
+        for line in block.lines().map(str::trim) {
+            if let Some(value) = quoted(line, "ssid") {
+                ssid = Some(value);
+            } else if let Some(value) = quoted(line, "psk") {
+                password = value;
+            } else if let Some(value) = line.strip_prefix("priority=") {
+                priority = value.parse::<i32>().unwrap_or_default();
+            }
+        }
+        if let Some(ssid) = ssid {
+            networks.push((priority, WiFiNetwork { ssid, password }));
+        }
+    }
+    // Stable, so networks with the same priority stay in the order they're written in
+    networks.sort_by_key(|(priority, _)| -priority);
+    networks.into_iter().map(|(_, network)| network).collect()
+}
+
+/// Parses the output of `wpa_cli scan_results`, which has a header followed by a line like
+/// `bssid\tfrequency\tsignal level\tflags\tssid` for each access point. Access points of the same
+/// network are merged, and hidden networks are left out.
+#[cfg_attr(not(any(feature = "miyoo", test)), allow(dead_code))]
+fn parse_scan_results(output: &str) -> Vec<ScannedNetwork> {
+    let mut networks: Vec<ScannedNetwork> = Vec::new();
+    for line in output.lines() {
+        let mut fields = line.split('\t');
+        let (Some(_bssid), Some(_frequency), Some(level), Some(flags), Some(ssid)) = (
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+        ) else {
+            continue;
+        };
+        let Ok(level) = level.parse::<i32>() else {
+            continue;
+        };
+        if ssid.is_empty() {
+            continue;
+        }
+
+        // -100 dBm is about as weak as a usable signal gets, and -50 dBm is excellent
+        let signal = ((level + 100) * 2).clamp(0, 100) as u8;
+        let secured = ["WPA", "WEP", "RSN"].iter().any(|s| flags.contains(s));
+        match networks.iter_mut().find(|n| n.ssid == ssid) {
+            Some(network) => network.signal = network.signal.max(signal),
+            None => networks.push(ScannedNetwork {
+                ssid: ssid.to_string(),
+                signal,
+                secured,
+            }),
+        }
+    }
+    networks.sort_by(|a, b| b.signal.cmp(&a.signal));
+    networks
+}
+
+/// Returns whether the FTP server is running.
+pub fn ftp_running() -> bool {
+    is_running("tcpsvd")
+}
+
+/// Returns whether the web file browser is running.
+pub fn web_file_browser_running() -> bool {
+    is_running("dufs")
+}
+
+#[allow(unused_variables)]
+fn is_running(process: &str) -> bool {
+    #[cfg(feature = "miyoo")]
+    return std::process::Command::new("pidof")
+        .arg(process)
+        .output()
+        .is_ok_and(|output| output.status.success());
+
+    #[cfg(not(feature = "miyoo"))]
+    return false;
+}
+
 pub fn ip_address() -> Option<String> {
     #[cfg(feature = "miyoo")]
     {
@@ -511,3 +708,120 @@ pub fn ip_address() -> Option<String> {
     #[cfg(not(any(feature = "miyoo", feature = "simulator")))]
     return None;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_web_remote_token() {
+        let mut settings = WiFiSettings::new();
+        assert!(!settings.is_web_remote_token(""));
+
+        settings.toggle_web_remote(true);
+        let token = settings.web_remote_token.clone();
+        assert_eq!(token.len(), WEB_REMOTE_TOKEN_LENGTH);
+        assert!(settings.is_web_remote_token(&token));
+        assert!(!settings.is_web_remote_token(&token[1..]));
+        assert!(!settings.is_web_remote_token("wrong"));
+
+        // The code is kept when the web remote is enabled again
+        settings.toggle_web_remote(false);
+        settings.toggle_web_remote(true);
+        assert_eq!(settings.web_remote_token, token);
+    }
+
+    #[test]
+    fn test_is_valid_password() {
+        assert!(WiFiNetwork::is_valid_password("password"));
+        assert!(WiFiNetwork::is_valid_password(
+            "correct horse battery staple"
+        ));
+        assert!(WiFiNetwork::is_valid_password(&"a".repeat(63)));
+        assert!(!WiFiNetwork::is_valid_password("short"));
+        assert!(!WiFiNetwork::is_valid_password(&"a".repeat(64)));
+        assert!(!WiFiNetwork::is_valid_password("pässwörd"));
+    }
+
+    #[test]
+    fn test_wpa_supplicant_conf() {
+        let networks = vec![
+            WiFiNetwork {
+                ssid: "Home".to_string(),
+                password: "hunter2".to_string(),
+            },
+            WiFiNetwork {
+                ssid: "Cafe \"Free\"".to_string(),
+                password: String::new(),
+            },
+        ];
+        let conf = wpa_supplicant_conf(&networks);
+        assert!(conf.contains("\tssid=\"Home\"\n\tpsk=\"hunter2\"\n\tpriority=2\n"));
+        assert!(conf.contains("\tssid=\"Cafe \\\"Free\\\"\"\n\tkey_mgmt=NONE\n\tpriority=1\n"));
+        assert_eq!(parse_wpa_supplicant_conf(&conf), networks);
+    }
+
+    #[test]
+    fn test_parse_wpa_supplicant_conf() {
+        let data = "ctrl_interface=/var/run/wpa_supplicant\n\
+                    update_config=1\n\
+                    \n\
+                    network={\n\
+                    \tssid=\"Low\"\n\
+                    \tpsk=\"password\"\n\
+                    \tpriority=1\n\
+                    }\n\
+                    network={\n\
+                    \tssid=\"High\"\n\
+                    \tpsk=\"secret\"\n\
+                    \tpriority=5\n\
+                    }";
+        let networks = parse_wpa_supplicant_conf(data);
+        assert_eq!(networks.len(), 2);
+        assert_eq!(networks[0].ssid, "High");
+        assert_eq!(networks[0].password, "secret");
+        assert_eq!(networks[1].ssid, "Low");
+    }
+
+    #[test]
+    fn test_parse_scan_results() {
+        let output = "bssid / frequency / signal level / flags / ssid\n\
+                      00:11:22:33:44:55\t2412\t-80\t[WPA2-PSK-CCMP][ESS]\tHome\n\
+                      66:77:88:99:aa:bb\t5180\t-55\t[WPA2-PSK-CCMP][ESS]\tHome\n\
+                      cc:dd:ee:ff:00:11\t2437\t-70\t[ESS]\tCafe\n\
+                      22:33:44:55:66:77\t2462\t-60\t[WPA2-PSK-CCMP][ESS]\t\n";
+        assert_eq!(
+            parse_scan_results(output),
+            vec![
+                ScannedNetwork {
+                    ssid: "Home".to_string(),
+                    signal: 90,
+                    secured: true,
+                },
+                ScannedNetwork {
+                    ssid: "Cafe".to_string(),
+                    signal: 60,
+                    secured: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_migrate_network() {
+        let mut settings: WiFiSettings = serde_json::from_str(
+            r#"{"wifi":true,"ssid":"Home","password":"hunter2","ntp":false,
+            "web_file_browser":false,"telnet":false,"ftp":false,"syncthing":false}"#,
+        )
+        .unwrap();
+        settings.migrate_network();
+        assert_eq!(
+            settings.networks,
+            vec![WiFiNetwork {
+                ssid: "Home".to_string(),
+                password: "hunter2".to_string(),
+            }]
+        );
+        assert!(settings.ssid.is_empty());
+    }
+}
@@ -0,0 +1,20 @@
+mod file_manager;
+mod operation;
+mod view;
+
+use anyhow::Result;
+
+use common::platform::{DefaultPlatform, Platform};
+use simple_logger::SimpleLogger;
+
+use crate::file_manager::FileManagerApp;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    SimpleLogger::new().env().init().unwrap();
+
+    let platform = DefaultPlatform::new()?;
+    let mut app = FileManagerApp::new(platform)?;
+    app.run_event_loop().await?;
+    Ok(())
+}
@@ -0,0 +1,234 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{Result, bail};
+use log::{info, warn};
+
+/// Number of files done by a copy, move or delete running in the background, out of the total.
+#[derive(Debug, Default)]
+pub struct Progress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+}
+
+impl Progress {
+    /// Returns the number of files done and the total.
+    pub fn get(&self) -> (usize, usize) {
+        (
+            self.done.load(Ordering::Relaxed),
+            self.total.load(Ordering::Relaxed),
+        )
+    }
+
+    fn start(&self, path: &Path) -> Result<()> {
+        self.done.store(0, Ordering::Relaxed);
+        self.total.store(count_files(path)?, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn advance(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Copies a file or folder into `dir`, returning the path of the copy.
+pub fn copy(src: &Path, dir: &Path, progress: &Progress) -> Result<PathBuf> {
+    let dest = destination(src, dir)?;
+    info!("copying {} to {}", src.display(), dest.display());
+    progress.start(src)?;
+    copy_whole(src, &dest, progress)?;
+    Ok(dest)
+}
+
+/// Moves a file or folder into `dir`, returning its new path.
+pub fn move_to(src: &Path, dir: &Path, progress: &Progress) -> Result<PathBuf> {
+    let dest = destination(src, dir)?;
+    info!("moving {} to {}", src.display(), dest.display());
+    if fs::rename(src, &dest).is_err() {
+        // Renaming fails across filesystems, such as from /tmp to the SD card
+        progress.start(src)?;
+        copy_whole(src, &dest, progress)?;
+        delete_recursive(src, &Progress::default())?;
+    }
+    Ok(dest)
+}
+
+/// Renames a file or folder, keeping it in the same folder.
+pub fn rename(path: &Path, name: &str) -> Result<PathBuf> {
+    check_name(name)?;
+    let dest = path.with_file_name(name);
+    if dest.exists() {
+        bail!("{} already exists", name);
+    }
+    info!("renaming {} to {}", path.display(), dest.display());
+    fs::rename(path, &dest)?;
+    Ok(dest)
+}
+
+/// Deletes a file, or a folder and everything in it.
+pub fn delete(path: &Path, progress: &Progress) -> Result<()> {
+    info!("deleting {}", path.display());
+    progress.start(path)?;
+    delete_recursive(path, progress)
+}
+
+/// Creates a folder named `name` in `dir`.
+pub fn create_dir(dir: &Path, name: &str) -> Result<PathBuf> {
+    check_name(name)?;
+    let path = dir.join(name);
+    if path.exists() {
+        bail!("{} already exists", name);
+    }
+    info!("creating folder {}", path.display());
+    fs::create_dir(&path)?;
+    Ok(path)
+}
+
+/// Returns where `src` ends up when copied or moved into `dir`. Existing files are never
+/// overwritten.
+fn destination(src: &Path, dir: &Path) -> Result<PathBuf> {
+    let Some(name) = src.file_name() else {
+        bail!("can't copy {}", src.display());
+    };
+    if dir.starts_with(src) {
+        bail!("can't copy a folder into itself");
+    }
+    let dest = dir.join(name);
+    if dest.exists() {
+        bail!("{} already exists", name.to_string_lossy());
+    }
+    Ok(dest)
+}
+
+/// Copies to a hidden name next to `dest`, then renames it to `dest` once everything is copied,
+/// so that a copy that fails partway through doesn't leave part of it behind.
+fn copy_whole(src: &Path, dest: &Path, progress: &Progress) -> Result<()> {
+    let name = dest.file_name().unwrap_or_default().to_string_lossy();
+    let temp = dest.with_file_name(format!(".{name}.partial"));
+    let result = copy_recursive(src, &temp, progress).and_then(|()| Ok(fs::rename(&temp, dest)?));
+    if result.is_err()
+        && temp.exists()
+        && let Err(e) = delete_recursive(&temp, &Progress::default())
+    {
+        warn!("failed to remove {}: {}", temp.display(), e);
+    }
+    result
+}
+
+fn copy_recursive(src: &Path, dest: &Path, progress: &Progress) -> Result<()> {
+    if src.is_dir() {
+        fs::create_dir(dest)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &dest.join(entry.file_name()), progress)?;
+        }
+    } else {
+        fs::copy(src, dest)?;
+        progress.advance();
+    }
+    Ok(())
+}
+
+fn delete_recursive(path: &Path, progress: &Progress) -> Result<()> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            delete_recursive(&entry?.path(), progress)?;
+        }
+        fs::remove_dir(path)?;
+    } else {
+        fs::remove_file(path)?;
+        progress.advance();
+    }
+    Ok(())
+}
+
+/// Counts the files in a folder and its subfolders, or 1 for a file.
+fn count_files(path: &Path) -> Result<usize> {
+    if !path.is_dir() {
+        return Ok(1);
+    }
+    let mut count = 0;
+    for entry in fs::read_dir(path)? {
+        count += count_files(&entry?.path())?;
+    }
+    Ok(count)
+}
+
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+        bail!("{} is not a valid name", name);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_name() {
+        assert!(check_name("Pokemon (USA).gba").is_ok());
+        assert!(check_name("").is_err());
+        assert!(check_name("..").is_err());
+        assert!(check_name("Roms/GBA").is_err());
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("allium-file-manager-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src/Hacks")).unwrap();
+        fs::create_dir_all(dir.join("dest")).unwrap();
+        fs::write(dir.join("src/Game One.gba"), "one").unwrap();
+        fs::write(dir.join("src/Hacks/Game Two.gba"), "two").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_copy() -> Result<()> {
+        let dir = temp_dir("copy");
+        let progress = Progress::default();
+
+        let copied = copy(&dir.join("src"), &dir.join("dest"), &progress)?;
+        assert_eq!(copied, dir.join("dest/src"));
+        assert_eq!(
+            fs::read_to_string(copied.join("Hacks/Game Two.gba"))?,
+            "two"
+        );
+        assert_eq!(progress.get(), (2, 2));
+        assert_eq!(fs::read_dir(dir.join("dest"))?.count(), 1);
+
+        // An existing file is never overwritten
+        assert!(copy(&dir.join("src"), &dir.join("dest"), &progress).is_err());
+        assert_eq!(fs::read_dir(dir.join("dest"))?.count(), 1);
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_and_delete() -> Result<()> {
+        let dir = temp_dir("move");
+        let progress = Progress::default();
+
+        let moved = move_to(&dir.join("src/Hacks"), &dir.join("dest"), &progress)?;
+        assert_eq!(moved, dir.join("dest/Hacks"));
+        assert!(moved.join("Game Two.gba").exists());
+        assert!(!dir.join("src/Hacks").exists());
+
+        delete(&dir.join("src"), &progress)?;
+        assert!(!dir.join("src").exists());
+        assert_eq!(progress.get(), (1, 1));
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_destination() {
+        let src = Path::new("/mnt/SDCARD/Roms/GBA");
+        assert!(destination(src, Path::new("/mnt/SDCARD/Roms/GBA/Hacks")).is_err());
+        assert!(destination(src, src).is_err());
+    }
+}
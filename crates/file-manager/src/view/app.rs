@@ -0,0 +1,693 @@
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::battery::Battery;
+use common::command::Command;
+use common::constants::ALLIUM_SD_ROOT;
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::{
+    BatteryIndicator, ButtonHint, ButtonIcon, Clock, Dialog, Keyboard, Label, Row, ScrollList, View,
+};
+use embedded_graphics::Drawable;
+use embedded_graphics::prelude::Size;
+use embedded_graphics::primitives::{CornerRadii, Primitive, PrimitiveStyle, RoundedRectangle};
+use log::error;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::oneshot;
+
+use crate::operation::{self, Progress};
+use crate::view::Pane;
+
+/// Something that can be done to the selected file or folder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Copy,
+    Move,
+    Rename,
+    Delete,
+    NewFolder,
+    Paste,
+}
+
+impl Action {
+    fn text(&self, locale: &Locale) -> String {
+        match self {
+            Action::Copy => locale.t("file-manager-copy"),
+            Action::Move => locale.t("file-manager-move"),
+            Action::Rename => locale.t("file-manager-rename"),
+            Action::Delete => locale.t("file-manager-delete"),
+            Action::NewFolder => locale.t("file-manager-new-folder"),
+            Action::Paste => locale.t("file-manager-paste"),
+        }
+    }
+}
+
+/// A copy, move or delete that needs to be confirmed first.
+#[derive(Debug, Clone)]
+struct Pending {
+    action: Action,
+    path: PathBuf,
+    /// Folder to copy or move into.
+    dir: PathBuf,
+}
+
+/// A copy, move or delete running in the background.
+#[derive(Debug)]
+struct Task {
+    pending: Pending,
+    progress: Arc<Progress>,
+    /// Progress last shown in the title.
+    shown: Option<(usize, usize)>,
+    result: oneshot::Receiver<Result<PathBuf>>,
+}
+
+#[derive(Debug)]
+pub struct App<B>
+where
+    B: Battery + 'static,
+{
+    rect: Rect,
+    res: Resources,
+    /// Shows the focused pane's folder, or the result of the last action.
+    title: Label<String>,
+    row: Row<Box<dyn View>>,
+    panes: Vec<Pane>,
+    focused: usize,
+    button_hints: Row<ButtonHint<String>>,
+    menu: Option<ScrollList>,
+    menu_actions: Vec<Action>,
+    /// The action the keyboard is open for, with the keyboard.
+    keyboard: Option<(Action, Keyboard)>,
+    /// The copy, move or delete waiting to be confirmed, with the dialog asking for it.
+    confirm: Option<(Pending, Dialog)>,
+    /// File or folder to copy or move into the next folder, when there's only one pane.
+    clipboard: Option<(Action, PathBuf)>,
+    /// The copy, move or delete that is running. Input is ignored until it's done.
+    task: Option<Task>,
+    dirty: bool,
+    _phantom_battery: PhantomData<B>,
+}
+
+impl<B> App<B>
+where
+    B: Battery + 'static,
+{
+    pub fn new(rect: Rect, res: Resources, battery: B) -> Result<Self> {
+        let Rect { x, y, w, .. } = rect;
+        let styles = res.get::<Stylesheet>();
+
+        let battery_indicator = BatteryIndicator::new(
+            res.clone(),
+            Point::new(0, 0),
+            battery,
+            styles.show_battery_level,
+        );
+
+        let mut children: Vec<Box<dyn View>> = vec![Box::new(battery_indicator)];
+
+        if styles.show_clock {
+            let clock = Clock::new(res.clone(), Point::new(0, 0), Alignment::Right);
+            children.push(Box::new(clock));
+        }
+
+        let row: Row<Box<dyn View>> = Row::new(
+            Point::new(w as i32 - 12, y + 8),
+            children,
+            Alignment::Right,
+            8,
+        );
+
+        let title = Label::new(
+            Point::new(x + 12, y + 8),
+            String::new(),
+            Alignment::Left,
+            Some((w - 24) * 2 / 3),
+        );
+
+        drop(styles);
+
+        let button_hints = Self::button_hints(rect, &res);
+
+        let mut this = Self {
+            rect,
+            res,
+            title,
+            row,
+            panes: Vec::new(),
+            focused: 0,
+            button_hints,
+            menu: None,
+            menu_actions: Vec::new(),
+            keyboard: None,
+            confirm: None,
+            clipboard: None,
+            task: None,
+            dirty: true,
+            _phantom_battery: PhantomData,
+        };
+        let pane = Pane::new(
+            this.pane_rect(0, 1),
+            this.res.clone(),
+            ALLIUM_SD_ROOT.clone(),
+            None,
+        );
+        this.panes.push(pane);
+        this.show_dir();
+        Ok(this)
+    }
+
+    fn button_hints(rect: Rect, res: &Resources) -> Row<ButtonHint<String>> {
+        let Rect { x, y, w, h } = rect;
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let hints = [
+            (Key::A, "file-manager-open"),
+            (Key::X, "file-manager-actions"),
+            (Key::Y, "file-manager-dual-pane"),
+            (Key::B, "button-back"),
+        ];
+        Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            hints
+                .into_iter()
+                .map(|(key, text)| {
+                    ButtonHint::new(
+                        res.clone(),
+                        Point::zero(),
+                        key,
+                        locale.t(text),
+                        Alignment::Right,
+                    )
+                })
+                .collect(),
+            Alignment::Right,
+            12,
+        )
+    }
+
+    /// Area of the `i`th of `count` panes, side by side between the title and button hints.
+    fn pane_rect(&self, i: usize, count: usize) -> Rect {
+        let Rect { x, y, w, h } = self.rect;
+        let styles = self.res.get::<Stylesheet>();
+        let top = 8 + styles.ui_font.size + 8;
+        let bottom = ButtonIcon::diameter(&styles) + 16;
+        let width = w / count as u32;
+        Rect::new(
+            x + (width * i as u32) as i32,
+            y + top as i32,
+            width,
+            h - top - bottom,
+        )
+    }
+
+    fn pane(&self) -> &Pane {
+        &self.panes[self.focused]
+    }
+
+    /// Splits the screen in two panes, or goes back to a single one. Files are copied and moved
+    /// between the two panes.
+    fn toggle_dual_pane(&mut self) {
+        let count = if self.panes.len() == 1 { 2 } else { 1 };
+        let focused = self.pane();
+        let (dir, selected) = (
+            focused.dir().to_path_buf(),
+            focused.selected().map(Path::to_path_buf),
+        );
+        self.panes = (0..count)
+            .map(|i| {
+                Pane::new(
+                    self.pane_rect(i, count),
+                    self.res.clone(),
+                    dir.clone(),
+                    selected.as_deref(),
+                )
+            })
+            .collect();
+        self.focused = 0;
+        self.clipboard = None;
+        self.show_dir();
+        self.set_should_draw();
+    }
+
+    fn show_dir(&mut self) {
+        let path = sd_path(self.pane().dir());
+        self.title.set_text(path);
+    }
+
+    fn show_status(&mut self, key: &str, path: &Path) {
+        let mut map = HashMap::new();
+        map.insert("name".into(), file_name(path).into());
+        let text = self.res.get::<Locale>().ta(key, &map);
+        self.title.set_text(text);
+    }
+
+    fn open_menu(&mut self) {
+        let mut actions = Vec::new();
+        if self.pane().selected().is_some() {
+            actions.extend([Action::Copy, Action::Move, Action::Rename, Action::Delete]);
+        }
+        actions.push(Action::NewFolder);
+        if self.clipboard.is_some() {
+            actions.push(Action::Paste);
+        }
+
+        let Rect { x, y, w, h } = self.rect;
+        let styles = self.res.get::<Stylesheet>();
+        let locale = self.res.get::<Locale>();
+        let entry_height = styles.list_entry_height();
+        let height = actions.len() as u32 * entry_height;
+        let mut menu = ScrollList::new(
+            Rect::new(
+                x + 12 + (w as i32 - 24) / 6,
+                y + (h as i32 - height as i32) / 2,
+                (w - 24) * 2 / 3,
+                height,
+            ),
+            actions.iter().map(|action| action.text(&locale)).collect(),
+            Alignment::Left,
+            entry_height,
+        );
+        menu.set_background_color(Some(StylesheetColor::BackgroundHighlightBlend));
+        self.menu = Some(menu);
+        self.menu_actions = actions;
+    }
+
+    fn select_action(&mut self, action: Action) {
+        let selected = self.pane().selected().map(Path::to_path_buf);
+        match (action, selected) {
+            (Action::NewFolder, _) => {
+                let keyboard = Keyboard::new(self.res.clone(), String::new(), false);
+                self.keyboard = Some((action, keyboard));
+            }
+            (Action::Paste, _) => {
+                if let Some((action, path)) = self.clipboard.clone() {
+                    let dir = self.pane().dir().to_path_buf();
+                    self.ask_confirm(Pending { action, path, dir });
+                }
+            }
+            (Action::Copy | Action::Move, Some(path)) if self.panes.len() == 2 => {
+                let dir = self.panes[1 - self.focused].dir().to_path_buf();
+                self.ask_confirm(Pending { action, path, dir });
+            }
+            (Action::Copy | Action::Move, Some(path)) => {
+                self.show_status("file-manager-clipboard", &path);
+                self.clipboard = Some((action, path));
+            }
+            (Action::Delete, Some(path)) => {
+                let dir = self.pane().dir().to_path_buf();
+                self.ask_confirm(Pending { action, path, dir });
+            }
+            (Action::Rename, Some(path)) => {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let keyboard = Keyboard::new(self.res.clone(), name.to_string(), false);
+                self.keyboard = Some((action, keyboard));
+            }
+            (_, None) => {}
+        }
+    }
+
+    fn ask_confirm(&mut self, pending: Pending) {
+        let key = match pending.action {
+            Action::Copy => "file-manager-confirm-copy",
+            Action::Move => "file-manager-confirm-move",
+            _ => "file-manager-confirm-delete",
+        };
+        let mut map = HashMap::new();
+        map.insert("name".into(), file_name(&pending.path).into());
+        map.insert("dir".into(), sd_path(&pending.dir).into());
+        let locale = self.res.get::<Locale>();
+        let title = locale.ta(key, &map);
+        let message =
+            (pending.action != Action::Delete).then(|| locale.ta("file-manager-destination", &map));
+        drop(locale);
+        let dialog = Dialog::confirm(self.rect, self.res.clone(), title, message);
+        self.confirm = Some((pending, dialog));
+    }
+
+    /// Runs a rename or folder creation, then lists the panes again to show the changes.
+    fn run(&mut self, action: Action, path: &Path, arg: &Path) {
+        let result = match action {
+            Action::Rename => operation::rename(path, &arg.to_string_lossy()),
+            Action::NewFolder => operation::create_dir(path, &arg.to_string_lossy()),
+            _ => unreachable!("copies, moves and deletes run in the background"),
+        };
+        self.finish(action, path, result);
+    }
+
+    /// Starts a copy, move or delete in the background, as it can take a while for big folders.
+    fn spawn(&mut self, pending: Pending) {
+        let progress = Arc::new(Progress::default());
+        let (tx, rx) = oneshot::channel();
+        {
+            let Pending { action, path, dir } = pending.clone();
+            let progress = Arc::clone(&progress);
+            tokio::task::spawn_blocking(move || {
+                let result = match action {
+                    Action::Copy => operation::copy(&path, &dir, &progress),
+                    Action::Move => operation::move_to(&path, &dir, &progress),
+                    _ => operation::delete(&path, &progress).map(|()| path),
+                };
+                let _ = tx.send(result);
+            });
+        }
+        self.task = Some(Task {
+            pending,
+            progress,
+            shown: None,
+            result: rx,
+        });
+    }
+
+    /// Shows how far the running task is in the title.
+    fn show_progress(&mut self) {
+        let Some(task) = self.task.as_mut() else {
+            return;
+        };
+        let progress = task.progress.get();
+        if task.shown == Some(progress) {
+            return;
+        }
+        task.shown = Some(progress);
+
+        let key = match task.pending.action {
+            Action::Copy => "file-manager-copying",
+            Action::Move => "file-manager-moving",
+            _ => "file-manager-deleting",
+        };
+        let (done, total) = progress;
+        let mut map = HashMap::new();
+        map.insert("name".into(), file_name(&task.pending.path).into());
+        map.insert("done".into(), done.into());
+        map.insert("total".into(), total.into());
+        let text = self.res.get::<Locale>().ta(key, &map);
+        self.title.set_text(text);
+    }
+
+    /// Shows the result of an action, and lists the panes again to show the changes.
+    fn finish(&mut self, action: Action, path: &Path, result: Result<PathBuf>) {
+        match result {
+            Ok(changed) => {
+                if matches!(action, Action::Move) {
+                    self.clipboard = None;
+                }
+                let selected = (action != Action::Delete).then_some(changed.as_path());
+                for pane in &mut self.panes {
+                    // Only select what changed in the pane showing it
+                    let selected = selected.filter(|path| path.parent() == Some(pane.dir()));
+                    if let Err(e) = pane.refresh(selected) {
+                        error!("failed to list {}: {}", pane.dir().display(), e);
+                    }
+                }
+                let key = match action {
+                    Action::Copy => "file-manager-copied",
+                    Action::Move => "file-manager-moved",
+                    Action::Delete => "file-manager-deleted",
+                    Action::Rename => "file-manager-renamed",
+                    Action::NewFolder | Action::Paste => "file-manager-created",
+                };
+                self.show_status(key, &changed);
+            }
+            Err(e) => {
+                error!("failed to {:?} {}: {}", action, path.display(), e);
+                let mut map = HashMap::new();
+                map.insert("error".into(), e.to_string().into());
+                let text = self.res.get::<Locale>().ta("file-manager-failed", &map);
+                self.title.set_text(text);
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<B> View for App<B>
+where
+    B: Battery,
+{
+    fn update(&mut self, _dt: Duration) {
+        let Some(task) = self.task.as_mut() else {
+            return;
+        };
+        match task.result.try_recv() {
+            Ok(result) => {
+                let Pending { action, path, .. } = self.task.take().unwrap().pending;
+                self.finish(action, &path, result);
+            }
+            Err(oneshot::error::TryRecvError::Empty) => self.show_progress(),
+            Err(oneshot::error::TryRecvError::Closed) => {
+                error!("file operation panicked");
+                self.task = None;
+            }
+        }
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if self.dirty {
+            display.load(self.bounding_box(styles))?;
+            self.title.set_should_draw();
+            self.row.set_should_draw();
+            self.button_hints.set_should_draw();
+            for pane in &mut self.panes {
+                pane.set_should_draw();
+            }
+            self.dirty = false;
+        }
+
+        let mut drawn = false;
+
+        if self.title.should_draw() {
+            let mut rect = self.title.bounding_box(styles);
+            rect.w = (self.rect.w - 24) * 2 / 3;
+            display.load(rect)?;
+            drawn |= self.title.draw(display, styles)?;
+        }
+        drawn |= self.row.should_draw() && self.row.draw(display, styles)?;
+        for pane in &mut self.panes {
+            drawn |= pane.should_draw() && pane.draw(display, styles)?;
+        }
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        if let Some(menu) = self.menu.as_mut()
+            && menu.should_draw()
+        {
+            let mut rect = menu.bounding_box(styles);
+            rect.y -= 12;
+            rect.h += 24;
+            rect.x -= 24;
+            rect.w += 48;
+            RoundedRectangle::new(
+                rect.into(),
+                CornerRadii::new(Size::new_equal((styles.ui_font.size + 8) / 2)),
+            )
+            .into_styled(PrimitiveStyle::with_fill(
+                StylesheetColor::BackgroundHighlightBlend.to_color(styles),
+            ))
+            .draw(display)?;
+            menu.set_should_draw();
+            drawn |= menu.draw(display, styles)?;
+        }
+
+        if let Some((_, keyboard)) = self.keyboard.as_mut() {
+            drawn |= keyboard.should_draw() && keyboard.draw(display, styles)?;
+        }
+
+        if let Some((_, dialog)) = self.confirm.as_mut() {
+            drawn |= dialog.should_draw() && dialog.draw(display, styles)?;
+        }
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.title.should_draw()
+            || self.row.should_draw()
+            || self.panes.iter().any(View::should_draw)
+            || self.button_hints.should_draw()
+            || self.menu.as_ref().is_some_and(View::should_draw)
+            || self
+                .keyboard
+                .as_ref()
+                .is_some_and(|(_, keyboard)| keyboard.should_draw())
+            || self
+                .confirm
+                .as_ref()
+                .is_some_and(|(_, dialog)| dialog.should_draw())
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        if let Some(menu) = self.menu.as_mut() {
+            menu.set_should_draw();
+        }
+        if let Some((_, keyboard)) = self.keyboard.as_mut() {
+            keyboard.set_should_draw();
+        }
+        if let Some((_, dialog)) = self.confirm.as_mut() {
+            dialog.set_should_draw();
+        }
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if self.task.is_some() {
+            return Ok(true);
+        }
+
+        if let Some((action, keyboard)) = self.keyboard.as_mut() {
+            let action = *action;
+            if !keyboard.handle_key_event(event, commands, bubble).await? {
+                return Ok(false);
+            }
+            while let Some(command) = bubble.pop_front() {
+                match command {
+                    Command::ValueChanged(_, value) => {
+                        let name = PathBuf::from(value.as_string().unwrap().trim());
+                        match action {
+                            Action::Rename => {
+                                if let Some(path) = self.pane().selected().map(Path::to_path_buf) {
+                                    self.run(action, &path, &name);
+                                }
+                            }
+                            _ => {
+                                let dir = self.pane().dir().to_path_buf();
+                                self.run(action, &dir, &name);
+                            }
+                        }
+                    }
+                    Command::CloseView => {
+                        self.keyboard = None;
+                        self.set_should_draw();
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(true);
+        }
+
+        if let Some(menu) = self.menu.as_mut() {
+            match event {
+                KeyEvent::Pressed(Key::A) => {
+                    let action = self.menu_actions[menu.selected()];
+                    self.menu = None;
+                    self.set_should_draw();
+                    self.select_action(action);
+                }
+                KeyEvent::Pressed(Key::B | Key::X) => {
+                    self.menu = None;
+                    self.set_should_draw();
+                }
+                _ => {
+                    menu.handle_key_event(event, commands, bubble).await?;
+                }
+            }
+            return Ok(true);
+        }
+
+        if let Some((pending, dialog)) = self.confirm.as_mut() {
+            let pending = pending.clone();
+            dialog.handle_key_event(event, commands, bubble).await?;
+            while let Some(command) = bubble.pop_front() {
+                match command {
+                    Command::ValueChanged(_, _) => {
+                        self.spawn(pending.clone());
+                    }
+                    Command::CloseView => {
+                        self.confirm = None;
+                        self.set_should_draw();
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::X) => {
+                self.open_menu();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Y) => {
+                self.toggle_dual_pane();
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::Left | Key::Right) if self.panes.len() == 2 => {
+                self.focused = 1 - self.focused;
+                self.show_dir();
+                Ok(true)
+            }
+            _ => {
+                let pane = &mut self.panes[self.focused];
+                if pane
+                    .handle_key_event(event, commands.clone(), bubble)
+                    .await?
+                {
+                    self.show_dir();
+                    Ok(true)
+                } else if event == KeyEvent::Pressed(Key::B) {
+                    commands.send(Command::Exit).await?;
+                    Ok(true)
+                } else {
+                    Ok(false)
+                }
+            }
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        let mut children: Vec<&dyn View> = vec![&self.title, &self.row, &self.button_hints];
+        children.extend(self.panes.iter().map(|pane| pane as &dyn View));
+        children
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        let mut children: Vec<&mut dyn View> =
+            vec![&mut self.title, &mut self.row, &mut self.button_hints];
+        children.extend(self.panes.iter_mut().map(|pane| pane as &mut dyn View));
+        children
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Returns a path as shown to the user, relative to the SD card.
+fn sd_path(path: &Path) -> String {
+    match path.strip_prefix(ALLIUM_SD_ROOT.as_path()) {
+        Ok(path) => format!("/{}", path.display()),
+        Err(_) => path.display().to_string(),
+    }
+}
@@ -0,0 +1,5 @@
+mod app;
+mod pane;
+
+pub use app::App;
+pub use pane::Pane;
@@ -0,0 +1,175 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::{ALLIUM_SD_ROOT, SELECTION_MARGIN};
+use common::geom::{Alignment, Point, Rect};
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ScrollList, View};
+use tokio::sync::mpsc::Sender;
+
+/// Lists the contents of a folder on the SD card.
+#[derive(Debug)]
+pub struct Pane {
+    rect: Rect,
+    dir: PathBuf,
+    entries: Vec<PathBuf>,
+    list: ScrollList,
+    dirty: bool,
+}
+
+impl Pane {
+    pub fn new(rect: Rect, res: Resources, dir: PathBuf, selected: Option<&Path>) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let list = ScrollList::new(
+            Rect::new(x + 12, y + 8, w - 24, h - 8),
+            Vec::new(),
+            Alignment::Left,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+        drop(styles);
+
+        let mut this = Self {
+            rect,
+            dir: ALLIUM_SD_ROOT.clone(),
+            entries: Vec::new(),
+            list,
+            dirty: true,
+        };
+        if this.open_dir(dir, selected).is_err() {
+            // The folder may have been deleted from the other pane
+            this.open_dir(ALLIUM_SD_ROOT.clone(), None).ok();
+        }
+        this
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the selected file or folder.
+    pub fn selected(&self) -> Option<&Path> {
+        self.entries.get(self.list.selected()).map(PathBuf::as_path)
+    }
+
+    /// Lists the folder again after its contents changed, selecting `selected` if given.
+    pub fn refresh(&mut self, selected: Option<&Path>) -> Result<()> {
+        let index = self.list.selected();
+        let dir = self.dir.clone();
+        if !dir.is_dir() {
+            return self.open_dir(ALLIUM_SD_ROOT.clone(), None);
+        }
+        self.open_dir(dir, selected)?;
+        if selected.is_none() && !self.entries.is_empty() {
+            self.list.select(index.min(self.entries.len() - 1));
+        }
+        Ok(())
+    }
+
+    /// Lists the contents of `dir`, selecting `selected` if it's one of its entries.
+    fn open_dir(&mut self, dir: PathBuf, selected: Option<&Path>) -> Result<()> {
+        let mut entries = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|path| (!path.is_dir(), path.to_string_lossy().to_lowercase()));
+
+        let items = entries
+            .iter()
+            .map(|path| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                if path.is_dir() {
+                    format!("{name}/")
+                } else {
+                    name.to_string()
+                }
+            })
+            .collect();
+        self.list.set_items(items, false);
+        if let Some(i) = selected.and_then(|s| entries.iter().position(|path| path == s)) {
+            self.list.select(i);
+        }
+
+        self.dir = dir;
+        self.entries = entries;
+        self.dirty = true;
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Pane {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if self.dirty {
+            display.load(self.rect)?;
+            self.list.set_should_draw();
+            self.dirty = false;
+        }
+
+        Ok(self.list.should_draw() && self.list.draw(display, styles)?)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty || self.list.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                if let Some(dir) = self.selected().map(Path::to_path_buf)
+                    && dir.is_dir()
+                {
+                    self.open_dir(dir, None)?;
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                if self.dir == *ALLIUM_SD_ROOT {
+                    return Ok(false);
+                }
+                if let Some(parent) = self.dir.parent() {
+                    let dir = self.dir.clone();
+                    self.open_dir(parent.to_path_buf(), Some(&dir))?;
+                }
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
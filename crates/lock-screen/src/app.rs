@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+use std::process;
+
+use anyhow::Result;
+use common::command::Command;
+use common::display::Display;
+use common::locale::{Locale, LocaleSettings};
+use common::lock::LockSettings;
+use common::platform::{DefaultPlatform, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::View;
+use embedded_graphics::prelude::*;
+use log::warn;
+use type_map::TypeMap;
+
+use crate::view::lock::Lock;
+
+#[cfg(unix)]
+use tokio::signal::unix::SignalKind;
+
+pub struct App<P>
+where
+    P: Platform,
+{
+    platform: P,
+    display: P::Display,
+    res: Resources,
+    view: Lock,
+}
+
+impl App<DefaultPlatform> {
+    pub async fn new(mut platform: DefaultPlatform) -> Result<Self> {
+        let display = platform.display()?;
+        let rect = display.bounding_box().into();
+
+        let mut res = TypeMap::new();
+        res.insert(Stylesheet::load()?);
+        res.insert(Locale::from_settings(&LocaleSettings::load()?));
+        let res = Resources::new(res);
+
+        Ok(App {
+            platform,
+            display,
+            res: res.clone(),
+            view: Lock::new(rect, res, LockSettings::load()?.pattern),
+        })
+    }
+
+    pub async fn run_event_loop(&mut self) -> Result<()> {
+        // Keep whatever was on screen, to restore it once unlocked
+        self.display.save()?;
+
+        #[cfg(unix)]
+        let mut sigterm = tokio::signal::unix::signal(SignalKind::terminate())?;
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+
+        loop {
+            if self.view.should_draw() && self.view.draw(&mut self.display, &self.res.get())? {
+                self.display.flush()?;
+            }
+
+            #[cfg(unix)]
+            tokio::select! {
+                _ = sigterm.recv() => {
+                    self.handle_command(Command::Exit)?;
+                }
+                Some(command) = rx.recv() => {
+                    self.handle_command(command)?;
+                }
+                event = self.platform.poll() => {
+                    let mut bubble = VecDeque::new();
+                    self.view.handle_key_event(event, tx.clone(), &mut bubble).await?;
+                }
+                else => {}
+            }
+
+            #[cfg(not(unix))]
+            tokio::select! {
+                Some(command) = rx.recv() => {
+                    self.handle_command(command)?;
+                }
+                event = self.platform.poll() => {
+                    let mut bubble = VecDeque::new();
+                    self.view.handle_key_event(event, tx.clone(), &mut bubble).await?;
+                }
+                else => {}
+            }
+        }
+    }
+
+    fn handle_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Exit => {
+                self.display.load(self.display.bounding_box().into())?;
+                self.display.flush()?;
+                process::exit(0);
+            }
+            command => {
+                warn!("unhandled command: {:?}", command);
+            }
+        }
+        Ok(())
+    }
+}
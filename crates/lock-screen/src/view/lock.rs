@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::lock::LockSettings;
+use common::pin::KeyPin;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::{Label, View};
+use embedded_graphics::prelude::*;
+use tokio::sync::mpsc::Sender;
+
+pub struct Lock {
+    rect: Rect,
+    res: Resources,
+    pattern: KeyPin,
+    entered: Vec<Key>,
+    title: Label<String>,
+    progress: Label<String>,
+    message: Label<String>,
+    dirty: bool,
+}
+
+impl Lock {
+    pub fn new(rect: Rect, res: Resources, pattern: KeyPin) -> Self {
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let x = rect.x + rect.w as i32 / 2;
+        let mut y = rect.y + rect.h as i32 / 3;
+
+        let title = Label::new(
+            Point::new(x, y),
+            locale.t("lock-screen-title"),
+            Alignment::Center,
+            None,
+        );
+        y += styles.ui_font.size as i32 + 16;
+
+        let progress = Label::new(Point::new(x, y), String::new(), Alignment::Center, None);
+        y += styles.ui_font.size as i32 + 16;
+
+        let mut message = Label::new(Point::new(x, y), String::new(), Alignment::Center, None);
+        message.color(StylesheetColor::Highlight);
+
+        drop(locale);
+        drop(styles);
+
+        Self {
+            rect,
+            res,
+            pattern,
+            entered: Vec::new(),
+            title,
+            progress,
+            message,
+            dirty: true,
+        }
+    }
+
+    fn update_progress(&mut self) {
+        self.progress.set_text("•".repeat(self.entered.len()));
+        self.dirty = true;
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Lock {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        // The saved framebuffer holds the screen from before locking, so labels are redrawn
+        // over a cleared screen instead of restoring their background.
+        display.clear(styles.background_color)?;
+
+        self.title.draw(display, styles)?;
+        self.progress.draw(display, styles)?;
+        self.message.draw(display, styles)?;
+
+        self.dirty = false;
+        Ok(true)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        _bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        let KeyEvent::Pressed(key) = event else {
+            return Ok(true);
+        };
+        if !LockSettings::is_pattern_key(key) {
+            return Ok(true);
+        }
+
+        self.entered.push(key);
+        if self.entered.len() >= self.pattern.len() {
+            if self.pattern.verify(&self.entered) {
+                commands.send(Command::Exit).await?;
+            } else {
+                self.entered.clear();
+                let locale = self.res.get::<Locale>();
+                self.message.set_text(locale.t("lock-screen-wrong-pattern"));
+            }
+        } else {
+            self.message.set_text(String::new());
+        }
+        self.update_progress();
+
+        Ok(true)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.title, &self.progress, &self.message]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.title, &mut self.progress, &mut self.message]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
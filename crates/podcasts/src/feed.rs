@@ -0,0 +1,193 @@
+use std::process::Stdio;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{Result, bail};
+use common::constants::HTTP_TIMEOUT;
+use serde::Deserialize;
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+
+/// Maximum size of a feed that will be downloaded.
+const MAX_FEED_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Number of bytes read before deciding whether a URL is a feed or a stream.
+const SNIFF_SIZE: usize = 512;
+
+/// A podcast RSS feed.
+#[derive(Debug, Clone)]
+pub struct Feed {
+    pub title: String,
+    pub episodes: Vec<Episode>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Episode {
+    pub title: String,
+    pub url: String,
+}
+
+/// What a URL entered by the user points to.
+#[derive(Debug, Clone)]
+pub enum Source {
+    Feed(Feed),
+    Stream,
+}
+
+#[derive(Debug, Deserialize)]
+struct Rss {
+    channel: Channel,
+}
+
+#[derive(Debug, Deserialize)]
+struct Channel {
+    #[serde(default)]
+    title: String,
+    #[serde(default, rename = "item")]
+    items: Vec<Item>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Item {
+    #[serde(default)]
+    title: String,
+    enclosure: Option<Enclosure>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Enclosure {
+    #[serde(rename = "@url")]
+    url: String,
+}
+
+impl Feed {
+    /// Downloads and parses a feed. The number of bytes downloaded so far is kept in `received`.
+    pub async fn fetch(url: &str, received: &AtomicUsize) -> Result<Self> {
+        match download(url, received).await? {
+            Some(xml) => Self::parse(&xml),
+            None => bail!("{} is not a feed", url),
+        }
+    }
+
+    /// Parses an RSS feed. Items without an audio enclosure are skipped.
+    pub fn parse(xml: &str) -> Result<Self> {
+        let rss: Rss = quick_xml::de::from_str(xml)?;
+        let episodes = rss
+            .channel
+            .items
+            .into_iter()
+            .filter_map(|item| {
+                item.enclosure.map(|enclosure| Episode {
+                    title: item.title,
+                    url: enclosure.url,
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            title: rss.channel.title,
+            episodes,
+        })
+    }
+}
+
+impl Source {
+    /// Determines whether a URL is a podcast feed or an audio stream. The number of bytes
+    /// downloaded so far is kept in `received`.
+    pub async fn probe(url: &str, received: &AtomicUsize) -> Result<Self> {
+        match download(url, received).await? {
+            Some(xml) => Ok(Source::Feed(Feed::parse(&xml)?)),
+            None => Ok(Source::Stream),
+        }
+    }
+}
+
+/// Downloads a URL, returning `None` if the response doesn't look like XML.
+/// Audio streams never finish downloading, so only the start of the response
+/// is read before deciding. A server that stops responding gives up after `HTTP_TIMEOUT`, without
+/// retrying.
+async fn download(url: &str, received: &AtomicUsize) -> Result<Option<String>> {
+    let mut child = Command::new("wget")
+        .arg("-q")
+        .arg("-T")
+        .arg(HTTP_TIMEOUT.as_secs().to_string())
+        .args(["-t", "1", "-O", "-", url])
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    let Some(stdout) = child.stdout.take() else {
+        bail!("failed to read from wget");
+    };
+    let mut reader = stdout.take(MAX_FEED_SIZE);
+
+    let mut body = Vec::new();
+    let mut buf = [0; SNIFF_SIZE];
+    while body.len() < SNIFF_SIZE {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&buf[..n]);
+        received.store(body.len(), Ordering::Relaxed);
+    }
+    if body.is_empty() {
+        let status = child.wait().await?;
+        bail!("no response from {}: {}", url, status);
+    }
+
+    let start = body.strip_prefix(b"\xef\xbb\xbf").unwrap_or(&body[..]);
+    if start.iter().find(|b| !b.is_ascii_whitespace()) != Some(&b'<') {
+        return Ok(None);
+    }
+
+    let mut buf = [0; 16 * 1024];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&buf[..n]);
+        received.store(body.len(), Ordering::Relaxed);
+    }
+    Ok(Some(String::from_utf8_lossy(&body).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_feed() {
+        let feed = Feed::parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+  <channel>
+    <title>Podcast</title>
+    <link>https://example.com</link>
+    <itunes:author>Someone</itunes:author>
+    <image>
+      <url>https://example.com/cover.png</url>
+      <title>Cover</title>
+    </image>
+    <item>
+      <title>Episode 2</title>
+      <enclosure url="https://example.com/2.mp3" length="1" type="audio/mpeg"/>
+    </item>
+    <item>
+      <title>Announcement</title>
+    </item>
+    <item>
+      <title>Episode 1</title>
+      <itunes:duration>12:34</itunes:duration>
+      <enclosure url="https://example.com/1.mp3" length="1" type="audio/mpeg"/>
+    </item>
+  </channel>
+</rss>"#,
+        )
+        .unwrap();
+
+        assert_eq!(feed.title, "Podcast");
+        assert_eq!(feed.episodes.len(), 2);
+        assert_eq!(feed.episodes[0].title, "Episode 2");
+        assert_eq!(feed.episodes[1].url, "https://example.com/1.mp3");
+    }
+}
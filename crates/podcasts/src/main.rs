@@ -0,0 +1,21 @@
+mod feed;
+mod player;
+mod podcasts;
+mod view;
+
+use anyhow::Result;
+
+use common::platform::{DefaultPlatform, Platform};
+use simple_logger::SimpleLogger;
+
+use crate::podcasts::PodcastsApp;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    SimpleLogger::new().env().init().unwrap();
+
+    let platform = DefaultPlatform::new()?;
+    let mut app = PodcastsApp::new(platform)?;
+    app.run_event_loop().await?;
+    Ok(())
+}
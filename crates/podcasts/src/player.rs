@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Instant;
+
+use anyhow::Result;
+use chrono::Duration;
+use common::constants::{ALLIUM_FFPLAY_DIR, ALLIUM_SD_ROOT};
+use log::{info, warn};
+use tokio::process::{Child, Command};
+
+/// Plays an audio URL in the background using the bundled ffplay.
+#[derive(Debug)]
+pub struct Player {
+    pub url: String,
+    pub title: String,
+    /// Live streams can't be resumed, so their position isn't saved.
+    pub is_stream: bool,
+    child: Child,
+    start: Duration,
+    started_at: Instant,
+}
+
+impl Player {
+    /// Starts playing a URL from the given position, or as a live stream if
+    /// there is no position.
+    pub fn play(url: String, title: String, start: Option<Duration>) -> Result<Self> {
+        let is_stream = start.is_none();
+        let start = start.unwrap_or_else(Duration::zero);
+        info!("playing {} from {}s", url, start.num_seconds());
+
+        // ffplay needs exclusive access to the audio device
+        run_script("stop_audioserver.sh");
+
+        let ffplay = ALLIUM_FFPLAY_DIR.join("bin/ffplay");
+        let program = if ffplay.exists() {
+            ffplay
+        } else {
+            PathBuf::from("ffplay")
+        };
+        let mut command = Command::new(program);
+        command.args(["-nodisp", "-autoexit", "-loglevel", "error"]);
+        if !is_stream {
+            command.arg("-ss").arg(start.num_seconds().to_string());
+        }
+        let child = command
+            .arg("-i")
+            .arg(&url)
+            .env("HOME", ALLIUM_FFPLAY_DIR.as_path())
+            .env("LD_LIBRARY_PATH", ALLIUM_FFPLAY_DIR.join("libs"))
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        Ok(Self {
+            url,
+            title,
+            is_stream,
+            child,
+            start,
+            started_at: Instant::now(),
+        })
+    }
+
+    /// Current playback position, estimated from the time since playback started.
+    pub fn position(&self) -> Duration {
+        self.start + Duration::from_std(self.started_at.elapsed()).unwrap_or_default()
+    }
+
+    /// Whether ffplay has exited, i.e. the end of the audio has been reached.
+    pub fn is_finished(&mut self) -> bool {
+        self.child.try_wait().ok().flatten().is_some()
+    }
+
+    /// Stops playback, returning the position it was stopped at.
+    pub fn stop(mut self) -> Duration {
+        let position = self.position();
+        if let Err(e) = self.child.start_kill() {
+            warn!("failed to stop ffplay: {}", e);
+        }
+        run_script("start_audioserver.sh");
+        position
+    }
+}
+
+fn run_script(name: &str) {
+    let script = ALLIUM_SD_ROOT.join(".tmp_update/script").join(name);
+    if script.exists()
+        && let Err(e) = std::process::Command::new(&script).status()
+    {
+        warn!("failed to run {}: {}", script.display(), e);
+    }
+}
@@ -0,0 +1,5 @@
+mod app;
+mod podcasts;
+
+pub use app::App;
+pub use podcasts::Podcasts;
@@ -0,0 +1,592 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration as StdDuration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Duration;
+use common::command::{Command, Value};
+use common::constants::SELECTION_MARGIN;
+use common::database::{Database, Podcast};
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Keyboard, Label, NullView, Row, SettingsList, View};
+use log::warn;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::feed::{Feed, Source};
+use crate::player::Player;
+
+/// How often the playback position is saved while playing.
+const SAVE_INTERVAL: StdDuration = StdDuration::from_secs(10);
+
+/// A feed being downloaded in the background, so that playback and the list keep going while a
+/// slow server responds.
+#[derive(Debug)]
+struct Download {
+    /// The podcast being opened, or `None` if the URL is being added.
+    podcast: Option<Podcast>,
+    url: String,
+    /// Number of bytes downloaded so far.
+    received: Arc<AtomicUsize>,
+    result: oneshot::Receiver<Result<Source>>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Debug)]
+pub struct Podcasts {
+    rect: Rect,
+    res: Resources,
+    podcasts: Vec<Podcast>,
+    feed: Option<Feed>,
+    player: Option<Player>,
+    download: Option<Download>,
+    since_save: StdDuration,
+    list: SettingsList,
+    status: Label<String>,
+    keyboard: Option<Keyboard>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl Podcasts {
+    pub fn new(rect: Rect, res: Resources) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y,
+                w - 24,
+                h - 8 - styles.ui_font.size - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            Vec::new(),
+            Vec::new(),
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let status = Label::new(
+            Point::new(
+                x + 12,
+                y + h as i32
+                    - 8
+                    - ButtonIcon::diameter(&styles) as i32
+                    - 8
+                    - styles.ui_font.size as i32,
+            ),
+            String::new(),
+            Alignment::Left,
+            Some(w - 24),
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-select"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::X,
+                    locale.t("podcasts-button-remove"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+
+        let mut this = Self {
+            rect,
+            res,
+            podcasts: Vec::new(),
+            feed: None,
+            player: None,
+            download: None,
+            since_save: StdDuration::ZERO,
+            list,
+            status,
+            keyboard: None,
+            button_hints,
+            dirty: true,
+        };
+        this.show_podcasts()?;
+
+        Ok(this)
+    }
+
+    fn show_podcasts(&mut self) -> Result<()> {
+        self.feed = None;
+        self.podcasts = self.res.get::<Database>().select_podcasts()?;
+
+        let locale = self.res.get::<Locale>();
+        let mut labels = self
+            .podcasts
+            .iter()
+            .map(|p| p.title.clone())
+            .collect::<Vec<_>>();
+        let mut values = self
+            .podcasts
+            .iter()
+            .map(|p| {
+                if p.is_stream {
+                    Box::new(Label::new(
+                        Point::zero(),
+                        locale.t("podcasts-stream"),
+                        Alignment::Right,
+                        None,
+                    )) as Box<dyn View>
+                } else {
+                    Box::new(NullView) as Box<dyn View>
+                }
+            })
+            .collect::<Vec<_>>();
+        labels.push(locale.t("podcasts-add"));
+        values.push(Box::new(NullView));
+        drop(locale);
+
+        self.list.set_items(labels, values);
+        self.list.select(0);
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Downloads a feed or probes a URL in the background. `podcast` is opened once its feed is
+    /// downloaded, or the URL is added if there's no podcast.
+    fn start_download(&mut self, podcast: Option<Podcast>, url: String) {
+        self.cancel_download();
+
+        let received = Arc::new(AtomicUsize::new(0));
+        let (tx, result) = oneshot::channel();
+        let task = tokio::spawn({
+            let url = url.clone();
+            let received = Arc::clone(&received);
+            let is_feed = podcast.is_some();
+            async move {
+                let source = if is_feed {
+                    Feed::fetch(&url, &received).await.map(Source::Feed)
+                } else {
+                    Source::probe(&url, &received).await
+                };
+                tx.send(source).ok();
+            }
+        });
+        self.download = Some(Download {
+            podcast,
+            url,
+            received,
+            result,
+            task,
+        });
+        self.update_download_status();
+    }
+
+    /// Stops the download in progress, if any. Dropping it kills `wget`.
+    fn cancel_download(&mut self) {
+        if let Some(download) = self.download.take() {
+            download.task.abort();
+            self.status.set_text(String::new());
+        }
+    }
+
+    /// Opens the feed or adds the URL once its download finishes.
+    fn check_download(&mut self) -> Result<()> {
+        let Some(download) = self.download.as_mut() else {
+            return Ok(());
+        };
+        let result = match download.result.try_recv() {
+            Ok(result) => result,
+            Err(oneshot::error::TryRecvError::Empty) => {
+                self.update_download_status();
+                return Ok(());
+            }
+            Err(oneshot::error::TryRecvError::Closed) => Err(anyhow::anyhow!("download stopped")),
+        };
+        let Some(Download { podcast, url, .. }) = self.download.take() else {
+            return Ok(());
+        };
+        self.status.set_text(String::new());
+
+        match (podcast, result) {
+            (Some(_), Ok(Source::Feed(feed))) => {
+                self.feed = Some(feed);
+                self.show_episodes()?;
+                self.list.select(0);
+            }
+            (None, Ok(source)) => self.add(url, source)?,
+            (_, Ok(Source::Stream)) => {
+                warn!("{} is no longer a feed", url);
+                self.show_error(&url, "not a feed");
+            }
+            (_, Err(e)) => {
+                warn!("failed to load {}: {}", url, e);
+                self.show_error(&url, &e.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn update_download_status(&mut self) {
+        let Some(download) = self.download.as_ref() else {
+            return;
+        };
+        let mut map = HashMap::new();
+        map.insert("url".into(), download.url.clone().into());
+        map.insert(
+            "kb".into(),
+            (download.received.load(Ordering::Relaxed) / 1024).into(),
+        );
+        let text = self.res.get::<Locale>().ta("podcasts-loading", &map);
+        self.status.set_text(text);
+    }
+
+    /// Lists the episodes of the open feed, keeping the current selection.
+    fn show_episodes(&mut self) -> Result<()> {
+        let Some(feed) = self.feed.as_ref() else {
+            return Ok(());
+        };
+
+        let database = self.res.get::<Database>();
+        let mut values = Vec::with_capacity(feed.episodes.len());
+        for episode in &feed.episodes {
+            let position = database.get_media_position(&episode.url)?;
+            values.push(if position > Duration::zero() {
+                Box::new(Label::new(
+                    Point::zero(),
+                    format_position(position),
+                    Alignment::Right,
+                    None,
+                )) as Box<dyn View>
+            } else {
+                Box::new(NullView) as Box<dyn View>
+            });
+        }
+        drop(database);
+
+        let selected = self
+            .list
+            .selected()
+            .min(feed.episodes.len().saturating_sub(1));
+        self.list.set_items(
+            feed.episodes.iter().map(|e| e.title.clone()).collect(),
+            values,
+        );
+        self.list.select(selected);
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn add(&mut self, url: String, source: Source) -> Result<()> {
+        let podcast = match source {
+            Source::Feed(feed) => Podcast {
+                title: if feed.title.is_empty() {
+                    url.clone()
+                } else {
+                    feed.title
+                },
+                url,
+                is_stream: false,
+            },
+            Source::Stream => Podcast {
+                title: url
+                    .split_once("://")
+                    .map_or(url.as_str(), |(_, rest)| rest)
+                    .to_string(),
+                url,
+                is_stream: true,
+            },
+        };
+
+        self.res.get::<Database>().add_podcast(&podcast)?;
+        self.show_podcasts()
+    }
+
+    /// Plays the given URL, or stops it if it is already playing.
+    fn toggle_playback(&mut self, url: &str, title: &str, is_stream: bool) -> Result<()> {
+        let was_playing = self.player.as_ref().is_some_and(|p| p.url == url);
+        self.stop()?;
+
+        if !was_playing {
+            let start = if is_stream {
+                None
+            } else {
+                Some(self.res.get::<Database>().get_media_position(url)?)
+            };
+            self.player = Some(Player::play(url.to_string(), title.to_string(), start)?);
+            self.since_save = StdDuration::ZERO;
+            self.update_status();
+        }
+
+        Ok(())
+    }
+
+    /// Stops playback, saving the position.
+    pub fn stop(&mut self) -> Result<()> {
+        if let Some(player) = self.player.take() {
+            let url = player.url.clone();
+            let is_stream = player.is_stream;
+            let position = player.stop();
+            if !is_stream {
+                self.res
+                    .get::<Database>()
+                    .set_media_position(&url, position)?;
+            }
+            self.update_status();
+            self.show_episodes()?;
+        }
+        Ok(())
+    }
+
+    fn update_status(&mut self) {
+        let text = match self.player.as_ref() {
+            Some(player) => {
+                let mut map = HashMap::new();
+                map.insert("title".into(), player.title.clone().into());
+                map.insert("position".into(), format_position(player.position()).into());
+                self.res.get::<Locale>().ta("podcasts-playing", &map)
+            }
+            None => String::new(),
+        };
+        self.status.set_text(text);
+    }
+
+    fn show_error(&mut self, url: &str, error: &str) {
+        let mut map = HashMap::new();
+        map.insert("url".into(), url.to_string().into());
+        map.insert("error".into(), error.to_string().into());
+        let text = self.res.get::<Locale>().ta("podcasts-error", &map);
+        self.status.set_text(text);
+    }
+
+    fn update_playback(&mut self, dt: StdDuration) -> Result<()> {
+        let Some(player) = self.player.as_mut() else {
+            return Ok(());
+        };
+
+        if player.is_finished() {
+            // Start finished episodes from the beginning next time
+            if let Some(player) = self.player.take() {
+                let url = player.url.clone();
+                let is_stream = player.is_stream;
+                player.stop();
+                if !is_stream {
+                    self.res
+                        .get::<Database>()
+                        .set_media_position(&url, Duration::zero())?;
+                }
+                self.show_episodes()?;
+            }
+        } else if !player.is_stream {
+            self.since_save += dt;
+            if self.since_save >= SAVE_INTERVAL {
+                self.since_save = StdDuration::ZERO;
+                self.res
+                    .get::<Database>()
+                    .set_media_position(&player.url, player.position())?;
+            }
+        }
+
+        self.update_status();
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Podcasts {
+    fn update(&mut self, dt: StdDuration) {
+        if let Err(e) = self.update_playback(dt) {
+            warn!("failed to update playback: {}", e);
+        }
+        if let Err(e) = self.check_download() {
+            warn!("failed to open download: {}", e);
+        }
+        self.children_mut().iter_mut().for_each(|c| c.update(dt));
+    }
+
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.list.set_should_draw();
+            self.status.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        if self.status.should_draw() {
+            display.load(Rect::new(
+                self.rect.x,
+                self.status.bounding_box(styles).y,
+                self.rect.w,
+                styles.ui_font.size,
+            ))?;
+            drawn |= self.status.draw(display, styles)?;
+        }
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            if drawn {
+                keyboard.set_should_draw();
+            }
+            drawn |= keyboard.should_draw() && keyboard.draw(display, styles)?;
+        }
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.list.should_draw()
+            || self.status.should_draw()
+            || self.button_hints.should_draw()
+            || self.keyboard.as_ref().is_some_and(|k| k.should_draw())
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            keyboard.set_should_draw();
+        }
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(keyboard) = self.keyboard.as_mut()
+            && keyboard
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+        {
+            let mut url = None;
+            bubble.retain_mut(|c| match c {
+                Command::ValueChanged(_, val) => {
+                    if let Value::String(val) = val {
+                        url = Some(val.clone());
+                    }
+                    false
+                }
+                Command::CloseView => {
+                    self.keyboard = None;
+                    false
+                }
+                _ => true,
+            });
+            if let Some(url) = url.filter(|url| !url.trim().is_empty()) {
+                self.start_download(None, url.trim().to_string());
+            }
+            if self.keyboard.is_none() {
+                commands.send(Command::Redraw).await?;
+            }
+            return Ok(true);
+        }
+
+        let selected = self.list.selected();
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                if let Some(feed) = self.feed.as_ref() {
+                    if let Some(episode) = feed.episodes.get(selected).cloned() {
+                        self.toggle_playback(&episode.url, &episode.title, false)?;
+                    }
+                } else if let Some(podcast) = self.podcasts.get(selected).cloned() {
+                    if podcast.is_stream {
+                        self.toggle_playback(&podcast.url, &podcast.title, true)?;
+                    } else {
+                        let url = podcast.url.clone();
+                        self.start_download(Some(podcast), url);
+                    }
+                } else {
+                    self.keyboard = Some(Keyboard::new(self.res.clone(), String::new(), false));
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::X) => {
+                if self.feed.is_none()
+                    && let Some(podcast) = self.podcasts.get(selected).cloned()
+                {
+                    if self.player.as_ref().is_some_and(|p| p.url == podcast.url) {
+                        self.stop()?;
+                    }
+                    self.res.get::<Database>().delete_podcast(&podcast.url)?;
+                    self.show_podcasts()?;
+                    self.list.select(selected.min(self.podcasts.len()));
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                if self.download.is_some() {
+                    self.cancel_download();
+                } else if self.feed.is_some() {
+                    self.show_podcasts()?;
+                } else {
+                    self.stop()?;
+                    commands.send(Command::Exit).await?;
+                }
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.status, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.status, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+fn format_position(position: Duration) -> String {
+    let seconds = position.num_seconds();
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
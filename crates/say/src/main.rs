@@ -43,7 +43,7 @@ fn say(text: &str, bg: bool) -> Result<()> {
 
     let text_style = FontTextStyleBuilder::<Color>::new(styles.ui_font.font())
         .text_color(styles.foreground_color)
-        .font_fallback(styles.cjk_font.font())
+        .font_fallbacks(styles.fallbacks())
         .font_size(styles.ui_font.size)
         .build();
 
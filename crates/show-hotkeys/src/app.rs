@@ -35,7 +35,7 @@ impl App<DefaultPlatform> {
 
         let mut res = TypeMap::new();
         res.insert(Stylesheet::load()?);
-        res.insert(Locale::new(&LocaleSettings::load()?.lang));
+        res.insert(Locale::from_settings(&LocaleSettings::load()?));
         let res = Resources::new(res);
 
         Ok(App {
@@ -4,8 +4,11 @@ use anyhow::Result;
 use async_trait::async_trait;
 use common::command::Command;
 use common::geom::{Alignment, Point, Rect};
+use common::hotkeys::{HotkeySettings, REWIND_KEY};
 use common::locale::Locale;
+use common::macros::MacroSettings;
 use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::power::PowerSettings;
 use common::resources::Resources;
 use common::stylesheet::Stylesheet;
 use common::view::{Label, View};
@@ -38,14 +41,24 @@ impl Hotkeys {
         );
         y += styles.ui_font.size as i32 + 8;
 
-        let mut global_hotkeys = Vec::with_capacity(5);
-        let global_hotkeys_data = [
+        let mut global_hotkeys = Vec::with_capacity(8);
+        let mut global_hotkeys_data = vec![
             (Key::Power, locale.t("hotkeys-screenshot")),
             (Key::Up, locale.t("hotkeys-brightness-up")),
             (Key::Down, locale.t("hotkeys-brightness-down")),
             (Key::Right, locale.t("hotkeys-volume-up")),
             (Key::Left, locale.t("hotkeys-volume-down")),
+            (REWIND_KEY, locale.t("hotkeys-rewind")),
         ];
+        let power_settings = PowerSettings::load().unwrap_or_default();
+        if let Some(key) =
+            power_settings.battery_saver_key(&MacroSettings::load().unwrap_or_default())
+        {
+            global_hotkeys_data.push((key, locale.t("hotkeys-battery-saver")));
+        }
+        if power_settings.fast_forward_hotkeys {
+            global_hotkeys_data.push((Key::R, locale.t("hotkeys-fast-forward")));
+        }
         for (key, label) in global_hotkeys_data {
             global_hotkeys.push(ButtonChordHint::new(
                 Point::new(x, y),
@@ -66,11 +79,34 @@ impl Hotkeys {
         );
         y += styles.ui_font.size as i32 + 8;
 
-        let mut ingame_hotkeys = Vec::with_capacity(2);
-        let ingame_hotkeys_data = [
+        let mut ingame_hotkeys = Vec::with_capacity(4);
+        let mut ingame_hotkeys_data = vec![
             (Key::Start, locale.t("hotkeys-toggle-aspect-ratio")),
             (Key::X, locale.t("hotkeys-toggle-fps")),
+            (Key::Select, locale.t("hotkeys-record-macro")),
         ];
+        let macro_settings = MacroSettings::load().unwrap_or_default();
+        if !macro_settings.steps.is_empty() {
+            ingame_hotkeys_data.push((macro_settings.hotkey, locale.t("hotkeys-play-macro")));
+        }
+        let hotkey_settings = HotkeySettings::load().unwrap_or_default();
+        if hotkey_settings.enabled {
+            let reserved = HotkeySettings::reserved_keys(
+                &PowerSettings::load().unwrap_or_default(),
+                &macro_settings,
+            );
+            let labels = [
+                "hotkeys-retroarch-menu",
+                "hotkeys-save-state",
+                "hotkeys-load-state",
+                "hotkeys-fast-forward",
+            ];
+            for ((_, key), label) in hotkey_settings.bindings(&reserved).into_iter().zip(labels) {
+                if let Some(key) = key {
+                    ingame_hotkeys_data.push((key, locale.t(label)));
+                }
+            }
+        }
         for (key, label) in ingame_hotkeys_data {
             ingame_hotkeys.push(ButtonChordHint::new(
                 Point::new(x, y),
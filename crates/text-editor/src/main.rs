@@ -0,0 +1,20 @@
+mod syntax;
+mod text_editor;
+mod view;
+
+use anyhow::Result;
+
+use common::platform::{DefaultPlatform, Platform};
+use simple_logger::SimpleLogger;
+
+use crate::text_editor::TextEditorApp;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    SimpleLogger::new().env().init().unwrap();
+
+    let platform = DefaultPlatform::new()?;
+    let mut app = TextEditorApp::new(platform)?;
+    app.run_event_loop().await?;
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use std::ffi::OsStr;
+use std::ops::Range;
+use std::path::Path;
+
+use common::stylesheet::StylesheetColor;
+
+/// File formats with syntax highlighting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// RetroArch .cfg, .ini and .conf files, and TOML, which all use `key = value` lines.
+    KeyValue,
+    Json,
+    Shell,
+    Plain,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .unwrap_or_default()
+            .to_lowercase();
+        match extension.as_str() {
+            "cfg" | "ini" | "conf" | "toml" | "opt" => Self::KeyValue,
+            "json" => Self::Json,
+            "sh" => Self::Shell,
+            _ => Self::Plain,
+        }
+    }
+}
+
+/// Kinds of text that are highlighted differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Text,
+    Comment,
+    Section,
+    Key,
+    String,
+    Number,
+}
+
+impl Token {
+    pub fn color(self) -> StylesheetColor {
+        match self {
+            Token::Text => StylesheetColor::Foreground,
+            Token::Comment => StylesheetColor::Disabled,
+            Token::Section => StylesheetColor::Highlight,
+            Token::Key => StylesheetColor::ButtonY,
+            Token::String => StylesheetColor::ButtonA,
+            Token::Number => StylesheetColor::ButtonX,
+        }
+    }
+}
+
+/// Splits a line into highlighted spans. The spans cover the whole line, in order.
+pub fn highlight(line: &str, format: Format) -> Vec<(Range<usize>, Token)> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+
+    let mut spans = Vec::new();
+    match format {
+        Format::Plain => {}
+        Format::KeyValue | Format::Shell => {
+            if trimmed.starts_with('#') || trimmed.starts_with(';') {
+                spans.push((indent..line.len(), Token::Comment));
+            } else if format == Format::KeyValue && trimmed.starts_with('[') {
+                spans.push((indent..line.len(), Token::Section));
+            } else if let Some(eq) = line.find('=') {
+                spans.push((indent..eq, Token::Key));
+                let value = line[eq + 1..].trim_start();
+                let start = line.len() - value.len();
+                spans.push((start..line.len(), value_token(value.trim_end())));
+            }
+        }
+        Format::Json => {
+            if let Some(colon) = line.find("\":") {
+                spans.push((indent..colon + 1, Token::Key));
+                let value = line[colon + 2..].trim_start();
+                let start = line.len() - value.len();
+                let value = value.trim_end().trim_end_matches(',');
+                spans.push((start..start + value.len(), value_token(value)));
+            } else if trimmed.starts_with('"') {
+                let value = trimmed.trim_end().trim_end_matches(',');
+                spans.push((indent..indent + value.len(), Token::String));
+            }
+        }
+    }
+
+    fill_gaps(line, spans)
+}
+
+fn value_token(value: &str) -> Token {
+    if value.starts_with('"') || value.starts_with('\'') {
+        Token::String
+    } else if value.parse::<f64>().is_ok() || value == "true" || value == "false" {
+        Token::Number
+    } else {
+        Token::Text
+    }
+}
+
+/// Covers the parts of the line between spans with plain text spans.
+fn fill_gaps(line: &str, spans: Vec<(Range<usize>, Token)>) -> Vec<(Range<usize>, Token)> {
+    let mut filled = Vec::with_capacity(spans.len() * 2 + 1);
+    let mut end = 0;
+    for (range, token) in spans {
+        if range.start > end {
+            filled.push((end..range.start, Token::Text));
+        }
+        end = range.end;
+        if !range.is_empty() {
+            filled.push((range, token));
+        }
+    }
+    if end < line.len() {
+        filled.push((end..line.len(), Token::Text));
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(line: &str, format: Format) -> Vec<(&str, Token)> {
+        highlight(line, format)
+            .into_iter()
+            .map(|(range, token)| (&line[range], token))
+            .collect()
+    }
+
+    #[test]
+    fn test_key_value() {
+        assert_eq!(
+            tokens("video_scale = \"2\"", Format::KeyValue),
+            vec![
+                ("video_scale ", Token::Key),
+                ("= ", Token::Text),
+                ("\"2\"", Token::String),
+            ]
+        );
+        assert_eq!(
+            tokens("  # comment", Format::KeyValue),
+            vec![("  ", Token::Text), ("# comment", Token::Comment)]
+        );
+        assert_eq!(
+            tokens("[cores.gpsp]", Format::KeyValue),
+            vec![("[cores.gpsp]", Token::Section)]
+        );
+        assert_eq!(
+            tokens("swap = true", Format::KeyValue),
+            vec![
+                ("swap ", Token::Key),
+                ("= ", Token::Text),
+                ("true", Token::Number),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            tokens("  \"size\": 24,", Format::Json),
+            vec![
+                ("  ", Token::Text),
+                ("\"size\"", Token::Key),
+                (": ", Token::Text),
+                ("24", Token::Number),
+                (",", Token::Text),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plain() {
+        assert_eq!(tokens("a = b", Format::Plain), vec![("a = b", Token::Text)]);
+        assert!(highlight("", Format::KeyValue).is_empty());
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::VecDeque;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::{ALLIUM_SD_ROOT, SELECTION_MARGIN};
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Row, ScrollList, View};
+use tokio::sync::mpsc::Sender;
+
+/// Extensions of files that can be opened in the editor.
+const EDITABLE_EXTENSIONS: &[&str] = &[
+    "cfg", "conf", "ini", "json", "log", "md", "opt", "sh", "toml", "txt",
+];
+
+/// Lists the folders and editable files on the SD card.
+#[derive(Debug)]
+pub struct Browser {
+    rect: Rect,
+    dir: PathBuf,
+    entries: Vec<PathBuf>,
+    list: ScrollList,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl Browser {
+    pub fn new(rect: Rect, res: Resources) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let list = ScrollList::new(
+            Rect::new(
+                x + 12,
+                y + 8,
+                w - 24,
+                h - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            Vec::new(),
+            Alignment::Left,
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("text-editor-open"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+
+        let mut this = Self {
+            rect,
+            dir: ALLIUM_SD_ROOT.clone(),
+            entries: Vec::new(),
+            list,
+            button_hints,
+            dirty: true,
+        };
+        this.open_dir(ALLIUM_SD_ROOT.clone(), None)?;
+        Ok(this)
+    }
+
+    /// Returns the selected file, or `None` if a folder is selected.
+    pub fn selected_file(&self) -> Option<PathBuf> {
+        self.entries
+            .get(self.list.selected())
+            .filter(|path| path.is_file())
+            .cloned()
+    }
+
+    /// Lists the contents of `dir`, selecting `selected` if it's one of its entries.
+    fn open_dir(&mut self, dir: PathBuf, selected: Option<&Path>) -> Result<()> {
+        let mut entries = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir() || is_editable(path))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|path| (!path.is_dir(), path.to_string_lossy().to_lowercase()));
+
+        let items = entries
+            .iter()
+            .map(|path| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                if path.is_dir() {
+                    format!("{name}/")
+                } else {
+                    name.to_string()
+                }
+            })
+            .collect();
+        self.list.set_items(items, false);
+        if let Some(i) = selected.and_then(|s| entries.iter().position(|path| path == s)) {
+            self.list.select(i);
+        }
+
+        self.dir = dir;
+        self.entries = entries;
+        self.dirty = true;
+        Ok(())
+    }
+}
+
+fn is_editable(path: &Path) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|ext| EDITABLE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+#[async_trait(?Send)]
+impl View for Browser {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if self.dirty {
+            display.load(self.rect)?;
+            self.list.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+        }
+
+        let mut drawn = false;
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty || self.list.should_draw() || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                if let Some(dir) = self.entries.get(self.list.selected()).cloned()
+                    && dir.is_dir()
+                {
+                    self.open_dir(dir, None)?;
+                }
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                if self.dir == *ALLIUM_SD_ROOT {
+                    commands.send(Command::Exit).await?;
+                } else if let Some(parent) = self.dir.parent() {
+                    let dir = self.dir.clone();
+                    self.open_dir(parent.to_path_buf(), Some(&dir))?;
+                }
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,445 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::ErrorKind;
+use std::mem;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::display::Display;
+use common::display::font::FontTextStyleBuilder;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::{ButtonHint, ButtonIcon, Keyboard, Label, Row, View};
+use embedded_graphics::Drawable;
+use embedded_graphics::prelude::{Dimensions, Size};
+use embedded_graphics::primitives::{Primitive, PrimitiveStyle, Rectangle, RoundedRectangle};
+use embedded_graphics::text::Text;
+use log::{error, info, warn};
+use tokio::sync::mpsc::Sender;
+
+use crate::syntax::{self, Format};
+
+/// Edits a file line by line. Each line is edited with the on-screen keyboard.
+#[derive(Debug)]
+pub struct Editor {
+    rect: Rect,
+    res: Resources,
+    path: PathBuf,
+    format: Format,
+    lines: Vec<String>,
+    /// Line ending used by the file, kept when saving.
+    line_ending: &'static str,
+    trailing_newline: bool,
+    selected: usize,
+    top: usize,
+    modified: bool,
+    /// Whether the file couldn't be read as UTF-8 text, so saving it would corrupt it.
+    read_only: bool,
+    /// Whether the original file has been backed up this session.
+    backed_up: bool,
+    /// Whether B was pressed once with unsaved changes.
+    confirm_discard: bool,
+    /// Whether the line being edited was just inserted, and should be removed if cancelled.
+    inserted: bool,
+    status: Label<String>,
+    button_hints: Row<ButtonHint<String>>,
+    keyboard: Option<Keyboard>,
+    dirty: bool,
+}
+
+impl Editor {
+    pub fn new(rect: Rect, res: Resources, path: PathBuf) -> Self {
+        let Rect { x, y, w, h } = rect;
+
+        let (text, read_only) = match fs::read(&path) {
+            Ok(bytes) => match String::from_utf8(bytes) {
+                Ok(text) => (text, false),
+                Err(e) => {
+                    warn!("{} is not UTF-8, opening read-only", path.display());
+                    (String::from_utf8_lossy(e.as_bytes()).into_owned(), true)
+                }
+            },
+            Err(e) => {
+                error!("failed to load file {}: {}", path.display(), e);
+                (String::new(), e.kind() != ErrorKind::NotFound)
+            }
+        };
+        let line_ending = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        let trailing_newline = text.ends_with('\n');
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let status = Label::new(
+            Point::new(
+                x + 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            file_name(&path),
+            Alignment::Left,
+            Some(w / 3),
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            if read_only {
+                Vec::new()
+            } else {
+                vec![
+                    ButtonHint::new(
+                        res.clone(),
+                        Point::zero(),
+                        Key::A,
+                        locale.t("text-editor-edit"),
+                        Alignment::Right,
+                    ),
+                    ButtonHint::new(
+                        res.clone(),
+                        Point::zero(),
+                        Key::X,
+                        locale.t("text-editor-insert"),
+                        Alignment::Right,
+                    ),
+                    ButtonHint::new(
+                        res.clone(),
+                        Point::zero(),
+                        Key::Y,
+                        locale.t("text-editor-delete"),
+                        Alignment::Right,
+                    ),
+                    ButtonHint::new(
+                        res.clone(),
+                        Point::zero(),
+                        Key::Start,
+                        locale.t("text-editor-save"),
+                        Alignment::Right,
+                    ),
+                ]
+            },
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+
+        let mut editor = Self {
+            rect,
+            res,
+            format: Format::from_path(&path),
+            path,
+            lines,
+            line_ending,
+            trailing_newline,
+            selected: 0,
+            top: 0,
+            modified: false,
+            read_only,
+            backed_up: false,
+            confirm_discard: false,
+            inserted: false,
+            status,
+            button_hints,
+            keyboard: None,
+            dirty: true,
+        };
+        if read_only {
+            editor.show_status("text-editor-read-only");
+        }
+        editor
+    }
+
+    /// Area the lines are drawn in.
+    fn text_rect(&self, styles: &Stylesheet) -> Rect {
+        Rect::new(
+            self.rect.x + 12,
+            self.rect.y + 8,
+            self.rect.w - 24,
+            self.rect.h - 8 - 8 - ButtonIcon::diameter(styles) - 8,
+        )
+    }
+
+    fn visible_count(&self, styles: &Stylesheet) -> usize {
+        ((self.text_rect(styles).h - 16) / styles.guide_font.size) as usize
+    }
+
+    fn select(&mut self, index: usize) {
+        let visible_count = self.visible_count(&self.res.get::<Stylesheet>());
+        self.selected = index.min(self.lines.len() - 1);
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + visible_count {
+            self.top = self.selected + 1 - visible_count;
+        }
+        self.dirty = true;
+    }
+
+    fn edit(&mut self) {
+        self.keyboard = Some(Keyboard::new(
+            self.res.clone(),
+            self.lines[self.selected].clone(),
+            false,
+        ));
+    }
+
+    fn set_modified(&mut self, modified: bool) {
+        self.modified = modified;
+        let name = file_name(&self.path);
+        self.status
+            .set_text(if modified { format!("{name}*") } else { name });
+    }
+
+    /// Saves the file, backing up the original to `<file>.bak` the first time it's saved.
+    fn save(&mut self) -> Result<()> {
+        if !self.backed_up && self.path.exists() {
+            let mut backup = self.path.clone().into_os_string();
+            backup.push(".bak");
+            fs::copy(&self.path, &backup)?;
+            self.backed_up = true;
+        }
+
+        let mut text = self.lines.join(self.line_ending);
+        if self.trailing_newline {
+            text.push_str(self.line_ending);
+        }
+        fs::write(&self.path, text)?;
+        info!("saved {}", self.path.display());
+
+        self.set_modified(false);
+        Ok(())
+    }
+
+    fn show_status(&mut self, key: &str) {
+        let mut map = HashMap::new();
+        map.insert("file".into(), file_name(&self.path).into());
+        let text = self.res.get::<Locale>().ta(key, &map);
+        self.status.set_text(text);
+    }
+}
+
+fn file_name(path: &std::path::Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+#[async_trait(?Send)]
+impl View for Editor {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            let rect = self.text_rect(styles);
+            RoundedRectangle::with_equal_corners(
+                <Rect as Into<Rectangle>>::into(rect),
+                Size::new_equal(8),
+            )
+            .into_styled(PrimitiveStyle::with_fill(styles.background_color))
+            .draw(display)?;
+
+            let line_height = styles.guide_font.size;
+            let mut y = rect.y + 8;
+            let visible_count = self.visible_count(styles);
+            for (i, line) in self
+                .lines
+                .iter()
+                .enumerate()
+                .skip(self.top)
+                .take(visible_count)
+            {
+                let background = if i == self.selected {
+                    StylesheetColor::BackgroundHighlightBlend
+                } else {
+                    StylesheetColor::Background
+                };
+                let background = background.to_color(styles);
+                Rectangle::new(
+                    Point::new(rect.x + 4, y).into(),
+                    Size::new(rect.w - 8, line_height),
+                )
+                .into_styled(PrimitiveStyle::with_fill(background))
+                .draw(display)?;
+
+                let mut x = rect.x + 12;
+                for (range, token) in syntax::highlight(line, self.format) {
+                    let text_style = FontTextStyleBuilder::new(styles.guide_font.font())
+                        .font_fallbacks(styles.fallbacks())
+                        .font_size(line_height)
+                        .background_color(background)
+                        .text_color(token.color().to_color(styles))
+                        .build();
+                    let text = Text::new(&line[range], Point::new(x, y).into(), text_style);
+                    text.draw(display)?;
+                    x += text.bounding_box().size.width as i32;
+                    if x >= rect.x + rect.w as i32 {
+                        break;
+                    }
+                }
+
+                y += line_height as i32;
+            }
+
+            self.dirty = false;
+            self.status.set_should_draw();
+            drawn = true;
+        }
+
+        if self.status.should_draw() {
+            let mut rect = self.status.bounding_box(styles);
+            rect.w = self.rect.w / 3;
+            display.load(rect)?;
+            drawn |= self.status.draw(display, styles)?;
+        }
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            drawn |= keyboard.should_draw() && keyboard.draw(display, styles)?;
+        }
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.status.should_draw()
+            || self.button_hints.should_draw()
+            || self.keyboard.as_ref().is_some_and(View::should_draw)
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        self.status.set_should_draw();
+        self.button_hints.set_should_draw();
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            keyboard.set_should_draw();
+        }
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            if !keyboard.handle_key_event(event, commands, bubble).await? {
+                return Ok(false);
+            }
+            while let Some(command) = bubble.pop_front() {
+                match command {
+                    Command::ValueChanged(_, value) => {
+                        self.lines[self.selected] = value.as_string().unwrap();
+                        self.inserted = false;
+                        self.set_modified(true);
+                    }
+                    Command::CloseView => {
+                        self.keyboard = None;
+                        if mem::take(&mut self.inserted) {
+                            self.lines.remove(self.selected);
+                            self.select(self.selected.saturating_sub(1));
+                        }
+                        self.dirty = true;
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(true);
+        }
+
+        let confirm_discard = mem::take(&mut self.confirm_discard);
+        if confirm_discard {
+            self.set_modified(self.modified);
+        }
+        let page = self.visible_count(&self.res.get::<Stylesheet>());
+        match event {
+            KeyEvent::Pressed(Key::Up) | KeyEvent::Autorepeat(Key::Up) => {
+                if self.selected == 0 {
+                    self.select(self.lines.len() - 1);
+                } else {
+                    self.select(self.selected - 1);
+                }
+            }
+            KeyEvent::Pressed(Key::Down) | KeyEvent::Autorepeat(Key::Down) => {
+                if self.selected + 1 == self.lines.len() {
+                    self.select(0);
+                } else {
+                    self.select(self.selected + 1);
+                }
+            }
+            KeyEvent::Pressed(Key::L) | KeyEvent::Autorepeat(Key::L) => {
+                self.select(self.selected.saturating_sub(page));
+            }
+            KeyEvent::Pressed(Key::R) | KeyEvent::Autorepeat(Key::R) => {
+                self.select(self.selected + page);
+            }
+            KeyEvent::Pressed(Key::A | Key::X | Key::Y | Key::Start) if self.read_only => {
+                self.show_status("text-editor-read-only");
+            }
+            KeyEvent::Pressed(Key::A) => self.edit(),
+            KeyEvent::Pressed(Key::X) => {
+                self.lines.insert(self.selected + 1, String::new());
+                self.select(self.selected + 1);
+                self.inserted = true;
+                self.edit();
+            }
+            KeyEvent::Pressed(Key::Y) => {
+                if self.lines.len() > 1 {
+                    self.lines.remove(self.selected);
+                } else {
+                    self.lines[0].clear();
+                }
+                self.select(self.selected);
+                self.set_modified(true);
+            }
+            KeyEvent::Pressed(Key::Start) => {
+                if let Err(e) = self.save() {
+                    error!("failed to save {}: {}", self.path.display(), e);
+                    self.show_status("text-editor-save-failed");
+                }
+            }
+            KeyEvent::Pressed(Key::B) => {
+                if self.modified && !confirm_discard {
+                    self.confirm_discard = true;
+                    self.show_status("text-editor-unsaved");
+                } else {
+                    bubble.push_back(Command::CloseView);
+                }
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.status, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.status, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
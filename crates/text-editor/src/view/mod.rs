@@ -0,0 +1,7 @@
+mod app;
+mod browser;
+mod editor;
+
+pub use app::App;
+pub use browser::Browser;
+pub use editor::Editor;
@@ -0,0 +1,7 @@
+mod app;
+mod preview;
+mod theme_creator;
+
+pub use app::App;
+pub use preview::Preview;
+pub use theme_creator::ThemeCreator;
@@ -0,0 +1,236 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::Command;
+use common::constants::{ALLIUM_SD_ROOT, SELECTION_MARGIN};
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::{Stylesheet, StylesheetColor};
+use common::view::{ButtonIcon, Image, ImageMode, Label, View};
+use embedded_graphics::Drawable;
+use embedded_graphics::prelude::Size;
+use embedded_graphics::primitives::{Primitive, PrimitiveStyle, Rectangle, RoundedRectangle};
+use tokio::sync::mpsc::Sender;
+
+/// Games shown in the mockup game list.
+const GAMES: [&str; 4] = [
+    "Super Mario Bros.",
+    "The Legend of Zelda",
+    "Metroid",
+    "Kirby's Adventure",
+];
+
+/// The preview is drawn at a fraction of the real size so that it fits next to the editor.
+const SCALE: u32 = 2;
+
+/// A mockup of the launcher, drawn with the stylesheet being edited rather than the current one.
+#[derive(Debug)]
+pub struct Preview {
+    rect: Rect,
+    res: Resources,
+    stylesheet: Stylesheet,
+    wallpaper: Image,
+    tabs: Vec<Label<String>>,
+    games: Vec<Label<String>>,
+    button_icons: Vec<ButtonIcon>,
+    button_labels: Vec<Label<String>>,
+    dirty: bool,
+}
+
+impl Preview {
+    pub fn new(rect: Rect, res: Resources, stylesheet: &Stylesheet) -> Self {
+        let mut this = Self {
+            rect,
+            res,
+            stylesheet: stylesheet.clone(),
+            wallpaper: Image::empty(rect, ImageMode::Cover),
+            tabs: Vec::new(),
+            games: Vec::new(),
+            button_icons: Vec::new(),
+            button_labels: Vec::new(),
+            dirty: true,
+        };
+        this.set_stylesheet(stylesheet);
+        this
+    }
+
+    pub fn set_stylesheet(&mut self, stylesheet: &Stylesheet) {
+        let mut styles = stylesheet.clone();
+        styles.ui_font.size /= SCALE;
+        styles.guide_font.size /= SCALE;
+
+        self.wallpaper.set_path(
+            styles
+                .wallpaper
+                .as_ref()
+                .map(|wallpaper| ALLIUM_SD_ROOT.join(wallpaper)),
+        );
+
+        let locale = self.res.get::<Locale>();
+        let Rect { x, y, w, h } = self.rect;
+
+        // Tabs, with the games tab selected
+        let mut tabs = Vec::new();
+        let mut tab_x = x + 8;
+        for (i, key) in ["tab-recents", "tab-games", "tab-apps", "tab-settings"]
+            .into_iter()
+            .enumerate()
+        {
+            let mut tab = Label::new(
+                Point::new(tab_x, y + 6),
+                locale.t(key),
+                Alignment::Left,
+                None,
+            );
+            tab.font_size(styles.tab_font_size);
+            tab.color(if i == 1 {
+                StylesheetColor::TabSelected
+            } else {
+                StylesheetColor::Tab
+            });
+            tab_x += tab.bounding_box(&styles).w as i32 + 8;
+            tabs.push(tab);
+        }
+
+        // Game list, with the first game selected
+        let tab_h = (styles.ui_font.size as f32 * styles.tab_font_size) as i32;
+        let line_h = (styles.ui_font.size + SELECTION_MARGIN / SCALE) as i32;
+        let games = GAMES
+            .iter()
+            .enumerate()
+            .map(|(i, game)| {
+                Label::new(
+                    Point::new(x + 16, y + 6 + tab_h + 12 + i as i32 * line_h),
+                    game.to_string(),
+                    Alignment::Left,
+                    Some(w - 32),
+                )
+            })
+            .collect();
+
+        // Button hints
+        let diameter = ButtonIcon::diameter(&styles) as i32;
+        let hint_y = y + h as i32 - diameter - 6;
+        let mut button_icons = Vec::new();
+        let mut button_labels = Vec::new();
+        let mut hint_x = x + 8;
+        for (key, text) in [(Key::A, "button-select"), (Key::B, "button-back")] {
+            let mut icon = ButtonIcon::new(Point::new(hint_x, hint_y), key, Alignment::Left);
+            hint_x += icon.bounding_box(&styles).w as i32 + 4;
+            let mut label = Label::new(
+                Point::new(hint_x, hint_y),
+                locale.t(text),
+                Alignment::Left,
+                None,
+            );
+            label.font_size(styles.button_hint_font_size);
+            hint_x += label.bounding_box(&styles).w as i32 + 8;
+            button_icons.push(icon);
+            button_labels.push(label);
+        }
+
+        drop(locale);
+
+        self.stylesheet = styles;
+        self.tabs = tabs;
+        self.games = games;
+        self.button_icons = button_icons;
+        self.button_labels = button_labels;
+        self.dirty = true;
+    }
+}
+
+#[async_trait(?Send)]
+impl View for Preview {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        if !self.should_draw() {
+            return Ok(false);
+        }
+
+        // Everything inside the frame is drawn with the edited stylesheet
+        let preview = &self.stylesheet;
+
+        if preview.wallpaper.is_some() {
+            self.wallpaper.draw(display, preview)?;
+        } else {
+            Rectangle::from(self.rect)
+                .into_styled(PrimitiveStyle::with_fill(preview.background_color))
+                .draw(display)?;
+        }
+
+        for tab in &mut self.tabs {
+            tab.draw(display, preview)?;
+        }
+
+        if let Some(selected) = self.games.first_mut() {
+            let rect = selected.bounding_box(preview);
+            RoundedRectangle::with_equal_corners(
+                Rectangle::new(
+                    embedded_graphics::prelude::Point::new(rect.x - 6, rect.y - 2),
+                    Size::new(rect.w + 12, rect.h + 4),
+                ),
+                Size::new_equal(rect.h),
+            )
+            .into_styled(PrimitiveStyle::with_fill(preview.highlight_color))
+            .draw(display)?;
+        }
+        for game in &mut self.games {
+            game.draw(display, preview)?;
+        }
+
+        for icon in &mut self.button_icons {
+            icon.draw(display, preview)?;
+        }
+        for label in &mut self.button_labels {
+            label.draw(display, preview)?;
+        }
+
+        RoundedRectangle::with_equal_corners(Rectangle::from(self.rect), Size::new_equal(8))
+            .into_styled(PrimitiveStyle::with_stroke(styles.disabled_color, 2))
+            .draw(display)?;
+
+        self.dirty = false;
+        Ok(true)
+    }
+
+    fn should_draw(&self) -> bool {
+        // Drawn again once the wallpaper is decoded
+        self.dirty || self.wallpaper.is_loading()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        _event: KeyEvent,
+        _commands: Sender<Command>,
+        _bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
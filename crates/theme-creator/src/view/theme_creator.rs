@@ -0,0 +1,485 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::command::{Command, Value};
+use common::constants::SELECTION_MARGIN;
+use common::display::Display;
+use common::display::color::Color;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::{Stylesheet, StylesheetFont};
+use common::view::{
+    ButtonHint, ButtonIcon, ColorPicker, Keyboard, Label, NullView, Number, Row, Select,
+    SettingsList, View,
+};
+use log::error;
+use tokio::sync::mpsc::Sender;
+
+use crate::view::Preview;
+
+const NAME: usize = 0;
+const BASE: usize = 1;
+const COLORS: usize = 2;
+const COLOR_LABELS: [&str; 10] = [
+    "settings-theme-highlight-color",
+    "settings-theme-foreground-color",
+    "settings-theme-background-color",
+    "settings-theme-disabled-color",
+    "settings-theme-tab-color",
+    "settings-theme-tab-selected-color",
+    "settings-theme-button-a-color",
+    "settings-theme-button-b-color",
+    "settings-theme-button-x-color",
+    "settings-theme-button-y-color",
+];
+const UI_FONT: usize = COLORS + COLOR_LABELS.len();
+const UI_FONT_SIZE: usize = UI_FONT + 1;
+const WALLPAPER: usize = UI_FONT + 2;
+const SAVE: usize = UI_FONT + 3;
+const APPLY: usize = UI_FONT + 4;
+
+/// Returns the color edited by the n-th color row.
+fn color_mut(stylesheet: &mut Stylesheet, n: usize) -> &mut Color {
+    match n {
+        0 => &mut stylesheet.highlight_color,
+        1 => &mut stylesheet.foreground_color,
+        2 => &mut stylesheet.background_color,
+        3 => &mut stylesheet.disabled_color,
+        4 => &mut stylesheet.tab_color,
+        5 => &mut stylesheet.tab_selected_color,
+        6 => &mut stylesheet.button_a_color,
+        7 => &mut stylesheet.button_b_color,
+        8 => &mut stylesheet.button_x_color,
+        9 => &mut stylesheet.button_y_color,
+        _ => unreachable!("Invalid color index"),
+    }
+}
+
+fn file_stem(path: &std::path::Path) -> String {
+    path.file_stem()
+        .and_then(std::ffi::OsStr::to_str)
+        .unwrap_or("Unknown")
+        .replace(['_', '-'], " ")
+}
+
+/// Edits a stylesheet with a live preview, and saves it as a theme package.
+#[derive(Debug)]
+pub struct ThemeCreator {
+    rect: Rect,
+    res: Resources,
+    name: String,
+    base: usize,
+    stylesheet: Stylesheet,
+    themes: Vec<PathBuf>,
+    fonts: Vec<PathBuf>,
+    wallpapers: Vec<PathBuf>,
+    list: SettingsList,
+    preview: Preview,
+    status: Label<String>,
+    keyboard: Option<Keyboard>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl ThemeCreator {
+    pub fn new(rect: Rect, res: Resources) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let stylesheet = Stylesheet::load()?;
+
+        let locale = res.get::<Locale>();
+        let styles = res.get::<Stylesheet>();
+
+        let preview_w = w * 2 / 5;
+        let content_h = h - 8 - ButtonIcon::diameter(&styles) - 8 - styles.ui_font.size - 8;
+
+        let mut labels = vec![
+            locale.t("theme-creator-name"),
+            locale.t("theme-creator-base"),
+        ];
+        labels.extend(COLOR_LABELS.iter().map(|key| locale.t(key)));
+        labels.extend([
+            locale.t("settings-theme-ui-font"),
+            locale.t("settings-theme-ui-font-size"),
+            locale.t("theme-creator-wallpaper"),
+            locale.t("theme-creator-save"),
+            locale.t("theme-creator-apply"),
+        ]);
+        let list = SettingsList::new(
+            Rect::new(x + 12, y + 8, w - preview_w - 36, content_h),
+            labels.clone(),
+            labels
+                .iter()
+                .map(|_| Box::new(NullView) as Box<dyn View>)
+                .collect(),
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let preview = Preview::new(
+            Rect::new(x + w as i32 - preview_w as i32 - 12, y + 8, preview_w, content_h),
+            res.clone(),
+            &stylesheet,
+        );
+
+        let status = Label::new(
+            Point::new(x + 12, y + 8 + content_h as i32 + 8),
+            String::new(),
+            Alignment::Left,
+            Some(w - 24),
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("button-edit"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        let name = locale.t("theme-creator-default-name");
+
+        drop(locale);
+        drop(styles);
+
+        let mut this = Self {
+            rect,
+            res,
+            name,
+            base: 0,
+            stylesheet,
+            themes: Stylesheet::available_themes(),
+            fonts: StylesheetFont::available_fonts().unwrap_or_default(),
+            wallpapers: Stylesheet::available_wallpapers(),
+            list,
+            preview,
+            status,
+            keyboard: None,
+            button_hints,
+            dirty: true,
+        };
+        this.update_rights();
+        Ok(this)
+    }
+
+    /// Updates every row to reflect the stylesheet being edited.
+    fn update_rights(&mut self) {
+        let locale = self.res.get::<Locale>();
+
+        self.list.set_right(
+            NAME,
+            Box::new(Label::new(Point::zero(), self.name.clone(), Alignment::Right, None)),
+        );
+
+        let mut bases = vec![locale.t("theme-creator-base-current")];
+        bases.extend(self.themes.iter().map(|p| file_stem(p)));
+        self.list.set_right(
+            BASE,
+            Box::new(Select::new(Point::zero(), self.base, bases, Alignment::Right)),
+        );
+
+        for n in 0..COLOR_LABELS.len() {
+            let color = *color_mut(&mut self.stylesheet, n);
+            self.list.set_right(
+                COLORS + n,
+                Box::new(ColorPicker::new(Point::zero(), color, Alignment::Right)),
+            );
+        }
+
+        self.list.set_right(
+            UI_FONT,
+            Box::new(Select::new(
+                Point::zero(),
+                self.fonts
+                    .iter()
+                    .position(|p| *p == self.stylesheet.ui_font.path)
+                    .unwrap_or_default(),
+                self.fonts.iter().map(|p| file_stem(p)).collect(),
+                Alignment::Right,
+            )),
+        );
+        self.list.set_right(
+            UI_FONT_SIZE,
+            Box::new(Number::new(
+                Point::zero(),
+                self.stylesheet.ui_font.size as i32,
+                20,
+                60,
+                5,
+                i32::to_string,
+                Alignment::Right,
+            )),
+        );
+
+        let mut wallpapers = vec![locale.t("theme-creator-wallpaper-none")];
+        wallpapers.extend(self.wallpapers.iter().map(|p| file_stem(p)));
+        let wallpaper = self
+            .stylesheet
+            .wallpaper
+            .as_ref()
+            .and_then(|w| self.wallpapers.iter().position(|p| p == w))
+            .map_or(0, |i| i + 1);
+        self.list.set_right(
+            WALLPAPER,
+            Box::new(Select::new(Point::zero(), wallpaper, wallpapers, Alignment::Right)),
+        );
+
+        self.list.set_right(SAVE, Box::new(NullView));
+        self.list.set_right(APPLY, Box::new(NullView));
+    }
+
+    fn set_status(&mut self, status: String) {
+        self.status.set_text(status);
+        self.dirty = true;
+    }
+
+    /// Replaces the stylesheet being edited with the current stylesheet or a saved theme.
+    fn set_base(&mut self, base: usize) -> Result<()> {
+        self.stylesheet = match base.checked_sub(1).and_then(|i| self.themes.get(i)) {
+            Some(theme) => {
+                self.name = file_stem(theme);
+                Stylesheet::load_theme(theme)?
+            }
+            None => Stylesheet::load()?,
+        };
+        self.base = base;
+        self.update_rights();
+        Ok(())
+    }
+
+    fn save(&mut self) -> Result<PathBuf> {
+        let dir = self.stylesheet.save_theme(&self.name)?;
+        self.themes = Stylesheet::available_themes();
+        Ok(dir)
+    }
+
+    /// Saves the theme package, then makes it the current theme.
+    fn apply(&mut self) -> Result<()> {
+        let dir = self.save()?;
+        Stylesheet::load_theme(&dir)?.save()?;
+        Ok(())
+    }
+
+    fn handle_value_changed(&mut self, i: usize, value: Value) -> Result<()> {
+        match (i, value) {
+            (BASE, Value::Int(base)) => self.set_base(base as usize)?,
+            (UI_FONT, Value::Int(font)) => {
+                if let Some(path) = self.fonts.get(font as usize) {
+                    self.stylesheet.ui_font.path.clone_from(path);
+                    self.stylesheet.ui_font.load()?;
+                }
+            }
+            (UI_FONT_SIZE, Value::Int(size)) => self.stylesheet.ui_font.size = size as u32,
+            (WALLPAPER, Value::Int(wallpaper)) => {
+                self.stylesheet.wallpaper = (wallpaper as usize)
+                    .checked_sub(1)
+                    .and_then(|i| self.wallpapers.get(i))
+                    .cloned();
+            }
+            (i, Value::Color(color)) if (COLORS..UI_FONT).contains(&i) => {
+                *color_mut(&mut self.stylesheet, i - COLORS) = color;
+            }
+            _ => {}
+        }
+        self.preview.set_stylesheet(&self.stylesheet);
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl View for ThemeCreator {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.list.set_should_draw();
+            self.preview.set_should_draw();
+            self.status.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        drawn |= self.preview.should_draw() && self.preview.draw(display, styles)?;
+        drawn |= self.status.should_draw() && self.status.draw(display, styles)?;
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            if drawn {
+                keyboard.set_should_draw();
+            }
+            drawn |= keyboard.should_draw() && keyboard.draw(display, styles)?;
+        }
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.list.should_draw()
+            || self.preview.should_draw()
+            || self.status.should_draw()
+            || self.button_hints.should_draw()
+            || self.keyboard.as_ref().is_some_and(|k| k.should_draw())
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+        if let Some(keyboard) = self.keyboard.as_mut() {
+            keyboard.set_should_draw();
+        }
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        if let Some(keyboard) = self.keyboard.as_mut()
+            && keyboard
+                .handle_key_event(event, commands.clone(), bubble)
+                .await?
+        {
+            let mut name = None;
+            bubble.retain_mut(|c| match c {
+                Command::ValueChanged(_, val) => {
+                    if let Value::String(val) = val {
+                        name = Some(val.clone());
+                    }
+                    false
+                }
+                Command::CloseView => {
+                    self.keyboard = None;
+                    false
+                }
+                _ => true,
+            });
+            if let Some(name) = name.filter(|name| !name.trim().is_empty()) {
+                self.name = name.trim().to_string();
+                self.list.set_right(
+                    NAME,
+                    Box::new(Label::new(Point::zero(), self.name.clone(), Alignment::Right, None)),
+                );
+            }
+            if self.keyboard.is_none() {
+                commands.send(Command::Redraw).await?;
+            }
+            return Ok(true);
+        }
+
+        if let KeyEvent::Pressed(Key::A) = event {
+            match self.list.selected() {
+                NAME => {
+                    self.keyboard = Some(Keyboard::new(self.res.clone(), self.name.clone(), false));
+                    return Ok(true);
+                }
+                SAVE => {
+                    let status = match self.save() {
+                        Ok(dir) => {
+                            let mut map = HashMap::new();
+                            map.insert("path".into(), dir.display().to_string().into());
+                            self.res.get::<Locale>().ta("theme-creator-saved", &map)
+                        }
+                        Err(e) => {
+                            error!("failed to save theme: {}", e);
+                            self.res.get::<Locale>().t("theme-creator-error")
+                        }
+                    };
+                    self.set_status(status);
+                    return Ok(true);
+                }
+                APPLY => {
+                    let status = match self.apply() {
+                        Ok(()) => self.res.get::<Locale>().t("theme-creator-applied"),
+                        Err(e) => {
+                            error!("failed to apply theme: {}", e);
+                            self.res.get::<Locale>().t("theme-creator-error")
+                        }
+                    };
+                    self.set_status(status);
+                    return Ok(true);
+                }
+                _ => {}
+            }
+        }
+
+        if self
+            .list
+            .handle_key_event(event, commands.clone(), bubble)
+            .await?
+        {
+            let mut changed = Vec::new();
+            bubble.retain(|cmd| match cmd {
+                Command::ValueChanged(i, value) => {
+                    changed.push((*i, value.clone()));
+                    false
+                }
+                _ => true,
+            });
+            for (i, value) in changed {
+                if let Err(e) = self.handle_value_changed(i, value) {
+                    error!("failed to update theme: {}", e);
+                    let status = self.res.get::<Locale>().t("theme-creator-error");
+                    self.set_status(status);
+                }
+            }
+            return Ok(true);
+        }
+
+        match event {
+            KeyEvent::Pressed(Key::B) => {
+                commands.send(Command::Exit).await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.preview, &self.status, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![
+            &mut self.list,
+            &mut self.preview,
+            &mut self.status,
+            &mut self.button_hints,
+        ]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
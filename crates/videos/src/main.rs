@@ -0,0 +1,20 @@
+mod player;
+mod videos;
+mod view;
+
+use anyhow::Result;
+
+use common::platform::{DefaultPlatform, Platform};
+use simple_logger::SimpleLogger;
+
+use crate::videos::VideosApp;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    SimpleLogger::new().env().init().unwrap();
+
+    let platform = DefaultPlatform::new()?;
+    let mut app = VideosApp::new(platform)?;
+    app.run_event_loop().await?;
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use anyhow::Result;
+use chrono::Duration;
+use common::constants::{ALLIUM_FFPLAY_DIR, ALLIUM_SD_ROOT};
+use log::{info, warn};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+
+/// Keeps alliumd from putting the device to sleep while a video plays.
+const STAY_AWAKE: &str = "/tmp/stay_awake";
+
+/// How close to the end a video has to be stopped to count as watched.
+const END_MARGIN: Duration = Duration::seconds(10);
+
+/// Where a video stopped playing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Playback {
+    pub position: Duration,
+    /// Whether the video was watched to the end, so it should start over next time.
+    pub finished: bool,
+}
+
+/// Plays a video from the given position with the bundled ffplay, which draws over the app
+/// until the video ends or is quit. The position is read from the status ffplay prints, so it
+/// stays correct when seeking or pausing, and is passed to `on_progress` as the video plays.
+pub async fn play(
+    path: &Path,
+    start: Duration,
+    mut on_progress: impl FnMut(Duration),
+) -> Result<Playback> {
+    info!("playing {} from {}s", path.display(), start.num_seconds());
+
+    // ffplay needs exclusive access to the audio device
+    run_script("stop_audioserver.sh");
+    if let Err(e) = fs::write(STAY_AWAKE, "") {
+        warn!("failed to keep the device awake: {}", e);
+    }
+
+    let ffplay = ALLIUM_FFPLAY_DIR.join("bin/ffplay");
+    let program = if ffplay.exists() {
+        ffplay
+    } else {
+        PathBuf::from("ffplay")
+    };
+    let mut command = Command::new(program);
+    command.args(["-autoexit", "-hide_banner"]);
+    // The screen is mounted upside down
+    #[cfg(feature = "miyoo")]
+    command.args(["-vf", "hflip,vflip"]);
+    if start > Duration::zero() {
+        command.arg("-ss").arg(start.num_seconds().to_string());
+    }
+    let mut child = command
+        .arg("-i")
+        .arg(path)
+        .env("HOME", ALLIUM_FFPLAY_DIR.as_path())
+        .env("LD_LIBRARY_PATH", ALLIUM_FFPLAY_DIR.join("libs"))
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let mut duration = None;
+    let mut position = start;
+    if let Some(stderr) = child.stderr.take() {
+        // The status line is redrawn with a carriage return, rather than printed on a new line
+        let mut segments = BufReader::new(stderr).split(b'\r');
+        while let Some(segment) = segments.next_segment().await? {
+            for line in String::from_utf8_lossy(&segment).lines() {
+                if duration.is_none() {
+                    duration = parse_duration(line);
+                }
+                if let Some(clock) = parse_position(line) {
+                    position = clock;
+                    on_progress(position);
+                }
+            }
+        }
+    }
+    let status = child.wait().await?;
+    info!(
+        "ffplay exited with {} at {}s",
+        status,
+        position.num_seconds()
+    );
+
+    let _ = fs::remove_file(STAY_AWAKE);
+    run_script("start_audioserver.sh");
+
+    Ok(Playback {
+        position,
+        finished: duration.is_some_and(|duration| position + END_MARGIN >= duration),
+    })
+}
+
+/// Parses the length of the video from ffplay's input info, e.g.
+/// `  Duration: 00:21:05.12, start: 0.000000, bitrate: 1234 kb/s`.
+fn parse_duration(line: &str) -> Option<Duration> {
+    let time = line.trim().strip_prefix("Duration:")?.split(',').next()?;
+    let mut parts = time.trim().split(':');
+    let hours: i64 = parts.next()?.parse().ok()?;
+    let minutes: i64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    Some(
+        Duration::hours(hours)
+            + Duration::minutes(minutes)
+            + Duration::milliseconds((seconds * 1000.0).round() as i64),
+    )
+}
+
+/// Parses the playback position from ffplay's status line, e.g.
+/// `  12.34 A-V:  0.001 fd=   0 aq=   15KB vq=  120KB sq=    0B f=0/0`.
+fn parse_position(line: &str) -> Option<Duration> {
+    if !line.contains(" fd=") || !line.contains(" aq=") {
+        return None;
+    }
+    let clock: f64 = line.split_whitespace().next()?.parse().ok()?;
+    clock
+        .is_finite()
+        .then(|| Duration::milliseconds((clock.max(0.0) * 1000.0).round() as i64))
+}
+
+fn run_script(name: &str) {
+    let script = ALLIUM_SD_ROOT.join(".tmp_update/script").join(name);
+    if script.exists()
+        && let Err(e) = std::process::Command::new(&script).status()
+    {
+        warn!("failed to run {}: {}", script.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(
+            parse_duration("  Duration: 00:21:05.12, start: 0.000000, bitrate: 1234 kb/s"),
+            Some(Duration::milliseconds(1_265_120))
+        );
+        assert_eq!(
+            parse_duration("  Duration: N/A, start: 0.000000, bitrate: N/A"),
+            None
+        );
+        assert_eq!(parse_duration("    Stream #0:0: Video: h264"), None);
+    }
+
+    #[test]
+    fn test_parse_position() {
+        assert_eq!(
+            parse_position("  12.34 A-V:  0.001 fd=   0 aq=   15KB vq=  120KB sq=    0B f=0/0   "),
+            Some(Duration::milliseconds(12_340))
+        );
+        assert_eq!(
+            parse_position("    nan M-V:    nan fd=   0 aq=    0KB vq=    0KB sq=    0B f=0/0   "),
+            None
+        );
+        assert_eq!(parse_position("  Duration: 00:21:05.12"), None);
+    }
+}
@@ -0,0 +1,5 @@
+mod app;
+mod videos;
+
+pub use app::App;
+pub use videos::Videos;
@@ -0,0 +1,356 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Duration;
+use common::command::Command;
+use common::constants::{ALLIUM_VIDEOS_DIR, SELECTION_MARGIN};
+use common::database::Database;
+use common::display::Display;
+use common::geom::{Alignment, Point, Rect};
+use common::locale::Locale;
+use common::platform::{DefaultPlatform, Key, KeyEvent, Platform};
+use common::resources::Resources;
+use common::stylesheet::Stylesheet;
+use common::view::{ButtonHint, ButtonIcon, Label, NullView, Row, SettingsList, View};
+use log::warn;
+use tokio::sync::mpsc::Sender;
+
+use crate::player;
+
+/// Extensions of the files listed as videos.
+const VIDEO_EXTENSIONS: [&str; 2] = ["mp4", "mkv"];
+
+/// How often the playback position is saved while playing.
+const SAVE_INTERVAL: Duration = Duration::seconds(10);
+
+/// Lists the videos in the Videos folder, and plays them from where they were left off.
+#[derive(Debug)]
+pub struct Videos {
+    rect: Rect,
+    res: Resources,
+    videos: Vec<PathBuf>,
+    list: SettingsList,
+    status: Label<String>,
+    button_hints: Row<ButtonHint<String>>,
+    dirty: bool,
+}
+
+impl Videos {
+    pub fn new(rect: Rect, res: Resources) -> Result<Self> {
+        let Rect { x, y, w, h } = rect;
+
+        let styles = res.get::<Stylesheet>();
+        let locale = res.get::<Locale>();
+
+        let list = SettingsList::new(
+            Rect::new(
+                x + 12,
+                y,
+                w - 24,
+                h - 8 - styles.ui_font.size - 8 - ButtonIcon::diameter(&styles) - 8,
+            ),
+            Vec::new(),
+            Vec::new(),
+            styles.ui_font.size + SELECTION_MARGIN,
+        );
+
+        let status = Label::new(
+            Point::new(
+                x + 12,
+                y + h as i32
+                    - 8
+                    - ButtonIcon::diameter(&styles) as i32
+                    - 8
+                    - styles.ui_font.size as i32,
+            ),
+            String::new(),
+            Alignment::Left,
+            Some(w - 24),
+        );
+
+        let button_hints = Row::new(
+            Point::new(
+                x + w as i32 - 12,
+                y + h as i32 - ButtonIcon::diameter(&styles) as i32 - 8,
+            ),
+            vec![
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::A,
+                    locale.t("videos-button-play"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::B,
+                    locale.t("button-back"),
+                    Alignment::Right,
+                ),
+                ButtonHint::new(
+                    res.clone(),
+                    Point::zero(),
+                    Key::X,
+                    locale.t("videos-button-play-from-start"),
+                    Alignment::Right,
+                ),
+            ],
+            Alignment::Right,
+            12,
+        );
+
+        drop(styles);
+        drop(locale);
+
+        let mut this = Self {
+            rect,
+            res,
+            videos: Vec::new(),
+            list,
+            status,
+            button_hints,
+            dirty: true,
+        };
+        this.show_videos()?;
+
+        Ok(this)
+    }
+
+    /// Lists the videos with their saved positions, keeping the current selection.
+    fn show_videos(&mut self) -> Result<()> {
+        self.videos = find_videos(&ALLIUM_VIDEOS_DIR);
+
+        let database = self.res.get::<Database>();
+        let mut labels = Vec::with_capacity(self.videos.len());
+        let mut values = Vec::with_capacity(self.videos.len());
+        for video in &self.videos {
+            labels.push(
+                video
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string(),
+            );
+            let position = database.get_media_position(&video.display().to_string())?;
+            values.push(if position > Duration::zero() {
+                Box::new(Label::new(
+                    Point::zero(),
+                    format_position(position),
+                    Alignment::Right,
+                    None,
+                )) as Box<dyn View>
+            } else {
+                Box::new(NullView) as Box<dyn View>
+            });
+        }
+        drop(database);
+
+        let selected = self
+            .list
+            .selected()
+            .min(self.videos.len().saturating_sub(1));
+        self.list.set_items(labels, values);
+        self.list.select(selected);
+
+        if self.videos.is_empty() {
+            let text = self.res.get::<Locale>().t("videos-empty");
+            self.status.set_text(text);
+        }
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Plays the selected video from its saved position, or from the start. The app is drawn
+    /// again once the video is quit.
+    async fn play(&mut self, from_start: bool, commands: Sender<Command>) -> Result<()> {
+        let Some(path) = self.videos.get(self.list.selected()).cloned() else {
+            return Ok(());
+        };
+        let key = path.display().to_string();
+
+        let start = if from_start {
+            Duration::zero()
+        } else {
+            self.res.get::<Database>().get_media_position(&key)?
+        };
+
+        // Saved as the video plays too, in case the device is turned off before it's quit
+        let res = self.res.clone();
+        let mut saved = start;
+        let result = player::play(&path, start, |position| {
+            if (position - saved).abs() >= SAVE_INTERVAL {
+                saved = position;
+                if let Err(e) = res.get::<Database>().set_media_position(&key, position) {
+                    warn!("failed to save video position: {}", e);
+                }
+            }
+        })
+        .await;
+
+        match result {
+            Ok(playback) => {
+                // Start finished videos from the beginning next time
+                let position = if playback.finished {
+                    Duration::zero()
+                } else {
+                    playback.position
+                };
+                self.res
+                    .get::<Database>()
+                    .set_media_position(&key, position)?;
+                self.status.set_text(String::new());
+            }
+            Err(e) => {
+                warn!("failed to play {}: {}", path.display(), e);
+                let mut map = HashMap::new();
+                map.insert(
+                    "name".into(),
+                    path.file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string()
+                        .into(),
+                );
+                let text = self.res.get::<Locale>().ta("videos-error", &map);
+                self.status.set_text(text);
+            }
+        }
+
+        self.show_videos()?;
+        commands.send(Command::Redraw).await?;
+        Ok(())
+    }
+}
+
+/// Returns the videos in the folder and its subfolders, sorted by path.
+fn find_videos(dir: &Path) -> Vec<PathBuf> {
+    let mut videos = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(read_dir) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in read_dir.filter_map(std::result::Result::ok) {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if is_video(&path) {
+                videos.push(path);
+            }
+        }
+    }
+    videos.sort_unstable_by_key(|path| path.to_string_lossy().to_lowercase());
+    videos
+}
+
+fn is_video(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            VIDEO_EXTENSIONS
+                .iter()
+                .any(|video| ext.eq_ignore_ascii_case(video))
+        })
+}
+
+#[async_trait(?Send)]
+impl View for Videos {
+    fn draw(
+        &mut self,
+        display: &mut <DefaultPlatform as Platform>::Display,
+        styles: &Stylesheet,
+    ) -> Result<bool> {
+        let mut drawn = false;
+
+        if self.dirty {
+            display.load(self.rect)?;
+            self.list.set_should_draw();
+            self.status.set_should_draw();
+            self.button_hints.set_should_draw();
+            self.dirty = false;
+            drawn = true;
+        }
+
+        drawn |= self.list.should_draw() && self.list.draw(display, styles)?;
+        if self.status.should_draw() {
+            display.load(Rect::new(
+                self.rect.x,
+                self.status.bounding_box(styles).y,
+                self.rect.w,
+                styles.ui_font.size,
+            ))?;
+            drawn |= self.status.draw(display, styles)?;
+        }
+        drawn |= self.button_hints.should_draw() && self.button_hints.draw(display, styles)?;
+
+        Ok(drawn)
+    }
+
+    fn should_draw(&self) -> bool {
+        self.dirty
+            || self.list.should_draw()
+            || self.status.should_draw()
+            || self.button_hints.should_draw()
+    }
+
+    fn set_should_draw(&mut self) {
+        self.dirty = true;
+    }
+
+    async fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        commands: Sender<Command>,
+        bubble: &mut VecDeque<Command>,
+    ) -> Result<bool> {
+        match event {
+            KeyEvent::Pressed(Key::A) => {
+                self.play(false, commands).await?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::X) => {
+                self.play(true, commands).await?;
+                Ok(true)
+            }
+            KeyEvent::Pressed(Key::B) => {
+                commands.send(Command::Exit).await?;
+                Ok(true)
+            }
+            _ => self.list.handle_key_event(event, commands, bubble).await,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn View> {
+        vec![&self.list, &self.status, &self.button_hints]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn View> {
+        vec![&mut self.list, &mut self.status, &mut self.button_hints]
+    }
+
+    fn bounding_box(&mut self, _styles: &Stylesheet) -> Rect {
+        self.rect
+    }
+
+    fn set_position(&mut self, _point: Point) {
+        unimplemented!()
+    }
+}
+
+fn format_position(position: Duration) -> String {
+    let seconds = position.num_seconds();
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
@@ -1,3 +1,14 @@
 #!/bin/sh
 DIR=/mnt/SDCARD/RetroArch
-HOME=/mnt/SDCARD/RetroArch LD_PRELOAD=libpadsp.so exec "$DIR/retroarch" -v -L "$DIR/.retroarch/cores/$1_libretro.so" "$2"
+CLOCK_OVERLAY_CONFIG=/mnt/SDCARD/.allium/state/clock-overlay.cfg
+HOTKEYS_CONFIG=/mnt/SDCARD/.allium/state/retroarch-hotkeys.cfg
+if [ -f "$CLOCK_OVERLAY_CONFIG" ]; then
+    APPEND_CONFIGS="$CLOCK_OVERLAY_CONFIG"
+fi
+if [ -f "$HOTKEYS_CONFIG" ]; then
+    APPEND_CONFIGS="${APPEND_CONFIGS:+$APPEND_CONFIGS|}$HOTKEYS_CONFIG"
+fi
+if [ -n "$APPEND_CONFIGS" ]; then
+    APPEND_CONFIG="--appendconfig=$APPEND_CONFIGS"
+fi
+HOME=/mnt/SDCARD/RetroArch LD_PRELOAD=libpadsp.so exec "$DIR/retroarch" -v -L "$DIR/.retroarch/cores/$1_libretro.so" "$2" $APPEND_CONFIG
@@ -9,6 +9,7 @@ sort-favorites = Sort: Favourites
 menu-set-as-favorite = Set as Favourite
 menu-unset-as-favorite = Remove from Favourites
 
+settings-theme-group-favorites = Favourites First
 settings-theme-highlight-color = Highlight Colour
 settings-theme-foreground-color = Foreground Colour
 settings-theme-background-color = Background Colour
@@ -1,3 +1,17 @@
 activity-tracker-title = Activity Tracker
 
-activity-tracker-play-time = { $hours_decimal } hours
\ No newline at end of file
+activity-tracker-play-time = { $hours_decimal } hours
+
+activity-tracker-statistics = Statistics
+activity-tracker-games = Games
+activity-tracker-next-chart = Next Chart
+activity-tracker-total = Total play time: { $hours_decimal } hours
+activity-tracker-longest-session = Longest session: { $game } ({ $hours }h { $minutes }m)
+activity-tracker-longest-session-none = Longest session: none yet
+activity-tracker-streak = Streak: { $days ->
+    [one] 1 day
+   *[other] { $days } days
+} (best: { $best })
+activity-tracker-chart-weeks = Play time by week
+activity-tracker-chart-consoles = Play time by console
+activity-tracker-chart-games = Most played games
@@ -0,0 +1,6 @@
+books-title = Books
+
+books-empty = No books found. Copy TXT or EPUB files to the Books folder.
+books-error = Failed to open { $name }
+
+books-button-read = Read
@@ -0,0 +1,17 @@
+calculator-title = Calculator
+calculator-converter = Convert
+calculator-calculator = Calculate
+calculator-error = Error
+
+calculator-category = Category
+calculator-from = From
+calculator-to = To
+calculator-value = Value
+calculator-result = Result
+
+calculator-length = Length
+calculator-mass = Mass
+calculator-temperature = Temperature
+calculator-volume = Volume
+calculator-speed = Speed
+calculator-data = Data
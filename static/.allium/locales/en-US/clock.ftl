@@ -0,0 +1,23 @@
+clock-title = Clock
+
+clock-alarm = Alarm
+clock-alarm-hour = Alarm Hour
+clock-alarm-minute = Alarm Minute
+clock-stopwatch = Stopwatch
+clock-timer = Timer
+clock-timer-minutes = Timer Minutes
+clock-timer-suspend = Suspend When Timer Ends
+clock-start = Start
+clock-reset = Reset
+clock-overlay = Show Clock In Game
+clock-overlay-corner = Clock Corner
+clock-overlay-corner-top-left = Top Left
+clock-overlay-corner-top-right = Top Right
+clock-overlay-corner-bottom-left = Bottom Left
+clock-overlay-corner-bottom-right = Bottom Right
+clock-overlay-opacity = Clock Background Opacity
+clock-overlay-auto-hide = Auto-Hide Clock
+
+clock-alarm-ringing = Alarm
+clock-timer-finished = Timer finished
+clock-reminder = Reminder
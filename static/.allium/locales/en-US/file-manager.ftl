@@ -0,0 +1,23 @@
+file-manager-open = Open
+file-manager-actions = Actions
+file-manager-dual-pane = Dual Pane
+file-manager-copy = Copy
+file-manager-move = Move
+file-manager-rename = Rename
+file-manager-delete = Delete
+file-manager-new-folder = New Folder
+file-manager-paste = Paste Here
+file-manager-clipboard = Open a folder and paste { $name } there
+file-manager-confirm-copy = Copy { $name }?
+file-manager-confirm-move = Move { $name }?
+file-manager-confirm-delete = Delete { $name }?
+file-manager-destination = Into { $dir }
+file-manager-copying = Copying { $name } ({ $done }/{ $total })
+file-manager-moving = Moving { $name } ({ $done }/{ $total })
+file-manager-deleting = Deleting { $name } ({ $done }/{ $total })
+file-manager-copied = Copied { $name }
+file-manager-moved = Moved { $name }
+file-manager-deleted = Deleted { $name }
+file-manager-renamed = Renamed to { $name }
+file-manager-created = Created { $name }
+file-manager-failed = Failed: { $error }
@@ -2,6 +2,7 @@
 tab-recents = Recents
 tab-games = Games
 tab-apps = Apps
+tab-ports = Ports
 tab-settings = Settings
 
 sort-alphabetical = Sort: A-Z
@@ -10,40 +11,176 @@ sort-most-played = Sort: Playtime
 sort-rating = Sort: Rating
 sort-release-date = Sort: Release Date
 sort-random = Sort: Random
+sort-collection = Collection: { $name }
 sort-search = Sort: Search
 sort-favorites = Sort: Favorites
+sort-up-next = Sort: Up Next
 
 no-recent-games = Play a game to get started
+recents-lifetime-play-time = Played for { $hours }h { $minutes }m
+up-next = Up Next: { $name }
+recents-today = Today
+recents-yesterday = Yesterday
+recents-this-week = This Week
+recents-earlier = Earlier
+
+ports-empty = Add ports to Roms/PORTS to play them here
+ports-button-play = Play
 
 populating-database = Populating database...
     This may take several minutes.
     Go grab a coffee!
 populating-games = Populating games... ({ $directory })
+populating-database-progress = Populating database... { $percent }%
+importing-onion-data = Importing OnionOS data...
+imported-onion-data = Imported { $count } games from OnionOS
+storage-connected = Storage device connected
+storage-removed = Storage device removed
+exporting-bundle = Exporting settings...
+exported-bundle = Exported settings to allium-bundle.tar.gz
+export-bundle-failed = Failed to export settings
+resetting = Resetting...
+reset-done = Reset. Backed up to { $backup }
+reset-done-no-backup = Reset
+reset-failed = Failed to reset
+importing-bundle = Importing settings...
+imported-bundle = Imported settings, restart to apply
+import-bundle-failed = Failed to import allium-bundle.tar.gz
+backing-up-library = Backing up library...
+backed-up-library = Backed up library to allium-library.json
+back-up-library-failed = Failed to back up library
+restoring-library = Restoring library...
+restored-library = Restored { $count } games from allium-library.json
+restore-library-failed = Failed to restore allium-library.json
+syncing-library = Looking for nearby devices...
+synced-library = Synced { $count } games
+sync-library-no-devices = No nearby devices found
+sync-library-failed = Failed to sync
+syncing-saves = Syncing saves...
+syncing-saves-progress = Syncing saves... { $copied }/{ $total }
+synced-saves = Uploaded { $uploaded } and downloaded { $downloaded } saves
+synced-saves-with-conflicts =
+    Uploaded { $uploaded } and downloaded { $downloaded } saves.
+    { $conflicts } saves changed on both sides. The newest were kept, and the others were saved as .conflict files.
+sync-saves-failed = Failed to sync saves
+sync-saves-not-configured = Save sync is not set up
+save-backup-restored = Restored saves from { $time }
+save-backup-restore-failed = Failed to restore saves
+parental-play-time-limit = No play time left today
+parental-restricted = This console is restricted
+extracting-archive = Extracting... { $percent }%
+extract-archive-failed = Failed to extract archive
+launch-failed = Couldn't launch { $name }
+launch-error-missing-console = No console matches the game's folder
+launch-error-bad-core = The core is missing or couldn't be run
+launch-error-missing-bios = The core is missing BIOS files
+launch-error-unsupported-extension = The core doesn't support this file type
+launch-error-storage-unavailable = The game's storage device isn't connected
+launch-error-unknown = The game exited with an error
 
 menu-set-as-favorite = Set as Favorite
 menu-unset-as-favorite = Remove from Favorites
+menu-add-to-up-next = Add to Up Next
+menu-mark-as-beaten = Mark as Beaten
 menu-launch = Launch
 menu-launch-with-core = Launch with { $core }
+launching = Launching…
+menu-cpu-profile = CPU: { $profile }
+menu-cpu-profile-console = Console Default
+menu-cpu-profile-powersave = Power Saving
+menu-cpu-profile-balanced = Balanced
+menu-cpu-profile-performance = Performance
+menu-cpu-profile-overclock = Overclock
 menu-reset = Reset
 menu-remove-from-recents = Remove from Recents
+remove-from-recents-title = Remove { $name } from Recents?
+remove-from-recents-message = Its play count and play time will be reset
+menu-view-manual = View Manual
+menu-set-artwork = Set Artwork
+menu-show-in-folder = Show in Folder
+menu-open-on-startup = Open on Startup
+menu-unset-open-on-startup = Don't Open on Startup
+menu-launch-on-startup = Launch on Startup
+menu-unset-launch-on-startup = Don't Launch on Startup
+show-in-folder-failed = Game is not in the Roms folder
+menu-rename = Rename
+menu-move = Move to Folder
+menu-move-to = Move to
+move-no-folders = No other folders to move to
+menu-delete = Delete
+delete-game-title = Delete { $name }?
+delete-games-title = Delete { $count } games?
+delete-game-message = Its saves, states and artwork will be deleted too
+file-operation-failed = Couldn't change the game's files
+menu-hide = Hide
+undo-removed-from-recents = Removed from Recents
+undo-hidden = Hidden from Games
+undo-deleted = Deleted
+menu-selected-games = { $count } Selected
 menu-repopulate-database = Repopulate Database
+menu-pick-console = Pick a System
+menu-pick-console-suggested = Pick a System (looks like { $console })
+artwork-title = Choose Artwork
+artwork-empty = No images in Screenshots or Imgs
 
 settings-wifi = Wi-Fi
 settings-wifi-wifi-enabled = Wi-Fi Enabled
 settings-wifi-ip-address = IP Address
-settings-wifi-wifi-network = Wi-Fi Network Name
-settings-wifi-wifi-password = Wi-Fi Password
+settings-wifi-networks = Networks
+settings-wifi-networks-manage = Press A
 settings-wifi-ntp-enabled = NTP Enabled
 settings-wifi-web-file-explorer = Web File Explorer
-settings-wifi-telnet-enabled = Telnet Enabled
 settings-wifi-ftp-enabled = FTP Enabled
 settings-wifi-syncthing = Syncthing Enabled
 settings-wifi-connecting= Connecting...
+settings-wifi-ftp-address = FTP Address
+settings-wifi-web-file-explorer-address = Web File Explorer Address
+settings-wifi-server-stopped = Stopped
+settings-wifi-web-remote = Web Remote
+settings-wifi-device-name = Device Name
+settings-wifi-host-name = Network Address
+settings-wifi-web-remote-token = Pairing Code
+settings-wifi-connected = Connected to Wi-Fi
+settings-wifi-networks-scan = Scan for Networks
+settings-wifi-networks-scan-start = Press A
+settings-wifi-networks-scanning = Scanning...
+settings-wifi-networks-saved = Saved
+settings-wifi-networks-connecting = Connecting...
+settings-wifi-networks-connected = Connected
+settings-wifi-networks-connect-failed = Failed to connect to { $name }
+settings-wifi-networks-not-enabled = Turn on Wi-Fi first
+settings-wifi-networks-invalid-password = Passwords are 8 to 63 characters long
+settings-wifi-networks-forget = Forget
+settings-wifi-networks-raise-priority = Move Up
+
+settings-network = Network Diagnostics
+settings-network-quality = Connection Quality
+settings-network-quality-offline = Offline
+settings-network-quality-poor = Poor
+settings-network-quality-fair = Fair
+settings-network-quality-good = Good
+settings-network-gateway = Gateway
+settings-network-gateway-ping = Gateway Ping
+settings-network-internet-ping = Internet Ping
+settings-network-dns-lookup = DNS Lookup
+settings-network-download-speed = Download Speed
+settings-network-testing = Testing...
+settings-network-failed = Failed
+
+network-quality-poor-warning = Network connection is poor, syncing may be slow.
 
 settings-clock = Date & Time
 settings-clock-datetime = Date & Time
 settings-clock-timezone = Timezone
 
+settings-battery = Battery
+settings-battery-runtime = Estimated Runtime
+settings-battery-runtime-value = { $hours }h { $minutes }m
+settings-battery-charging = Charging
+settings-battery-not-enough-data = Not enough data
+settings-battery-not-in-game = Menus
+settings-battery-drain-rate = { $rate }%/h
+
 settings-display = Display
 settings-display-luminance = Luminance
 settings-display-hue = Hue
@@ -52,13 +189,65 @@ settings-display-saturation = Saturation
 settings-display-red = Red
 settings-display-green = Green
 settings-display-blue = Blue
+settings-display-idle-dim = Dim When Idle
+settings-display-idle-dim-off = Off
+settings-display-screensaver = Screensaver
+settings-display-screensaver-off = Off
+settings-display-screensaver-clock = Clock
+settings-display-screensaver-box-art = Box Art
+settings-display-screensaver-minutes = Screensaver After (Minutes)
+settings-display-night-mode = Night Mode
+settings-display-night-mode-off = Off
+settings-display-night-mode-on = On
+settings-display-night-mode-scheduled = Scheduled
+settings-display-night-mode-strength = Night Mode Strength
+settings-display-night-mode-start = Night Mode From
+settings-display-night-mode-end = Night Mode Until
 settings-display-screen-resolution = Screen Resolution
 
+settings-controls = Controls
+settings-controls-analog-enabled = Analog Stick Navigation
+settings-controls-analog-deadzone = Deadzone
+settings-controls-analog-repeat-interval = Repeat Interval
+settings-controls-calibrate = Calibrate Analog Stick
+settings-controls-calibrated = Calibrated
+settings-controls-not-calibrated = Not Calibrated
+settings-controls-calibrate-instructions = Rotate the stick all the way around, then let go and press A. Press B to cancel.
+settings-controls-calibration-failed = Calibration failed, move the stick in every direction
+settings-controls-no-analog-stick = This device has no analog stick
+settings-controls-controllers = Controllers
+settings-controls-no-controllers = None Connected
+settings-controls-test-buttons = Test Buttons
+settings-controls-test-buttons-start = Press A
+settings-controls-test-buttons-instructions = Press buttons on the device or a controller to see them. Press B twice to stop.
+settings-controls-unknown-button = Unknown
+settings-controls-macro-hotkey = Macro Hotkey
+settings-controls-battery-saver-hotkey = Battery Saver Hotkey
+settings-controls-battery-saver-hotkey-none = None
+settings-controls-button-layout = Button Layout
+settings-controls-button-layout-nintendo = Nintendo (A Confirms)
+settings-controls-button-layout-xbox = Xbox (B Confirms)
+settings-controls-rumble-strength = Rumble Strength
+
+settings-bluetooth = Bluetooth
+settings-bluetooth-enabled = Bluetooth Enabled
+settings-bluetooth-audio-output = Audio Output
+settings-bluetooth-device-speaker = Device Speaker
+settings-bluetooth-scan = Scan for Devices
+settings-bluetooth-scan-start = Press A
+settings-bluetooth-scanning = Scanning...
+settings-bluetooth-connecting = Connecting...
+settings-bluetooth-connected = Connected
+settings-bluetooth-connect-failed = Failed to connect to { $name }
+settings-bluetooth-not-enabled = Turn on Bluetooth first
+bluetooth-unavailable = This device has no Bluetooth
+
 settings-theme = Theme
 settings-theme-dark-mode = Dark Mode
 settings-theme-show-battery-level = Battery Percentage
 settings-theme-show-clock = Clock
 settings-theme-use-recents-carousel = Recents Carousel
+settings-theme-group-favorites = Favorites First
 settings-theme-boxart-width = Boxart Width
 settings-theme-ui-font = UI Font
 settings-theme-ui-font-size = UI Font Size
@@ -77,9 +266,29 @@ settings-theme-button-a-color = Button A Color
 settings-theme-button-b-color = Button B Color
 settings-theme-button-x-color = Button X Color
 settings-theme-button-y-color = Button Y Color
+settings-theme-list-density = List Density
+settings-theme-list-density-compact = Compact
+settings-theme-list-density-normal = Normal
+settings-theme-list-density-comfortable = Comfortable
+settings-theme-marquee-speed = Scrolling Speed
+settings-theme-marquee-pause = Scrolling Pause
+settings-theme-tag-display = Game Name Tags
+settings-theme-tag-display-hidden = Hidden
+settings-theme-tag-display-region = Region
+settings-theme-tag-display-all = All
+settings-theme-preferred-region = Preferred Region
+settings-theme-preferred-region-any = Any
+settings-theme-button-icons = Button Icons
+settings-theme-button-icons-letters = Letters
+settings-theme-button-icons-playstation = PlayStation
+settings-theme-button-icons-xbox = Xbox
+settings-theme-button-icons-text = Text
+settings-theme-show-button-hints = Button Hints
 
 settings-language = Language
 settings-language-language = Language
+settings-language-translation-debug = Translation Debug
+settings-language-missing-keys = Missing Translations
 
 settings-power = Power
 settings-power-power-button-action = Power Button Action
@@ -87,13 +296,164 @@ settings-power-power-button-action-suspend = Suspend
 settings-power-power-button-action-shutdown = Shutdown
 settings-power-power-button-action-nothing = Nothing
 settings-power-lid-close-action = Lid Close Action
+settings-power-auto-save-state = Save State on Sleep
 settings-power-auto-sleep-when-charging = Auto Sleep When Charging
 settings-power-auto-sleep-duration-minutes = Auto Sleep Duration (Minutes)
 settings-power-auto-sleep-duration-disabled = Disabled
+settings-power-fast-forward-hotkeys = Fast Forward Hotkeys
+settings-power-attract-mode = Attract Mode
+settings-power-attract-mode-delay-minutes = Attract Mode Delay (Minutes)
+settings-power-sleep-timer = Sleep Timer
+settings-power-sleep-timer-off = Off
+settings-power-sleep-timer-minutes = { $minutes } min
+settings-power-sleep-timer-action = Sleep Timer Action
+settings-power-battery-saver-threshold = Battery Saver Below
+settings-power-battery-saver-disabled = Off
+settings-power-battery-saver-brightness = Battery Saver Brightness
+settings-power-led = Power LED
+settings-power-led-suspended = LED While Suspended
+settings-power-led-low-battery = LED On Low Battery
+settings-power-led-charging = LED While Charging
+settings-power-led-pattern-off = Off
+settings-power-led-pattern-solid = Solid
+settings-power-led-pattern-blink = Blink
+settings-power-led-pattern-breathe = Breathe
+
+settings-lock = Lock Screen
+settings-lock-enabled = Lock on Boot and Wake
+settings-lock-pattern = Unlock Pattern
+settings-lock-pattern-not-set = Not Set
+settings-lock-pattern-set = { $count } Buttons
+settings-lock-pattern-recording = { $count } Pressed, Start to Save
+
+settings-parental = Parental Controls
+settings-parental-enabled = Parental Controls
+settings-parental-pin = PIN
+settings-parental-pin-not-set = Not Set
+settings-parental-pin-set = { $count } Buttons
+settings-parental-pin-recording = { $count } Pressed, Start to Save
+settings-parental-enter-pin = Enter PIN ({ $count } Pressed), Select to Leave
+settings-parental-wrong-pin = Wrong PIN
+settings-parental-unlimited = Unlimited
+settings-parental-mon = Monday Play Time
+settings-parental-tue = Tuesday Play Time
+settings-parental-wed = Wednesday Play Time
+settings-parental-thu = Thursday Play Time
+settings-parental-fri = Friday Play Time
+settings-parental-sat = Saturday Play Time
+settings-parental-sun = Sunday Play Time
+settings-parental-restrict = Restrict { $console }
 
 settings-files = Files
 
 settings-about = About
+settings-device-sync = Sync Devices
+settings-device-sync-export-bundle = Export Settings Bundle
+settings-device-sync-import-bundle = Import Settings Bundle
+settings-device-sync-backup-library = Back Up Library
+settings-device-sync-restore-library = Restore Library
+settings-device-sync-lan-sync = Sync Library with Nearby Device
+settings-webhook = Activity Webhook
+settings-webhook-enabled = Post Activity
+settings-webhook-url = Webhook URL
+settings-webhook-format = Format
+settings-webhook-format-discord = Discord
+settings-webhook-format-generic = Generic JSON
+settings-webhook-now-playing = Post When a Game Starts
+settings-webhook-session-summary = Post Play Time When a Game Stops
+settings-save-sync = Save Sync
+settings-save-sync-enabled = Sync Saves
+settings-save-sync-backend = Server Type
+settings-save-sync-backend-webdav = WebDAV
+settings-save-sync-backend-sftp = SFTP
+settings-save-sync-server = Server
+settings-save-sync-folder = Folder
+settings-save-sync-username = Username
+settings-save-sync-password = Password
+settings-save-sync-on-exit = Sync When a Game Exits
+settings-save-sync-now = Sync Now
+settings-save-backups = Save Backups
+settings-save-backups-enabled = Back Up When a Game Exits
+settings-save-backups-versions = Backups per Game
+settings-duplicates = Duplicate Games
+settings-duplicates-scanning = Looking for duplicate games...
+settings-duplicates-none = No duplicate games found
+settings-duplicates-count = { $count } copies
+settings-duplicates-hide = Hide
+settings-duplicates-delete-title = Delete { $name }?
+settings-duplicates-delete-message = The file will be removed from the SD card.
+settings-duplicates-delete-failed = Failed to delete { $name }
+settings-hotkeys = RetroArch Hotkeys
+settings-hotkeys-enabled = Managed by Allium
+settings-hotkeys-menu = RetroArch Menu
+settings-hotkeys-save-state = Save State
+settings-hotkeys-load-state = Load State
+settings-hotkeys-fast-forward = Fast Forward
+settings-hotkeys-off = Off
+settings-hotkeys-conflict = This button is already used with Menu, so it won't be bound
+settings-sound = Sound
+settings-sound-volume-curve = Volume Curve
+settings-sound-volume-curve-logarithmic = Natural
+settings-sound-volume-curve-linear = Linear
+settings-sound-max-volume = Maximum Volume
+settings-sound-max-volume-disabled = No Limit
+settings-sound-equalizer = Equalizer
+settings-sound-equalizer-flat = Flat
+settings-sound-equalizer-bass-boost = Bass Boost
+settings-sound-equalizer-speech = Speech
+settings-sound-equalizer-next-launch = The equalizer applies from the next game or app launched
+settings-sound-equalizer-not-installed = Not Installed
+settings-developer = Developer
+settings-developer-ssh = SSH Server
+settings-developer-telnet = Telnet Server
+settings-developer-ip-address = IP Address
+settings-developer-ssh-command = SSH Command
+settings-developer-telnet-command = Telnet Command
+settings-developer-not-connected = Not Connected
+settings-developer-stopped = Stopped
+settings-developer-not-installed = Not Installed
+settings-developer-no-keys = Add .ssh/authorized_keys
+settings-logs = Logs
+settings-logs-empty = No logs yet
+settings-bios = BIOS Files
+settings-bios-present = OK
+settings-bios-missing = Missing
+settings-bios-optional = Missing (optional)
+settings-bios-bad-checksum = Wrong checksum
+settings-bios-none = No consoles need BIOS files
+settings-maintenance = Maintenance
+settings-maintenance-reset-database = Reset Library Database
+settings-maintenance-reset-theme = Reset Theme
+settings-maintenance-reset-wifi = Forget Wi-Fi Networks
+settings-maintenance-reset-all = Reset Everything
+settings-maintenance-confirm-database = Reset the library database?
+settings-maintenance-confirm-theme = Reset the theme?
+settings-maintenance-confirm-wifi = Forget all Wi-Fi networks?
+settings-maintenance-confirm-all = Reset all settings and the library?
+settings-maintenance-confirm-message = A backup is saved to .allium/reset-backups first.
+settings-maintenance-enter-lock-pattern = Enter Lock Pattern ({ $count } Pressed), Select to Cancel
+settings-maintenance-enter-parental-pin = Enter Parental PIN ({ $count } Pressed), Select to Cancel
+settings-maintenance-wrong-pin = Wrong PIN
+settings-tabs = Tabs and Startup
+settings-tabs-startup-tab = Startup Tab
+settings-tabs-startup-tab-last = Last Opened
+settings-tabs-move-up = Move Up
+settings-tabs-move-down = Move Down
+settings-tabs-startup = Boot Into
+settings-tabs-startup-last-view = Last View
+settings-tabs-group-consoles = Group Games by Console
+settings-consoles = Consoles
+settings-consoles-none = No console folders found
+settings-recents = Recents
+settings-recents-limit = Maximum Games
+settings-recents-prune = Remove Games Not Played In
+settings-recents-prune-never = Never
+settings-recents-prune-days = { $days } Days
+settings-recents-group-by-day = Group by Day
+settings-import-onion-data = Import OnionOS Data
+settings-search = Search
+settings-search-query = Search: { $query }
+settings-search-none = No settings found
 settings-about-allium-version = Allium Version
 settings-about-model-name = Model Name
 settings-about-firmware-version = Firmware Version
@@ -113,6 +473,16 @@ ingame-menu-load = Load
 ingame-menu-reset = Reset
 ingame-menu-settings = Settings
 ingame-menu-guide = Guide
+ingame-menu-manual = Manual
+ingame-menu-cheats = Cheats
+ingame-menu-fast-forward = Fast Forward
+ingame-menu-rewind = Rewind
+ingame-menu-reminder = Remind Me in 30 Min
+ingame-menu-sleep-timer = Sleep Timer
+ingame-menu-sleep-timer-off = Off
+ingame-menu-sleep-timer-minutes = { $minutes } min
+ingame-menu-status-overlay = Show Status In Game
+ingame-menu-box-art = Use Current Frame as Box Art
 ingame-menu-quit = Quit
 ingame-menu-slot = Slot { $slot }
 ingame-menu-slot-auto = Auto
@@ -122,6 +492,15 @@ guide-button-search = Search
 guide-button-next = Next
 guide-button-prev = Prev
 
+manual-zoom-in = Zoom In
+manual-zoom-out = Zoom Out
+manual-render-failed = Couldn't open the manual
+manual-rendering = Loading...
+
+# Lock screen
+lock-screen-title = Enter Pattern to Unlock
+lock-screen-wrong-pattern = Wrong Pattern
+
 # Hotkeys
 hotkeys-global = Global Hotkeys:
 hotkeys-screenshot = Screenshot
@@ -129,19 +508,50 @@ hotkeys-volume-down = Volume -
 hotkeys-volume-up = Volume +
 hotkeys-brightness-down = Brightness -
 hotkeys-brightness-up = Brightness +
+hotkeys-fast-forward = Toggle Fast Forward
+hotkeys-rewind = Rewind (Hold)
+hotkeys-battery-saver = Toggle Battery Saver
 
 hotkeys-ingame = Ingame Hotkeys:
 hotkeys-toggle-aspect-ratio = Toggle Aspect Ratio
 hotkeys-toggle-fps = Toggle FPS
+hotkeys-record-macro = Start/Stop Recording Macro
+hotkeys-play-macro = Play Macro
+hotkeys-retroarch-menu = RetroArch Menu
+hotkeys-save-state = Save State
+hotkeys-load-state = Load State
 
 # Common
 button-back = Back
 button-confirm = Confirm
+button-delete = Delete
 button-edit = Edit
+button-retry = Retry
 button-select = Select
+button-check = Check
+button-select-several = Select Several
+button-done = Done
+button-undo = Undo
 
 keyboard-button-backspace = Backspace
 keyboard-button-shift = Shift
 
 powering-off = Powering off...
 charging = Charging...
+fast-forward-indicator = >> Fast Forward
+rewind-indicator = << Rewind
+status-overlay = Battery { $battery }%  Vol { $volume }
+macro-recording = Recording macro
+macro-saved = Macro saved
+sleep-timer-warning = Sleeping in 1 minute
+parental-play-time-warning = 10 minutes of play time left today
+battery-low-warning = Battery low ({ $percent }%)
+battery-shutdown-warning = Battery critically low, shutting down in { $seconds } seconds
+battery-saver-on = Battery saver on
+battery-saver-off = Battery saver off
+
+headphones-connected = Headphones connected
+headphones-disconnected = Headphones disconnected
+
+webhook-now-playing = Now playing { $game }
+webhook-session-ended = Played { $game } for { $minutes } min
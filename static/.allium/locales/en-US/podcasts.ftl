@@ -0,0 +1,9 @@
+podcasts-title = Podcasts
+
+podcasts-add = Add Feed or Stream...
+podcasts-stream = Stream
+podcasts-error = Failed to load { $url }: { $error }
+podcasts-loading = Loading { $url }... ({ $kb } KB)
+podcasts-playing = Playing: { $title } ({ $position })
+
+podcasts-button-remove = Remove
@@ -0,0 +1,9 @@
+text-editor-title = Text Editor
+text-editor-open = Open
+text-editor-edit = Edit
+text-editor-insert = Insert
+text-editor-delete = Delete
+text-editor-save = Save
+text-editor-unsaved = Unsaved changes, press again to discard
+text-editor-save-failed = Failed to save { $file }
+text-editor-read-only = { $file } is not text, read-only
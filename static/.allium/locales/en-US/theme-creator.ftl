@@ -0,0 +1,12 @@
+theme-creator-title = Theme Creator
+theme-creator-default-name = My Theme
+theme-creator-name = Name
+theme-creator-base = Start From
+theme-creator-base-current = Current Theme
+theme-creator-wallpaper = Wallpaper
+theme-creator-wallpaper-none = None
+theme-creator-save = Save Theme
+theme-creator-apply = Save and Apply
+theme-creator-saved = Saved to { $path }
+theme-creator-applied = Theme applied
+theme-creator-error = Something went wrong
@@ -0,0 +1,7 @@
+videos-title = Videos
+
+videos-empty = No videos found. Copy MP4 or MKV files to the Videos folder.
+videos-error = Failed to play { $name }
+
+videos-button-play = Play
+videos-button-play-from-start = From Start
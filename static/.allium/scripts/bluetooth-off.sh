@@ -0,0 +1,5 @@
+#!/bin/sh
+
+bluetoothctl power off
+killall bluealsa > /dev/null 2>&1
+killall bluetoothd > /dev/null 2>&1
@@ -0,0 +1,10 @@
+#!/bin/sh
+
+if ! pidof bluetoothd > /dev/null; then
+	bluetoothd > /dev/null 2>&1 &
+	sleep 1
+fi
+if ! pidof bluealsa > /dev/null; then
+	bluealsa -p a2dp-source > /dev/null 2>&1 &
+fi
+bluetoothctl power on
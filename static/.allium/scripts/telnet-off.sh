@@ -1,3 +0,0 @@
-#!/bin/sh
-
-killall telnetd
\ No newline at end of file